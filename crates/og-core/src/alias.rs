@@ -0,0 +1,156 @@
+//! Short names for runs: `latest` for the most recently updated run under a
+//! project, and user aliases (`og alias set best run_2024_06_01`) kept in
+//! `<project>/.og_aliases.json`. A real run directory always wins over an
+//! alias of the same name.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::OgError;
+use crate::query::{latest_mtime_unix, list_run_dirs};
+
+/// Alias file written at the top of a project.
+pub const FILE_NAME: &str = ".og_aliases.json";
+/// Pseudo-run naming the most recently updated run.
+pub const LATEST: &str = "latest";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct AliasFile {
+    #[serde(default)]
+    aliases: BTreeMap<String, String>,
+}
+
+pub fn path(base: &Path) -> PathBuf {
+    base.join(FILE_NAME)
+}
+
+/// Aliases under `base`, name to run. Runs are relative to `base` unless absolute.
+pub fn load(base: &Path) -> Result<BTreeMap<String, String>> {
+    let path = path(base);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let raw = fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+    let file: AliasFile =
+        serde_json::from_str(&raw).with_context(|| OgError::parse_failed(path.display()))?;
+    Ok(file.aliases)
+}
+
+fn save(base: &Path, aliases: BTreeMap<String, String>) -> Result<()> {
+    let path = path(base);
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(&AliasFile { aliases })?)
+        .with_context(|| format!("writing {}", tmp.display()))?;
+    fs::rename(&tmp, &path).with_context(|| format!("writing {}", path.display()))
+}
+
+/// Point `name` at `run` (a run directory), replacing any earlier target.
+/// Runs outside `base` are stored as absolute paths. Returns the target as
+/// stored.
+pub fn set(base: &Path, name: &str, run: &Path) -> Result<String> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        bail!("alias '{name}' must be a plain name without '/' or a leading '.'");
+    }
+    if name == LATEST {
+        bail!("'{LATEST}' always means the most recently updated run");
+    }
+    if base.join(name).exists() {
+        bail!("'{name}' is already a run in {}", base.display());
+    }
+    let target = match run.strip_prefix(base) {
+        Ok(relative) => relative.to_path_buf(),
+        Err(_) => fs::canonicalize(run).with_context(|| format!("resolving {}", run.display()))?,
+    }
+    .to_string_lossy()
+    .into_owned();
+    let mut aliases = load(base)?;
+    aliases.insert(name.to_string(), target.clone());
+    save(base, aliases)?;
+    Ok(target)
+}
+
+/// Forget `name`. Returns whether it was set.
+pub fn remove(base: &Path, name: &str) -> Result<bool> {
+    let mut aliases = load(base)?;
+    let removed = aliases.remove(name).is_some();
+    if removed {
+        save(base, aliases)?;
+    }
+    Ok(removed)
+}
+
+/// The run `name` stands for under `base`: the newest run for `latest`, the
+/// target of an alias, or None. Unreadable alias files resolve nothing.
+pub fn resolve(base: &Path, name: &str) -> Option<PathBuf> {
+    if name == LATEST {
+        return latest(base);
+    }
+    let target = load(base).ok()?.remove(name)?;
+    Some(base.join(target))
+}
+
+/// The run under `base` whose files changed last.
+pub fn latest(base: &Path) -> Option<PathBuf> {
+    list_run_dirs(base)
+        .ok()?
+        .into_iter()
+        .filter(|dir| dir != base)
+        .max_by_key(|dir| latest_mtime_unix(dir).ok().flatten())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LATEST, load, remove, resolve, set};
+    use crate::query::resolve_run_path;
+    use std::fs;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn aliases_and_latest_resolve_through_the_shared_resolver() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("og-core-alias-{nonce}"));
+        let base = root.join("proj");
+        for (run, age) in [("run_2024_06_01", 3600), ("run_2024_06_02", 60)] {
+            let file = base.join(run).join("events.out.tfevents.1");
+            fs::create_dir_all(file.parent().expect("run dir")).expect("create run");
+            let handle = fs::File::create(&file).expect("create events");
+            handle
+                .set_modified(SystemTime::now() - Duration::from_secs(age))
+                .expect("set mtime");
+        }
+
+        assert_eq!(resolve(&base, LATEST), Some(base.join("run_2024_06_02")));
+        assert_eq!(
+            resolve_run_path(&root, Some("proj"), LATEST),
+            base.join("run_2024_06_02")
+        );
+
+        let stored = set(&base, "best", &base.join("run_2024_06_01")).expect("set");
+        assert_eq!(stored, "run_2024_06_01");
+        assert_eq!(
+            resolve_run_path(&root, Some("proj"), "best"),
+            base.join("run_2024_06_01")
+        );
+        assert!(set(&base, LATEST, &base.join("run_2024_06_01")).is_err());
+        assert!(set(&base, "run_2024_06_02", &base.join("run_2024_06_01")).is_err());
+        assert!(set(&base, "a/b", &base.join("run_2024_06_01")).is_err());
+
+        // A run that is really named like an alias wins.
+        fs::create_dir_all(base.join("latest")).expect("create run named latest");
+        assert_eq!(
+            resolve_run_path(&root, Some("proj"), LATEST),
+            base.join("latest")
+        );
+
+        assert!(remove(&base, "best").expect("remove"));
+        assert!(!remove(&base, "best").expect("remove again"));
+        assert!(load(&base).expect("load").is_empty());
+        fs::remove_dir_all(&root).ok();
+    }
+}
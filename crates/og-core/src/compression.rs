@@ -0,0 +1,177 @@
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+/// Compression applied to archived event files. Both codecs go through the
+/// `gzip` / `zstd` command-line tools rather than bundled libraries, so
+/// reading or writing compressed files needs the tool on `PATH`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Codec {
+    Gzip,
+    Zstd,
+}
+
+impl Codec {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Codec::Gzip => ".gz",
+            Codec::Zstd => ".zst",
+        }
+    }
+
+    /// Also the name of the command-line tool that implements the codec.
+    pub fn program(self) -> &'static str {
+        match self {
+            Codec::Gzip => "gzip",
+            Codec::Zstd => "zstd",
+        }
+    }
+
+    /// Codec implied by a file name's extension, if any.
+    pub fn from_name(name: &str) -> Option<Codec> {
+        let lower = name.to_ascii_lowercase();
+        [Codec::Gzip, Codec::Zstd]
+            .into_iter()
+            .find(|codec| lower.ends_with(codec.extension()))
+    }
+}
+
+/// `name` without a trailing `.gz` / `.zst`.
+pub fn strip_extension(name: &str) -> &str {
+    match Codec::from_name(name) {
+        Some(codec) => &name[..name.len() - codec.extension().len()],
+        None => name,
+    }
+}
+
+/// Read a file, transparently decompressing `.gz` / `.zst` content.
+pub fn read(path: &Path) -> Result<Vec<u8>> {
+    let Some(codec) = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .and_then(Codec::from_name)
+    else {
+        return fs::read(path).with_context(|| format!("reading {}", path.display()));
+    };
+    let mut command = Command::new(codec.program());
+    command.arg("-dc").arg(path);
+    let output = run_tool(
+        command,
+        codec.program(),
+        &format!("read {}", path.display()),
+    )?;
+    if !output.status.success() {
+        bail!(
+            "{} failed to decompress {}: {}",
+            codec.program(),
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// Compress `path` in place, replacing it with `<path><ext>`.
+pub fn compress_in_place(path: &Path, codec: Codec) -> Result<PathBuf> {
+    let mut command = Command::new(codec.program());
+    match codec {
+        Codec::Gzip => command.args(["-9", "-n"]),
+        Codec::Zstd => command.args(["-q", "-19", "--rm"]),
+    };
+    command.arg(path);
+    let output = run_tool(
+        command,
+        codec.program(),
+        &format!("compress {}", path.display()),
+    )?;
+    if !output.status.success() {
+        bail!(
+            "{} failed to compress {}: {}",
+            codec.program(),
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let mut compressed = path.as_os_str().to_owned();
+    compressed.push(codec.extension());
+    Ok(PathBuf::from(compressed))
+}
+
+/// Run a codec's tool, saying which one to install when it is missing.
+fn run_tool(mut command: Command, program: &str, action: &str) -> Result<Output> {
+    match command.stdin(Stdio::null()).output() {
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => bail!(
+            "cannot {action}: the `{program}` command-line tool is not installed \
+             (og uses it for .gz / .zst files)"
+        ),
+        result => result.with_context(|| format!("running {program} to {action}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Codec, compress_in_place, read, run_tool, strip_extension};
+    use std::fs;
+    use std::process::Command;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn codec_follows_file_extension() {
+        assert_eq!(
+            Codec::from_name("events.out.tfevents.1.gz"),
+            Some(Codec::Gzip)
+        );
+        assert_eq!(
+            Codec::from_name("events.out.tfevents.1.ZST"),
+            Some(Codec::Zstd)
+        );
+        assert_eq!(Codec::from_name("events.out.tfevents.1"), None);
+        assert_eq!(strip_extension("a.tfevents.1.zst"), "a.tfevents.1");
+        assert_eq!(strip_extension("a.tfevents.1"), "a.tfevents.1");
+    }
+
+    #[test]
+    fn gzip_round_trips_in_place() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("ogtui-compression-{nonce}"));
+        fs::create_dir_all(&dir).expect("create dir");
+        let path = dir.join("events.out.tfevents.1");
+        fs::write(&path, b"record bytes record bytes").expect("write file");
+
+        if Command::new("gzip").arg("--version").output().is_err() {
+            eprintln!("skipping gzip_round_trips_in_place: gzip is not installed");
+            fs::remove_dir_all(&dir).ok();
+            return;
+        }
+        let compressed = compress_in_place(&path, Codec::Gzip).expect("compress");
+        assert!(!path.exists());
+        assert_eq!(compressed, dir.join("events.out.tfevents.1.gz"));
+        assert_eq!(
+            read(&compressed).expect("read"),
+            b"record bytes record bytes"
+        );
+
+        fs::remove_dir_all(&dir).ok();
+    }
+    #[test]
+    fn missing_tools_are_named_in_the_error() {
+        let err = run_tool(
+            Command::new("og-missing-codec-tool"),
+            "og-missing-codec-tool",
+            "read a.gz",
+        )
+        .expect_err("tool is missing");
+        assert_eq!(
+            err.to_string(),
+            "cannot read a.gz: the `og-missing-codec-tool` command-line tool is not installed \
+             (og uses it for .gz / .zst files)"
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! DNS wire format shared by ogd's mDNS responder and `og discover`: the
+//! `_ogd._tcp.local` service, record types, and reading and writing names
+//! and fields in packets.
+
+use std::net::Ipv4Addr;
+
+/// DNS-SD service ogd advertises itself under.
+pub const SERVICE: &str = "_ogd._tcp.local";
+pub const MDNS_ADDR: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+pub const MDNS_PORT: u16 = 5353;
+pub const TYPE_A: u16 = 1;
+pub const TYPE_PTR: u16 = 12;
+pub const TYPE_TXT: u16 = 16;
+pub const TYPE_AAAA: u16 = 28;
+pub const TYPE_SRV: u16 = 33;
+pub const TYPE_ANY: u16 = 255;
+pub const CLASS_IN: u16 = 1;
+
+pub fn put_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+/// `name` as length-prefixed labels, uncompressed. Empty labels are dropped
+/// and long ones cut to the 63 bytes a label can hold.
+pub fn put_name(out: &mut Vec<u8>, name: &str) {
+    for part in name.split('.').filter(|part| !part.is_empty()) {
+        let bytes = &part.as_bytes()[..part.len().min(63)];
+        out.push(bytes.len() as u8);
+        out.extend_from_slice(bytes);
+    }
+    out.push(0);
+}
+
+pub fn read_u16(packet: &[u8], offset: usize) -> Option<u16> {
+    let bytes = packet.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+/// Dotted name at `offset`, following compression pointers, and the offset
+/// just past it.
+pub fn read_name(packet: &[u8], mut offset: usize) -> Option<(String, usize)> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut end = None;
+    // Bounds pointer loops in malformed packets.
+    for _ in 0..128 {
+        let len = usize::from(*packet.get(offset)?);
+        if len & 0xc0 == 0xc0 {
+            let pointer = usize::from(read_u16(packet, offset)? & 0x3fff);
+            end.get_or_insert(offset + 2);
+            offset = pointer;
+            continue;
+        }
+        if len == 0 {
+            return Some((labels.join("."), end.unwrap_or(offset + 1)));
+        }
+        let bytes = packet.get(offset + 1..offset + 1 + len)?;
+        labels.push(String::from_utf8_lossy(bytes).into_owned());
+        offset += 1 + len;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SERVICE, put_name, put_u16, read_name, read_u16};
+
+    #[test]
+    fn names_round_trip_and_follow_pointers() {
+        let mut packet = Vec::new();
+        put_u16(&mut packet, 0x8400);
+        put_name(&mut packet, SERVICE);
+        let after_service = packet.len();
+        assert_eq!(
+            read_name(&packet, 2),
+            Some((SERVICE.to_string(), after_service))
+        );
+        assert_eq!(read_u16(&packet, 0), Some(0x8400));
+        assert_eq!(read_u16(&packet, after_service - 1), None);
+
+        // `gpu-box-8787` followed by a pointer back to the service name.
+        packet.push(12);
+        packet.extend_from_slice(b"gpu-box-8787");
+        packet.extend_from_slice(&[0xc0, 2]);
+        assert_eq!(
+            read_name(&packet, after_service),
+            Some((format!("gpu-box-8787.{SERVICE}"), packet.len()))
+        );
+
+        let looped = [0xc0, 0];
+        assert_eq!(read_name(&looped, 0), None);
+        assert_eq!(read_name(&[5, b'a'], 0), None, "label past the end");
+    }
+}
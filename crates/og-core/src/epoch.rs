@@ -0,0 +1,142 @@
+//! Epochs as an x axis. Many training scripts count in epochs, and runs with
+//! different batch sizes only line up by epoch. A run's epoch at a step comes
+//! from the counter it logs (a tag named `epoch`, like `train/epoch`, or the
+//! tag its config names as `epoch_key`), read between the steps where the
+//! counter moved so steps inside an epoch get fractional epochs. Runs that log
+//! no counter fall back to `steps_per_epoch` from their config.
+
+use crate::run_meta::RunMeta;
+use crate::tfevents::SeriesByTag;
+
+/// Config key naming the tag a run logs its epoch counter under.
+pub const KEY_CONFIG: &str = "epoch_key";
+/// Config key holding a run's steps per epoch.
+pub const STEPS_PER_EPOCH_CONFIG: &str = "steps_per_epoch";
+
+/// How a run's steps map to epochs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EpochScale {
+    /// `(step, epoch)` where the logged counter changed, step-sorted
+    Counter(Vec<(f64, f64)>),
+    StepsPerEpoch(f64),
+}
+
+impl EpochScale {
+    /// The run's counter series when it logs one, else its configured steps
+    /// per epoch; None when it has neither.
+    pub fn for_run(scalars: &SeriesByTag, meta: &RunMeta) -> Option<Self> {
+        let counter = match meta.config.get(KEY_CONFIG) {
+            Some(key) => scalars.get(key),
+            None => scalars
+                .iter()
+                .find(|(tag, _)| tag.rsplit('/').next() == Some("epoch"))
+                .map(|(_, series)| series),
+        };
+        if let Some(scale) = counter.and_then(|series| Self::from_counter(series)) {
+            return Some(scale);
+        }
+        meta.config
+            .get(STEPS_PER_EPOCH_CONFIG)?
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|steps| steps.is_finite() && *steps > 0.0)
+            .map(Self::StepsPerEpoch)
+    }
+
+    /// Counters logged every step as a whole number (0, 0, 0, 1, 1, ...) keep
+    /// the first step of each value, so the steps between read fractionally.
+    pub fn from_counter(series: &[(f64, f64)]) -> Option<Self> {
+        let mut points: Vec<(f64, f64)> = Vec::new();
+        for &(step, epoch) in series {
+            if !(step.is_finite() && epoch.is_finite()) {
+                continue;
+            }
+            if points
+                .last()
+                .is_none_or(|&(last_step, last_epoch)| epoch != last_epoch && step > last_step)
+            {
+                points.push((step, epoch));
+            }
+        }
+        (!points.is_empty()).then_some(Self::Counter(points))
+    }
+
+    /// Epoch at `step`: linear between counter points, and past either end at
+    /// the rate of the nearest pair (from step 0 for a lone point).
+    pub fn epoch_at(&self, step: f64) -> f64 {
+        let points = match self {
+            Self::StepsPerEpoch(steps) => return step / steps,
+            Self::Counter(points) => points,
+        };
+        let segment = match points.as_slice() {
+            [] => return 0.0,
+            [(s, e)] if *s > 0.0 => ((0.0, 0.0), (*s, *e)),
+            [(_, e)] => return *e,
+            _ => {
+                let idx = points
+                    .partition_point(|(s, _)| *s < step)
+                    .clamp(1, points.len() - 1);
+                (points[idx - 1], points[idx])
+            }
+        };
+        let ((s0, e0), (s1, e1)) = segment;
+        e0 + (e1 - e0) * (step - s0) / (s1 - s0)
+    }
+
+    /// `series` with each step replaced by its epoch, sorted by epoch (a
+    /// counter reset by a restart can run backwards).
+    pub fn align(&self, series: &[(f64, f64)]) -> Vec<(f64, f64)> {
+        let mut aligned: Vec<(f64, f64)> = series
+            .iter()
+            .map(|&(step, value)| (self.epoch_at(step), value))
+            .collect();
+        aligned.sort_by(|a, b| a.0.total_cmp(&b.0));
+        aligned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EpochScale;
+    use crate::run_meta::RunMeta;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn epochs_come_from_the_counter_else_steps_per_epoch() {
+        let counter: Vec<(f64, f64)> = (0..=30)
+            .map(|step| (step as f64, (step / 10) as f64))
+            .collect();
+        let mut scalars = BTreeMap::from([
+            ("train/epoch".to_string(), counter),
+            ("train/loss".to_string(), vec![(0.0, 2.0), (30.0, 1.0)]),
+        ]);
+        let mut meta = RunMeta::default();
+        let scale = EpochScale::for_run(&scalars, &meta).expect("counter");
+        assert_eq!(
+            scale,
+            EpochScale::Counter(vec![(0.0, 0.0), (10.0, 1.0), (20.0, 2.0), (30.0, 3.0)])
+        );
+        assert_eq!(scale.epoch_at(15.0), 1.5);
+        assert_eq!(scale.epoch_at(35.0), 3.5, "past the last point");
+        assert_eq!(
+            scale.align(&scalars["train/loss"]),
+            [(0.0, 2.0), (3.0, 1.0)]
+        );
+
+        let lone = EpochScale::from_counter(&[(500.0, 1.0)]).expect("one point");
+        assert_eq!(lone.epoch_at(250.0), 0.5);
+
+        meta.config
+            .insert("epoch_key".to_string(), "data/pass".to_string());
+        assert_eq!(EpochScale::for_run(&scalars, &meta), None, "key not logged");
+        meta.config
+            .insert("steps_per_epoch".to_string(), "400".to_string());
+        let scale = EpochScale::for_run(&scalars, &meta).expect("steps per epoch");
+        assert_eq!(scale.epoch_at(1000.0), 2.5);
+
+        scalars.insert("data/pass".to_string(), vec![(0.0, 0.0), (100.0, 1.0)]);
+        let scale = EpochScale::for_run(&scalars, &meta).expect("configured key");
+        assert_eq!(scale.epoch_at(50.0), 0.5);
+    }
+}
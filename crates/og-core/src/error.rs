@@ -0,0 +1,268 @@
+//! Failures og reports with a hint and their own exit code, so a script can
+//! tell a misspelled run from a daemon that is down. Anything else stays a
+//! plain `anyhow` error and exits 1.
+//!
+//! `ParseFailed` is meant as context (`.with_context(|| OgError::parse_failed(path))`)
+//! so the parser's own message stays the cause.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::query::list_immediate_dirs;
+
+/// Names offered in a "did you mean" hint.
+const MAX_SUGGESTIONS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OgError {
+    /// `run` as the user typed it resolved to `path`, which does not exist.
+    RunNotFound {
+        run: String,
+        path: PathBuf,
+        suggestions: Vec<String>,
+    },
+    MetricNotFound {
+        metric: String,
+        run: PathBuf,
+        suggestions: Vec<String>,
+    },
+    /// The run has event files but none of them could be read.
+    CorruptEvents {
+        path: PathBuf,
+        files: usize,
+        detail: String,
+    },
+    DaemonUnreachable {
+        socket: PathBuf,
+        reason: String,
+    },
+    /// A config file, metadata file or flag value that did not parse.
+    ParseFailed {
+        input: String,
+    },
+}
+
+impl OgError {
+    /// A missing run, with the closest names among its would-be siblings.
+    pub fn run_not_found(run: &str, path: &Path) -> Self {
+        let siblings: Vec<String> = path
+            .parent()
+            .and_then(|parent| list_immediate_dirs(parent).ok())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|dir| dir.file_name()?.to_str().map(str::to_string))
+            .filter(|name| !name.starts_with('.'))
+            .collect();
+        let siblings: Vec<&str> = siblings.iter().map(String::as_str).collect();
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(run);
+        Self::RunNotFound {
+            run: run.to_string(),
+            path: path.to_path_buf(),
+            suggestions: suggest(name, &siblings),
+        }
+    }
+
+    /// A metric `run` does not log, with the closest of the tags it does.
+    pub fn metric_not_found<'a>(
+        metric: &str,
+        run: &Path,
+        tags: impl IntoIterator<Item = &'a String>,
+    ) -> Self {
+        let tags: Vec<&str> = tags.into_iter().map(String::as_str).collect();
+        Self::MetricNotFound {
+            metric: metric.to_string(),
+            run: run.to_path_buf(),
+            suggestions: suggest(metric, &tags),
+        }
+    }
+
+    pub fn parse_failed(input: impl fmt::Display) -> Self {
+        Self::ParseFailed {
+            input: input.to_string(),
+        }
+    }
+
+    /// Short snake_case name for JSON output.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::RunNotFound { .. } => "run_not_found",
+            Self::MetricNotFound { .. } => "metric_not_found",
+            Self::CorruptEvents { .. } => "corrupt_events",
+            Self::DaemonUnreachable { .. } => "daemon_unreachable",
+            Self::ParseFailed { .. } => "parse_failed",
+        }
+    }
+
+    /// Process exit code: 2 not found, 3 parse error, 4 daemon error. Any
+    /// other error exits 1.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::RunNotFound { .. } | Self::MetricNotFound { .. } => 2,
+            Self::CorruptEvents { .. } | Self::ParseFailed { .. } => 3,
+            Self::DaemonUnreachable { .. } => 4,
+        }
+    }
+
+    /// What to try next, printed under the error.
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            Self::RunNotFound { suggestions, .. } if !suggestions.is_empty() => {
+                Some(format!("did you mean run {}?", quote_all(suggestions)))
+            }
+            Self::RunNotFound { path, .. } => Some(format!(
+                "`og list runs --path {}` shows the runs there",
+                path.parent().unwrap_or(Path::new(".")).display()
+            )),
+            Self::MetricNotFound { suggestions, .. } if !suggestions.is_empty() => {
+                Some(format!("did you mean metric {}?", quote_all(suggestions)))
+            }
+            Self::MetricNotFound { run, .. } => Some(format!(
+                "`og list metrics --run {}` shows the metrics it logs",
+                run.display()
+            )),
+            Self::CorruptEvents { path, .. } => Some(format!(
+                "the files under {} may be truncated or in a format no reader knows; `og list readers` shows the formats og reads",
+                path.display()
+            )),
+            Self::DaemonUnreachable { socket, .. } => Some(format!(
+                "start the daemon with `og daemon start --socket {}`",
+                socket.display()
+            )),
+            Self::ParseFailed { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for OgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RunNotFound { run, path, .. } if Path::new(run) == path => {
+                write!(f, "run '{run}' not found")
+            }
+            Self::RunNotFound { run, path, .. } => {
+                write!(f, "run '{run}' not found at {}", path.display())
+            }
+            Self::MetricNotFound { metric, run, .. } => {
+                write!(f, "metric '{metric}' not found in run {}", run.display())
+            }
+            Self::CorruptEvents {
+                path,
+                files,
+                detail,
+            } => write!(
+                f,
+                "none of the {files} event files in {} could be read: {detail}",
+                path.display()
+            ),
+            Self::DaemonUnreachable { socket, reason } => {
+                write!(f, "no daemon answers on {}: {reason}", socket.display())
+            }
+            Self::ParseFailed { input } => write!(f, "parsing {input}"),
+        }
+    }
+}
+
+impl std::error::Error for OgError {}
+
+fn quote_all(names: &[String]) -> String {
+    names
+        .iter()
+        .map(|name| format!("'{name}'"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Names within a small edit distance of `needle`, compared against the
+/// whole name and each of its words so `lsos` finds `train/loss`. A `*` in
+/// `needle` is ignored, so globs get suggestions too.
+pub fn suggest(needle: &str, names: &[&str]) -> Vec<String> {
+    let needle: String = needle
+        .to_ascii_lowercase()
+        .chars()
+        .filter(|c| *c != '*')
+        .collect();
+    let needle = needle.trim_matches('/');
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    let limit = (needle.chars().count() / 3).max(1);
+    let mut scored: Vec<(usize, &str)> = names
+        .iter()
+        .filter_map(|name| {
+            let name_l = name.to_ascii_lowercase();
+            let best = std::iter::once(name_l.as_str())
+                .chain(name_l.split(['/', '_', '-', '.']))
+                .map(|candidate| edit_distance(needle, candidate))
+                .min()?;
+            (best <= limit).then_some((best, *name))
+        })
+        .collect();
+    scored.sort();
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+/// Edit distance counting an adjacent swap (`lsos` -> `loss`) as one edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OgError, edit_distance, suggest};
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn suggestions_tolerate_swaps_and_match_words() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        let names = ["train/loss", "val/loss", "lr-sweep-03"];
+        assert_eq!(suggest("lsos", &names), ["train/loss", "val/loss"]);
+        assert_eq!(suggest("lr-sweep-30", &names), ["lr-sweep-03"]);
+        assert!(suggest("zzz", &names).is_empty());
+    }
+
+    #[test]
+    fn a_missing_run_suggests_its_closest_sibling() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("og-core-error-{nonce}"));
+        fs::create_dir_all(root.join("baseline")).expect("create run");
+        fs::create_dir_all(root.join(".og")).expect("create state dir");
+
+        let err = OgError::run_not_found("basline", &root.join("basline"));
+        assert_eq!(err.exit_code(), 2);
+        assert_eq!(err.hint().as_deref(), Some("did you mean run 'baseline'?"));
+        let err = OgError::run_not_found("og", &root.join("og"));
+        assert!(err.hint().expect("hint").contains("og list runs"));
+        fs::remove_dir_all(&root).ok();
+    }
+}
@@ -0,0 +1,166 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// File name of the uncommitted-changes patch saved next to the event files.
+pub const PATCH_FILE_NAME: &str = "git.patch";
+
+/// Source tree state captured when a run is launched.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitState {
+    pub commit: String,
+    /// Branch name; absent for a detached HEAD.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Tracked changes or untracked files were present.
+    pub dirty: bool,
+    /// Patch file (relative to the run directory) holding `git diff HEAD`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patch: Option<String>,
+}
+
+impl GitState {
+    pub fn short_commit(&self) -> &str {
+        self.commit.get(..12).unwrap_or(&self.commit)
+    }
+}
+
+fn git(repo: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Current branch of the repository containing `repo`, if any.
+pub fn current_branch(repo: &Path) -> Option<String> {
+    let branch = git(repo, &["rev-parse", "--abbrev-ref", "HEAD"])?
+        .trim()
+        .to_string();
+    (!branch.is_empty() && branch != "HEAD").then_some(branch)
+}
+
+/// Capture commit, branch and dirty status; `None` outside a git checkout.
+pub fn capture(repo: &Path) -> Option<GitState> {
+    let commit = git(repo, &["rev-parse", "HEAD"])?.trim().to_string();
+    if commit.is_empty() {
+        return None;
+    }
+    let dirty = git(repo, &["status", "--porcelain"])
+        .map(|status| !status.trim().is_empty())
+        .unwrap_or(false);
+    Some(GitState {
+        commit,
+        branch: current_branch(repo),
+        dirty,
+        patch: None,
+    })
+}
+
+/// Write `git diff HEAD` into the run directory. Returns the patch file name,
+/// or `None` when there are no tracked changes to save.
+pub fn write_patch(repo: &Path, run_dir: &Path) -> Result<Option<String>> {
+    let Some(diff) = git(repo, &["diff", "HEAD", "--binary"]) else {
+        return Ok(None);
+    };
+    if diff.trim().is_empty() {
+        return Ok(None);
+    }
+    let path = run_dir.join(PATCH_FILE_NAME);
+    fs::write(&path, diff).with_context(|| format!("writing {}", path.display()))?;
+    Ok(Some(PATCH_FILE_NAME.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GitState, PATCH_FILE_NAME, capture, write_patch};
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn run_git(repo: &Path, args: &[&str]) {
+        let out = Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(args)
+            .output()
+            .expect("run git");
+        assert!(
+            out.status.success(),
+            "git {}: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+
+    #[test]
+    fn short_commit_truncates_hash() {
+        let state = GitState {
+            commit: "0123456789abcdef0123".to_string(),
+            branch: None,
+            dirty: false,
+            patch: None,
+        };
+        assert_eq!(state.short_commit(), "0123456789ab");
+    }
+
+    #[test]
+    fn capture_reports_dirty_tree_and_writes_patch() {
+        if Command::new("git").arg("--version").output().is_err() {
+            eprintln!("skipping capture_reports_dirty_tree_and_writes_patch: git is not installed");
+            return;
+        }
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let repo = std::env::temp_dir().join(format!("ogtui-git-state-{nonce}"));
+        fs::create_dir_all(&repo).expect("create repo dir");
+        run_git(&repo, &["init", "-q", "-b", "main"]);
+        fs::write(repo.join("train.py"), "lr = 3e-4\n").expect("write file");
+        run_git(&repo, &["add", "train.py"]);
+        // Identity and signing come from the command line, not the CI
+        // machine's config.
+        run_git(
+            &repo,
+            &[
+                "-c",
+                "user.name=og",
+                "-c",
+                "user.email=og@example.com",
+                "-c",
+                "commit.gpgsign=false",
+                "commit",
+                "-qm",
+                "init",
+            ],
+        );
+
+        let clean = capture(&repo).expect("capture clean repo");
+        assert_eq!(clean.branch.as_deref(), Some("main"));
+        assert!(!clean.dirty);
+        assert_eq!(write_patch(&repo, &repo).expect("no patch"), None);
+
+        fs::write(repo.join("train.py"), "lr = 1e-4\n").expect("modify file");
+        let dirty = capture(&repo).expect("capture dirty repo");
+        assert!(dirty.dirty);
+        assert_eq!(dirty.commit, clean.commit);
+        let patch = write_patch(&repo, &repo).expect("write patch");
+        assert_eq!(patch.as_deref(), Some(PATCH_FILE_NAME));
+        let body = fs::read_to_string(repo.join(PATCH_FILE_NAME)).expect("read patch");
+        assert!(body.contains("+lr = 1e-4"));
+
+        fs::remove_dir_all(&repo).ok();
+    }
+}
@@ -0,0 +1,34 @@
+//! Reading og runs: event-file parsing, run metadata, and the queries behind
+//! `og list`, `og get`, `og compare` and `og search`. The og CLI and TUI call
+//! these directly; other tools can embed them instead of shelling out to og.
+
+pub mod alias;
+pub mod compression;
+pub mod dns;
+pub mod epoch;
+pub mod error;
+pub mod git_state;
+pub mod metrics_jsonl;
+pub mod par_load;
+pub mod query;
+pub mod readers;
+pub mod run_color;
+pub mod run_meta;
+pub mod run_query;
+pub mod tfevents;
+pub mod tfrecord;
+pub mod throughput;
+
+/// Whether a tag names a hardware metric (GPU, CPU, memory, disk, network)
+/// rather than something the training script logged.
+pub fn looks_system_metric(name: &str) -> bool {
+    let n = name.to_ascii_lowercase();
+    n.starts_with("sys/")
+        || n.contains("gpu")
+        || n.contains("vram")
+        || n.contains("cpu")
+        || n.contains("memory")
+        || n.contains("/mem")
+        || n.contains("disk")
+        || n.contains("net")
+}
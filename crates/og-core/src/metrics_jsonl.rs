@@ -0,0 +1,234 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::tfevents::{CorruptionStats, ScalarEvent};
+
+/// File name of the native append-only store.
+pub const FILE_NAME: &str = "metrics.jsonl";
+
+/// One line of `metrics.jsonl`:
+/// `{"step": 12, "wall_time": 1712000000.5, "metrics": {"train/loss": 0.42}}`.
+/// `wall_time` is optional; non-numeric metric values are ignored. A line may
+/// carry a structured `event` (`{"type": "eval_result", ...}`) instead of or
+/// next to its metrics.
+#[derive(Debug, Deserialize)]
+struct Line {
+    step: i64,
+    #[serde(default)]
+    wall_time: f64,
+    #[serde(default)]
+    metrics: BTreeMap<String, serde_json::Value>,
+    #[serde(default)]
+    event: Option<serde_json::Value>,
+}
+
+/// A non-scalar event from the store: its `type` and the rest of its fields.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StructuredEvent {
+    pub step: i64,
+    pub wall_time: f64,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub payload: serde_json::Map<String, serde_json::Value>,
+}
+
+impl StructuredEvent {
+    /// The payload as compact JSON, for one-line listings.
+    pub fn summary(&self) -> String {
+        serde_json::Value::Object(self.payload.clone()).to_string()
+    }
+}
+
+/// `metrics.jsonl` or `<anything>.metrics.jsonl`, optionally `.gz` / `.zst` compressed.
+pub fn is_metrics_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|s| s.to_str()) else {
+        return false;
+    };
+    let lower = name.to_ascii_lowercase();
+    let lower = crate::compression::strip_extension(&lower);
+    lower == FILE_NAME || lower.ends_with(&format!(".{FILE_NAME}"))
+}
+
+/// Parse every well-formed line. Malformed lines are skipped and counted like
+/// resyncs; an unterminated last line is a writer caught mid-append.
+pub fn parse_bytes(bytes: &[u8]) -> (Vec<ScalarEvent>, CorruptionStats) {
+    let mut events = Vec::new();
+    let mut stats = CorruptionStats::default();
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let (raw, terminated) = match rest.iter().position(|b| *b == b'\n') {
+            Some(end) => {
+                let raw = &rest[..end];
+                rest = &rest[end + 1..];
+                (raw, true)
+            }
+            None => (std::mem::take(&mut rest), false),
+        };
+        if raw.trim_ascii().is_empty() {
+            continue;
+        }
+        match serde_json::from_slice::<Line>(raw) {
+            Ok(line) => {
+                for (tag, value) in line.metrics {
+                    if let Some(value) = value.as_f64() {
+                        events.push(ScalarEvent {
+                            tag,
+                            step: line.step,
+                            wall_time: line.wall_time,
+                            value,
+                        });
+                    }
+                }
+            }
+            Err(_) if !terminated => stats.truncated_bytes += raw.len() as u64,
+            Err(_) => {
+                stats.resyncs += 1;
+                stats.skipped_bytes += raw.len() as u64 + 1;
+            }
+        }
+    }
+    if stats.resyncs > 0 || stats.truncated_bytes > 0 {
+        stats.damaged_files = 1;
+    }
+    (events, stats)
+}
+
+/// One `metrics.jsonl` line, newline included.
+pub fn format_line(step: i64, wall_time: f64, metrics: &[(String, f64)]) -> String {
+    let metrics: serde_json::Map<String, serde_json::Value> = metrics
+        .iter()
+        .map(|(tag, value)| (tag.clone(), serde_json::json!(value)))
+        .collect();
+    let mut line = serde_json::json!({
+        "step": step,
+        "wall_time": wall_time,
+        "metrics": metrics,
+    })
+    .to_string();
+    line.push('\n');
+    line
+}
+
+pub fn parse_file(path: &Path) -> Result<(Vec<ScalarEvent>, CorruptionStats)> {
+    let bytes = crate::compression::read(path)?;
+    Ok(parse_bytes(&bytes))
+}
+
+/// Structured events in `bytes`; lines without one (or whose event is not an
+/// object with a string `type`) are left to `parse_bytes`.
+pub fn parse_structured(bytes: &[u8]) -> Vec<StructuredEvent> {
+    bytes
+        .split(|b| *b == b'\n')
+        .filter_map(|raw| serde_json::from_slice::<Line>(raw).ok())
+        .filter_map(|line| {
+            let serde_json::Value::Object(mut payload) = line.event? else {
+                return None;
+            };
+            let serde_json::Value::String(kind) = payload.remove("type")? else {
+                return None;
+            };
+            Some(StructuredEvent {
+                step: line.step,
+                wall_time: line.wall_time,
+                kind,
+                payload,
+            })
+        })
+        .collect()
+}
+
+/// Structured events from every metrics store under `run_path`, ordered by
+/// step. Unreadable files are skipped; `load_run` already warns about them.
+pub fn load_structured(run_path: &Path) -> Result<Vec<StructuredEvent>> {
+    let mut events = Vec::new();
+    for path in crate::tfevents::discover_event_files(run_path)? {
+        if !is_metrics_file(&path) {
+            continue;
+        }
+        if let Ok(bytes) = crate::compression::read(&path) {
+            events.extend(parse_structured(&bytes));
+        }
+    }
+    events.sort_by(|a, b| {
+        a.step
+            .cmp(&b.step)
+            .then(a.wall_time.total_cmp(&b.wall_time))
+    });
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_line, is_metrics_file, parse_bytes, parse_structured};
+    use std::path::Path;
+
+    #[test]
+    fn recognises_plain_and_compressed_metrics_files() {
+        assert!(is_metrics_file(Path::new("run/metrics.jsonl")));
+        assert!(is_metrics_file(Path::new("run/rank1.metrics.jsonl.gz")));
+        assert!(!is_metrics_file(Path::new("run/other.jsonl")));
+        assert!(!is_metrics_file(Path::new("run/metrics.jsonl.bak")));
+    }
+
+    #[test]
+    fn formatted_lines_parse_back() {
+        let line = format_line(7, 12.5, &[("val/acc".to_string(), 0.25)]);
+        assert!(line.ends_with('\n'));
+        let (events, stats) = parse_bytes(line.as_bytes());
+        assert!(stats.is_clean());
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].step, events[0].value), (7, 0.25));
+        assert_eq!(events[0].wall_time, 12.5);
+    }
+
+    #[test]
+    fn skips_bad_lines_and_counts_cut_off_tail() {
+        let bytes = concat!(
+            "{\"step\": 1, \"wall_time\": 10.0, \"metrics\": {\"loss\": 2.0, \"lr\": 0.1}}\n",
+            "not json\n",
+            "\n",
+            "{\"step\": 2, \"metrics\": {\"loss\": 1.5, \"note\": \"warmup done\"}}\n",
+            "{\"step\": 3, \"metr",
+        );
+        let (events, stats) = parse_bytes(bytes.as_bytes());
+        let parsed: Vec<(i64, &str, f64)> = events
+            .iter()
+            .map(|e| (e.step, e.tag.as_str(), e.value))
+            .collect();
+        assert_eq!(
+            parsed,
+            vec![(1, "loss", 2.0), (1, "lr", 0.1), (2, "loss", 1.5)]
+        );
+        assert_eq!(events[0].wall_time, 10.0);
+        assert_eq!(stats.damaged_files, 1);
+        assert_eq!(stats.resyncs, 1);
+        assert_eq!(stats.skipped_bytes, 9);
+        assert_eq!(stats.truncated_bytes, 17);
+    }
+
+    #[test]
+    fn reads_structured_events_without_counting_them_as_damage() {
+        let bytes = concat!(
+            "{\"step\": 4, \"metrics\": {\"loss\": 1.0}}\n",
+            "{\"step\": 5, \"wall_time\": 20.0, \"event\": {\"type\": \"eval_result\", \"task\": \"gsm8k\", \"accuracy\": 0.4}}\n",
+            "{\"step\": 6, \"event\": {\"text\": \"no type\"}}\n",
+        );
+        let (events, stats) = parse_bytes(bytes.as_bytes());
+        assert!(stats.is_clean());
+        assert_eq!(events.len(), 1);
+
+        let structured = parse_structured(bytes.as_bytes());
+        assert_eq!(structured.len(), 1);
+        assert_eq!(
+            (structured[0].step, structured[0].kind.as_str()),
+            (5, "eval_result")
+        );
+        assert_eq!(structured[0].wall_time, 20.0);
+        assert_eq!(
+            structured[0].summary(),
+            "{\"accuracy\":0.4,\"task\":\"gsm8k\"}"
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use std::io::{IsTerminal, Write};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Upper bound on default worker threads; run loading is mostly disk-bound.
+const MAX_DEFAULT_JOBS: usize = 8;
+
+/// Scans shorter than this finish too quickly for a progress line to help.
+const PROGRESS_MIN_ITEMS: usize = 16;
+
+/// Worker count when `--jobs` / `OG_LOAD_JOBS` is not given.
+pub fn default_jobs() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(MAX_DEFAULT_JOBS)
+}
+
+/// Apply `load` to every item on at most `jobs` threads, keeping input order.
+/// `progress` is called with `(done, total)` after each item finishes.
+pub fn map_bounded<T, R, F>(
+    items: &[T],
+    jobs: usize,
+    progress: Option<&(dyn Fn(usize, usize) + Sync)>,
+    load: F,
+) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let workers = jobs.max(1).min(items.len());
+    if workers <= 1 {
+        return items
+            .iter()
+            .enumerate()
+            .map(|(idx, item)| {
+                let result = load(item);
+                if let Some(progress) = progress {
+                    progress(idx + 1, items.len());
+                }
+                result
+            })
+            .collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new((0..items.len()).map(|_| None).collect());
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = items.get(idx) else {
+                        break;
+                    };
+                    let result = load(item);
+                    results.lock().expect("results lock")[idx] = Some(result);
+                    let finished = done.fetch_add(1, Ordering::Relaxed) + 1;
+                    if let Some(progress) = progress {
+                        progress(finished, items.len());
+                    }
+                }
+            });
+        }
+    });
+    results
+        .into_inner()
+        .expect("results lock")
+        .into_iter()
+        .map(|result| result.expect("every item is loaded"))
+        .collect()
+}
+
+/// `loading runs 120/300` on stderr for long scans, only when stderr is a terminal.
+pub fn stderr_progress(label: &'static str) -> impl Fn(usize, usize) + Sync {
+    let interactive = std::io::stderr().is_terminal();
+    move |done, total| {
+        if !interactive || total < PROGRESS_MIN_ITEMS {
+            return;
+        }
+        let mut stderr = std::io::stderr().lock();
+        if done >= total {
+            let _ = write!(stderr, "\r\x1b[2K");
+        } else {
+            let _ = write!(stderr, "\r{} {}/{}", label, done, total);
+        }
+        let _ = stderr.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::map_bounded;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn map_bounded_keeps_order_and_reports_progress() {
+        let items: Vec<usize> = (0..50).collect();
+        let seen = Mutex::new(Vec::new());
+        let progress = |done: usize, total: usize| seen.lock().expect("lock").push((done, total));
+        let squares = map_bounded(&items, 4, Some(&progress), |n| n * n);
+
+        assert_eq!(squares, items.iter().map(|n| n * n).collect::<Vec<_>>());
+        let mut seen = seen.into_inner().expect("lock");
+        seen.sort();
+        assert_eq!(seen.len(), 50);
+        assert_eq!(seen.last(), Some(&(50, 50)));
+    }
+
+    #[test]
+    fn map_bounded_never_exceeds_job_limit() {
+        let items: Vec<usize> = (0..32).collect();
+        let active = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        map_bounded(&items, 3, None, |_| {
+            let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            std::thread::sleep(std::time::Duration::from_millis(2));
+            active.fetch_sub(1, Ordering::SeqCst);
+        });
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+        assert!(map_bounded(&Vec::<usize>::new(), 3, None, |n| *n).is_empty());
+    }
+}
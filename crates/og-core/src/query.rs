@@ -0,0 +1,959 @@
+//! The read side of og: find projects and runs under a runs root, summarize
+//! them and answer metric questions, with typed results. Callers format the
+//! results themselves; the og CLI prints them as text or JSON.
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::epoch::EpochScale;
+use crate::error::OgError;
+use crate::tfevents::{self, CorruptionStats, LoadedRun, ScalarEvent};
+use crate::{alias, looks_system_metric, par_load, readers, run_color, run_meta, run_query};
+
+/// Progress callback for scans over many runs: `(done, total)`.
+pub type Progress<'a> = Option<&'a (dyn Fn(usize, usize) + Sync)>;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProjectSummary {
+    pub name: String,
+    pub path: String,
+    pub run_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    pub id: String,
+    pub path: String,
+    pub metric_count: usize,
+    pub event_count: usize,
+    pub max_step: i64,
+    pub status: String,
+    pub last_updated_unix: Option<u64>,
+    /// Bytes on disk, including registered checkpoints; only `og list runs` measures it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+}
+
+/// Which runs `list_runs` keeps. Empty fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct RunFilter {
+    /// Run status, case-insensitive.
+    pub status: Option<String>,
+    /// Each must be a label set with `og tag` or part of the run id.
+    pub tags: Vec<String>,
+    /// Each must match the run's query fields (see `run_query_fields`).
+    pub config: Vec<run_query::Query>,
+    /// SLURM job (or array job) the run was launched in.
+    pub slurm_job: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct MetricPoint {
+    pub step: f64,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunDetail {
+    #[serde(rename = "run")]
+    pub summary: RunSummary,
+    pub latest_metrics: BTreeMap<String, MetricPoint>,
+    pub ranks: Vec<u32>,
+    pub rank: Option<u32>,
+    pub corruption: CorruptionStats,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricSeries {
+    pub run: String,
+    pub metric: String,
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub last: f64,
+    pub points: Vec<MetricPoint>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareAxis {
+    /// Seconds since the run's first event
+    #[value(alias = "wall_time")]
+    WallTime,
+    Step,
+    /// The run's epoch counter, else its configured steps per epoch
+    Epoch,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompareOptions {
+    pub metric: String,
+    /// Rank runs by when they first reached this value.
+    pub target: Option<f64>,
+    pub x: CompareAxis,
+    /// Direction from the project objective, used when the run records none.
+    pub objective_higher_is_better: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Comparison {
+    pub run: String,
+    pub color: String,
+    pub found: bool,
+    #[serde(flatten)]
+    pub stats: Option<SeriesStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<TargetOutcome>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SeriesStats {
+    pub count: usize,
+    pub first: f64,
+    pub last: f64,
+    pub delta: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TargetOutcome {
+    pub value: f64,
+    pub higher_is_better: bool,
+    pub reached: bool,
+    pub step: Option<i64>,
+    pub wall_secs: Option<f64>,
+    /// Epoch of `step`, for runs with an epoch counter or steps per epoch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<f64>,
+    pub value_at: Option<f64>,
+    /// Whether the run logs an epoch counter or sets steps per epoch
+    pub epoch_scale: bool,
+}
+
+/// Where a metric first reached a target value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TargetHit {
+    pub step: i64,
+    /// Seconds from the run's first event (any tag) to this one.
+    pub wall_secs: f64,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunMatch {
+    pub run: RunSummary,
+    pub config: BTreeMap<String, String>,
+    /// Everything the query could reference, for showing the matched columns.
+    #[serde(skip)]
+    pub fields: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricMatch {
+    pub metric: String,
+    pub run: String,
+    pub path: String,
+}
+
+/// Projects under a runs root, by name.
+pub fn list_projects(root: &Path) -> Result<Vec<ProjectSummary>> {
+    let mut projects = Vec::new();
+    for dir in list_immediate_dirs(root)? {
+        projects.push(ProjectSummary {
+            name: dir
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default()
+                .to_string(),
+            path: dir.display().to_string(),
+            run_count: list_run_dirs(&dir)?.len(),
+        });
+    }
+    projects.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(projects)
+}
+
+/// Runs under `base` that pass `filter`, most recently updated first.
+pub fn list_runs(
+    base: &Path,
+    filter: &RunFilter,
+    jobs: usize,
+    progress: Progress<'_>,
+) -> Result<Vec<RunSummary>> {
+    let run_dirs = list_run_dirs(base)?;
+    let summaries =
+        par_load::map_bounded(&run_dirs, jobs, progress, |run_dir| summarize_run(run_dir));
+    let mut runs = Vec::new();
+    for (run_dir, summary) in run_dirs.iter().zip(summaries) {
+        let summary = summary?;
+        if let Some(status_filter) = filter.status.as_deref()
+            && !summary.status.eq_ignore_ascii_case(status_filter)
+        {
+            continue;
+        }
+        let id_l = summary.id.to_ascii_lowercase();
+        let tags = if filter.tags.is_empty() {
+            Vec::new()
+        } else {
+            run_meta::load(run_dir).unwrap_or_default().tags
+        };
+        // A tag matches a label set with `og tag` or, as before, part of the run id.
+        if !filter.tags.iter().all(|needle| {
+            id_l.contains(&needle.to_ascii_lowercase())
+                || tags.iter().any(|tag| tag.eq_ignore_ascii_case(needle))
+        }) {
+            continue;
+        }
+        if let Some(job) = filter.slurm_job.as_deref()
+            && !run_meta::load(run_dir)
+                .unwrap_or_default()
+                .slurm
+                .is_some_and(|slurm| slurm.matches(job))
+        {
+            continue;
+        }
+        if !filter.config.is_empty() {
+            let fields = run_query_fields(&summary, &run_meta::load(run_dir).unwrap_or_default());
+            if !filter.config.iter().all(|query| query.matches(&fields)) {
+                continue;
+            }
+        }
+        runs.push(summary);
+    }
+    runs.sort_by(|a, b| match b.last_updated_unix.cmp(&a.last_updated_unix) {
+        Ordering::Equal => a.id.cmp(&b.id),
+        other => other,
+    });
+    Ok(runs)
+}
+
+/// Metric names in a run with their point counts; `system_only` keeps the
+/// hardware metrics.
+pub fn list_metrics(run_path: &Path, system_only: bool) -> Result<BTreeMap<String, usize>> {
+    let loaded = load(run_path)?;
+    Ok(loaded
+        .scalars
+        .iter()
+        .filter(|(name, _)| !system_only || looks_system_metric(name))
+        .map(|(name, series)| (name.clone(), series.len()))
+        .collect())
+}
+
+pub fn summarize_run(path: &Path) -> Result<RunSummary> {
+    summary_from_loaded(path, &load(path)?)
+}
+
+/// A run's summary and latest value of every metric, for one rank when `rank`
+/// is set.
+pub fn get_run(run_path: &Path, rank: Option<u32>) -> Result<RunDetail> {
+    let loaded = load(run_path)?;
+    if let Some(rank) = rank
+        && !loaded.ranks.contains_key(&rank)
+    {
+        bail!("run {} has no rank {}", run_path.display(), rank);
+    }
+    let latest_metrics = loaded
+        .view(rank)
+        .iter()
+        .filter_map(|(metric, series)| {
+            let &(step, value) = series.last()?;
+            Some((metric.clone(), MetricPoint { step, value }))
+        })
+        .collect();
+    Ok(RunDetail {
+        summary: summary_from_loaded(run_path, &loaded)?,
+        latest_metrics,
+        ranks: loaded.ranks.keys().copied().collect(),
+        rank,
+        corruption: loaded.corruption,
+    })
+}
+
+pub fn get_metric(run_path: &Path, metric: &str) -> Result<MetricSeries> {
+    let loaded = load(run_path)?;
+    let Some(series) = loaded.scalars.get(metric) else {
+        return Err(OgError::metric_not_found(metric, run_path, loaded.scalars.keys()).into());
+    };
+    let (min, max, last) = summarize_series(series);
+    Ok(MetricSeries {
+        run: run_path.display().to_string(),
+        metric: metric.to_string(),
+        count: series.len(),
+        min,
+        max,
+        last,
+        points: series
+            .iter()
+            .map(|&(step, value)| MetricPoint { step, value })
+            .collect(),
+    })
+}
+
+/// One metric across runs. With a target, runs that reached it come first,
+/// fastest first along `options.x`; otherwise runs keep their given order.
+/// Along epochs, runs without an epoch scale rank after those with one.
+pub fn compare(run_paths: &[PathBuf], options: &CompareOptions) -> Result<Vec<Comparison>> {
+    let metric = &options.metric;
+    let mut ranked: Vec<(Option<f64>, Comparison)> = Vec::new();
+    for run_path in run_paths {
+        let loaded = load(run_path)?;
+        let color = run_color_hex(run_path);
+        let Some(series) = loaded.scalars.get(metric) else {
+            ranked.push((
+                None,
+                Comparison {
+                    run: run_path.display().to_string(),
+                    color,
+                    found: false,
+                    stats: None,
+                    target: None,
+                },
+            ));
+            continue;
+        };
+        let (min, max, last) = summarize_series(series);
+        let first = series.first().map(|(_, v)| *v).unwrap_or(last);
+        let mut sort_key = None;
+        let target = options.target.map(|target| {
+            let meta = run_meta::load(run_path).unwrap_or_default();
+            // The run's recorded direction, else the objective's, else
+            // whichever way the metric has to move from its first value.
+            let higher_is_better = meta
+                .metrics
+                .get(metric)
+                .and_then(|attrs| attrs.higher_is_better)
+                .or(options.objective_higher_is_better)
+                .unwrap_or(first < target);
+            let hit = first_reaching(&loaded.events, metric, target, higher_is_better);
+            let scale = EpochScale::for_run(&loaded.scalars, &meta);
+            let epoch = hit
+                .as_ref()
+                .and_then(|hit| scale.as_ref().map(|scale| scale.epoch_at(hit.step as f64)));
+            sort_key = hit.as_ref().and_then(|hit| match options.x {
+                CompareAxis::WallTime => Some(hit.wall_secs),
+                CompareAxis::Step => Some(hit.step as f64),
+                CompareAxis::Epoch => epoch,
+            });
+            TargetOutcome {
+                value: target,
+                higher_is_better,
+                reached: hit.is_some(),
+                step: hit.as_ref().map(|hit| hit.step),
+                wall_secs: hit.as_ref().map(|hit| hit.wall_secs),
+                epoch,
+                value_at: hit.as_ref().map(|hit| hit.value),
+                epoch_scale: scale.is_some(),
+            }
+        });
+        ranked.push((
+            sort_key,
+            Comparison {
+                run: run_path.display().to_string(),
+                color,
+                found: true,
+                stats: Some(SeriesStats {
+                    count: series.len(),
+                    first,
+                    last,
+                    delta: last - first,
+                    min,
+                    max,
+                }),
+                target,
+            },
+        ));
+    }
+    if options.target.is_some() {
+        // Stable, so runs that never reached the target keep their order.
+        ranked.sort_by(|a, b| match (a.0, b.0) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+    }
+    Ok(ranked
+        .into_iter()
+        .map(|(_, comparison)| comparison)
+        .collect())
+}
+
+/// The earliest `tag` event, in wall-time order, at or past `target`.
+pub fn first_reaching(
+    events: &[ScalarEvent],
+    tag: &str,
+    target: f64,
+    higher_is_better: bool,
+) -> Option<TargetHit> {
+    let start = events
+        .iter()
+        .map(|event| event.wall_time)
+        .filter(|time| time.is_finite())
+        .fold(f64::INFINITY, f64::min);
+    events
+        .iter()
+        .filter(|event| event.tag == tag)
+        .filter(|event| {
+            if higher_is_better {
+                event.value >= target
+            } else {
+                event.value <= target
+            }
+        })
+        .min_by(|a, b| {
+            a.wall_time
+                .total_cmp(&b.wall_time)
+                .then(a.step.cmp(&b.step))
+        })
+        .map(|event| TargetHit {
+            step: event.step,
+            wall_secs: (event.wall_time - start).max(0.0),
+            value: event.value,
+        })
+}
+
+/// Runs under `base` whose query fields match `query`.
+pub fn search_runs(base: &Path, query: &run_query::Query) -> Result<Vec<RunMatch>> {
+    let mut matches = Vec::new();
+    for run_dir in list_run_dirs(base)? {
+        let summary = summarize_run(&run_dir)?;
+        let meta = run_meta::load(&run_dir).unwrap_or_default();
+        let fields = run_query_fields(&summary, &meta);
+        if !query.matches(&fields) {
+            continue;
+        }
+        matches.push(RunMatch {
+            run: summary,
+            config: meta.config,
+            fields,
+        });
+    }
+    Ok(matches)
+}
+
+/// Metrics whose name contains `needle` (case-insensitive) in any run under
+/// `base`, by metric name.
+pub fn search_metrics(
+    base: &Path,
+    needle: &str,
+    jobs: usize,
+    progress: Progress<'_>,
+) -> Result<Vec<MetricMatch>> {
+    let needle = needle.to_ascii_lowercase();
+    let run_dirs = list_run_dirs(base)?;
+    let loaded = par_load::map_bounded(&run_dirs, jobs, progress, |run_dir| load(run_dir));
+    let mut matches = Vec::new();
+    for (run_dir, loaded) in run_dirs.iter().zip(loaded) {
+        let loaded = loaded?;
+        let run_id = run_dir
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string();
+        for name in loaded.scalars.keys() {
+            if name.to_ascii_lowercase().contains(&needle) {
+                matches.push(MetricMatch {
+                    metric: name.clone(),
+                    run: run_id.clone(),
+                    path: run_dir.display().to_string(),
+                });
+            }
+        }
+    }
+    matches.sort_by(|a, b| a.metric.cmp(&b.metric));
+    Ok(matches)
+}
+
+/// Fields a run query can reference: recorded config plus a few built-ins.
+pub fn run_query_fields(
+    summary: &RunSummary,
+    meta: &run_meta::RunMeta,
+) -> BTreeMap<String, String> {
+    let mut fields = meta.config.clone();
+    fields.insert("run".to_string(), summary.id.clone());
+    fields.insert("status".to_string(), summary.status.clone());
+    fields.insert("step".to_string(), summary.max_step.to_string());
+    if let Some(source) = &meta.forked_from {
+        fields.insert("forked_from".to_string(), source.clone());
+    }
+    if let Some(git) = &meta.git {
+        fields.insert("git.commit".to_string(), git.commit.clone());
+        fields.insert("git.dirty".to_string(), git.dirty.to_string());
+        if let Some(branch) = &git.branch {
+            fields.insert("git.branch".to_string(), branch.clone());
+        }
+    }
+    fields
+}
+
+/// `(min, max, last)` of a series; zeros when it is empty.
+pub fn summarize_series(series: &[(f64, f64)]) -> (f64, f64, f64) {
+    if series.is_empty() {
+        return (0.0, 0.0, 0.0);
+    }
+    let mut min = f64::INFINITY;
+    let mut max = f64::NEG_INFINITY;
+    for (_, value) in series {
+        min = min.min(*value);
+        max = max.max(*value);
+    }
+    let last = series.last().map(|(_, v)| *v).unwrap_or(0.0);
+    (min, max, last)
+}
+
+/// `#rrggbb` color of a run: the metadata override, else hashed from the run id.
+pub fn run_color_hex(run_path: &Path) -> String {
+    let run_dir = run_meta::run_dir_for(run_path);
+    let id = run_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let meta = run_meta::load(&run_dir).unwrap_or_default();
+    run_color::to_hex(run_color::for_run(&id, meta.color.as_deref()))
+}
+
+/// The run's recorded heartbeat or final state when there is a usable one;
+/// otherwise a run counts as running if its files were written in the last
+/// two minutes.
+pub fn run_status(
+    heartbeat: Option<&run_meta::RunHeartbeat>,
+    last_updated_unix: Option<u64>,
+) -> &'static str {
+    if let Some(status) = heartbeat.and_then(|heartbeat| heartbeat.status(unix_now_secs())) {
+        return status;
+    }
+    match last_updated_unix {
+        Some(last) if unix_now_secs().saturating_sub(last) <= 120 => "running",
+        Some(_) => "inactive",
+        None => "unknown",
+    }
+}
+
+pub fn project_base(path: &Path, project: Option<&str>) -> PathBuf {
+    if let Some(project) = project {
+        path.join(project)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Where `run` lives: a path as given, else a run under the project, where
+/// `latest` and aliases from `og alias set` also name runs.
+pub fn resolve_run_path(path: &Path, project: Option<&str>, run: &str) -> PathBuf {
+    let direct = PathBuf::from(run);
+    if direct.exists() {
+        return direct;
+    }
+    resolve_alias_path(&project_base(path, project).join(run))
+}
+
+/// `path` itself if it exists; otherwise, when its last component is
+/// `latest` or an alias set in its parent directory, the run that names.
+pub fn resolve_alias_path(path: &Path) -> PathBuf {
+    if path.exists() {
+        return path.to_path_buf();
+    }
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name().and_then(|n| n.to_str()))
+    else {
+        return path.to_path_buf();
+    };
+    let parent = if parent.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        parent
+    };
+    alias::resolve(parent, name).unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Like `resolve_run_path`, but a run missing from the project may also be
+/// named `project/run` relative to the runs root, so runs from several
+/// projects can be mixed.
+pub fn resolve_qualified_run_path(path: &Path, project: Option<&str>, run: &str) -> PathBuf {
+    let resolved = resolve_run_path(path, project, run);
+    if resolved.exists() || project.is_none() {
+        return resolved;
+    }
+    let qualified = resolve_alias_path(&path.join(run));
+    if qualified.exists() {
+        qualified
+    } else {
+        resolved
+    }
+}
+
+pub fn list_immediate_dirs(path: &Path) -> Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    if !path.exists() {
+        return Ok(dirs);
+    }
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let entry_path = entry.path();
+        if entry_path.is_dir() {
+            dirs.push(entry_path);
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// Run directories under `path`, or `path` itself when it is a run.
+pub fn list_run_dirs(path: &Path) -> Result<Vec<PathBuf>> {
+    let mut runs = Vec::new();
+    for dir in list_immediate_dirs(path)? {
+        if contains_tfevents(&dir)? {
+            runs.push(dir);
+        }
+    }
+
+    if runs.is_empty() && path.exists() && contains_tfevents(path)? {
+        runs.push(path.to_path_buf());
+    }
+    runs.sort();
+    Ok(runs)
+}
+
+pub fn contains_tfevents_direct(path: &Path) -> Result<bool> {
+    if !path.exists() || !path.is_dir() {
+        return Ok(false);
+    }
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let entry_path = entry.path();
+        if entry_path.is_file() && readers::is_run_file(&entry_path) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+pub fn contains_tfevents(path: &Path) -> Result<bool> {
+    if path.is_file() {
+        return Ok(readers::is_run_file(path));
+    }
+
+    if !path.exists() || !path.is_dir() {
+        return Ok(false);
+    }
+
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let entry_path = entry.path();
+        if entry_path.is_dir() {
+            if contains_tfevents(&entry_path)? {
+                return Ok(true);
+            }
+        } else if readers::is_run_file(&entry_path) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+pub fn latest_mtime_unix(path: &Path) -> Result<Option<u64>> {
+    fn inner(path: &Path, best: &mut Option<u64>) -> Result<()> {
+        if path.is_file() {
+            let mtime = fs::metadata(path)
+                .ok()
+                .and_then(|m| m.modified().ok())
+                .and_then(|ts| ts.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs());
+            if let Some(mtime) = mtime {
+                *best = Some(best.map_or(mtime, |current| current.max(mtime)));
+            }
+            return Ok(());
+        }
+        if !path.exists() {
+            return Ok(());
+        }
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            inner(&entry.path(), best)?;
+        }
+        Ok(())
+    }
+
+    let mut best = None;
+    inner(path, &mut best)?;
+    Ok(best)
+}
+
+fn load(path: &Path) -> Result<LoadedRun> {
+    if !path.exists() {
+        return Err(OgError::run_not_found(&path.display().to_string(), path).into());
+    }
+    tfevents::load_run(path).with_context(|| format!("loading events from {}", path.display()))
+}
+
+fn summary_from_loaded(path: &Path, loaded: &LoadedRun) -> Result<RunSummary> {
+    let id = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| path.display().to_string());
+    let last_updated_unix = latest_mtime_unix(path)?;
+    let heartbeat = run_meta::load(path).unwrap_or_default().status;
+    let status = run_status(heartbeat.as_ref(), last_updated_unix).to_string();
+    Ok(RunSummary {
+        id,
+        path: path.display().to_string(),
+        metric_count: loaded.scalars.len(),
+        event_count: loaded.events.len(),
+        max_step: loaded.events.iter().map(|e| e.step).max().unwrap_or(0),
+        status,
+        last_updated_unix,
+        size_bytes: None,
+    })
+}
+
+fn unix_now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CompareAxis, CompareOptions, MetricPoint, RunFilter, TargetHit, compare, first_reaching,
+        get_metric, get_run, list_metrics, list_projects, list_runs, resolve_qualified_run_path,
+        run_status, search_metrics, search_runs, unix_now_secs,
+    };
+    use crate::run_meta;
+    use crate::tfevents::ScalarEvent;
+    use clap::ValueEnum;
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn run_status_prefers_the_heartbeat_over_file_times() {
+        let now = unix_now_secs();
+        let long_ago = Some(now - 3600);
+        assert_eq!(run_status(None, long_ago), "inactive");
+
+        // A run logging every ten minutes, with a fresh heartbeat.
+        let mut heartbeat = run_meta::RunHeartbeat {
+            state: run_meta::RunState::Running,
+            heartbeat_unix: now - 10,
+            pid: None,
+            exit_code: None,
+            reason: None,
+        };
+        assert_eq!(run_status(Some(&heartbeat), long_ago), "running");
+
+        heartbeat.state = run_meta::RunState::Killed;
+        assert_eq!(run_status(Some(&heartbeat), Some(now)), "killed");
+
+        // A stale heartbeat leaves it to the file times.
+        heartbeat.state = run_meta::RunState::Running;
+        heartbeat.heartbeat_unix = now - 3600;
+        assert_eq!(run_status(Some(&heartbeat), Some(now)), "running");
+        assert_eq!(run_status(Some(&heartbeat), long_ago), "inactive");
+    }
+
+    #[test]
+    fn resolve_qualified_run_path_falls_back_to_other_projects() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("og-core-qualified-{nonce}"));
+        fs::create_dir_all(root.join("new/r1")).expect("create new run");
+        fs::create_dir_all(root.join("old/base")).expect("create old run");
+
+        assert_eq!(
+            resolve_qualified_run_path(&root, Some("new"), "r1"),
+            root.join("new/r1")
+        );
+        assert_eq!(
+            resolve_qualified_run_path(&root, Some("new"), "old/base"),
+            root.join("old/base")
+        );
+        assert_eq!(
+            resolve_qualified_run_path(&root, Some("new"), "missing"),
+            root.join("new/missing")
+        );
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn first_reaching_measures_from_the_run_start_in_the_metric_direction() {
+        let event = |tag: &str, step, wall_time, value| ScalarEvent {
+            tag: tag.to_string(),
+            step,
+            wall_time,
+            value,
+        };
+        let events = vec![
+            event("sys/gpu_util", 0, 100.0, 90.0),
+            event("val/loss", 10, 160.0, 3.0),
+            event("val/loss", 20, 400.0, 2.4),
+            // Resumed from step 15 later; the earlier crossing still counts.
+            event("val/loss", 15, 900.0, 2.2),
+        ];
+
+        let hit = first_reaching(&events, "val/loss", 2.5, false).expect("reached");
+        assert_eq!(
+            hit,
+            TargetHit {
+                step: 20,
+                wall_secs: 300.0,
+                value: 2.4,
+            }
+        );
+        assert_eq!(first_reaching(&events, "val/loss", 2.0, false), None);
+        let hit = first_reaching(&events, "val/loss", 2.5, true).expect("reached");
+        assert_eq!(hit.step, 10);
+    }
+
+    #[test]
+    fn queries_list_get_compare_and_search_runs() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("og-core-query-{nonce}"));
+        let write_run = |run: &str, losses: &[f64]| {
+            let dir = root.join("proj").join(run);
+            fs::create_dir_all(&dir).expect("run dir");
+            let lines: String = losses
+                .iter()
+                .enumerate()
+                .map(|(step, loss)| {
+                    format!(
+                        "{{\"step\": {step}, \"wall_time\": {}, \"metrics\": {{\"val/loss\": {loss}, \"sys/gpu_util\": 90}}}}\n",
+                        100 + step * 10
+                    )
+                })
+                .collect();
+            fs::write(dir.join(crate::metrics_jsonl::FILE_NAME), lines).expect("metrics");
+            dir
+        };
+        let slow = write_run("slow", &[3.0, 2.8, 2.6, 2.4]);
+        let fast = write_run("fast", &[3.0, 2.2]);
+        fs::create_dir_all(root.join("empty")).expect("empty project");
+
+        let projects = list_projects(&root).expect("projects");
+        let counts: Vec<(&str, usize)> = projects
+            .iter()
+            .map(|p| (p.name.as_str(), p.run_count))
+            .collect();
+        assert_eq!(counts, [("empty", 0), ("proj", 2)]);
+
+        let filter = RunFilter {
+            tags: vec!["fa".to_string()],
+            ..RunFilter::default()
+        };
+        let runs = list_runs(&root.join("proj"), &filter, 2, None).expect("runs");
+        assert_eq!(runs.len(), 1);
+        assert_eq!((runs[0].id.as_str(), runs[0].max_step), ("fast", 1));
+        assert_eq!(runs[0].metric_count, 2);
+
+        run_meta::update(&slow, |meta| {
+            meta.slurm = Some(run_meta::SlurmJob {
+                job_id: "4242_1".to_string(),
+                node_list: None,
+                partition: None,
+            });
+        })
+        .expect("record job");
+        let by_job = |job: &str| {
+            let filter = RunFilter {
+                slurm_job: Some(job.to_string()),
+                ..RunFilter::default()
+            };
+            list_runs(&root.join("proj"), &filter, 2, None)
+                .expect("runs")
+                .into_iter()
+                .map(|run| run.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(by_job("4242"), ["slow"]);
+        assert!(by_job("4243").is_empty());
+
+        let metrics = list_metrics(&slow, true).expect("metrics");
+        assert_eq!(
+            metrics.into_iter().collect::<Vec<_>>(),
+            [("sys/gpu_util".to_string(), 4)]
+        );
+        let detail = get_run(&slow, None).expect("run");
+        assert_eq!(
+            detail.latest_metrics["val/loss"],
+            MetricPoint {
+                step: 3.0,
+                value: 2.4
+            }
+        );
+        assert!(get_run(&slow, Some(1)).is_err());
+        let series = get_metric(&fast, "val/loss").expect("series");
+        assert_eq!((series.count, series.min, series.last), (2, 2.2, 2.2));
+        assert!(get_metric(&fast, "missing").is_err());
+
+        let options = CompareOptions {
+            metric: "val/loss".to_string(),
+            target: Some(2.5),
+            x: CompareAxis::Step,
+            objective_higher_is_better: None,
+        };
+        let comparisons = compare(&[slow.clone(), fast.clone()], &options).expect("compare");
+        let order: Vec<(&str, Option<i64>)> = comparisons
+            .iter()
+            .map(|c| (c.run.as_str(), c.target.and_then(|t| t.step)))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                (fast.to_str().expect("utf8"), Some(1)),
+                (slow.to_str().expect("utf8"), Some(3))
+            ]
+        );
+        let json = serde_json::to_value(&comparisons[0]).expect("json");
+        assert_eq!(json["delta"].as_f64(), Some(2.2 - 3.0));
+        assert_eq!(json["target"]["higher_is_better"].as_bool(), Some(false));
+        assert_eq!(json["target"]["epoch_scale"].as_bool(), Some(false));
+        assert_eq!(
+            CompareAxis::from_str("wall_time", false),
+            Ok(CompareAxis::WallTime)
+        );
+
+        // Fast took half of a 2-step epoch, slow three steps of 8 and ranks first.
+        for (run, steps_per_epoch) in [(&slow, "8"), (&fast, "2")] {
+            run_meta::update(run, |meta| {
+                meta.config
+                    .insert("steps_per_epoch".to_string(), steps_per_epoch.to_string());
+            })
+            .expect("steps per epoch");
+        }
+        let by_epoch = CompareOptions {
+            x: CompareAxis::Epoch,
+            ..options
+        };
+        let comparisons = compare(&[fast.clone(), slow.clone()], &by_epoch).expect("compare");
+        let epochs: Vec<Option<f64>> = comparisons
+            .iter()
+            .map(|c| c.target.and_then(|t| t.epoch))
+            .collect();
+        assert_eq!(epochs, [Some(0.375), Some(0.5)]);
+        assert!(
+            comparisons
+                .iter()
+                .all(|c| c.target.is_some_and(|t| t.epoch_scale))
+        );
+
+        let found = search_metrics(&root.join("proj"), "LOSS", 1, None).expect("search");
+        let runs: Vec<&str> = found.iter().map(|m| m.run.as_str()).collect();
+        assert_eq!(runs, ["fast", "slow"]);
+        let query = crate::run_query::parse("step>=2").expect("query");
+        let matches = search_runs(&root.join("proj"), &query).expect("search runs");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].run.id, "slow");
+        fs::remove_dir_all(&root).ok();
+    }
+}
@@ -0,0 +1,263 @@
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+use crate::tfevents::{CorruptionStats, ScalarEvent};
+
+/// Environment variable registering external reader plugins:
+/// `pattern=command;pattern=command`, e.g. `*.wandb=og-read-wandb --scalars`.
+pub const PLUGINS_ENV_VAR: &str = "OG_READERS";
+
+/// What a reader found in one file, without keeping the points.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FileSummary {
+    pub points: usize,
+    pub tags: BTreeSet<String>,
+    pub last_step: Option<i64>,
+    pub corruption: CorruptionStats,
+}
+
+/// A source of scalar points for runs. Built-in readers cover `.tfevents` and
+/// `metrics.jsonl`; other formats plug in through `OG_READERS` or by adding an
+/// implementation to `builtin_readers`.
+pub trait RunReader: Send + Sync {
+    /// Short name shown by `og list readers`.
+    fn name(&self) -> &str;
+
+    /// Whether `path` is a file this reader understands. Run discovery asks
+    /// every reader about every file, so this should only look at the name.
+    fn recognizes(&self, path: &Path) -> bool;
+
+    /// Feed every point in `path` to `sink`, in file order.
+    fn stream(&self, path: &Path, sink: &mut dyn FnMut(ScalarEvent)) -> Result<CorruptionStats>;
+
+    fn summarize(&self, path: &Path) -> Result<FileSummary> {
+        let mut summary = FileSummary::default();
+        summary.corruption = self.stream(path, &mut |event| {
+            summary.points += 1;
+            summary.last_step = summary.last_step.max(Some(event.step));
+            summary.tags.insert(event.tag);
+        })?;
+        Ok(summary)
+    }
+}
+
+struct TfeventsReader;
+
+impl RunReader for TfeventsReader {
+    fn name(&self) -> &str {
+        "tfevents"
+    }
+
+    fn recognizes(&self, path: &Path) -> bool {
+        crate::tfevents::is_tfevents_file(path)
+    }
+
+    fn stream(&self, path: &Path, sink: &mut dyn FnMut(ScalarEvent)) -> Result<CorruptionStats> {
+        let (events, stats) = crate::tfevents::parse_tfevents_file(path)?;
+        events.into_iter().for_each(sink);
+        Ok(stats)
+    }
+}
+
+struct MetricsJsonlReader;
+
+impl RunReader for MetricsJsonlReader {
+    fn name(&self) -> &str {
+        "metrics.jsonl"
+    }
+
+    fn recognizes(&self, path: &Path) -> bool {
+        crate::metrics_jsonl::is_metrics_file(path)
+    }
+
+    fn stream(&self, path: &Path, sink: &mut dyn FnMut(ScalarEvent)) -> Result<CorruptionStats> {
+        let (events, stats) = crate::metrics_jsonl::parse_file(path)?;
+        events.into_iter().for_each(sink);
+        Ok(stats)
+    }
+}
+
+/// Plugin reader: runs `command <file>` and reads `metrics.jsonl` lines from
+/// its stdout.
+struct ExternalReader {
+    spec: String,
+    pattern: String,
+    argv: Vec<String>,
+}
+
+impl RunReader for ExternalReader {
+    fn name(&self) -> &str {
+        &self.spec
+    }
+
+    fn recognizes(&self, path: &Path) -> bool {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| glob_match(&self.pattern, name))
+    }
+
+    fn stream(&self, path: &Path, sink: &mut dyn FnMut(ScalarEvent)) -> Result<CorruptionStats> {
+        let output = Command::new(&self.argv[0])
+            .args(&self.argv[1..])
+            .arg(path)
+            .stdin(Stdio::null())
+            .output()
+            .with_context(|| format!("running reader plugin '{}'", self.spec))?;
+        if !output.status.success() {
+            bail!(
+                "reader plugin '{}' failed on {}: {}",
+                self.spec,
+                path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        let (events, stats) = crate::metrics_jsonl::parse_bytes(&output.stdout);
+        events.into_iter().for_each(sink);
+        Ok(stats)
+    }
+}
+
+/// `*` matches any run of characters; everything else matches literally.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let Some((head, rest)) = pattern.split_once('*') else {
+        return pattern == name;
+    };
+    let Some(mut remaining) = name.strip_prefix(head) else {
+        return false;
+    };
+    let mut parts = rest.split('*').peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            return remaining.len() >= part.len() && remaining.ends_with(part);
+        }
+        match remaining.find(part) {
+            Some(idx) => remaining = &remaining[idx + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+fn parse_plugins(raw: &str) -> Result<Vec<ExternalReader>> {
+    raw.split(';')
+        .map(str::trim)
+        .filter(|spec| !spec.is_empty())
+        .map(|spec| {
+            let Some((pattern, command)) = spec.split_once('=') else {
+                bail!("{PLUGINS_ENV_VAR} entry '{spec}' is not pattern=command");
+            };
+            let argv = shlex::split(command.trim())
+                .filter(|argv| !argv.is_empty())
+                .with_context(|| format!("{PLUGINS_ENV_VAR} entry '{spec}' has no command"))?;
+            Ok(ExternalReader {
+                spec: spec.to_string(),
+                pattern: pattern.trim().to_string(),
+                argv,
+            })
+        })
+        .collect()
+}
+
+fn builtin_readers() -> Vec<Box<dyn RunReader>> {
+    vec![Box::new(TfeventsReader), Box::new(MetricsJsonlReader)]
+}
+
+/// Plugins first, so they can take over file names a built-in would claim.
+pub fn registry() -> &'static [Box<dyn RunReader>] {
+    static REGISTRY: OnceLock<Vec<Box<dyn RunReader>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut readers: Vec<Box<dyn RunReader>> = Vec::new();
+        if let Ok(raw) = std::env::var(PLUGINS_ENV_VAR) {
+            match parse_plugins(&raw) {
+                Ok(plugins) => readers.extend(
+                    plugins
+                        .into_iter()
+                        .map(|plugin| Box::new(plugin) as Box<dyn RunReader>),
+                ),
+                Err(e) => eprintln!("warning: ignoring {PLUGINS_ENV_VAR}: {e}"),
+            }
+        }
+        readers.extend(builtin_readers());
+        readers
+    })
+}
+
+pub fn reader_for(path: &Path) -> Option<&'static dyn RunReader> {
+    registry()
+        .iter()
+        .find(|reader| reader.recognizes(path))
+        .map(|reader| reader.as_ref())
+}
+
+/// Whether any registered reader understands `path`.
+pub fn is_run_file(path: &Path) -> bool {
+    reader_for(path).is_some()
+}
+
+/// All points in `path`, read by the first reader that recognizes it.
+#[tracing::instrument(level = "trace", skip_all, fields(file = %path.display()))]
+pub fn read_file(path: &Path) -> Result<(Vec<ScalarEvent>, CorruptionStats)> {
+    let Some(reader) = reader_for(path) else {
+        bail!("no reader for {}", path.display());
+    };
+    let mut events = Vec::new();
+    let stats = reader.stream(path, &mut |event| events.push(event))?;
+    Ok((events, stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RunReader, glob_match, parse_plugins};
+    use std::fs;
+    use std::process::Command;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn glob_matches_file_names() {
+        assert!(glob_match("*.wandb", "run-1.wandb"));
+        assert!(glob_match("log_*.txt", "log_7.txt"));
+        assert!(glob_match("*metrics*", "train.metrics.log"));
+        assert!(glob_match("exact.log", "exact.log"));
+        assert!(!glob_match("*.wandb", "run.wandb.bak"));
+        assert!(!glob_match("a*a", "a"));
+    }
+
+    #[test]
+    fn external_plugin_streams_jsonl_from_its_stdout() {
+        assert!(parse_plugins("*.x").is_err());
+        let plugins = parse_plugins(" *.mylog = cat ; ").expect("parse plugins");
+        assert_eq!(plugins.len(), 1);
+        let plugin = &plugins[0];
+        assert!(plugin.recognizes(std::path::Path::new("runs/a/train.mylog")));
+        assert!(!plugin.recognizes(std::path::Path::new("runs/a/train.log")));
+
+        if Command::new("cat").arg("--version").output().is_err() {
+            eprintln!(
+                "skipping external_plugin_streams_jsonl_from_its_stdout: cat is not installed"
+            );
+            return;
+        }
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("ogtui-readers-{nonce}.mylog"));
+        fs::write(
+            &path,
+            "{\"step\": 1, \"metrics\": {\"loss\": 2.0}}\n{\"step\": 4, \"metrics\": {\"loss\": 1.0, \"acc\": 0.5}}\n",
+        )
+        .expect("write log");
+        let summary = plugin.summarize(&path).expect("summarize through cat");
+        assert_eq!(summary.points, 3);
+        assert_eq!(summary.last_step, Some(4));
+        assert_eq!(
+            summary.tags.iter().map(String::as_str).collect::<Vec<_>>(),
+            vec!["acc", "loss"]
+        );
+        fs::remove_file(&path).ok();
+    }
+}
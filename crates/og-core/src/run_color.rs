@@ -0,0 +1,68 @@
+/// Distinguishable colors assigned to runs (Tableau 10, lightened for dark terminals).
+const PALETTE: &[(u8, u8, u8)] = &[
+    (100, 149, 237), // blue
+    (255, 157, 66),  // orange
+    (240, 98, 98),   // red
+    (118, 200, 188), // teal
+    (95, 195, 95),   // green
+    (237, 201, 72),  // yellow
+    (190, 140, 230), // purple
+    (255, 157, 167), // pink
+    (176, 140, 120), // brown
+    (186, 176, 172), // grey
+];
+
+/// FNV-1a, so the color of a run id never changes between sessions or builds.
+fn stable_hash(input: &str) -> u64 {
+    input.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Color for `run_id`, honouring a `#rrggbb` override from the run metadata.
+pub fn for_run(run_id: &str, override_hex: Option<&str>) -> (u8, u8, u8) {
+    override_hex
+        .and_then(parse_hex)
+        .unwrap_or_else(|| PALETTE[(stable_hash(run_id) % PALETTE.len() as u64) as usize])
+}
+
+pub fn parse_hex(raw: &str) -> Option<(u8, u8, u8)> {
+    let hex = raw.trim().strip_prefix('#').unwrap_or(raw.trim());
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |idx: usize| u8::from_str_radix(&hex[idx..idx + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+pub fn to_hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PALETTE, for_run, parse_hex, to_hex};
+
+    #[test]
+    fn run_colors_are_stable_and_from_palette() {
+        let first = for_run("2024-06-01_frosty-otter-17", None);
+        assert_eq!(first, for_run("2024-06-01_frosty-otter-17", None));
+        assert!(PALETTE.contains(&first));
+    }
+
+    #[test]
+    fn override_wins_when_valid() {
+        assert_eq!(for_run("run-a", Some("#E45756")), (0xe4, 0x57, 0x56));
+        assert_eq!(
+            for_run("run-a", Some("not-a-color")),
+            for_run("run-a", None)
+        );
+    }
+
+    #[test]
+    fn hex_round_trips() {
+        assert_eq!(parse_hex("ff8800"), Some((255, 136, 0)));
+        assert_eq!(to_hex((255, 136, 0)), "#ff8800");
+        assert_eq!(parse_hex("#fff"), None);
+    }
+}
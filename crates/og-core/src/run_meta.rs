@@ -0,0 +1,733 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::error::OgError;
+use crate::git_state::GitState;
+
+/// File name of the per-run metadata sidecar, stored next to the event files.
+pub const META_FILE_NAME: &str = "og_meta.json";
+
+/// Advisory lock file held by every writer around a read-modify-write of
+/// `og_meta.json` (og, ogd and the Python agent take the same lock).
+pub const LOCK_FILE_NAME: &str = "og_meta.json.lock";
+
+/// Environment variable carrying a run's recorded config (JSON object) to the training process.
+pub const CONFIG_ENV_VAR: &str = "OG_RUN_CONFIG";
+
+/// What produced an annotation on the metric timeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnotationKind {
+    Note,
+    Restart,
+    LrChange,
+    /// A live-tunable parameter changed with `og set`.
+    Hparam,
+}
+
+impl AnnotationKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            AnnotationKind::Note => "note",
+            AnnotationKind::Restart => "restart",
+            AnnotationKind::LrChange => "lr change",
+            AnnotationKind::Hparam => "hparam",
+        }
+    }
+}
+
+/// A labelled marker at a given step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub step: i64,
+    pub kind: AnnotationKind,
+    pub message: String,
+    pub created_unix: u64,
+}
+
+/// A timestamped journal entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Note {
+    pub created_unix: u64,
+    /// Latest step of the run when the note was written, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<i64>,
+    pub text: String,
+}
+
+/// How a run was launched, recorded so it can be forked and relaunched.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LaunchSpec {
+    pub training_file: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub training_cmd: Option<String>,
+    pub codebase_root: PathBuf,
+    /// Socket of the daemon supervising the run, so a TUI reopening the run
+    /// finds it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socket: Option<PathBuf>,
+}
+
+/// Format an imported run was converted from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportKind {
+    Csv,
+    Tensorboard,
+    Bundle,
+}
+
+/// Where an imported run's metrics came from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportSource {
+    pub kind: ImportKind,
+    pub path: PathBuf,
+    pub imported_unix: u64,
+}
+
+/// What happened when the objective metric stopped improving.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EarlyStopAction {
+    /// An alert was raised; the run kept going.
+    Suggested,
+    /// Autonomous mode asked the daemon to stop training.
+    StopRequested,
+}
+
+/// Early-stop decision taken when the objective metric plateaued.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EarlyStop {
+    pub metric: String,
+    pub action: EarlyStopAction,
+    /// Step at which the plateau was detected.
+    pub step: i64,
+    pub best: f64,
+    pub best_step: i64,
+    pub patience: usize,
+    pub min_delta: f64,
+    pub decided_unix: u64,
+}
+
+/// Liveness state a writer or supervisor records for a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunState {
+    Running,
+    Finished,
+    Crashed,
+    Killed,
+}
+
+/// A `running` heartbeat older than this no longer says anything: the writer
+/// died without recording how the run ended.
+pub const HEARTBEAT_STALE_SECS: u64 = 300;
+
+/// Heartbeat and final state written by the metrics writer or the daemon
+/// supervising training.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunHeartbeat {
+    pub state: RunState,
+    /// When the state was last written.
+    pub heartbeat_unix: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// Why the run stopped, for runs that did not finish on their own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl RunHeartbeat {
+    /// Run status label, or None when a `running` heartbeat has gone stale
+    /// and file times have to decide.
+    pub fn status(&self, now_unix: u64) -> Option<&'static str> {
+        match self.state {
+            RunState::Running
+                if now_unix.saturating_sub(self.heartbeat_unix) <= HEARTBEAT_STALE_SECS =>
+            {
+                Some("running")
+            }
+            RunState::Running => None,
+            RunState::Finished => Some("finished"),
+            RunState::Crashed => Some("crashed"),
+            RunState::Killed => Some("killed"),
+        }
+    }
+}
+
+/// SLURM allocation a run was started in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlurmJob {
+    /// `SLURM_JOB_ID`, or `<array job>_<task>` for array tasks.
+    pub job_id: String,
+    /// Compressed node list, e.g. `gpu[01-04]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_list: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partition: Option<String>,
+}
+
+impl SlurmJob {
+    /// The job from `SLURM_*` variables read with `var`, or None outside SLURM.
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let first = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| var(name).filter(|value| !value.is_empty()))
+        };
+        let job_id = match (
+            first(&["SLURM_ARRAY_JOB_ID"]),
+            first(&["SLURM_ARRAY_TASK_ID"]),
+        ) {
+            (Some(array), Some(task)) => format!("{array}_{task}"),
+            _ => first(&["SLURM_JOB_ID", "SLURM_JOBID"])?,
+        };
+        Some(Self {
+            job_id,
+            node_list: first(&["SLURM_JOB_NODELIST", "SLURM_NODELIST"]),
+            partition: first(&["SLURM_JOB_PARTITION"]),
+        })
+    }
+
+    /// The job this process runs in, from its environment.
+    pub fn from_env() -> Option<Self> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// Whether `id` names this job: its own id, or the array job it belongs to.
+    pub fn matches(&self, id: &str) -> bool {
+        self.job_id == id
+            || self
+                .job_id
+                .split_once('_')
+                .is_some_and(|(array, _)| array == id)
+    }
+}
+
+/// Kubernetes pod a run was started in.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct K8sPod {
+    pub namespace: String,
+    pub pod: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    /// Resource requests of the training container, as quantities (`cpu: 4`, `memory: 16Gi`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub requests: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub limits: BTreeMap<String, String>,
+}
+
+/// Axis scale a metric reads best on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Scale {
+    Linear,
+    Log,
+}
+
+/// How a metric's card in the TUI grid is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Card {
+    Line,
+    /// The latest value as a bar between the metric's bounds
+    Gauge,
+}
+
+/// Display hints for one metric, set with `og describe` or the writer API.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricAttrs {
+    /// Unit of the values, e.g. `%`, `s` or `tok/s`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    /// Whether larger values are better; decides best-value arrows and
+    /// leaderboard order.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub higher_is_better: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<Scale>,
+    /// Name shown instead of the tag.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Lowest and highest value the metric can take, e.g. `[0, 1]` for accuracy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bounds: Option<[f64; 2]>,
+    /// Card style; without one, bounded metrics get a gauge.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub card: Option<Card>,
+}
+
+impl MetricAttrs {
+    /// Take every attribute `other` sets.
+    pub fn merge(&mut self, other: MetricAttrs) {
+        if other.unit.is_some() {
+            self.unit = other.unit;
+        }
+        if other.higher_is_better.is_some() {
+            self.higher_is_better = other.higher_is_better;
+        }
+        if other.scale.is_some() {
+            self.scale = other.scale;
+        }
+        if other.display_name.is_some() {
+            self.display_name = other.display_name;
+        }
+        if other.bounds.is_some() {
+            self.bounds = other.bounds;
+        }
+        if other.card.is_some() {
+            self.card = other.card;
+        }
+    }
+}
+
+/// Metadata recorded alongside a run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunMeta {
+    /// Run id this run was forked from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forked_from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launch: Option<LaunchSpec>,
+    /// Set for runs created by `og import`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imported_from: Option<ImportSource>,
+    /// Source tree state at launch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitState>,
+    /// `#rrggbb` color overriding the one hashed from the run id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Hyperparameters set with `og fork --set key=value`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub config: BTreeMap<String, String>,
+    /// Labels set with `og tag`; `og gc --older-than` never deletes a tagged run.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<Annotation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<Note>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub early_stop: Option<EarlyStop>,
+    /// GPUs visible to the training process at launch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpu_count: Option<u32>,
+    /// SLURM job the run was launched in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slurm: Option<SlurmJob>,
+    /// Kubernetes pod the run was launched in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub k8s: Option<K8sPod>,
+    /// Per-metric display hints, by tag.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metrics: BTreeMap<String, MetricAttrs>,
+    /// Last heartbeat or final state, preferred over file times.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<RunHeartbeat>,
+    /// Fields this version does not know, written back unchanged so a newer
+    /// writer's data survives an older og.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl RunMeta {
+    /// Insert an annotation keeping the list ordered by step.
+    pub fn add_annotation(&mut self, annotation: Annotation) {
+        let idx = self
+            .annotations
+            .partition_point(|existing| existing.step <= annotation.step);
+        self.annotations.insert(idx, annotation);
+    }
+
+    /// Metadata for a new run forked from this one: config, launch spec and metric
+    /// attributes carry over (with `overrides` applied), annotations and notes start empty.
+    pub fn fork(&self, source_id: &str, overrides: &BTreeMap<String, String>) -> RunMeta {
+        let mut config = self.config.clone();
+        config.extend(overrides.iter().map(|(k, v)| (k.clone(), v.clone())));
+        RunMeta {
+            forked_from: Some(source_id.to_string()),
+            // The fork gets a daemon of its own when launched.
+            launch: self.launch.clone().map(|launch| LaunchSpec {
+                socket: None,
+                ..launch
+            }),
+            config,
+            metrics: self.metrics.clone(),
+            ..RunMeta::default()
+        }
+    }
+
+    /// Record where an imported run came from. Nothing writes to the copy, so
+    /// one without a final state (a CSV, or a bundle taken mid-run) is marked
+    /// finished rather than left to look running from fresh file times.
+    pub fn record_import(&mut self, source: ImportSource) {
+        if self
+            .status
+            .as_ref()
+            .is_none_or(|status| status.state == RunState::Running)
+        {
+            self.status = Some(RunHeartbeat {
+                state: RunState::Finished,
+                heartbeat_unix: source.imported_unix,
+                pid: None,
+                exit_code: None,
+                reason: None,
+            });
+        }
+        self.imported_from = Some(source);
+    }
+
+    /// Note that og stopped the run for `reason`. A run still marked running
+    /// (its supervisor never got to write an end) becomes killed; one the
+    /// supervisor marked killed takes this reason, which says more than the
+    /// signal it got. Finished and crashed runs keep their end.
+    pub fn record_stop(&mut self, reason: &str, now_unix: u64) {
+        match &mut self.status {
+            Some(status) if status.state == RunState::Killed => {
+                status.reason = Some(reason.to_string());
+            }
+            Some(status) if status.state != RunState::Running => {}
+            _ => {
+                self.status = Some(RunHeartbeat {
+                    state: RunState::Killed,
+                    heartbeat_unix: now_unix,
+                    pid: self.status.as_ref().and_then(|status| status.pid),
+                    exit_code: None,
+                    reason: Some(reason.to_string()),
+                });
+            }
+        }
+    }
+}
+
+/// Directory that owns the metadata for `path` (a run dir or a single event file).
+pub fn run_dir_for(path: &Path) -> PathBuf {
+    if path.is_file() {
+        path.parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."))
+    } else {
+        path.to_path_buf()
+    }
+}
+
+pub fn meta_path(run_dir: &Path) -> PathBuf {
+    run_dir_for(run_dir).join(META_FILE_NAME)
+}
+
+/// Load run metadata; a missing sidecar yields empty metadata.
+pub fn load(run_dir: &Path) -> Result<RunMeta> {
+    let path = meta_path(run_dir);
+    if !path.exists() {
+        return Ok(RunMeta::default());
+    }
+    let raw = fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+    serde_json::from_str(&raw).with_context(|| OgError::parse_failed(path.display()))
+}
+
+/// Persist run metadata atomically (write to a temp file, then rename).
+pub fn save(run_dir: &Path, meta: &RunMeta) -> Result<()> {
+    let path = meta_path(run_dir);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("creating run directory {}", parent.display()))?;
+    }
+    let tmp = tmp_path(&path);
+    let body = serde_json::to_string_pretty(meta)?;
+    fs::write(&tmp, body).with_context(|| format!("writing {}", tmp.display()))?;
+    if let Err(err) = fs::rename(&tmp, &path) {
+        let _ = fs::remove_file(&tmp);
+        return Err(err).with_context(|| format!("writing {}", path.display()));
+    }
+    Ok(())
+}
+
+/// Temp file for one write of `path`, unique per process and write so two
+/// writers never rename each other's half-written file into place.
+pub fn tmp_path(path: &Path) -> PathBuf {
+    static WRITES: AtomicU64 = AtomicU64::new(0);
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    path.with_file_name(name)
+}
+
+/// Take the advisory lock on a run's metadata, blocking until other writers
+/// release it. Held until the returned file is dropped.
+pub fn lock(run_dir: &Path) -> Result<fs::File> {
+    let path = meta_path(run_dir).with_file_name(LOCK_FILE_NAME);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("creating run directory {}", parent.display()))?;
+    }
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("opening {}", path.display()))?;
+    file.lock()
+        .with_context(|| format!("locking {}", path.display()))?;
+    Ok(file)
+}
+
+/// Load, mutate and save the metadata for a run in one step, holding the
+/// metadata lock so concurrent writers don't lose each other's changes.
+pub fn update<T>(run_dir: &Path, f: impl FnOnce(&mut RunMeta) -> T) -> Result<T> {
+    let _lock = lock(run_dir)?;
+    let mut meta = load(run_dir)?;
+    let out = f(&mut meta);
+    save(run_dir, &meta)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Annotation, AnnotationKind, HEARTBEAT_STALE_SECS, ImportKind, ImportSource, LaunchSpec,
+        MetricAttrs, Note, RunHeartbeat, RunMeta, RunState, Scale, SlurmJob, load, meta_path,
+        update,
+    };
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn annotation(step: i64, message: &str) -> Annotation {
+        Annotation {
+            step,
+            kind: AnnotationKind::Note,
+            message: message.to_string(),
+            created_unix: 0,
+        }
+    }
+
+    #[test]
+    fn add_annotation_keeps_step_order() {
+        let mut meta = RunMeta::default();
+        meta.add_annotation(annotation(30, "c"));
+        meta.add_annotation(annotation(10, "a"));
+        meta.add_annotation(annotation(20, "b"));
+        let steps: Vec<i64> = meta.annotations.iter().map(|a| a.step).collect();
+        assert_eq!(steps, vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn fork_applies_overrides_and_drops_journal() {
+        let mut source = RunMeta::default();
+        source.config.insert("lr".to_string(), "3e-4".to_string());
+        source
+            .config
+            .insert("batch_size".to_string(), "32".to_string());
+        source.add_annotation(annotation(10, "warmup done"));
+        source.notes.push(Note {
+            created_unix: 0,
+            step: None,
+            text: "baseline".to_string(),
+        });
+        source.launch = Some(LaunchSpec {
+            training_file: PathBuf::from("train.py"),
+            training_cmd: None,
+            codebase_root: PathBuf::from("."),
+            socket: Some(PathBuf::from("/tmp/opengraphs-ogd-run-a-0.sock")),
+        });
+
+        let overrides = BTreeMap::from([("lr".to_string(), "1e-4".to_string())]);
+        let forked = source.fork("run-a", &overrides);
+
+        assert_eq!(forked.forked_from.as_deref(), Some("run-a"));
+        assert_eq!(forked.config["lr"], "1e-4");
+        assert_eq!(forked.config["batch_size"], "32");
+        assert!(forked.annotations.is_empty());
+        assert!(forked.notes.is_empty());
+        let launch = forked.launch.expect("launch carries over");
+        assert_eq!(launch.training_file, PathBuf::from("train.py"));
+        assert_eq!(launch.socket, None, "the fork needs a daemon of its own");
+    }
+
+    #[test]
+    fn heartbeat_status_ends_or_goes_stale() {
+        // As written by ogd's MetricsWriter and the daemon.
+        let meta: RunMeta = serde_json::from_str(
+            r#"{"status": {"state": "running", "heartbeat_unix": 1000, "pid": 42}}"#,
+        )
+        .expect("parse");
+        let heartbeat = meta.status.expect("status");
+        assert_eq!(heartbeat.pid, Some(42));
+        assert_eq!(heartbeat.status(1000 + 60), Some("running"));
+        assert_eq!(heartbeat.status(1000 + HEARTBEAT_STALE_SECS + 1), None);
+
+        let ended = RunHeartbeat {
+            state: RunState::Crashed,
+            exit_code: Some(1),
+            ..heartbeat
+        };
+        assert_eq!(ended.status(u64::MAX), Some("crashed"));
+    }
+
+    #[test]
+    fn record_stop_marks_unended_runs_killed() {
+        let mut meta: RunMeta = serde_json::from_str(
+            r#"{"status": {"state": "running", "heartbeat_unix": 1000, "pid": 42}}"#,
+        )
+        .expect("parse");
+        meta.record_stop("quit from the TUI", 1100);
+        let status = meta.status.as_ref().expect("status");
+        assert_eq!(status.state, RunState::Killed);
+        assert_eq!((status.heartbeat_unix, status.pid), (1100, Some(42)));
+        assert_eq!(status.reason.as_deref(), Some("quit from the TUI"));
+
+        // The supervisor's "received SIGTERM" gives way to why og sent it.
+        meta.status.as_mut().expect("status").reason = Some("daemon received SIGTERM".into());
+        meta.record_stop("TUI received SIGHUP", 1200);
+        let status = meta.status.as_ref().expect("status");
+        assert_eq!(status.reason.as_deref(), Some("TUI received SIGHUP"));
+        assert_eq!(status.heartbeat_unix, 1100);
+
+        let mut finished: RunMeta =
+            serde_json::from_str(r#"{"status": {"state": "finished", "heartbeat_unix": 1000}}"#)
+                .expect("parse");
+        finished.record_stop("quit from the TUI", 1100);
+        assert_eq!(finished.status.expect("status").reason, None);
+    }
+
+    #[test]
+    fn record_import_finishes_runs_without_an_end() {
+        let source = ImportSource {
+            kind: ImportKind::Bundle,
+            path: PathBuf::from("run-a.tar.zst"),
+            imported_unix: 2000,
+        };
+        let mut mid_run: RunMeta = serde_json::from_str(
+            r#"{"status": {"state": "running", "heartbeat_unix": 1000, "pid": 42}}"#,
+        )
+        .expect("parse");
+        mid_run.record_import(source.clone());
+        let status = mid_run.status.as_ref().expect("status");
+        assert_eq!(
+            (status.state, status.heartbeat_unix),
+            (RunState::Finished, 2000)
+        );
+        assert_eq!(mid_run.imported_from, Some(source.clone()));
+
+        let mut crashed: RunMeta =
+            serde_json::from_str(r#"{"status": {"state": "crashed", "heartbeat_unix": 1000}}"#)
+                .expect("parse");
+        crashed.record_import(source);
+        assert_eq!(crashed.status.expect("status").state, RunState::Crashed);
+    }
+
+    #[test]
+    fn update_round_trips_through_sidecar() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("ogtui-run-meta-{nonce}"));
+        fs::create_dir_all(&dir).expect("create run dir");
+
+        assert_eq!(load(&dir).expect("load empty"), RunMeta::default());
+        update(&dir, |meta| meta.add_annotation(annotation(5, "restart"))).expect("update meta");
+        let loaded = load(&dir).expect("load meta");
+        assert_eq!(loaded.annotations, vec![annotation(5, "restart")]);
+
+        // A field a newer writer added survives, and concurrent updates all land.
+        let raw = fs::read_to_string(meta_path(&dir)).expect("read meta");
+        let mut fields: serde_json::Value = serde_json::from_str(&raw).expect("parse meta");
+        fields["sweep"] = serde_json::json!({"id": "s1"});
+        fs::write(meta_path(&dir), fields.to_string()).expect("write meta");
+        std::thread::scope(|scope| {
+            for step in 0..8 {
+                let dir = &dir;
+                scope.spawn(move || {
+                    update(dir, |meta| {
+                        meta.add_annotation(annotation(step, "concurrent"))
+                    })
+                    .expect("update meta")
+                });
+            }
+        });
+        let loaded = load(&dir).expect("load meta");
+        assert_eq!(loaded.annotations.len(), 9);
+        assert_eq!(loaded.extra["sweep"], serde_json::json!({"id": "s1"}));
+        let leftovers = fs::read_dir(&dir)
+            .expect("read run dir")
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+            .count();
+        assert_eq!(leftovers, 0);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn metric_attrs_merge_and_round_trip() {
+        let mut attrs = MetricAttrs {
+            unit: Some("%".to_string()),
+            higher_is_better: Some(true),
+            ..MetricAttrs::default()
+        };
+        attrs.merge(MetricAttrs {
+            scale: Some(Scale::Log),
+            display_name: Some("Val accuracy".to_string()),
+            ..MetricAttrs::default()
+        });
+        assert_eq!(attrs.unit.as_deref(), Some("%"));
+        assert_eq!(attrs.scale, Some(Scale::Log));
+
+        let mut meta = RunMeta::default();
+        meta.metrics.insert("val/acc".to_string(), attrs.clone());
+        let json = serde_json::to_string(&meta).expect("serialize");
+        assert_eq!(
+            json,
+            r#"{"metrics":{"val/acc":{"unit":"%","higher_is_better":true,"scale":"log","display_name":"Val accuracy"}}}"#
+        );
+        let parsed: RunMeta = serde_json::from_str(&json).expect("parse");
+        assert_eq!(parsed.metrics["val/acc"], attrs);
+        assert_eq!(parsed.fork("run-a", &BTreeMap::new()).metrics, meta.metrics);
+    }
+
+    #[test]
+    fn slurm_job_reads_the_allocation_from_env_vars() {
+        let env = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                pairs
+                    .iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        assert_eq!(SlurmJob::from_vars(env(&[("HOME", "/root")])), None);
+
+        let job = SlurmJob::from_vars(env(&[
+            ("SLURM_JOBID", "4242"),
+            ("SLURM_NODELIST", "gpu[01-04]"),
+            ("SLURM_JOB_PARTITION", "a100"),
+        ]))
+        .expect("job");
+        assert_eq!(job.job_id, "4242");
+        assert_eq!(job.node_list.as_deref(), Some("gpu[01-04]"));
+        assert_eq!(job.partition.as_deref(), Some("a100"));
+        assert!(job.matches("4242") && !job.matches("424"));
+
+        let task = SlurmJob::from_vars(env(&[
+            ("SLURM_JOB_ID", "4300"),
+            ("SLURM_ARRAY_JOB_ID", "4242"),
+            ("SLURM_ARRAY_TASK_ID", "3"),
+        ]))
+        .expect("array task");
+        assert_eq!(task.job_id, "4242_3");
+        assert!(task.matches("4242_3") && task.matches("4242"));
+    }
+}
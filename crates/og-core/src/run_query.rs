@@ -0,0 +1,190 @@
+use anyhow::{Result, bail};
+use std::collections::BTreeMap;
+
+/// Comparison operator in a `key<op>value` predicate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// Case-insensitive substring match (`~`).
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Predicate {
+    pub key: String,
+    pub op: Op,
+    pub value: String,
+}
+
+/// Predicates joined by `and` / `or`; `and` binds tighter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    any_of: Vec<Vec<Predicate>>,
+}
+
+impl Query {
+    /// Keys referenced by the query, in first-use order.
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = Vec::new();
+        for predicate in self.any_of.iter().flatten() {
+            if !keys.contains(&predicate.key) {
+                keys.push(predicate.key.clone());
+            }
+        }
+        keys
+    }
+
+    pub fn matches(&self, fields: &BTreeMap<String, String>) -> bool {
+        self.any_of
+            .iter()
+            .any(|all_of| all_of.iter().all(|predicate| predicate.matches(fields)))
+    }
+}
+
+impl Predicate {
+    pub fn matches(&self, fields: &BTreeMap<String, String>) -> bool {
+        let Some(actual) = fields.get(&self.key) else {
+            return false;
+        };
+        let numbers = actual
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .zip(self.value.parse::<f64>().ok());
+        match self.op {
+            Op::Eq => match numbers {
+                Some((a, b)) => a == b,
+                None => actual == &self.value,
+            },
+            Op::Ne => match numbers {
+                Some((a, b)) => a != b,
+                None => actual != &self.value,
+            },
+            Op::Lt => numbers.is_some_and(|(a, b)| a < b),
+            Op::Le => numbers.is_some_and(|(a, b)| a <= b),
+            Op::Gt => numbers.is_some_and(|(a, b)| a > b),
+            Op::Ge => numbers.is_some_and(|(a, b)| a >= b),
+            Op::Contains => actual
+                .to_ascii_lowercase()
+                .contains(&self.value.to_ascii_lowercase()),
+        }
+    }
+}
+
+/// Parse `lr<1e-3 and optimizer=adamw or tag~sweep`.
+pub fn parse(raw: &str) -> Result<Query> {
+    let Some(words) = shlex::split(raw) else {
+        bail!("unbalanced quotes in query '{}'", raw);
+    };
+    let mut any_of = Vec::new();
+    let mut all_of = Vec::new();
+    let mut clause: Vec<String> = Vec::new();
+    for word in words {
+        let connective = word.to_ascii_lowercase();
+        if connective == "and" || connective == "or" || word == "&&" || word == "||" {
+            all_of.push(parse_predicate(&clause.join(" "))?);
+            clause.clear();
+            if connective == "or" || word == "||" {
+                any_of.push(std::mem::take(&mut all_of));
+            }
+        } else {
+            clause.push(word);
+        }
+    }
+    all_of.push(parse_predicate(&clause.join(" "))?);
+    any_of.push(all_of);
+    Ok(Query { any_of })
+}
+
+/// Parse a single `key<op>value` clause.
+pub fn parse_predicate(raw: &str) -> Result<Predicate> {
+    let Some(pos) = raw.find(['=', '!', '<', '>', '~']) else {
+        bail!("expected key<op>value in '{}'", raw);
+    };
+    let rest = &raw[pos..];
+    let (op, op_len) = if rest.starts_with("==") {
+        (Op::Eq, 2)
+    } else if rest.starts_with("!=") {
+        (Op::Ne, 2)
+    } else if rest.starts_with("<=") {
+        (Op::Le, 2)
+    } else if rest.starts_with(">=") {
+        (Op::Ge, 2)
+    } else if rest.starts_with('=') {
+        (Op::Eq, 1)
+    } else if rest.starts_with('<') {
+        (Op::Lt, 1)
+    } else if rest.starts_with('>') {
+        (Op::Gt, 1)
+    } else if rest.starts_with('~') {
+        (Op::Contains, 1)
+    } else {
+        bail!("unknown operator in '{}'", raw);
+    };
+    let key = raw[..pos].trim();
+    let value = rest[op_len..].trim();
+    if key.is_empty() || value.is_empty() {
+        bail!("expected key<op>value in '{}'", raw);
+    }
+    Ok(Predicate {
+        key: key.to_string(),
+        op,
+        value: value.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Op, parse, parse_predicate};
+    use std::collections::BTreeMap;
+
+    fn fields(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_predicate_reads_two_char_operators() {
+        let predicate = parse_predicate("batch_size >= 64").expect("parse");
+        assert_eq!(predicate.key, "batch_size");
+        assert_eq!(predicate.op, Op::Ge);
+        assert_eq!(predicate.value, "64");
+        assert!(parse_predicate("lr").is_err());
+        assert!(parse_predicate("=3").is_err());
+    }
+
+    #[test]
+    fn query_compares_numbers_numerically() {
+        let query = parse("lr<1e-3 and optimizer=adamw").expect("parse");
+        assert_eq!(query.keys(), vec!["lr", "optimizer"]);
+        assert!(query.matches(&fields(&[("lr", "3e-4"), ("optimizer", "adamw")])));
+        assert!(!query.matches(&fields(&[("lr", "0.01"), ("optimizer", "adamw")])));
+        assert!(!query.matches(&fields(&[("lr", "3e-4")])));
+        assert!(
+            parse("lr=0.0003")
+                .expect("parse")
+                .matches(&fields(&[("lr", "3e-4")]))
+        );
+    }
+
+    #[test]
+    fn or_binds_looser_than_and() {
+        let query = parse("optimizer=sgd or lr<1e-3 and warmup>0").expect("parse");
+        assert!(query.matches(&fields(&[("optimizer", "sgd")])));
+        assert!(query.matches(&fields(&[("lr", "1e-4"), ("warmup", "100")])));
+        assert!(!query.matches(&fields(&[("lr", "1e-4"), ("warmup", "0")])));
+    }
+
+    #[test]
+    fn contains_is_case_insensitive_and_quotes_group_values() {
+        let query = parse("note~'LR Sweep'").expect("parse");
+        assert!(query.matches(&fields(&[("note", "part of the lr sweep")])));
+    }
+}
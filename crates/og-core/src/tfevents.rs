@@ -0,0 +1,539 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::OgError;
+pub use crate::tfrecord::CorruptionStats;
+use crate::tfrecord::{Event, decode_records};
+
+// ── Public types ────────────────────────────────────────────────────────────
+
+/// A parsed scalar event.
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct ScalarEvent {
+    pub tag: String,
+    pub step: i64,
+    pub wall_time: f64,
+    pub value: f64,
+}
+
+/// Tag -> `(step, value)` series.
+pub type SeriesByTag = BTreeMap<String, Vec<(f64, f64)>>;
+
+/// Parsed run payload used by the TUI.
+#[derive(Debug, Clone)]
+pub struct LoadedRun {
+    /// Logical view: for multi-rank runs, training tags are deduplicated and
+    /// system tags are replaced by their cross-rank mean/max.
+    pub scalars: SeriesByTag,
+    pub events: Vec<ScalarEvent>,
+    /// Per-rank series; empty unless event files from two or more ranks were found.
+    pub ranks: BTreeMap<u32, SeriesByTag>,
+    /// Latest event written by each rank (same keys as `ranks`).
+    pub rank_progress: BTreeMap<u32, RankProgress>,
+    /// Damage the resyncing reader worked around while loading.
+    pub corruption: CorruptionStats,
+}
+
+/// How far a single rank has got, used to spot ranks that stopped writing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RankProgress {
+    pub last_step: i64,
+    pub last_wall_time: f64,
+}
+
+impl RankProgress {
+    fn of(events: &[ScalarEvent]) -> Self {
+        RankProgress {
+            last_step: events.iter().map(|e| e.step).max().unwrap_or(0),
+            last_wall_time: events
+                .iter()
+                .map(|e| e.wall_time)
+                .fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+impl LoadedRun {
+    /// Series for one rank, or the logical view when `rank` is `None` or unknown.
+    pub fn view(&self, rank: Option<u32>) -> &SeriesByTag {
+        rank.and_then(|rank| self.ranks.get(&rank))
+            .unwrap_or(&self.scalars)
+    }
+}
+
+/// Suffixes of the aggregated series built for system tags in multi-rank runs.
+pub const RANK_MEAN_SUFFIX: &str = " (rank mean)";
+pub const RANK_MAX_SUFFIX: &str = " (rank max)";
+
+/// Return true only for likely TensorBoard event files.
+pub fn is_tfevents_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|s| s.to_str()) else {
+        return false;
+    };
+    is_tfevents_filename(name)
+}
+
+fn is_tfevents_filename(name: &str) -> bool {
+    let lower = name.to_ascii_lowercase();
+    if lower.ends_with(".tgz") || lower.ends_with(".zip") {
+        return false;
+    }
+    // Single compressed event files are read transparently; tarballs are not.
+    let lower = crate::compression::strip_extension(&lower);
+    if lower.ends_with(".tar") {
+        return false;
+    }
+    lower.contains(".tfevents.") || lower.ends_with(".tfevents")
+}
+
+fn push_scalars(event: Event, events: &mut Vec<ScalarEvent>) {
+    if let Some(summary) = event.summary {
+        for val in summary.value {
+            if let Some(value) = val.scalar() {
+                events.push(ScalarEvent {
+                    tag: val.tag,
+                    step: event.step,
+                    wall_time: event.wall_time,
+                    value,
+                });
+            }
+        }
+    }
+}
+
+/// Scalar events in already-read (decompressed) event file bytes.
+pub fn parse_events_bytes(
+    bytes: &[u8],
+    resync: bool,
+) -> Result<(Vec<ScalarEvent>, CorruptionStats)> {
+    let (records, stats) = decode_records(bytes, resync)?;
+    let mut events = Vec::new();
+    for (_, event) in records {
+        push_scalars(event, &mut events);
+    }
+    Ok((events, stats))
+}
+
+// ── Public API ──────────────────────────────────────────────────────────────
+
+/// Parse all scalar events from a single `.tfevents` file (optionally `.gz` /
+/// `.zst` compressed), resyncing past damaged records instead of giving up on
+/// the rest of the file.
+pub fn parse_tfevents_file(path: &Path) -> Result<(Vec<ScalarEvent>, CorruptionStats)> {
+    let bytes = crate::compression::read(path)?;
+    parse_events_bytes(&bytes, true)
+}
+
+pub fn discover_event_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if path.is_file() {
+        if crate::readers::is_run_file(path) {
+            return Ok(vec![path.to_path_buf()]);
+        }
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+    for entry in walkdir(path)? {
+        if crate::readers::is_run_file(&entry) {
+            files.push(entry);
+        }
+    }
+    Ok(files)
+}
+
+/// Rank encoded in a path component such as `rank0`, `rank_3` or `rank-12`
+/// (the layouts torchrun launchers commonly write per-rank event files to).
+pub fn rank_from_path(path: &Path) -> Option<u32> {
+    path.components()
+        .rev()
+        .find_map(|component| rank_in_text(component.as_os_str().to_str()?))
+}
+
+/// First `rank<sep>N` mention in `text` (case-insensitive; `rank 3`, `Rank=3`, `rank_3`).
+pub fn rank_in_text(text: &str) -> Option<u32> {
+    let lower = text.to_ascii_lowercase();
+    lower.match_indices("rank").find_map(|(idx, _)| {
+        if idx > 0 && lower.as_bytes()[idx - 1].is_ascii_alphanumeric() {
+            return None;
+        }
+        let rest = lower[idx + 4..].trim_start_matches(['_', '-', ' ', '=', ':']);
+        let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+        digits.parse().ok()
+    })
+}
+
+/// Events grouped by the rank of the file they came from (`None`: no rank in the path).
+type EventsByRank = BTreeMap<Option<u32>, Vec<ScalarEvent>>;
+
+fn load_events(path: &Path) -> Result<(EventsByRank, CorruptionStats)> {
+    let mut by_rank = EventsByRank::new();
+    let mut corruption = CorruptionStats::default();
+    let files = discover_event_files(path)?;
+    let mut unreadable = Vec::new();
+    for entry in &files {
+        let relative = entry.strip_prefix(path).unwrap_or(entry);
+        match crate::readers::read_file(entry) {
+            Ok((evts, stats)) => {
+                corruption.merge(stats);
+                by_rank
+                    .entry(rank_from_path(relative))
+                    .or_default()
+                    .extend(evts);
+            }
+            Err(e) => {
+                tracing::warn!(file = %entry.display(), "skipping unreadable file: {e:#}");
+                eprintln!("warning: skipping {}: {e}", entry.display());
+                unreadable.push(format!("{}: {e}", relative.display()));
+            }
+        }
+    }
+    if !files.is_empty() && unreadable.len() == files.len() {
+        return Err(OgError::CorruptEvents {
+            path: path.to_path_buf(),
+            files: files.len(),
+            detail: unreadable.swap_remove(0),
+        }
+        .into());
+    }
+    Ok((by_rank, corruption))
+}
+
+fn build_series<'a>(events: impl IntoIterator<Item = &'a ScalarEvent>) -> SeriesByTag {
+    let mut scalars: SeriesByTag = BTreeMap::new();
+    for ev in events {
+        scalars
+            .entry(ev.tag.clone())
+            .or_default()
+            .push((ev.step as f64, ev.value));
+    }
+
+    // Sort each series by step
+    for series in scalars.values_mut() {
+        series.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    }
+    scalars
+}
+
+/// Fold per-rank series into one logical run. Training tags logged by several
+/// ranks are taken from the lowest rank; system tags become mean and max
+/// series across ranks, aligned on step.
+pub fn merge_ranks(ranks: &BTreeMap<u32, SeriesByTag>) -> SeriesByTag {
+    let mut merged: SeriesByTag = BTreeMap::new();
+    let mut system: BTreeMap<String, BTreeMap<i64, Vec<f64>>> = BTreeMap::new();
+    for series_by_tag in ranks.values() {
+        for (tag, series) in series_by_tag {
+            if crate::looks_system_metric(tag) {
+                let by_step = system.entry(tag.clone()).or_default();
+                for (step, value) in series {
+                    by_step.entry(*step as i64).or_default().push(*value);
+                }
+            } else if !merged.contains_key(tag) {
+                merged.insert(tag.clone(), series.clone());
+            }
+        }
+    }
+    for (tag, by_step) in system {
+        let mut mean = Vec::with_capacity(by_step.len());
+        let mut max = Vec::with_capacity(by_step.len());
+        for (step, values) in by_step {
+            mean.push((
+                step as f64,
+                values.iter().sum::<f64>() / values.len() as f64,
+            ));
+            max.push((
+                step as f64,
+                values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            ));
+        }
+        merged.insert(format!("{tag}{RANK_MEAN_SUFFIX}"), mean);
+        merged.insert(format!("{tag}{RANK_MAX_SUFFIX}"), max);
+    }
+    merged
+}
+
+/// Add synthetic series computed from the raw events, unless the run logged
+/// a metric of the same name itself.
+fn add_derived_series<'a>(
+    scalars: &mut SeriesByTag,
+    events: impl IntoIterator<Item = &'a ScalarEvent>,
+) {
+    if !scalars.contains_key(crate::throughput::TAG)
+        && let Some(series) = crate::throughput::derive(events)
+    {
+        scalars.insert(crate::throughput::TAG.to_string(), series);
+    }
+}
+
+/// Discover event data under `path` (any file a registered reader recognizes) and build both scalar series and raw events.
+#[tracing::instrument(name = "parse", level = "debug", skip_all, fields(path = %path.display()))]
+pub fn load_run(path: &Path) -> Result<LoadedRun> {
+    let (mut by_rank, corruption) = load_events(path)?;
+    let rank_count = by_rank.keys().filter(|rank| rank.is_some()).count();
+
+    if rank_count < 2 {
+        let events: Vec<ScalarEvent> = by_rank.into_values().flatten().collect();
+        let mut scalars = build_series(&events);
+        add_derived_series(&mut scalars, &events);
+        return Ok(LoadedRun {
+            scalars,
+            events,
+            ranks: BTreeMap::new(),
+            rank_progress: BTreeMap::new(),
+            corruption,
+        });
+    }
+
+    // Files outside any rank directory (e.g. launcher-level summaries) are
+    // shown as-is alongside the merged rank view.
+    let shared = by_rank.remove(&None).unwrap_or_default();
+    let ranks: BTreeMap<u32, SeriesByTag> = by_rank
+        .iter()
+        .filter_map(|(rank, events)| Some(((*rank)?, build_series(events))))
+        .collect();
+    let rank_progress = by_rank
+        .iter()
+        .filter_map(|(rank, events)| Some(((*rank)?, RankProgress::of(events))))
+        .collect();
+    let mut scalars = build_series(&shared);
+    for (tag, series) in merge_ranks(&ranks) {
+        scalars.entry(tag).or_insert(series);
+    }
+    // Counters, like training tags, come from the lowest rank.
+    let lowest_rank = by_rank.first_key_value().map(|(_, events)| events);
+    add_derived_series(
+        &mut scalars,
+        shared.iter().chain(lowest_rank.into_iter().flatten()),
+    );
+    let events = shared
+        .into_iter()
+        .chain(by_rank.into_values().flatten())
+        .collect();
+    Ok(LoadedRun {
+        scalars,
+        events,
+        ranks,
+        rank_progress,
+        corruption,
+    })
+}
+
+/// Simple recursive directory walk (avoids adding walkdir dependency).
+fn walkdir(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
+    let mut files = Vec::new();
+    if dir.is_dir() {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.is_dir() {
+                files.extend(walkdir(&path)?);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CorruptionStats, RANK_MAX_SUFFIX, RANK_MEAN_SUFFIX, is_tfevents_file, merge_ranks,
+        parse_events_bytes, rank_from_path,
+    };
+    use crate::tfrecord::{
+        DT_DOUBLE, DT_FLOAT, Event, Summary, SummaryValue, TensorProto, TensorShape,
+        TensorShapeDim, encode_record,
+    };
+    use prost::Message;
+    use std::collections::BTreeMap;
+    use std::path::Path;
+
+    fn record(step: i64, value: f32) -> Vec<u8> {
+        let data = Event {
+            wall_time: step as f64,
+            step,
+            file_version: None,
+            summary: Some(Summary {
+                value: vec![SummaryValue {
+                    tag: "loss".to_string(),
+                    simple_value: Some(value),
+                    histo: None,
+                    audio: None,
+                    tensor: None,
+                    metadata: None,
+                }],
+            }),
+        }
+        .encode_to_vec();
+        encode_record(&data)
+    }
+
+    #[test]
+    fn detects_real_tfevents_and_ignores_archives() {
+        assert!(is_tfevents_file(Path::new(
+            "events.out.tfevents.1771672979.job-abc123"
+        )));
+        assert!(is_tfevents_file(Path::new("model.tfevents")));
+        assert!(!is_tfevents_file(Path::new("tfevents_latest.tgz")));
+        assert!(!is_tfevents_file(Path::new("events.out.tfevents.1.tar.gz")));
+        assert!(!is_tfevents_file(Path::new(
+            "events.out.tfevents.1.tar.zst"
+        )));
+        assert!(is_tfevents_file(Path::new("events.out.tfevents.1.host.gz")));
+        assert!(is_tfevents_file(Path::new(
+            "events.out.tfevents.1.host.zst"
+        )));
+    }
+
+    #[test]
+    fn rank_is_read_from_directory_or_file_names() {
+        assert_eq!(
+            rank_from_path(Path::new("rank0/events.out.tfevents.1")),
+            Some(0)
+        );
+        assert_eq!(
+            rank_from_path(Path::new("logs/rank_3/events.out.tfevents.1")),
+            Some(3)
+        );
+        assert_eq!(
+            rank_from_path(Path::new("events.out.tfevents.1.host.rank-12")),
+            Some(12)
+        );
+        assert_eq!(
+            rank_from_path(Path::new("frank1/events.out.tfevents.1")),
+            None
+        );
+        assert_eq!(rank_from_path(Path::new("events.out.tfevents.1")), None);
+    }
+
+    #[test]
+    fn merge_ranks_dedupes_training_tags_and_aggregates_system_tags() {
+        let mut ranks = BTreeMap::new();
+        for (rank, loss, mem) in [(0u32, 1.0, 10.0), (1, 9.0, 30.0)] {
+            let mut series = BTreeMap::new();
+            series.insert("train/loss".to_string(), vec![(1.0, loss)]);
+            series.insert(
+                "sys/gpu_mem".to_string(),
+                vec![(1.0, mem), (2.0, mem + 2.0)],
+            );
+            ranks.insert(rank, series);
+        }
+
+        let merged = merge_ranks(&ranks);
+        assert_eq!(merged["train/loss"], vec![(1.0, 1.0)]);
+        assert!(!merged.contains_key("sys/gpu_mem"));
+        assert_eq!(
+            merged[&format!("sys/gpu_mem{RANK_MEAN_SUFFIX}")],
+            vec![(1.0, 20.0), (2.0, 22.0)]
+        );
+        assert_eq!(
+            merged[&format!("sys/gpu_mem{RANK_MAX_SUFFIX}")],
+            vec![(1.0, 30.0), (2.0, 32.0)]
+        );
+    }
+
+    #[test]
+    fn resync_skips_damaged_records_and_reports_truncated_tail() {
+        let mut bytes = record(1, 1.0);
+        let mut damaged = record(2, 2.0);
+        let last = damaged.len() - 1;
+        damaged[last] ^= 0xff; // break the data CRC
+        bytes.extend_from_slice(&damaged);
+        bytes.extend_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+        bytes.extend_from_slice(&record(3, 3.0));
+        let tail = record(4, 4.0);
+        bytes.extend_from_slice(&tail[..tail.len() - 5]);
+
+        let (events, stats) = parse_events_bytes(&bytes, true).expect("resync parse");
+        let steps: Vec<i64> = events.iter().map(|e| e.step).collect();
+        assert_eq!(steps, vec![1, 3]);
+        assert_eq!(
+            stats,
+            CorruptionStats {
+                damaged_files: 1,
+                resyncs: 1,
+                skipped_bytes: (damaged.len() + 4) as u64,
+                truncated_bytes: (tail.len() - 5) as u64,
+            }
+        );
+
+        let err = parse_events_bytes(&bytes, false).expect_err("strict parse fails");
+        assert!(err.to_string().contains("CRC mismatch"));
+    }
+
+    #[test]
+    fn clean_file_reports_no_corruption() {
+        let mut bytes = record(1, 1.0);
+        bytes.extend_from_slice(&record(2, 0.5));
+        let (events, stats) = parse_events_bytes(&bytes, false).expect("strict parse");
+        assert_eq!(events.len(), 2);
+        assert!(stats.is_clean());
+    }
+
+    #[test]
+    fn reads_tf2_scalar_tensors_like_tensorboard() {
+        let value = |tag: &str, tensor: TensorProto| SummaryValue {
+            tag: tag.to_string(),
+            simple_value: None,
+            histo: None,
+            audio: None,
+            tensor: Some(tensor),
+            metadata: None,
+        };
+        let histogram_shape = TensorShape {
+            dim: vec![TensorShapeDim { size: 30 }, TensorShapeDim { size: 3 }],
+        };
+        let event = Event {
+            wall_time: 5.0,
+            step: 3,
+            file_version: None,
+            summary: Some(Summary {
+                value: vec![
+                    value(
+                        "epoch_loss",
+                        TensorProto {
+                            dtype: DT_FLOAT,
+                            float_val: vec![0.25],
+                            ..Default::default()
+                        },
+                    ),
+                    value(
+                        "epoch_lr",
+                        TensorProto {
+                            dtype: DT_DOUBLE,
+                            tensor_content: 0.001f64.to_le_bytes().to_vec(),
+                            ..Default::default()
+                        },
+                    ),
+                    value(
+                        "weights",
+                        TensorProto {
+                            dtype: DT_FLOAT,
+                            tensor_shape: Some(histogram_shape),
+                            float_val: vec![0.0; 90],
+                            ..Default::default()
+                        },
+                    ),
+                ],
+            }),
+        };
+        let mut bytes = encode_record(
+            &Event {
+                wall_time: 1.0,
+                step: 0,
+                file_version: Some("brain.Event:2".to_string()),
+                summary: None,
+            }
+            .encode_to_vec(),
+        );
+        bytes.extend(encode_record(&event.encode_to_vec()));
+
+        let (events, stats) = parse_events_bytes(&bytes, false).expect("strict parse");
+        assert!(stats.is_clean());
+        let parsed: Vec<(&str, f64)> = events.iter().map(|e| (e.tag.as_str(), e.value)).collect();
+        assert_eq!(parsed, vec![("epoch_loss", 0.25), ("epoch_lr", 0.001)]);
+    }
+}
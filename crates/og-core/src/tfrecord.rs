@@ -0,0 +1,548 @@
+//! TFRecord framing and the TensorFlow `Event` messages inside it: the
+//! layer of the event-file reader that sees raw bytes. Nothing here trusts
+//! the input. Length fields are checked against the bytes actually present
+//! and never used to size an allocation, and tensor shapes are checked
+//! before any values are materialized, so damaged or hostile files yield
+//! `Corrupt` records or `None` instead of panics or huge buffers. The module
+//! only depends on prost, crc32c, serde and anyhow so the fuzz targets under
+//! `fuzz/` can compile it on its own.
+
+use anyhow::{Result, bail};
+use prost::Message;
+use serde::Serialize;
+
+// ── Minimal protobuf definitions (matching TensorFlow event.proto / summary.proto) ──
+
+/// A single TensorFlow Event record.
+#[derive(Clone, PartialEq, Message)]
+pub struct Event {
+    /// Wall clock time of the event (seconds since epoch).
+    #[prost(double, tag = "1")]
+    pub wall_time: f64,
+
+    /// Global step of the event.
+    #[prost(int64, tag = "2")]
+    pub step: i64,
+
+    // oneof `what` — we only care about file_version and summary for scalar extraction.
+    #[prost(string, optional, tag = "3")]
+    pub file_version: Option<String>,
+
+    #[prost(message, optional, tag = "5")]
+    pub summary: Option<Summary>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Summary {
+    #[prost(message, repeated, tag = "1")]
+    pub value: Vec<SummaryValue>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct SummaryValue {
+    /// Tag name, e.g. "train/loss"
+    #[prost(string, tag = "1")]
+    pub tag: String,
+
+    /// Simple scalar value.
+    #[prost(float, optional, tag = "2")]
+    pub simple_value: Option<f32>,
+
+    /// Bucketed values of a TF1 `tf.summary.histogram`.
+    #[prost(message, optional, tag = "5")]
+    pub histo: Option<HistogramProto>,
+
+    /// Encoded clip of a TF1 `tf.summary.audio`.
+    #[prost(message, optional, tag = "6")]
+    pub audio: Option<Audio>,
+
+    /// Tensor value; TF2 `tf.summary.scalar` writes scalars this way.
+    #[prost(message, optional, tag = "8")]
+    pub tensor: Option<TensorProto>,
+    // Images and other value types are skipped.
+    /// Which TensorBoard plugin a tensor is for; TF2 writes it with the first
+    /// value of each tag only.
+    #[prost(message, optional, tag = "9")]
+    pub metadata: Option<SummaryMetadata>,
+}
+
+/// TF1 histogram: `bucket[i]` values fell at or below `bucket_limit[i]`
+/// and above the previous limit. Writers may leave out empty buckets.
+#[derive(Clone, PartialEq, Message)]
+pub struct HistogramProto {
+    #[prost(double, tag = "1")]
+    pub min: f64,
+    #[prost(double, tag = "2")]
+    pub max: f64,
+    #[prost(double, tag = "3")]
+    pub num: f64,
+    #[prost(double, tag = "4")]
+    pub sum: f64,
+    #[prost(double, tag = "5")]
+    pub sum_squares: f64,
+    #[prost(double, repeated, tag = "6")]
+    pub bucket_limit: Vec<f64>,
+    #[prost(double, repeated, tag = "7")]
+    pub bucket: Vec<f64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Audio {
+    #[prost(float, tag = "1")]
+    pub sample_rate: f32,
+    #[prost(bytes = "vec", tag = "4")]
+    pub encoded_audio_string: Vec<u8>,
+    /// MIME type, `audio/wav` for everything TensorFlow writes.
+    #[prost(string, tag = "5")]
+    pub content_type: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct SummaryMetadata {
+    #[prost(message, optional, tag = "1")]
+    pub plugin_data: Option<PluginData>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct PluginData {
+    #[prost(string, tag = "1")]
+    pub plugin_name: String,
+}
+
+/// The parts of TensorFlow's `TensorProto` needed to read scalar tensors.
+#[derive(Clone, PartialEq, Message)]
+pub struct TensorProto {
+    #[prost(int32, tag = "1")]
+    pub dtype: i32,
+    #[prost(message, optional, tag = "2")]
+    pub tensor_shape: Option<TensorShape>,
+    /// Little-endian values, used instead of the typed fields by some writers.
+    #[prost(bytes = "vec", tag = "4")]
+    pub tensor_content: Vec<u8>,
+    #[prost(float, repeated, tag = "5")]
+    pub float_val: Vec<f32>,
+    #[prost(double, repeated, tag = "6")]
+    pub double_val: Vec<f64>,
+    #[prost(int32, repeated, tag = "7")]
+    pub int_val: Vec<i32>,
+    /// `DT_STRING` elements, such as the encoded clips of TF2 audio summaries.
+    #[prost(bytes = "vec", repeated, tag = "8")]
+    pub string_val: Vec<Vec<u8>>,
+    #[prost(int64, repeated, tag = "10")]
+    pub int64_val: Vec<i64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct TensorShape {
+    #[prost(message, repeated, tag = "2")]
+    pub dim: Vec<TensorShapeDim>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct TensorShapeDim {
+    #[prost(int64, tag = "1")]
+    pub size: i64,
+}
+
+// TensorFlow `DataType` enum values.
+/// Largest 2-D tensor `matrix` expands. A single stored value stands for a
+/// whole tensor of it, so without a bound a shape from the file alone could
+/// ask for any amount of memory.
+pub const MAX_MATRIX_ELEMENTS: usize = 1 << 20;
+
+pub const DT_FLOAT: i32 = 1;
+pub const DT_DOUBLE: i32 = 2;
+const DT_INT32: i32 = 3;
+pub const DT_STRING: i32 = 7;
+const DT_INT64: i32 = 9;
+
+impl TensorProto {
+    /// The value of a single-element numeric tensor; histograms, text and
+    /// other multi-element tensors yield `None`.
+    pub fn scalar(&self) -> Option<f64> {
+        // Folded with checks: shapes come from the file and may overflow.
+        let elements = self
+            .tensor_shape
+            .iter()
+            .flat_map(|shape| &shape.dim)
+            .try_fold(1i64, |product, dim| product.checked_mul(dim.size));
+        if elements != Some(1) {
+            return None;
+        }
+        let content = self.tensor_content.as_slice();
+        match self.dtype {
+            DT_FLOAT => self
+                .float_val
+                .first()
+                .copied()
+                .or_else(|| Some(f32::from_le_bytes(content.try_into().ok()?)))
+                .map(f64::from),
+            DT_DOUBLE => self
+                .double_val
+                .first()
+                .copied()
+                .or_else(|| Some(f64::from_le_bytes(content.try_into().ok()?))),
+            DT_INT32 => self.int_val.first().map(|v| f64::from(*v)),
+            DT_INT64 => self.int64_val.first().map(|v| *v as f64),
+            _ => None,
+        }
+    }
+
+    /// Rows of a 2-D numeric tensor; other ranks and dtypes yield `None`.
+    /// Writers may store a tensor of one repeated value as that value alone.
+    pub fn matrix(&self) -> Option<Vec<Vec<f64>>> {
+        let dims: Vec<usize> = self
+            .tensor_shape
+            .iter()
+            .flat_map(|shape| &shape.dim)
+            .map(|dim| usize::try_from(dim.size).ok())
+            .collect::<Option<_>>()?;
+        let [rows, cols] = dims[..] else {
+            return None;
+        };
+        let elements = rows
+            .checked_mul(cols)
+            .filter(|n| (1..=MAX_MATRIX_ELEMENTS).contains(n))?;
+        let content = self.tensor_content.as_slice();
+        let values: Vec<f64> = match self.dtype {
+            DT_FLOAT if self.float_val.is_empty() => content
+                .chunks_exact(4)
+                .map(|b| f64::from(f32::from_le_bytes(b.try_into().expect("4-byte chunk"))))
+                .collect(),
+            DT_FLOAT => self.float_val.iter().map(|v| f64::from(*v)).collect(),
+            DT_DOUBLE if self.double_val.is_empty() => content
+                .chunks_exact(8)
+                .map(|b| f64::from_le_bytes(b.try_into().expect("8-byte chunk")))
+                .collect(),
+            DT_DOUBLE => self.double_val.clone(),
+            DT_INT32 if self.int_val.is_empty() => content
+                .chunks_exact(4)
+                .map(|b| f64::from(i32::from_le_bytes(b.try_into().expect("4-byte chunk"))))
+                .collect(),
+            DT_INT32 => self.int_val.iter().map(|v| f64::from(*v)).collect(),
+            DT_INT64 if self.int64_val.is_empty() => content
+                .chunks_exact(8)
+                .map(|b| i64::from_le_bytes(b.try_into().expect("8-byte chunk")) as f64)
+                .collect(),
+            DT_INT64 => self.int64_val.iter().map(|v| *v as f64).collect(),
+            _ => return None,
+        };
+        let values = match values.len() {
+            1 => vec![values[0]; elements],
+            n if n == elements => values,
+            _ => return None,
+        };
+        Some(values.chunks(cols).map(<[f64]>::to_vec).collect())
+    }
+}
+
+impl SummaryValue {
+    pub fn scalar(&self) -> Option<f64> {
+        self.simple_value
+            .map(f64::from)
+            .or_else(|| self.tensor.as_ref()?.scalar())
+    }
+}
+
+/// What the resyncing reader skipped to get through damaged event files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct CorruptionStats {
+    /// Files with at least one skipped region or a cut-off final record.
+    pub damaged_files: usize,
+    /// Times the reader jumped forward to the next plausible record boundary.
+    pub resyncs: usize,
+    /// Bytes skipped while resyncing.
+    pub skipped_bytes: u64,
+    /// Bytes of incomplete records at end of file (typically a killed writer).
+    pub truncated_bytes: u64,
+}
+
+impl CorruptionStats {
+    pub fn is_clean(&self) -> bool {
+        self.damaged_files == 0
+    }
+
+    pub fn merge(&mut self, other: CorruptionStats) {
+        self.damaged_files += other.damaged_files;
+        self.resyncs += other.resyncs;
+        self.skipped_bytes += other.skipped_bytes;
+        self.truncated_bytes += other.truncated_bytes;
+    }
+}
+
+// ── Record-level reader ─────────────────────────────────────────────────────
+
+// TF record format per record:
+//   uint64  length           (little-endian)
+//   uint32  masked_crc32c(length_bytes)
+//   byte    data[length]
+//   uint32  masked_crc32c(data)
+
+fn masked_crc32c(data: &[u8]) -> u32 {
+    let crc = crc32c::crc32c(data);
+    crc.rotate_right(15).wrapping_add(0xa282_ead8)
+}
+
+/// Header (length + length CRC) and trailer (data CRC) bytes around each record.
+const RECORD_HEADER_LEN: usize = 12;
+const RECORD_FOOTER_LEN: usize = 4;
+/// Largest record length accepted. TensorFlow writes nothing near it; a
+/// header announcing more is damage that happens to pass the length CRC.
+pub const MAX_RECORD_LEN: u64 = 1 << 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordAt<'a> {
+    Valid {
+        data: &'a [u8],
+        next: usize,
+    },
+    /// Not enough bytes left for the record the header announces.
+    Truncated,
+    /// Checksum mismatch or impossible length: not a record boundary, or
+    /// damaged data.
+    Corrupt,
+}
+
+/// The record starting at `pos` in `bytes`. Total over arbitrary input:
+/// any `pos`, any bytes.
+pub fn read_record(bytes: &[u8], pos: usize) -> RecordAt<'_> {
+    let Some(rest) = bytes.get(pos..) else {
+        return RecordAt::Truncated;
+    };
+    let (Some(len_buf), Some(len_crc_buf)) = (rest.get(..8), rest.get(8..RECORD_HEADER_LEN)) else {
+        return RecordAt::Truncated;
+    };
+    let len_crc = u32::from_le_bytes(len_crc_buf.try_into().expect("4-byte slice"));
+    if len_crc != masked_crc32c(len_buf) {
+        return RecordAt::Corrupt;
+    }
+    let data_len = u64::from_le_bytes(len_buf.try_into().expect("8-byte slice"));
+    if data_len > MAX_RECORD_LEN {
+        return RecordAt::Corrupt;
+    }
+    let Some(end) = usize::try_from(data_len)
+        .ok()
+        .and_then(|len| len.checked_add(RECORD_HEADER_LEN + RECORD_FOOTER_LEN))
+    else {
+        return RecordAt::Corrupt;
+    };
+    let (Some(data), Some(data_crc_buf)) = (
+        rest.get(RECORD_HEADER_LEN..end - RECORD_FOOTER_LEN),
+        rest.get(end - RECORD_FOOTER_LEN..end),
+    ) else {
+        return RecordAt::Truncated;
+    };
+    let data_crc = u32::from_le_bytes(data_crc_buf.try_into().expect("4-byte slice"));
+    if data_crc != masked_crc32c(data) {
+        return RecordAt::Corrupt;
+    }
+    RecordAt::Valid {
+        data,
+        next: pos + end,
+    }
+}
+
+/// First offset after `pos` where a checksummed, decodable record starts.
+fn next_record_boundary(bytes: &[u8], pos: usize) -> Option<usize> {
+    (pos + 1..bytes.len().saturating_sub(RECORD_HEADER_LEN - 1)).find(|&candidate| {
+        matches!(
+            read_record(bytes, candidate),
+            RecordAt::Valid { data, .. } if Event::decode(data).is_ok()
+        )
+    })
+}
+
+/// A decoded record next to the raw payload it was decoded from.
+pub type RawRecord<'a> = (&'a [u8], Event);
+
+/// Decode every record, keeping the raw payload next to the decoded event.
+/// Strict mode fails on the first damaged record; resync mode skips ahead to
+/// the next valid record boundary instead.
+pub fn decode_records(bytes: &[u8], resync: bool) -> Result<(Vec<RawRecord<'_>>, CorruptionStats)> {
+    let mut records = Vec::new();
+    let mut stats = CorruptionStats::default();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let reason = match read_record(bytes, pos) {
+            RecordAt::Valid { data, next } => match Event::decode(data) {
+                Ok(event) => {
+                    records.push((data, event));
+                    pos = next;
+                    continue;
+                }
+                Err(_) => "decoding Event protobuf",
+            },
+            RecordAt::Truncated => {
+                // A partially written final record: nothing after it to recover.
+                stats.truncated_bytes += (bytes.len() - pos) as u64;
+                break;
+            }
+            RecordAt::Corrupt => "CRC mismatch on record",
+        };
+        if !resync {
+            bail!("{} at byte {}", reason, pos);
+        }
+        let resume = next_record_boundary(bytes, pos).unwrap_or(bytes.len());
+        stats.resyncs += 1;
+        stats.skipped_bytes += (resume - pos) as u64;
+        pos = resume;
+    }
+
+    if stats.resyncs > 0 || stats.truncated_bytes > 0 {
+        stats.damaged_files = 1;
+    }
+    Ok((records, stats))
+}
+
+/// Size of `data` once framed as a TF record.
+pub fn framed_len(data: &[u8]) -> usize {
+    RECORD_HEADER_LEN + data.len() + RECORD_FOOTER_LEN
+}
+
+/// Frame `data` as a TF record (length, length CRC, data, data CRC).
+pub fn encode_record(data: &[u8]) -> Vec<u8> {
+    let len = (data.len() as u64).to_le_bytes();
+    let mut record = Vec::with_capacity(data.len() + RECORD_HEADER_LEN + RECORD_FOOTER_LEN);
+    record.extend_from_slice(&len);
+    record.extend_from_slice(&masked_crc32c(&len).to_le_bytes());
+    record.extend_from_slice(data);
+    record.extend_from_slice(&masked_crc32c(data).to_le_bytes());
+    record
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DT_DOUBLE, DT_FLOAT, Event, RecordAt, Summary, SummaryValue, TensorProto, TensorShape,
+        TensorShapeDim, decode_records, encode_record, masked_crc32c, read_record,
+    };
+    use prost::Message;
+
+    /// xorshift64: the property tests must replay identically on failure.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+    }
+
+    fn shaped(dtype: i32, dims: &[i64], float_val: Vec<f32>) -> TensorProto {
+        TensorProto {
+            dtype,
+            tensor_shape: Some(TensorShape {
+                dim: dims.iter().map(|&size| TensorShapeDim { size }).collect(),
+            }),
+            float_val,
+            ..Default::default()
+        }
+    }
+
+    fn valid_file(records: i64) -> Vec<u8> {
+        (0..records)
+            .flat_map(|step| {
+                let event = Event {
+                    wall_time: step as f64,
+                    step,
+                    file_version: None,
+                    summary: Some(Summary {
+                        value: vec![SummaryValue {
+                            tag: "loss".to_string(),
+                            simple_value: Some(step as f32),
+                            histo: None,
+                            audio: None,
+                            tensor: Some(shaped(DT_DOUBLE, &[], Vec::new())),
+                            metadata: None,
+                        }],
+                    }),
+                };
+                encode_record(&event.encode_to_vec())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn record_framing_matches_tensorflow_crc_convention() {
+        // CRC-32C check value of "123456789" is 0xe3069283; TF masks it as
+        // rotate_right(15) + 0xa282ead8.
+        assert_eq!(masked_crc32c(b"123456789"), 0xc78a_b0e5);
+        let framed = encode_record(b"abc");
+        assert_eq!(&framed[..8], &3u64.to_le_bytes());
+        assert_eq!(framed.len(), 12 + 3 + 4);
+    }
+
+    #[test]
+    fn read_record_is_total_over_offsets_and_lengths() {
+        let framed = encode_record(b"abc");
+        assert_eq!(read_record(&framed, framed.len() + 7), RecordAt::Truncated);
+        assert_eq!(read_record(&framed, usize::MAX), RecordAt::Truncated);
+        assert_eq!(
+            read_record(&framed[..framed.len() - 1], 0),
+            RecordAt::Truncated
+        );
+
+        // A length that passes its CRC but could never fit in memory.
+        let len = u64::MAX.to_le_bytes();
+        let mut header = len.to_vec();
+        header.extend_from_slice(&masked_crc32c(&len).to_le_bytes());
+        assert_eq!(read_record(&header, 0), RecordAt::Corrupt);
+    }
+
+    #[test]
+    fn decoding_arbitrary_and_mutated_bytes_never_panics() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        let valid = valid_file(8);
+        for _ in 0..2_000 {
+            let mut bytes = if rng.below(2) == 0 {
+                valid.clone()
+            } else {
+                (0..rng.below(256)).map(|_| rng.next() as u8).collect()
+            };
+            for _ in 0..rng.below(8) {
+                if bytes.is_empty() {
+                    break;
+                }
+                let at = rng.below(bytes.len());
+                match rng.below(3) {
+                    0 => bytes[at] ^= 1 << rng.below(8),
+                    1 => bytes.truncate(at),
+                    _ => bytes.insert(at, rng.next() as u8),
+                }
+            }
+            let _ = decode_records(&bytes, false);
+            let (records, stats) = decode_records(&bytes, true).expect("resync never fails");
+            for (_, event) in &records {
+                for value in event.summary.iter().flat_map(|s| &s.value) {
+                    let _ = value.scalar();
+                    let _ = value.tensor.as_ref().and_then(TensorProto::matrix);
+                }
+            }
+            assert!(stats.skipped_bytes + stats.truncated_bytes <= bytes.len() as u64);
+        }
+    }
+
+    #[test]
+    fn hostile_tensor_shapes_yield_none() {
+        // The product of these dims overflows i64.
+        let overflowing = shaped(DT_FLOAT, &[1 << 32, 1 << 32, 2], vec![1.0]);
+        assert_eq!(overflowing.scalar(), None);
+        assert_eq!(overflowing.matrix(), None);
+
+        // One value broadcast over a shape from the file must not allocate it.
+        let huge = shaped(DT_FLOAT, &[1 << 30, 1 << 30], vec![1.0]);
+        assert_eq!(huge.matrix(), None);
+        assert_eq!(shaped(DT_FLOAT, &[-1, 2], vec![1.0]).matrix(), None);
+        assert_eq!(
+            shaped(DT_FLOAT, &[2, 2], vec![1.0]).matrix(),
+            Some(vec![vec![1.0, 1.0], vec![1.0, 1.0]])
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! Throughput derived from cumulative counters: training scripts usually log
+//! a running `tokens` or `samples` count rather than a rate, so the loader adds
+//! its change per wall-clock second as a synthetic metric.
+
+use std::collections::BTreeMap;
+
+use crate::tfevents::ScalarEvent;
+
+/// Tag of the derived series.
+pub const TAG: &str = "derived/throughput";
+
+/// Whether `tag` names a cumulative token or sample counter (`tokens`,
+/// `train/tokens_seen`, `num_samples`), not a rate like `tokens_per_sec`.
+/// Returns the counter's unit.
+pub fn counter_unit(tag: &str) -> Option<&'static str> {
+    let name = tag.rsplit('/').next()?.to_ascii_lowercase();
+    let words: Vec<&str> = name.split(['_', '-', '.']).collect();
+    if words
+        .iter()
+        .any(|word| matches!(*word, "per" | "s" | "sec" | "rate" | "throughput"))
+    {
+        return None;
+    }
+    if words.contains(&"tokens") {
+        Some("tokens")
+    } else if words.contains(&"samples") {
+        Some("samples")
+    } else {
+        None
+    }
+}
+
+/// `(step, units per second)` between consecutive points of the first token
+/// counter in `events` (else the first sample counter). Points where the
+/// counter went down (a restart) or the clock did not move are skipped.
+pub fn derive<'a>(events: impl IntoIterator<Item = &'a ScalarEvent>) -> Option<Vec<(f64, f64)>> {
+    let mut counters: BTreeMap<&str, BTreeMap<i64, (f64, f64)>> = BTreeMap::new();
+    for event in events {
+        if counter_unit(&event.tag).is_some() {
+            counters
+                .entry(&event.tag)
+                .or_default()
+                .entry(event.step)
+                .or_insert((event.wall_time, event.value));
+        }
+    }
+    let (_, points) = counters
+        .iter()
+        .min_by_key(|(tag, _)| (counter_unit(tag) != Some("tokens"), **tag))?;
+    let points: Vec<(i64, (f64, f64))> = points.iter().map(|(s, p)| (*s, *p)).collect();
+    let series: Vec<(f64, f64)> = points
+        .windows(2)
+        .filter_map(|pair| {
+            let (_, (t0, v0)) = pair[0];
+            let (step, (t1, v1)) = pair[1];
+            let (dt, dv) = (t1 - t0, v1 - v0);
+            (dt > 0.0 && dv >= 0.0 && dv.is_finite()).then(|| (step as f64, dv / dt))
+        })
+        .collect();
+    (!series.is_empty()).then_some(series)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{counter_unit, derive};
+    use crate::tfevents::ScalarEvent;
+
+    fn event(tag: &str, step: i64, wall_time: f64, value: f64) -> ScalarEvent {
+        ScalarEvent {
+            tag: tag.to_string(),
+            step,
+            wall_time,
+            value,
+        }
+    }
+
+    #[test]
+    fn derives_rate_per_second_from_cumulative_counters() {
+        assert_eq!(counter_unit("train/tokens_seen"), Some("tokens"));
+        assert_eq!(counter_unit("num_samples"), Some("samples"));
+        assert_eq!(counter_unit("perf/tokens_per_sec"), None);
+        assert_eq!(counter_unit("train/loss"), None);
+
+        let events = vec![
+            event("samples", 0, 100.0, 0.0),
+            event("samples", 1, 101.0, 8.0),
+            event("train/tokens", 0, 100.0, 0.0),
+            event("train/tokens", 1, 102.0, 4096.0),
+            event("train/tokens", 2, 103.0, 6144.0),
+            // Restarted counter: no point for step 3.
+            event("train/tokens", 3, 104.0, 1024.0),
+            event("train/tokens", 4, 106.0, 2048.0),
+            event("train/loss", 4, 106.0, 1.5),
+        ];
+        assert_eq!(
+            derive(&events),
+            Some(vec![(1.0, 2048.0), (2.0, 2048.0), (4.0, 512.0)])
+        );
+        assert_eq!(derive(&events[..2]), Some(vec![(1.0, 8.0)]));
+        assert_eq!(derive(&events[7..]), None);
+    }
+}
@@ -0,0 +1,397 @@
+//! Access control for ogd's network APIs. Clients send
+//! `Authorization: Bearer <token>`. Each token grants some of three
+//! permissions: `query` for reading runs, `ingest` for writing metrics, and
+//! `control` for acting on training jobs (stop, restart). Read-only mode
+//! refuses ingest and control to every token. Without tokens, ogd only
+//! serves on a loopback address.
+
+use std::collections::BTreeSet;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use axum::Router;
+use axum::extract::{Request, State};
+use axum::http::{HeaderValue, StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+
+/// File of tokens, one per line: `<name> <permissions> <token>`, where
+/// permissions is a comma-separated list such as `query,ingest` or `all`.
+pub const TOKENS_FILE_ENV: &str = "OGD_TOKENS_FILE";
+/// `1`/`true` refuses ingest and control requests whatever the token.
+pub const READ_ONLY_ENV: &str = "OGD_READ_ONLY";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Permission {
+    Query,
+    Ingest,
+    Control,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 3] = [Permission::Query, Permission::Ingest, Permission::Control];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Permission::Query => "query",
+            Permission::Ingest => "ingest",
+            Permission::Control => "control",
+        }
+    }
+
+    /// Whether read-only mode still allows it.
+    fn reads_only(self) -> bool {
+        self == Permission::Query
+    }
+}
+
+impl FromStr for Permission {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        match text {
+            "query" => Ok(Permission::Query),
+            "ingest" => Ok(Permission::Ingest),
+            "control" => Ok(Permission::Control),
+            other => bail!("unknown permission '{other}' (expected query, ingest or control)"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub name: String,
+    secret: String,
+    pub permissions: BTreeSet<Permission>,
+}
+
+/// Why a request was turned away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Denied {
+    /// No bearer token, or one ogd does not know.
+    Unauthenticated,
+    /// A known token without the route's permission.
+    Forbidden,
+    /// The route writes and ogd is read-only.
+    ReadOnly,
+}
+
+impl IntoResponse for Denied {
+    fn into_response(self) -> Response {
+        match self {
+            Denied::Unauthenticated => {
+                let mut response =
+                    (StatusCode::UNAUTHORIZED, "missing or unknown bearer token").into_response();
+                response
+                    .headers_mut()
+                    .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+                response
+            }
+            Denied::Forbidden => (
+                StatusCode::FORBIDDEN,
+                "token lacks permission for this route",
+            )
+                .into_response(),
+            Denied::ReadOnly => (StatusCode::FORBIDDEN, "ogd is read-only").into_response(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuthConfig {
+    pub tokens: Vec<Token>,
+    pub read_only: bool,
+}
+
+impl AuthConfig {
+    /// Config from `OGD_TOKENS_FILE` and `OGD_READ_ONLY`.
+    pub fn from_env() -> Result<Self> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    fn from_lookup(get: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let tokens = match get(TOKENS_FILE_ENV).filter(|path| !path.trim().is_empty()) {
+            Some(path) => load_tokens(Path::new(path.trim()))?,
+            None => Vec::new(),
+        };
+        let read_only = match get(READ_ONLY_ENV).as_deref().map(str::trim) {
+            None | Some("" | "0" | "false") => false,
+            Some("1" | "true") => true,
+            Some(other) => bail!("{READ_ONLY_ENV} must be 1 or 0, got '{other}'"),
+        };
+        Ok(AuthConfig { tokens, read_only })
+    }
+
+    /// Refuse to serve without tokens anywhere but loopback, where only
+    /// local users can reach the port.
+    pub fn check_bind(&self, bind_addr: &str) -> Result<()> {
+        let addr: SocketAddr = bind_addr
+            .parse()
+            .with_context(|| format!("invalid bind address '{bind_addr}'"))?;
+        if self.tokens.is_empty() && !addr.ip().is_loopback() {
+            bail!("refusing to serve on {addr} without tokens; set {TOKENS_FILE_ENV}");
+        }
+        Ok(())
+    }
+
+    /// The token a request may use a route needing `permission` with,
+    /// given its `Authorization` header. With no tokens configured every
+    /// request is anonymous and allowed; read-only mode still applies.
+    pub fn authorize(
+        &self,
+        authorization: Option<&str>,
+        permission: Permission,
+    ) -> Result<Option<&Token>, Denied> {
+        if self.read_only && !permission.reads_only() {
+            return Err(Denied::ReadOnly);
+        }
+        if self.tokens.is_empty() {
+            return Ok(None);
+        }
+        let presented = authorization
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim)
+            .ok_or(Denied::Unauthenticated)?;
+        let token = self
+            .tokens
+            .iter()
+            .find(|token| constant_time_eq(token.secret.as_bytes(), presented.as_bytes()))
+            .ok_or(Denied::Unauthenticated)?;
+        if !token.permissions.contains(&permission) {
+            return Err(Denied::Forbidden);
+        }
+        Ok(Some(token))
+    }
+
+    /// Require `permission` on every route of `router`:
+    /// `auth.protect(Permission::Query, Router::new().route("/runs", get(runs)))`.
+    pub fn protect<S>(self: &Arc<Self>, permission: Permission, router: Router<S>) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        router.route_layer(middleware::from_fn_with_state(
+            (Arc::clone(self), permission),
+            require,
+        ))
+    }
+}
+
+async fn require(
+    State((auth, permission)): State<(Arc<AuthConfig>, Permission)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let authorization = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    match auth.authorize(authorization, permission) {
+        Ok(_) => next.run(request).await,
+        Err(denied) => {
+            tracing::warn!(
+                path = %request.uri().path(),
+                permission = permission.as_str(),
+                ?denied,
+                "request denied"
+            );
+            denied.into_response()
+        }
+    }
+}
+
+pub fn load_tokens(path: &Path) -> Result<Vec<Token>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read tokens file {}", path.display()))?;
+    parse_tokens(&text).with_context(|| format!("invalid tokens file {}", path.display()))
+}
+
+fn parse_tokens(text: &str) -> Result<Vec<Token>> {
+    let mut tokens: Vec<Token> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [name, permissions, secret] = fields[..] else {
+            bail!(
+                "line {}: expected '<name> <permissions> <token>'",
+                index + 1
+            );
+        };
+        let permissions = if permissions == "all" {
+            Permission::ALL.into_iter().collect()
+        } else {
+            permissions
+                .split(',')
+                .map(str::parse)
+                .collect::<Result<BTreeSet<_>>>()
+                .with_context(|| format!("line {}", index + 1))?
+        };
+        if tokens.iter().any(|token| token.secret == secret) {
+            bail!(
+                "line {}: token '{name}' repeats an earlier token",
+                index + 1
+            );
+        }
+        tokens.push(Token {
+            name: name.to_string(),
+            secret: secret.to_string(),
+            permissions,
+        });
+    }
+    Ok(tokens)
+}
+
+/// Compares every byte so the time taken does not reveal how much of a
+/// guessed token was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AuthConfig, Denied, Permission, READ_ONLY_ENV, parse_tokens};
+    use axum::Router;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode, header};
+    use axum::routing::{get, post};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    const TOKENS: &str = "\
+# name permissions token
+dashboard query            q-secret
+trainer   query,ingest     i-secret
+oncall    all              c-secret
+";
+
+    fn config(read_only: bool) -> AuthConfig {
+        AuthConfig {
+            tokens: parse_tokens(TOKENS).expect("tokens"),
+            read_only,
+        }
+    }
+
+    #[test]
+    fn tokens_grant_only_their_permissions() {
+        let auth = config(false);
+        let name = |header: &str, permission| {
+            auth.authorize(Some(header), permission)
+                .map(|token| token.map(|token| token.name.clone()))
+        };
+        assert_eq!(
+            name("Bearer q-secret", Permission::Query),
+            Ok(Some("dashboard".into()))
+        );
+        assert_eq!(
+            name("Bearer q-secret", Permission::Ingest),
+            Err(Denied::Forbidden)
+        );
+        assert_eq!(
+            name("Bearer i-secret", Permission::Control),
+            Err(Denied::Forbidden)
+        );
+        assert_eq!(
+            name("Bearer c-secret", Permission::Control),
+            Ok(Some("oncall".into()))
+        );
+        assert_eq!(
+            name("Bearer q-secre", Permission::Query),
+            Err(Denied::Unauthenticated)
+        );
+        assert_eq!(
+            name("q-secret", Permission::Query),
+            Err(Denied::Unauthenticated)
+        );
+        assert_eq!(
+            auth.authorize(None, Permission::Query),
+            Err(Denied::Unauthenticated)
+        );
+
+        let read_only = config(true);
+        assert!(
+            read_only
+                .authorize(Some("Bearer c-secret"), Permission::Query)
+                .is_ok()
+        );
+        assert_eq!(
+            read_only.authorize(Some("Bearer c-secret"), Permission::Control),
+            Err(Denied::ReadOnly)
+        );
+        // No tokens: anonymous, but read-only still holds.
+        let open = AuthConfig {
+            tokens: Vec::new(),
+            read_only: true,
+        };
+        assert_eq!(open.authorize(None, Permission::Query), Ok(None));
+        assert_eq!(
+            open.authorize(None, Permission::Ingest),
+            Err(Denied::ReadOnly)
+        );
+    }
+
+    #[test]
+    fn config_rejects_bad_files_and_open_public_binds() {
+        assert!(parse_tokens("trainer query,write t-secret").is_err());
+        assert!(parse_tokens("trainer query").is_err());
+        assert!(parse_tokens("a query same\nb all same").is_err());
+
+        let open = AuthConfig::default();
+        assert!(open.check_bind("127.0.0.1:8787").is_ok());
+        assert!(open.check_bind("0.0.0.0:8787").is_err());
+        assert!(config(false).check_bind("0.0.0.0:8787").is_ok());
+
+        let lookup = |value: &'static str| {
+            move |key: &str| (key == READ_ONLY_ENV).then(|| value.to_string())
+        };
+        assert!(AuthConfig::from_lookup(lookup("1")).unwrap().read_only);
+        assert!(!AuthConfig::from_lookup(lookup("0")).unwrap().read_only);
+        assert!(AuthConfig::from_lookup(lookup("yes")).is_err());
+    }
+
+    #[tokio::test]
+    async fn protected_routes_answer_401_and_403() {
+        let auth = Arc::new(config(false));
+        let app: Router = auth
+            .protect(
+                Permission::Query,
+                Router::new().route("/runs", get(|| async { "runs" })),
+            )
+            .merge(auth.protect(
+                Permission::Control,
+                Router::new().route("/restart", post(|| async { "restarting" })),
+            ));
+        let status = |method: &str, uri: &str, token: Option<&str>| {
+            let mut request = Request::builder().method(method).uri(uri);
+            if let Some(token) = token {
+                request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+            }
+            let app = app.clone();
+            let request = request.body(Body::empty()).expect("request");
+            async move { app.oneshot(request).await.expect("response") }
+        };
+
+        let unauthenticated = status("GET", "/runs", None).await;
+        assert_eq!(unauthenticated.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            unauthenticated.headers()[header::WWW_AUTHENTICATE],
+            "Bearer"
+        );
+        assert_eq!(
+            status("GET", "/runs", Some("q-secret")).await.status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            status("POST", "/restart", Some("q-secret")).await.status(),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status("POST", "/restart", Some("c-secret")).await.status(),
+            StatusCode::OK
+        );
+    }
+}
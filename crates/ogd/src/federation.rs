@@ -0,0 +1,146 @@
+//! Peer ogd instances, one per node of a multi-node job. An instance lists
+//! its peers in `OGD_PEERS` (`node1=http://10.0.0.2:8787,node2=...`); in a
+//! merged view their runs sit next to its own as `<node>/<run>`, so the job
+//! needs no NFS-shared runs directory. ogd does not serve query routes yet,
+//! so this holds the registry, the merge and the routing back to a peer for
+//! them to build on.
+
+use anyhow::{Result, bail};
+use og_core::query::RunSummary;
+use std::cmp::Ordering;
+
+/// Comma-separated `node=url` peers; a bare URL is named after its host.
+pub const PEERS_ENV: &str = "OGD_PEERS";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Peer {
+    /// Prefix of the peer's run ids in the merged view
+    pub node: String,
+    /// Base URL of the peer's ogd, without a trailing slash
+    pub url: String,
+}
+
+impl Peer {
+    /// The peer's id for a run the merged view calls `<node>/<id>`.
+    pub fn local_id<'a>(&self, id: &'a str) -> Option<&'a str> {
+        id.strip_prefix(self.node.as_str())?.strip_prefix('/')
+    }
+}
+
+pub fn from_env() -> Result<Vec<Peer>> {
+    parse_peers(&std::env::var(PEERS_ENV).unwrap_or_default())
+}
+
+/// Peers listed as `node=url` or bare `url`, separated by commas.
+pub fn parse_peers(text: &str) -> Result<Vec<Peer>> {
+    let mut peers: Vec<Peer> = Vec::new();
+    for item in text
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+    {
+        let (node, url) = match item.split_once('=') {
+            Some((node, url)) => (node.trim().to_string(), url.trim()),
+            None => (host(item).to_string(), item),
+        };
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            bail!("{PEERS_ENV}: '{url}' is not an http(s) URL");
+        }
+        if node.is_empty() || node.contains('/') {
+            bail!("{PEERS_ENV}: '{node}' is not a node name");
+        }
+        if peers.iter().any(|peer| peer.node == node) {
+            bail!("{PEERS_ENV}: node '{node}' is listed twice");
+        }
+        peers.push(Peer {
+            node,
+            url: url.trim_end_matches('/').to_string(),
+        });
+    }
+    Ok(peers)
+}
+
+/// `10.0.0.2` for `http://10.0.0.2:8787/`.
+fn host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split('/').next().unwrap_or(rest);
+    authority
+        .rsplit_once(':')
+        .map_or(authority, |(host, _)| host)
+}
+
+/// The peer serving a merged-view run id and its id there; None for local runs.
+pub fn route<'a, 'p>(peers: &'p [Peer], id: &'a str) -> Option<(&'p Peer, &'a str)> {
+    peers
+        .iter()
+        .find_map(|peer| Some((peer, peer.local_id(id)?)))
+}
+
+/// Local runs and each peer's runs in one list, newest first like
+/// `list_runs`. Peer runs are renamed `<node>/<id>`.
+pub fn merge_runs(
+    local: Vec<RunSummary>,
+    remote: Vec<(&Peer, Vec<RunSummary>)>,
+) -> Vec<RunSummary> {
+    let mut runs = local;
+    for (peer, peer_runs) in remote {
+        runs.extend(peer_runs.into_iter().map(|run| RunSummary {
+            id: format!("{}/{}", peer.node, run.id),
+            ..run
+        }));
+    }
+    runs.sort_by(|a, b| match b.last_updated_unix.cmp(&a.last_updated_unix) {
+        Ordering::Equal => a.id.cmp(&b.id),
+        other => other,
+    });
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge_runs, parse_peers, route};
+    use og_core::query::RunSummary;
+
+    fn run(id: &str, last_updated_unix: u64) -> RunSummary {
+        RunSummary {
+            id: id.to_string(),
+            path: format!("runs/{id}"),
+            metric_count: 1,
+            event_count: 1,
+            max_step: 1,
+            status: "running".to_string(),
+            last_updated_unix: Some(last_updated_unix),
+            size_bytes: None,
+        }
+    }
+
+    #[test]
+    fn peers_parse_and_route_node_qualified_runs() {
+        let peers =
+            parse_peers(" node1=http://10.0.0.2:8787/ , https://gpu-b.local:9000").expect("parse");
+        assert_eq!(peers[0].node, "node1");
+        assert_eq!(peers[0].url, "http://10.0.0.2:8787");
+        assert_eq!(peers[1].node, "gpu-b.local");
+        assert!(parse_peers("").expect("empty").is_empty());
+        assert!(
+            parse_peers("node1=10.0.0.2:8787").is_err(),
+            "needs a scheme"
+        );
+        assert!(
+            parse_peers("a=http://x,a=http://y").is_err(),
+            "duplicate node"
+        );
+
+        let (peer, id) = route(&peers, "node1/rank-1").expect("peer run");
+        assert_eq!((peer.node.as_str(), id), ("node1", "rank-1"));
+        assert!(route(&peers, "node10/rank-1").is_none());
+        assert!(route(&peers, "local-run").is_none());
+
+        let merged = merge_runs(
+            vec![run("rank-0", 20)],
+            vec![(&peers[0], vec![run("rank-1", 30), run("rank-0", 10)])],
+        );
+        let ids: Vec<&str> = merged.iter().map(|run| run.id.as_str()).collect();
+        assert_eq!(ids, ["node1/rank-1", "rank-0", "node1/rank-0"]);
+    }
+}
@@ -1,6 +1,17 @@
 use anyhow::Result;
 
+pub mod auth;
+pub mod federation;
+pub mod mdns;
+pub mod metrics_jsonl;
+pub mod otlp;
+
 pub const DEFAULT_BIND_ADDR: &str = "127.0.0.1:8787";
+/// Overrides `DEFAULT_BIND_ADDR`.
+pub const BIND_ADDR_ENV: &str = "OGD_BIND_ADDR";
+/// Runs directory whose projects ogd serves and advertises.
+pub const RUNS_DIR_ENV: &str = "OGD_RUNS_DIR";
+pub const DEFAULT_RUNS_DIR: &str = "runs/";
 
 pub async fn start(bind_addr: &str) -> Result<()> {
     tracing::info!(%bind_addr, "ogd placeholder started");
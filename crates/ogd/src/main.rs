@@ -1,15 +1,59 @@
 use anyhow::Result;
+use tracing::Instrument;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    tracing_subscriber::fmt()
-        .with_env_filter(
+    let otlp = ogd::otlp::OtlpExporter::from_env();
+    tracing_subscriber::registry()
+        .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
         )
+        .with(tracing_subscriber::fmt::layer())
+        .with(otlp.clone().map(ogd::otlp::SpanLayer::new))
         .init();
 
+    let bind_addr =
+        std::env::var(ogd::BIND_ADDR_ENV).unwrap_or_else(|_| ogd::DEFAULT_BIND_ADDR.to_string());
+    let auth = ogd::auth::AuthConfig::from_env()?;
+    auth.check_bind(&bind_addr)?;
+    tracing::info!(
+        tokens = auth.tokens.len(),
+        read_only = auth.read_only,
+        "access control loaded"
+    );
+    let peers = ogd::federation::from_env()?;
+    for peer in &peers {
+        tracing::info!(node = %peer.node, url = %peer.url, "federation peer registered");
+    }
+
+    // Loopback binds are unreachable from the LAN; nothing to advertise.
+    let socket_addr: std::net::SocketAddr = bind_addr.parse()?;
+    let mdns_enabled = std::env::var(ogd::mdns::ENABLE_ENV).map_or(true, |value| value != "0");
+    let mut advertising = false;
+    if mdns_enabled && !socket_addr.ip().is_loopback() {
+        let runs_dir =
+            std::env::var(ogd::RUNS_DIR_ENV).unwrap_or_else(|_| ogd::DEFAULT_RUNS_DIR.to_string());
+        let advertisement = ogd::mdns::Advertisement::new(socket_addr, runs_dir.into());
+        match ogd::mdns::advertise(advertisement) {
+            Ok(_) => advertising = true,
+            Err(err) => tracing::warn!("mDNS advertising disabled: {err:#}"),
+        }
+    }
+
     // Ensure the trackio-rs crate is linked and ready for future integration.
     let _ = std::any::type_name::<trackio_rs::Client>();
-    ogd::start(ogd::DEFAULT_BIND_ADDR).await
+    let result = ogd::start(&bind_addr)
+        .instrument(tracing::info_span!("ogd", %bind_addr))
+        .await;
+    // The advertisement lives as long as the process; keep answering.
+    if result.is_ok() && advertising {
+        tokio::signal::ctrl_c().await?;
+    }
+    if let Some(otlp) = otlp {
+        otlp.flush(std::time::Duration::from_secs(5));
+    }
+    result
 }
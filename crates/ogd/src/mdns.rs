@@ -0,0 +1,322 @@
+//! mDNS/DNS-SD advertisement, so `og discover` finds ogd instances on the
+//! LAN. ogd answers PTR queries for `_ogd._tcp.local` with its instance's
+//! SRV, TXT (`version`, `host`, `projects`) and A records. Queries from a
+//! port other than 5353, as `og discover` sends, get a unicast reply
+//! (RFC 6762 legacy unicast); others are answered on the multicast group.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::path::PathBuf;
+use std::thread::JoinHandle;
+
+use anyhow::{Context, Result};
+use og_core::dns::{
+    CLASS_IN, TYPE_A, TYPE_ANY, TYPE_PTR, TYPE_SRV, TYPE_TXT, put_name, put_u16, read_name,
+    read_u16,
+};
+use socket2::{Domain, Protocol, Socket, Type};
+
+pub use og_core::dns::{MDNS_ADDR, MDNS_PORT, SERVICE};
+
+/// `0` turns advertising off.
+pub const ENABLE_ENV: &str = "OGD_MDNS";
+const TTL_SECS: u32 = 120;
+/// Longest single TXT string.
+const TXT_MAX: usize = 255;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Advertisement {
+    pub host: String,
+    pub port: u16,
+    /// Address put in the A record; unspecified means the interface that
+    /// routes to the multicast group.
+    pub addr: Ipv4Addr,
+    pub version: String,
+    /// Runs directory whose subdirectories are listed as projects, read
+    /// again for every answer so new projects show up.
+    pub runs_dir: PathBuf,
+}
+
+impl Advertisement {
+    pub fn new(bind_addr: SocketAddr, runs_dir: PathBuf) -> Self {
+        let addr = match bind_addr.ip() {
+            IpAddr::V4(addr) => addr,
+            IpAddr::V6(_) => Ipv4Addr::UNSPECIFIED,
+        };
+        Advertisement {
+            host: hostname(),
+            port: bind_addr.port(),
+            addr,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            runs_dir,
+        }
+    }
+
+    /// `<host>-<port>`, one DNS label, so several ogds on a box differ.
+    pub fn instance(&self) -> String {
+        format!("{}-{}", label(&self.host), self.port)
+    }
+
+    fn projects(&self) -> Vec<String> {
+        og_core::query::list_projects(&self.runs_dir)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|project| project.name)
+            .filter(|name| !name.starts_with('.'))
+            .collect()
+    }
+
+    /// The advertisement's records as a DNS response. `query` is the id and
+    /// question section of a legacy unicast query, echoed back as required.
+    fn response(&self, query: Option<(u16, &[u8])>, addr: Ipv4Addr) -> Vec<u8> {
+        let (id, question) = query.unwrap_or((0, &[]));
+        let instance = format!("{}.{SERVICE}", self.instance());
+        let target = format!("{}.local", label(&self.host));
+        let mut out = Vec::with_capacity(512);
+        put_u16(&mut out, id);
+        // Response, authoritative.
+        put_u16(&mut out, 0x8400);
+        put_u16(&mut out, u16::from(!question.is_empty()));
+        // PTR answer; SRV, TXT and A as additional records.
+        put_u16(&mut out, 1);
+        put_u16(&mut out, 0);
+        put_u16(&mut out, 3);
+        out.extend_from_slice(question);
+
+        let mut data = Vec::new();
+        put_name(&mut data, &instance);
+        put_record(&mut out, SERVICE, TYPE_PTR, &data);
+
+        data.clear();
+        put_u16(&mut data, 0);
+        put_u16(&mut data, 0);
+        put_u16(&mut data, self.port);
+        put_name(&mut data, &target);
+        put_record(&mut out, &instance, TYPE_SRV, &data);
+
+        data.clear();
+        for entry in [
+            format!("version={}", self.version),
+            format!("host={}", self.host),
+            projects_entry(&self.projects()),
+        ] {
+            let bytes = &entry.as_bytes()[..entry.len().min(TXT_MAX)];
+            data.push(bytes.len() as u8);
+            data.extend_from_slice(bytes);
+        }
+        put_record(&mut out, &instance, TYPE_TXT, &data);
+
+        put_record(&mut out, &target, TYPE_A, &addr.octets());
+        out
+    }
+
+    fn address(&self) -> Ipv4Addr {
+        if !self.addr.is_unspecified() {
+            return self.addr;
+        }
+        // Connecting a UDP socket sends nothing; it only picks the route.
+        UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+            .and_then(|socket| {
+                socket.connect((MDNS_ADDR, MDNS_PORT))?;
+                socket.local_addr()
+            })
+            .ok()
+            .and_then(|addr| match addr.ip() {
+                IpAddr::V4(addr) => Some(addr),
+                IpAddr::V6(_) => None,
+            })
+            .unwrap_or(Ipv4Addr::LOCALHOST)
+    }
+}
+
+/// Announce `advertisement` once and answer queries for it on a background
+/// thread for the life of the process.
+pub fn advertise(advertisement: Advertisement) -> Result<JoinHandle<()>> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    // Other responders (avahi, Bonjour) share the port.
+    socket.set_reuse_address(true)?;
+    #[cfg(unix)]
+    socket.set_reuse_port(true)?;
+    socket
+        .bind(&SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, MDNS_PORT).into())
+        .with_context(|| format!("failed to bind mDNS port {MDNS_PORT}"))?;
+    let socket: UdpSocket = socket.into();
+    socket
+        .join_multicast_v4(&MDNS_ADDR, &Ipv4Addr::UNSPECIFIED)
+        .context("failed to join the mDNS multicast group")?;
+    let group = SocketAddr::from((MDNS_ADDR, MDNS_PORT));
+    socket.send_to(
+        &advertisement.response(None, advertisement.address()),
+        group,
+    )?;
+    tracing::info!(instance = %advertisement.instance(), "advertising ogd over mDNS");
+
+    let handle = std::thread::Builder::new()
+        .name("ogd-mdns".into())
+        .spawn(move || {
+            let mut packet = [0u8; 9000];
+            loop {
+                let Ok((len, from)) = socket.recv_from(&mut packet) else {
+                    continue;
+                };
+                let Some((id, question)) = service_query(&packet[..len]) else {
+                    continue;
+                };
+                let addr = advertisement.address();
+                let sent = if from.port() == MDNS_PORT {
+                    socket.send_to(&advertisement.response(None, addr), group)
+                } else {
+                    socket.send_to(&advertisement.response(Some((id, question)), addr), from)
+                };
+                if let Err(err) = sent {
+                    tracing::debug!(%from, %err, "mDNS reply failed");
+                }
+            }
+        })?;
+    Ok(handle)
+}
+
+/// Id and question section of a query asking for `SERVICE`.
+fn service_query(packet: &[u8]) -> Option<(u16, &[u8])> {
+    let id = read_u16(packet, 0)?;
+    let flags = read_u16(packet, 2)?;
+    if flags & 0x8000 != 0 {
+        return None;
+    }
+    let questions = read_u16(packet, 4)?;
+    let mut offset = 12;
+    let mut asked = false;
+    for _ in 0..questions {
+        let (name, next) = read_name(packet, offset)?;
+        let kind = read_u16(packet, next)?;
+        offset = next + 4;
+        asked |= name.eq_ignore_ascii_case(SERVICE) && matches!(kind, TYPE_PTR | TYPE_ANY);
+    }
+    if !asked {
+        return None;
+    }
+    Some((id, packet.get(12..offset)?))
+}
+
+fn hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| {
+            let output = std::process::Command::new("hostname").output().ok()?;
+            String::from_utf8(output.stdout).ok()
+        })
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "ogd".to_string())
+}
+
+/// `name` cut to one DNS label of letters, digits and dashes.
+fn label(name: &str) -> String {
+    let first = name.split('.').next().unwrap_or(name);
+    let label: String = first
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .take(50)
+        .collect();
+    if label.is_empty() {
+        "ogd".into()
+    } else {
+        label
+    }
+}
+
+/// `projects=a,b,...`, dropping whole names past the TXT string limit.
+fn projects_entry(projects: &[String]) -> String {
+    let mut entry = String::from("projects=");
+    for project in projects {
+        let sep = usize::from(!entry.ends_with('='));
+        if entry.len() + sep + project.len() > TXT_MAX {
+            break;
+        }
+        if sep == 1 {
+            entry.push(',');
+        }
+        entry.push_str(project);
+    }
+    entry
+}
+
+fn put_record(out: &mut Vec<u8>, name: &str, kind: u16, data: &[u8]) {
+    put_name(out, name);
+    put_u16(out, kind);
+    put_u16(out, CLASS_IN);
+    out.extend_from_slice(&TTL_SECS.to_be_bytes());
+    put_u16(out, data.len() as u16);
+    out.extend_from_slice(data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Advertisement, SERVICE, TYPE_PTR, TYPE_SRV, TYPE_TXT, put_name, put_u16, read_name,
+        read_u16, service_query,
+    };
+    use std::net::Ipv4Addr;
+
+    fn query(id: u16, name: &str, kind: u16) -> Vec<u8> {
+        let mut packet = Vec::new();
+        for value in [id, 0, 1, 0, 0, 0] {
+            put_u16(&mut packet, value);
+        }
+        put_name(&mut packet, name);
+        put_u16(&mut packet, kind);
+        put_u16(&mut packet, 1);
+        packet
+    }
+
+    #[test]
+    fn only_queries_for_the_service_are_answered() {
+        let asked = query(7, "_OGD._tcp.local", TYPE_PTR);
+        let (id, question) = service_query(&asked).expect("service query");
+        assert_eq!(id, 7);
+        assert_eq!(question, &asked[12..]);
+        assert_eq!(service_query(&query(7, "_http._tcp.local", TYPE_PTR)), None);
+        assert_eq!(service_query(&query(7, SERVICE, TYPE_TXT)), None);
+        let mut response = asked.clone();
+        response[2] = 0x84;
+        assert_eq!(service_query(&response), None);
+        assert_eq!(service_query(&asked[..20]), None);
+    }
+
+    #[test]
+    fn responses_carry_instance_port_version_and_projects() {
+        let runs = std::env::temp_dir().join(format!("ogd-mdns-{}", std::process::id()));
+        for project in ["vision", "nlp", ".cache"] {
+            std::fs::create_dir_all(runs.join(project)).expect("project dir");
+        }
+        let advertisement = Advertisement {
+            host: "gpu box.lab".into(),
+            port: 8787,
+            addr: Ipv4Addr::UNSPECIFIED,
+            version: "0.1.7".into(),
+            runs_dir: runs.clone(),
+        };
+        assert_eq!(advertisement.instance(), "gpu-box-8787");
+        let asked = query(9, SERVICE, TYPE_PTR);
+        let response = advertisement.response(Some((9, &asked[12..])), Ipv4Addr::new(10, 0, 0, 12));
+        std::fs::remove_dir_all(&runs).ok();
+
+        assert_eq!(read_u16(&response, 0), Some(9));
+        assert_eq!(read_u16(&response, 4), Some(1));
+        // The echoed question, then the PTR answer naming the instance.
+        let (ptr_name, ptr) = read_name(&response, asked.len()).expect("ptr name");
+        assert_eq!(ptr_name, SERVICE);
+        assert_eq!(read_u16(&response, ptr), Some(TYPE_PTR));
+        let (instance, srv) = read_name(&response, ptr + 10).expect("instance");
+        assert_eq!(instance, format!("gpu-box-8787.{SERVICE}"));
+        let (_, srv_fields) = read_name(&response, srv).expect("srv name");
+        assert_eq!(read_u16(&response, srv_fields), Some(TYPE_SRV));
+        assert_eq!(read_u16(&response, srv_fields + 14), Some(8787));
+
+        let text = String::from_utf8_lossy(&response);
+        assert!(text.contains("version=0.1.7"));
+        assert!(text.contains("host=gpu box.lab"));
+        assert!(text.contains("projects=nlp,vision"));
+        assert!(response.ends_with(&[10, 0, 0, 12]));
+    }
+}
@@ -0,0 +1,888 @@
+use anyhow::{Context, Result, anyhow, bail};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, SyncSender};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use og_core::run_meta;
+
+use crate::otlp::{self, AttrValue, OtlpExporter, SpanData};
+
+/// File name ogtui looks for next to (or instead of) `.tfevents` files.
+pub const FILE_NAME: &str = "metrics.jsonl";
+/// Run metadata sidecar ogtui reads per-metric display hints from.
+pub const META_FILE_NAME: &str = run_meta::META_FILE_NAME;
+/// Attributes `describe_metric` accepts.
+const METRIC_ATTRS: [&str; 6] = [
+    "unit",
+    "higher_is_better",
+    "scale",
+    "display_name",
+    "bounds",
+    "card",
+];
+/// How often `append` refreshes the heartbeat on its own.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+/// How long `finish` waits for exported points to reach the collector.
+const FINISH_EXPORT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Buffered lines are handed to the writer thread once they reach this many
+/// bytes...
+pub const BATCH_BYTES: usize = 256 * 1024;
+/// ...or once the oldest of them has waited this long.
+pub const BATCH_DELAY: Duration = Duration::from_millis(500);
+/// Batches queued for the writer thread before `append` waits for it.
+const QUEUE_LEN: usize = 16;
+
+/// How a run ended, recorded by `MetricsWriter::finish`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunEnd {
+    Finished,
+    Crashed,
+    Killed,
+}
+
+impl RunEnd {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RunEnd::Finished => "finished",
+            RunEnd::Crashed => "crashed",
+            RunEnd::Killed => "killed",
+        }
+    }
+}
+
+/// One kept sample of a decimated tag.
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    step: i64,
+    wall_time: f64,
+    value: f64,
+}
+
+/// Samples of a decimated tag since its current window opened. The first is
+/// written when it arrives; the lowest and highest are written when the
+/// window closes, so spikes survive the thinning.
+#[derive(Debug)]
+struct Window {
+    first: Sample,
+    low: Sample,
+    high: Sample,
+}
+
+impl Window {
+    fn open(sample: Sample) -> Self {
+        Self {
+            first: sample,
+            low: sample,
+            high: sample,
+        }
+    }
+
+    fn add(&mut self, sample: Sample) {
+        if sample.value < self.low.value {
+            self.low = sample;
+        }
+        if sample.value > self.high.value {
+            self.high = sample;
+        }
+    }
+
+    /// The extremes not written yet, in step order.
+    fn extremes(&self) -> Vec<Sample> {
+        let mut extremes = Vec::with_capacity(2);
+        for sample in [self.low, self.high] {
+            let written =
+                |other: &Sample| other.step == sample.step && other.wall_time == sample.wall_time;
+            if !written(&self.first) && !extremes.iter().any(written) {
+                extremes.push(sample);
+            }
+        }
+        extremes.sort_by_key(|sample| sample.step);
+        extremes
+    }
+}
+
+/// Ingest-time thinning for one high-frequency tag, set with
+/// `MetricsWriter::decimate`.
+#[derive(Debug)]
+struct Decimation {
+    min_interval: f64,
+    window: Option<Window>,
+}
+
+/// Appends one JSON object per step to a `metrics.jsonl` store:
+/// `{"step": 12, "wall_time": 1712000000.5, "metrics": {"train/loss": 0.42}}`.
+/// Structured events share the file, one per line:
+/// `{"step": 12, "wall_time": 1712000000.5, "event": {"type": "eval_result", ...}}`.
+pub struct MetricsWriter {
+    run_dir: PathBuf,
+    file: FileWriter,
+    /// Whole lines not yet handed to the writer thread.
+    pending: Vec<u8>,
+    pending_since: Option<Instant>,
+    batch_bytes: usize,
+    batch_delay: Duration,
+    last_heartbeat: Option<Instant>,
+    decimated: HashMap<String, Decimation>,
+    exporter: Option<Export>,
+}
+
+enum Job {
+    Write(Vec<u8>),
+    /// Sync the file and report the first write error since the last sync.
+    Sync(SyncSender<io::Result<()>>),
+}
+
+/// Owns `metrics.jsonl` on a background thread, so writing and syncing
+/// never stall the training loop. Dropping it writes what is queued, syncs
+/// the file and joins the thread.
+struct FileWriter {
+    tx: Option<SyncSender<Job>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl FileWriter {
+    fn spawn(mut file: File) -> Result<Self> {
+        let (tx, rx) = mpsc::sync_channel(QUEUE_LEN);
+        let handle = std::thread::Builder::new()
+            .name("ogd-metrics".to_string())
+            .spawn(move || {
+                let mut failed = None;
+                for job in rx {
+                    match job {
+                        // Each batch is whole lines in one write, so readers
+                        // polling the file never see half a line.
+                        Job::Write(batch) => {
+                            if failed.is_none()
+                                && let Err(err) = file.write_all(&batch)
+                            {
+                                failed = Some(err);
+                            }
+                        }
+                        Job::Sync(ack) => {
+                            let result = match failed.take() {
+                                Some(err) => Err(err),
+                                None => file.sync_data(),
+                            };
+                            let _ = ack.send(result);
+                        }
+                    }
+                }
+                if let Err(err) = file.sync_data() {
+                    tracing::warn!("syncing metrics.jsonl: {err}");
+                }
+            })
+            .context("spawning the metrics writer thread")?;
+        Ok(Self {
+            tx: Some(tx),
+            handle: Some(handle),
+        })
+    }
+
+    fn send(&self, job: Job) -> Result<()> {
+        self.tx
+            .as_ref()
+            .and_then(|tx| tx.send(job).ok())
+            .ok_or_else(|| anyhow!("the metrics.jsonl writer thread has stopped"))
+    }
+
+    fn write(&self, batch: Vec<u8>) -> Result<()> {
+        self.send(Job::Write(batch))
+    }
+
+    /// Wait until everything sent so far is written and synced.
+    fn sync(&self) -> Result<()> {
+        let (ack, done) = mpsc::sync_channel(1);
+        self.send(Job::Sync(ack))?;
+        done.recv()
+            .map_err(|_| anyhow!("the metrics.jsonl writer thread has stopped"))?
+            .context("writing metrics.jsonl")
+    }
+}
+
+impl Drop for FileWriter {
+    fn drop(&mut self) {
+        drop(self.tx.take());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Where `MetricsWriter::export_to` sends the run's points.
+struct Export {
+    exporter: OtlpExporter,
+    run: String,
+    trace_id: u128,
+    opened_unix_nano: u64,
+}
+
+impl MetricsWriter {
+    /// Open `<run_dir>/metrics.jsonl` for appending, creating the directory if needed.
+    pub fn open(run_dir: &Path) -> Result<Self> {
+        fs::create_dir_all(run_dir)
+            .with_context(|| format!("creating run directory {}", run_dir.display()))?;
+        let path = run_dir.join(FILE_NAME);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("opening {}", path.display()))?;
+        Ok(Self {
+            run_dir: run_dir.to_path_buf(),
+            file: FileWriter::spawn(file)?,
+            pending: Vec::new(),
+            pending_since: None,
+            batch_bytes: BATCH_BYTES,
+            batch_delay: BATCH_DELAY,
+            last_heartbeat: None,
+            decimated: HashMap::new(),
+            exporter: None,
+        })
+    }
+
+    /// Also send every written point to an OpenTelemetry collector, tagged
+    /// with the run directory's name as `og.run`. `finish` adds a `run` span
+    /// from this call to the end of the run. See `otlp::OtlpExporter::from_env`.
+    pub fn export_to(&mut self, exporter: OtlpExporter) {
+        let run = self
+            .run_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.run_dir.display().to_string());
+        self.exporter = Some(Export {
+            exporter,
+            run,
+            trace_id: otlp::random_trace_id(),
+            opened_unix_nano: otlp::now_unix_nano(),
+        });
+    }
+
+    /// Hand lines to the writer thread once `max_bytes` are buffered or the
+    /// oldest has waited `max_delay`, instead of `BATCH_BYTES` and
+    /// `BATCH_DELAY`. `0` bytes hands over every line as it is written.
+    pub fn batch(&mut self, max_bytes: usize, max_delay: Duration) {
+        self.batch_bytes = max_bytes;
+        self.batch_delay = max_delay;
+    }
+
+    /// Thin `tag` as it is appended: at most one sample per `min_interval`
+    /// of wall time is written as it arrives, plus the lowest and highest
+    /// value of each interval once it ends. Meant for sensor-like tags logged
+    /// at hundreds of Hz, which would otherwise grow the store without
+    /// bound. Non-finite values are always written.
+    pub fn decimate(&mut self, tag: &str, min_interval: Duration) {
+        self.decimated.insert(
+            tag.to_string(),
+            Decimation {
+                min_interval: min_interval.as_secs_f64(),
+                window: None,
+            },
+        );
+    }
+
+    /// Write one step. Lines are buffered and written in batches from a
+    /// background thread (see `batch`); a line is never split across writes,
+    /// so readers polling the file never see half of it. Samples of
+    /// decimated tags that fall inside their current interval are held back
+    /// (see `decimate`).
+    pub fn append(&mut self, step: i64, wall_time: f64, metrics: &[(&str, f64)]) -> Result<()> {
+        let mut kept = serde_json::Map::new();
+        let mut closed = Vec::new();
+        for &(tag, value) in metrics {
+            let Some(decimation) = self.decimated.get_mut(tag).filter(|_| value.is_finite()) else {
+                kept.insert(tag.to_string(), serde_json::json!(value));
+                continue;
+            };
+            let sample = Sample {
+                step,
+                wall_time,
+                value,
+            };
+            match &mut decimation.window {
+                // A clock that steps back (a resumed run) opens a new interval.
+                Some(window)
+                    if (0.0..decimation.min_interval)
+                        .contains(&(wall_time - window.first.wall_time)) =>
+                {
+                    window.add(sample);
+                }
+                window => {
+                    if let Some(ended) = window.replace(Window::open(sample)) {
+                        closed.push((tag, ended));
+                    }
+                    kept.insert(tag.to_string(), serde_json::json!(value));
+                }
+            }
+        }
+        for (tag, window) in closed {
+            self.write_extremes(tag, &window)?;
+        }
+        if !kept.is_empty() {
+            self.write_metrics(step, wall_time, kept)?;
+        }
+        self.heartbeat_if_due()
+    }
+
+    /// Write the held-back extremes of every decimated tag's current
+    /// interval and every buffered line, and wait until they are synced to
+    /// disk. Errors from earlier batches are reported here. `finish` and
+    /// dropping the writer do this too.
+    pub fn flush(&mut self) -> Result<()> {
+        let open: Vec<(String, Window)> = self
+            .decimated
+            .iter_mut()
+            .filter_map(|(tag, decimation)| Some((tag.clone(), decimation.window.take()?)))
+            .collect();
+        for (tag, window) in open {
+            self.write_extremes(&tag, &window)?;
+        }
+        self.write_pending()?;
+        self.file.sync()
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> Result<()> {
+        self.pending.extend_from_slice(line);
+        let since = *self.pending_since.get_or_insert_with(Instant::now);
+        if self.pending.len() >= self.batch_bytes || since.elapsed() >= self.batch_delay {
+            self.write_pending()?;
+        }
+        Ok(())
+    }
+
+    fn write_pending(&mut self) -> Result<()> {
+        self.pending_since = None;
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        self.file.write(std::mem::take(&mut self.pending))
+    }
+
+    fn write_extremes(&mut self, tag: &str, window: &Window) -> Result<()> {
+        for sample in window.extremes() {
+            let mut metrics = serde_json::Map::new();
+            metrics.insert(tag.to_string(), serde_json::json!(sample.value));
+            self.write_metrics(sample.step, sample.wall_time, metrics)?;
+        }
+        Ok(())
+    }
+
+    fn write_metrics(
+        &mut self,
+        step: i64,
+        wall_time: f64,
+        metrics: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<()> {
+        if let Some(export) = &self.exporter {
+            for (tag, value) in &metrics {
+                if let Some(value) = value.as_f64() {
+                    export
+                        .exporter
+                        .metric(&export.run, tag, step, wall_time, value);
+                }
+            }
+        }
+        let mut line = serde_json::to_vec(&serde_json::json!({
+            "step": step,
+            "wall_time": wall_time,
+            "metrics": metrics,
+        }))?;
+        line.push(b'\n');
+        self.write_line(&line)
+    }
+
+    /// Write one structured event of type `kind` (`eval_result`,
+    /// `generation_sample`, ...). `payload` must be a JSON object; its fields
+    /// are stored next to `type`.
+    pub fn append_event(
+        &mut self,
+        step: i64,
+        wall_time: f64,
+        kind: &str,
+        payload: serde_json::Value,
+    ) -> Result<()> {
+        let serde_json::Value::Object(mut event) = payload else {
+            bail!("event payload must be a JSON object");
+        };
+        event.insert("type".to_string(), serde_json::json!(kind));
+        let mut line = serde_json::to_vec(&serde_json::json!({
+            "step": step,
+            "wall_time": wall_time,
+            "event": event,
+        }))?;
+        line.push(b'\n');
+        self.write_line(&line)?;
+        self.heartbeat_if_due()
+    }
+
+    /// Record display hints for one metric in the run's `og_meta.json`:
+    /// `{"unit": "%", "higher_is_better": true, "scale": "log", "display_name": "Val accuracy"}`,
+    /// plus `"bounds": [0, 1]` and `"card": "gauge"` or `"line"`.
+    /// Attributes merge into those already recorded; `null` clears one. The
+    /// rest of the sidecar is left as it is.
+    pub fn describe_metric(&self, tag: &str, attrs: serde_json::Value) -> Result<()> {
+        let serde_json::Value::Object(attrs) = attrs else {
+            bail!("metric attributes must be a JSON object");
+        };
+        if let Some(key) = attrs
+            .keys()
+            .find(|key| !METRIC_ATTRS.contains(&key.as_str()))
+        {
+            bail!(
+                "unknown metric attribute {key:?} (expected one of {})",
+                METRIC_ATTRS.join(", ")
+            );
+        }
+        self.update_meta(|meta_fields| {
+            let metrics = meta_fields
+                .entry("metrics")
+                .or_insert_with(|| serde_json::json!({}));
+            let Some(entry) = metrics
+                .as_object_mut()
+                .map(|metrics| metrics.entry(tag).or_insert_with(|| serde_json::json!({})))
+                .and_then(serde_json::Value::as_object_mut)
+            else {
+                bail!("malformed \"metrics\" field");
+            };
+            for (key, value) in attrs {
+                if value.is_null() {
+                    entry.remove(&key);
+                } else {
+                    entry.insert(key, value);
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Record in `og_meta.json` that the run is alive:
+    /// `"status": {"state": "running", "heartbeat_unix": 1712000000, "pid": 4242}`.
+    /// ogtui trusts a fresh heartbeat over file times, so a run that logs a
+    /// step every ten minutes still shows as running. `append` beats on its
+    /// own at most every `HEARTBEAT_INTERVAL`; call this from a timer when
+    /// steps are further apart than that. Buffered lines go out with it.
+    pub fn heartbeat(&mut self) -> Result<()> {
+        self.write_pending()?;
+        self.last_heartbeat = Some(Instant::now());
+        self.write_status("running", None)
+    }
+
+    /// Record how the run ended, with the exit code if there is one.
+    pub fn finish(&mut self, end: RunEnd, exit_code: Option<i32>) -> Result<()> {
+        self.flush()?;
+        if let Some(export) = &self.exporter {
+            let mut attributes = vec![
+                ("og.run".to_string(), AttrValue::Str(export.run.clone())),
+                (
+                    "og.state".to_string(),
+                    AttrValue::Str(end.as_str().to_string()),
+                ),
+            ];
+            if let Some(code) = exit_code {
+                attributes.push(("og.exit_code".to_string(), AttrValue::Int(code.into())));
+            }
+            export.exporter.span(SpanData {
+                trace_id: export.trace_id,
+                span_id: otlp::random_id(),
+                parent_span_id: None,
+                name: "run".to_string(),
+                start_unix_nano: export.opened_unix_nano,
+                end_unix_nano: otlp::now_unix_nano(),
+                attributes,
+                events: Vec::new(),
+                error: (end != RunEnd::Finished).then(|| format!("run {}", end.as_str())),
+            });
+            export.exporter.flush(FINISH_EXPORT_TIMEOUT);
+        }
+        self.write_status(end.as_str(), exit_code)
+    }
+
+    fn heartbeat_if_due(&mut self) -> Result<()> {
+        match self.last_heartbeat {
+            Some(last) if last.elapsed() < HEARTBEAT_INTERVAL => Ok(()),
+            _ => self.heartbeat(),
+        }
+    }
+
+    fn write_status(&self, state: &str, exit_code: Option<i32>) -> Result<()> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        let mut status = serde_json::json!({
+            "state": state,
+            "heartbeat_unix": now,
+            "pid": std::process::id(),
+        });
+        if let Some(code) = exit_code {
+            status["exit_code"] = serde_json::json!(code);
+        }
+        self.update_meta(|meta_fields| {
+            meta_fields.insert("status".to_string(), status);
+            Ok(())
+        })
+    }
+
+    /// Read-modify-write the run's `og_meta.json` under the metadata lock,
+    /// keeping the fields this writer does not touch. The file is replaced in
+    /// one rename.
+    fn update_meta(
+        &self,
+        edit: impl FnOnce(&mut serde_json::Map<String, serde_json::Value>) -> Result<()>,
+    ) -> Result<()> {
+        let _lock = run_meta::lock(&self.run_dir)?;
+        let path = self.run_dir.join(META_FILE_NAME);
+        let mut meta = match fs::read_to_string(&path) {
+            Ok(raw) => {
+                serde_json::from_str(&raw).with_context(|| format!("parsing {}", path.display()))?
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => serde_json::json!({}),
+            Err(err) => return Err(err).with_context(|| format!("reading {}", path.display())),
+        };
+        let Some(meta_fields) = meta.as_object_mut() else {
+            bail!("{} is not a JSON object", path.display());
+        };
+        edit(meta_fields).with_context(|| format!("updating {}", path.display()))?;
+        let tmp = run_meta::tmp_path(&path);
+        fs::write(&tmp, serde_json::to_string_pretty(&meta)?)
+            .with_context(|| format!("writing {}", tmp.display()))?;
+        if let Err(err) = fs::rename(&tmp, &path) {
+            let _ = fs::remove_file(&tmp);
+            return Err(err).with_context(|| format!("writing {}", path.display()));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for MetricsWriter {
+    fn drop(&mut self) {
+        if let Err(err) = self.flush() {
+            tracing::warn!("dropping metrics writer: {err:#}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FILE_NAME, META_FILE_NAME, MetricsWriter, RunEnd};
+    use crate::otlp::{AttrValue, Item, OtlpExporter};
+    use std::fs;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn appends_one_line_per_step() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("ogd-metrics-jsonl-{nonce}"));
+
+        let mut writer = MetricsWriter::open(&dir).expect("open writer");
+        writer
+            .append(1, 100.0, &[("train/loss", 2.5)])
+            .expect("append step 1");
+        drop(writer);
+        let mut writer = MetricsWriter::open(&dir).expect("reopen writer");
+        writer
+            .append(2, 101.0, &[("train/loss", 2.0), ("lr", 0.001)])
+            .expect("append step 2");
+        writer.flush().expect("flush");
+
+        let raw = fs::read_to_string(dir.join(FILE_NAME)).expect("read store");
+        let lines: Vec<serde_json::Value> = raw
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("valid json line"))
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["step"], 1);
+        assert_eq!(lines[1]["metrics"]["lr"], 0.001);
+        assert_eq!(lines[1]["wall_time"], 101.0);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn appends_structured_events_next_to_metrics() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("ogd-metrics-events-{nonce}"));
+
+        let mut writer = MetricsWriter::open(&dir).expect("open writer");
+        writer
+            .append(1, 100.0, &[("train/loss", 2.5)])
+            .expect("append step 1");
+        writer
+            .append_event(
+                1,
+                100.5,
+                "eval_result",
+                serde_json::json!({"task": "gsm8k", "accuracy": 0.41}),
+            )
+            .expect("append event");
+        assert!(
+            writer
+                .append_event(2, 101.0, "note", serde_json::json!("not an object"))
+                .is_err()
+        );
+        writer.flush().expect("flush");
+
+        let raw = fs::read_to_string(dir.join(FILE_NAME)).expect("read store");
+        let lines: Vec<serde_json::Value> = raw
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("valid json line"))
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1]["step"], 1);
+        assert_eq!(lines[1]["event"]["type"], "eval_result");
+        assert_eq!(lines[1]["event"]["accuracy"], 0.41);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn lines_are_buffered_until_a_batch_fills_or_the_writer_flushes() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("ogd-metrics-batch-{nonce}"));
+        let steps = || -> Vec<i64> {
+            fs::read_to_string(dir.join(FILE_NAME))
+                .expect("read store")
+                .lines()
+                .map(|line| {
+                    let line: serde_json::Value = serde_json::from_str(line).expect("json line");
+                    line["step"].as_i64().expect("step")
+                })
+                .collect()
+        };
+
+        let mut writer = MetricsWriter::open(&dir).expect("open writer");
+        writer.batch(usize::MAX, Duration::from_secs(3600));
+        for step in 1..=2 {
+            writer
+                .append(step, 100.0 + step as f64, &[("train/loss", 1.0)])
+                .expect("append");
+        }
+        writer.flush().expect("flush");
+        assert_eq!(steps(), vec![1, 2]);
+
+        writer
+            .append(3, 103.0, &[("train/loss", 1.0)])
+            .expect("append step 3");
+        writer.flush().expect("flush");
+        writer
+            .append(4, 104.0, &[("train/loss", 1.0)])
+            .expect("append step 4");
+        assert_eq!(steps(), vec![1, 2, 3], "step 4 is still buffered");
+
+        // A full batch goes out without a flush; dropping writes the rest.
+        writer.batch(0, Duration::from_secs(3600));
+        writer
+            .append(5, 105.0, &[("train/loss", 1.0)])
+            .expect("append step 5");
+        writer.batch(usize::MAX, Duration::from_secs(3600));
+        writer
+            .append(6, 106.0, &[("train/loss", 1.0)])
+            .expect("append step 6");
+        drop(writer);
+        assert_eq!(steps(), vec![1, 2, 3, 4, 5, 6]);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn describe_metric_merges_into_the_meta_sidecar() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("ogd-describe-metric-{nonce}"));
+        let writer = MetricsWriter::open(&dir).expect("open writer");
+        fs::write(dir.join(META_FILE_NAME), r#"{"tags": ["baseline"]}"#).expect("seed meta");
+
+        writer
+            .describe_metric(
+                "val/acc",
+                serde_json::json!({"unit": "%", "higher_is_better": true}),
+            )
+            .expect("describe");
+        writer
+            .describe_metric(
+                "val/acc",
+                serde_json::json!({"unit": null, "display_name": "Val accuracy"}),
+            )
+            .expect("describe again");
+        assert!(
+            writer
+                .describe_metric("val/acc", serde_json::json!({"colour": "red"}))
+                .is_err()
+        );
+
+        let raw = fs::read_to_string(dir.join(META_FILE_NAME)).expect("read meta");
+        let meta: serde_json::Value = serde_json::from_str(&raw).expect("valid json");
+        assert_eq!(meta["tags"][0], "baseline");
+        assert_eq!(
+            meta["metrics"]["val/acc"],
+            serde_json::json!({"higher_is_better": true, "display_name": "Val accuracy"})
+        );
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn appending_beats_and_finish_records_the_end() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("ogd-heartbeat-{nonce}"));
+        let mut writer = MetricsWriter::open(&dir).expect("open writer");
+        fs::write(dir.join(META_FILE_NAME), r#"{"tags": ["baseline"]}"#).expect("seed meta");
+        let read_meta = || -> serde_json::Value {
+            let raw = fs::read_to_string(dir.join(META_FILE_NAME)).expect("read meta");
+            serde_json::from_str(&raw).expect("valid json")
+        };
+
+        writer
+            .append(1, 100.0, &[("train/loss", 2.5)])
+            .expect("append step 1");
+        let meta = read_meta();
+        assert_eq!(meta["tags"][0], "baseline");
+        assert_eq!(meta["status"]["state"], "running");
+        assert_eq!(meta["status"]["pid"], std::process::id());
+        assert!(
+            meta["status"]["heartbeat_unix"]
+                .as_u64()
+                .expect("heartbeat")
+                > 0
+        );
+
+        writer.finish(RunEnd::Crashed, Some(137)).expect("finish");
+        let meta = read_meta();
+        assert_eq!(meta["status"]["state"], "crashed");
+        assert_eq!(meta["status"]["exit_code"], 137);
+        // The next step within the interval does not overwrite the end.
+        writer
+            .append(2, 101.0, &[("train/loss", 2.0)])
+            .expect("append step 2");
+        assert_eq!(read_meta()["status"]["state"], "crashed");
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn decimated_tags_keep_one_sample_per_interval_and_the_extremes() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("ogd-decimate-{nonce}"));
+        let mut writer = MetricsWriter::open(&dir).expect("open writer");
+        writer.decimate("sensor/current", Duration::from_millis(100));
+
+        // One second of a 1 kHz sensor, with a spike and a dip mid-interval.
+        for step in 0..1000_i64 {
+            let value = match step {
+                437 => 50.0,
+                612 => -50.0,
+                _ => (step % 10) as f64,
+            };
+            let wall_time = 100.0 + step as f64 / 1000.0;
+            let mut metrics = vec![("sensor/current", value)];
+            if step % 100 == 0 {
+                metrics.push(("train/loss", 1.0));
+            }
+            writer.append(step, wall_time, &metrics).expect("append");
+        }
+        writer
+            .append(1000, 101.5, &[("sensor/current", f64::NAN)])
+            .expect("append nan");
+        drop(writer);
+
+        let raw = fs::read_to_string(dir.join(FILE_NAME)).expect("read store");
+        let mut sensor = Vec::new();
+        let mut losses = 0;
+        for line in raw.lines() {
+            let line: serde_json::Value = serde_json::from_str(line).expect("valid json line");
+            if line["metrics"].get("train/loss").is_some() {
+                losses += 1;
+            }
+            if let Some(value) = line["metrics"].get("sensor/current") {
+                sensor.push((line["step"].as_i64().expect("step"), value.clone()));
+            }
+        }
+        assert_eq!(losses, 10, "other tags are untouched");
+        // Ten intervals: a first sample plus a low and a high each, at most.
+        assert!(sensor.len() <= 31, "{} samples kept", sensor.len());
+        assert!(sensor.contains(&(437, serde_json::json!(50.0))));
+        assert!(sensor.contains(&(612, serde_json::json!(-50.0))));
+        assert!(sensor.contains(&(0, serde_json::json!(0.0))));
+        // NaN is written as null, and never thinned away.
+        assert!(sensor.contains(&(1000, serde_json::Value::Null)));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn exported_runs_forward_written_points_and_end_with_a_run_span() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("ogd-metrics-otlp-{nonce}"));
+        let (exporter, items) = OtlpExporter::channel();
+        // Stands in for the export thread, acknowledging `finish`'s flush.
+        let collector = std::thread::spawn(move || {
+            let mut points = Vec::new();
+            let mut spans = Vec::new();
+            for item in items {
+                match item {
+                    Item::Metric(point) => points.push((point.name, point.step, point.value)),
+                    Item::Span(span) => spans.push(span),
+                    Item::Flush(ack) => ack.send(()).expect("ack flush"),
+                }
+            }
+            (points, spans)
+        });
+
+        let mut writer = MetricsWriter::open(&dir).expect("open writer");
+        writer.export_to(exporter);
+        writer.decimate("sys/power", Duration::from_secs(10));
+        writer
+            .append(1, 100.0, &[("train/loss", 2.5), ("sys/power", 300.0)])
+            .expect("append step 1");
+        // Held back by decimation until `finish` writes the interval's extremes.
+        writer
+            .append(2, 101.0, &[("sys/power", 310.0)])
+            .expect("append step 2");
+        writer.finish(RunEnd::Crashed, Some(137)).expect("finish");
+        drop(writer);
+
+        let (mut points, spans) = collector.join().expect("collector");
+        points.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
+        assert_eq!(
+            points,
+            vec![
+                ("sys/power".to_string(), 1, 300.0),
+                ("sys/power".to_string(), 2, 310.0),
+                ("train/loss".to_string(), 1, 2.5),
+            ]
+        );
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].name, "run");
+        assert_eq!(spans[0].error.as_deref(), Some("run crashed"));
+        assert!(
+            spans[0]
+                .attributes
+                .contains(&("og.exit_code".to_string(), AttrValue::Int(137)))
+        );
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,723 @@
+//! Optional OpenTelemetry export. With `OTEL_EXPORTER_OTLP_ENDPOINT` set
+//! (e.g. `http://collector:4318`), metric points from a `MetricsWriter` and
+//! ogd's own tracing spans are posted to the collector as OTLP/HTTP JSON.
+//! Posting happens in batches on a background thread, so a slow or missing
+//! collector never stalls training; points that do not fit in the queue are
+//! dropped and counted.
+
+use std::collections::BTreeMap;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde_json::{Value, json};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Collector base URL; `/v1/metrics` and `/v1/traces` are appended.
+pub const ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+/// Extra request headers, `key=value` pairs separated by commas.
+pub const HEADERS_ENV: &str = "OTEL_EXPORTER_OTLP_HEADERS";
+pub const SERVICE_NAME_ENV: &str = "OTEL_SERVICE_NAME";
+const DEFAULT_SERVICE_NAME: &str = "ogd";
+/// Items waiting for the background thread before new ones are dropped.
+const QUEUE_LEN: usize = 8192;
+/// Items per request.
+const BATCH_LEN: usize = 512;
+/// Longest a queued item waits before it is posted.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+thread_local! {
+    /// Set on the export thread, whose HTTP client may open spans of its
+    /// own; exporting those would feed the exporter its own traffic.
+    static EXPORTING: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OtlpConfig {
+    pub endpoint: String,
+    pub service_name: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl OtlpConfig {
+    /// Config from the standard OpenTelemetry environment variables; None
+    /// unless an endpoint is set.
+    pub fn from_env() -> Option<Self> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    fn from_lookup(get: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let endpoint = get(ENDPOINT_ENV)?.trim().trim_end_matches('/').to_string();
+        if endpoint.is_empty() {
+            return None;
+        }
+        let headers = get(HEADERS_ENV)
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|pair| {
+                let (key, value) = pair.split_once('=')?;
+                Some((key.trim().to_string(), value.trim().to_string()))
+            })
+            .filter(|(key, _)| !key.is_empty())
+            .collect();
+        let service_name = get(SERVICE_NAME_ENV)
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| DEFAULT_SERVICE_NAME.to_string());
+        Some(Self {
+            endpoint,
+            service_name,
+            headers,
+        })
+    }
+}
+
+/// One metric value, exported as a gauge data point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricPoint {
+    pub run: String,
+    pub name: String,
+    pub step: i64,
+    pub time_unix_nano: u64,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttrValue {
+    Str(String),
+    Int(i64),
+    Double(f64),
+    Bool(bool),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanEvent {
+    pub time_unix_nano: u64,
+    pub name: String,
+    pub attributes: Vec<(String, AttrValue)>,
+}
+
+/// A finished span.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanData {
+    pub trace_id: u128,
+    pub span_id: u64,
+    pub parent_span_id: Option<u64>,
+    pub name: String,
+    pub start_unix_nano: u64,
+    pub end_unix_nano: u64,
+    pub attributes: Vec<(String, AttrValue)>,
+    pub events: Vec<SpanEvent>,
+    /// Set when the span failed; exported as an error status.
+    pub error: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum Item {
+    Metric(MetricPoint),
+    Span(SpanData),
+    /// Post everything queued before this, then acknowledge.
+    Flush(SyncSender<()>),
+}
+
+/// Joins the export thread once the last exporter handle is gone.
+struct Worker(Option<JoinHandle<()>>);
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        if let Some(handle) = self.0.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Handle to the export thread. Clones share the thread; it posts what is
+/// left and exits when the last one is dropped.
+#[derive(Clone)]
+pub struct OtlpExporter {
+    // Declared before `_worker` so the sender is gone before the join.
+    tx: SyncSender<Item>,
+    dropped: Arc<AtomicU64>,
+    _worker: Option<Arc<Worker>>,
+}
+
+impl OtlpExporter {
+    /// An exporter configured from the environment, if an endpoint is set.
+    pub fn from_env() -> Option<Self> {
+        OtlpConfig::from_env().map(Self::start)
+    }
+
+    pub fn start(config: OtlpConfig) -> Self {
+        let (tx, rx) = mpsc::sync_channel(QUEUE_LEN);
+        let handle = std::thread::Builder::new()
+            .name("ogd-otlp".to_string())
+            .spawn(move || {
+                EXPORTING.with(|exporting| exporting.set(true));
+                export_loop(&config, rx);
+            })
+            .expect("spawning the OTLP export thread");
+        Self {
+            tx,
+            dropped: Arc::new(AtomicU64::new(0)),
+            _worker: Some(Arc::new(Worker(Some(handle)))),
+        }
+    }
+
+    /// An exporter whose items land in the returned receiver.
+    #[cfg(test)]
+    pub(crate) fn channel() -> (Self, Receiver<Item>) {
+        let (tx, rx) = mpsc::sync_channel(QUEUE_LEN);
+        let exporter = Self {
+            tx,
+            dropped: Arc::new(AtomicU64::new(0)),
+            _worker: None,
+        };
+        (exporter, rx)
+    }
+
+    /// Queue one metric point. Non-finite values have no OTLP JSON encoding
+    /// and are skipped.
+    pub fn metric(&self, run: &str, name: &str, step: i64, wall_time: f64, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        let time_unix_nano = if wall_time.is_finite() && wall_time > 0.0 {
+            (wall_time * 1e9) as u64
+        } else {
+            now_unix_nano()
+        };
+        self.send(Item::Metric(MetricPoint {
+            run: run.to_string(),
+            name: name.to_string(),
+            step,
+            time_unix_nano,
+            value,
+        }));
+    }
+
+    pub fn span(&self, span: SpanData) {
+        self.send(Item::Span(span));
+    }
+
+    /// Post everything queued so far, waiting up to `timeout` for it.
+    /// Returns whether the export thread got through it in time.
+    pub fn flush(&self, timeout: Duration) -> bool {
+        let (ack, done) = mpsc::sync_channel(1);
+        if self.tx.send(Item::Flush(ack)).is_err() {
+            return false;
+        }
+        done.recv_timeout(timeout).is_ok()
+    }
+
+    /// Items dropped because the queue was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn send(&self, item: Item) {
+        if let Err(TrySendError::Full(_)) = self.tx.try_send(item) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+fn export_loop(config: &OtlpConfig, rx: Receiver<Item>) {
+    let client = match reqwest::blocking::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+    {
+        Ok(client) => client,
+        Err(err) => {
+            tracing::warn!("OTLP export disabled: {err}");
+            return;
+        }
+    };
+    let post = |path: &str, body: Value| {
+        let mut request = client
+            .post(format!("{}{path}", config.endpoint))
+            .header("Content-Type", "application/json")
+            .body(body.to_string());
+        for (key, value) in &config.headers {
+            request = request.header(key, value);
+        }
+        match request
+            .send()
+            .and_then(|response| response.error_for_status())
+        {
+            Ok(_) => {}
+            Err(err) => tracing::warn!("OTLP export to {path} failed: {err}"),
+        }
+    };
+    let mut metrics = Vec::new();
+    let mut spans = Vec::new();
+    let mut last_post = Instant::now();
+    loop {
+        let item = rx.recv_timeout(FLUSH_INTERVAL);
+        let mut ack = None;
+        let closed = match item {
+            Ok(Item::Metric(point)) => {
+                metrics.push(point);
+                false
+            }
+            Ok(Item::Span(span)) => {
+                spans.push(span);
+                false
+            }
+            Ok(Item::Flush(done)) => {
+                ack = Some(done);
+                false
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => true,
+        };
+        let due = ack.is_some() || closed || last_post.elapsed() >= FLUSH_INTERVAL;
+        if due || metrics.len() >= BATCH_LEN {
+            for batch in metrics.chunks(BATCH_LEN) {
+                post("/v1/metrics", metrics_body(&config.service_name, batch));
+            }
+            metrics.clear();
+        }
+        if due || spans.len() >= BATCH_LEN {
+            for batch in spans.chunks(BATCH_LEN) {
+                post("/v1/traces", traces_body(&config.service_name, batch));
+            }
+            spans.clear();
+        }
+        if due {
+            last_post = Instant::now();
+        }
+        if let Some(done) = ack {
+            let _ = done.send(());
+        }
+        if closed {
+            return;
+        }
+    }
+}
+
+fn resource(service_name: &str) -> Value {
+    json!({ "attributes": [attribute("service.name", &AttrValue::Str(service_name.to_string()))] })
+}
+
+fn scope() -> Value {
+    json!({ "name": "ogd", "version": env!("CARGO_PKG_VERSION") })
+}
+
+/// `ExportMetricsServiceRequest` with one gauge per metric name.
+fn metrics_body(service_name: &str, points: &[MetricPoint]) -> Value {
+    let mut by_name: BTreeMap<&str, Vec<Value>> = BTreeMap::new();
+    for point in points {
+        by_name.entry(&point.name).or_default().push(json!({
+            // 64-bit integers are strings in OTLP JSON.
+            "timeUnixNano": point.time_unix_nano.to_string(),
+            "asDouble": point.value,
+            "attributes": [
+                attribute("og.run", &AttrValue::Str(point.run.clone())),
+                attribute("og.step", &AttrValue::Int(point.step)),
+            ],
+        }));
+    }
+    let metrics: Vec<Value> = by_name
+        .into_iter()
+        .map(|(name, data_points)| json!({ "name": name, "gauge": { "dataPoints": data_points } }))
+        .collect();
+    json!({
+        "resourceMetrics": [{
+            "resource": resource(service_name),
+            "scopeMetrics": [{ "scope": scope(), "metrics": metrics }],
+        }]
+    })
+}
+
+/// `ExportTraceServiceRequest`; ids are hex, as OTLP JSON wants them.
+fn traces_body(service_name: &str, spans: &[SpanData]) -> Value {
+    let spans: Vec<Value> = spans
+        .iter()
+        .map(|span| {
+            let events: Vec<Value> = span
+                .events
+                .iter()
+                .map(|event| {
+                    json!({
+                        "timeUnixNano": event.time_unix_nano.to_string(),
+                        "name": event.name,
+                        "attributes": attributes(&event.attributes),
+                    })
+                })
+                .collect();
+            let status = match &span.error {
+                Some(message) => json!({ "code": 2, "message": message }),
+                None => json!({ "code": 0 }),
+            };
+            json!({
+                "traceId": format!("{:032x}", span.trace_id),
+                "spanId": format!("{:016x}", span.span_id),
+                "parentSpanId": span.parent_span_id.map(|id| format!("{id:016x}")).unwrap_or_default(),
+                "name": span.name,
+                // SPAN_KIND_INTERNAL
+                "kind": 1,
+                "startTimeUnixNano": span.start_unix_nano.to_string(),
+                "endTimeUnixNano": span.end_unix_nano.to_string(),
+                "attributes": attributes(&span.attributes),
+                "events": events,
+                "status": status,
+            })
+        })
+        .collect();
+    json!({
+        "resourceSpans": [{
+            "resource": resource(service_name),
+            "scopeSpans": [{ "scope": scope(), "spans": spans }],
+        }]
+    })
+}
+
+fn attributes(pairs: &[(String, AttrValue)]) -> Vec<Value> {
+    pairs
+        .iter()
+        .map(|(key, value)| attribute(key, value))
+        .collect()
+}
+
+fn attribute(key: &str, value: &AttrValue) -> Value {
+    let value = match value {
+        AttrValue::Str(text) => json!({ "stringValue": text }),
+        AttrValue::Int(number) => json!({ "intValue": number.to_string() }),
+        AttrValue::Double(number) if number.is_finite() => json!({ "doubleValue": number }),
+        AttrValue::Double(number) => json!({ "stringValue": number.to_string() }),
+        AttrValue::Bool(flag) => json!({ "boolValue": flag }),
+    };
+    json!({ "key": key, "value": value })
+}
+
+pub fn now_unix_nano() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// A non-zero random id; all-zero trace and span ids are invalid.
+pub fn random_id() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    hasher.write_u64(now_unix_nano());
+    hasher.finish().max(1)
+}
+
+pub fn random_trace_id() -> u128 {
+    (u128::from(random_id()) << 64) | u128::from(random_id())
+}
+
+/// State kept in a tracing span's extensions while it is open.
+struct OpenSpan {
+    trace_id: u128,
+    span_id: u64,
+    parent_span_id: Option<u64>,
+    start_unix_nano: u64,
+    attributes: Vec<(String, AttrValue)>,
+    events: Vec<SpanEvent>,
+    error: Option<String>,
+}
+
+/// Collects tracing fields as OTLP attributes; an event's `message` becomes
+/// its name.
+#[derive(Default)]
+struct Fields {
+    message: Option<String>,
+    attributes: Vec<(String, AttrValue)>,
+}
+
+impl Fields {
+    fn set(&mut self, field: &Field, value: AttrValue) {
+        self.attributes.retain(|(key, _)| key != field.name());
+        self.attributes.push((field.name().to_string(), value));
+    }
+}
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = Some(format!("{value:?}"));
+        } else {
+            self.set(field, AttrValue::Str(format!("{value:?}")));
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = Some(value.to_string());
+        } else {
+            self.set(field, AttrValue::Str(value.to_string()));
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.set(field, AttrValue::Int(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.set(field, AttrValue::Int(value as i64));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.set(field, AttrValue::Double(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.set(field, AttrValue::Bool(value));
+    }
+}
+
+/// Exports closed tracing spans, with the events logged inside them. A span
+/// that logged an ERROR event is exported with an error status.
+pub struct SpanLayer {
+    exporter: OtlpExporter,
+}
+
+impl SpanLayer {
+    pub fn new(exporter: OtlpExporter) -> Self {
+        Self { exporter }
+    }
+}
+
+impl<S> Layer<S> for SpanLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if EXPORTING.with(|exporting| exporting.get()) {
+            return;
+        }
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let parent = span.parent().and_then(|parent| {
+            let extensions = parent.extensions();
+            let open = extensions.get::<OpenSpan>()?;
+            Some((open.trace_id, open.span_id))
+        });
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        span.extensions_mut().insert(OpenSpan {
+            trace_id: parent.map_or_else(random_trace_id, |(trace_id, _)| trace_id),
+            span_id: random_id(),
+            parent_span_id: parent.map(|(_, span_id)| span_id),
+            start_unix_nano: now_unix_nano(),
+            attributes: fields.attributes,
+            events: Vec::new(),
+            error: None,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        let Some(open) = extensions.get_mut::<OpenSpan>() else {
+            return;
+        };
+        let mut fields = Fields {
+            message: None,
+            attributes: std::mem::take(&mut open.attributes),
+        };
+        values.record(&mut fields);
+        open.attributes = fields.attributes;
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.event_span(event) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        let Some(open) = extensions.get_mut::<OpenSpan>() else {
+            return;
+        };
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let name = fields
+            .message
+            .unwrap_or_else(|| event.metadata().name().to_string());
+        if *event.metadata().level() == Level::ERROR {
+            open.error = Some(name.clone());
+        }
+        open.events.push(SpanEvent {
+            time_unix_nano: now_unix_nano(),
+            name,
+            attributes: fields.attributes,
+        });
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(open) = span.extensions_mut().remove::<OpenSpan>() else {
+            return;
+        };
+        self.exporter.span(SpanData {
+            trace_id: open.trace_id,
+            span_id: open.span_id,
+            parent_span_id: open.parent_span_id,
+            name: span.name().to_string(),
+            start_unix_nano: open.start_unix_nano,
+            end_unix_nano: now_unix_nano(),
+            attributes: open.attributes,
+            events: open.events,
+            error: open.error,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ENDPOINT_ENV, HEADERS_ENV, OtlpConfig, OtlpExporter, SERVICE_NAME_ENV, SpanLayer};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::time::Duration;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn config_reads_the_standard_env_vars() {
+        let env = |pairs: &'static [(&'static str, &'static str)]| {
+            move |key: &str| {
+                pairs
+                    .iter()
+                    .find(|(name, _)| *name == key)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        assert_eq!(OtlpConfig::from_lookup(env(&[])), None);
+        let config = OtlpConfig::from_lookup(env(&[
+            (ENDPOINT_ENV, "http://collector:4318/"),
+            (HEADERS_ENV, "x-api-key=abc, x-team = ml"),
+        ]))
+        .expect("endpoint set");
+        assert_eq!(config.endpoint, "http://collector:4318");
+        assert_eq!(config.service_name, "ogd");
+        assert_eq!(
+            config.headers,
+            vec![
+                ("x-api-key".to_string(), "abc".to_string()),
+                ("x-team".to_string(), "ml".to_string()),
+            ]
+        );
+        let named = OtlpConfig::from_lookup(env(&[
+            (ENDPOINT_ENV, "http://c:4318"),
+            (SERVICE_NAME_ENV, "trainer"),
+        ]))
+        .expect("endpoint set");
+        assert_eq!(named.service_name, "trainer");
+    }
+
+    /// Accept `count` requests, replying 200 to each, and hand back their
+    /// paths and JSON bodies.
+    fn collector(count: usize) -> (String, mpsc::Receiver<(String, serde_json::Value)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind collector");
+        let endpoint = format!("http://{}", listener.local_addr().expect("addr"));
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(count) {
+                let mut stream = stream.expect("accept");
+                let mut reader = BufReader::new(stream.try_clone().expect("clone stream"));
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).expect("request line");
+                let path = request_line
+                    .split(' ')
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_string();
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).expect("header");
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        length = value.trim().parse().expect("content length");
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).expect("body");
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                    .expect("reply");
+                let body = serde_json::from_slice(&body).expect("json body");
+                tx.send((path, body)).expect("hand back request");
+            }
+        });
+        (endpoint, rx)
+    }
+
+    #[test]
+    fn exporter_posts_metrics_and_nested_spans() {
+        let (endpoint, requests) = collector(2);
+        let exporter = OtlpExporter::start(OtlpConfig {
+            endpoint,
+            service_name: "ogd-test".to_string(),
+            headers: Vec::new(),
+        });
+        let subscriber = tracing_subscriber::registry().with(SpanLayer::new(exporter.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            let supervise = tracing::info_span!("supervise", run = "r1");
+            let _supervise = supervise.enter();
+            let restart = tracing::info_span!("restart", attempt = 2);
+            let _restart = restart.enter();
+            tracing::error!("training crashed");
+        });
+        exporter.metric("r1", "train/loss", 7, 1_712_000_000.5, 0.25);
+        exporter.metric("r1", "train/loss", 8, 1_712_000_001.0, f64::NAN);
+        assert!(exporter.flush(Duration::from_secs(10)));
+
+        let mut received: Vec<(String, serde_json::Value)> = requests.iter().take(2).collect();
+        received.sort_by(|a, b| a.0.cmp(&b.0));
+        let (metrics_path, metrics) = &received[0];
+        assert_eq!(metrics_path, "/v1/metrics");
+        let resource = &metrics["resourceMetrics"][0];
+        assert_eq!(
+            resource["resource"]["attributes"][0]["value"]["stringValue"],
+            "ogd-test"
+        );
+        let gauge = &resource["scopeMetrics"][0]["metrics"][0];
+        assert_eq!(gauge["name"], "train/loss");
+        let points = gauge["gauge"]["dataPoints"].as_array().expect("points");
+        assert_eq!(points.len(), 1, "NaN is skipped");
+        assert_eq!(points[0]["asDouble"], 0.25);
+        assert_eq!(points[0]["timeUnixNano"], "1712000000500000000");
+        assert_eq!(points[0]["attributes"][1]["value"]["intValue"], "7");
+
+        let (traces_path, traces) = &received[1];
+        assert_eq!(traces_path, "/v1/traces");
+        let spans = traces["resourceSpans"][0]["scopeSpans"][0]["spans"]
+            .as_array()
+            .expect("spans");
+        // Inner spans close first.
+        let (restart, supervise) = (&spans[0], &spans[1]);
+        assert_eq!(restart["name"], "restart");
+        assert_eq!(supervise["name"], "supervise");
+        assert_eq!(restart["traceId"], supervise["traceId"]);
+        assert_eq!(restart["parentSpanId"], supervise["spanId"]);
+        assert_eq!(supervise["parentSpanId"], "");
+        assert_eq!(restart["status"]["code"], 2);
+        assert_eq!(restart["events"][0]["name"], "training crashed");
+        assert_eq!(restart["attributes"][0]["value"]["intValue"], "2");
+        assert_eq!(supervise["attributes"][0]["value"]["stringValue"], "r1");
+        assert_eq!(supervise["status"]["code"], 0);
+        assert_eq!(exporter.dropped(), 0);
+    }
+}
@@ -0,0 +1,145 @@
+//! Reader benchmarks: `cargo bench -p ogtui`.
+//!
+//! Builds a synthetic event file (100k points over 100 tags by default; set
+//! `OG_BENCH_EVENTS` / `OG_BENCH_TAGS` to change) and the same points as a
+//! CSV log, then times the tfevents reader (full parse, `load_run`,
+//! incremental poll) and the CSV importer on them. `og bench parse` times a
+//! file of your own.
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use og_core::{tfevents, tfrecord};
+use prost::Message;
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::hint::black_box;
+use std::path::PathBuf;
+
+// Its unit tests come along under `cargo clippy --all-targets`.
+#[allow(dead_code, unused_imports)]
+#[path = "../src/csv_import.rs"]
+mod csv_import;
+
+/// Records an incremental poll decodes, as `og bench parse` does by default.
+const POLL_POINTS: usize = 1_000;
+
+struct Fixture {
+    events: usize,
+    tags: usize,
+}
+
+impl Fixture {
+    fn from_env() -> Self {
+        let var = |name: &str, default: usize| {
+            env::var(name)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .filter(|value| *value > 0)
+                .unwrap_or(default)
+        };
+        Fixture {
+            events: var("OG_BENCH_EVENTS", 100_000),
+            tags: var("OG_BENCH_TAGS", 100),
+        }
+    }
+
+    fn point(&self, idx: usize) -> (i64, String, f32) {
+        let step = (idx / self.tags) as i64;
+        let value = 1.0 / (1.0 + step as f32) + (idx % 7) as f32 * 1e-3;
+        (step, format!("train/loss_{}", idx % self.tags), value)
+    }
+
+    /// One point per record, like `add_scalar` writes them.
+    fn tfevents(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for idx in 0..self.events {
+            let (step, tag, value) = self.point(idx);
+            let event = tfrecord::Event {
+                wall_time: 1_700_000_000.0 + step as f64 * 0.1,
+                step,
+                file_version: None,
+                summary: Some(tfrecord::Summary {
+                    value: vec![tfrecord::SummaryValue {
+                        tag,
+                        simple_value: Some(value),
+                        histo: None,
+                        audio: None,
+                        tensor: None,
+                        metadata: None,
+                    }],
+                }),
+            };
+            out.extend(tfrecord::encode_record(&event.encode_to_vec()));
+        }
+        out
+    }
+
+    /// A `step,<tag>...` log with one row per step, like Lightning's CSVLogger.
+    fn csv(&self) -> String {
+        let mut out = String::from("step");
+        for idx in 0..self.tags {
+            let _ = write!(out, ",{}", self.point(idx).1);
+        }
+        for idx in 0..self.events {
+            let (step, _, value) = self.point(idx);
+            if idx % self.tags == 0 {
+                let _ = write!(out, "\n{step}");
+            }
+            let _ = write!(out, ",{value}");
+        }
+        out.push('\n');
+        out
+    }
+}
+
+fn tfevents_reader(c: &mut Criterion) {
+    let fixture = Fixture::from_env();
+    let bytes = fixture.tfevents();
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!(
+        "bench-{}x{}.tfevents.0",
+        fixture.events, fixture.tags
+    ));
+    fs::write(&path, &bytes).expect("write fixture");
+    let (records, _) = tfrecord::decode_records(&bytes, true).expect("decode fixture");
+    let tail_start = bytes.len()
+        - records
+            .iter()
+            .rev()
+            .take(POLL_POINTS)
+            .map(|(data, _)| tfrecord::framed_len(data))
+            .sum::<usize>();
+    drop(records);
+
+    let mut group = c.benchmark_group("tfevents");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(fixture.events as u64));
+    group.bench_function("full_parse", |b| {
+        b.iter(|| tfevents::parse_tfevents_file(black_box(&path)).expect("parse"))
+    });
+    group.bench_function("load_run", |b| {
+        b.iter(|| tfevents::load_run(black_box(&path)).expect("load run"))
+    });
+    group.throughput(Throughput::Elements(POLL_POINTS.min(fixture.events) as u64));
+    group.bench_function("incremental_poll", |b| {
+        b.iter(|| {
+            tfevents::parse_events_bytes(black_box(&bytes[tail_start..]), true).expect("poll")
+        })
+    });
+    group.finish();
+}
+
+fn csv_reader(c: &mut Criterion) {
+    let fixture = Fixture::from_env();
+    let text = fixture.csv();
+
+    let mut group = c.benchmark_group("csv");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(fixture.events as u64));
+    group.bench_function("convert", |b| {
+        b.iter(|| csv_import::convert(black_box(&text), "step", None, 0.0).expect("convert"))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, tfevents_reader, csv_reader);
+criterion_main!(benches);
@@ -0,0 +1,30 @@
+//! Arbitrary bytes on either end of the JSON-lines socket protocol: as a
+//! daemon response read by the client and as a request to the query server.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code)]
+#[path = "../../src/query_server.rs"]
+mod query_server;
+#[allow(dead_code)]
+#[path = "../../src/socket_client.rs"]
+mod socket_client;
+
+fn echo(argv: &[String]) -> anyhow::Result<serde_json::Value> {
+    Ok(serde_json::json!(argv))
+}
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(resp) = socket_client::decode_response(data) {
+        let _ = serde_json::from_value::<socket_client::RunStateResponse>(resp);
+    }
+    if let Ok(line) = std::str::from_utf8(data) {
+        let response = query_server::handle_request(line, &echo);
+        assert!(
+            response
+                .get("ok")
+                .is_some_and(serde_json::Value::is_boolean)
+        );
+    }
+});
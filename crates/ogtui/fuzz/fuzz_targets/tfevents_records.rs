@@ -0,0 +1,24 @@
+//! Arbitrary bytes as the contents of a `.tfevents` file: the record reader
+//! and tensor decoding must never panic or allocate past the input's size.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code)]
+#[path = "../../../og-core/src/tfrecord.rs"]
+mod tfrecord;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = tfrecord::decode_records(data, false);
+    let (records, stats) = tfrecord::decode_records(data, true).expect("resync never fails");
+    assert!(stats.skipped_bytes + stats.truncated_bytes <= data.len() as u64);
+    for (_, event) in &records {
+        for value in event.summary.iter().flat_map(|summary| &summary.value) {
+            let _ = value.scalar();
+            let _ = value
+                .tensor
+                .as_ref()
+                .and_then(tfrecord::TensorProto::matrix);
+        }
+    }
+});
@@ -0,0 +1,209 @@
+//! Online anomaly detection on live metric series: a rolling robust z-score
+//! (median and MAD of the recent window, so one spike does not hide the next)
+//! flags outlying points, and a run of them on the same side marks a step
+//! change rather than a one-off spike.
+
+use serde::Serialize;
+
+/// Points before the judged one that form its baseline.
+pub const DEFAULT_WINDOW: usize = 50;
+/// Default `--anomaly-z`: how many standard deviations count as anomalous.
+pub const DEFAULT_Z_THRESHOLD: f64 = 6.0;
+/// Consecutive anomalous points on one side that make a step change.
+const STEP_CHANGE_POINTS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    Spike,
+    StepChange,
+    /// The series went NaN or infinite.
+    NonFinite,
+}
+
+impl AnomalyKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            AnomalyKind::Spike => "spike",
+            AnomalyKind::StepChange => "step change",
+            AnomalyKind::NonFinite => "non-finite value",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Anomaly {
+    pub step: f64,
+    pub value: f64,
+    pub kind: AnomalyKind,
+    /// Distance from the baseline median in robust standard deviations (0 for
+    /// non-finite values).
+    pub zscore: f64,
+}
+
+/// Scan progress for one metric, so each refresh only judges new points.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScanState {
+    /// Step of the last point judged; None before the first scan.
+    pub scanned_until: Option<f64>,
+    /// Points still to skip after a step change, so the new level is not
+    /// flagged again while the window still holds the old one.
+    cooldown: usize,
+}
+
+/// Judge the points of `series` after `state.scanned_until`. A point waits to
+/// be judged until the points needed to tell a spike from a step change exist.
+pub fn scan(
+    series: &[(f64, f64)],
+    state: &mut ScanState,
+    window: usize,
+    z_threshold: f64,
+) -> Vec<Anomaly> {
+    let mut found = Vec::new();
+    let start = match state.scanned_until {
+        Some(step) => series.partition_point(|(s, _)| *s <= step),
+        None => 0,
+    };
+    for idx in start..series.len() {
+        let (step, value) = series[idx];
+        if !value.is_finite() {
+            let previous_finite = idx == 0 || series[idx - 1].1.is_finite();
+            if previous_finite {
+                found.push(Anomaly {
+                    step,
+                    value,
+                    kind: AnomalyKind::NonFinite,
+                    zscore: 0.0,
+                });
+            }
+            state.scanned_until = Some(step);
+            continue;
+        }
+        if idx + STEP_CHANGE_POINTS > series.len() {
+            break;
+        }
+        state.scanned_until = Some(step);
+        if state.cooldown > 0 {
+            state.cooldown -= 1;
+            continue;
+        }
+        if idx < window {
+            continue;
+        }
+        let Some((center, scale)) = baseline(&series[idx - window..idx]) else {
+            continue;
+        };
+        let z = |v: f64| (v - center) / scale;
+        let zscore = z(value);
+        if zscore.abs() < z_threshold {
+            continue;
+        }
+        let sustained = series[idx + 1..idx + STEP_CHANGE_POINTS]
+            .iter()
+            .all(|(_, v)| {
+                let next = z(*v);
+                next.abs() >= z_threshold && next.signum() == zscore.signum()
+            });
+        let kind = if sustained {
+            state.cooldown = window;
+            AnomalyKind::StepChange
+        } else {
+            AnomalyKind::Spike
+        };
+        found.push(Anomaly {
+            step,
+            value,
+            kind,
+            zscore,
+        });
+    }
+    found
+}
+
+/// Median and scaled MAD of the finite values, with a floor on the scale so
+/// a perfectly flat baseline still yields finite z-scores.
+fn baseline(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let mut values: Vec<f64> = points
+        .iter()
+        .map(|(_, v)| *v)
+        .filter(|v| v.is_finite())
+        .collect();
+    if values.len() < 2 {
+        return None;
+    }
+    let center = median(&mut values);
+    let mut deviations: Vec<f64> = values.iter().map(|v| (v - center).abs()).collect();
+    // 1.4826 makes the MAD match the standard deviation for normal noise.
+    let scale = 1.4826 * median(&mut deviations);
+    Some((center, scale.max(1e-9 * center.abs().max(1.0))))
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnomalyKind, ScanState, scan};
+
+    fn noisy(len: usize, level: f64) -> Vec<f64> {
+        (0..len)
+            .map(|i| level + if i.is_multiple_of(2) { 0.01 } else { -0.01 })
+            .collect()
+    }
+
+    fn series(values: Vec<f64>) -> Vec<(f64, f64)> {
+        values
+            .into_iter()
+            .enumerate()
+            .map(|(step, value)| (step as f64, value))
+            .collect()
+    }
+
+    #[test]
+    fn flags_spikes_step_changes_and_nan_once() {
+        let mut values = noisy(60, 1.0);
+        values[55] = 5.0;
+        values.extend(noisy(10, 3.0));
+        values.push(f64::NAN);
+        values.push(f64::NAN);
+        let series = series(values);
+
+        let mut state = ScanState::default();
+        let found = scan(&series, &mut state, 20, 6.0);
+        let kinds: Vec<(f64, AnomalyKind)> = found.iter().map(|a| (a.step, a.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (55.0, AnomalyKind::Spike),
+                (60.0, AnomalyKind::StepChange),
+                (70.0, AnomalyKind::NonFinite),
+            ]
+        );
+        assert!(found[0].zscore > 6.0);
+        assert_eq!(state.scanned_until, Some(71.0));
+        assert!(scan(&series, &mut state, 20, 6.0).is_empty());
+    }
+
+    #[test]
+    fn waits_for_enough_points_to_classify() {
+        let mut values = noisy(30, 1.0);
+        values.push(9.0);
+        let mut series = series(values);
+        let mut state = ScanState::default();
+        assert!(scan(&series, &mut state, 20, 6.0).is_empty());
+        assert_eq!(state.scanned_until, Some(28.0));
+
+        series.push((31.0, 1.0));
+        series.push((32.0, 1.01));
+        let found = scan(&series, &mut state, 20, 6.0);
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].step, found[0].kind), (30.0, AnomalyKind::Spike));
+    }
+}
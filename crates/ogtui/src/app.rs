@@ -1,15 +1,61 @@
-use std::collections::{BTreeMap, HashSet};
-use std::path::PathBuf;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::ops::{Range, RangeInclusive};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::AtomicI64;
+use std::time::{Duration, Instant};
 
 use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
 
+use crate::anomaly::{self, Anomaly, ScanState};
+use crate::cgroup::Limits;
+use crate::checkpoints::Checkpoint;
+use crate::convergence::{self, Plateau};
+use crate::cost::{self, Pricing, Spend};
+use crate::dashboard::DashboardRow;
+use crate::dist_health::{self, DistIssue, RankHealth, RankStatus};
+use crate::epoch::EpochScale;
+use crate::format;
+use crate::grad_health::{self, GradHealth};
+use crate::graph_filter;
+use crate::histogram::Histogram;
+use crate::input_stall;
+use crate::log_buffer::{self, LogBuffer};
+use crate::log_entry::{LogEntry, LogLevel, LogSource};
+use crate::lr_schedule::{self, LrReport};
+use crate::metrics_jsonl::StructuredEvent;
+use crate::oom_forecast::{self, Forecast};
+use crate::project_config::ObjectiveMode;
+use crate::query::ProjectSummary;
+use crate::run_diff::{self, RunDiff};
+use crate::run_meta::{Annotation, Card, MetricAttrs, RunMeta, Scale};
+use crate::seed_band::{BandKind, SeedGroup};
 use crate::socket_client::{ActionPlanResponse, ChatMessage};
+use crate::sys_strip::SysStrip;
+use crate::table::Table;
+use crate::tfevents::RankProgress;
+use crate::thermal;
+use crate::timeline;
+
+/// When to suggest stopping a run whose objective metric has stopped improving.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EarlyStopPolicy {
+    pub metric: String,
+    pub mode: ObjectiveMode,
+    pub min_delta: f64,
+    pub patience: usize,
+}
 
 /// Which tab is currently active.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Tab {
     Graphs,
+    Dashboard,
     Logs,
+    Events,
+    Distributions,
     Processes,
     Chat,
 }
@@ -33,13 +79,62 @@ impl ProcessSort {
     }
 }
 
+/// How long a toast stays on screen.
+pub const TOAST_TTL: Duration = Duration::from_secs(6);
+/// Older toasts are dropped once this many are stacked.
+pub const MAX_TOASTS: usize = 4;
+/// Default `--run-size-warn`: runs above 50 GiB are flagged.
+pub const DEFAULT_RUN_SIZE_WARN_BYTES: u64 = 50 << 30;
+/// Default `--min-free-space` for the runs filesystem: 10 GiB.
+pub const DEFAULT_MIN_FREE_BYTES: u64 = 10 << 30;
+
+/// A chart's `(x, value)` points, borrowed or realigned.
+type Series<'a> = Cow<'a, [(f64, f64)]>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastLevel {
+    Info,
+    Success,
+    Alert,
+    /// Thermal throttling, drawn apart from other alerts.
+    Thermal,
+}
+
+/// Short-lived notification drawn over whatever tab is active.
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub level: ToastLevel,
+    pub message: String,
+    pub shown_at: Instant,
+}
+
+/// Whether toasts also reach the terminal: a bell, or an OSC 777 desktop
+/// notification (understood by e.g. kitty, foot, WezTerm and rxvt).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum NotifyMode {
+    Off,
+    Bell,
+    Desktop,
+}
+
 impl Tab {
-    pub const ALL: &[Tab] = &[Tab::Chat, Tab::Graphs, Tab::Processes, Tab::Logs];
+    pub const ALL: &[Tab] = &[
+        Tab::Chat,
+        Tab::Graphs,
+        Tab::Dashboard,
+        Tab::Processes,
+        Tab::Logs,
+        Tab::Events,
+        Tab::Distributions,
+    ];
 
     pub fn title(self) -> &'static str {
         match self {
             Tab::Graphs => "graphs",
+            Tab::Dashboard => "dashboard",
             Tab::Logs => "logs",
+            Tab::Events => "events",
+            Tab::Distributions => "dists",
             Tab::Processes => "procs",
             Tab::Chat => "chat",
         }
@@ -48,14 +143,158 @@ impl Tab {
     pub fn next(self) -> Tab {
         match self {
             Tab::Chat => Tab::Graphs,
-            Tab::Graphs => Tab::Processes,
+            Tab::Graphs => Tab::Dashboard,
+            Tab::Dashboard => Tab::Processes,
             Tab::Processes => Tab::Logs,
-            Tab::Logs => Tab::Chat,
+            Tab::Logs => Tab::Events,
+            Tab::Events => Tab::Distributions,
+            Tab::Distributions => Tab::Chat,
+        }
+    }
+
+    pub fn prev(self) -> Tab {
+        match self {
+            Tab::Chat => Tab::Distributions,
+            Tab::Distributions => Tab::Events,
+            Tab::Graphs => Tab::Chat,
+            Tab::Dashboard => Tab::Graphs,
+            Tab::Processes => Tab::Dashboard,
+            Tab::Logs => Tab::Processes,
+            Tab::Events => Tab::Logs,
+        }
+    }
+
+    /// Panes of the tab that take keys, in Tab order.
+    pub fn panes(self) -> &'static [Pane] {
+        match self {
+            Tab::Graphs => &[Pane::Main, Pane::Side],
+            Tab::Chat => &[Pane::Main, Pane::Input],
+            Tab::Dashboard | Tab::Logs | Tab::Events | Tab::Distributions | Tab::Processes => {
+                &[Pane::Main]
+            }
         }
     }
 }
 
-#[derive(Debug, Clone)]
+/// Region of a tab that scroll keys go to; drawn with the tab's accent border.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    /// The tab's grid, table or transcript
+    Main,
+    /// The stats column beside the metrics grid
+    Side,
+    /// The chat input (typing mode)
+    Input,
+}
+
+/// Visual selection in the logs tab, over logical (unwrapped) lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogSelection {
+    /// Line where `v` was pressed
+    pub anchor: usize,
+    /// Line that movement keys move; the selection spans anchor..=cursor
+    pub cursor: usize,
+}
+
+impl LogSelection {
+    pub fn range(self) -> RangeInclusive<usize> {
+        self.anchor.min(self.cursor)..=self.anchor.max(self.cursor)
+    }
+}
+
+/// What happened at a step marked on the metric charts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MarkerKind {
+    Checkpoint,
+    Eval,
+    /// A learning-rate schedule warning, shown on the loss charts.
+    Lr,
+}
+
+/// A checkpoint save, eval run or LR warning shown as a marker on the metric charts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartMarker {
+    pub step: i64,
+    pub kind: MarkerKind,
+    /// Checkpoint file name or LR warning; None for evals
+    pub id: Option<String>,
+    /// Metrics recorded with the checkpoint, or the eval metrics at that step
+    pub values: Vec<(String, f64)>,
+}
+
+/// Loss metrics, the charts LR warnings are marked on.
+pub fn is_loss_tag(tag: &str) -> bool {
+    tag.rsplit('/')
+        .next()
+        .is_some_and(|name| name.contains("loss"))
+}
+
+/// PIDs of `roots` and everything they started, plus any process holding
+/// GPU memory.
+fn training_tree(processes: &[ProcessSnapshot], roots: &[i32]) -> HashSet<i32> {
+    let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+    for process in processes {
+        children.entry(process.ppid).or_default().push(process.pid);
+    }
+    let mut tree: HashSet<i32> = processes
+        .iter()
+        .filter(|p| p.gpu_mem_mb.is_some())
+        .map(|p| p.pid)
+        .collect();
+    let mut pending: Vec<i32> = roots.to_vec();
+    let mut seen = HashSet::new();
+    while let Some(pid) = pending.pop() {
+        if !seen.insert(pid) {
+            continue;
+        }
+        tree.insert(pid);
+        pending.extend(children.get(&pid).into_iter().flatten());
+    }
+    let running: HashSet<i32> = processes.iter().map(|p| p.pid).collect();
+    tree.retain(|pid| running.contains(pid));
+    tree
+}
+
+/// Bounds a metric evidently has: 0–1 (or 0–100) for accuracy-style
+/// scores, 0–100 for percentages and utilization. None unless every value
+/// logged so far fits.
+fn detected_bounds(tag: &str, unit: Option<&str>, values: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let name = tag.rsplit('/').next()?.to_ascii_lowercase();
+    let words: Vec<&str> = name.split(['_', '-', '.', ' ']).collect();
+    let fits = |hi: f64| {
+        !values.is_empty()
+            && values
+                .iter()
+                .all(|(_, value)| value.is_finite() && (0.0..=hi).contains(value))
+    };
+    let percent = unit == Some("%")
+        || words
+            .iter()
+            .any(|word| matches!(*word, "pct" | "percent" | "util" | "utilization"));
+    let score = words.iter().any(|word| {
+        matches!(
+            *word,
+            "acc" | "accuracy" | "precision" | "recall" | "f1" | "auc" | "iou"
+        )
+    });
+    if percent {
+        fits(100.0).then_some((0.0, 100.0))
+    } else if score && fits(1.0) {
+        Some((0.0, 1.0))
+    } else if score && fits(100.0) {
+        Some((0.0, 100.0))
+    } else {
+        None
+    }
+}
+
+/// Tags logged by evaluation passes (`eval/…`, `val/…`, `validation/…`).
+fn is_eval_tag(tag: &str) -> bool {
+    tag.split_once('/')
+        .is_some_and(|(head, _)| matches!(head, "eval" | "val" | "validation"))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessSnapshot {
     pub pid: i32,
     pub ppid: i32,
@@ -65,27 +304,67 @@ pub struct ProcessSnapshot {
     pub cpu_pct: f32,
     pub mem_pct: f32,
     pub command: String,
+    /// GPU memory held in MiB (None when the process holds none or there is no GPU)
+    pub gpu_mem_mb: Option<f64>,
+}
+
+/// Samples kept per process for the processes tab's sparklines.
+pub const PROCESS_HISTORY_LEN: usize = 24;
+
+/// Recent samples of one running process, oldest first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProcessHistory {
+    pub cpu_pct: VecDeque<f64>,
+    pub mem_pct: VecDeque<f64>,
+    /// Only samples where the process held GPU memory
+    pub gpu_mem_mb: VecDeque<f64>,
+}
+
+impl ProcessHistory {
+    fn push(&mut self, process: &ProcessSnapshot) {
+        let samples = [
+            (&mut self.cpu_pct, Some(f64::from(process.cpu_pct))),
+            (&mut self.mem_pct, Some(f64::from(process.mem_pct))),
+            (&mut self.gpu_mem_mb, process.gpu_mem_mb),
+        ];
+        for (history, sample) in samples {
+            if let Some(sample) = sample {
+                if history.len() == PROCESS_HISTORY_LEN {
+                    history.pop_front();
+                }
+                history.push_back(sample);
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ExitedProcess {
     pub snapshot: ProcessSnapshot,
     pub exited_at_unix: u64,
+    /// Whether it belonged to the training tree when last seen
+    pub training: bool,
 }
 
 /// Application state.
 pub struct App {
     pub active_tab: Tab,
+    /// Pane picked with Tab / Shift+Tab; see `focused_pane`
+    pub pane_focus: Pane,
     /// tag → sorted (step, value) pairs
     pub scalars: BTreeMap<String, Vec<(f64, f64)>>,
     /// Optional display labels keyed by metric tag
     pub metric_labels: BTreeMap<String, String>,
     /// Ordered list of tag names for grid iteration
     pub tags: Vec<String>,
-    /// Log lines derived from events
-    pub log_lines: Vec<String>,
+    /// Log lines derived from events, or the live log once a run is live
+    pub log_lines: LogBuffer,
     /// Whether the help overlay is shown
     pub show_help: bool,
+    /// Run journal note being typed (None = note box closed)
+    pub note_input: Option<String>,
+    /// Text typed after `:` (None = command line closed)
+    pub command_input: Option<String>,
     /// Path that was loaded
     pub events_path: PathBuf,
     /// Scroll offset in the logs tab
@@ -94,8 +373,21 @@ pub struct App {
     pub logs_follow_tail: bool,
     /// Number of visible rows in the logs viewport (set by UI)
     pub logs_viewport_rows: usize,
+    /// Rows the log lines take once wrapped to the viewport width (set by
+    /// UI; one per line until the first draw)
+    pub logs_rendered_rows: usize,
+    /// Rows each log line wrapped to on the last draw (set by UI)
+    pub logs_line_rows: Vec<usize>,
+    /// Visual line selection in the logs tab (None = not selecting)
+    pub log_selection: Option<LogSelection>,
+    /// Lowest level shown in the logs tab (None = every entry)
+    pub log_level_filter: Option<LogLevel>,
     /// Whether the app should quit
     pub should_quit: bool,
+    /// Training runs under the daemon this TUI spawned, so it stops on quit
+    pub owns_training: bool,
+    /// Quit was requested while owning training; waiting for confirmation
+    pub quit_pending: bool,
     /// Selected metric index for highlight
     pub selected_metric: usize,
     /// Focused metric index for fullscreen detail view (None = grid view)
@@ -106,28 +398,71 @@ pub struct App {
     pub metrics_visible_rows: usize,
     /// Number of columns in the metrics grid (set by UI)
     pub metrics_cols: usize,
+    /// Scroll offset of the stats column beside the grid (wrapped rows)
+    pub stats_scroll: u16,
+    /// Largest useful `stats_scroll` (set by UI)
+    pub stats_max_scroll: u16,
     /// Total number of events parsed
     pub total_events: usize,
     /// Total steps (max step value)
     pub max_step: i64,
+    /// Timeline annotations loaded from the run metadata sidecar
+    pub annotations: Vec<Annotation>,
+    /// Per-metric unit, direction and display hints from the run metadata sidecar
+    pub metric_attrs: BTreeMap<String, MetricAttrs>,
+    /// The run was launched from a git tree with uncommitted changes
+    pub dirty_tree: bool,
+    /// Stable color of the loaded run (None until run metadata is applied)
+    pub run_color: Option<(u8, u8, u8)>,
+    /// Distributed ranks the run's event files were written by
+    pub ranks: Vec<u32>,
+    /// Graph preset selecting the metrics shown (None = all, or the --graph JSON filter)
+    pub graph_preset: Option<String>,
+    /// `--graph-strict`: selectors that match nothing leave the grid empty
+    pub graph_strict: bool,
+    /// Rank shown in the graphs; None shows the merged view across ranks
+    pub selected_rank: Option<u32>,
+    /// Latest event per rank, from the last event-file load
+    pub rank_progress: BTreeMap<u32, RankProgress>,
+    /// NCCL / torch.distributed problems seen in the live logs (newest last)
+    pub dist_issues: Vec<DistIssue>,
+    /// Per-rank status shown in the distributed health panel
+    pub rank_health: Vec<RankHealth>,
+    /// Ranks already reported as stalled, so the alert fires once per stall
+    pub stalled_alerted: HashSet<u32>,
 
     // ── Agent chat state ─────────────────────────────────────────────────
-    /// Chat messages from the daemon
+    /// Chat messages from the daemon, the newest `chat_limit` of them
     pub chat_messages: Vec<ChatMessage>,
+    /// Most chat messages kept in memory; the daemon keeps the full history
+    pub chat_limit: usize,
+    /// Older chat messages left out of `chat_messages`
+    pub chat_hidden: usize,
     /// Current input buffer for the chat
     pub chat_input: String,
     /// Scroll offset in the chat message list
     pub chat_scroll: u16,
     /// Whether chat should auto-follow incoming messages
     pub chat_follow_tail: bool,
+    /// Largest useful `chat_scroll` for the wrapped transcript (set by UI)
+    pub chat_max_scroll: u16,
     /// Whether the chat input is focused (typing mode)
     pub chat_input_focused: bool,
     /// Whether the agent is currently processing
     pub agent_thinking: bool,
+    /// Agent reply streamed so far for the request in flight
+    pub chat_stream: String,
+    /// Notifications stacked top-right, oldest first
+    pub toasts: Vec<Toast>,
+    pub notify_mode: NotifyMode,
+    /// Bell / OSC escape sequences waiting to be written after the next draw
+    pub pending_terminal_notifications: Vec<String>,
     /// Whether the daemon is connected
     pub daemon_connected: bool,
     /// Socket path for the daemon
     pub daemon_socket: PathBuf,
+    /// Codebase refactors are applied to (snapshots go to `.og_checkpoints` inside it)
+    pub codebase_root: PathBuf,
     /// Status message shown in chat footer
     pub chat_status: String,
     /// Auto-mode flag from daemon
@@ -146,6 +481,20 @@ pub struct App {
     pub copy_mode: bool,
     /// Current running processes from host snapshot
     pub running_processes: Vec<ProcessSnapshot>,
+    /// Recent samples of each running process by PID, for sparklines
+    pub process_history: HashMap<i32, ProcessHistory>,
+    /// Limit the processes tab to the training tree and GPU users (`f`)
+    pub process_filter: bool,
+    /// Processes whose descendants make up the training tree: this TUI, the
+    /// agent daemon and the loaded run's training process
+    pub process_roots: Vec<i32>,
+    /// Running processes in the training tree or holding GPU memory
+    pub training_pids: HashSet<i32>,
+    /// Training process recorded in the loaded run's heartbeat
+    pub training_pid: Option<u32>,
+    /// cgroup limits of the container the TUI runs in, which process CPU and
+    /// memory percentages are relative to (None outside a container)
+    pub container_limits: Option<Limits>,
     /// Recently exited processes that were seen in prior snapshots
     pub exited_processes: Vec<ExitedProcess>,
     /// Scroll offset in the processes tab
@@ -160,13 +509,109 @@ pub struct App {
     pub process_sort: ProcessSort,
     /// Maximum processes to show/store in lists
     pub process_limit: usize,
+
+    /// Checkpoints of the loaded run, reread on refresh and measured when the panel opens
+    pub checkpoints: Vec<Checkpoint>,
+    /// Selected row of the checkpoints panel (None = panel closed)
+    pub checkpoint_panel: Option<usize>,
+    /// Chart marker picked with `[` / `]` in the focused metric view
+    pub selected_marker: Option<usize>,
+    /// Whether the learning-rate panel is open
+    pub lr_panel: bool,
+    /// Latest version of each table summary, reread while the tables panel is open
+    pub tables: Vec<Table>,
+    /// Table shown in the tables panel (None = panel closed)
+    pub table_panel: Option<usize>,
+    /// Projects under the runs root, listed when the project picker opens
+    pub projects: Vec<ProjectSummary>,
+    /// Selected row of the project picker (None = picker closed)
+    pub project_panel: Option<usize>,
+    /// Second run the focused metric is compared with (None = no comparison)
+    pub run_diff: Option<RunDiff>,
+    /// Runs of a multi-run project grouped by config minus seeds; when set,
+    /// charts draw each group as a band instead of one merged series
+    pub seed_groups: Vec<SeedGroup>,
+    /// Spread the seed bands cover, toggled with `b`
+    pub band_kind: BandKind,
+    /// How the loaded run's steps map to epochs (None without an epoch
+    /// counter or `steps_per_epoch` in its config)
+    pub epoch_scale: Option<EpochScale>,
+    /// Focused and comparison charts run along epochs, toggled with `e`
+    pub epoch_axis: bool,
+
+    // ── Events state ─────────────────────────────────────────────────────
+    /// Structured events of the loaded run, reread on refresh
+    pub structured_events: Vec<StructuredEvent>,
+    /// Event type shown in the events tab (None = all types)
+    pub events_filter: Option<String>,
+    /// Scroll offset in the events tab (in rows)
+    pub events_scroll: usize,
+
+    // ── Distributions state ──────────────────────────────────────────────
+    /// Histograms of the loaded run by tag, reread while the distributions tab is shown
+    pub histograms: BTreeMap<String, Vec<Histogram>>,
+    /// Tag shown in the distributions tab, by position in `histograms`
+    pub histogram_tag: usize,
+    /// Step picked with the slider, by position in the tag's histograms (None = latest)
+    pub histogram_step: Option<usize>,
+    /// Why the last histogram reload failed; cleared by the next good one
+    pub histogram_error: Option<String>,
+
+    // ── Dashboard state ──────────────────────────────────────────────────
+    /// Directory whose runs are summarised in the dashboard tab
+    pub dashboard_root: PathBuf,
+    /// One row per run under `dashboard_root`
+    pub dashboard_rows: Vec<DashboardRow>,
+    /// Scroll offset in the dashboard tab (in rows)
+    pub dashboard_scroll: usize,
+    /// On-disk size of each run under `dashboard_root` by id, measured in the background
+    pub run_sizes: BTreeMap<String, u64>,
+    /// Runs larger than this are flagged in the dashboard
+    pub run_size_warn_bytes: u64,
+    /// Free space on the filesystem holding `dashboard_root` (None = unknown)
+    pub runs_free_bytes: Option<u64>,
+    /// Alert once free space on the runs filesystem drops below this
+    pub min_free_bytes: u64,
+    /// `--graph`-style selectors of the metrics watched for anomalies (empty = off)
+    pub anomaly_metrics: Vec<String>,
+    /// Robust z-score at which a point counts as anomalous
+    pub anomaly_z: f64,
+    /// Anomalous points found so far, by metric
+    pub anomalies: BTreeMap<String, Vec<Anomaly>>,
+    anomaly_scans: BTreeMap<String, ScanState>,
+    /// Convergence check on the objective metric (None = off)
+    pub early_stop: Option<EarlyStopPolicy>,
+    /// Set once the plateau has been reported, so it is reported once per session
+    pub early_stop_decided: bool,
+    /// Explosion and vanishing limits for `grad_norm`-like metrics
+    pub grad_limits: grad_health::Limits,
+    /// Price per GPU-hour and budget for the loaded run's spend estimate
+    pub pricing: Pricing,
+    /// GPUs the loaded run uses (from its metadata, else its ranks)
+    pub gpu_count: Option<u32>,
+    /// Seconds between the loaded run's first and last event
+    pub wall_secs: Option<f64>,
+    /// Long wall-clock pauses in the loaded run, shaded under the focused chart
+    pub wall_gaps: Vec<timeline::Gap>,
+    /// Hardware gauges and step rate shown under the header on every tab
+    pub sys_strip: SysStrip,
+    /// Set once the run has been reported over budget
+    pub budget_alerted: bool,
+    /// Latest step, shared with the system sampler of a run started here
+    pub sampler_step: Option<Arc<AtomicI64>>,
+    /// Devices last reported as thermally throttling
+    pub throttled_devices: HashSet<String>,
+    /// First steps of the dataloader stalls already reported
+    pub reported_stalls: HashSet<i64>,
+    /// Memory metrics already warned about running out
+    pub oom_warned: HashSet<String>,
 }
 
 impl App {
     pub fn new(
         scalars: BTreeMap<String, Vec<(f64, f64)>>,
         metric_labels: BTreeMap<String, String>,
-        log_lines: Vec<String>,
+        log_lines: Vec<LogEntry>,
         events_path: PathBuf,
         total_events: usize,
         max_step: i64,
@@ -175,31 +620,62 @@ impl App {
         let daemon_socket = crate::socket_client::socket_path();
         Self {
             active_tab: Tab::Chat,
+            pane_focus: Pane::Main,
             scalars,
             metric_labels,
             tags,
-            log_lines,
+            log_lines: log_lines.into_iter().collect(),
             show_help: false,
+            note_input: None,
+            command_input: None,
             events_path,
             logs_scroll: 0,
             logs_follow_tail: true,
             logs_viewport_rows: 1,
+            logs_rendered_rows: 0,
+            logs_line_rows: Vec::new(),
+            log_selection: None,
+            log_level_filter: None,
             should_quit: false,
+            owns_training: false,
+            quit_pending: false,
             selected_metric: 0,
             focused_metric: None,
             metrics_scroll: 0,
             metrics_visible_rows: 3,
             metrics_cols: 4,
+            stats_scroll: 0,
+            stats_max_scroll: 0,
             total_events,
             max_step,
+            annotations: Vec::new(),
+            metric_attrs: BTreeMap::new(),
+            dirty_tree: false,
+            run_color: None,
+            ranks: Vec::new(),
+            graph_preset: None,
+            graph_strict: false,
+            selected_rank: None,
+            rank_progress: BTreeMap::new(),
+            dist_issues: Vec::new(),
+            rank_health: Vec::new(),
+            stalled_alerted: HashSet::new(),
             chat_messages: Vec::new(),
+            chat_limit: log_buffer::DEFAULT_CHAT_MESSAGES,
+            chat_hidden: 0,
             chat_input: String::new(),
             chat_scroll: 0,
             chat_follow_tail: true,
+            chat_max_scroll: 0,
             chat_input_focused: false,
             agent_thinking: false,
+            chat_stream: String::new(),
+            toasts: Vec::new(),
+            notify_mode: NotifyMode::Off,
+            pending_terminal_notifications: Vec::new(),
             daemon_connected: false,
             daemon_socket,
+            codebase_root: PathBuf::from("."),
             chat_status: "Disconnected".to_string(),
             auto_mode: false,
             pending_refactor: None,
@@ -209,6 +685,12 @@ impl App {
             last_logged_step: max_step,
             copy_mode: false,
             running_processes: Vec::new(),
+            process_history: HashMap::new(),
+            process_filter: true,
+            process_roots: Vec::new(),
+            training_pids: HashSet::new(),
+            training_pid: None,
+            container_limits: None,
             exited_processes: Vec::new(),
             processes_scroll: 0,
             processes_follow_tail: false,
@@ -216,16 +698,113 @@ impl App {
             processes_total_rows: 1,
             process_sort: ProcessSort::Cpu,
             process_limit: 300,
+            checkpoints: Vec::new(),
+            checkpoint_panel: None,
+            lr_panel: false,
+            tables: Vec::new(),
+            table_panel: None,
+            projects: Vec::new(),
+            project_panel: None,
+            run_diff: None,
+            seed_groups: Vec::new(),
+            band_kind: BandKind::Quartiles,
+            epoch_scale: None,
+            epoch_axis: false,
+            selected_marker: None,
+            structured_events: Vec::new(),
+            events_filter: None,
+            events_scroll: 0,
+            histograms: BTreeMap::new(),
+            histogram_tag: 0,
+            histogram_step: None,
+            histogram_error: None,
+            dashboard_root: PathBuf::from("runs/"),
+            dashboard_rows: Vec::new(),
+            dashboard_scroll: 0,
+            run_sizes: BTreeMap::new(),
+            run_size_warn_bytes: DEFAULT_RUN_SIZE_WARN_BYTES,
+            runs_free_bytes: None,
+            min_free_bytes: DEFAULT_MIN_FREE_BYTES,
+            anomaly_metrics: vec!["loss".to_string()],
+            anomaly_z: anomaly::DEFAULT_Z_THRESHOLD,
+            anomalies: BTreeMap::new(),
+            anomaly_scans: BTreeMap::new(),
+            early_stop: None,
+            early_stop_decided: false,
+            grad_limits: grad_health::Limits::default(),
+            pricing: Pricing::default(),
+            gpu_count: None,
+            wall_secs: None,
+            sys_strip: SysStrip::default(),
+            wall_gaps: Vec::new(),
+            budget_alerted: false,
+            sampler_step: None,
+            throttled_devices: HashSet::new(),
+            reported_stalls: HashSet::new(),
+            oom_warned: HashSet::new(),
         }
     }
 
+    /// `--graph-labels` override, else the display name the run recorded.
     pub fn metric_display_name<'a>(&'a self, tag: &'a str) -> &'a str {
         self.metric_labels
             .get(tag)
+            .or_else(|| self.metric_attrs.get(tag)?.display_name.as_ref())
             .map(String::as_str)
             .unwrap_or(tag)
     }
 
+    pub fn metric_unit(&self, tag: &str) -> Option<&str> {
+        self.metric_attrs.get(tag)?.unit.as_deref()
+    }
+
+    /// Recorded direction, else the objective's when `tag` is the early-stop
+    /// objective.
+    pub fn higher_is_better(&self, tag: &str) -> Option<bool> {
+        self.metric_attrs
+            .get(tag)
+            .and_then(|attrs| attrs.higher_is_better)
+            .or_else(|| {
+                let policy = self.early_stop.as_ref().filter(|p| p.metric == tag)?;
+                Some(policy.mode == ObjectiveMode::Max)
+            })
+    }
+
+    pub fn metric_scale(&self, tag: &str) -> Scale {
+        self.metric_attrs
+            .get(tag)
+            .and_then(|attrs| attrs.scale)
+            .unwrap_or(Scale::Linear)
+    }
+
+    /// Bounds to draw `tag`'s card as a gauge between, or None for a line
+    /// chart. Recorded bounds win over detected ones; `card: gauge` without
+    /// either spans the values seen so far.
+    pub fn metric_gauge(&self, tag: &str) -> Option<(f64, f64)> {
+        let attrs = self.metric_attrs.get(tag);
+        let card = attrs.and_then(|attrs| attrs.card);
+        if card == Some(Card::Line) {
+            return None;
+        }
+        let values = self.scalars.get(tag)?;
+        let bounds = attrs
+            .and_then(|attrs| attrs.bounds)
+            .map(|[lo, hi]| (lo, hi))
+            .or_else(|| detected_bounds(tag, self.metric_unit(tag), values));
+        match (bounds, card) {
+            (Some(bounds), _) => Some(bounds),
+            (None, Some(Card::Gauge)) => {
+                let (lo, hi) = values
+                    .iter()
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), (_, v)| {
+                        (lo.min(*v), hi.max(*v))
+                    });
+                (lo < hi).then_some((lo, hi))
+            }
+            (None, _) => None,
+        }
+    }
+
     pub fn set_process_preferences(&mut self, sort: ProcessSort, limit: usize) {
         self.process_sort = sort;
         self.process_limit = limit.max(1);
@@ -233,14 +812,92 @@ impl App {
         self.exited_processes.truncate(self.process_limit);
     }
 
-    pub fn cycle_tab(&mut self) {
-        self.active_tab = self.active_tab.next();
+    /// Show a toast and, depending on `notify_mode`, queue a bell or
+    /// desktop notification for it.
+    pub fn toast(&mut self, level: ToastLevel, message: impl Into<String>) {
+        let message: String = message.into();
+        match self.notify_mode {
+            NotifyMode::Off => {}
+            NotifyMode::Bell => self.pending_terminal_notifications.push("\x07".to_string()),
+            NotifyMode::Desktop => {
+                // The body is `;`-delimited and ends at BEL, so strip both.
+                let body: String = message
+                    .chars()
+                    .map(|c| if c == ';' || c.is_control() { ' ' } else { c })
+                    .collect();
+                self.pending_terminal_notifications
+                    .push(format!("\x1b]777;notify;OpenGraphs;{body}\x07"));
+            }
+        }
+        self.toasts.push(Toast {
+            level,
+            message,
+            shown_at: Instant::now(),
+        });
+        if self.toasts.len() > MAX_TOASTS {
+            let excess = self.toasts.len() - MAX_TOASTS;
+            self.toasts.drain(..excess);
+        }
+    }
+
+    /// Drop toasts older than `TOAST_TTL`. Returns whether any were removed.
+    pub fn expire_toasts(&mut self, now: Instant) -> bool {
+        let before = self.toasts.len();
+        self.toasts
+            .retain(|toast| now.saturating_duration_since(toast.shown_at) < TOAST_TTL);
+        self.toasts.len() != before
+    }
+
+    /// Pane that keys go to on the active tab. The chat input counts as
+    /// focused while typing, however it got there (`i`, Enter, Tab).
+    pub fn focused_pane(&self) -> Pane {
+        if self.active_tab == Tab::Chat && self.chat_input_focused {
+            Pane::Input
+        } else if self.pane_focus != Pane::Input
+            && self.active_tab.panes().contains(&self.pane_focus)
+        {
+            self.pane_focus
+        } else {
+            Pane::Main
+        }
+    }
+
+    /// Tab: the next pane of this tab, or the first pane of the next tab.
+    pub fn focus_next_pane(&mut self) {
+        let panes = self.active_tab.panes();
+        let at = panes.iter().position(|p| *p == self.focused_pane());
+        match at.map(|i| i + 1).filter(|i| *i < panes.len()) {
+            Some(i) => self.set_pane(panes[i]),
+            None => {
+                self.active_tab = self.active_tab.next();
+                self.set_pane(self.active_tab.panes()[0]);
+            }
+        }
+    }
+
+    /// Shift+Tab: the previous pane of this tab, or the last pane of the
+    /// previous tab.
+    pub fn focus_prev_pane(&mut self) {
+        let panes = self.active_tab.panes();
+        match panes.iter().position(|p| *p == self.focused_pane()) {
+            Some(i) if i > 0 => self.set_pane(panes[i - 1]),
+            _ => {
+                self.active_tab = self.active_tab.prev();
+                let panes = self.active_tab.panes();
+                self.set_pane(panes[panes.len() - 1]);
+            }
+        }
+    }
+
+    fn set_pane(&mut self, pane: Pane) {
+        self.pane_focus = pane;
+        self.chat_input_focused = pane == Pane::Input;
     }
 
     pub fn replace_data(
         &mut self,
         scalars: BTreeMap<String, Vec<(f64, f64)>>,
-        log_lines: Vec<String>,
+        log_lines: Vec<LogEntry>,
         total_events: usize,
         max_step: i64,
     ) {
@@ -251,7 +908,9 @@ impl App {
         self.total_events = total_events;
         self.max_step = max_step;
         if !self.live_logs_active {
-            self.log_lines = log_lines;
+            self.log_lines.replace(log_lines);
+            self.clamp_log_selection();
+            self.logs_rendered_rows = self.logs_rendered_rows.min(self.log_lines.len());
             self.last_logged_step = max_step;
         }
 
@@ -269,10 +928,10 @@ impl App {
             self.selected_metric = self.tags.len() - 1;
         }
 
-        if let Some(focused) = self.focused_metric {
-            if focused >= self.tags.len() {
-                self.focused_metric = None;
-            }
+        if let Some(focused) = self.focused_metric
+            && focused >= self.tags.len()
+        {
+            self.focused_metric = None;
         }
 
         self.ensure_metric_visible();
@@ -288,23 +947,352 @@ impl App {
             return;
         }
         self.live_logs_active = true;
-        self.log_lines = vec![
-            "-- live run log --".to_string(),
-            "[info] listening to live daemon updates".to_string(),
-        ];
+        let run_dir = crate::run_meta::run_dir_for(&self.events_path);
+        if run_dir.is_dir() {
+            self.log_lines
+                .set_spill(Some(&run_dir.join(log_buffer::CONSOLE_LOG_FILE_NAME)));
+        }
+        self.log_lines.replace(vec![
+            LogEntry::new(LogLevel::Info, LogSource::Og, "-- live run log --"),
+            LogEntry::now(
+                LogLevel::Info,
+                LogSource::Og,
+                "listening to live daemon updates",
+            ),
+        ]);
+        self.logs_rendered_rows = self.log_lines.len();
+        self.log_selection = None;
         self.logs_follow_tail = true;
         self.logs_scroll = self.logs_max_scroll();
         self.last_daemon_log_tail.clear();
         self.seen_alert_count = 0;
     }
 
-    pub fn append_live_log(&mut self, line: impl Into<String>) {
-        self.log_lines.push(line.into());
+    /// Log a line from og itself.
+    pub fn append_live_log(&mut self, level: LogLevel, message: impl Into<String>) {
+        self.push_log(LogEntry::now(level, LogSource::Og, message));
+    }
+
+    pub fn push_log(&mut self, entry: LogEntry) {
+        let issue = dist_health::parse_log_line(&entry.message);
+        let shown = self.shows_log(&entry);
+        let mut evicted = self.log_lines.push(entry);
+        if self.logs_follow_tail {
+            evicted += self.log_lines.trim();
+        }
+        self.logs_rendered_rows += usize::from(shown);
+        self.forget_log_lines(evicted);
         if self.logs_follow_tail {
             self.logs_scroll = self.logs_max_scroll();
         } else {
             self.clamp_logs_scroll();
         }
+        if let Some(issue) = issue {
+            self.dist_issues.push(issue);
+            let overflow = self
+                .dist_issues
+                .len()
+                .saturating_sub(dist_health::MAX_ISSUES);
+            self.dist_issues.drain(..overflow);
+            self.refresh_rank_health();
+        }
+    }
+
+    /// Record per-rank progress and alert once for each rank that stops
+    /// producing events while the others continue.
+    pub fn set_rank_progress(&mut self, progress: BTreeMap<u32, RankProgress>) {
+        self.rank_progress = progress;
+        self.refresh_rank_health();
+    }
+
+    fn refresh_rank_health(&mut self) {
+        self.rank_health = dist_health::assess(&self.rank_progress, &self.dist_issues);
+        let stalled: Vec<(u32, i64)> = self
+            .rank_health
+            .iter()
+            .filter(|health| health.status == RankStatus::Stalled)
+            .map(|health| (health.rank, health.last_step))
+            .collect();
+        self.stalled_alerted
+            .retain(|rank| stalled.iter().any(|(stalled_rank, _)| stalled_rank == rank));
+        for (rank, step) in stalled {
+            if self.stalled_alerted.insert(rank) {
+                let alert = format!(
+                    "rank {} stopped producing events at step {} while other ranks continue (possible hung collective)",
+                    rank, step
+                );
+                self.chat_status = format!("⚠ {}", alert);
+                self.append_live_log(LogLevel::Important, alert.clone());
+                self.toast(
+                    ToastLevel::Alert,
+                    format!("rank {rank} stalled at step {step}"),
+                );
+            }
+        }
+    }
+
+    /// Pick up the parts of the metadata sidecar the TUI renders.
+    pub fn apply_run_meta(&mut self, meta: RunMeta) {
+        let run_dir = crate::run_meta::run_dir_for(&self.events_path);
+        let run_id = run_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.run_color = Some(crate::run_color::for_run(&run_id, meta.color.as_deref()));
+        self.training_pid = meta.status.as_ref().and_then(|status| status.pid);
+        self.gpu_count = cost::run_gpus(&meta, self.ranks.len());
+        self.annotations = meta.annotations;
+        self.metric_attrs = meta.metrics;
+        self.dirty_tree = meta.git.is_some_and(|git| git.dirty);
+    }
+
+    /// Record the ranks found on the last load, dropping a selection that vanished.
+    pub fn set_ranks(&mut self, ranks: Vec<u32>) {
+        if self
+            .selected_rank
+            .is_some_and(|rank| !ranks.contains(&rank))
+        {
+            self.selected_rank = None;
+        }
+        self.ranks = ranks;
+    }
+
+    /// Step the rank selector: merged view, then each rank in order.
+    pub fn cycle_rank(&mut self) {
+        self.selected_rank = match self.selected_rank {
+            None => self.ranks.first().copied(),
+            Some(current) => self.ranks.iter().copied().find(|rank| *rank > current),
+        };
+    }
+
+    /// Open the run-journal input box unless no run is loaded.
+    pub fn open_note_input(&mut self) {
+        if self.events_path.exists() {
+            self.note_input = Some(String::new());
+        } else {
+            self.chat_status = "No run loaded — nothing to note".to_string();
+        }
+    }
+
+    /// Open the checkpoints panel on the newest checkpoint.
+    pub fn open_checkpoints(&mut self, checkpoints: Vec<Checkpoint>) {
+        self.checkpoint_panel = Some(checkpoints.len().saturating_sub(1));
+        self.checkpoints = checkpoints;
+    }
+
+    pub fn move_checkpoint_selection(&mut self, delta: isize) {
+        if let Some(selected) = self.checkpoint_panel.as_mut() {
+            let last = self.checkpoints.len().saturating_sub(1);
+            *selected = selected.saturating_add_signed(delta).min(last);
+        }
+    }
+
+    pub fn open_tables(&mut self, tables: Vec<Table>) {
+        self.table_panel = Some(0);
+        self.tables = tables;
+    }
+
+    /// Swap in reloaded tables, keeping the shown one selected by name.
+    pub fn set_tables(&mut self, tables: Vec<Table>) {
+        let shown = self.selected_table().map(|table| table.name.clone());
+        self.tables = tables;
+        if let Some(selected) = self.table_panel.as_mut() {
+            *selected = shown
+                .and_then(|name| self.tables.iter().position(|table| table.name == name))
+                .unwrap_or(0);
+        }
+    }
+
+    pub fn move_table_selection(&mut self, delta: isize) {
+        if let Some(selected) = self.table_panel.as_mut() {
+            let last = self.tables.len().saturating_sub(1);
+            *selected = selected.saturating_add_signed(delta).min(last);
+        }
+    }
+
+    pub fn selected_table(&self) -> Option<&Table> {
+        self.tables.get(self.table_panel?)
+    }
+
+    /// Open the project picker on the project being shown, if it is listed.
+    pub fn open_projects(&mut self, projects: Vec<ProjectSummary>) {
+        let shown = self.dashboard_root.as_path();
+        self.project_panel = Some(
+            projects
+                .iter()
+                .position(|project| Path::new(&project.path) == shown)
+                .unwrap_or(0),
+        );
+        self.projects = projects;
+    }
+
+    pub fn move_project_selection(&mut self, delta: isize) {
+        if let Some(selected) = self.project_panel.as_mut() {
+            let last = self.projects.len().saturating_sub(1);
+            *selected = selected.saturating_add_signed(delta).min(last);
+        }
+    }
+
+    pub fn selected_project(&self) -> Option<&ProjectSummary> {
+        self.projects.get(self.project_panel?)
+    }
+
+    /// Point the TUI at another project: forget everything read from the
+    /// previous one so the next load starts clean. Chat, daemon and
+    /// process state stay.
+    pub fn switch_project(&mut self, path: PathBuf) {
+        self.project_panel = None;
+        self.events_path = path.clone();
+        self.dashboard_root = path;
+        self.dashboard_rows.clear();
+        self.dashboard_scroll = 0;
+        self.run_sizes.clear();
+        self.runs_free_bytes = None;
+        self.selected_metric = 0;
+        self.focused_metric = None;
+        self.metrics_scroll = 0;
+        self.stats_scroll = 0;
+        self.selected_marker = None;
+        self.selected_rank = None;
+        self.ranks.clear();
+        self.rank_progress.clear();
+        self.rank_health.clear();
+        self.dist_issues.clear();
+        self.stalled_alerted.clear();
+        self.log_selection = None;
+        self.logs_scroll = 0;
+        self.logs_follow_tail = true;
+        self.annotations.clear();
+        self.metric_attrs.clear();
+        self.dirty_tree = false;
+        self.run_color = None;
+        self.gpu_count = None;
+        self.wall_secs = None;
+        self.sys_strip = SysStrip::default();
+        self.epoch_scale = None;
+        self.wall_gaps.clear();
+        self.checkpoints.clear();
+        self.tables.clear();
+        self.structured_events.clear();
+        self.events_filter = None;
+        self.events_scroll = 0;
+        self.histograms.clear();
+        self.histogram_tag = 0;
+        self.histogram_step = None;
+        self.histogram_error = None;
+        self.anomalies.clear();
+        self.anomaly_scans.clear();
+        self.early_stop_decided = false;
+        self.budget_alerted = false;
+        self.throttled_devices.clear();
+        self.reported_stalls.clear();
+        self.oom_warned.clear();
+        self.run_diff = None;
+        self.seed_groups.clear();
+        self.last_logged_step = 0;
+    }
+
+    pub fn selected_checkpoint(&self) -> Option<&Checkpoint> {
+        self.checkpoints.get(self.checkpoint_panel?)
+    }
+
+    /// Checkpoint saves and eval runs to mark on the charts, ordered by step.
+    /// Eval markers come from the steps at which `eval/…` style tags were logged.
+    pub fn chart_markers(&self) -> Vec<ChartMarker> {
+        let mut markers: Vec<ChartMarker> = self
+            .checkpoints
+            .iter()
+            .map(|checkpoint| ChartMarker {
+                step: checkpoint.step,
+                kind: MarkerKind::Checkpoint,
+                id: Some(checkpoint.id()),
+                values: checkpoint
+                    .metrics
+                    .iter()
+                    .map(|(tag, value)| (tag.clone(), *value))
+                    .collect(),
+            })
+            .collect();
+
+        let mut evals: BTreeMap<i64, Vec<(String, f64)>> = BTreeMap::new();
+        for (tag, points) in &self.scalars {
+            if !is_eval_tag(tag) {
+                continue;
+            }
+            for (step, value) in points {
+                evals
+                    .entry(*step as i64)
+                    .or_default()
+                    .push((tag.clone(), *value));
+            }
+        }
+        markers.extend(evals.into_iter().map(|(step, values)| ChartMarker {
+            step,
+            kind: MarkerKind::Eval,
+            id: None,
+            values,
+        }));
+        if let Some(report) = self.lr_report() {
+            let series = &self.scalars[&report.tag];
+            markers.extend(report.warnings.into_iter().filter_map(|warning| {
+                let step = warning.step?;
+                let value = series.iter().find(|(s, _)| *s == step).map(|(_, v)| *v)?;
+                Some(ChartMarker {
+                    step: step as i64,
+                    kind: MarkerKind::Lr,
+                    id: Some(warning.message),
+                    values: vec![(report.tag.clone(), value)],
+                })
+            }));
+        }
+        markers.sort_by_key(|marker| (marker.step, marker.kind));
+        markers
+    }
+
+    /// Watchdog state of each gradient-norm metric, in tag order.
+    pub fn grad_health(&self) -> Vec<GradHealth> {
+        self.scalars
+            .iter()
+            .filter(|(tag, _)| grad_health::is_grad_norm_tag(tag))
+            .filter_map(|(tag, series)| grad_health::check(tag, series, self.grad_limits))
+            .collect()
+    }
+
+    /// Schedule checks on the learning-rate metric, if one is logged. LR jumps
+    /// are matched against anomalies found on the loss metrics.
+    pub fn lr_report(&self) -> Option<LrReport> {
+        let tag = lr_schedule::find_lr_tag(self.scalars.keys())?;
+        let loss_spikes: Vec<f64> = self
+            .anomalies
+            .iter()
+            .filter(|(tag, _)| is_loss_tag(tag))
+            .flat_map(|(_, found)| found.iter().map(|a| a.step))
+            .collect();
+        lr_schedule::analyze(tag, &self.scalars[tag], &loss_spikes)
+    }
+
+    /// Step to the next (`delta` > 0) or previous chart marker; the first
+    /// press picks the newest one.
+    pub fn move_marker_selection(&mut self, delta: isize) {
+        let count = self.chart_markers().len();
+        if count == 0 {
+            self.selected_marker = None;
+            return;
+        }
+        self.selected_marker = Some(match self.selected_marker {
+            None => count - 1,
+            Some(current) => current.saturating_add_signed(delta).min(count - 1),
+        });
+    }
+
+    /// Quit, or ask first when quitting would take training down with the
+    /// daemon.
+    pub fn request_quit(&mut self) {
+        if self.owns_training {
+            self.quit_pending = true;
+        } else {
+            self.should_quit = true;
+        }
     }
 
     pub fn toggle_help(&mut self) {
@@ -321,11 +1309,79 @@ impl App {
 
     pub fn scroll_logs_up(&mut self) {
         self.logs_follow_tail = false;
+        if self.logs_scroll == 0 {
+            self.load_earlier_logs();
+        }
         self.logs_scroll = self.logs_scroll.saturating_sub(1);
     }
 
-    pub fn set_logs_viewport_rows(&mut self, rows: usize) {
+    /// Cap the log and chat buffers (`--log-lines`, `--chat-messages`).
+    pub fn set_buffer_limits(&mut self, log_lines: usize, chat_messages: usize) {
+        let evicted = self.log_lines.set_cap(log_lines);
+        self.forget_log_lines(evicted);
+        self.chat_limit = chat_messages.max(1);
+        self.trim_chat_messages();
+    }
+
+    /// Shift scroll state and selection after `count` lines left the front
+    /// of the log buffer.
+    fn forget_log_lines(&mut self, count: usize) {
+        if count == 0 {
+            return;
+        }
+        let gone = count.min(self.logs_line_rows.len());
+        let rows = self
+            .logs_line_rows
+            .drain(..gone)
+            .map(|rows| rows.max(1))
+            .sum::<usize>()
+            + (count - gone);
+        self.logs_rendered_rows = self.logs_rendered_rows.saturating_sub(rows);
+        if self.logs_follow_tail {
+            self.logs_scroll = self.logs_max_scroll();
+        } else {
+            self.logs_scroll = self
+                .logs_scroll
+                .saturating_sub(rows.min(u16::MAX as usize) as u16);
+        }
+        self.log_selection = self
+            .log_selection
+            .filter(|selection| *selection.range().end() >= count)
+            .map(|selection| LogSelection {
+                anchor: selection.anchor.saturating_sub(count),
+                cursor: selection.cursor.saturating_sub(count),
+            });
+    }
+
+    /// Bring a page of lines evicted to the console log back above the top.
+    fn load_earlier_logs(&mut self) {
+        match self.log_lines.load_earlier(log_buffer::LOAD_PAGE) {
+            Ok(0) => {}
+            Ok(count) => {
+                let rows: Vec<usize> = self.log_lines[..count]
+                    .iter()
+                    .map(|entry| usize::from(self.shows_log(entry)))
+                    .collect();
+                let added: usize = rows.iter().sum();
+                self.logs_line_rows.splice(0..0, rows);
+                self.logs_rendered_rows += added;
+                self.logs_scroll =
+                    (self.logs_scroll as usize + added).min(u16::MAX as usize) as u16;
+                if let Some(selection) = self.log_selection.as_mut() {
+                    selection.anchor += count;
+                    selection.cursor += count;
+                }
+            }
+            Err(err) => self.chat_status = format!("Could not read earlier log lines: {err}"),
+        }
+    }
+
+    /// Record the logs viewport height and how many rows each wrapped line
+    /// fills at the current width, then re-pin or clamp the scroll offset.
+    pub fn set_logs_viewport(&mut self, rows: usize, line_rows: Vec<usize>) {
         self.logs_viewport_rows = rows.max(1);
+        self.logs_rendered_rows = line_rows.iter().sum();
+        self.logs_line_rows = line_rows;
         if self.logs_follow_tail {
             self.logs_scroll = self.logs_max_scroll();
         } else {
@@ -333,10 +1389,154 @@ impl App {
         }
     }
 
+    /// Whether the level filter lets `entry` into the logs tab.
+    pub fn shows_log(&self, entry: &LogEntry) -> bool {
+        self.log_level_filter
+            .is_none_or(|level| entry.level >= level)
+    }
+
+    /// Step the logs tab filter: every entry, warnings and up, errors only.
+    pub fn cycle_log_filter(&mut self) {
+        self.log_level_filter = LogLevel::next_filter(self.log_level_filter);
+        self.log_selection = None;
+        self.logs_line_rows.clear();
+        self.logs_rendered_rows = self
+            .log_lines
+            .iter()
+            .filter(|entry| self.shows_log(entry))
+            .count();
+        self.logs_follow_tail = true;
+        self.logs_scroll = self.logs_max_scroll();
+        self.chat_status = match self.log_level_filter {
+            Some(level) => format!("Logs: {} and up", level.as_str()),
+            None => "Logs: all levels".to_string(),
+        };
+    }
+
+    /// Rows log entry `idx` takes: none when filtered out, one per wrapped
+    /// row otherwise (one until the first draw).
+    fn log_rows(&self, idx: usize) -> usize {
+        if !self
+            .log_lines
+            .get(idx)
+            .is_some_and(|entry| self.shows_log(entry))
+        {
+            return 0;
+        }
+        self.logs_line_rows.get(idx).copied().unwrap_or(1).max(1)
+    }
+
     fn logs_max_scroll(&self) -> u16 {
-        self.log_lines
-            .len()
-            .saturating_sub(self.logs_viewport_rows.max(1)) as u16
+        let rendered = if self.log_level_filter.is_some() {
+            self.logs_rendered_rows
+        } else {
+            self.logs_rendered_rows.max(self.log_lines.len())
+        };
+        rendered
+            .saturating_sub(self.logs_viewport_rows.max(1))
+            .min(u16::MAX as usize) as u16
+    }
+
+    /// Logical log lines with at least one row inside the viewport.
+    pub fn visible_log_range(&self) -> Range<usize> {
+        let top = self.logs_scroll as usize;
+        let bottom = top + self.logs_viewport_rows.max(1);
+        let mut start = self.log_lines.len();
+        let mut end = self.log_lines.len();
+        let mut row = 0;
+        for idx in 0..self.log_lines.len() {
+            if row >= bottom {
+                end = idx;
+                break;
+            }
+            row += self.log_rows(idx);
+            if row > top {
+                start = start.min(idx);
+            }
+        }
+        start.min(end)..end
+    }
+
+    /// Enter visual selection on the last line on screen.
+    pub fn start_log_selection(&mut self) {
+        let Some(line) = self
+            .visible_log_range()
+            .rev()
+            .find(|&idx| self.log_rows(idx) > 0)
+        else {
+            return;
+        };
+        self.log_selection = Some(LogSelection {
+            anchor: line,
+            cursor: line,
+        });
+        self.logs_follow_tail = false;
+    }
+
+    /// Move the selection cursor by `delta` lines, scrolling to keep it visible.
+    pub fn move_log_cursor(&mut self, delta: isize) {
+        let Some(mut cursor) = self.log_selection.map(|selection| selection.cursor) else {
+            return;
+        };
+        for _ in 0..delta.unsigned_abs() {
+            let next = if delta < 0 {
+                (0..cursor).rev().find(|&idx| self.log_rows(idx) > 0)
+            } else {
+                (cursor + 1..self.log_lines.len()).find(|&idx| self.log_rows(idx) > 0)
+            };
+            match next {
+                Some(idx) => cursor = idx,
+                None => break,
+            }
+        }
+        if let Some(selection) = self.log_selection.as_mut() {
+            selection.cursor = cursor;
+        }
+        self.logs_follow_tail = false;
+        self.scroll_log_line_into_view(cursor);
+    }
+
+    fn clamp_log_selection(&mut self) {
+        let Some(last) = self.log_lines.len().checked_sub(1) else {
+            self.log_selection = None;
+            return;
+        };
+        if let Some(selection) = self.log_selection.as_mut() {
+            selection.anchor = selection.anchor.min(last);
+            selection.cursor = selection.cursor.min(last);
+        }
+    }
+
+    fn scroll_log_line_into_view(&mut self, line: usize) {
+        let first_row: usize = (0..line).map(|idx| self.log_rows(idx)).sum();
+        let end_row = first_row + self.log_rows(line);
+        let viewport = self.logs_viewport_rows.max(1);
+        let top = self.logs_scroll as usize;
+        if first_row < top {
+            self.logs_scroll = first_row.min(u16::MAX as usize) as u16;
+        } else if end_row > top + viewport {
+            let top = end_row.saturating_sub(viewport).min(first_row);
+            self.logs_scroll = top.min(u16::MAX as usize) as u16;
+        }
+        self.clamp_logs_scroll();
+    }
+
+    /// Queue an OSC 52 sequence putting `text` on the system clipboard.
+    pub fn copy_to_clipboard(&mut self, text: &str, what: &str) {
+        self.pending_terminal_notifications
+            .push(crate::clipboard::osc52(text));
+        self.chat_status = format!("Copied {what} to clipboard");
+        self.toast(ToastLevel::Info, format!("Copied {what}"));
+    }
+
+    /// Record how far the wrapped chat transcript can scroll; following the
+    /// tail pins to the bottom, otherwise the offset is clamped.
+    pub fn set_chat_max_scroll(&mut self, max_scroll: u16) {
+        self.chat_max_scroll = max_scroll;
+        if self.chat_follow_tail || self.chat_scroll >= max_scroll {
+            self.chat_scroll = max_scroll;
+            self.chat_follow_tail = true;
+        }
     }
 
     fn clamp_logs_scroll(&mut self) {
@@ -365,7 +1565,7 @@ impl App {
     }
 
     /// Scroll the metrics grid so the selected metric is visible.
-    fn ensure_metric_visible(&mut self) {
+    pub fn ensure_metric_visible(&mut self) {
         if self.tags.is_empty() || self.metrics_cols == 0 {
             return;
         }
@@ -391,6 +1591,19 @@ impl App {
         self.ensure_metric_visible();
     }
 
+    pub fn scroll_stats_down(&mut self) {
+        self.stats_scroll = (self.stats_scroll + 1).min(self.stats_max_scroll);
+    }
+
+    pub fn scroll_stats_up(&mut self) {
+        self.stats_scroll = self.stats_scroll.saturating_sub(1);
+    }
+
+    pub fn set_stats_max_scroll(&mut self, max_scroll: u16) {
+        self.stats_max_scroll = max_scroll;
+        self.stats_scroll = self.stats_scroll.min(max_scroll);
+    }
+
     pub fn scroll_metrics_up(&mut self) {
         if self.tags.is_empty() || self.metrics_cols == 0 {
             return;
@@ -409,8 +1622,75 @@ impl App {
         }
     }
 
+    /// A key pressed while comparing the focused metric with another run:
+    /// `o` switches layout, `+` / `-` zoom, `<` / `>` pan, `0` shows every step.
+    pub fn run_diff_key(&mut self, key: char) {
+        let Some(tag) = self.focused_metric.and_then(|idx| self.tags.get(idx)) else {
+            return;
+        };
+        let Some(diff) = self.run_diff.as_ref() else {
+            return;
+        };
+        let (loaded, other, _) = self.run_diff_series(diff, tag);
+        let full = run_diff::span(&loaded, &other);
+        let Some(diff) = self.run_diff.as_mut() else {
+            return;
+        };
+        match (key, full) {
+            ('o', _) => diff.toggle_layout(),
+            ('0', _) => diff.window = None,
+            ('+' | '=', Some(full)) => diff.zoom(full, 0.5),
+            ('-', Some(full)) => diff.zoom(full, 2.0),
+            ('<', Some(full)) => diff.pan(full, -1.0),
+            ('>', Some(full)) => diff.pan(full, 1.0),
+            _ => {}
+        }
+    }
+
+    /// Switch charts between steps and epochs. Runs without an epoch scale
+    /// stay on steps.
+    pub fn toggle_epoch_axis(&mut self) {
+        if self.epoch_scale.is_none() && !self.epoch_axis {
+            self.chat_status =
+                "No epoch axis: the run logs no epoch counter and sets no steps_per_epoch"
+                    .to_string();
+            return;
+        }
+        self.epoch_axis = !self.epoch_axis;
+        // The comparison window is in the old axis' units.
+        if let Some(diff) = self.run_diff.as_mut() {
+            diff.window = None;
+        }
+    }
+
+    /// The loaded run's epoch scale while charts run along epochs.
+    pub fn x_epochs(&self) -> Option<&EpochScale> {
+        self.epoch_scale.as_ref().filter(|_| self.epoch_axis)
+    }
+
+    /// `tag` of the loaded run and of `diff`'s run, along epochs while the
+    /// epoch axis is on and both runs have a scale, else along steps. The
+    /// flag says which.
+    pub fn run_diff_series<'a>(
+        &'a self,
+        diff: &'a RunDiff,
+        tag: &str,
+    ) -> (Series<'a>, Series<'a>, bool) {
+        let loaded = self.scalars.get(tag).map_or(&[][..], Vec::as_slice);
+        let other = diff.scalars.get(tag).map_or(&[][..], Vec::as_slice);
+        match (self.x_epochs(), diff.epoch_scale.as_ref()) {
+            (Some(ours), Some(theirs)) => (
+                Cow::Owned(ours.align(loaded)),
+                Cow::Owned(theirs.align(other)),
+                true,
+            ),
+            _ => (Cow::Borrowed(loaded), Cow::Borrowed(other), false),
+        }
+    }
+
     pub fn unfocus_metric(&mut self) {
         self.focused_metric = None;
+        self.selected_marker = None;
     }
 
     // ── Chat methods ────────────────────────────────────────────────────
@@ -428,7 +1708,10 @@ impl App {
     }
 
     pub fn scroll_chat_down(&mut self) {
-        self.chat_scroll = self.chat_scroll.saturating_add(1);
+        self.chat_scroll = (self.chat_scroll + 1).min(self.chat_max_scroll);
+        if self.chat_scroll >= self.chat_max_scroll {
+            self.chat_follow_tail = true;
+        }
     }
 
     pub fn scroll_chat_up(&mut self) {
@@ -445,11 +1728,21 @@ impl App {
                     ExitedProcess {
                         snapshot: previous.clone(),
                         exited_at_unix,
+                        training: self.training_pids.contains(&previous.pid),
                     },
                 );
             }
         }
 
+        self.process_history
+            .retain(|pid, _| current_pids.contains(pid));
+        for process in &running {
+            self.process_history
+                .entry(process.pid)
+                .or_default()
+                .push(process);
+        }
+        self.training_pids = training_tree(&running, &self.process_roots);
         self.running_processes = running;
         self.exited_processes.truncate(self.process_limit);
 
@@ -460,6 +1753,27 @@ impl App {
         }
     }
 
+    /// Running processes the processes tab lists under the current filter.
+    pub fn shown_processes(&self) -> Vec<&ProcessSnapshot> {
+        self.running_processes
+            .iter()
+            .filter(|p| !self.process_filter || self.training_pids.contains(&p.pid))
+            .collect()
+    }
+
+    /// Exited processes the processes tab lists under the current filter.
+    pub fn shown_exited_processes(&self) -> Vec<&ExitedProcess> {
+        self.exited_processes
+            .iter()
+            .filter(|p| !self.process_filter || p.training)
+            .collect()
+    }
+
+    pub fn toggle_process_filter(&mut self) {
+        self.process_filter = !self.process_filter;
+        self.clamp_processes_scroll();
+    }
+
     pub fn scroll_processes_down(&mut self) {
         let max = self.processes_max_scroll();
         self.processes_scroll = (self.processes_scroll + 1).min(max);
@@ -468,6 +1782,321 @@ impl App {
         }
     }
 
+    pub fn scroll_dashboard_down(&mut self) {
+        let max = self.dashboard_rows.len().saturating_sub(1);
+        self.dashboard_scroll = (self.dashboard_scroll + 1).min(max);
+    }
+
+    pub fn scroll_dashboard_up(&mut self) {
+        self.dashboard_scroll = self.dashboard_scroll.saturating_sub(1);
+    }
+
+    /// Event types of the run with how many events each has, by type name.
+    pub fn event_types(&self) -> BTreeMap<&str, usize> {
+        let mut types = BTreeMap::new();
+        for event in &self.structured_events {
+            *types.entry(event.kind.as_str()).or_default() += 1;
+        }
+        types
+    }
+
+    /// Events the events tab lists under the current type filter.
+    pub fn filtered_events(&self) -> Vec<&StructuredEvent> {
+        self.structured_events
+            .iter()
+            .filter(|event| {
+                self.events_filter
+                    .as_deref()
+                    .is_none_or(|kind| event.kind == kind)
+            })
+            .collect()
+    }
+
+    /// Show the next event type, then all types again after the last one.
+    pub fn cycle_events_filter(&mut self) {
+        let next = {
+            let types: Vec<&str> = self.event_types().into_keys().collect();
+            match self.events_filter.as_deref() {
+                None => types.first().map(|kind| kind.to_string()),
+                Some(current) => types
+                    .iter()
+                    .skip_while(|kind| **kind != current)
+                    .nth(1)
+                    .map(|kind| kind.to_string()),
+            }
+        };
+        self.events_filter = next;
+        self.events_scroll = 0;
+    }
+
+    pub fn set_structured_events(&mut self, events: Vec<StructuredEvent>) {
+        self.structured_events = events;
+        if self
+            .events_filter
+            .as_deref()
+            .is_some_and(|kind| !self.structured_events.iter().any(|e| e.kind == kind))
+        {
+            self.events_filter = None;
+        }
+        self.events_scroll = self
+            .events_scroll
+            .min(self.filtered_events().len().saturating_sub(1));
+    }
+
+    pub fn scroll_events_down(&mut self) {
+        let max = self.filtered_events().len().saturating_sub(1);
+        self.events_scroll = (self.events_scroll + 1).min(max);
+    }
+
+    pub fn scroll_events_up(&mut self) {
+        self.events_scroll = self.events_scroll.saturating_sub(1);
+    }
+
+    /// Seed groups that logged `tag`.
+    pub fn seed_bands(&self, tag: &str) -> Vec<&SeedGroup> {
+        self.seed_groups
+            .iter()
+            .filter(|group| group.scalars.contains_key(tag))
+            .collect()
+    }
+
+    /// Swap in reloaded histograms, keeping the shown tag and picked step.
+    pub fn set_histograms(&mut self, histograms: BTreeMap<String, Vec<Histogram>>) {
+        let shown = self.selected_histograms().map(|(tag, _)| tag.clone());
+        self.histograms = histograms;
+        self.histogram_error = None;
+        self.histogram_tag = shown
+            .and_then(|tag| self.histograms.keys().position(|t| *t == tag))
+            .unwrap_or(0);
+        let last = self
+            .selected_histograms()
+            .map_or(0, |(_, h)| h.len().saturating_sub(1));
+        self.histogram_step = self.histogram_step.map(|step| step.min(last));
+    }
+
+    /// Tag shown in the distributions tab and its histograms, oldest first.
+    pub fn selected_histograms(&self) -> Option<(&String, &[Histogram])> {
+        self.histograms
+            .iter()
+            .nth(self.histogram_tag)
+            .map(|(tag, histograms)| (tag, histograms.as_slice()))
+    }
+
+    /// Position of the step on the slider: the picked one, else the latest.
+    pub fn histogram_step_index(&self) -> Option<usize> {
+        let (_, histograms) = self.selected_histograms()?;
+        let last = histograms.len().checked_sub(1)?;
+        Some(self.histogram_step.unwrap_or(last).min(last))
+    }
+
+    pub fn move_histogram_tag(&mut self, delta: isize) {
+        let last = self.histograms.len().saturating_sub(1);
+        self.histogram_tag = self.histogram_tag.saturating_add_signed(delta).min(last);
+        self.histogram_step = None;
+    }
+
+    /// Move the step slider; reaching the latest step follows new ones again.
+    pub fn move_histogram_step(&mut self, delta: isize) {
+        let Some(current) = self.histogram_step_index() else {
+            return;
+        };
+        let last = self.selected_histograms().map_or(0, |(_, h)| h.len() - 1);
+        let step = current.saturating_add_signed(delta).min(last);
+        self.histogram_step = (step < last).then_some(step);
+    }
+
+    /// Store freshly measured run sizes and free space, raising an alert when
+    /// free space first drops below `min_free_bytes`.
+    pub fn apply_disk_usage(&mut self, sizes: BTreeMap<String, u64>, free_bytes: Option<u64>) {
+        let floor = self.min_free_bytes;
+        let was_low = self.runs_free_bytes.is_some_and(|free| free < floor);
+        self.run_sizes = sizes;
+        self.runs_free_bytes = free_bytes;
+        if let Some(free) = free_bytes
+            && free < floor
+            && !was_low
+        {
+            let message = format!(
+                "Low disk: {} free under {} (floor {})",
+                format::bytes(free),
+                self.dashboard_root.display(),
+                format::bytes(floor)
+            );
+            self.append_live_log(LogLevel::Important, message.clone());
+            self.toast(ToastLevel::Alert, message);
+        }
+    }
+
+    /// Judge new points of the watched metrics and alert on anomalies among
+    /// them. A metric's first scan only marks its history on the charts, so
+    /// opening an old run does not replay its alerts.
+    pub fn scan_anomalies(&mut self) {
+        let mut alerts = Vec::new();
+        for (tag, series) in &self.scalars {
+            if !self
+                .anomaly_metrics
+                .iter()
+                .any(|selector| graph_filter::selector_matches(tag, selector))
+            {
+                continue;
+            }
+            let state = self.anomaly_scans.entry(tag.clone()).or_default();
+            // After a restart the series is judged afresh.
+            if let (Some(scanned), Some((last, _))) = (state.scanned_until, series.last())
+                && *last < scanned
+            {
+                *state = ScanState::default();
+                self.anomalies.remove(tag);
+            }
+            let first_scan = state.scanned_until.is_none();
+            let found = anomaly::scan(series, state, anomaly::DEFAULT_WINDOW, self.anomaly_z);
+            if found.is_empty() {
+                continue;
+            }
+            if !first_scan {
+                alerts.extend(found.iter().map(|a| (tag.clone(), *a)));
+            }
+            self.anomalies.entry(tag.clone()).or_default().extend(found);
+        }
+        for (tag, anomaly) in alerts {
+            let message = format!(
+                "{} {} at step {:.0} (value {})",
+                self.metric_display_name(&tag),
+                anomaly.kind.label(),
+                anomaly.step,
+                anomaly.value
+            );
+            self.append_live_log(LogLevel::Important, format!("anomaly: {message}"));
+            self.toast(ToastLevel::Alert, format!("Anomaly: {message}"));
+        }
+    }
+
+    /// Estimated spend of the loaded run so far.
+    pub fn spend(&self) -> Option<Spend> {
+        cost::estimate(self.wall_secs?, self.gpu_count?, self.pricing)
+    }
+
+    /// Alert once when the loaded run's estimated spend crosses the budget.
+    pub fn check_budget(&mut self) {
+        if self.budget_alerted {
+            return;
+        }
+        let Some(spend) = self.spend().filter(|spend| spend.over_budget) else {
+            return;
+        };
+        self.budget_alerted = true;
+        let message = format!("Over budget: {}", spend.describe());
+        self.append_live_log(LogLevel::Important, message.clone());
+        self.toast(ToastLevel::Alert, message);
+    }
+
+    /// Alert when devices start thermally throttling. A device is reported
+    /// again only after it has recovered.
+    pub fn check_thermal(&mut self) {
+        let throttled = thermal::detect(&self.scalars);
+        let new: Vec<&thermal::Throttle> = throttled
+            .iter()
+            .filter(|throttle| !self.throttled_devices.contains(&throttle.device))
+            .collect();
+        if !new.is_empty() {
+            let labels: Vec<String> = new.iter().map(|throttle| throttle.label()).collect();
+            let details: Vec<String> = new.iter().map(|throttle| throttle.describe()).collect();
+            self.append_live_log(
+                LogLevel::Important,
+                format!("thermal throttling: {}", details.join("; ")),
+            );
+            self.toast(
+                ToastLevel::Thermal,
+                format!("Thermal throttling on {}", labels.join(", ")),
+            );
+        }
+        self.throttled_devices = throttled
+            .into_iter()
+            .map(|throttle| throttle.device)
+            .collect();
+    }
+
+    /// Projected exhaustion of each memory metric that is growing like a leak.
+    pub fn oom_forecasts(&self) -> Vec<Forecast> {
+        oom_forecast::scan(&self.scalars)
+    }
+
+    /// Mean wall-clock seconds per step of the loaded run so far.
+    pub fn secs_per_step(&self) -> Option<f64> {
+        let secs = self.wall_secs?;
+        (self.max_step > 0).then(|| secs / self.max_step as f64)
+    }
+
+    /// Warn once per memory metric when it starts heading for exhaustion.
+    pub fn check_oom(&mut self) {
+        for forecast in self.oom_forecasts() {
+            if !self.oom_warned.insert(forecast.tag.clone()) {
+                continue;
+            }
+            let message = format!(
+                "Memory may run out: {}",
+                forecast.describe(
+                    self.metric_display_name(&forecast.tag),
+                    self.secs_per_step()
+                )
+            );
+            self.append_live_log(LogLevel::Important, message.clone());
+            self.toast(ToastLevel::Alert, message);
+        }
+    }
+
+    /// Log each suspected dataloader stall once it is long enough to report.
+    /// Returns the new diagnostics so they can be passed on to the agent.
+    pub fn check_input_stalls(&mut self) -> Vec<String> {
+        let mut diagnostics = Vec::new();
+        for stall in input_stall::detect(&self.scalars) {
+            if self.reported_stalls.insert(stall.first_step as i64) {
+                diagnostics.push(stall.describe());
+            }
+        }
+        for diagnostic in &diagnostics {
+            self.append_live_log(LogLevel::Important, diagnostic.clone());
+        }
+        diagnostics
+    }
+
+    /// Report the objective metric's plateau the first time it shows up while
+    /// the run is live. The caller records the decision and, in autonomous
+    /// mode, stops training.
+    pub fn check_convergence(&mut self) -> Option<Plateau> {
+        if self.early_stop_decided || !self.live_logs_active {
+            return None;
+        }
+        let policy = self.early_stop.as_ref()?;
+        let series = self.scalars.get(&policy.metric)?;
+        let plateau = convergence::plateau(series, policy.mode, policy.min_delta, policy.patience)?;
+        self.early_stop_decided = true;
+        let message = format!(
+            "{} has not improved by more than {} for {} evaluations (best {} at step {:.0})",
+            self.metric_display_name(&policy.metric),
+            policy.min_delta,
+            plateau.evaluations,
+            plateau.best,
+            plateau.best_step
+        );
+        self.append_live_log(
+            LogLevel::Important,
+            format!("early stop suggested: {message}"),
+        );
+        self.toast(
+            ToastLevel::Alert,
+            format!("Early stop suggested: {message}"),
+        );
+        Some(plateau)
+    }
+
+    pub fn set_dashboard_rows(&mut self, rows: Vec<DashboardRow>) {
+        self.dashboard_rows = rows;
+        self.dashboard_scroll = self
+            .dashboard_scroll
+            .min(self.dashboard_rows.len().saturating_sub(1));
+    }
+
     pub fn scroll_processes_up(&mut self) {
         self.processes_follow_tail = false;
         self.processes_scroll = self.processes_scroll.saturating_sub(1);
@@ -505,19 +2134,38 @@ impl App {
 
     pub fn update_chat_messages(&mut self, messages: Vec<ChatMessage>) {
         self.chat_messages = messages;
-        if self.chat_follow_tail {
-            // Clamp to bottom during render once wrapped line count is known.
-            self.chat_scroll = u16::MAX;
-        }
+        self.chat_hidden = 0;
+        self.trim_chat_messages();
+    }
+
+    pub fn push_chat_message(&mut self, message: ChatMessage) {
+        self.chat_messages.push(message);
+        self.trim_chat_messages();
+    }
+
+    fn trim_chat_messages(&mut self) {
+        let overflow = self.chat_messages.len().saturating_sub(self.chat_limit);
+        self.chat_messages.drain(..overflow);
+        self.chat_hidden += overflow;
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{App, ProcessSnapshot};
+    use super::{
+        App, Card, Checkpoint, EarlyStopPolicy, MAX_TOASTS, MarkerKind, MetricAttrs, NotifyMode,
+        ObjectiveMode, PROCESS_HISTORY_LEN, Pane, ProcessSnapshot, RankProgress, RankStatus,
+        StructuredEvent, TOAST_TTL, Tab, ToastLevel,
+    };
+    use crate::log_entry::{LogEntry, LogLevel, LogSource};
+    use crate::socket_client::ChatMessage;
     use std::collections::BTreeMap;
     use std::path::PathBuf;
 
+    fn line(idx: usize) -> LogEntry {
+        LogEntry::new(LogLevel::Info, LogSource::Og, format!("line {idx}"))
+    }
+
     fn empty_app() -> App {
         App::new(
             BTreeMap::new(),
@@ -529,6 +2177,89 @@ mod tests {
         )
     }
 
+    #[test]
+    fn tab_walks_panes_then_tabs() {
+        let mut app = empty_app();
+        assert_eq!(
+            (app.active_tab, app.focused_pane()),
+            (Tab::Chat, Pane::Main)
+        );
+        app.focus_next_pane();
+        assert_eq!(app.focused_pane(), Pane::Input);
+        assert!(app.chat_input_focused, "Tab into the input starts typing");
+        app.focus_next_pane();
+        assert_eq!(
+            (app.active_tab, app.focused_pane()),
+            (Tab::Graphs, Pane::Main)
+        );
+        assert!(!app.chat_input_focused);
+        app.focus_next_pane();
+        assert_eq!(
+            (app.active_tab, app.focused_pane()),
+            (Tab::Graphs, Pane::Side)
+        );
+
+        // Jumping to a single-pane tab (`:tab`, a click) shows its only pane.
+        app.active_tab = Tab::Logs;
+        assert_eq!(app.focused_pane(), Pane::Main);
+        app.focus_prev_pane();
+        assert_eq!(
+            (app.active_tab, app.focused_pane()),
+            (Tab::Processes, Pane::Main)
+        );
+
+        app.active_tab = Tab::Graphs;
+        app.pane_focus = Pane::Main;
+        app.focus_prev_pane();
+        assert_eq!(
+            (app.active_tab, app.focused_pane()),
+            (Tab::Chat, Pane::Input)
+        );
+        app.chat_input_focused = false; // Esc
+        assert_eq!(app.focused_pane(), Pane::Main);
+    }
+
+    #[test]
+    fn process_filter_keeps_the_training_tree_and_gpu_users() {
+        let mut app = empty_app();
+        let process = |pid: i32, ppid: i32, gpu_mem_mb: Option<f64>| ProcessSnapshot {
+            pid,
+            ppid,
+            state: "S".to_string(),
+            elapsed: "00:01".to_string(),
+            elapsed_secs: 1,
+            cpu_pct: 0.0,
+            mem_pct: 0.0,
+            command: format!("cmd{pid}"),
+            gpu_mem_mb,
+        };
+        app.process_roots = vec![10, 999];
+        app.update_processes(
+            vec![
+                process(10, 1, None),  // daemon
+                process(11, 10, None), // its training run
+                process(12, 11, None), // a dataloader worker
+                process(20, 1, None),  // an editor
+                process(30, 1, Some(512.0)),
+            ],
+            1_700_000_000,
+        );
+        let shown =
+            |app: &App| -> Vec<i32> { app.shown_processes().iter().map(|p| p.pid).collect() };
+        assert_eq!(shown(&app), [10, 11, 12, 30]);
+
+        app.update_processes(vec![process(10, 1, None)], 1_700_000_100);
+        let exited: Vec<i32> = app
+            .shown_exited_processes()
+            .iter()
+            .map(|p| p.snapshot.pid)
+            .collect();
+        assert_eq!(exited, [30, 12, 11], "the editor's exit is filtered out");
+
+        app.toggle_process_filter();
+        assert_eq!(app.shown_exited_processes().len(), 4);
+    }
+
     #[test]
     fn update_processes_tracks_recently_exited() {
         let mut app = empty_app();
@@ -541,6 +2272,7 @@ mod tests {
             cpu_pct: 10.0,
             mem_pct: 2.0,
             command: "python train.py".to_string(),
+            gpu_mem_mb: Some(2048.0),
         };
         let p2 = ProcessSnapshot {
             pid: 101,
@@ -551,16 +2283,30 @@ mod tests {
             cpu_pct: 1.0,
             mem_pct: 0.5,
             command: "bash".to_string(),
+            gpu_mem_mb: None,
         };
 
         app.update_processes(vec![p1.clone(), p2.clone()], 1_700_000_000);
         assert_eq!(app.running_processes.len(), 2);
         assert!(app.exited_processes.is_empty());
+        assert_eq!(app.process_history[&100].gpu_mem_mb, [2048.0]);
 
-        app.update_processes(vec![p2], 1_700_000_100);
+        app.update_processes(vec![p2.clone()], 1_700_000_100);
         assert_eq!(app.running_processes.len(), 1);
         assert_eq!(app.exited_processes.len(), 1);
         assert_eq!(app.exited_processes[0].snapshot.pid, p1.pid);
+        assert!(!app.process_history.contains_key(&100));
+        assert_eq!(app.process_history[&101].cpu_pct, [1.0, 1.0]);
+        assert!(app.process_history[&101].gpu_mem_mb.is_empty());
+
+        for _ in 0..PROCESS_HISTORY_LEN {
+            app.update_processes(vec![p2.clone()], 1_700_000_200);
+        }
+        assert_eq!(
+            app.process_history[&101].mem_pct.len(),
+            PROCESS_HISTORY_LEN,
+            "history keeps the latest samples only"
+        );
     }
 
     #[test]
@@ -570,5 +2316,625 @@ mod tests {
             .insert("train/loss".to_string(), "Loss".to_string());
         assert_eq!(app.metric_display_name("train/loss"), "Loss");
         assert_eq!(app.metric_display_name("train/accuracy"), "train/accuracy");
+
+        app.metric_attrs.insert(
+            "train/accuracy".to_string(),
+            MetricAttrs {
+                display_name: Some("Accuracy".to_string()),
+                higher_is_better: Some(true),
+                ..MetricAttrs::default()
+            },
+        );
+        app.metric_attrs.insert(
+            "train/loss".to_string(),
+            MetricAttrs {
+                display_name: Some("Training loss".to_string()),
+                ..MetricAttrs::default()
+            },
+        );
+        assert_eq!(app.metric_display_name("train/accuracy"), "Accuracy");
+        assert_eq!(app.metric_display_name("train/loss"), "Loss");
+        assert_eq!(app.higher_is_better("train/accuracy"), Some(true));
+        assert_eq!(app.higher_is_better("train/loss"), None);
+    }
+
+    #[test]
+    fn bounded_metrics_get_gauges_unless_recorded_otherwise() {
+        let mut app = empty_app();
+        let series = |values: &[f64]| -> Vec<(f64, f64)> {
+            values
+                .iter()
+                .enumerate()
+                .map(|(step, v)| (step as f64, *v))
+                .collect()
+        };
+        for (tag, values) in [
+            ("val/acc", series(&[0.2, 0.9])),
+            ("val/top1_accuracy", series(&[12.0, 71.5])),
+            ("sys/gpu0/util_pct", series(&[40.0, 100.0])),
+            ("train/f1", series(&[0.5, 1.5])),
+            ("train/loss", series(&[2.0, 0.5])),
+            ("train/lr", series(&[0.1, 0.2])),
+        ] {
+            app.scalars.insert(tag.to_string(), values);
+        }
+        assert_eq!(app.metric_gauge("val/acc"), Some((0.0, 1.0)));
+        assert_eq!(app.metric_gauge("val/top1_accuracy"), Some((0.0, 100.0)));
+        assert_eq!(app.metric_gauge("sys/gpu0/util_pct"), Some((0.0, 100.0)));
+        assert_eq!(app.metric_gauge("train/f1"), Some((0.0, 100.0)));
+        assert_eq!(app.metric_gauge("train/loss"), None);
+
+        app.metric_attrs.insert(
+            "val/acc".to_string(),
+            MetricAttrs {
+                card: Some(Card::Line),
+                ..MetricAttrs::default()
+            },
+        );
+        app.metric_attrs.insert(
+            "train/loss".to_string(),
+            MetricAttrs {
+                bounds: Some([0.0, 4.0]),
+                ..MetricAttrs::default()
+            },
+        );
+        app.metric_attrs.insert(
+            "train/lr".to_string(),
+            MetricAttrs {
+                card: Some(Card::Gauge),
+                ..MetricAttrs::default()
+            },
+        );
+        assert_eq!(app.metric_gauge("val/acc"), None);
+        assert_eq!(app.metric_gauge("train/loss"), Some((0.0, 4.0)));
+        assert_eq!(app.metric_gauge("train/lr"), Some((0.1, 0.2)));
+    }
+
+    #[test]
+    fn cycle_rank_walks_merged_view_then_each_rank() {
+        let mut app = empty_app();
+        app.cycle_rank();
+        assert_eq!(app.selected_rank, None);
+
+        app.set_ranks(vec![0, 1, 3]);
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            app.cycle_rank();
+            seen.push(app.selected_rank);
+        }
+        assert_eq!(seen, vec![Some(0), Some(1), Some(3), None]);
+
+        app.selected_rank = Some(3);
+        app.set_ranks(vec![0, 1]);
+        assert_eq!(app.selected_rank, None);
+    }
+
+    #[test]
+    fn stalled_rank_raises_one_alert_and_nccl_lines_are_recorded() {
+        let mut app = empty_app();
+        let progress = |wall_1: f64| {
+            let mut progress = BTreeMap::new();
+            progress.insert(
+                0,
+                RankProgress {
+                    last_step: 500,
+                    last_wall_time: 1_000.0,
+                },
+            );
+            progress.insert(
+                1,
+                RankProgress {
+                    last_step: 200,
+                    last_wall_time: wall_1,
+                },
+            );
+            progress
+        };
+
+        app.set_rank_progress(progress(990.0));
+        assert!(app.stalled_alerted.is_empty());
+
+        app.set_rank_progress(progress(600.0));
+        app.set_rank_progress(progress(600.0));
+        let alerts = app
+            .log_lines
+            .iter()
+            .filter(|entry| {
+                entry
+                    .message
+                    .contains("rank 1 stopped producing events at step 200")
+            })
+            .count();
+        assert_eq!(alerts, 1);
+        assert!(app.dist_issues.is_empty());
+
+        app.append_live_log(
+            LogLevel::Error,
+            "[Rank 1] NCCL watchdog timeout after 600000ms",
+        );
+        assert_eq!(app.dist_issues.len(), 1);
+        assert_eq!(app.rank_health[1].status, RankStatus::TimedOut);
+    }
+
+    #[test]
+    fn visible_log_range_accounts_for_wrapped_lines() {
+        let mut app = empty_app();
+        app.log_lines = (0..6).map(line).collect();
+        app.logs_follow_tail = false;
+        app.set_logs_viewport(3, vec![1, 2, 1, 3, 1, 1]);
+        assert_eq!(app.visible_log_range(), 0..2);
+
+        app.logs_scroll = 2;
+        assert_eq!(app.visible_log_range(), 1..4);
+
+        app.copy_to_clipboard("line 1", "1 log line");
+        assert_eq!(
+            app.pending_terminal_notifications,
+            vec!["\x1b]52;c;bGluZSAx\x07".to_string()]
+        );
+    }
+
+    #[test]
+    fn log_selection_extends_over_logical_lines_and_scrolls() {
+        let mut app = empty_app();
+        app.log_lines = (0..6).map(line).collect();
+        app.set_logs_viewport(3, vec![1, 2, 1, 3, 1, 1]);
+        assert_eq!(app.logs_scroll, 6);
+
+        app.start_log_selection();
+        assert_eq!(app.log_selection.map(|s| s.cursor), Some(5));
+        app.move_log_cursor(-2);
+        assert_eq!(app.logs_scroll, 4, "wrapped line 3 starts at row 4");
+        app.move_log_cursor(-1);
+        assert_eq!(app.logs_scroll, 3);
+        assert!(!app.logs_follow_tail);
+
+        assert_eq!(app.log_selection.map(|s| s.range()), Some(2..=5));
+
+        app.replace_data(BTreeMap::new(), vec![line(0)], 0, 0);
+        assert_eq!(app.log_selection.map(|s| s.range()), Some(0..=0));
+    }
+
+    #[test]
+    fn level_filter_hides_entries_from_rows_and_the_cursor() {
+        let mut app = empty_app();
+        let levels = [
+            LogLevel::Info,
+            LogLevel::Warn,
+            LogLevel::Success,
+            LogLevel::Error,
+            LogLevel::Info,
+            LogLevel::Important,
+        ];
+        app.log_lines = levels
+            .iter()
+            .enumerate()
+            .map(|(idx, &level)| LogEntry::new(level, LogSource::Og, format!("line {idx}")))
+            .collect();
+
+        app.cycle_log_filter();
+        assert_eq!(app.log_level_filter, Some(LogLevel::Warn));
+        assert_eq!(app.chat_status, "Logs: warn and up");
+        assert_eq!(app.logs_rendered_rows, 3);
+        app.set_logs_viewport(10, vec![0, 1, 0, 2, 0, 1]);
+        assert_eq!(app.logs_scroll, 0);
+
+        app.start_log_selection();
+        assert_eq!(app.log_selection.map(|s| s.cursor), Some(5));
+        app.move_log_cursor(-2);
+        assert_eq!(app.log_selection.map(|s| s.cursor), Some(1));
+        app.move_log_cursor(-1);
+        assert_eq!(app.log_selection.map(|s| s.cursor), Some(1));
+
+        app.cycle_log_filter();
+        assert_eq!(app.log_level_filter, Some(LogLevel::Error));
+        assert!(app.log_selection.is_none());
+        assert_eq!(app.logs_rendered_rows, 1);
+        app.cycle_log_filter();
+        assert_eq!(app.log_level_filter, None);
+        assert_eq!(app.chat_status, "Logs: all levels");
+    }
+
+    #[test]
+    fn capped_live_logs_spill_and_load_back_when_scrolling_up() {
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let run_dir = std::env::temp_dir().join(format!("ogtui-app-logs-{nonce}"));
+        std::fs::create_dir_all(&run_dir).expect("run dir");
+        let mut app = empty_app();
+        app.events_path = run_dir.clone();
+        app.set_buffer_limits(4, 2);
+        app.activate_live_logs();
+        for idx in 0..10 {
+            app.append_live_log(LogLevel::Info, format!("line {idx}"));
+        }
+        assert_eq!(app.log_lines.len(), 4);
+        assert_eq!(app.log_lines[0].message, "line 6");
+        assert_eq!(app.log_lines.earlier(), 8);
+
+        app.set_logs_viewport(2, vec![1; 4]);
+        app.start_log_selection();
+        app.logs_scroll = 0;
+        app.scroll_logs_up();
+        assert_eq!(app.log_lines.len(), 12);
+        assert_eq!(app.log_lines[0].message, "-- live run log --");
+        assert_eq!(app.logs_scroll, 7, "view stays on the same line");
+        assert_eq!(app.log_selection.map(|s| s.cursor), Some(11));
+
+        // Following the tail again lets the loaded lines go.
+        app.logs_follow_tail = true;
+        app.append_live_log(LogLevel::Info, "line 10");
+        let messages: Vec<&str> = app.log_lines.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, ["line 7", "line 8", "line 9", "line 10"]);
+        assert_eq!(app.log_selection.map(|s| s.cursor), Some(2));
+
+        for sender in ["user", "agent", "user"] {
+            app.push_chat_message(ChatMessage {
+                sender: sender.to_string(),
+                content: String::new(),
+                timestamp: 0.0,
+            });
+        }
+        assert_eq!(app.chat_messages.len(), 2);
+        assert_eq!(app.chat_hidden, 1);
+        std::fs::remove_dir_all(&run_dir).ok();
+    }
+
+    #[test]
+    fn toasts_stack_expire_and_queue_terminal_notifications() {
+        let mut app = empty_app();
+        app.toast(ToastLevel::Info, "quiet");
+        assert!(app.pending_terminal_notifications.is_empty());
+
+        app.notify_mode = NotifyMode::Desktop;
+        app.toast(ToastLevel::Alert, "loss; spiked\n");
+        assert_eq!(
+            app.pending_terminal_notifications,
+            vec!["\x1b]777;notify;OpenGraphs;loss  spiked \x07".to_string()]
+        );
+        app.notify_mode = NotifyMode::Bell;
+        for idx in 0..MAX_TOASTS {
+            app.toast(ToastLevel::Success, format!("run {idx} finished"));
+        }
+        assert_eq!(app.pending_terminal_notifications.len(), 1 + MAX_TOASTS);
+        assert_eq!(app.toasts.len(), MAX_TOASTS);
+        assert_eq!(app.toasts[0].message, "run 0 finished");
+
+        let shown_at = app.toasts[0].shown_at;
+        assert!(!app.expire_toasts(shown_at));
+        assert!(app.expire_toasts(shown_at + TOAST_TTL * 2));
+        assert!(app.toasts.is_empty());
+    }
+
+    #[test]
+    fn chart_markers_merge_checkpoints_and_eval_steps() {
+        let mut app = empty_app();
+        app.scalars.insert(
+            "train/loss".to_string(),
+            vec![(0.0, 2.0), (10.0, 1.5), (20.0, 1.2)],
+        );
+        app.scalars
+            .insert("val/loss".to_string(), vec![(10.0, 1.6), (20.0, 1.4)]);
+        app.checkpoints = vec![Checkpoint {
+            step: 10,
+            path: PathBuf::from("/ckpt/step_10.pt"),
+            wall_time: None,
+            metrics: BTreeMap::from([("val/loss".to_string(), 1.6)]),
+            size_bytes: None,
+        }];
+
+        let markers = app.chart_markers();
+        let summary: Vec<(i64, MarkerKind)> = markers.iter().map(|m| (m.step, m.kind)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (10, MarkerKind::Checkpoint),
+                (10, MarkerKind::Eval),
+                (20, MarkerKind::Eval)
+            ]
+        );
+        assert_eq!(markers[0].id.as_deref(), Some("step_10.pt"));
+        assert_eq!(markers[2].values, vec![("val/loss".to_string(), 1.4)]);
+
+        app.move_marker_selection(-1);
+        assert_eq!(app.selected_marker, Some(2));
+        app.move_marker_selection(-5);
+        assert_eq!(app.selected_marker, Some(0));
+        app.move_marker_selection(9);
+        assert_eq!(app.selected_marker, Some(2));
+    }
+
+    #[test]
+    fn lr_jump_before_a_loss_spike_becomes_a_chart_marker() {
+        let mut app = empty_app();
+        let mut lr: Vec<(f64, f64)> = (0..80)
+            .map(|step| (step as f64, 1e-3 * (1.0 - step as f64 / 100.0)))
+            .collect();
+        lr[60].1 = 1e-2;
+        app.scalars.insert("optim/lr".to_string(), lr);
+        let mut loss: Vec<(f64, f64)> = (0..80)
+            .map(|step| (step as f64, 1.0 + if step % 2 == 0 { 0.01 } else { -0.01 }))
+            .collect();
+        loss[61].1 = 50.0;
+        app.scalars.insert("train/loss".to_string(), loss);
+        assert!(app.chart_markers().is_empty());
+
+        app.scan_anomalies();
+        let report = app.lr_report().expect("lr report");
+        assert_eq!(report.tag, "optim/lr");
+        let markers = app.chart_markers();
+        assert_eq!(markers.len(), 1);
+        assert_eq!((markers[0].step, markers[0].kind), (60, MarkerKind::Lr));
+        assert_eq!(markers[0].values, vec![("optim/lr".to_string(), 1e-2)]);
+    }
+
+    #[test]
+    fn low_disk_alerts_once_per_crossing() {
+        let mut app = empty_app();
+        app.min_free_bytes = 1000;
+        app.apply_disk_usage(BTreeMap::from([("a".to_string(), 42)]), Some(5000));
+        assert_eq!(app.run_sizes.get("a"), Some(&42));
+        assert!(app.toasts.is_empty());
+
+        app.apply_disk_usage(BTreeMap::new(), Some(900));
+        app.apply_disk_usage(BTreeMap::new(), Some(800));
+        assert_eq!(app.toasts.len(), 1);
+        assert_eq!(app.toasts[0].level, ToastLevel::Alert);
+
+        app.apply_disk_usage(BTreeMap::new(), Some(2000));
+        app.apply_disk_usage(BTreeMap::new(), Some(500));
+        assert_eq!(app.toasts.len(), 2);
+    }
+
+    #[test]
+    fn convergence_is_reported_once_while_live() {
+        let mut app = empty_app();
+        app.early_stop = Some(EarlyStopPolicy {
+            metric: "val/loss".to_string(),
+            mode: ObjectiveMode::Min,
+            min_delta: 0.01,
+            patience: 3,
+        });
+        let values = [1.0, 0.5, 0.4, 0.399, 0.41, 0.405];
+        app.scalars.insert(
+            "val/loss".to_string(),
+            values
+                .iter()
+                .enumerate()
+                .map(|(step, value)| (step as f64 * 100.0, *value))
+                .collect(),
+        );
+        assert!(app.check_convergence().is_none());
+
+        app.live_logs_active = true;
+        let plateau = app.check_convergence().expect("plateau");
+        assert_eq!((plateau.best_step, plateau.step), (200.0, 500.0));
+        assert_eq!(app.toasts.len(), 1);
+        assert!(app.toasts[0].message.starts_with("Early stop suggested"));
+        assert!(app.check_convergence().is_none());
+    }
+
+    #[test]
+    fn thermal_throttling_alerts_once_per_episode() {
+        let mut app = empty_app();
+        let series = |values: &[f64]| -> Vec<(f64, f64)> {
+            values
+                .iter()
+                .enumerate()
+                .map(|(step, value)| (step as f64, *value))
+                .collect()
+        };
+        app.scalars.insert(
+            "sys/gpu2/sm_clock_mhz".to_string(),
+            series(&[1980.0, 1300.0, 1300.0, 1300.0]),
+        );
+        app.scalars
+            .insert("sys/gpu2/util_pct".to_string(), series(&[100.0; 4]));
+        app.check_thermal();
+        app.check_thermal();
+        assert_eq!(app.toasts.len(), 1);
+        assert_eq!(app.toasts[0].level, ToastLevel::Thermal);
+        assert_eq!(app.toasts[0].message, "Thermal throttling on GPU 2");
+
+        app.scalars
+            .get_mut("sys/gpu2/sm_clock_mhz")
+            .expect("clock")
+            .push((4.0, 1980.0));
+        app.check_thermal();
+        assert!(app.throttled_devices.is_empty());
+    }
+
+    #[test]
+    fn dataloader_stalls_are_logged_once() {
+        let mut app = empty_app();
+        let series = |values: &[f64]| -> Vec<(f64, f64)> {
+            values
+                .iter()
+                .enumerate()
+                .map(|(step, value)| (step as f64 * 10.0, *value))
+                .collect()
+        };
+        app.scalars.insert(
+            "sys/gpu0/util_pct".to_string(),
+            series(&[95.0, 5.0, 5.0, 5.0]),
+        );
+        app.scalars
+            .insert("sys/cpu/util_pct".to_string(), series(&[30.0; 4]));
+        app.scalars.insert(
+            "sys/cpu/iowait_pct".to_string(),
+            series(&[1.0, 60.0, 60.0, 60.0]),
+        );
+        let diagnostics = app.check_input_stalls();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].starts_with("dataloader stall suspected between steps 10–30"));
+        assert!(app.check_input_stalls().is_empty());
+    }
+
+    #[test]
+    fn growing_memory_warns_once_with_time_to_oom() {
+        let mut app = empty_app();
+        app.scalars.insert(
+            "sys/ram_used_mb".to_string(),
+            (0..10)
+                .map(|i| (i as f64 * 10.0, 1000.0 + i as f64 * 100.0))
+                .collect(),
+        );
+        app.scalars
+            .insert("sys/ram_total_mb".to_string(), vec![(0.0, 2000.0)]);
+        app.max_step = 90;
+        app.wall_secs = Some(90.0 * 60.0);
+        app.check_oom();
+        app.check_oom();
+        assert_eq!(app.toasts.len(), 1);
+        assert_eq!(
+            app.toasts[0].message,
+            "Memory may run out: sys/ram_used_mb reaches 2000 around step 100 (~10m)"
+        );
+    }
+
+    #[test]
+    fn budget_overrun_alerts_once() {
+        let mut app = empty_app();
+        app.pricing = crate::cost::Pricing {
+            gpu_hour_price: Some(2.0),
+            budget: Some(10.0),
+        };
+        app.gpu_count = Some(2);
+        app.wall_secs = Some(3600.0);
+        app.check_budget();
+        assert!(app.toasts.is_empty());
+
+        app.wall_secs = Some(4.0 * 3600.0);
+        app.check_budget();
+        app.check_budget();
+        assert_eq!(app.toasts.len(), 1);
+        assert!(app.toasts[0].message.starts_with("Over budget: $16.00"));
+    }
+
+    #[test]
+    fn anomalies_in_history_are_marked_but_only_new_ones_alert() {
+        let mut app = empty_app();
+        let noisy = |step: usize| {
+            (
+                step as f64,
+                1.0 + if step.is_multiple_of(2) { 0.01 } else { -0.01 },
+            )
+        };
+        let mut loss: Vec<(f64, f64)> = (0..80).map(noisy).collect();
+        loss[60].1 = 50.0;
+        app.scalars.insert("train/loss".to_string(), loss);
+        app.scalars.insert(
+            "lr".to_string(),
+            (0..80).map(|s| (s as f64, s as f64)).collect(),
+        );
+        app.scan_anomalies();
+        assert_eq!(app.anomalies["train/loss"].len(), 1);
+        assert!(!app.anomalies.contains_key("lr"));
+        assert!(app.toasts.is_empty());
+
+        let loss = app.scalars.get_mut("train/loss").expect("loss");
+        loss.extend((80..90).map(noisy));
+        loss[84].1 = 60.0;
+        app.scan_anomalies();
+        assert_eq!(app.anomalies["train/loss"].len(), 2);
+        assert_eq!(app.toasts.len(), 1);
+        assert_eq!(app.toasts[0].level, ToastLevel::Alert);
+        assert!(app.toasts[0].message.contains("spike at step 84"));
+    }
+
+    #[test]
+    fn events_filter_cycles_through_types_and_back_to_all() {
+        let mut app = empty_app();
+        let event = |step: i64, kind: &str| StructuredEvent {
+            step,
+            wall_time: 0.0,
+            kind: kind.to_string(),
+            payload: serde_json::Map::new(),
+        };
+        app.set_structured_events(vec![
+            event(10, "generation_sample"),
+            event(20, "eval_result"),
+            event(30, "generation_sample"),
+        ]);
+        assert_eq!(app.filtered_events().len(), 3);
+        let types: Vec<(&str, usize)> = app.event_types().into_iter().collect();
+        assert_eq!(types, vec![("eval_result", 1), ("generation_sample", 2)]);
+
+        app.scroll_events_down();
+        app.cycle_events_filter();
+        assert_eq!(app.events_filter.as_deref(), Some("eval_result"));
+        assert_eq!(app.events_scroll, 0);
+        assert_eq!(app.filtered_events()[0].step, 20);
+        app.cycle_events_filter();
+        let steps: Vec<i64> = app.filtered_events().iter().map(|e| e.step).collect();
+        assert_eq!(steps, vec![10, 30]);
+        app.cycle_events_filter();
+        assert_eq!(app.events_filter, None);
+
+        // A filter whose type vanished from the run falls back to all events.
+        app.events_filter = Some("eval_result".to_string());
+        app.set_structured_events(vec![event(40, "generation_sample")]);
+        assert_eq!(app.events_filter, None);
+    }
+
+    #[test]
+    fn switching_projects_selects_the_current_one_and_forgets_the_old_run() {
+        use crate::query::ProjectSummary;
+        let project = |name: &str| ProjectSummary {
+            name: name.to_string(),
+            path: format!("root/{name}"),
+            run_count: 1,
+        };
+        let mut app = empty_app();
+        app.dashboard_root = PathBuf::from("root/beta");
+        app.open_projects(vec![project("alpha"), project("beta"), project("gamma")]);
+        assert_eq!(app.project_panel, Some(1));
+        app.move_project_selection(5);
+        assert_eq!(
+            app.selected_project().map(|p| p.name.as_str()),
+            Some("gamma")
+        );
+        app.move_project_selection(-9);
+        assert_eq!(app.project_panel, Some(0));
+
+        app.selected_metric = 3;
+        app.last_logged_step = 40;
+        app.switch_project(PathBuf::from("root/alpha"));
+        assert_eq!(app.project_panel, None);
+        assert_eq!(app.events_path, PathBuf::from("root/alpha"));
+        assert_eq!(app.dashboard_root, PathBuf::from("root/alpha"));
+        assert_eq!((app.selected_metric, app.last_logged_step), (0, 0));
+    }
+
+    #[test]
+    fn the_step_slider_follows_new_steps_until_moved_back() {
+        use crate::histogram::Histogram;
+        let at = |step: i64| Histogram {
+            step,
+            buckets: Vec::new(),
+        };
+        let mut app = empty_app();
+        assert_eq!(app.histogram_step_index(), None);
+        let mut histograms = BTreeMap::new();
+        histograms.insert("a".to_string(), vec![at(1), at(2), at(3)]);
+        histograms.insert("b".to_string(), vec![at(5)]);
+        app.set_histograms(histograms.clone());
+        assert_eq!(app.histogram_step_index(), Some(2));
+
+        app.move_histogram_step(-1);
+        assert_eq!(app.histogram_step, Some(1));
+        app.move_histogram_step(5);
+        assert_eq!(app.histogram_step, None, "back at the latest step");
+
+        app.move_histogram_tag(1);
+        assert_eq!(
+            app.selected_histograms().map(|(tag, _)| tag.as_str()),
+            Some("b")
+        );
+        histograms.remove("a");
+        app.set_histograms(histograms);
+        assert_eq!(app.histogram_tag, 0, "the shown tag stays selected");
     }
 }
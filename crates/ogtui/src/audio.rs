@@ -0,0 +1,259 @@
+//! Audio summaries (TTS/ASR samples): the encoded clips of TF1
+//! `tf.summary.audio` values and of TF2 audio-plugin tensors, which hold one
+//! `[encoded clip, label]` row per clip.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::tfevents;
+use crate::tfrecord;
+
+/// TensorBoard plugin name of TF2 audio summaries.
+const PLUGIN_NAME: &str = "audio";
+
+/// One encoded clip.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AudioClip {
+    pub tag: String,
+    pub step: i64,
+    /// Position of the clip within its summary (one summary can hold a batch).
+    pub index: usize,
+    /// Only TF1 summaries record it outside the encoded clip.
+    pub sample_rate: Option<f64>,
+    pub content_type: String,
+    /// Label text TF2 stores next to each clip, when not empty.
+    pub label: Option<String>,
+    #[serde(skip)]
+    pub bytes: Vec<u8>,
+}
+
+impl AudioClip {
+    /// `samples_audio-step120-0.wav`: the tag with path separators and other
+    /// unsafe characters replaced, the step, the index and an extension for
+    /// the encoding.
+    pub fn file_name(&self) -> String {
+        let tag: String = self
+            .tag
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '-' | '.') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let extension = match self.content_type.as_str() {
+            "audio/mpeg" | "audio/mp3" => "mp3",
+            "audio/ogg" => "ogg",
+            "audio/flac" => "flac",
+            _ => "wav",
+        };
+        format!("{tag}-step{}-{}.{extension}", self.step, self.index)
+    }
+}
+
+/// Clips in event file bytes, in file order.
+pub fn parse_tfevents(bytes: &[u8]) -> Vec<AudioClip> {
+    let Ok((records, _)) = tfrecord::decode_records(bytes, true) else {
+        return Vec::new();
+    };
+    // TF2 writes the plugin name with a tag's first value only.
+    let mut plugins: HashMap<String, String> = HashMap::new();
+    let mut clips = Vec::new();
+    for (_, event) in records {
+        for value in event.summary.into_iter().flat_map(|summary| summary.value) {
+            if let Some(plugin) = value.metadata.and_then(|meta| meta.plugin_data) {
+                plugins.insert(value.tag.clone(), plugin.plugin_name);
+            }
+            if let Some(audio) = value.audio {
+                clips.push(AudioClip {
+                    tag: value.tag,
+                    step: event.step,
+                    index: 0,
+                    sample_rate: (audio.sample_rate > 0.0).then_some(f64::from(audio.sample_rate)),
+                    content_type: audio.content_type,
+                    label: None,
+                    bytes: audio.encoded_audio_string,
+                });
+                continue;
+            }
+            let Some(tensor) = value.tensor else {
+                continue;
+            };
+            if tensor.dtype != tfrecord::DT_STRING
+                || plugins.get(&value.tag).map(String::as_str) != Some(PLUGIN_NAME)
+            {
+                continue;
+            }
+            // Rows are `[clip, label]`; older writers left the label column out.
+            let width = match tensor.tensor_shape.as_ref().map(|shape| shape.dim.len()) {
+                Some(2) => 2,
+                _ => 1,
+            };
+            for (index, row) in tensor.string_val.chunks(width).enumerate() {
+                let label = row
+                    .get(1)
+                    .map(|label| String::from_utf8_lossy(label).into_owned())
+                    .filter(|label| !label.is_empty());
+                clips.push(AudioClip {
+                    tag: value.tag.clone(),
+                    step: event.step,
+                    index,
+                    sample_rate: None,
+                    content_type: "audio/wav".to_string(),
+                    label,
+                    bytes: row[0].clone(),
+                });
+            }
+        }
+    }
+    clips
+}
+
+/// Every clip logged under `run_path`, ordered by tag and step. Unreadable
+/// files are skipped; `load_run` already warns about them.
+pub fn load(run_path: &Path) -> Result<Vec<AudioClip>> {
+    let mut clips = Vec::new();
+    for path in tfevents::discover_event_files(run_path)? {
+        if tfevents::is_tfevents_file(&path)
+            && let Ok(bytes) = crate::compression::read(&path)
+        {
+            clips.extend(parse_tfevents(&bytes));
+        }
+    }
+    clips.sort_by(|a, b| (&a.tag, a.step, a.index).cmp(&(&b.tag, b.step, b.index)));
+    Ok(clips)
+}
+
+/// Clips per tag.
+pub fn count_by_tag(clips: &[AudioClip]) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for clip in clips {
+        *counts.entry(clip.tag.clone()).or_default() += 1;
+    }
+    counts
+}
+
+/// Write each clip into `out_dir` under its `file_name`, creating the
+/// directory first. Returns the written paths.
+pub fn export(clips: &[AudioClip], out_dir: &Path) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(out_dir).with_context(|| format!("creating {}", out_dir.display()))?;
+    clips
+        .iter()
+        .map(|clip| {
+            let path = out_dir.join(clip.file_name());
+            fs::write(&path, &clip.bytes).with_context(|| format!("writing {}", path.display()))?;
+            Ok(path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count_by_tag, parse_tfevents};
+    use crate::tfrecord::{
+        Audio, DT_STRING, Event, PluginData, Summary, SummaryMetadata, SummaryValue, TensorProto,
+        TensorShape, TensorShapeDim, encode_record,
+    };
+    use prost::Message;
+
+    fn record(step: i64, value: SummaryValue) -> Vec<u8> {
+        encode_record(
+            &Event {
+                wall_time: 1.0,
+                step,
+                file_version: None,
+                summary: Some(Summary { value: vec![value] }),
+            }
+            .encode_to_vec(),
+        )
+    }
+
+    fn tf2_value(tag: &str, plugin: Option<&str>, rows: &[(&[u8], &str)]) -> SummaryValue {
+        SummaryValue {
+            tag: tag.to_string(),
+            simple_value: None,
+            histo: None,
+            audio: None,
+            tensor: Some(TensorProto {
+                dtype: DT_STRING,
+                tensor_shape: Some(TensorShape {
+                    dim: vec![
+                        TensorShapeDim {
+                            size: rows.len() as i64,
+                        },
+                        TensorShapeDim { size: 2 },
+                    ],
+                }),
+                string_val: rows
+                    .iter()
+                    .flat_map(|(clip, label)| [clip.to_vec(), label.as_bytes().to_vec()])
+                    .collect(),
+                ..Default::default()
+            }),
+            metadata: plugin.map(|name| SummaryMetadata {
+                plugin_data: Some(PluginData {
+                    plugin_name: name.to_string(),
+                }),
+            }),
+        }
+    }
+
+    #[test]
+    fn decodes_tf1_and_tf2_audio_but_not_text() {
+        let mut bytes = record(
+            5,
+            SummaryValue {
+                tag: "tts/sample".to_string(),
+                simple_value: None,
+                histo: None,
+                audio: Some(Audio {
+                    sample_rate: 22050.0,
+                    encoded_audio_string: b"RIFF-one".to_vec(),
+                    content_type: "audio/wav".to_string(),
+                }),
+                tensor: None,
+                metadata: None,
+            },
+        );
+        bytes.extend(record(
+            10,
+            tf2_value(
+                "samples/audio",
+                Some("audio"),
+                &[(b"RIFF-a", "hello"), (b"RIFF-b", "")],
+            ),
+        ));
+        // The plugin name comes with the first value only.
+        bytes.extend(record(
+            20,
+            tf2_value("samples/audio", None, &[(b"RIFF-c", "")]),
+        ));
+        bytes.extend(record(
+            20,
+            tf2_value("notes", Some("text"), &[(b"not audio", "")]),
+        ));
+
+        let clips = parse_tfevents(&bytes);
+        assert_eq!(clips.len(), 4);
+        assert_eq!(clips[0].sample_rate, Some(22050.0));
+        assert_eq!(clips[0].file_name(), "tts_sample-step5-0.wav");
+        assert_eq!(clips[1].label.as_deref(), Some("hello"));
+        assert_eq!(clips[2].label, None);
+        assert_eq!(clips[2].file_name(), "samples_audio-step10-1.wav");
+        assert_eq!(clips[3].bytes, b"RIFF-c");
+
+        let counts: Vec<(String, usize)> = count_by_tag(&clips).into_iter().collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("samples/audio".to_string(), 3),
+                ("tts/sample".to_string(), 1)
+            ]
+        );
+    }
+}
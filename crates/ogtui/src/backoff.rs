@@ -0,0 +1,100 @@
+use std::time::{Duration, Instant};
+
+/// How long a run has to stay quiet before polling slows down, and the
+/// interval it slows to: 1s → 10s after 30s idle → 30s after 5 minutes.
+pub const IDLE_STEPS: [(Duration, Duration); 2] = [
+    (Duration::from_secs(30), Duration::from_secs(10)),
+    (Duration::from_secs(300), Duration::from_secs(30)),
+];
+
+/// Tracks when new data last arrived and stretches poll intervals while the
+/// run is idle (long eval phases, finished runs left open). Any activity
+/// snaps polling back to the configured rate.
+#[derive(Debug)]
+pub struct IdleBackoff {
+    enabled: bool,
+    last_activity: Instant,
+}
+
+impl IdleBackoff {
+    pub fn new(enabled: bool, now: Instant) -> Self {
+        Self {
+            enabled,
+            last_activity: now,
+        }
+    }
+
+    pub fn activity(&mut self, now: Instant) {
+        self.last_activity = now;
+    }
+
+    pub fn idle_for(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.last_activity)
+    }
+
+    /// 0 while active, else the index + 1 of the idle step reached.
+    pub fn level(&self, now: Instant) -> usize {
+        if !self.enabled {
+            return 0;
+        }
+        let idle = self.idle_for(now);
+        IDLE_STEPS
+            .iter()
+            .take_while(|(after, _)| idle >= *after)
+            .count()
+    }
+
+    /// `base`, or the idle interval of the current level when that is longer.
+    pub fn interval(&self, base: Duration, now: Instant) -> Duration {
+        match self.level(now) {
+            0 => base,
+            level => base.max(IDLE_STEPS[level - 1].1),
+        }
+    }
+}
+
+/// `500ms`, `10s`, `5m`: short labels for poll intervals in log lines.
+pub fn describe(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs == 0 {
+        format!("{}ms", duration.as_millis())
+    } else if secs < 60 || !secs.is_multiple_of(60) {
+        format!("{secs}s")
+    } else {
+        format!("{}m", secs / 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IdleBackoff, describe};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn stretches_intervals_while_idle_and_snaps_back_on_activity() {
+        let start = Instant::now();
+        let secs = |n: u64| start + Duration::from_secs(n);
+        let base = Duration::from_secs(1);
+        let mut backoff = IdleBackoff::new(true, start);
+
+        assert_eq!(backoff.interval(base, secs(29)), base);
+        assert_eq!(backoff.interval(base, secs(30)), Duration::from_secs(10));
+        assert_eq!(backoff.interval(base, secs(300)), Duration::from_secs(30));
+        assert_eq!(
+            backoff.interval(Duration::from_secs(60), secs(300)),
+            Duration::from_secs(60),
+            "never faster than configured"
+        );
+
+        backoff.activity(secs(301));
+        assert_eq!(backoff.level(secs(302)), 0);
+        assert_eq!(backoff.interval(base, secs(302)), base);
+
+        let disabled = IdleBackoff::new(false, start);
+        assert_eq!(disabled.interval(base, secs(1000)), base);
+
+        assert_eq!(describe(Duration::from_millis(500)), "500ms");
+        assert_eq!(describe(Duration::from_secs(30)), "30s");
+        assert_eq!(describe(Duration::from_secs(300)), "5m");
+    }
+}
@@ -0,0 +1,207 @@
+use anyhow::{Context, Result, bail};
+use prost::Message;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::rollup::{RollupMode, RollupOptions};
+use crate::tfevents;
+use crate::tfrecord::{self, Event, Summary, SummaryValue};
+
+/// Write a synthetic event file: `events` scalar points spread round-robin
+/// over `tags` tags, one point per record like `add_scalar` writes them.
+/// Returns the file size in bytes.
+pub fn generate_fixture(path: &Path, events: usize, tags: usize) -> Result<u64> {
+    if tags == 0 {
+        bail!("--tags must be at least 1");
+    }
+    let names: Vec<String> = (0..tags)
+        .map(|idx| match idx % 4 {
+            0 => format!("train/loss_{idx}"),
+            1 => format!("val/acc_{idx}"),
+            2 => format!("sys/gpu_{idx}"),
+            _ => format!("opt/lr_{idx}"),
+        })
+        .collect();
+    let file = File::create(path).with_context(|| format!("creating {}", path.display()))?;
+    let mut out = BufWriter::new(file);
+    let header = Event {
+        wall_time: 1_700_000_000.0,
+        step: 0,
+        file_version: Some("brain.Event:2".to_string()),
+        summary: None,
+    };
+    out.write_all(&tfrecord::encode_record(&header.encode_to_vec()))?;
+    for idx in 0..events {
+        let step = (idx / tags) as i64;
+        let tag = &names[idx % tags];
+        let event = Event {
+            wall_time: 1_700_000_000.0 + step as f64 * 0.1,
+            step,
+            file_version: None,
+            summary: Some(Summary {
+                value: vec![SummaryValue {
+                    tag: tag.clone(),
+                    simple_value: Some(1.0 / (1.0 + step as f32) + (idx % 7) as f32 * 1e-3),
+                    histo: None,
+                    audio: None,
+                    tensor: None,
+                    metadata: None,
+                }],
+            }),
+        };
+        out.write_all(&tfrecord::encode_record(&event.encode_to_vec()))?;
+    }
+    out.flush()?;
+    Ok(std::fs::metadata(path)?.len())
+}
+
+/// Timings of one benchmark over its iterations.
+#[derive(Debug, Clone, Serialize)]
+pub struct Measurement {
+    pub name: String,
+    /// Points processed per iteration.
+    pub points: usize,
+    pub iterations: usize,
+    pub min_ms: f64,
+    pub median_ms: f64,
+    /// Points per second at the median time.
+    pub points_per_sec: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub file: String,
+    pub bytes: u64,
+    pub points: usize,
+    pub tags: usize,
+    pub measurements: Vec<Measurement>,
+}
+
+fn measure(
+    name: &str,
+    iterations: usize,
+    mut run: impl FnMut() -> Result<usize>,
+) -> Result<Measurement> {
+    let mut times: Vec<Duration> = Vec::with_capacity(iterations);
+    let mut points = 0;
+    for _ in 0..iterations.max(1) {
+        let start = Instant::now();
+        points = run()?;
+        times.push(start.elapsed());
+    }
+    times.sort();
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    let median = times[times.len() / 2];
+    Ok(Measurement {
+        name: name.to_string(),
+        points,
+        iterations: times.len(),
+        min_ms: ms(times[0]),
+        median_ms: ms(median),
+        points_per_sec: points as f64 / median.as_secs_f64().max(1e-9),
+    })
+}
+
+/// Benchmark the reader on one event file:
+/// - `full_parse`: read and decode the whole file
+/// - `load_run`: parse and build per-tag series, what every TUI refresh does
+/// - `incremental_poll`: decode only the last `poll_points` records, what a
+///   poll costs when only new bytes are read
+/// - `downsample`: roll every series up by 10 (mean), as `og compact --rollup` does
+pub fn run(path: &Path, iterations: usize, poll_points: usize) -> Result<Report> {
+    let bytes = crate::compression::read(path)?;
+    let (records, stats) = tfrecord::decode_records(&bytes, true)?;
+    if !stats.is_clean() {
+        eprintln!(
+            "warning: {} is damaged; incremental poll timings skip the damaged part",
+            path.display()
+        );
+    }
+    let tail_start = bytes.len()
+        - records
+            .iter()
+            .rev()
+            .take(poll_points)
+            .map(|(data, _)| tfrecord::framed_len(data))
+            .sum::<usize>()
+            .min(bytes.len());
+    drop(records);
+
+    let (events, _) = tfevents::parse_events_bytes(&bytes, true)?;
+    let tags = events
+        .iter()
+        .map(|event| event.tag.as_str())
+        .collect::<std::collections::BTreeSet<_>>()
+        .len();
+    let points = events.len();
+    drop(events);
+
+    let mut measurements = vec![
+        measure("full_parse", iterations, || {
+            Ok(tfevents::parse_tfevents_file(path)?.0.len())
+        })?,
+        measure("load_run", iterations, || {
+            let loaded = tfevents::load_run(path)?;
+            Ok(loaded.scalars.values().map(Vec::len).sum())
+        })?,
+        measure("incremental_poll", iterations, || {
+            Ok(tfevents::parse_events_bytes(&bytes[tail_start..], true)?
+                .0
+                .len())
+        })?,
+    ];
+    let rollup = RollupOptions {
+        every: 10,
+        mode: RollupMode::Mean,
+        keep: Vec::new(),
+    };
+    measurements.push(measure("downsample", iterations, || {
+        let (stats, _) = crate::rollup::rollup_file(path, &rollup, true)?;
+        Ok(stats.points_before)
+    })?);
+
+    Ok(Report {
+        file: path.display().to_string(),
+        bytes: std::fs::metadata(path)?.len(),
+        points,
+        tags,
+        measurements,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_fixture, run};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn fixture_round_trips_through_every_benchmark() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("ogtui-bench-{nonce}.tfevents.0"));
+        generate_fixture(&path, 1_000, 25).expect("generate fixture");
+
+        let report = run(&path, 1, 100).expect("run benchmarks");
+        assert_eq!((report.points, report.tags), (1_000, 25));
+        let points: Vec<(&str, usize)> = report
+            .measurements
+            .iter()
+            .map(|m| (m.name.as_str(), m.points))
+            .collect();
+        assert_eq!(
+            points,
+            vec![
+                ("full_parse", 1_000),
+                ("load_run", 1_000),
+                ("incremental_poll", 100),
+                ("downsample", 1_000),
+            ]
+        );
+        std::fs::remove_file(&path).ok();
+    }
+}
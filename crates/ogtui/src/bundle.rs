@@ -0,0 +1,482 @@
+//! Run bundles for `og bundle`: one archive holding a run's event files,
+//! `og_meta.json` (notes, tags, annotations), console logs, sidecars and the
+//! checkpoints picked for it, to hand a whole experiment to someone else.
+//! Archives are tarballs, compressed by the `zstd` / `gzip` tools when the
+//! file name ends in `.zst` / `.gz`. Their single top-level directory is
+//! named after the run and holds an `og_bundle.json` manifest.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::str::FromStr;
+
+use crate::checkpoints::{self, Checkpoint};
+use crate::compression::Codec;
+
+pub const MANIFEST_FILE_NAME: &str = "og_bundle.json";
+const FORMAT_VERSION: u32 = 1;
+/// Where checkpoints saved outside the run directory go in the bundle.
+const EXTERNAL_CHECKPOINT_DIR: &str = "checkpoints";
+
+/// Which of a run's checkpoints go into its bundle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckpointSelection {
+    None,
+    Latest,
+    All,
+    /// Checkpoints named by id (file name) or step.
+    Listed(Vec<String>),
+}
+
+impl FromStr for CheckpointSelection {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Ok(match text.trim() {
+            "none" => CheckpointSelection::None,
+            "latest" => CheckpointSelection::Latest,
+            "all" => CheckpointSelection::All,
+            "" => return Err("expected none, latest, all or checkpoint ids".into()),
+            list => CheckpointSelection::Listed(
+                list.split(',')
+                    .map(|item| item.trim().to_string())
+                    .filter(|item| !item.is_empty())
+                    .collect(),
+            ),
+        })
+    }
+}
+
+impl CheckpointSelection {
+    /// The picked checkpoints, in step order. Listed ids that match nothing
+    /// are an error rather than a bundle silently missing them.
+    fn pick<'a>(&self, checkpoints: &'a [Checkpoint]) -> Result<Vec<&'a Checkpoint>> {
+        Ok(match self {
+            CheckpointSelection::None => Vec::new(),
+            CheckpointSelection::Latest => checkpoints.last().into_iter().collect(),
+            CheckpointSelection::All => checkpoints.iter().collect(),
+            CheckpointSelection::Listed(wanted) => {
+                for id in wanted {
+                    if !checkpoints
+                        .iter()
+                        .any(|checkpoint| matches_id(checkpoint, id))
+                    {
+                        bail!("no checkpoint '{id}' in the run's registry");
+                    }
+                }
+                checkpoints
+                    .iter()
+                    .filter(|checkpoint| wanted.iter().any(|id| matches_id(checkpoint, id)))
+                    .collect()
+            }
+        })
+    }
+}
+
+fn matches_id(checkpoint: &Checkpoint, id: &str) -> bool {
+    checkpoint.id() == id || checkpoint.step.to_string() == id
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: u32,
+    pub run: String,
+    pub created_unix: u64,
+    pub og_version: String,
+    pub files: usize,
+    pub bytes: u64,
+    /// Ids of the checkpoints included.
+    pub checkpoints: Vec<String>,
+}
+
+/// Write `run_dir` as a bundle at `out`. Checkpoints the registry lists but
+/// `selection` leaves out are skipped, as are unregistered `.pt`-style files
+/// unless every checkpoint was asked for.
+pub fn create(
+    run_dir: &Path,
+    run: &str,
+    selection: &CheckpointSelection,
+    out: &Path,
+    now_unix: u64,
+) -> Result<Manifest> {
+    let registry = checkpoints::load(run_dir)?;
+    let picked = selection.pick(&registry)?;
+    let staging = Staging::new(
+        &std::env::temp_dir(),
+        &format!("og-bundle-{}", std::process::id()),
+    )?;
+    let root = staging.path.join(run);
+    fs::create_dir_all(&root)?;
+
+    let mut stage = Stage {
+        registered: registry.iter().map(|c| c.path.clone()).collect(),
+        picked: picked.iter().map(|c| c.path.clone()).collect(),
+        all_checkpoints: *selection == CheckpointSelection::All,
+        out: out.canonicalize().ok(),
+        files: 0,
+        bytes: 0,
+    };
+    stage.link_dir(run_dir, &root)?;
+
+    // The registry is rewritten to list what the bundle holds, with paths
+    // relative to the run so it still resolves after import.
+    let mut included = Vec::new();
+    for checkpoint in &picked {
+        let mut entry = (*checkpoint).clone();
+        entry.path = match checkpoint.path.strip_prefix(run_dir) {
+            Ok(relative) => root.join(relative),
+            Err(_) => {
+                let dir = root.join(EXTERNAL_CHECKPOINT_DIR);
+                fs::create_dir_all(&dir)?;
+                let target = dir.join(checkpoint.id());
+                stage.link(&checkpoint.path, &target)?;
+                target
+            }
+        };
+        included.push(entry);
+    }
+    if !registry.is_empty() {
+        checkpoints::save(&root, &included)?;
+    }
+
+    let manifest = Manifest {
+        format: FORMAT_VERSION,
+        run: run.to_string(),
+        created_unix: now_unix,
+        og_version: env!("CARGO_PKG_VERSION").to_string(),
+        files: stage.files,
+        bytes: stage.bytes,
+        checkpoints: included.iter().map(Checkpoint::id).collect(),
+    };
+    fs::write(
+        root.join(MANIFEST_FILE_NAME),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+    write_archive(&staging.path, run, out)?;
+    Ok(manifest)
+}
+
+/// Unpack `bundle` into `project_dir` as run `name` (default: the bundled
+/// run's name). The run directory must not exist yet.
+pub fn import(
+    bundle: &Path,
+    project_dir: &Path,
+    name: Option<&str>,
+) -> Result<(PathBuf, Manifest)> {
+    if !bundle.is_file() {
+        bail!("bundle '{}' not found", bundle.display());
+    }
+    fs::create_dir_all(project_dir)
+        .with_context(|| format!("creating {}", project_dir.display()))?;
+    // Unpacked next to its destination so the final move is a rename.
+    let staging = Staging::new(
+        project_dir,
+        &format!(".og-bundle-import-{}", std::process::id()),
+    )?;
+    extract_archive(bundle, &staging.path)?;
+
+    let mut tops = fs::read_dir(&staging.path)?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+    let (Some(top), None) = (tops.pop(), tops.pop()) else {
+        bail!(
+            "'{}' is not an og bundle: expected one top-level directory",
+            bundle.display()
+        );
+    };
+    let manifest_path = top.join(MANIFEST_FILE_NAME);
+    let manifest: Manifest = serde_json::from_str(
+        &fs::read_to_string(&manifest_path)
+            .with_context(|| format!("'{}' has no {MANIFEST_FILE_NAME}", bundle.display()))?,
+    )
+    .with_context(|| format!("reading {MANIFEST_FILE_NAME} in {}", bundle.display()))?;
+    if manifest.format > FORMAT_VERSION {
+        bail!(
+            "'{}' is bundle format {}; this og reads up to {FORMAT_VERSION}",
+            bundle.display(),
+            manifest.format
+        );
+    }
+
+    let name = name.unwrap_or(&manifest.run);
+    if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+        bail!("'{name}' is not a valid run name");
+    }
+    let dest = project_dir.join(name);
+    if dest.exists() {
+        bail!("run '{}' already exists", dest.display());
+    }
+    fs::remove_file(&manifest_path)?;
+    fs::rename(&top, &dest)
+        .with_context(|| format!("moving the bundle into {}", dest.display()))?;
+    Ok((dest, manifest))
+}
+
+/// Mirror of a run directory made of symlinks, which `tar -h` archives as
+/// the files they point to.
+struct Stage {
+    registered: Vec<PathBuf>,
+    picked: Vec<PathBuf>,
+    all_checkpoints: bool,
+    /// The archive being written, when it sits inside the run directory.
+    out: Option<PathBuf>,
+    files: usize,
+    bytes: u64,
+}
+
+impl Stage {
+    fn link_dir(&mut self, from: &Path, to: &Path) -> Result<()> {
+        for entry in fs::read_dir(from).with_context(|| format!("reading {}", from.display()))? {
+            let path = entry?.path();
+            let target = to.join(path.file_name().unwrap_or_default());
+            if self.registered.contains(&path) {
+                if self.picked.contains(&path) {
+                    self.link(&path, &target)?;
+                }
+                continue;
+            }
+            // Rewritten for the bundle by `create`.
+            if path == checkpoints::registry_path(from) {
+                continue;
+            }
+            if self.out.is_some() && path.canonicalize().ok() == self.out {
+                continue;
+            }
+            let Ok(meta) = fs::metadata(&path) else {
+                continue;
+            };
+            if meta.is_dir() {
+                fs::create_dir_all(&target)?;
+                self.link_dir(&path, &target)?;
+            } else if meta.is_file() {
+                if !self.all_checkpoints && crate::gc::is_checkpoint_file(&path) {
+                    continue;
+                }
+                self.link(&path, &target)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn link(&mut self, source: &Path, target: &Path) -> Result<()> {
+        let source = source
+            .canonicalize()
+            .with_context(|| format!("reading {}", source.display()))?;
+        std::os::unix::fs::symlink(&source, target)
+            .with_context(|| format!("staging {}", source.display()))?;
+        self.files += 1;
+        self.bytes += crate::disk_usage::path_size(&source);
+        Ok(())
+    }
+}
+
+/// Scratch directory removed when dropped, error or not.
+struct Staging {
+    path: PathBuf,
+}
+
+impl Staging {
+    fn new(parent: &Path, name: &str) -> Result<Self> {
+        let path = parent.join(name);
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).with_context(|| format!("creating {}", path.display()))?;
+        Ok(Staging { path })
+    }
+}
+
+impl Drop for Staging {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+fn write_archive(staging: &Path, run: &str, out: &Path) -> Result<()> {
+    let name = out
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let partial = out.with_file_name(format!(".{name}.partial"));
+    let file =
+        fs::File::create(&partial).with_context(|| format!("creating {}", partial.display()))?;
+    let mut tar = Command::new("tar");
+    tar.arg("-chf").arg("-").arg("-C").arg(staging).arg(run);
+    let result = match Codec::from_name(&name) {
+        None => wait(tar.stdout(file).spawn().context("running tar")?, "tar"),
+        Some(codec) => {
+            let mut tar = tar.stdout(Stdio::piped()).spawn().context("running tar")?;
+            let stdout = tar.stdout.take().context("tar stdout")?;
+            let mut compress = Command::new(codec.program());
+            match codec {
+                Codec::Gzip => compress.args(["-n", "-c"]),
+                Codec::Zstd => compress.args(["-q", "-T0", "-c"]),
+            };
+            let compress = compress
+                .stdin(stdout)
+                .stdout(file)
+                .spawn()
+                .with_context(|| format!("running {}", codec.program()))?;
+            wait(tar, "tar").and(wait(compress, codec.program()))
+        }
+    };
+    match result {
+        Ok(()) => fs::rename(&partial, out).with_context(|| format!("writing {}", out.display())),
+        Err(err) => {
+            let _ = fs::remove_file(&partial);
+            Err(err)
+        }
+    }
+}
+
+fn extract_archive(bundle: &Path, into: &Path) -> Result<()> {
+    let name = bundle
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut tar = Command::new("tar");
+    tar.arg("-xf").arg("-").arg("-C").arg(into);
+    match Codec::from_name(&name) {
+        None => {
+            let file =
+                fs::File::open(bundle).with_context(|| format!("opening {}", bundle.display()))?;
+            wait(tar.stdin(file).spawn().context("running tar")?, "tar")
+        }
+        Some(codec) => {
+            let mut decompress = Command::new(codec.program())
+                .arg("-dc")
+                .arg(bundle)
+                .stdout(Stdio::piped())
+                .spawn()
+                .with_context(|| format!("running {}", codec.program()))?;
+            let stdout = decompress.stdout.take().context("decompressor stdout")?;
+            let tar = tar.stdin(stdout).spawn().context("running tar")?;
+            wait(decompress, codec.program()).and(wait(tar, "tar"))
+        }
+    }
+    .with_context(|| format!("unpacking {}", bundle.display()))
+}
+
+fn wait(child: Child, program: &str) -> Result<()> {
+    let status = child
+        .wait_with_output()
+        .with_context(|| format!("waiting for {program}"))?
+        .status;
+    if !status.success() {
+        bail!("{program} exited with {status}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CheckpointSelection, MANIFEST_FILE_NAME, create, import};
+    use crate::checkpoints::{self, Checkpoint};
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process::Command;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn checkpoint(step: i64, path: PathBuf) -> Checkpoint {
+        Checkpoint {
+            step,
+            path,
+            wall_time: None,
+            metrics: BTreeMap::new(),
+            size_bytes: None,
+        }
+    }
+
+    #[test]
+    fn selection_parses_keywords_and_id_lists() {
+        assert_eq!("latest".parse(), Ok(CheckpointSelection::Latest));
+        assert_eq!(
+            "step_100.pt, 200".parse(),
+            Ok(CheckpointSelection::Listed(vec![
+                "step_100.pt".into(),
+                "200".into()
+            ]))
+        );
+        assert!("".parse::<CheckpointSelection>().is_err());
+    }
+
+    #[test]
+    fn bundles_round_trip_with_only_the_picked_checkpoints() {
+        if let Some(tool) = ["tar", "gzip"]
+            .into_iter()
+            .find(|tool| Command::new(tool).arg("--version").output().is_err())
+        {
+            eprintln!(
+                "skipping bundles_round_trip_with_only_the_picked_checkpoints: {tool} is not installed"
+            );
+            return;
+        }
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("ogtui-bundle-{nonce}"));
+        let run = root.join("src/r1");
+        let external = root.join("scratch");
+        fs::create_dir_all(run.join("ckpt")).expect("run dir");
+        fs::create_dir_all(&external).expect("scratch dir");
+        fs::write(run.join("events.out.tfevents.1"), b"events").expect("events");
+        fs::write(run.join("og_meta.json"), r#"{"tags":["baseline"]}"#).expect("meta");
+        fs::write(run.join("train.log"), b"step 1\n").expect("log");
+        fs::write(run.join("ckpt/step_100.pt"), b"old weights").expect("ckpt");
+        fs::write(run.join("stray.pt"), b"unregistered").expect("stray");
+        fs::write(external.join("step_200.pt"), b"new weights").expect("external ckpt");
+        checkpoints::save(
+            &run,
+            &[
+                checkpoint(100, run.join("ckpt/step_100.pt")),
+                checkpoint(200, external.join("step_200.pt")),
+            ],
+        )
+        .expect("registry");
+
+        let out = root.join("r1.tar.gz");
+        let manifest = create(
+            &run,
+            "r1",
+            &CheckpointSelection::Latest,
+            &out,
+            1_700_000_000,
+        )
+        .expect("create bundle");
+        assert_eq!(manifest.checkpoints, vec!["step_200.pt"]);
+        // Events, meta, log and the checkpoint; the registry is rewritten.
+        assert_eq!(manifest.files, 4);
+
+        let project = root.join("dest");
+        let (dest, imported) = import(&out, &project, Some("from-alice")).expect("import");
+        assert_eq!(dest, project.join("from-alice"));
+        assert_eq!(imported, manifest);
+        assert_eq!(
+            fs::read(dest.join("events.out.tfevents.1")).expect("events"),
+            b"events"
+        );
+        assert_eq!(fs::read(dest.join("train.log")).expect("log"), b"step 1\n");
+        assert!(
+            fs::read_to_string(dest.join("og_meta.json"))
+                .expect("meta")
+                .contains("baseline")
+        );
+        assert!(!dest.join("ckpt/step_100.pt").exists());
+        assert!(!dest.join("stray.pt").exists());
+        assert!(!dest.join(MANIFEST_FILE_NAME).exists());
+        let registry = checkpoints::load(&dest).expect("registry");
+        assert_eq!(registry.len(), 1);
+        assert_eq!(registry[0].path, dest.join("checkpoints/step_200.pt"));
+        assert_eq!(fs::read(&registry[0].path).expect("ckpt"), b"new weights");
+
+        // The source is untouched and the destination is never overwritten.
+        assert_eq!(checkpoints::load(&run).expect("source registry").len(), 2);
+        assert!(import(&out, &project, Some("from-alice")).is_err());
+        assert!(create(&run, "r1", &"step_300.pt".parse().unwrap(), &out, 0).is_err());
+
+        fs::remove_dir_all(&root).ok();
+    }
+}
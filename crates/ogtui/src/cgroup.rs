@@ -0,0 +1,192 @@
+//! Container limits from cgroup v2. Inside Docker or Kubernetes the host's
+//! CPU count and memory say little about what the run can use, so the system
+//! sampler and the processes tab read CPU and memory against the quota and
+//! limit of the cgroup the TUI runs in.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::format;
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// A cgroup v2 group directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cgroup {
+    dir: PathBuf,
+}
+
+/// What a group may use; None where it sets no limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Limits {
+    /// CPU quota in cores (`cpu.max` quota over period)
+    pub cpus: Option<f64>,
+    pub memory_bytes: Option<u64>,
+}
+
+impl Limits {
+    pub fn is_empty(&self) -> bool {
+        self.cpus.is_none() && self.memory_bytes.is_none()
+    }
+
+    /// `2 cpus, 4.0 GiB`, leaving out what is not limited.
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(cpus) = self.cpus {
+            let plural = if cpus == 1.0 { "" } else { "s" };
+            let cores = if cpus.fract() == 0.0 {
+                format!("{cpus:.0}")
+            } else {
+                format!("{cpus:.2}").trim_end_matches('0').to_string()
+            };
+            parts.push(format!("{cores} cpu{plural}"));
+        }
+        if let Some(bytes) = self.memory_bytes {
+            parts.push(format::bytes(bytes));
+        }
+        parts.join(", ")
+    }
+}
+
+impl Cgroup {
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The group this process runs in, when cgroup v2 is mounted and the
+    /// group limits CPU or memory. Hosts without limits yield None.
+    pub fn detect() -> Option<Self> {
+        let membership = fs::read_to_string("/proc/self/cgroup").ok()?;
+        let relative = v2_path(&membership)?;
+        let group = Self::at(Path::new(CGROUP_ROOT).join(relative.trim_start_matches('/')));
+        (!group.limits().is_empty()).then_some(group)
+    }
+
+    fn read(&self, file: &str) -> Option<String> {
+        fs::read_to_string(self.dir.join(file)).ok()
+    }
+
+    pub fn limits(&self) -> Limits {
+        Limits {
+            cpus: self.read("cpu.max").as_deref().and_then(parse_cpu_max),
+            memory_bytes: self
+                .read("memory.max")
+                .as_deref()
+                .and_then(parse_memory_max),
+        }
+    }
+
+    /// Memory in use as `docker stats` counts it: `memory.current` less the
+    /// inactive page cache the kernel reclaims first.
+    pub fn memory_used_bytes(&self) -> Option<u64> {
+        let current: u64 = self.read("memory.current")?.trim().parse().ok()?;
+        let inactive = self
+            .read("memory.stat")
+            .as_deref()
+            .and_then(|stat| stat_field(stat, "inactive_file"))
+            .unwrap_or(0);
+        Some(current.saturating_sub(inactive))
+    }
+
+    /// CPU time the group has used, in microseconds.
+    pub fn cpu_usage_usec(&self) -> Option<u64> {
+        stat_field(&self.read("cpu.stat")?, "usage_usec")
+    }
+}
+
+/// The v2 entry (`0::/docker/abc`) of a `/proc/self/cgroup` listing.
+pub fn v2_path(membership: &str) -> Option<&str> {
+    membership
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(str::trim)
+}
+
+/// Cores allowed by a `cpu.max` line: `200000 100000` is 2, `max 100000` is
+/// no limit.
+pub fn parse_cpu_max(text: &str) -> Option<f64> {
+    let mut fields = text.split_whitespace();
+    let quota: f64 = fields.next()?.parse().ok()?;
+    let period: f64 = fields.next().unwrap_or("100000").parse().ok()?;
+    (quota > 0.0 && period > 0.0).then(|| quota / period)
+}
+
+/// Bytes allowed by `memory.max`; `max` is no limit.
+pub fn parse_memory_max(text: &str) -> Option<u64> {
+    text.trim().parse().ok().filter(|bytes| *bytes > 0)
+}
+
+fn stat_field(stat: &str, name: &str) -> Option<u64> {
+    stat.lines().find_map(|line| {
+        let (key, value) = line.split_once(' ')?;
+        (key == name).then(|| value.trim().parse().ok())?
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cgroup, Limits, parse_cpu_max, parse_memory_max, v2_path};
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn parses_limits_and_the_v2_membership_line() {
+        assert_eq!(parse_cpu_max("200000 100000\n"), Some(2.0));
+        assert_eq!(parse_cpu_max("50000 100000"), Some(0.5));
+        assert_eq!(parse_cpu_max("max 100000"), None);
+        assert_eq!(parse_memory_max("4294967296\n"), Some(4 << 30));
+        assert_eq!(parse_memory_max("max\n"), None);
+        assert_eq!(
+            v2_path("12:cpuset:/\n0::/kubepods/pod1/abc\n"),
+            Some("/kubepods/pod1/abc")
+        );
+        assert_eq!(v2_path("4:memory:/docker/abc\n"), None, "cgroup v1");
+        assert_eq!(
+            Limits {
+                cpus: Some(2.0),
+                memory_bytes: Some(4 << 30),
+            }
+            .describe(),
+            "2 cpus, 4.0 GiB"
+        );
+        assert_eq!(
+            Limits {
+                cpus: Some(1.5),
+                memory_bytes: None,
+            }
+            .describe(),
+            "1.5 cpus"
+        );
+    }
+
+    #[test]
+    fn reads_usage_from_a_group_directory() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("ogtui-cgroup-{nonce}"));
+        fs::create_dir_all(&dir).expect("create group dir");
+        for (file, text) in [
+            ("cpu.max", "max 100000\n"),
+            ("memory.max", "1073741824\n"),
+            ("memory.current", "600000000\n"),
+            ("memory.stat", "anon 400000000\ninactive_file 100000000\n"),
+            ("cpu.stat", "usage_usec 123456\nuser_usec 100000\n"),
+        ] {
+            fs::write(dir.join(file), text).expect("write cgroup file");
+        }
+
+        let group = Cgroup::at(&dir);
+        assert_eq!(
+            group.limits(),
+            Limits {
+                cpus: None,
+                memory_bytes: Some(1 << 30),
+            }
+        );
+        assert_eq!(group.memory_used_bytes(), Some(500_000_000));
+        assert_eq!(group.cpu_usage_usec(), Some(123_456));
+        fs::remove_dir_all(&dir).ok();
+    }
+}
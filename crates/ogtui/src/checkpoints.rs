@@ -0,0 +1,141 @@
+//! Model checkpoints recorded by training scripts in `checkpoints.jsonl`
+//! (see `og_agent_chat.checkpoints.record_checkpoint`).
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::run_meta::run_dir_for;
+
+/// Registry file, one JSON object per saved checkpoint, next to the event files.
+pub const REGISTRY_FILE_NAME: &str = "checkpoints.jsonl";
+
+/// One saved checkpoint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub step: i64,
+    pub path: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wall_time: Option<f64>,
+    /// Metric values at the time of the save.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metrics: BTreeMap<String, f64>,
+    /// Bytes on disk, filled in by `measure`; None until then or once the
+    /// checkpoint is deleted.
+    #[serde(skip)]
+    pub size_bytes: Option<u64>,
+}
+
+impl Checkpoint {
+    /// File or directory name, which is how scripts usually tell checkpoints apart.
+    pub fn id(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.path.display().to_string())
+    }
+}
+
+pub fn registry_path(run_path: &Path) -> PathBuf {
+    run_dir_for(run_path).join(REGISTRY_FILE_NAME)
+}
+
+/// Checkpoints of a run ordered by step; a missing registry yields none and
+/// malformed lines are skipped. Relative paths are resolved against the run dir.
+pub fn load(run_path: &Path) -> Result<Vec<Checkpoint>> {
+    let path = registry_path(run_path);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let raw = fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+    let run_dir = run_dir_for(run_path);
+    let mut checkpoints: Vec<Checkpoint> = raw
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Checkpoint>(line).ok())
+        .map(|mut checkpoint| {
+            if checkpoint.path.is_relative() {
+                checkpoint.path = run_dir.join(&checkpoint.path);
+            }
+            checkpoint
+        })
+        .collect();
+    checkpoints.sort_by_key(|checkpoint| checkpoint.step);
+    Ok(checkpoints)
+}
+
+/// Rewrite the registry with `checkpoints`, e.g. after pruning some of them.
+/// Paths inside the run dir are stored relative to it, as `load` expects.
+pub fn save(run_path: &Path, checkpoints: &[Checkpoint]) -> Result<()> {
+    let path = registry_path(run_path);
+    let run_dir = run_dir_for(run_path);
+    let mut raw = String::new();
+    for checkpoint in checkpoints {
+        let mut entry = checkpoint.clone();
+        if let Ok(relative) = entry.path.strip_prefix(&run_dir) {
+            entry.path = relative.to_path_buf();
+        }
+        raw.push_str(&serde_json::to_string(&entry)?);
+        raw.push('\n');
+    }
+    fs::write(&path, raw).with_context(|| format!("writing {}", path.display()))
+}
+
+/// Record how much disk each checkpoint takes. Walks checkpoint directories,
+/// so it runs when the panel opens rather than on every refresh.
+pub fn measure(checkpoints: &mut [Checkpoint]) {
+    for checkpoint in checkpoints {
+        checkpoint.size_bytes = checkpoint
+            .path
+            .exists()
+            .then(|| crate::disk_usage::path_size(&checkpoint.path));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{REGISTRY_FILE_NAME, load, measure, save};
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn load_orders_by_step_and_notes_missing_files() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let run_dir = std::env::temp_dir().join(format!("ogtui-checkpoints-{nonce}"));
+        fs::create_dir_all(&run_dir).expect("create run dir");
+        assert!(load(&run_dir).expect("load").is_empty());
+
+        fs::write(run_dir.join("step_20.pt"), [0u8; 64]).expect("write checkpoint");
+        fs::write(
+            run_dir.join(REGISTRY_FILE_NAME),
+            concat!(
+                "{\"step\": 20, \"path\": \"step_20.pt\", \"metrics\": {\"val/loss\": 1.23}}\n",
+                "not json\n",
+                "{\"step\": 10, \"path\": \"/gone/step_10.pt\"}\n",
+            ),
+        )
+        .expect("write registry");
+
+        let mut checkpoints = load(&run_dir).expect("load");
+        measure(&mut checkpoints);
+        assert_eq!(checkpoints.len(), 2);
+        assert_eq!(checkpoints[0].step, 10);
+        assert_eq!(checkpoints[0].size_bytes, None);
+        assert_eq!(checkpoints[1].path, run_dir.join("step_20.pt"));
+        assert_eq!(checkpoints[1].id(), "step_20.pt");
+        assert_eq!(checkpoints[1].size_bytes, Some(64));
+        assert_eq!(checkpoints[1].metrics.get("val/loss"), Some(&1.23));
+
+        save(&run_dir, &checkpoints[1..]).expect("save");
+        let registry = fs::read_to_string(run_dir.join(REGISTRY_FILE_NAME)).expect("read");
+        assert!(registry.contains("\"path\":\"step_20.pt\""));
+        let reloaded = load(&run_dir).expect("reload");
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(reloaded[0].path, checkpoints[1].path);
+        fs::remove_dir_all(&run_dir).expect("cleanup");
+    }
+}
@@ -0,0 +1,116 @@
+//! Copying text out of the TUI through the terminal (OSC 52).
+//!
+//! OSC 52 asks the terminal emulator to set the system clipboard, so it also
+//! works over SSH and inside tmux (with `set -g set-clipboard on`), where a
+//! native clipboard library would only reach the remote host's clipboard.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use std::collections::BTreeMap;
+
+/// Most points `C` copies from a series. Many terminals drop OSC 52
+/// payloads past ~100 KB, so longer series are copied from their tail.
+pub const MAX_CSV_POINTS: usize = 2_000;
+
+/// Escape sequence that puts `text` on the clipboard.
+pub fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", STANDARD.encode(text))
+}
+
+/// One-line summary of a series, the same numbers the focused chart shows.
+pub fn metric_stats(tag: &str, data: &[(f64, f64)]) -> String {
+    let (Some(first), Some(last)) = (data.first(), data.last()) else {
+        return format!("{tag}: no data");
+    };
+    let min = data.iter().map(|d| d.1).fold(f64::INFINITY, f64::min);
+    let max = data.iter().map(|d| d.1).fold(f64::NEG_INFINITY, f64::max);
+    let mean = data.iter().map(|d| d.1).sum::<f64>() / data.len() as f64;
+    format!(
+        "{tag}: latest={} min={} max={} mean={} points={} steps={}-{}",
+        last.1,
+        min,
+        max,
+        mean,
+        data.len(),
+        first.0,
+        last.0
+    )
+}
+
+/// The last `max_points` points of a series as `step,<tag>` CSV.
+pub fn series_csv(tag: &str, data: &[(f64, f64)], max_points: usize) -> String {
+    let tail = &data[data.len().saturating_sub(max_points)..];
+    let mut out = format!("step,{}\n", csv_field(tag));
+    for (step, value) in tail {
+        out.push_str(&format!("{step},{value}\n"));
+    }
+    out
+}
+
+/// Every series as one CSV table: a `step` column, then a column per tag,
+/// empty where a tag has no point at that step.
+pub fn scalars_csv(scalars: &BTreeMap<String, Vec<(f64, f64)>>) -> String {
+    let mut rows: BTreeMap<u64, Vec<Option<f64>>> = BTreeMap::new();
+    for (col, data) in scalars.values().enumerate() {
+        for (step, value) in data {
+            // Steps are non-negative, so their bit patterns sort like the numbers.
+            let row = rows
+                .entry(step.max(0.0).to_bits())
+                .or_insert_with(|| vec![None; scalars.len()]);
+            row[col] = Some(*value);
+        }
+    }
+    let mut out = String::from("step");
+    for tag in scalars.keys() {
+        out.push(',');
+        out.push_str(&csv_field(tag));
+    }
+    out.push('\n');
+    for (step, values) in rows {
+        out.push_str(&f64::from_bits(step).to_string());
+        for value in values {
+            out.push(',');
+            if let Some(value) = value {
+                out.push_str(&value.to_string());
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{metric_stats, osc52, scalars_csv, series_csv};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn osc52_base64_encodes_the_text() {
+        assert_eq!(osc52("loss"), "\x1b]52;c;bG9zcw==\x07");
+    }
+
+    #[test]
+    fn stats_and_csv_cover_the_series_tail() {
+        let data = [(0.0, 4.0), (10.0, 2.0), (20.0, 3.0)];
+        assert_eq!(
+            metric_stats("train/loss", &data),
+            "train/loss: latest=3 min=2 max=4 mean=3 points=3 steps=0-20"
+        );
+        assert_eq!(metric_stats("train/loss", &[]), "train/loss: no data");
+        assert_eq!(series_csv("a,b", &data, 2), "step,\"a,b\"\n10,2\n20,3\n");
+
+        let scalars = BTreeMap::from([
+            ("a".to_string(), vec![(0.0, 1.0), (10.0, 2.0)]),
+            ("b".to_string(), vec![(10.0, 5.0)]),
+        ]);
+        assert_eq!(scalars_csv(&scalars), "step,a,b\n0,1,\n10,2,5\n");
+    }
+}
@@ -0,0 +1,201 @@
+//! The TUI's `:` command line. A few commands drive the view (`:tab logs`,
+//! `:focus val/loss`, `:filter train/*`, `:export csv`, `:diff <run>`, `:q`); anything else
+//! is an `og` command (`:list runs`, `:annotate ...`) and runs exactly like
+//! `!og ...` typed into the chat.
+
+use anyhow::{Result, bail};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+use crate::app::Tab;
+
+#[derive(Debug, Parser)]
+#[command(no_binary_name = true, disable_help_subcommand = true)]
+struct CommandLine {
+    #[command(subcommand)]
+    command: ViewCommand,
+}
+
+/// Commands that act on the TUI itself.
+#[derive(Debug, Clone, PartialEq, Subcommand)]
+pub enum ViewCommand {
+    /// Switch tab (graphs, dashboard, logs, events, dists, procs, chat)
+    Tab {
+        #[arg(value_parser = parse_tab)]
+        tab: Tab,
+    },
+    /// Open a metric fullscreen (exact tag, or a part of only one tag)
+    Focus { metric: String },
+    /// Show only metrics matching the patterns; no pattern shows all metrics
+    Filter { patterns: Vec<String> },
+    /// Write the shown metrics to a file
+    Export {
+        format: ExportFormat,
+        /// Output file (default: <run>.csv in the working directory)
+        path: Option<PathBuf>,
+    },
+    /// Compare the focused metric with another run; no run ends the comparison
+    Diff {
+        /// Run id, alias, `latest` or path, beside the loaded run
+        run: Option<String>,
+        /// Overlay both runs and chart their difference instead of side by side
+        #[arg(long)]
+        overlay: bool,
+    },
+    /// Quit
+    #[command(name = "q", visible_alias = "quit")]
+    Quit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+}
+
+/// What a command line asks for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Parsed {
+    View(ViewCommand),
+    /// An `og` command, as `!og ...` chat input.
+    Og(String),
+}
+
+fn parse_tab(raw: &str) -> Result<Tab, String> {
+    let name = raw.to_ascii_lowercase();
+    Tab::ALL
+        .iter()
+        .copied()
+        .find(|tab| {
+            tab.title() == name
+                || (name == "processes" && *tab == Tab::Processes)
+                || (name == "distributions" && *tab == Tab::Distributions)
+        })
+        .ok_or_else(|| {
+            let names: Vec<&str> = Tab::ALL.iter().map(|tab| tab.title()).collect();
+            format!("unknown tab '{raw}' (expected {})", names.join(", "))
+        })
+}
+
+/// Parse the text typed after `:`. Words that are not a view command go to
+/// the `og` command registry when `is_og_command` accepts them; otherwise
+/// the view command's parse error is returned.
+pub fn parse(line: &str, is_og_command: impl Fn(&str) -> bool) -> Result<Parsed> {
+    let line = line.trim();
+    let Some(words) = shlex::split(line) else {
+        bail!("failed to parse command line");
+    };
+    if words.is_empty() {
+        bail!("empty command");
+    }
+    match CommandLine::try_parse_from(&words) {
+        Ok(parsed) => Ok(Parsed::View(parsed.command)),
+        Err(_) if is_og_command(line) => Ok(Parsed::Og(format!("!og {line}"))),
+        Err(err) => bail!("{}", first_line(&err.to_string())),
+    }
+}
+
+/// clap errors end with usage hints; the status bar has room for one line.
+fn first_line(message: &str) -> String {
+    message
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches("error: ")
+        .to_string()
+}
+
+/// Index of the tag `query` names: an exact match, else the only tag
+/// containing it.
+pub fn find_metric(tags: &[String], query: &str) -> Result<usize> {
+    if let Some(idx) = tags.iter().position(|tag| tag == query) {
+        return Ok(idx);
+    }
+    let needle = query.to_ascii_lowercase();
+    let matches: Vec<usize> = tags
+        .iter()
+        .enumerate()
+        .filter(|(_, tag)| tag.to_ascii_lowercase().contains(&needle))
+        .map(|(idx, _)| idx)
+        .collect();
+    match matches[..] {
+        [idx] => Ok(idx),
+        [] => bail!("no metric matches '{query}'"),
+        _ => bail!(
+            "'{query}' matches {} metrics; be more specific",
+            matches.len()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExportFormat, Parsed, ViewCommand, find_metric, parse};
+    use crate::app::Tab;
+    use std::path::PathBuf;
+
+    fn og(line: &str) -> bool {
+        line.starts_with("list ")
+    }
+
+    #[test]
+    fn parses_view_commands_and_hands_the_rest_to_og() {
+        assert_eq!(
+            parse("tab logs", og).expect("tab"),
+            Parsed::View(ViewCommand::Tab { tab: Tab::Logs })
+        );
+        assert_eq!(
+            parse("tab processes", og).expect("tab"),
+            Parsed::View(ViewCommand::Tab {
+                tab: Tab::Processes
+            })
+        );
+        assert_eq!(
+            parse("filter train/* 'val/loss'", og).expect("filter"),
+            Parsed::View(ViewCommand::Filter {
+                patterns: vec!["train/*".to_string(), "val/loss".to_string()]
+            })
+        );
+        assert_eq!(
+            parse("export csv out.csv", og).expect("export"),
+            Parsed::View(ViewCommand::Export {
+                format: ExportFormat::Csv,
+                path: Some(PathBuf::from("out.csv"))
+            })
+        );
+        assert_eq!(
+            parse("diff latest --overlay", og).expect("diff"),
+            Parsed::View(ViewCommand::Diff {
+                run: Some("latest".to_string()),
+                overlay: true
+            })
+        );
+        assert_eq!(
+            parse(" q ", og).expect("quit"),
+            Parsed::View(ViewCommand::Quit)
+        );
+        assert_eq!(
+            parse("list runs --path runs/", og).expect("og"),
+            Parsed::Og("!og list runs --path runs/".to_string())
+        );
+        assert_eq!(
+            parse("diff a b", |line| line.starts_with("diff ")).expect("og diff"),
+            Parsed::Og("!og diff a b".to_string())
+        );
+        let err = parse("tab nope", og).expect_err("unknown tab");
+        assert!(err.to_string().contains("unknown tab 'nope'"));
+        assert!(parse("", og).is_err());
+    }
+
+    #[test]
+    fn find_metric_prefers_exact_tags_then_a_unique_part() {
+        let tags = vec![
+            "train/loss".to_string(),
+            "val/loss".to_string(),
+            "val/loss_ema".to_string(),
+        ];
+        assert_eq!(find_metric(&tags, "val/loss").expect("exact"), 1);
+        assert_eq!(find_metric(&tags, "EMA").expect("unique part"), 2);
+        assert!(find_metric(&tags, "loss").is_err());
+        assert!(find_metric(&tags, "acc").is_err());
+    }
+}
@@ -0,0 +1,201 @@
+use anyhow::{Context, Result, bail};
+use og_core::error::OgError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File the training shim polls for live parameter changes, next to the event files.
+pub const CONTROL_FILE_NAME: &str = "control.json";
+
+/// Environment variable pointing the training process at its control file.
+pub const CONTROL_ENV_VAR: &str = "OG_CONTROL_FILE";
+
+/// Live-tunable parameters of a running job. The training shim declares
+/// `tunable` names and their starting values; `og set` bumps `version` and
+/// rewrites `params`, and the shim applies whatever changed since the version
+/// it last saw.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ControlFile {
+    #[serde(default)]
+    pub version: u64,
+    #[serde(default)]
+    pub tunable: BTreeSet<String>,
+    #[serde(default)]
+    pub params: BTreeMap<String, Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_unix: Option<u64>,
+}
+
+/// One parameter `set` changed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParamChange {
+    pub key: String,
+    pub old: Option<Value>,
+    pub new: Value,
+}
+
+pub fn control_path(run_dir: &Path) -> PathBuf {
+    crate::run_meta::run_dir_for(run_dir).join(CONTROL_FILE_NAME)
+}
+
+/// Load the control file; a run whose shim never declared anything yields an empty one.
+pub fn load(run_dir: &Path) -> Result<ControlFile> {
+    let path = control_path(run_dir);
+    if !path.exists() {
+        return Ok(ControlFile::default());
+    }
+    let raw = fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+    serde_json::from_str(&raw).with_context(|| OgError::parse_failed(path.display()))
+}
+
+/// Write atomically so the shim never reads a half-written file.
+fn save(run_dir: &Path, control: &ControlFile) -> Result<()> {
+    let path = control_path(run_dir);
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(control)?)
+        .with_context(|| format!("writing {}", tmp.display()))?;
+    fs::rename(&tmp, &path).with_context(|| format!("writing {}", path.display()))?;
+    Ok(())
+}
+
+/// Parse `key=value`. Numbers, booleans and JSON literals keep their type;
+/// anything else is taken as a string.
+pub fn parse_assignment(raw: &str) -> Result<(String, Value)> {
+    let Some((key, value)) = raw.split_once('=') else {
+        bail!("expected key=value, got '{raw}'");
+    };
+    let key = key.trim();
+    if key.is_empty() {
+        bail!("missing parameter name in '{raw}'");
+    }
+    let value = value.trim();
+    let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+    Ok((key.to_string(), value))
+}
+
+/// Apply `assignments` to a run's control file. Keys the shim did not declare
+/// tunable are refused unless `force` is set, since the training code would
+/// silently ignore them. Returns the new version and the values that changed.
+pub fn set(
+    run_dir: &Path,
+    assignments: &[(String, Value)],
+    force: bool,
+    now_unix: u64,
+) -> Result<(u64, Vec<ParamChange>)> {
+    let mut control = load(run_dir)?;
+    if !force {
+        let unknown: Vec<&str> = assignments
+            .iter()
+            .map(|(key, _)| key.as_str())
+            .filter(|key| !control.tunable.contains(*key))
+            .collect();
+        if !unknown.is_empty() {
+            let declared = if control.tunable.is_empty() {
+                "none; the training script has not declared live params".to_string()
+            } else {
+                control
+                    .tunable
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            bail!(
+                "not live-tunable: {} (tunable: {declared}); pass --force to set anyway",
+                unknown.join(", ")
+            );
+        }
+    }
+    let changes: Vec<ParamChange> = assignments
+        .iter()
+        .filter(|(key, value)| control.params.get(key) != Some(value))
+        .map(|(key, value)| ParamChange {
+            key: key.clone(),
+            old: control.params.get(key).cloned(),
+            new: value.clone(),
+        })
+        .collect();
+    if changes.is_empty() {
+        return Ok((control.version, changes));
+    }
+    for change in &changes {
+        control
+            .params
+            .insert(change.key.clone(), change.new.clone());
+    }
+    control.version += 1;
+    control.updated_unix = Some(now_unix);
+    save(run_dir, &control)?;
+    Ok((control.version, changes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{control_path, load, parse_assignment, set};
+    use serde_json::{Value, json};
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn assignments_keep_json_types() {
+        assert_eq!(
+            parse_assignment("lr=1e-4").expect("parse"),
+            ("lr".to_string(), json!(1e-4))
+        );
+        assert_eq!(
+            parse_assignment(" ema = true ").expect("parse"),
+            ("ema".to_string(), Value::Bool(true))
+        );
+        assert_eq!(
+            parse_assignment("sched=cosine").expect("parse"),
+            ("sched".to_string(), json!("cosine"))
+        );
+        assert!(parse_assignment("lr").is_err());
+        assert!(parse_assignment("=1").is_err());
+    }
+
+    #[test]
+    fn set_only_touches_declared_params_and_bumps_version() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let run_dir = std::env::temp_dir().join(format!("ogtui-control-{nonce}"));
+        fs::create_dir_all(&run_dir).expect("create run dir");
+        fs::write(
+            control_path(&run_dir),
+            r#"{"version": 0, "tunable": ["lr", "wd"], "params": {"lr": 0.001, "wd": 0.0}}"#,
+        )
+        .expect("write control");
+
+        let err = set(&run_dir, &[("batch".to_string(), json!(64))], false, 1)
+            .expect_err("undeclared key");
+        assert!(err.to_string().contains("not live-tunable: batch"));
+
+        let (version, changes) = set(
+            &run_dir,
+            &[
+                ("lr".to_string(), json!(1e-4)),
+                ("wd".to_string(), json!(0.0)),
+            ],
+            false,
+            7,
+        )
+        .expect("set lr");
+        assert_eq!(version, 1);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].old, Some(json!(0.001)));
+
+        let (version, changes) =
+            set(&run_dir, &[("lr".to_string(), json!(1e-4))], false, 8).expect("no-op set");
+        assert_eq!((version, changes.len()), (1, 0));
+
+        let control = load(&run_dir).expect("load");
+        assert_eq!(control.params["lr"], json!(1e-4));
+        assert_eq!(control.updated_unix, Some(7));
+
+        fs::remove_dir_all(&run_dir).ok();
+    }
+}
@@ -0,0 +1,83 @@
+//! Convergence check on an objective metric: the run has plateaued once the
+//! metric has not improved on its best value by more than `min_delta` for
+//! `patience` evaluations (logged points).
+
+use crate::project_config::ObjectiveMode;
+
+/// Default `--early-stop-patience`.
+pub const DEFAULT_PATIENCE: usize = 10;
+/// Default `--early-stop-min-delta`.
+pub const DEFAULT_MIN_DELTA: f64 = 1e-4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plateau {
+    pub best: f64,
+    pub best_step: f64,
+    /// Evaluations logged since the best one.
+    pub evaluations: usize,
+    /// Step of the latest evaluation.
+    pub step: f64,
+}
+
+/// Whether `series` has plateaued. Non-finite values never count as improvements.
+pub fn plateau(
+    series: &[(f64, f64)],
+    mode: ObjectiveMode,
+    min_delta: f64,
+    patience: usize,
+) -> Option<Plateau> {
+    if patience == 0 {
+        return None;
+    }
+    let mut best: Option<(f64, f64)> = None;
+    let mut since_best = 0;
+    for &(step, value) in series {
+        let improved = value.is_finite()
+            && best.is_none_or(|(_, best)| match mode {
+                ObjectiveMode::Min => value < best - min_delta,
+                ObjectiveMode::Max => value > best + min_delta,
+            });
+        if improved {
+            best = Some((step, value));
+            since_best = 0;
+        } else if best.is_some() {
+            since_best += 1;
+        }
+    }
+    let (best_step, best) = best?;
+    (since_best >= patience).then(|| Plateau {
+        best,
+        best_step,
+        evaluations: since_best,
+        step: series.last().map(|(step, _)| *step).unwrap_or(best_step),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::plateau;
+    use crate::project_config::ObjectiveMode;
+
+    #[test]
+    fn plateau_needs_patience_evaluations_without_real_improvement() {
+        let mut series: Vec<(f64, f64)> = (0..10)
+            .map(|step| (step as f64, 1.0 / (1.0 + step as f64)))
+            .collect();
+        assert_eq!(plateau(&series, ObjectiveMode::Min, 1e-3, 3), None);
+
+        // Improvements smaller than min_delta do not reset the count.
+        let best = series[9].1;
+        series.extend((10..13).map(|step| (step as f64, best - 1e-5 * step as f64)));
+        let found = plateau(&series, ObjectiveMode::Min, 1e-3, 3).expect("plateau");
+        assert_eq!(
+            (found.best_step, found.evaluations, found.step),
+            (9.0, 3, 12.0)
+        );
+
+        assert_eq!(plateau(&series, ObjectiveMode::Min, 1e-3, 4), None);
+        assert_eq!(plateau(&series, ObjectiveMode::Min, 1e-3, 0), None);
+        // Read as a metric to maximise, the first point was the best.
+        let found = plateau(&series, ObjectiveMode::Max, 1e-3, 3).expect("plateau");
+        assert_eq!((found.best_step, found.evaluations), (0.0, 12));
+    }
+}
@@ -0,0 +1,185 @@
+//! Estimated GPU spend of a run: wall-clock hours between its first and last
+//! logged event, times its GPU count, times a configured price per GPU-hour.
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+use crate::project_config::ProjectConfig;
+use crate::run_meta::RunMeta;
+use crate::tfevents::ScalarEvent;
+
+pub const PRICE_ENV_VAR: &str = "OG_GPU_HOUR_PRICE";
+pub const BUDGET_ENV_VAR: &str = "OG_BUDGET";
+pub const GPU_COUNT_ENV_VAR: &str = "OG_GPU_COUNT";
+
+/// Price and budget in dollars; either may be unset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Pricing {
+    pub gpu_hour_price: Option<f64>,
+    /// Spend per run above which the run is over budget.
+    pub budget: Option<f64>,
+}
+
+impl Pricing {
+    /// `OG_GPU_HOUR_PRICE` / `OG_BUDGET`, else the project's `[cost]` table.
+    pub fn resolve(project: &ProjectConfig) -> Result<Pricing> {
+        Ok(Pricing {
+            gpu_hour_price: env_amount(PRICE_ENV_VAR)?.or(project.cost.gpu_hour_price),
+            budget: env_amount(BUDGET_ENV_VAR)?.or(project.cost.budget),
+        })
+    }
+}
+
+fn env_amount(name: &str) -> Result<Option<f64>> {
+    let Ok(raw) = std::env::var(name) else {
+        return Ok(None);
+    };
+    if raw.trim().is_empty() {
+        return Ok(None);
+    }
+    match raw.trim().parse::<f64>() {
+        Ok(value) if value.is_finite() && value >= 0.0 => Ok(Some(value)),
+        _ => bail!("{name} must be a non-negative number, found '{raw}'"),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Spend {
+    pub gpus: u32,
+    pub hours: f64,
+    pub gpu_hour_price: f64,
+    pub dollars: f64,
+    pub budget: Option<f64>,
+    pub over_budget: bool,
+}
+
+impl Spend {
+    pub fn describe(&self) -> String {
+        let gpus = if self.gpus == 1 { "GPU" } else { "GPUs" };
+        let mut text = format!(
+            "${:.2} ({} {gpus} × {:.2} h at ${:.2}/GPU-hour)",
+            self.dollars, self.gpus, self.hours, self.gpu_hour_price
+        );
+        match self.budget {
+            Some(budget) if self.over_budget => {
+                text.push_str(&format!(", over the ${budget:.2} budget"))
+            }
+            Some(budget) => text.push_str(&format!(", budget ${budget:.2}")),
+            None => {}
+        }
+        text
+    }
+}
+
+/// Seconds between the first and last event; None without events.
+pub fn wall_secs(events: &[ScalarEvent]) -> Option<f64> {
+    let (first, last) = events
+        .iter()
+        .map(|event| event.wall_time)
+        .filter(|time| time.is_finite())
+        .fold(None, |span: Option<(f64, f64)>, time| match span {
+            Some((lo, hi)) => Some((lo.min(time), hi.max(time))),
+            None => Some((time, time)),
+        })?;
+    Some(last - first)
+}
+
+/// Spend over `wall_secs` on `gpus`; None when no price is configured.
+pub fn estimate(wall_secs: f64, gpus: u32, pricing: Pricing) -> Option<Spend> {
+    let gpu_hour_price = pricing.gpu_hour_price?;
+    let hours = wall_secs.max(0.0) / 3600.0;
+    let dollars = hours * f64::from(gpus) * gpu_hour_price;
+    Some(Spend {
+        gpus,
+        hours,
+        gpu_hour_price,
+        dollars,
+        budget: pricing.budget,
+        over_budget: pricing.budget.is_some_and(|budget| dollars > budget),
+    })
+}
+
+/// GPUs a run used: `OG_GPU_COUNT`, else the count recorded at launch, else
+/// one per distributed rank.
+pub fn run_gpus(meta: &RunMeta, ranks: usize) -> Option<u32> {
+    env_gpu_count()
+        .or(meta.gpu_count)
+        .or_else(|| (ranks > 0).then_some(ranks as u32))
+}
+
+/// GPUs a training process started from here would see: `OG_GPU_COUNT`,
+/// else `CUDA_VISIBLE_DEVICES`, else the GPUs `nvidia-smi -L` lists.
+pub fn detect_local_gpus() -> Option<u32> {
+    if let Some(count) = env_gpu_count() {
+        return Some(count);
+    }
+    if let Ok(devices) = std::env::var("CUDA_VISIBLE_DEVICES") {
+        return Some(visible_device_count(&devices));
+    }
+    let output = Command::new("nvidia-smi").arg("-L").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let listed = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| line.starts_with("GPU "))
+        .count();
+    Some(listed as u32)
+}
+
+fn env_gpu_count() -> Option<u32> {
+    std::env::var(GPU_COUNT_ENV_VAR).ok()?.trim().parse().ok()
+}
+
+/// Devices listed in a `CUDA_VISIBLE_DEVICES` value; CUDA stops at the first
+/// invalid entry, so `-1` hides every GPU after it.
+fn visible_device_count(raw: &str) -> u32 {
+    raw.split(',')
+        .map(str::trim)
+        .take_while(|entry| !entry.is_empty() && !entry.starts_with('-'))
+        .count() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Pricing, estimate, visible_device_count, wall_secs};
+    use crate::tfevents::ScalarEvent;
+
+    #[test]
+    fn estimates_spend_from_wall_time_gpus_and_price() {
+        let events: Vec<ScalarEvent> = [1000.0, 4600.0, 8200.0]
+            .into_iter()
+            .enumerate()
+            .map(|(step, wall_time)| ScalarEvent {
+                tag: "train/loss".to_string(),
+                step: step as i64,
+                wall_time,
+                value: 1.0,
+            })
+            .collect();
+        let secs = wall_secs(&events).expect("wall time");
+        assert_eq!(secs, 7200.0);
+        assert_eq!(wall_secs(&[]), None);
+
+        let pricing = Pricing {
+            gpu_hour_price: Some(2.5),
+            budget: Some(30.0),
+        };
+        let spend = estimate(secs, 4, pricing).expect("spend");
+        assert_eq!((spend.hours, spend.dollars), (2.0, 20.0));
+        assert!(!spend.over_budget);
+        assert_eq!(
+            spend.describe(),
+            "$20.00 (4 GPUs × 2.00 h at $2.50/GPU-hour), budget $30.00"
+        );
+        let spend = estimate(secs, 8, pricing).expect("spend");
+        assert!(spend.over_budget);
+        assert!(spend.describe().ends_with("over the $30.00 budget"));
+        assert_eq!(estimate(secs, 4, Pricing::default()), None);
+
+        assert_eq!(visible_device_count("0,1,2"), 3);
+        assert_eq!(visible_device_count(""), 0);
+        assert_eq!(visible_device_count("0,-1,2"), 1);
+    }
+}
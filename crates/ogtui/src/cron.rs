@@ -0,0 +1,225 @@
+//! Cron schedules (`minute hour day-of-month month day-of-week`) for jobs og
+//! runs on its own, such as project digests. Fields take `*`, numbers,
+//! ranges, lists and `/step`; `@hourly`, `@daily`, `@weekly` and `@monthly`
+//! are shorthands. Times are UTC.
+
+use anyhow::{Context, Result, bail};
+use serde::{Serialize, Serializer};
+
+const MINUTES_PER_DAY: u64 = 24 * 60;
+/// How far `next_after` looks ahead; covers `29 2` (Feb 29) schedules.
+const SEARCH_DAYS: u64 = 8 * 366;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    source: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether each day field was `*`; cron matches either day field when
+    /// both are restricted.
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Serialize for Schedule {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+impl Schedule {
+    pub fn parse(text: &str) -> Result<Schedule> {
+        let source = text.trim();
+        let expanded = match source {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            bail!(
+                "expected 5 fields (minute hour day month weekday), found {}",
+                fields.len()
+            );
+        };
+        let mut weekdays = field(weekday, 0, 7).context("weekday")?;
+        // 7 is Sunday too.
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Schedule {
+            source: source.to_string(),
+            minutes: field(minute, 0, 59).context("minute")?,
+            hours: field(hour, 0, 23).context("hour")?,
+            days: field(day, 1, 31).context("day of month")?,
+            months: field(month, 1, 12).context("month")?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+
+    /// First scheduled time strictly after `unix_secs`, on a minute boundary.
+    /// None for schedules that never fire, such as `0 0 31 2 *`.
+    pub fn next_after(&self, unix_secs: u64) -> Option<u64> {
+        let mut minute = unix_secs / 60 + 1;
+        let limit = minute + SEARCH_DAYS * MINUTES_PER_DAY;
+        while minute < limit {
+            let day = minute / MINUTES_PER_DAY;
+            if !self.matches_day(day) {
+                minute = (day + 1) * MINUTES_PER_DAY;
+                continue;
+            }
+            let hour = minute % MINUTES_PER_DAY / 60;
+            if self.hours & (1 << hour) == 0 {
+                minute = (minute / 60 + 1) * 60;
+                continue;
+            }
+            if self.minutes & (1 << (minute % 60)) != 0 {
+                return Some(minute * 60);
+            }
+            minute += 1;
+        }
+        None
+    }
+
+    fn matches_day(&self, days_since_epoch: u64) -> bool {
+        let (_, month, day) = civil_from_days(days_since_epoch);
+        if self.months & (1 << month) == 0 {
+            return false;
+        }
+        // 1970-01-01 was a Thursday.
+        let weekday = (days_since_epoch + 4) % 7;
+        let day_ok = self.days & (1 << day) != 0;
+        let weekday_ok = self.weekdays & (1 << weekday) != 0;
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day_ok || weekday_ok,
+            _ => day_ok && weekday_ok,
+        }
+    }
+}
+
+/// Bitmask of the values one field selects.
+fn field(text: &str, min: u64, max: u64) -> Result<u64> {
+    let mut mask = 0;
+    for item in text.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, Some(number(step)?)),
+            None => (item, None),
+        };
+        let (low, high) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((low, high)) => (number(low)?, number(high)?),
+                // `5/15` runs from 5 to the end of the range.
+                None if step.is_some() => (number(range)?, max),
+                None => (number(range)?, number(range)?),
+            },
+        };
+        if low < min || high > max || low > high {
+            bail!("'{item}' is outside {min}-{max}");
+        }
+        let step = step.unwrap_or(1);
+        if step == 0 {
+            bail!("'{item}' has a zero step");
+        }
+        for value in (low..=high).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+fn number(text: &str) -> Result<u64> {
+    text.parse()
+        .with_context(|| format!("'{text}' is not a number"))
+}
+
+/// (year, month 1-12, day 1-31) of a day count since 1970-01-01.
+fn civil_from_days(days: u64) -> (i64, u64, u64) {
+    // Howard Hinnant's days_from_civil, inverted.
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u64;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u64;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Schedule, civil_from_days};
+
+    // 2024-03-04 00:00 UTC, a Monday.
+    const MONDAY: u64 = 1_709_510_400;
+
+    #[test]
+    fn civil_dates_round_trip_through_leap_years() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(MONDAY / 86_400), (2024, 3, 4));
+        assert_eq!(civil_from_days(MONDAY / 86_400 - 4), (2024, 2, 29));
+    }
+
+    #[test]
+    fn next_after_walks_to_the_next_matching_minute() {
+        let weekdays_at_nine = Schedule::parse("0 9 * * 1-5").expect("parse");
+        assert_eq!(weekdays_at_nine.next_after(MONDAY), Some(MONDAY + 9 * 3600));
+        // Strictly after: the 09:00 tick itself moves on to Tuesday.
+        assert_eq!(
+            weekdays_at_nine.next_after(MONDAY + 9 * 3600),
+            Some(MONDAY + 86_400 + 9 * 3600)
+        );
+        // Friday evening skips the weekend.
+        let friday_evening = MONDAY + 4 * 86_400 + 18 * 3600;
+        assert_eq!(
+            weekdays_at_nine.next_after(friday_evening),
+            Some(MONDAY + 7 * 86_400 + 9 * 3600)
+        );
+
+        let quarter_hours = Schedule::parse("*/15 * * * *").expect("parse");
+        assert_eq!(quarter_hours.next_after(MONDAY + 61), Some(MONDAY + 900));
+        let sundays = Schedule::parse("@weekly").expect("parse");
+        assert_eq!(sundays.next_after(MONDAY), Some(MONDAY + 6 * 86_400));
+        assert_eq!(
+            Schedule::parse("0 0 * * 7")
+                .expect("parse")
+                .next_after(MONDAY),
+            Some(MONDAY + 6 * 86_400)
+        );
+        // Both day fields restricted: the 10th or any Monday.
+        let either = Schedule::parse("0 0 10 * 1").expect("parse");
+        assert_eq!(either.next_after(MONDAY), Some(MONDAY + 6 * 86_400));
+        assert_eq!(
+            either.next_after(MONDAY + 6 * 86_400),
+            Some(MONDAY + 7 * 86_400)
+        );
+        assert_eq!(
+            Schedule::parse("0 0 31 2 *")
+                .expect("parse")
+                .next_after(MONDAY),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_rejects_malformed_schedules() {
+        for bad in [
+            "0 9 * *",
+            "60 * * * *",
+            "0 9-7 * * *",
+            "*/0 * * * *",
+            "0 9 * * mon",
+        ] {
+            assert!(Schedule::parse(bad).is_err(), "{bad}");
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use anyhow::{Context, Result, bail};
+
+/// One CSV row converted to a metrics step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedRow {
+    pub step: i64,
+    pub wall_time: f64,
+    pub metrics: Vec<(String, f64)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportedCsv {
+    /// Metric columns (everything except the step and time columns), in file order.
+    pub columns: Vec<String>,
+    pub rows: Vec<ImportedRow>,
+    /// Rows without a usable step, or without any numeric metric.
+    pub skipped_rows: usize,
+}
+
+/// Split CSV text into records. Handles quoted fields (with `""` escapes and
+/// embedded newlines) and CRLF line endings.
+fn parse_records(text: &str) -> Result<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if in_quotes {
+            match ch {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(ch),
+            }
+            continue;
+        }
+        match ch {
+            '"' => in_quotes = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(ch),
+        }
+    }
+    if in_quotes {
+        bail!("unterminated quoted field");
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records.retain(|record| record.iter().any(|field| !field.trim().is_empty()));
+    Ok(records)
+}
+
+/// Steps are integers, but loggers often write them as `120.0`.
+fn parse_step(raw: &str) -> Option<i64> {
+    let raw = raw.trim();
+    if let Ok(step) = raw.parse() {
+        return Some(step);
+    }
+    let value: f64 = raw.parse().ok()?;
+    (value.is_finite() && value.fract() == 0.0).then_some(value as i64)
+}
+
+/// Convert a CSV log with a header row. Empty and non-numeric cells are left
+/// out, which is how CSVLogger-style files mark metrics not logged on a row.
+/// Rows take `default_wall_time` unless `time_column` gives unix seconds.
+pub fn convert(
+    text: &str,
+    step_column: &str,
+    time_column: Option<&str>,
+    default_wall_time: f64,
+) -> Result<ImportedCsv> {
+    let mut records = parse_records(text)?.into_iter();
+    let Some(header) = records.next() else {
+        bail!("CSV file is empty");
+    };
+    let header: Vec<String> = header.iter().map(|name| name.trim().to_string()).collect();
+    let find = |name: &str| header.iter().position(|column| column == name);
+    let step_idx = find(step_column).with_context(|| {
+        format!(
+            "step column '{}' not found (columns: {})",
+            step_column,
+            header.join(", ")
+        )
+    })?;
+    let time_idx = match time_column {
+        Some(name) => Some(find(name).with_context(|| format!("time column '{name}' not found"))?),
+        None => None,
+    };
+    let metric_idxs: Vec<usize> = (0..header.len())
+        .filter(|idx| *idx != step_idx && Some(*idx) != time_idx)
+        .collect();
+
+    let mut imported = ImportedCsv {
+        columns: metric_idxs.iter().map(|idx| header[*idx].clone()).collect(),
+        ..ImportedCsv::default()
+    };
+    for record in records {
+        let cell = |idx: usize| record.get(idx).map(String::as_str).unwrap_or_default();
+        let Some(step) = parse_step(cell(step_idx)) else {
+            imported.skipped_rows += 1;
+            continue;
+        };
+        let wall_time = time_idx
+            .and_then(|idx| cell(idx).trim().parse().ok())
+            .unwrap_or(default_wall_time);
+        let metrics: Vec<(String, f64)> = metric_idxs
+            .iter()
+            .filter_map(|idx| {
+                let value: f64 = cell(*idx).trim().parse().ok()?;
+                value.is_finite().then(|| (header[*idx].clone(), value))
+            })
+            .collect();
+        if metrics.is_empty() {
+            imported.skipped_rows += 1;
+            continue;
+        }
+        imported.rows.push(ImportedRow {
+            step,
+            wall_time,
+            metrics,
+        });
+    }
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{convert, parse_records};
+
+    #[test]
+    fn parses_quoted_fields_and_crlf() {
+        let records =
+            parse_records("a,\"b, c\",\"say \"\"hi\"\"\"\r\n1,2,3\r\n\r\n").expect("parse");
+        assert_eq!(
+            records,
+            vec![
+                vec![
+                    "a".to_string(),
+                    "b, c".to_string(),
+                    "say \"hi\"".to_string()
+                ],
+                vec!["1".to_string(), "2".to_string(), "3".to_string()],
+            ]
+        );
+        assert!(parse_records("a,\"open\n").is_err());
+    }
+
+    #[test]
+    fn converts_lightning_style_sparse_rows() {
+        let csv = "epoch,step,train_loss,val_loss,time\n\
+                   0,49,0.9,,1000.5\n\
+                   0,99.0,0.7,,1001.5\n\
+                   0,99,,0.8,1001.5\n\
+                   ,,,,\n\
+                   0,bad,0.1,,1002\n";
+        let imported = convert(csv, "step", Some("time"), 0.0).expect("convert");
+        assert_eq!(imported.columns, vec!["epoch", "train_loss", "val_loss"]);
+        assert_eq!(imported.skipped_rows, 1);
+        let rows: Vec<(i64, f64, Vec<&str>)> = imported
+            .rows
+            .iter()
+            .map(|row| {
+                (
+                    row.step,
+                    row.wall_time,
+                    row.metrics.iter().map(|(tag, _)| tag.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                (49, 1000.5, vec!["epoch", "train_loss"]),
+                (99, 1001.5, vec!["epoch", "train_loss"]),
+                (99, 1001.5, vec!["epoch", "val_loss"]),
+            ]
+        );
+
+        let err = convert(csv, "global_step", None, 0.0).expect_err("missing column");
+        assert!(
+            err.to_string()
+                .contains("step column 'global_step' not found")
+        );
+    }
+}
@@ -0,0 +1,338 @@
+//! Background agent daemons for `og daemon`. A managed daemon outlives the
+//! command that started it; its state sits next to its socket:
+//! `<socket>.pid` (the process id), `<socket>.launch.json` (how it was
+//! started, for `og daemon restart`) and `<socket>.log` (its stdout and
+//! stderr).
+
+use anyhow::{Context, Result, bail};
+use og_core::error::OgError;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::UnixStream;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Files that belong to the daemon on one socket.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paths {
+    pub socket: PathBuf,
+    pub pid: PathBuf,
+    pub launch: PathBuf,
+    pub log: PathBuf,
+}
+
+impl Paths {
+    pub fn for_socket(socket: &Path) -> Self {
+        let with_suffix = |suffix: &str| {
+            let mut path = OsString::from(socket.as_os_str());
+            path.push(suffix);
+            PathBuf::from(path)
+        };
+        Self {
+            socket: socket.to_path_buf(),
+            pid: with_suffix(".pid"),
+            launch: with_suffix(".launch.json"),
+            log: with_suffix(".log"),
+        }
+    }
+}
+
+/// Whether a process with this id exists (`kill -0`).
+pub fn is_alive(pid: u32) -> bool {
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// The pid in the pidfile, if that process is still alive. A pidfile whose
+/// process is gone is stale and yields None.
+pub fn running_pid(paths: &Paths) -> Option<u32> {
+    let pid = recorded_pid(paths)?;
+    is_alive(pid).then_some(pid)
+}
+
+/// The pid in the pidfile, without checking that it still runs.
+pub fn recorded_pid(paths: &Paths) -> Option<u32> {
+    fs::read_to_string(&paths.pid).ok()?.trim().parse().ok()
+}
+
+/// Managed daemons with their socket in `dir` whose process is alive, by
+/// socket path.
+pub fn running_in(dir: &Path) -> Vec<(Paths, u32)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut running: Vec<(Paths, u32)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let socket = name.strip_suffix(".launch.json")?;
+            let paths = Paths::for_socket(&dir.join(socket));
+            let pid = running_pid(&paths)?;
+            Some((paths, pid))
+        })
+        .collect();
+    running.sort_by(|a, b| a.0.socket.cmp(&b.0.socket));
+    running
+}
+
+/// Daemon sockets og named (`opengraphs-ogd*.sock`) in `dir`, managed or
+/// not, whether or not anything still listens on them.
+pub fn sockets_in(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut sockets: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_socket()))
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with(crate::socket_client::SOCKET_PREFIX) && name.ends_with(".sock")
+        })
+        .map(|entry| entry.path())
+        .collect();
+    sockets.sort();
+    sockets
+}
+
+/// Start `command` in its own process group, so it survives the terminal
+/// and the `og` process that started it, appending its output to the log.
+/// Records the pid and `launch`.
+pub fn spawn(mut command: Command, paths: &Paths, launch: &impl Serialize) -> Result<u32> {
+    if let Some(pid) = running_pid(paths) {
+        bail!(
+            "a daemon is already running on {} (pid {pid})",
+            paths.socket.display()
+        );
+    }
+    let log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&paths.log)
+        .with_context(|| format!("opening {}", paths.log.display()))?;
+    command
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log)
+        .process_group(0);
+    let mut child = command.spawn().context("spawning daemon")?;
+    let pid = child.id();
+    // Reap the child if it exits while this process still runs (e.g. `!og`
+    // from the TUI); otherwise it would linger as a zombie that `kill -0`
+    // still finds.
+    std::thread::spawn(move || child.wait());
+    fs::write(&paths.pid, format!("{pid}\n"))
+        .with_context(|| format!("writing {}", paths.pid.display()))?;
+    fs::write(&paths.launch, serde_json::to_string_pretty(launch)?)
+        .with_context(|| format!("writing {}", paths.launch.display()))?;
+    Ok(pid)
+}
+
+/// How the running daemon was started.
+pub fn load_launch<T: DeserializeOwned>(paths: &Paths) -> Result<T> {
+    let raw = fs::read_to_string(&paths.launch)
+        .with_context(|| format!("reading {}", paths.launch.display()))?;
+    serde_json::from_str(&raw).with_context(|| OgError::parse_failed(paths.launch.display()))
+}
+
+/// Wait until the daemon answers on its socket, it exits, or `timeout`
+/// passes. Returns whether it is up.
+pub fn wait_until_up(paths: &Paths, pid: u32, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if UnixStream::connect(&paths.socket).is_ok() {
+            return true;
+        }
+        if !is_alive(pid) {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    false
+}
+
+/// Ask `pid` to exit (SIGTERM), then force it (SIGKILL) after `timeout`.
+/// Removes the pidfile and a socket file nobody listens on any more.
+/// Returns whether the process had to be killed.
+pub fn stop(paths: &Paths, pid: u32, timeout: Duration) -> Result<bool> {
+    signal(pid, "TERM")?;
+    let deadline = Instant::now() + timeout;
+    let mut killed = false;
+    while is_alive(pid) {
+        if Instant::now() >= deadline {
+            signal(pid, "KILL")?;
+            killed = true;
+            let grace = Instant::now() + Duration::from_secs(2);
+            while is_alive(pid) && Instant::now() < grace {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    if is_alive(pid) {
+        bail!("pid {pid} is still running after SIGKILL");
+    }
+    let _ = fs::remove_file(&paths.pid);
+    if paths.socket.exists() && UnixStream::connect(&paths.socket).is_err() {
+        let _ = fs::remove_file(&paths.socket);
+    }
+    Ok(killed)
+}
+
+/// Variable the daemon reads for how long training gets to exit on SIGINT
+/// (checkpointing on the way out) before it is killed.
+pub const STOP_TIMEOUT_ENV: &str = "OG_STOP_TIMEOUT_SECS";
+pub const DEFAULT_STOP_TIMEOUT_SECS: u64 = 30;
+/// Extra time the daemon gets on top of the training stop timeout to kill
+/// training, record the run's end and exit itself.
+pub const STOP_GRACE: Duration = Duration::from_secs(10);
+
+/// How long a daemon may take to stop its training, from `OG_STOP_TIMEOUT_SECS`.
+pub fn stop_timeout() -> Duration {
+    let secs = std::env::var(STOP_TIMEOUT_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse::<f64>().ok())
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+        .unwrap_or(DEFAULT_STOP_TIMEOUT_SECS as f64);
+    Duration::from_secs_f64(secs) + STOP_GRACE
+}
+
+/// `stop` for a daemon this process spawned and still owns: SIGTERM, wait
+/// up to `timeout`, then SIGKILL. Returns whether it had to be killed.
+pub fn stop_child(child: &mut Child, timeout: Duration) -> Result<bool> {
+    if child.try_wait()?.is_some() {
+        return Ok(false);
+    }
+    signal(child.id(), "TERM")?;
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if child.try_wait()?.is_some() {
+            return Ok(false);
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    child.kill().context("killing daemon")?;
+    child.wait()?;
+    Ok(true)
+}
+
+fn signal(pid: u32, name: &str) -> Result<()> {
+    let status = Command::new("kill")
+        .args([format!("-{name}"), pid.to_string()])
+        .stderr(Stdio::null())
+        .status()
+        .context("running kill")?;
+    // The process may have exited between the check and the signal.
+    if !status.success() && is_alive(pid) {
+        bail!("failed to send SIG{name} to pid {pid}");
+    }
+    Ok(())
+}
+
+/// The last `lines` lines of the log.
+pub fn tail_log(paths: &Paths, lines: usize) -> Result<Vec<String>> {
+    let file = fs::File::open(&paths.log)
+        .with_context(|| format!("no daemon log at {}", paths.log.display()))?;
+    let mut tail = std::collections::VecDeque::with_capacity(lines);
+    for line in BufReader::new(file).lines() {
+        if tail.len() == lines {
+            tail.pop_front();
+        }
+        if lines > 0 {
+            tail.push_back(line?);
+        }
+    }
+    Ok(tail.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Paths, is_alive, running_in, running_pid, sockets_in, spawn, stop, stop_child, tail_log,
+    };
+    use std::fs;
+    use std::process::Command;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn spawn_records_the_pid_and_stop_clears_it() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("ogtui-daemon-{nonce}"));
+        fs::create_dir_all(&dir).expect("dir");
+        let paths = Paths::for_socket(&dir.join("ogd.sock"));
+        assert_eq!(paths.pid, dir.join("ogd.sock.pid"));
+        assert_eq!(running_pid(&paths), None);
+
+        let mut command = Command::new("sh");
+        command.args(["-c", "echo one; echo two; exec sleep 30"]);
+        let pid = spawn(
+            command,
+            &paths,
+            &serde_json::json!({"training_file": "t.py"}),
+        )
+        .expect("spawn");
+        assert_eq!(running_pid(&paths), Some(pid));
+        assert!(
+            spawn(Command::new("true"), &paths, &serde_json::json!({})).is_err(),
+            "one daemon per socket"
+        );
+        let launch: serde_json::Value = super::load_launch(&paths).expect("launch");
+        assert_eq!(launch["training_file"], "t.py");
+        assert_eq!(running_in(&dir), vec![(paths.clone(), pid)]);
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while tail_log(&paths, 5).map(|lines| lines.len()).unwrap_or(0) < 2
+            && std::time::Instant::now() < deadline
+        {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(tail_log(&paths, 1).expect("tail"), vec!["two"]);
+
+        let killed = stop(&paths, pid, Duration::from_secs(5)).expect("stop");
+        assert!(!killed, "sleep exits on SIGTERM");
+        assert!(!is_alive(pid));
+        assert!(!paths.pid.exists());
+        assert!(running_in(&dir).is_empty());
+
+        // Only og's daemon sockets are listed, not the query socket or logs.
+        let socket = dir.join("opengraphs-ogd-r1-0000.sock");
+        let _listener = std::os::unix::net::UnixListener::bind(&socket).expect("bind");
+        let _query =
+            std::os::unix::net::UnixListener::bind(dir.join("opengraphs-ogd-r1-0000.sock.query"))
+                .expect("bind query");
+        assert_eq!(sockets_in(&dir), vec![socket]);
+
+        // A pidfile left by a process that is gone is stale.
+        fs::write(&paths.pid, "999999999\n").expect("stale pidfile");
+        assert_eq!(running_pid(&paths), None);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn stop_child_kills_only_after_the_timeout() {
+        let mut polite = Command::new("sleep").arg("30").spawn().expect("spawn");
+        assert!(!stop_child(&mut polite, Duration::from_secs(5)).expect("stop"));
+
+        // Ignores SIGTERM, like a daemon stuck waiting on training.
+        let mut stubborn = Command::new("sh")
+            .args(["-c", "trap '' TERM; while :; do sleep 0.05; done"])
+            .spawn()
+            .expect("spawn");
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(stop_child(&mut stubborn, Duration::from_millis(300)).expect("stop"));
+        assert!(stubborn.try_wait().expect("wait").is_some());
+    }
+}
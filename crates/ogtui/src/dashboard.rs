@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::cost::Spend;
+use crate::tfevents::{LoadedRun, ScalarEvent};
+
+/// Number of recent values kept per row for the sparkline.
+pub const RECENT_POINTS: usize = 64;
+
+/// Wall-clock window used to estimate throughput.
+const THROUGHPUT_WINDOW_SECS: f64 = 60.0;
+
+/// One run's row in the dashboard tab.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DashboardRow {
+    pub run: String,
+    /// Metric drawn as the sparkline (first loss-like tag, else first non-system tag).
+    pub metric: Option<String>,
+    /// Most recent values of `metric`, oldest first.
+    pub recent: Vec<f64>,
+    pub step: i64,
+    /// Steps per second over the latest minute of wall time.
+    pub steps_per_sec: Option<f64>,
+    pub status: String,
+    /// Stable per-run color (see `run_color`).
+    pub color: (u8, u8, u8),
+    /// Estimated GPU spend (None without a configured price or known GPU count).
+    pub spend: Option<Spend>,
+}
+
+impl DashboardRow {
+    pub fn from_loaded(
+        run: String,
+        loaded: &LoadedRun,
+        status: String,
+        color: (u8, u8, u8),
+    ) -> Self {
+        let metric = primary_metric(&loaded.scalars).cloned();
+        let recent = metric
+            .as_ref()
+            .and_then(|tag| loaded.scalars.get(tag))
+            .map(|series| {
+                let skip = series.len().saturating_sub(RECENT_POINTS);
+                series[skip..].iter().map(|(_, value)| *value).collect()
+            })
+            .unwrap_or_default();
+        DashboardRow {
+            run,
+            metric,
+            recent,
+            step: loaded.events.iter().map(|e| e.step).max().unwrap_or(0),
+            steps_per_sec: steps_per_sec(&loaded.events),
+            status,
+            color,
+            spend: None,
+        }
+    }
+}
+
+pub fn primary_metric(scalars: &BTreeMap<String, Vec<(f64, f64)>>) -> Option<&String> {
+    let training = || {
+        scalars
+            .keys()
+            .filter(|tag| !crate::looks_system_metric(tag))
+    };
+    training()
+        .find(|tag| tag.to_ascii_lowercase().contains("loss"))
+        .or_else(|| training().next())
+        .or_else(|| scalars.keys().next())
+}
+
+/// Step rate between the first and last events inside the trailing window.
+pub fn steps_per_sec(events: &[ScalarEvent]) -> Option<f64> {
+    let latest = events
+        .iter()
+        .map(|e| e.wall_time)
+        .fold(f64::NEG_INFINITY, f64::max);
+    if !latest.is_finite() {
+        return None;
+    }
+    let window: Vec<&ScalarEvent> = events
+        .iter()
+        .filter(|e| e.wall_time >= latest - THROUGHPUT_WINDOW_SECS)
+        .collect();
+    let first = window
+        .iter()
+        .min_by(|a, b| a.wall_time.total_cmp(&b.wall_time))?;
+    let last = window
+        .iter()
+        .max_by(|a, b| a.wall_time.total_cmp(&b.wall_time))?;
+    let elapsed = last.wall_time - first.wall_time;
+    if elapsed <= 0.0 || last.step <= first.step {
+        return None;
+    }
+    Some((last.step - first.step) as f64 / elapsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DashboardRow, primary_metric, steps_per_sec};
+    use crate::tfevents::{LoadedRun, ScalarEvent};
+    use std::collections::BTreeMap;
+
+    fn event(tag: &str, step: i64, wall_time: f64, value: f64) -> ScalarEvent {
+        ScalarEvent {
+            tag: tag.to_string(),
+            step,
+            wall_time,
+            value,
+        }
+    }
+
+    #[test]
+    fn primary_metric_prefers_loss_over_system_tags() {
+        let mut scalars = BTreeMap::new();
+        scalars.insert("sys/gpu_util".to_string(), vec![(1.0, 90.0)]);
+        scalars.insert("train/acc".to_string(), vec![(1.0, 0.5)]);
+        scalars.insert("train/loss".to_string(), vec![(1.0, 1.2)]);
+        assert_eq!(
+            primary_metric(&scalars).map(String::as_str),
+            Some("train/loss")
+        );
+
+        scalars.remove("train/loss");
+        assert_eq!(
+            primary_metric(&scalars).map(String::as_str),
+            Some("train/acc")
+        );
+    }
+
+    #[test]
+    fn steps_per_sec_uses_trailing_window() {
+        let events = vec![
+            event("loss", 0, 0.0, 1.0),
+            event("loss", 10, 100.0, 1.0),
+            event("loss", 30, 140.0, 1.0),
+        ];
+        // Only the last two events fall inside the 60s window: 20 steps / 40s.
+        assert_eq!(steps_per_sec(&events), Some(0.5));
+        assert_eq!(steps_per_sec(&events[..1]), None);
+    }
+
+    #[test]
+    fn row_keeps_recent_primary_metric_values() {
+        let events = vec![
+            event("train/loss", 1, 10.0, 2.0),
+            event("train/loss", 2, 11.0, 1.5),
+        ];
+        let mut scalars = BTreeMap::new();
+        scalars.insert("train/loss".to_string(), vec![(1.0, 2.0), (2.0, 1.5)]);
+        let loaded = LoadedRun {
+            scalars,
+            events,
+            ranks: BTreeMap::new(),
+            rank_progress: BTreeMap::new(),
+            corruption: Default::default(),
+        };
+
+        let row = DashboardRow::from_loaded(
+            "run-a".to_string(),
+            &loaded,
+            "running".to_string(),
+            (1, 2, 3),
+        );
+        assert_eq!(row.metric.as_deref(), Some("train/loss"));
+        assert_eq!(row.recent, vec![2.0, 1.5]);
+        assert_eq!(row.step, 2);
+        assert_eq!(row.steps_per_sec, Some(1.0));
+    }
+}
@@ -0,0 +1,142 @@
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+
+/// Environment variable overriding the default `ogtui=debug` filter
+/// (`EnvFilter` syntax, e.g. `ogtui=trace`).
+pub const FILTER_ENV_VAR: &str = "OG_DEBUG_LOG_FILTER";
+
+/// Size at which the log is rotated to `<file>.1`.
+pub const MAX_LOG_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Rotated files kept next to the live one (`<file>.1` is the newest).
+pub const KEEP_ROTATED: usize = 3;
+
+/// Append-only log file that renames itself to `<file>.1` (shifting older
+/// ones up to `<file>.N`) once it grows past `max_bytes`.
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+    max_bytes: u64,
+    keep: usize,
+}
+
+impl RotatingFile {
+    pub fn open(path: &Path, max_bytes: u64, keep: usize) -> Result<Self> {
+        let file = open_append(path)?;
+        let written = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            written,
+            max_bytes,
+            keep,
+        })
+    }
+
+    fn rotated(&self, idx: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{idx}"));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.keep == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            for idx in (1..self.keep).rev() {
+                let from = self.rotated(idx);
+                if from.exists() {
+                    fs::rename(&from, self.rotated(idx + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated(1))?;
+            self.file = open_append(&self.path).map_err(io::Error::other)?;
+        }
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn open_append(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("opening debug log {}", path.display()))
+}
+
+/// Send `tracing` output to `path` and nowhere else; span close lines carry
+/// `time.busy` / `time.idle`, which is where refresh, parse and draw timings
+/// come from. Without this the TUI installs no subscriber and every span is
+/// a no-op.
+pub fn init(path: &Path) -> Result<()> {
+    let writer = RotatingFile::open(path, MAX_LOG_BYTES, KEEP_ROTATED)?;
+    let filter = std::env::var(FILTER_ENV_VAR)
+        .ok()
+        .and_then(|raw| EnvFilter::try_new(raw).ok())
+        .unwrap_or_else(|| EnvFilter::new("ogtui=debug"));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(Mutex::new(writer))
+        .with_ansi(false)
+        .with_thread_names(true)
+        .with_span_events(FmtSpan::CLOSE)
+        .try_init()
+        .map_err(|err| anyhow::anyhow!("installing debug log: {err}"))?;
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), "debug log started");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RotatingFile;
+    use std::fs;
+    use std::io::Write;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn rotates_past_the_size_limit_and_keeps_a_bounded_history() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("ogtui-debug-log-{nonce}"));
+        fs::create_dir_all(&dir).expect("create dir");
+        let path = dir.join("ogtui.log");
+
+        let mut log = RotatingFile::open(&path, 10, 2).expect("open");
+        for line in ["aaaaaaaa\n", "bbbbbbbb\n", "cccccccc\n", "dddddddd\n"] {
+            log.write_all(line.as_bytes()).expect("write");
+        }
+        log.flush().expect("flush");
+
+        let read = |name: &str| fs::read_to_string(dir.join(name)).expect("read log");
+        assert_eq!(read("ogtui.log"), "dddddddd\n");
+        assert_eq!(read("ogtui.log.1"), "cccccccc\n");
+        assert_eq!(read("ogtui.log.2"), "bbbbbbbb\n");
+        assert!(!dir.join("ogtui.log.3").exists());
+        fs::remove_dir_all(&dir).ok();
+    }
+}
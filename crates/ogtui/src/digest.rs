@@ -0,0 +1,436 @@
+//! Project digests for `og digest`: which runs started and finished since the
+//! last one, the best objective values and the alerts the agent daemon
+//! raised. The `[digest]` table in `project.toml` sets when they are sent and
+//! where: a webhook (Slack-style `{"text": ...}` JSON) or an SMTP server.
+//! Both go through `curl`, which also handles TLS and `~/.netrc` logins.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::cron::Schedule;
+use crate::format;
+use crate::project_config::{Objective, ObjectiveMode};
+use crate::run_meta::{RunHeartbeat, RunState};
+
+/// Alerts the agent daemon raised for a run, one JSON object per line.
+pub const ALERTS_FILE_NAME: &str = "og_alerts.jsonl";
+/// Runs listed under the best objective values.
+const TOP_RUNS: usize = 5;
+/// Alert messages quoted in the text; the rest are only counted.
+const QUOTED_ALERTS: usize = 10;
+
+/// `[digest]` in `project.toml`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DigestConfig {
+    pub schedule: Schedule,
+    pub webhook: Option<String>,
+    /// `smtp://host:587` (STARTTLS when offered) or `smtps://host:465`.
+    pub smtp: Option<String>,
+    pub from: Option<String>,
+    pub to: Vec<String>,
+}
+
+/// One line of `og_alerts.jsonl`, as the daemon serializes alerts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alert {
+    #[serde(default, skip_deserializing)]
+    pub run: String,
+    pub metric: String,
+    pub message: String,
+    pub timestamp: f64,
+}
+
+/// What the digest needs to know about one run.
+#[derive(Debug, Clone, Default)]
+pub struct RunSnapshot {
+    pub id: String,
+    /// Wall time of the run's first event.
+    pub started_unix: Option<u64>,
+    pub last_updated_unix: Option<u64>,
+    pub status: Option<RunHeartbeat>,
+    /// Best objective value and its step.
+    pub best: Option<(i64, f64)>,
+    pub alerts: Vec<Alert>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FinishedRun {
+    pub run: String,
+    pub state: RunState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BestRun {
+    pub run: String,
+    pub value: f64,
+    pub step: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Digest {
+    pub project: String,
+    pub since_unix: u64,
+    pub until_unix: u64,
+    pub objective: Option<Objective>,
+    pub started: Vec<String>,
+    pub finished: Vec<FinishedRun>,
+    /// Runs active in the period, best objective value first.
+    pub best: Vec<BestRun>,
+    /// Oldest first.
+    pub alerts: Vec<Alert>,
+}
+
+impl Digest {
+    /// Summarize `runs` over `[since_unix, until_unix)`.
+    pub fn collect(
+        project: String,
+        objective: Option<Objective>,
+        since_unix: u64,
+        until_unix: u64,
+        runs: Vec<RunSnapshot>,
+    ) -> Digest {
+        let within = |unix: u64| (since_unix..until_unix).contains(&unix);
+        let mut digest = Digest {
+            project,
+            since_unix,
+            until_unix,
+            objective,
+            started: Vec::new(),
+            finished: Vec::new(),
+            best: Vec::new(),
+            alerts: Vec::new(),
+        };
+        for run in runs {
+            if run.started_unix.is_some_and(within) {
+                digest.started.push(run.id.clone());
+            }
+            if let Some(status) = run
+                .status
+                .filter(|status| status.state != RunState::Running && within(status.heartbeat_unix))
+            {
+                digest.finished.push(FinishedRun {
+                    run: run.id.clone(),
+                    state: status.state,
+                    exit_code: status.exit_code,
+                    reason: status.reason,
+                });
+            }
+            let active = run
+                .last_updated_unix
+                .is_some_and(|updated| updated >= since_unix);
+            if let Some((step, value)) = run.best.filter(|_| active) {
+                digest.best.push(BestRun {
+                    run: run.id.clone(),
+                    value,
+                    step,
+                });
+            }
+            digest.alerts.extend(
+                run.alerts
+                    .into_iter()
+                    .filter(|alert| alert.timestamp >= since_unix as f64)
+                    .filter(|alert| alert.timestamp < until_unix as f64)
+                    .map(|alert| Alert {
+                        run: run.id.clone(),
+                        ..alert
+                    }),
+            );
+        }
+        let max = digest
+            .objective
+            .as_ref()
+            .is_some_and(|objective| objective.mode == ObjectiveMode::Max);
+        digest.best.sort_by(|a, b| {
+            let order = a.value.total_cmp(&b.value);
+            if max { order.reverse() } else { order }
+        });
+        digest.best.truncate(TOP_RUNS);
+        digest
+            .alerts
+            .sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+        digest
+    }
+
+    pub fn subject(&self) -> String {
+        format!(
+            "og digest for {}: {} started, {} finished, {} alerts",
+            self.project,
+            self.started.len(),
+            self.finished.len(),
+            self.alerts.len()
+        )
+    }
+
+    pub fn text(&self) -> String {
+        let mut lines = vec![format!(
+            "{} (last {})",
+            self.subject(),
+            format::duration(self.until_unix.saturating_sub(self.since_unix) as f64)
+        )];
+        if !self.started.is_empty() {
+            lines.push(format!("started: {}", self.started.join(", ")));
+        }
+        if !self.finished.is_empty() {
+            let finished: Vec<String> = self
+                .finished
+                .iter()
+                .map(|run| {
+                    let state = match run.state {
+                        RunState::Finished => "finished",
+                        RunState::Crashed => "crashed",
+                        RunState::Killed => "killed",
+                        RunState::Running => "running",
+                    };
+                    match (&run.reason, run.exit_code) {
+                        (Some(reason), _) => format!("{} ({state}: {reason})", run.run),
+                        (None, Some(code)) if code != 0 => {
+                            format!("{} ({state}, exit {code})", run.run)
+                        }
+                        _ => format!("{} ({state})", run.run),
+                    }
+                })
+                .collect();
+            lines.push(format!("finished: {}", finished.join(", ")));
+        }
+        if let Some(objective) = self.objective.as_ref().filter(|_| !self.best.is_empty()) {
+            lines.push(format!(
+                "best {} ({}):",
+                objective.metric,
+                match objective.mode {
+                    ObjectiveMode::Min => "min",
+                    ObjectiveMode::Max => "max",
+                }
+            ));
+            for (rank, best) in self.best.iter().enumerate() {
+                lines.push(format!(
+                    "  {}. {} {} at step {}",
+                    rank + 1,
+                    best.run,
+                    format::value(best.value),
+                    best.step
+                ));
+            }
+        }
+        if !self.alerts.is_empty() {
+            lines.push("alerts:".to_string());
+            let quoted = &self.alerts[self.alerts.len().saturating_sub(QUOTED_ALERTS)..];
+            for alert in quoted {
+                lines.push(format!("  {}: {}", alert.run, alert.message));
+            }
+            if quoted.len() < self.alerts.len() {
+                lines.push(format!(
+                    "  ...and {} earlier",
+                    self.alerts.len() - quoted.len()
+                ));
+            }
+        }
+        if lines.len() == 1 {
+            lines.push("no run activity".to_string());
+        }
+        lines.join("\n")
+    }
+}
+
+/// The alerts recorded for a run; a missing file means none.
+pub fn read_alerts(run_dir: &Path) -> Result<Vec<Alert>> {
+    let path = run_dir.join(ALERTS_FILE_NAME);
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).with_context(|| format!("reading {}", path.display())),
+    };
+    // A line cut short by a crash mid-write is skipped, not fatal.
+    Ok(text
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Send the digest everywhere `config` names; returns where it went.
+pub fn deliver(config: &DigestConfig, digest: &Digest) -> Result<Vec<String>> {
+    let mut sent = Vec::new();
+    if let Some(url) = &config.webhook {
+        let body = serde_json::json!({ "text": digest.text(), "digest": digest });
+        curl(
+            &[
+                "-X",
+                "POST",
+                "-H",
+                "Content-Type: application/json",
+                "--data-binary",
+                "@-",
+                url,
+            ],
+            &serde_json::to_vec(&body)?,
+        )
+        .context("posting to the digest webhook")?;
+        sent.push("webhook".to_string());
+    }
+    if let Some(url) = &config.smtp {
+        let from = config.from.as_deref().context("[digest] smtp needs from")?;
+        let mut args = vec!["--url", url, "--mail-from", from];
+        for to in &config.to {
+            args.extend(["--mail-rcpt", to.as_str()]);
+        }
+        if url.starts_with("smtp://") {
+            args.push("--ssl");
+        }
+        args.extend(["--netrc-optional", "--upload-file", "-"]);
+        curl(&args, email(from, &config.to, digest).as_bytes())
+            .context("sending the digest email")?;
+        sent.push(config.to.join(", "));
+    }
+    Ok(sent)
+}
+
+/// The digest as a plain-text RFC 5322 message.
+fn email(from: &str, to: &[String], digest: &Digest) -> String {
+    let body = digest.text().replace('\n', "\r\n");
+    format!(
+        "From: {from}\r\nTo: {}\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{body}\r\n",
+        to.join(", "),
+        digest.subject()
+    )
+}
+
+fn curl(args: &[&str], stdin: &[u8]) -> Result<()> {
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail"])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("running curl; is it installed?")?;
+    child
+        .stdin
+        .take()
+        .expect("piped stdin")
+        .write_all(stdin)
+        .context("writing to curl")?;
+    let output = child.wait_with_output().context("waiting for curl")?;
+    if !output.status.success() {
+        bail!("curl: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Alert, Digest, RunSnapshot, email, read_alerts};
+    use crate::project_config::{Objective, ObjectiveMode};
+    use crate::run_meta::{RunHeartbeat, RunState};
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn heartbeat(state: RunState, at: u64) -> Option<RunHeartbeat> {
+        Some(RunHeartbeat {
+            state,
+            heartbeat_unix: at,
+            pid: None,
+            exit_code: (state == RunState::Crashed).then_some(137),
+            reason: None,
+        })
+    }
+
+    fn alert(timestamp: f64, message: &str) -> Alert {
+        Alert {
+            run: String::new(),
+            metric: "train/loss".to_string(),
+            message: message.to_string(),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn collect_keeps_what_happened_in_the_period() {
+        let runs = vec![
+            RunSnapshot {
+                id: "old-done".to_string(),
+                started_unix: Some(100),
+                last_updated_unix: Some(500),
+                status: heartbeat(RunState::Finished, 500),
+                best: Some((40, 0.9)),
+                alerts: vec![alert(200.0, "too old")],
+            },
+            RunSnapshot {
+                id: "crashed".to_string(),
+                started_unix: Some(900),
+                last_updated_unix: Some(1500),
+                status: heartbeat(RunState::Crashed, 1500),
+                best: Some((10, 2.0)),
+                alerts: vec![alert(1400.0, "loss spiked")],
+            },
+            RunSnapshot {
+                id: "new".to_string(),
+                started_unix: Some(1100),
+                last_updated_unix: Some(1900),
+                status: heartbeat(RunState::Running, 1900),
+                best: Some((30, 1.5)),
+                alerts: vec![alert(1200.0, "lr too high")],
+            },
+        ];
+        let objective = Objective {
+            metric: "val/loss".to_string(),
+            mode: ObjectiveMode::Min,
+        };
+        let digest = Digest::collect("vision".to_string(), Some(objective), 1000, 2000, runs);
+        assert_eq!(digest.started, vec!["new"]);
+        assert_eq!(digest.finished.len(), 1);
+        assert_eq!(digest.finished[0].run, "crashed");
+        let best: Vec<&str> = digest.best.iter().map(|best| best.run.as_str()).collect();
+        assert_eq!(best, ["new", "crashed"]);
+        let alerts: Vec<(&str, &str)> = digest
+            .alerts
+            .iter()
+            .map(|alert| (alert.run.as_str(), alert.message.as_str()))
+            .collect();
+        assert_eq!(alerts, [("new", "lr too high"), ("crashed", "loss spiked")]);
+
+        let text = digest.text();
+        assert!(text.starts_with("og digest for vision: 1 started, 1 finished, 2 alerts"));
+        assert!(
+            text.contains("finished: crashed (crashed, exit 137)"),
+            "{text}"
+        );
+        assert!(text.contains("  1. new 1.5000 at step 30"), "{text}");
+        assert!(text.contains("  crashed: loss spiked"), "{text}");
+
+        let quiet = Digest::collect("vision".to_string(), None, 5000, 6000, Vec::new());
+        assert!(quiet.text().ends_with("no run activity"));
+        let message = email("og@lab", &["lead@lab".to_string()], &quiet);
+        assert!(message.starts_with("From: og@lab\r\nTo: lead@lab\r\nSubject: og digest"));
+        assert!(message.contains("\r\n\r\nog digest for vision"));
+    }
+
+    #[test]
+    fn read_alerts_skips_torn_lines_and_missing_files() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("ogtui-digest-{nonce}"));
+        fs::create_dir_all(&dir).expect("create dir");
+        assert!(read_alerts(&dir).expect("no file").is_empty());
+        fs::write(
+            dir.join(super::ALERTS_FILE_NAME),
+            concat!(
+                "{\"metric\": \"train/loss\", \"threshold\": 5.0, \"current\": 7.0, ",
+                "\"message\": \"loss spiked\", \"timestamp\": 1200.5}\n",
+                "{\"metric\": \"train/lo",
+            ),
+        )
+        .expect("write alerts");
+        let alerts = read_alerts(&dir).expect("read alerts");
+        assert_eq!(alerts, vec![alert(1200.5, "loss spiked")]);
+        fs::remove_dir_all(&dir).expect("cleanup");
+    }
+}
@@ -0,0 +1,302 @@
+//! `og discover`: browse the LAN for ogd instances advertising
+//! `_ogd._tcp.local` over mDNS. The query goes out from an ephemeral port,
+//! so responders answer it unicast and no port-5353 socket is needed here.
+
+use anyhow::{Context, Result};
+use og_core::dns::{
+    CLASS_IN, MDNS_ADDR, MDNS_PORT, SERVICE, TYPE_A, TYPE_AAAA, TYPE_PTR, TYPE_SRV, TYPE_TXT,
+    put_name, put_u16, read_name, read_u16,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+const MDNS_GROUP: SocketAddr = SocketAddr::new(IpAddr::V4(MDNS_ADDR), MDNS_PORT);
+/// Queries sent per browse; mDNS over wifi loses packets.
+const QUERIES: u32 = 3;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Instance {
+    /// DNS-SD instance name without the service suffix, e.g. `gpu-box-8787`.
+    pub name: String,
+    pub host: String,
+    pub addrs: Vec<IpAddr>,
+    pub port: u16,
+    pub version: Option<String>,
+    pub projects: Vec<String>,
+}
+
+impl Instance {
+    /// `addr:port` to reach it at, preferring IPv4.
+    pub fn endpoint(&self) -> Option<SocketAddr> {
+        let addr = self
+            .addrs
+            .iter()
+            .find(|addr| addr.is_ipv4())
+            .or(self.addrs.first())?;
+        Some(SocketAddr::new(*addr, self.port))
+    }
+}
+
+/// Ask for ogd instances and collect answers for `timeout`.
+pub fn browse(timeout: Duration) -> Result<Vec<Instance>> {
+    let socket =
+        UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).context("failed to open a UDP socket")?;
+    let query = query_packet(std::process::id() as u16);
+    let started = Instant::now();
+    let mut records = Records::default();
+    let mut packet = [0u8; 9000];
+    for attempt in 0..QUERIES {
+        socket
+            .send_to(&query, MDNS_GROUP)
+            .context("failed to send the mDNS query")?;
+        let until = started + timeout * (attempt + 1) / QUERIES;
+        while let Some(left) = until.checked_duration_since(Instant::now()) {
+            socket.set_read_timeout(Some(left.max(Duration::from_millis(1))))?;
+            match socket.recv_from(&mut packet) {
+                Ok((len, _)) => records.read(&packet[..len]),
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    break;
+                }
+                Err(err) => return Err(err).context("failed to read mDNS answers"),
+            }
+        }
+    }
+    Ok(records.instances())
+}
+
+fn query_packet(id: u16) -> Vec<u8> {
+    let mut packet = Vec::new();
+    // id, flags, one question, no records.
+    for value in [id, 0, 1, 0, 0, 0] {
+        put_u16(&mut packet, value);
+    }
+    put_name(&mut packet, SERVICE);
+    put_u16(&mut packet, TYPE_PTR);
+    put_u16(&mut packet, CLASS_IN);
+    packet
+}
+
+/// Records gathered from every answer, keyed by lower-cased owner name.
+#[derive(Debug, Default)]
+struct Records {
+    instances: Vec<String>,
+    services: BTreeMap<String, (u16, String)>,
+    texts: BTreeMap<String, Vec<String>>,
+    addrs: BTreeMap<String, Vec<IpAddr>>,
+}
+
+impl Records {
+    /// Add a response's records; malformed packets are ignored from the
+    /// first bad record on.
+    fn read(&mut self, packet: &[u8]) {
+        let (Some(flags), Some(questions)) = (read_u16(packet, 2), read_u16(packet, 4)) else {
+            return;
+        };
+        if flags & 0x8000 == 0 {
+            return;
+        }
+        let records = [6, 8, 10]
+            .iter()
+            .filter_map(|offset| read_u16(packet, *offset))
+            .map(usize::from)
+            .sum::<usize>();
+        let mut offset = 12;
+        for _ in 0..questions {
+            let Some((_, next)) = read_name(packet, offset) else {
+                return;
+            };
+            offset = next + 4;
+        }
+        for _ in 0..records {
+            let Some(next) = self.read_record(packet, offset) else {
+                return;
+            };
+            offset = next;
+        }
+    }
+
+    fn read_record(&mut self, packet: &[u8], offset: usize) -> Option<usize> {
+        let (owner, offset) = read_name(packet, offset)?;
+        let owner = owner.to_ascii_lowercase();
+        let kind = read_u16(packet, offset)?;
+        let len = usize::from(read_u16(packet, offset + 8)?);
+        let start = offset + 10;
+        let data = packet.get(start..start + len)?;
+        match kind {
+            TYPE_PTR if owner == SERVICE => {
+                let (instance, _) = read_name(packet, start)?;
+                let instance = instance.to_ascii_lowercase();
+                if !self.instances.contains(&instance) {
+                    self.instances.push(instance);
+                }
+            }
+            TYPE_SRV => {
+                let port = read_u16(data, 4)?;
+                let (target, _) = read_name(packet, start + 6)?;
+                self.services
+                    .insert(owner, (port, target.to_ascii_lowercase()));
+            }
+            TYPE_TXT => {
+                let mut strings = Vec::new();
+                let mut rest = data;
+                while let Some((&len, tail)) = rest.split_first() {
+                    let text = tail.get(..usize::from(len))?;
+                    strings.push(String::from_utf8_lossy(text).into_owned());
+                    rest = &tail[usize::from(len)..];
+                }
+                self.texts.insert(owner, strings);
+            }
+            TYPE_A => {
+                let octets: [u8; 4] = data.try_into().ok()?;
+                self.add_addr(owner, Ipv4Addr::from(octets).into());
+            }
+            TYPE_AAAA => {
+                let octets: [u8; 16] = data.try_into().ok()?;
+                self.add_addr(owner, Ipv6Addr::from(octets).into());
+            }
+            _ => {}
+        }
+        Some(start + len)
+    }
+
+    fn add_addr(&mut self, owner: String, addr: IpAddr) {
+        let addrs = self.addrs.entry(owner).or_default();
+        if !addrs.contains(&addr) {
+            addrs.push(addr);
+        }
+    }
+
+    /// Instances with a known port, sorted by name.
+    fn instances(&self) -> Vec<Instance> {
+        let suffix = format!(".{SERVICE}");
+        let mut found: Vec<Instance> = self
+            .instances
+            .iter()
+            .filter_map(|full| {
+                let (port, target) = self.services.get(full)?;
+                let mut instance = Instance {
+                    name: full.strip_suffix(&suffix).unwrap_or(full).to_string(),
+                    host: target.strip_suffix(".local").unwrap_or(target).to_string(),
+                    addrs: self.addrs.get(target).cloned().unwrap_or_default(),
+                    port: *port,
+                    ..Instance::default()
+                };
+                for entry in self.texts.get(full).into_iter().flatten() {
+                    match entry.split_once('=') {
+                        Some(("version", version)) => instance.version = Some(version.into()),
+                        Some(("host", host)) if !host.is_empty() => instance.host = host.into(),
+                        Some(("projects", projects)) => {
+                            instance.projects = projects
+                                .split(',')
+                                .filter(|project| !project.is_empty())
+                                .map(str::to_string)
+                                .collect();
+                        }
+                        _ => {}
+                    }
+                }
+                Some(instance)
+            })
+            .collect();
+        found.sort_by(|a, b| a.name.cmp(&b.name));
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Records, SERVICE, TYPE_A, TYPE_PTR, TYPE_SRV, TYPE_TXT, query_packet};
+    use std::net::{IpAddr, Ipv4Addr};
+
+    fn name(out: &mut Vec<u8>, name: &str) {
+        for label in name.split('.') {
+            out.push(label.len() as u8);
+            out.extend_from_slice(label.as_bytes());
+        }
+        out.push(0);
+    }
+
+    fn record(out: &mut Vec<u8>, owner: &[u8], kind: u16, data: &[u8]) {
+        out.extend_from_slice(owner);
+        out.extend_from_slice(&kind.to_be_bytes());
+        out.extend_from_slice(&[0, 1, 0, 0, 0, 120]);
+        out.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        out.extend_from_slice(data);
+    }
+
+    /// A response like ogd's, with the instance name compressed.
+    fn response(instance: &str, port: u16, txt: &[&str], addr: [u8; 4]) -> Vec<u8> {
+        let mut out = vec![0, 0, 0x84, 0, 0, 0, 0, 1, 0, 0, 0, 3];
+        let mut service = Vec::new();
+        name(&mut service, SERVICE);
+        let mut full = Vec::new();
+        name(&mut full, &format!("{instance}.{SERVICE}"));
+        let instance_at = (out.len() + service.len() + 10) as u16;
+        record(&mut out, &service, TYPE_PTR, &full);
+        let pointer = (0xc000 | instance_at).to_be_bytes();
+
+        let mut srv = vec![0, 0, 0, 0];
+        srv.extend_from_slice(&port.to_be_bytes());
+        name(&mut srv, "gpu-box.local");
+        record(&mut out, &pointer, TYPE_SRV, &srv);
+        let mut text = Vec::new();
+        for entry in txt {
+            text.push(entry.len() as u8);
+            text.extend_from_slice(entry.as_bytes());
+        }
+        record(&mut out, &pointer, TYPE_TXT, &text);
+        let mut target = Vec::new();
+        name(&mut target, "gpu-box.local");
+        record(&mut out, &target, TYPE_A, &addr);
+        out
+    }
+
+    #[test]
+    fn query_asks_for_the_ogd_service() {
+        let packet = query_packet(42);
+        assert_eq!(&packet[..12], &[0, 42, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&packet[12..17], b"\x04_ogd");
+        assert_eq!(&packet[packet.len() - 4..], &[0, 12, 0, 1]);
+    }
+
+    #[test]
+    fn responses_assemble_into_instances() {
+        let mut records = Records::default();
+        records.read(&response(
+            "gpu-box-8787",
+            8787,
+            &["version=0.1.7", "host=gpu-box", "projects=nlp,vision"],
+            [10, 0, 0, 12],
+        ));
+        // Repeated answers to later queries change nothing.
+        records.read(&response(
+            "gpu-box-8787",
+            8787,
+            &["version=0.1.7", "host=gpu-box", "projects=nlp,vision"],
+            [10, 0, 0, 12],
+        ));
+        // Queries and truncated packets are ignored.
+        records.read(&query_packet(1));
+        records.read(&response("other-9000", 9000, &[], [10, 0, 0, 13])[..40]);
+
+        let instances = records.instances();
+        assert_eq!(instances.len(), 1);
+        let found = &instances[0];
+        assert_eq!(found.name, "gpu-box-8787");
+        assert_eq!(found.host, "gpu-box");
+        assert_eq!(found.addrs, vec![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 12))]);
+        assert_eq!(found.version.as_deref(), Some("0.1.7"));
+        assert_eq!(found.projects, vec!["nlp", "vision"]);
+        assert_eq!(
+            found.endpoint().map(|addr| addr.to_string()).as_deref(),
+            Some("10.0.0.12:8787")
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Bytes used by a file, or by everything under a directory. Symlinks are
+/// not followed and unreadable entries count as empty.
+pub fn path_size(path: &Path) -> u64 {
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| path_size(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Parse a size such as `512`, `800M`, `10G` or `1.5TiB`. Units are binary
+/// whether or not they carry the `i`.
+pub fn parse_bytes(raw: &str) -> Result<u64> {
+    let raw = raw.trim();
+    let split = raw
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(raw.len());
+    let (number, unit) = raw.split_at(split);
+    let number: f64 = number
+        .parse()
+        .with_context(|| format!("invalid size '{raw}'"))?;
+    let shift = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 0,
+        "k" | "kb" | "kib" => 10,
+        "m" | "mb" | "mib" => 20,
+        "g" | "gb" | "gib" => 30,
+        "t" | "tb" | "tib" => 40,
+        other => bail!("unknown size unit '{other}' in '{raw}'"),
+    };
+    Ok((number * (1u64 << shift) as f64) as u64)
+}
+
+/// Bytes available to this user on the filesystem holding `path` (or its
+/// nearest existing ancestor), from `df`. None when `df` is unavailable.
+pub fn free_space(path: &Path) -> Option<u64> {
+    let existing = path.ancestors().find(|dir| dir.exists())?;
+    let output = Command::new("df").arg("-Pk").arg(existing).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_df_available(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// `Available` column (1K blocks) of POSIX `df -Pk` output, in bytes.
+fn parse_df_available(stdout: &str) -> Option<u64> {
+    let row = stdout.lines().nth(1)?;
+    let available: u64 = row.split_whitespace().nth(3)?.parse().ok()?;
+    Some(available * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_bytes, parse_df_available, path_size};
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn path_size_sums_nested_files() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("ogtui-disk-usage-{nonce}"));
+        fs::create_dir_all(dir.join("nested")).expect("create dirs");
+        fs::write(dir.join("a.bin"), [0u8; 100]).expect("write a");
+        fs::write(dir.join("nested/b.bin"), [0u8; 28]).expect("write b");
+        assert_eq!(path_size(&dir), 128);
+        assert_eq!(path_size(&dir.join("a.bin")), 100);
+        assert_eq!(path_size(&dir.join("missing")), 0);
+        fs::remove_dir_all(&dir).expect("cleanup");
+    }
+
+    #[test]
+    fn parse_bytes_accepts_binary_units() {
+        assert_eq!(parse_bytes("512").expect("plain"), 512);
+        assert_eq!(parse_bytes("10G").expect("short"), 10 << 30);
+        assert_eq!(parse_bytes("1.5 KiB").expect("fraction"), 1536);
+        assert!(parse_bytes("10 parsecs").is_err());
+        assert!(parse_bytes("lots").is_err());
+    }
+
+    #[test]
+    fn df_output_yields_available_bytes() {
+        let stdout = "Filesystem     1024-blocks      Used Available Capacity Mounted on\n\
+                      /dev/nvme0n1p2   959786032 612034100 298922084      68% /\n";
+        assert_eq!(parse_df_available(stdout), Some(298_922_084 * 1024));
+        assert_eq!(parse_df_available("garbage"), None);
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+
+use crate::tfevents::{RankProgress, rank_in_text};
+
+/// A rank whose newest event trails the most recent rank by this much is
+/// considered stuck (the usual symptom of a hung allreduce).
+pub const STALL_SECS: f64 = 120.0;
+
+/// Keep only the latest issues; NCCL can be very chatty once it starts failing.
+pub const MAX_ISSUES: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueKind {
+    Warning,
+    Error,
+    Timeout,
+}
+
+impl IssueKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            IssueKind::Warning => "warn",
+            IssueKind::Error => "error",
+            IssueKind::Timeout => "timeout",
+        }
+    }
+}
+
+/// An NCCL / torch.distributed problem reported in the training output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistIssue {
+    pub rank: Option<u32>,
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankStatus {
+    Alive,
+    /// No new events for `STALL_SECS` while other ranks kept writing.
+    Stalled,
+    /// The rank reported a collective or watchdog timeout.
+    TimedOut,
+}
+
+impl RankStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            RankStatus::Alive => "alive",
+            RankStatus::Stalled => "stalled",
+            RankStatus::TimedOut => "timed out",
+        }
+    }
+}
+
+/// One row of the distributed health panel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankHealth {
+    pub rank: u32,
+    pub status: RankStatus,
+    pub last_step: i64,
+    /// Seconds this rank's newest event trails the most recent rank.
+    pub behind_secs: f64,
+    pub issues: usize,
+}
+
+/// Recognise NCCL / torch.distributed warnings, errors and timeouts.
+/// Plain `NCCL INFO` chatter is ignored.
+pub fn parse_log_line(line: &str) -> Option<DistIssue> {
+    let lower = line.to_ascii_lowercase();
+    let distributed = lower.contains("nccl")
+        || lower.contains("torch.distributed")
+        || lower.contains("processgroup")
+        || lower.contains("c10d")
+        || lower.contains("allreduce")
+        || lower.contains("all_reduce");
+    if !distributed {
+        return None;
+    }
+    let kind = if lower.contains("timeout") || lower.contains("timed out") {
+        IssueKind::Timeout
+    } else if lower.contains("error") || lower.contains("exception") || lower.contains("abort") {
+        IssueKind::Error
+    } else if lower.contains("warn") {
+        IssueKind::Warning
+    } else {
+        return None;
+    };
+    let message: String = line.trim().chars().take(160).collect();
+    Some(DistIssue {
+        rank: rank_in_text(line),
+        kind,
+        message,
+    })
+}
+
+/// Combine per-rank event progress with the issues seen in the logs.
+pub fn assess(progress: &BTreeMap<u32, RankProgress>, issues: &[DistIssue]) -> Vec<RankHealth> {
+    let newest = progress
+        .values()
+        .map(|p| p.last_wall_time)
+        .fold(f64::NEG_INFINITY, f64::max);
+    progress
+        .iter()
+        .map(|(rank, p)| {
+            let rank_issues = issues.iter().filter(|i| i.rank == Some(*rank));
+            let timed_out = rank_issues
+                .clone()
+                .any(|issue| issue.kind == IssueKind::Timeout);
+            let behind_secs = if newest.is_finite() && p.last_wall_time.is_finite() {
+                (newest - p.last_wall_time).max(0.0)
+            } else {
+                0.0
+            };
+            let status = if timed_out {
+                RankStatus::TimedOut
+            } else if progress.len() > 1 && behind_secs > STALL_SECS {
+                RankStatus::Stalled
+            } else {
+                RankStatus::Alive
+            };
+            RankHealth {
+                rank: *rank,
+                status,
+                last_step: p.last_step,
+                behind_secs,
+                issues: rank_issues.count(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IssueKind, RankStatus, assess, parse_log_line};
+    use crate::tfevents::RankProgress;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn parses_nccl_watchdog_timeouts_and_warnings() {
+        let timeout = parse_log_line(
+            "[rank3]:[E ProcessGroupNCCL.cpp:616] [Rank 3] Watchdog caught collective operation timeout: WorkNCCL(SeqNum=812, OpType=ALLREDUCE) ran for 600000 milliseconds",
+        )
+        .expect("timeout issue");
+        assert_eq!(timeout.kind, IssueKind::Timeout);
+        assert_eq!(timeout.rank, Some(3));
+
+        let warning = parse_log_line("node1:4242:4300 [0] NCCL WARN NET/Socket : peer closed")
+            .expect("warning issue");
+        assert_eq!(warning.kind, IssueKind::Warning);
+        assert_eq!(warning.rank, None);
+
+        assert!(parse_log_line("node1:4242:4300 [0] NCCL INFO Channel 00/02").is_none());
+        assert!(parse_log_line("step 10 timeout while saving").is_none());
+    }
+
+    #[test]
+    fn assess_flags_rank_that_stopped_while_others_continue() {
+        let mut progress = BTreeMap::new();
+        progress.insert(
+            0,
+            RankProgress {
+                last_step: 900,
+                last_wall_time: 1_000.0,
+            },
+        );
+        progress.insert(
+            1,
+            RankProgress {
+                last_step: 400,
+                last_wall_time: 700.0,
+            },
+        );
+        progress.insert(
+            2,
+            RankProgress {
+                last_step: 895,
+                last_wall_time: 990.0,
+            },
+        );
+
+        let health = assess(&progress, &[]);
+        let statuses: Vec<RankStatus> = health.iter().map(|h| h.status).collect();
+        assert_eq!(
+            statuses,
+            vec![RankStatus::Alive, RankStatus::Stalled, RankStatus::Alive]
+        );
+        assert_eq!(health[1].last_step, 400);
+        assert_eq!(health[1].behind_secs, 300.0);
+
+        let issues = vec![parse_log_line("[Rank 2] NCCL watchdog timeout").expect("issue")];
+        let health = assess(&progress, &issues);
+        assert_eq!(health[2].status, RankStatus::TimedOut);
+        assert_eq!(health[2].issues, 1);
+    }
+}
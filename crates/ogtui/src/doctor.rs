@@ -0,0 +1,413 @@
+//! `og doctor`: checks of the environment `og` runs in — the daemon socket,
+//! the Python agent, GPU tooling, the runs directory, the terminal and files
+//! left behind by crashed processes — each with a fix when it fails.
+
+use serde::Serialize;
+use std::ffi::OsString;
+use std::fs;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+use crate::socket_client;
+
+/// Temp files younger than this may belong to a write in progress.
+const STALE_AFTER: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+impl Status {
+    pub fn label(self) -> &'static str {
+        match self {
+            Status::Ok => "[ok]",
+            Status::Warn => "[warn]",
+            Status::Fail => "[fail]",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+    /// What to do about a warning or failure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Fail,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Whether the daemon answers on `socket`. A socket file nobody listens on
+/// is left from a daemon that died, and `og run` cannot bind over it.
+pub fn daemon(socket: &Path) -> Check {
+    const NAME: &str = "daemon";
+    if !socket.exists() {
+        return Check::warn(
+            NAME,
+            format!("no daemon socket at {}", socket.display()),
+            "`og run <script>` starts one; set OGD_SOCKET if yours listens elsewhere",
+        );
+    }
+    match socket_client::ping(socket) {
+        Ok(true) => Check::ok(NAME, format!("responding at {}", socket.display())),
+        Ok(false) => Check::fail(
+            NAME,
+            format!("{} answered the ping with an error", socket.display()),
+            "restart the daemon; another program may own this socket path",
+        ),
+        Err(_) if UnixStream::connect(socket).is_err() => Check::fail(
+            NAME,
+            format!("stale socket {} (nothing is listening)", socket.display()),
+            format!("rm {}", socket.display()),
+        ),
+        Err(err) => Check::fail(
+            NAME,
+            format!(
+                "{} accepts connections but does not answer: {err}",
+                socket.display()
+            ),
+            "restart the daemon",
+        ),
+    }
+}
+
+/// Whether `python` runs and can import the agent, with `agent_src` (a
+/// source checkout of `og_agent_chat`) on its path the way `og run` sets it.
+pub fn python(python: &str, agent_src: Option<&Path>) -> Check {
+    const NAME: &str = "python";
+    let version = Command::new(python).arg("--version").output();
+    let version = match version {
+        Ok(output) if output.status.success() => {
+            let text = [output.stdout, output.stderr].concat();
+            String::from_utf8_lossy(&text).trim().to_string()
+        }
+        _ => {
+            return Check::fail(
+                NAME,
+                format!("no working Python interpreter ('{python}')"),
+                "install Python 3, or point OG_PYTHON_BIN at one",
+            );
+        }
+    };
+    let mut import = Command::new(python);
+    import.args(["-c", "import og_agent_chat"]);
+    if let Some(src) = agent_src {
+        let mut path = OsString::from(src);
+        if let Some(existing) = std::env::var_os("PYTHONPATH").filter(|p| !p.is_empty()) {
+            path.push(":");
+            path.push(existing);
+        }
+        import.env("PYTHONPATH", path);
+    }
+    match import.output() {
+        Ok(output) if output.status.success() => {
+            Check::ok(NAME, format!("{version} ({python}) imports og_agent_chat"))
+        }
+        _ => Check::fail(
+            NAME,
+            format!("{version} ({python}) cannot import og_agent_chat"),
+            "pip install -e python/agent-chat from the opengraphs checkout, or set OG_PYTHON_BIN to a Python that has it",
+        ),
+    }
+}
+
+/// GPU metrics come from `nvidia-smi`, the command-line face of NVML.
+pub fn gpu() -> Check {
+    const NAME: &str = "gpu";
+    match Command::new("nvidia-smi").arg("-L").output() {
+        Err(_) => Check::warn(
+            NAME,
+            "nvidia-smi not found; GPU and VRAM metrics are off",
+            "install the NVIDIA driver utilities, or ignore this on a CPU-only machine",
+        ),
+        Ok(output) if !output.status.success() => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Check::fail(
+                NAME,
+                format!(
+                    "nvidia-smi failed: {}",
+                    stderr.lines().next().unwrap_or("no output")
+                ),
+                "check the NVIDIA driver; `nvidia-smi` should list your GPUs",
+            )
+        }
+        Ok(output) => {
+            let gpus = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter(|line| line.starts_with("GPU "))
+                .count();
+            Check::ok(NAME, format!("nvidia-smi lists {gpus} GPU(s)"))
+        }
+    }
+}
+
+/// Whether runs can be written under `root`, or where it would be created.
+pub fn runs_dir(root: &Path) -> Check {
+    const NAME: &str = "runs dir";
+    if root.exists() && !root.is_dir() {
+        return Check::fail(
+            NAME,
+            format!("{} is not a directory", root.display()),
+            "pass --path with the directory runs are written to",
+        );
+    }
+    // `runs/` ends in the empty path, which stands for the working directory.
+    let Some(dir) = root
+        .ancestors()
+        .map(|dir| {
+            if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir
+            }
+        })
+        .find(|dir| dir.is_dir())
+    else {
+        return Check::fail(
+            NAME,
+            format!("no existing parent of {}", root.display()),
+            "pass --path with the directory runs are written to",
+        );
+    };
+    let probe = dir.join(format!(".og-doctor-{}", std::process::id()));
+    if let Err(err) = fs::write(&probe, b"") {
+        return Check::fail(
+            NAME,
+            format!("cannot write to {}: {err}", dir.display()),
+            format!(
+                "chmod u+w {}, or pass --path with a writable directory",
+                dir.display()
+            ),
+        );
+    }
+    let _ = fs::remove_file(&probe);
+    if dir == root {
+        Check::ok(NAME, format!("{} is writable", root.display()))
+    } else {
+        Check::ok(
+            NAME,
+            format!(
+                "{} does not exist yet; {} is writable",
+                root.display(),
+                dir.display()
+            ),
+        )
+    }
+}
+
+/// 24-bit color (run colors and heatmaps) and mouse support (clicking
+/// cards and tabs), from the `TERM` / `COLORTERM` the TUI would start under.
+pub fn terminal(term: Option<&str>, colorterm: Option<&str>, is_tty: bool) -> Vec<Check> {
+    let color = match colorterm {
+        Some("truecolor" | "24bit") => Check::ok("truecolor", "COLORTERM advertises 24-bit color"),
+        _ => Check::warn(
+            "truecolor",
+            "COLORTERM does not advertise 24-bit color; colors may be approximated",
+            "use a truecolor terminal and export COLORTERM=truecolor",
+        ),
+    };
+    let mouse = match term.filter(|term| !term.is_empty()) {
+        None | Some("dumb") => Check::fail(
+            "mouse",
+            format!("TERM is {}", term.unwrap_or("unset")),
+            "run og in a terminal emulator that sets TERM, e.g. xterm-256color",
+        ),
+        Some("linux") => Check::warn(
+            "mouse",
+            "the Linux console reports no mouse events",
+            "use a terminal emulator, or the keyboard (? lists the keys)",
+        ),
+        Some(term) if !is_tty => Check::warn(
+            "mouse",
+            format!("TERM={term}, but stdout is not a terminal"),
+            "run og directly in the terminal, not through a pipe",
+        ),
+        Some(term) => Check::ok("mouse", format!("TERM={term}")),
+    };
+    vec![color, mouse]
+}
+
+/// Files a crashed process leaves behind: the query socket nobody answers
+/// on, and half-written `*.json.tmp`, `og_meta.json.*.tmp` and
+/// `og-rollup-*.tmp` files in the runs.
+pub fn stale_files(query_sockets: &[PathBuf], runs_root: &Path) -> Check {
+    const NAME: &str = "stale files";
+    let mut stale: Vec<PathBuf> = query_sockets
+        .iter()
+        .filter(|socket| socket.exists() && UnixStream::connect(socket).is_err())
+        .cloned()
+        .collect();
+    collect_temp_files(runs_root, 3, &mut stale);
+    if stale.is_empty() {
+        return Check::ok(NAME, "none");
+    }
+    let mut listed: Vec<String> = stale
+        .iter()
+        .take(3)
+        .map(|path| path.display().to_string())
+        .collect();
+    if stale.len() > 3 {
+        listed.push(format!("and {} more", stale.len() - 3));
+    }
+    Check::warn(
+        NAME,
+        format!(
+            "{} left by an exited process: {}",
+            stale.len(),
+            listed.join(", ")
+        ),
+        format!(
+            "rm {}",
+            stale
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        ),
+    )
+}
+
+fn collect_temp_files(dir: &Path, depth: usize, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let now = SystemTime::now();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if depth > 0 {
+                collect_temp_files(&path, depth - 1, out);
+            }
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let temp = name.ends_with(".json.tmp")
+            || ((name.starts_with("og-rollup-") || name.starts_with("og_meta.json."))
+                && name.ends_with(".tmp"));
+        let old = entry
+            .metadata()
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age >= STALE_AFTER);
+        if temp && old {
+            out.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Status, daemon, runs_dir, stale_files, terminal};
+    use std::fs;
+    use std::os::unix::net::UnixListener;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("ogtui-doctor-{name}-{nonce}"));
+        fs::create_dir_all(&dir).expect("create dir");
+        dir
+    }
+
+    #[test]
+    fn flags_missing_and_stale_sockets_and_old_temp_files() {
+        let dir = temp_dir("stale");
+        let socket = dir.join("ogd.sock");
+        assert_eq!(daemon(&socket).status, Status::Warn);
+
+        // Binding and dropping the listener leaves the socket file behind.
+        drop(UnixListener::bind(&socket).expect("bind"));
+        let check = daemon(&socket);
+        assert_eq!(check.status, Status::Fail);
+        assert_eq!(
+            check.fix.as_deref(),
+            Some(format!("rm {}", socket.display()).as_str())
+        );
+
+        let runs = dir.join("runs");
+        let run = runs.join("proj").join("run-a");
+        fs::create_dir_all(&run).expect("run dir");
+        let fresh = run.join("og_meta.json.4242.0.tmp");
+        fs::write(&fresh, b"{").expect("fresh tmp");
+        let old = run.join("control.json.tmp");
+        fs::File::create(&old)
+            .and_then(|file| file.set_modified(SystemTime::now() - Duration::from_secs(600)))
+            .expect("old tmp");
+        let abandoned = run.join("og_meta.json.4242.1.tmp");
+        fs::File::create(&abandoned)
+            .and_then(|file| file.set_modified(SystemTime::now() - Duration::from_secs(600)))
+            .expect("abandoned tmp");
+        let check = stale_files(std::slice::from_ref(&socket), &runs);
+        assert_eq!(check.status, Status::Warn);
+        assert!(check.detail.starts_with("3 left by an exited process"));
+        assert!(check.detail.contains("control.json.tmp"));
+        assert!(!check.detail.contains("og_meta.json.4242.0.tmp"));
+
+        fs::remove_file(&socket).expect("rm socket");
+        fs::remove_file(&old).expect("rm tmp");
+        fs::remove_file(&abandoned).expect("rm tmp");
+        assert_eq!(
+            stale_files(std::slice::from_ref(&socket), &runs).status,
+            Status::Ok
+        );
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn runs_dir_and_terminal_checks() {
+        let dir = temp_dir("runs");
+        assert_eq!(runs_dir(&dir).status, Status::Ok);
+        let missing = runs_dir(&dir.join("runs"));
+        assert_eq!(missing.status, Status::Ok);
+        assert!(missing.detail.contains("does not exist yet"));
+        fs::write(dir.join("file"), b"").expect("file");
+        assert_eq!(runs_dir(&dir.join("file")).status, Status::Fail);
+        fs::remove_dir_all(&dir).ok();
+
+        let checks = terminal(Some("xterm-256color"), Some("truecolor"), true);
+        assert!(checks.iter().all(|check| check.status == Status::Ok));
+        let checks = terminal(Some("dumb"), None, true);
+        assert_eq!(checks[0].status, Status::Warn);
+        assert_eq!(checks[1].status, Status::Fail);
+        assert_eq!(terminal(Some("xterm"), None, false)[1].status, Status::Warn);
+    }
+}
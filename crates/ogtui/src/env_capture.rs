@@ -0,0 +1,262 @@
+use anyhow::{Context, Result};
+use og_core::error::OgError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// File name of the environment snapshot stored next to the event files.
+pub const ENV_FILE_NAME: &str = "og_env.json";
+
+/// Copy of the codebase's Cargo.lock saved into the run directory.
+pub const CARGO_LOCK_FILE_NAME: &str = "Cargo.lock";
+
+/// Variables captured verbatim; anything matching `CAPTURED_ENV_PREFIXES` is captured too.
+const CAPTURED_ENV_VARS: &[&str] = &[
+    "CUDA_VISIBLE_DEVICES",
+    "CUDA_HOME",
+    "CUDA_LAUNCH_BLOCKING",
+    "OMP_NUM_THREADS",
+    "MASTER_ADDR",
+    "MASTER_PORT",
+    "WORLD_SIZE",
+    "LOCAL_WORLD_SIZE",
+    "VIRTUAL_ENV",
+    "CONDA_DEFAULT_ENV",
+];
+
+const CAPTURED_ENV_PREFIXES: &[&str] = &["NCCL_", "TORCH_", "PYTORCH_"];
+
+/// Python packages and launch-relevant environment variables at run start.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EnvSnapshot {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub python: Option<String>,
+    /// `pip` or `conda`, whichever produced `packages`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_manager: Option<String>,
+    /// Package name -> version (pip freeze / conda list --export).
+    #[serde(default)]
+    pub packages: BTreeMap<String, String>,
+    #[serde(default)]
+    pub env_vars: BTreeMap<String, String>,
+    /// Cargo.lock copied into the run directory, if the codebase has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cargo_lock: Option<String>,
+}
+
+/// A single value that differs between two runs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValueChange {
+    pub from: String,
+    pub to: String,
+}
+
+/// Key-level difference between two string maps.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MapDiff {
+    pub added: BTreeMap<String, String>,
+    pub removed: BTreeMap<String, String>,
+    pub changed: BTreeMap<String, ValueChange>,
+}
+
+impl MapDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Render as `+ key=value` / `- key=value` / `~ key: a -> b` lines.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (key, value) in &self.added {
+            lines.push(format!("+ {}={}", key, value));
+        }
+        for (key, value) in &self.removed {
+            lines.push(format!("- {}={}", key, value));
+        }
+        for (key, change) in &self.changed {
+            lines.push(format!("~ {}: {} -> {}", key, change.from, change.to));
+        }
+        lines
+    }
+}
+
+pub fn diff_maps(from: &BTreeMap<String, String>, to: &BTreeMap<String, String>) -> MapDiff {
+    let mut diff = MapDiff::default();
+    for (key, old) in from {
+        match to.get(key) {
+            None => {
+                diff.removed.insert(key.clone(), old.clone());
+            }
+            Some(new) if new != old => {
+                diff.changed.insert(
+                    key.clone(),
+                    ValueChange {
+                        from: old.clone(),
+                        to: new.clone(),
+                    },
+                );
+            }
+            Some(_) => {}
+        }
+    }
+    for (key, new) in to {
+        if !from.contains_key(key) {
+            diff.added.insert(key.clone(), new.clone());
+        }
+    }
+    diff
+}
+
+fn is_captured_env_var(name: &str) -> bool {
+    CAPTURED_ENV_VARS.contains(&name)
+        || CAPTURED_ENV_PREFIXES
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+}
+
+/// Launch-relevant variables from the current process environment.
+pub fn captured_env_vars() -> BTreeMap<String, String> {
+    std::env::vars()
+        .filter(|(name, _)| is_captured_env_var(name))
+        .collect()
+}
+
+fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parse `pip freeze` (`name==version`, `name @ url`) or `conda list --export`
+/// (`name=version=build`) output into name -> version.
+pub fn parse_package_list(raw: &str) -> BTreeMap<String, String> {
+    let mut packages = BTreeMap::new();
+    for line in raw.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('-') {
+            continue;
+        }
+        let (name, version) = if let Some((name, version)) = line.split_once("==") {
+            (name, version)
+        } else if let Some((name, url)) = line.split_once(" @ ") {
+            (name, url)
+        } else if let Some((name, rest)) = line.split_once('=') {
+            (name, rest)
+        } else {
+            (line, "")
+        };
+        packages.insert(name.trim().to_string(), version.trim().to_string());
+    }
+    packages
+}
+
+/// Snapshot the Python environment (conda when active, else pip) and env vars.
+pub fn capture(python: &str, codebase_root: &Path) -> EnvSnapshot {
+    let python_version =
+        command_stdout(python, &["--version"]).map(|version| version.trim().to_string());
+
+    let conda = std::env::var_os("CONDA_PREFIX")
+        .and_then(|_| command_stdout("conda", &["list", "--export"]));
+    let (package_manager, packages) = match conda {
+        Some(raw) => (Some("conda".to_string()), parse_package_list(&raw)),
+        None => match command_stdout(python, &["-m", "pip", "freeze"]) {
+            Some(raw) => (Some("pip".to_string()), parse_package_list(&raw)),
+            None => (None, BTreeMap::new()),
+        },
+    };
+
+    EnvSnapshot {
+        python: python_version,
+        package_manager,
+        packages,
+        env_vars: captured_env_vars(),
+        cargo_lock: codebase_root
+            .join(CARGO_LOCK_FILE_NAME)
+            .is_file()
+            .then(|| CARGO_LOCK_FILE_NAME.to_string()),
+    }
+}
+
+/// Write the snapshot (and a copy of Cargo.lock, if recorded) into `run_dir`.
+pub fn save(run_dir: &Path, codebase_root: &Path, snapshot: &EnvSnapshot) -> Result<()> {
+    fs::create_dir_all(run_dir)
+        .with_context(|| format!("creating run directory {}", run_dir.display()))?;
+    if let Some(lock) = snapshot.cargo_lock.as_deref() {
+        let target = run_dir.join(lock);
+        fs::copy(codebase_root.join(CARGO_LOCK_FILE_NAME), &target)
+            .with_context(|| format!("writing {}", target.display()))?;
+    }
+    let path = run_dir.join(ENV_FILE_NAME);
+    fs::write(&path, serde_json::to_string_pretty(snapshot)?)
+        .with_context(|| format!("writing {}", path.display()))
+}
+
+/// Load the environment snapshot for a run, if one was captured.
+pub fn load(run_dir: &Path) -> Result<Option<EnvSnapshot>> {
+    let path = crate::run_meta::run_dir_for(run_dir).join(ENV_FILE_NAME);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let raw = fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+    serde_json::from_str(&raw)
+        .map(Some)
+        .with_context(|| OgError::parse_failed(path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff_maps, is_captured_env_var, parse_package_list};
+    use std::collections::BTreeMap;
+
+    fn map(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_package_list_handles_pip_and_conda_formats() {
+        let raw =
+            "# comment\ntorch==2.3.0\nnumpy==1.26.4\nmylib @ file:///src/mylib\n-e git+https://x\n";
+        let pip = parse_package_list(raw);
+        assert_eq!(pip["torch"], "2.3.0");
+        assert_eq!(pip["mylib"], "file:///src/mylib");
+        assert_eq!(pip.len(), 3);
+
+        let conda = parse_package_list("# platform: linux-64\npython=3.11.8=hab00c5b_0\n");
+        assert_eq!(conda["python"], "3.11.8=hab00c5b_0");
+    }
+
+    #[test]
+    fn env_var_filter_keeps_gpu_and_nccl_settings() {
+        assert!(is_captured_env_var("CUDA_VISIBLE_DEVICES"));
+        assert!(is_captured_env_var("NCCL_DEBUG"));
+        assert!(!is_captured_env_var("OPENAI_API_KEY"));
+        assert!(!is_captured_env_var("HOME"));
+    }
+
+    #[test]
+    fn diff_maps_reports_added_removed_and_changed() {
+        let from = map(&[("torch", "2.2.0"), ("numpy", "1.26.4"), ("tqdm", "4.66")]);
+        let to = map(&[("torch", "2.3.0"), ("numpy", "1.26.4"), ("wandb", "0.17")]);
+        let diff = diff_maps(&from, &to);
+
+        assert_eq!(diff.added, map(&[("wandb", "0.17")]));
+        assert_eq!(diff.removed, map(&[("tqdm", "4.66")]));
+        assert_eq!(diff.changed["torch"].to, "2.3.0");
+        assert_eq!(
+            diff.lines(),
+            vec!["+ wandb=0.17", "- tqdm=4.66", "~ torch: 2.2.0 -> 2.3.0"]
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! Human-friendly numbers, counts, sizes and durations for the TUI and for
+//! CLI text output. When metric values switch to scientific notation, and how
+//! many decimals they keep, come from the `[format]` table of the user config
+//! (`presets::config_path`):
+//!
+//! ```toml
+//! [format]
+//! sci_below = 0.001   # |v| under this (and not 0) prints as 5.00e-4
+//! sci_from = 10000    # |v| from this up prints as 1.23e4
+//! decimals = 4
+//! sci_decimals = 2
+//! ```
+
+use anyhow::{Context, Result, bail};
+use og_core::error::OgError;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::presets::{self, strip_comment};
+
+/// How metric values are printed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumberFormat {
+    /// Non-zero magnitudes below this use scientific notation.
+    pub sci_below: f64,
+    /// Magnitudes from this up use scientific notation.
+    pub sci_from: f64,
+    /// Decimals in fixed notation.
+    pub decimals: usize,
+    /// Decimals of the mantissa in scientific notation.
+    pub sci_decimals: usize,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            sci_below: 0.001,
+            sci_from: 10_000.0,
+            decimals: 4,
+            sci_decimals: 2,
+        }
+    }
+}
+
+impl NumberFormat {
+    /// `12.3457`, `5.00e-4`, `1.23e4`.
+    pub fn value(&self, v: f64) -> String {
+        if (v.abs() < self.sci_below && v != 0.0) || v.abs() >= self.sci_from {
+            format!("{:.*e}", self.sci_decimals, v)
+        } else {
+            format!("{:.*}", self.decimals, v)
+        }
+    }
+}
+
+static SETTINGS: OnceLock<NumberFormat> = OnceLock::new();
+
+/// Use `settings` for the rest of the process. Only the first call counts.
+pub fn init(settings: NumberFormat) {
+    let _ = SETTINGS.set(settings);
+}
+
+fn settings() -> &'static NumberFormat {
+    SETTINGS.get_or_init(NumberFormat::default)
+}
+
+/// A metric value in the configured notation.
+pub fn value(v: f64) -> String {
+    settings().value(v)
+}
+
+/// Steps, tokens and other counts: exact below 10 000, then `12.3k`, `1.2M`, `3.4B`.
+pub fn count(n: f64) -> String {
+    const UNITS: [(f64, &str); 4] = [(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "k")];
+    if !n.is_finite() || n.abs() < 10_000.0 {
+        return format!("{n:.0}");
+    }
+    let (scale, suffix) = UNITS
+        .into_iter()
+        .find(|(scale, _)| n.abs() >= *scale)
+        .unwrap_or((1e3, "k"));
+    format!("{:.1}{suffix}", n / scale)
+}
+
+/// Epochs to two decimals without trailing zeros: `3`, `2.5`, `0.38`.
+pub fn epoch(e: f64) -> String {
+    let text = format!("{e:.2}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" { "0" } else { text }.to_string()
+}
+
+/// `512 B`, `1.5 KiB`, `2.0 GiB`.
+pub fn bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+/// `45s`, `3m 20s`, `2h 13m`, `1d 4h`: the two largest units, the smaller
+/// one left out when it is zero.
+pub fn duration(secs: f64) -> String {
+    let secs = if secs.is_finite() {
+        secs.max(0.0).round() as u64
+    } else {
+        0
+    };
+    let (big, big_unit, small, small_unit) = if secs < 60 {
+        return format!("{secs}s");
+    } else if secs < 3600 {
+        (secs / 60, "m", secs % 60, "s")
+    } else if secs < 86_400 {
+        (secs / 3600, "h", secs % 3600 / 60, "m")
+    } else {
+        (secs / 86_400, "d", secs % 86_400 / 3600, "h")
+    };
+    if small == 0 {
+        format!("{big}{big_unit}")
+    } else {
+        format!("{big}{big_unit} {small}{small_unit}")
+    }
+}
+
+/// `59s ago`, `3m ago`, `2h ago`, `4d ago`.
+pub fn ago(delta_secs: u64) -> String {
+    if delta_secs < 60 {
+        format!("{delta_secs}s ago")
+    } else if delta_secs < 3600 {
+        format!("{}m ago", delta_secs / 60)
+    } else if delta_secs < 86_400 {
+        format!("{}h ago", delta_secs / 3600)
+    } else {
+        format!("{}d ago", delta_secs / 86_400)
+    }
+}
+
+/// Settings from the config file; no file, or no `[format]` table, means
+/// the defaults.
+pub fn load() -> Result<NumberFormat> {
+    match presets::config_path() {
+        Some(path) if path.exists() => load_file(&path),
+        _ => Ok(NumberFormat::default()),
+    }
+}
+
+pub fn load_file(path: &Path) -> Result<NumberFormat> {
+    let raw = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    parse(&raw).with_context(|| OgError::parse_failed(path.display()))
+}
+
+/// Read the `[format]` table; other tables are left for other settings.
+pub fn parse(text: &str) -> Result<NumberFormat> {
+    let mut settings = NumberFormat::default();
+    let mut table = String::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = strip_comment(line).trim();
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            table = name.trim().to_string();
+            continue;
+        }
+        if table != "format" || line.is_empty() {
+            continue;
+        }
+        let Some((key, raw)) = line.split_once('=') else {
+            bail!("line {}: expected key = value", idx + 1);
+        };
+        let key = key.trim().trim_matches('"');
+        let number: f64 = raw
+            .trim()
+            .replace('_', "")
+            .parse()
+            .ok()
+            .filter(|n: &f64| n.is_finite() && *n >= 0.0)
+            .with_context(|| {
+                format!(
+                    "line {}: format.{key} must be a non-negative number",
+                    idx + 1
+                )
+            })?;
+        let decimals = || -> Result<usize> {
+            if number.fract() != 0.0 || number > 17.0 {
+                bail!(
+                    "line {}: format.{key} must be a whole number up to 17",
+                    idx + 1
+                );
+            }
+            Ok(number as usize)
+        };
+        match key {
+            "sci_below" => settings.sci_below = number,
+            "sci_from" => settings.sci_from = number,
+            "decimals" => settings.decimals = decimals()?,
+            "sci_decimals" => settings.sci_decimals = decimals()?,
+            other => bail!(
+                "line {}: unknown key format.{other} (expected sci_below, sci_from, decimals or sci_decimals)",
+                idx + 1
+            ),
+        }
+    }
+    if settings.sci_below > settings.sci_from {
+        bail!("format.sci_below must not exceed format.sci_from");
+    }
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NumberFormat, ago, bytes, count, duration, epoch, parse};
+
+    #[test]
+    fn value_switches_between_fixed_and_scientific_notation() {
+        let format = NumberFormat::default();
+        assert_eq!(format.value(12.34567), "12.3457");
+        assert_eq!(format.value(0.0), "0.0000");
+        assert_eq!(format.value(0.0005), "5.00e-4");
+        assert_eq!(format.value(12_345.0), "1.23e4");
+
+        let wide = parse(
+            "[preset]\nlosses = [\"loss\"]\n[format]\nsci_from = 1_000_000 # wide\ndecimals = 2\n",
+        )
+        .expect("parse");
+        assert_eq!(wide.value(12_345.0), "12345.00");
+        assert_eq!(wide.value(0.0005), "5.00e-4");
+        assert!(parse("[format]\nprecision = 3\n").is_err());
+        assert!(parse("[format]\ndecimals = 2.5\n").is_err());
+        assert!(parse("[format]\nsci_below = 10\nsci_from = 1\n").is_err());
+    }
+
+    #[test]
+    fn counts_sizes_and_durations_read_at_a_glance() {
+        assert_eq!(count(9_999.0), "9999");
+        assert_eq!(count(12_345.0), "12.3k");
+        assert_eq!(count(1_234_567.0), "1.2M");
+        assert_eq!(count(3.4e9), "3.4B");
+        assert_eq!(epoch(3.0), "3");
+        assert_eq!(epoch(2.5), "2.5");
+        assert_eq!(epoch(0.375), "0.38");
+        assert_eq!(bytes(512), "512 B");
+        assert_eq!(bytes(1536), "1.5 KiB");
+        assert_eq!(bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+        assert_eq!(duration(45.0), "45s");
+        assert_eq!(duration(200.0), "3m 20s");
+        assert_eq!(duration(7_980.0), "2h 13m");
+        assert_eq!(duration(7_200.0), "2h");
+        assert_eq!(duration(100_800.0), "1d 4h");
+        assert_eq!(ago(59), "59s ago");
+        assert_eq!(ago(60), "1m ago");
+        assert_eq!(ago(3_599), "59m ago");
+        assert_eq!(ago(86_399), "23h ago");
+        assert_eq!(ago(86_400), "1d ago");
+    }
+}
@@ -0,0 +1,218 @@
+//! Disk accounting and retention for `og gc`.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use crate::checkpoints::Checkpoint;
+use crate::disk_usage::path_size;
+
+/// Extensions counted as model checkpoints even when no registry lists them.
+const CHECKPOINT_EXTENSIONS: &[&str] = &["pt", "pth", "ckpt", "safetensors"];
+/// Extensions counted as captured training output.
+const CONSOLE_LOG_EXTENSIONS: &[&str] = &["log", "out", "err"];
+
+/// Bytes a run takes on disk, split by what the files are.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RunUsage {
+    pub events: u64,
+    pub checkpoints: u64,
+    pub console_logs: u64,
+    /// Everything else: metadata sidecars, patches, plots, exported files.
+    pub artifacts: u64,
+}
+
+impl RunUsage {
+    pub fn total(&self) -> u64 {
+        self.events + self.checkpoints + self.console_logs + self.artifacts
+    }
+
+    pub fn add(&mut self, other: &RunUsage) {
+        self.events += other.events;
+        self.checkpoints += other.checkpoints;
+        self.console_logs += other.console_logs;
+        self.artifacts += other.artifacts;
+    }
+}
+
+/// Usage of one run. Checkpoints in `registered` count in full wherever they
+/// live, since pruning or deleting the run removes them too.
+pub fn run_usage(run_dir: &Path, registered: &[Checkpoint]) -> Result<RunUsage> {
+    let registered: HashSet<&Path> = registered.iter().map(|c| c.path.as_path()).collect();
+    let mut usage = RunUsage::default();
+    walk(run_dir, &registered, &mut usage)?;
+    for path in registered {
+        if !path.starts_with(run_dir) {
+            usage.checkpoints += path_size(path);
+        }
+    }
+    Ok(usage)
+}
+
+fn walk(dir: &Path, registered: &HashSet<&Path>, usage: &mut RunUsage) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))? {
+        let path = entry?.path();
+        if registered.contains(path.as_path()) {
+            usage.checkpoints += path_size(&path);
+            continue;
+        }
+        let Ok(meta) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if meta.is_dir() {
+            walk(&path, registered, usage)?;
+            continue;
+        }
+        let bytes = meta.len();
+        if crate::readers::is_run_file(&path) {
+            usage.events += bytes;
+            continue;
+        }
+        if is_checkpoint_file(&path) {
+            usage.checkpoints += bytes;
+        } else if CONSOLE_LOG_EXTENSIONS.contains(&extension(&path).as_str()) {
+            usage.console_logs += bytes;
+        } else {
+            usage.artifacts += bytes;
+        }
+    }
+    Ok(())
+}
+
+/// Whether `path` looks like a model checkpoint by its extension, whether
+/// or not a registry lists it.
+pub fn is_checkpoint_file(path: &Path) -> bool {
+    CHECKPOINT_EXTENSIONS.contains(&extension(path).as_str())
+}
+
+/// Lower-cased extension, looking past a `.gz` / `.zst` suffix.
+fn extension(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    let name = crate::compression::strip_extension(&name);
+    name.rsplit_once('.')
+        .map(|(_, ext)| ext.to_string())
+        .unwrap_or_default()
+}
+
+/// Split checkpoints (ordered by step) into the newest `keep` and the rest.
+pub fn split_checkpoints(
+    checkpoints: &[Checkpoint],
+    keep: usize,
+) -> (&[Checkpoint], &[Checkpoint]) {
+    let cut = checkpoints.len().saturating_sub(keep);
+    let (pruned, kept) = checkpoints.split_at(cut);
+    (kept, pruned)
+}
+
+/// Newest modification time of the run's event files. Sidecars such as
+/// `og_meta.json` are left out so tagging or annotating a run does not make
+/// it look recent.
+pub fn last_event_write_unix(run_dir: &Path) -> Result<Option<u64>> {
+    Ok(crate::tfevents::discover_event_files(run_dir)?
+        .iter()
+        .filter_map(|file| fs::metadata(file).ok()?.modified().ok())
+        .filter_map(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|age| age.as_secs())
+        .max())
+}
+
+/// Whether a run last written at `last_updated_unix` is past the retention age.
+/// Runs with no files at all are never expired.
+pub fn expired(last_updated_unix: Option<u64>, now_unix: u64, older_than_days: u64) -> bool {
+    last_updated_unix
+        .is_some_and(|last| now_unix.saturating_sub(last) > older_than_days.saturating_mul(86_400))
+}
+
+/// Whether `path` resolves to somewhere below `run_dir`, following symlinks
+/// and `..`. Paths that do not resolve count as outside.
+pub fn inside(run_dir: &Path, path: &Path) -> bool {
+    match (fs::canonicalize(run_dir), fs::canonicalize(path)) {
+        (Ok(dir), Ok(path)) => path != dir && path.starts_with(dir),
+        _ => false,
+    }
+}
+
+/// Delete a checkpoint file or directory; one already gone is not an error.
+pub fn remove_checkpoint(path: &Path) -> Result<()> {
+    let result = match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(_) => return Ok(()),
+    };
+    result.with_context(|| format!("removing {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RunUsage, expired, inside, run_usage, split_checkpoints};
+    use crate::checkpoints::Checkpoint;
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn checkpoint(step: i64, path: PathBuf) -> Checkpoint {
+        Checkpoint {
+            step,
+            path,
+            wall_time: None,
+            metrics: BTreeMap::new(),
+            size_bytes: None,
+        }
+    }
+
+    #[test]
+    fn run_usage_splits_files_by_kind() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let run_dir = std::env::temp_dir().join(format!("ogtui-gc-{nonce}"));
+        fs::create_dir_all(run_dir.join("ckpt/step_10")).expect("create run dir");
+        fs::write(run_dir.join("events.out.tfevents.1.host"), [0u8; 100]).expect("events");
+        fs::write(run_dir.join("ckpt/step_10/weights"), [0u8; 40]).expect("registered");
+        fs::write(run_dir.join("final.safetensors"), [0u8; 30]).expect("unregistered");
+        fs::write(run_dir.join("train.log"), [0u8; 20]).expect("log");
+        fs::write(run_dir.join("og_meta.json"), [0u8; 5]).expect("meta");
+
+        let registered = vec![checkpoint(10, run_dir.join("ckpt/step_10"))];
+        let usage = run_usage(&run_dir, &registered).expect("usage");
+        assert_eq!(
+            usage,
+            RunUsage {
+                events: 100,
+                checkpoints: 70,
+                console_logs: 20,
+                artifacts: 5,
+            }
+        );
+        assert_eq!(usage.total(), 195);
+        assert!(inside(&run_dir, &run_dir.join("ckpt/step_10")));
+        assert!(!inside(&run_dir, &run_dir.join("ckpt/../..")));
+        assert!(!inside(&run_dir, &run_dir), "the run dir itself");
+        fs::remove_dir_all(&run_dir).expect("cleanup");
+    }
+
+    #[test]
+    fn retention_keeps_the_newest_checkpoints_and_recent_runs() {
+        let checkpoints: Vec<Checkpoint> = (1..=4)
+            .map(|step| checkpoint(step * 10, PathBuf::from(format!("step_{step}.pt"))))
+            .collect();
+        let (kept, pruned) = split_checkpoints(&checkpoints, 1);
+        assert_eq!(kept.iter().map(|c| c.step).collect::<Vec<_>>(), vec![40]);
+        assert_eq!(pruned.len(), 3);
+        let (kept, pruned) = split_checkpoints(&checkpoints, 10);
+        assert_eq!((kept.len(), pruned.len()), (4, 0));
+
+        let now = 100 * 86_400;
+        assert!(expired(Some(now - 31 * 86_400), now, 30));
+        assert!(!expired(Some(now - 29 * 86_400), now, 30));
+        assert!(!expired(None, now, 30));
+    }
+}
@@ -0,0 +1,119 @@
+//! Gradient-norm watchdog: built-in checks on `grad_norm`-like metrics for
+//! explosion (above a limit, or NaN/inf) and vanishing (near zero for a run of
+//! logged points). The agent daemon alerts on the same limits, which the TUI
+//! passes it through the environment.
+
+/// Default `--grad-explode`.
+pub const DEFAULT_EXPLODE: f64 = 100.0;
+/// Default `--grad-vanish`.
+pub const DEFAULT_VANISH: f64 = 1e-7;
+/// Default `--grad-vanish-points`.
+pub const DEFAULT_VANISH_POINTS: usize = 50;
+
+pub const EXPLODE_ENV_VAR: &str = "OG_GRAD_EXPLODE";
+pub const VANISH_ENV_VAR: &str = "OG_GRAD_VANISH";
+pub const VANISH_POINTS_ENV_VAR: &str = "OG_GRAD_VANISH_POINTS";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    /// A norm above this is exploding.
+    pub explode: f64,
+    /// A norm below this for `vanish_points` points in a row is vanishing.
+    pub vanish: f64,
+    pub vanish_points: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            explode: DEFAULT_EXPLODE,
+            vanish: DEFAULT_VANISH,
+            vanish_points: DEFAULT_VANISH_POINTS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GradState {
+    Ok,
+    Vanishing,
+    Exploding,
+}
+
+impl GradState {
+    pub fn label(self) -> &'static str {
+        match self {
+            GradState::Ok => "ok",
+            GradState::Vanishing => "vanishing",
+            GradState::Exploding => "exploding",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradHealth {
+    pub tag: String,
+    pub state: GradState,
+    pub last: f64,
+}
+
+/// `grad_norm`, `gradnorm`, `grad-norm` or `grad/norm` anywhere in the tag.
+pub fn is_grad_norm_tag(tag: &str) -> bool {
+    let tag = tag.to_ascii_lowercase();
+    ["grad_norm", "gradnorm", "grad-norm", "grad/norm"]
+        .iter()
+        .any(|key| tag.contains(key))
+}
+
+/// State of one gradient-norm series; None when it has no points.
+pub fn check(tag: &str, series: &[(f64, f64)], limits: Limits) -> Option<GradHealth> {
+    let (_, last) = *series.last()?;
+    let tail = &series[series.len().saturating_sub(limits.vanish_points)..];
+    let state = if !last.is_finite() || last > limits.explode {
+        GradState::Exploding
+    } else if limits.vanish_points > 0
+        && tail.len() >= limits.vanish_points
+        && tail.iter().all(|(_, v)| v.abs() < limits.vanish)
+    {
+        GradState::Vanishing
+    } else {
+        GradState::Ok
+    };
+    Some(GradHealth {
+        tag: tag.to_string(),
+        state,
+        last,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GradState, Limits, check, is_grad_norm_tag};
+
+    #[test]
+    fn flags_exploding_and_vanishing_norms() {
+        assert!(is_grad_norm_tag("train/grad_norm"));
+        assert!(is_grad_norm_tag("model/GradNorm/layer0"));
+        assert!(!is_grad_norm_tag("train/loss"));
+
+        let limits = Limits {
+            explode: 10.0,
+            vanish: 1e-6,
+            vanish_points: 3,
+        };
+        let state = |values: &[f64]| {
+            let series: Vec<(f64, f64)> = values
+                .iter()
+                .enumerate()
+                .map(|(step, value)| (step as f64, *value))
+                .collect();
+            check("grad_norm", &series, limits).map(|health| health.state)
+        };
+        assert_eq!(state(&[]), None);
+        assert_eq!(state(&[1.0, 2.0]), Some(GradState::Ok));
+        assert_eq!(state(&[1.0, 20.0]), Some(GradState::Exploding));
+        assert_eq!(state(&[1.0, f64::NAN]), Some(GradState::Exploding));
+        assert_eq!(state(&[1e-9, 1e-9]), Some(GradState::Ok));
+        assert_eq!(state(&[1.0, 1e-9, 1e-9, 1e-9]), Some(GradState::Vanishing));
+    }
+}
@@ -0,0 +1,96 @@
+use anyhow::{Result, bail};
+use og_core::error::suggest;
+use std::fmt;
+
+/// Whether `tag` is picked by a `--graph` selector, ignoring case. Selectors
+/// are literal text, never regexes: `(`, `+` or `.` match themselves. A `*`
+/// makes the selector a glob over the whole tag (`sys/*`); anything else
+/// matches any part of the tag.
+pub fn selector_matches(tag: &str, selector: &str) -> bool {
+    let tag = tag.to_ascii_lowercase();
+    let selector = selector.to_ascii_lowercase();
+    if selector.contains('*') {
+        crate::readers::glob_match(&selector, &tag)
+    } else {
+        tag.contains(&selector)
+    }
+}
+
+pub fn validate_selector(key: &str, selector: &str) -> Result<()> {
+    if selector.trim().is_empty() {
+        bail!("'{key}' selectors must be non-empty");
+    }
+    if selector != selector.trim() {
+        bail!("'{key}' selector '{selector}' has leading or trailing whitespace");
+    }
+    Ok(())
+}
+
+/// A selector no metric matched, with the closest tag names.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unmatched {
+    pub selector: String,
+    pub suggestions: Vec<String>,
+}
+
+impl fmt::Display for Unmatched {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0 metrics matched '{}'", self.selector)?;
+        if !self.suggestions.is_empty() {
+            let quoted: Vec<String> = self
+                .suggestions
+                .iter()
+                .map(|tag| format!("'{tag}'"))
+                .collect();
+            write!(f, "; did you mean {}?", quoted.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Selectors that match none of `tags`, in the order given.
+pub fn unmatched<'a>(
+    selectors: impl IntoIterator<Item = &'a str>,
+    tags: &[&str],
+) -> Vec<Unmatched> {
+    selectors
+        .into_iter()
+        .filter(|selector| !tags.iter().any(|tag| selector_matches(tag, selector)))
+        .map(|selector| Unmatched {
+            selector: selector.to_string(),
+            suggestions: suggest(selector, tags),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{selector_matches, unmatched, validate_selector};
+
+    #[test]
+    fn selectors_are_literal_text_or_globs() {
+        assert!(selector_matches("train/Loss", "loss"));
+        assert!(selector_matches("eval/acc(top1)", "acc(top1)"));
+        assert!(!selector_matches("eval/accxtop1", "acc.top1"));
+        assert!(selector_matches("sys/gpu_util", "SYS/*"));
+        assert!(!selector_matches("train/sys/x", "sys/*"));
+        assert!(validate_selector("metrics", " ").is_err());
+        assert!(validate_selector("metrics", "loss ").is_err());
+    }
+
+    #[test]
+    fn unmatched_selectors_suggest_close_tags() {
+        let tags = ["train/loss", "val/loss", "train/accuracy", "sys/gpu_util"];
+        let missing = unmatched(["loss", "accuarcy", "lsos", "gpu/*", "zzz"], &tags);
+        let messages: Vec<String> = missing.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "0 metrics matched 'accuarcy'; did you mean 'train/accuracy'?",
+                "0 metrics matched 'lsos'; did you mean 'train/loss', 'val/loss'?",
+                "0 metrics matched 'gpu/*'; did you mean 'sys/gpu_util'?",
+                "0 metrics matched 'zzz'",
+            ]
+        );
+    }
+}
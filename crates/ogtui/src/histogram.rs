@@ -0,0 +1,303 @@
+//! Histogram summaries for the distributions tab, read from `.tfevents`
+//! files: TF1 `HistogramProto` values and TF2 histogram-plugin tensors
+//! (`k × 3` rows of left edge, right edge and count).
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::tfevents;
+use crate::tfrecord::{self, HistogramProto};
+
+/// TensorBoard plugin whose `k × 3` tensors are histograms.
+const HISTOGRAM_PLUGIN: &str = "histograms";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bucket {
+    pub left: f64,
+    pub right: f64,
+    pub count: f64,
+}
+
+/// One tag's distribution at one step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Histogram {
+    pub step: i64,
+    pub buckets: Vec<Bucket>,
+}
+
+impl Histogram {
+    /// None unless some bucket has finite edges and a positive count.
+    fn new(step: i64, buckets: Vec<Bucket>) -> Option<Self> {
+        let buckets: Vec<Bucket> = buckets
+            .into_iter()
+            .filter(|b| b.left.is_finite() && b.right.is_finite() && b.left <= b.right)
+            .filter(|b| b.count.is_finite() && b.count >= 0.0)
+            .collect();
+        buckets
+            .iter()
+            .any(|b| b.count > 0.0)
+            .then_some(Self { step, buckets })
+    }
+
+    /// TF1 buckets run from the previous limit to their own. The outermost
+    /// limits are often ±DBL_MAX, so edges are clamped to the recorded
+    /// min and max.
+    fn from_proto(step: i64, proto: &HistogramProto) -> Option<Self> {
+        if proto.bucket.len() != proto.bucket_limit.len() {
+            return None;
+        }
+        let clamp = |edge: f64| edge.max(proto.min).min(proto.max);
+        let buckets = proto
+            .bucket_limit
+            .iter()
+            .zip(&proto.bucket)
+            .enumerate()
+            .map(|(idx, (limit, count))| Bucket {
+                left: clamp(
+                    idx.checked_sub(1)
+                        .map_or(proto.min, |prev| proto.bucket_limit[prev]),
+                ),
+                right: clamp(*limit),
+                count: *count,
+            })
+            .collect();
+        Self::new(step, buckets)
+    }
+
+    fn from_rows(step: i64, rows: Vec<Vec<f64>>) -> Option<Self> {
+        let buckets = rows
+            .into_iter()
+            .map(|row| match row[..] {
+                [left, right, count] => Some(Bucket { left, right, count }),
+                _ => None,
+            })
+            .collect::<Option<_>>()?;
+        Self::new(step, buckets)
+    }
+
+    pub fn count(&self) -> f64 {
+        self.buckets.iter().map(|b| b.count).sum()
+    }
+
+    /// Lowest left edge and highest right edge of the non-empty buckets.
+    pub fn range(&self) -> Option<(f64, f64)> {
+        self.buckets
+            .iter()
+            .filter(|b| b.count > 0.0)
+            .fold(None, |range, b| match range {
+                None => Some((b.left, b.right)),
+                Some((lo, hi)) => Some((lo.min(b.left), hi.max(b.right))),
+            })
+    }
+
+    /// Mean taking each bucket's values to sit at its middle.
+    pub fn mean(&self) -> Option<f64> {
+        let count = self.count();
+        (count > 0.0).then(|| {
+            self.buckets
+                .iter()
+                .map(|b| (b.left + b.right) / 2.0 * b.count)
+                .sum::<f64>()
+                / count
+        })
+    }
+
+    /// Counts in `bins` equal-width bins over `lo..hi`, spreading each
+    /// bucket over the bins it overlaps so steps with different bucket edges
+    /// line up.
+    pub fn rebin(&self, (lo, hi): (f64, f64), bins: usize) -> Vec<f64> {
+        let mut out = vec![0.0; bins];
+        if bins == 0 || hi <= lo {
+            return out;
+        }
+        let width = (hi - lo) / bins as f64;
+        let bin_of = |x: f64| (((x - lo) / width).floor().max(0.0) as usize).min(bins - 1);
+        for bucket in self.buckets.iter().filter(|b| b.count > 0.0) {
+            let (first, last) = (bin_of(bucket.left), bin_of(bucket.right));
+            let span = bucket.right - bucket.left;
+            if first == last || span <= 0.0 {
+                out[bin_of((bucket.left + bucket.right) / 2.0)] += bucket.count;
+                continue;
+            }
+            for (bin, slot) in out.iter_mut().enumerate().take(last + 1).skip(first) {
+                let bin_lo = lo + bin as f64 * width;
+                let overlap = bucket.right.min(bin_lo + width) - bucket.left.max(bin_lo);
+                *slot += bucket.count * overlap.max(0.0) / span;
+            }
+        }
+        out
+    }
+}
+
+/// Range covering every histogram of a tag, so its steps share one axis.
+pub fn range(histograms: &[Histogram]) -> Option<(f64, f64)> {
+    histograms
+        .iter()
+        .filter_map(Histogram::range)
+        .reduce(|(lo, hi), (l, h)| (lo.min(l), hi.max(h)))
+}
+
+/// Histograms in event file bytes by tag, in file order.
+pub fn parse_tfevents(bytes: &[u8]) -> BTreeMap<String, Vec<Histogram>> {
+    let mut by_tag: BTreeMap<String, Vec<Histogram>> = BTreeMap::new();
+    let Ok((records, _)) = tfrecord::decode_records(bytes, true) else {
+        return by_tag;
+    };
+    let mut plugins: HashMap<String, String> = HashMap::new();
+    for (_, event) in records {
+        for value in event.summary.into_iter().flat_map(|summary| summary.value) {
+            if let Some(plugin) = value.metadata.and_then(|meta| meta.plugin_data) {
+                plugins.insert(value.tag.clone(), plugin.plugin_name);
+            }
+            let histogram = match (&value.histo, &value.tensor) {
+                (Some(proto), _) => Histogram::from_proto(event.step, proto),
+                (None, Some(tensor))
+                    if plugins.get(&value.tag).map(String::as_str) == Some(HISTOGRAM_PLUGIN) =>
+                {
+                    tensor
+                        .matrix()
+                        .and_then(|rows| Histogram::from_rows(event.step, rows))
+                }
+                _ => None,
+            };
+            if let Some(histogram) = histogram {
+                by_tag.entry(value.tag).or_default().push(histogram);
+            }
+        }
+    }
+    by_tag
+}
+
+/// Every histogram logged under `run_path` by tag, one per step (the last
+/// written wins), ordered by step. Unreadable files are skipped; `load_run`
+/// already warns about them.
+pub fn load(run_path: &Path) -> Result<BTreeMap<String, Vec<Histogram>>> {
+    let mut by_tag: BTreeMap<String, Vec<Histogram>> = BTreeMap::new();
+    for path in tfevents::discover_event_files(run_path)? {
+        if tfevents::is_tfevents_file(&path)
+            && let Ok(bytes) = crate::compression::read(&path)
+        {
+            for (tag, histograms) in parse_tfevents(&bytes) {
+                by_tag.entry(tag).or_default().extend(histograms);
+            }
+        }
+    }
+    for histograms in by_tag.values_mut() {
+        histograms.reverse();
+        histograms.sort_by_key(|histogram| histogram.step);
+        histograms.dedup_by_key(|histogram| histogram.step);
+    }
+    Ok(by_tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Histogram, parse_tfevents, range};
+    use crate::tfrecord::{
+        Event, HistogramProto, PluginData, Summary, SummaryMetadata, SummaryValue, TensorProto,
+        TensorShape, TensorShapeDim, encode_record,
+    };
+    use prost::Message;
+
+    fn event(step: i64, value: SummaryValue) -> Vec<u8> {
+        encode_record(
+            &Event {
+                wall_time: 0.0,
+                step,
+                file_version: None,
+                summary: Some(Summary { value: vec![value] }),
+            }
+            .encode_to_vec(),
+        )
+    }
+
+    fn value(tag: &str) -> SummaryValue {
+        SummaryValue {
+            tag: tag.to_string(),
+            simple_value: None,
+            histo: None,
+            audio: None,
+            tensor: None,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn reads_tf1_protos_and_tf2_plugin_tensors() {
+        let mut bytes = event(
+            10,
+            SummaryValue {
+                histo: Some(HistogramProto {
+                    min: -1.0,
+                    max: 2.0,
+                    num: 6.0,
+                    sum: 3.0,
+                    sum_squares: 7.0,
+                    bucket_limit: vec![0.0, 1.0, f64::MAX],
+                    bucket: vec![1.0, 3.0, 2.0],
+                }),
+                ..value("weights/w")
+            },
+        );
+        let tensor = TensorProto {
+            dtype: crate::tfrecord::DT_DOUBLE,
+            tensor_shape: Some(TensorShape {
+                dim: vec![TensorShapeDim { size: 2 }, TensorShapeDim { size: 3 }],
+            }),
+            double_val: vec![0.0, 0.5, 4.0, 0.5, 1.0, 0.0],
+            ..TensorProto::default()
+        };
+        bytes.extend(event(
+            20,
+            SummaryValue {
+                tensor: Some(tensor.clone()),
+                metadata: Some(SummaryMetadata {
+                    plugin_data: Some(PluginData {
+                        plugin_name: "histograms".to_string(),
+                    }),
+                }),
+                ..value("grads/g")
+            },
+        ));
+        // A 2-D tensor without the histogram plugin is a table, not a histogram.
+        bytes.extend(event(
+            20,
+            SummaryValue {
+                tensor: Some(tensor),
+                ..value("val/confusion")
+            },
+        ));
+
+        let by_tag = parse_tfevents(&bytes);
+        assert_eq!(by_tag.keys().collect::<Vec<_>>(), ["grads/g", "weights/w"]);
+        let weights = &by_tag["weights/w"][0];
+        assert_eq!(weights.step, 10);
+        assert_eq!(weights.range(), Some((-1.0, 2.0)));
+        assert_eq!(weights.count(), 6.0);
+        assert_eq!(weights.rebin((-1.0, 2.0), 3), vec![1.0, 3.0, 2.0]);
+        let grads = &by_tag["grads/g"][0];
+        assert_eq!(grads.range(), Some((0.0, 0.5)));
+        assert_eq!(grads.mean(), Some(0.25));
+        assert_eq!(range(&by_tag["weights/w"]), Some((-1.0, 2.0)));
+    }
+
+    #[test]
+    fn rebinning_spreads_buckets_over_the_bins_they_cover() {
+        let histogram = Histogram {
+            step: 0,
+            buckets: vec![super::Bucket {
+                left: 0.0,
+                right: 4.0,
+                count: 8.0,
+            }],
+        };
+        assert_eq!(histogram.rebin((0.0, 8.0), 4), vec![4.0, 4.0, 0.0, 0.0]);
+        assert_eq!(histogram.rebin((0.0, 8.0), 0), Vec::<f64>::new());
+        assert!(
+            Histogram::new(0, vec![]).is_none(),
+            "an empty histogram is skipped"
+        );
+    }
+}
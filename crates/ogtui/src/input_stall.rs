@@ -0,0 +1,188 @@
+//! Dataloader stalls: stretches where the GPUs sit mostly idle while the CPU
+//! is saturated (preprocessing can't keep up) or waiting on disk (reads can't
+//! keep up). Reads the system sampler's `sys/gpu<i>/util_pct`,
+//! `sys/cpu/util_pct` and `sys/cpu/iowait_pct`, which share one line per sample.
+
+use std::collections::BTreeMap;
+
+/// Mean GPU utilization below which the GPUs count as starved.
+const STARVED_GPU_PCT: f64 = 50.0;
+/// CPU utilization at or above which preprocessing is the likely bottleneck.
+const BUSY_CPU_PCT: f64 = 90.0;
+/// CPU time waiting on I/O at or above which reads are the likely bottleneck.
+const HIGH_IOWAIT_PCT: f64 = 20.0;
+/// Starved samples in a row before a stall is suspected.
+const MIN_POINTS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StallCause {
+    /// CPU saturated.
+    Cpu,
+    /// CPU waiting on disk.
+    Disk,
+}
+
+impl StallCause {
+    pub fn label(self) -> &'static str {
+        match self {
+            StallCause::Cpu => "CPU-bound",
+            StallCause::Disk => "I/O-bound",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stall {
+    pub first_step: f64,
+    pub last_step: f64,
+    pub cause: StallCause,
+    /// Means over the stall.
+    pub gpu_util: f64,
+    pub cpu_util: f64,
+    pub iowait: f64,
+}
+
+impl Stall {
+    /// `dataloader stall suspected between steps 1200–1450: GPU util 12%,
+    /// CPU 97%, iowait 3% (CPU-bound)`.
+    pub fn describe(&self) -> String {
+        format!(
+            "dataloader stall suspected between steps {:.0}–{:.0}: GPU util {:.0}%, CPU {:.0}%, iowait {:.0}% ({})",
+            self.first_step,
+            self.last_step,
+            self.gpu_util,
+            self.cpu_util,
+            self.iowait,
+            self.cause.label()
+        )
+    }
+}
+
+/// The last `n` values of `tag`, or None when it has fewer.
+fn tail<'a>(
+    scalars: &'a BTreeMap<String, Vec<(f64, f64)>>,
+    tag: &str,
+    n: usize,
+) -> Option<&'a [(f64, f64)]> {
+    let series = scalars.get(tag)?;
+    series.get(series.len().checked_sub(n)?..)
+}
+
+/// Every stretch of at least `MIN_POINTS` samples with starved GPUs and a busy
+/// CPU or high iowait, oldest first. The last one may still be growing.
+pub fn detect(scalars: &BTreeMap<String, Vec<(f64, f64)>>) -> Vec<Stall> {
+    let gpu_tags: Vec<&String> = scalars
+        .keys()
+        .filter(|tag| {
+            tag.strip_prefix("sys/gpu")
+                .is_some_and(|rest| rest.ends_with("/util_pct"))
+        })
+        .collect();
+    if gpu_tags.is_empty() {
+        return Vec::new();
+    }
+    let Some(n) = gpu_tags
+        .iter()
+        .map(|tag| scalars[*tag].len())
+        .chain(
+            ["sys/cpu/util_pct", "sys/cpu/iowait_pct"]
+                .map(|tag| scalars.get(tag).map_or(0, Vec::len)),
+        )
+        .min()
+        .filter(|n| *n > 0)
+    else {
+        return Vec::new();
+    };
+    let gpus: Vec<&[(f64, f64)]> = gpu_tags
+        .iter()
+        .filter_map(|tag| tail(scalars, tag, n))
+        .collect();
+    let (Some(cpu), Some(iowait)) = (
+        tail(scalars, "sys/cpu/util_pct", n),
+        tail(scalars, "sys/cpu/iowait_pct", n),
+    ) else {
+        return Vec::new();
+    };
+
+    let gpu: Vec<f64> = (0..n)
+        .map(|i| gpus.iter().map(|series| series[i].1).sum::<f64>() / gpus.len() as f64)
+        .collect();
+
+    let mut stalls = Vec::new();
+    let mut run: Vec<usize> = Vec::new();
+    for i in 0..=n {
+        if i < n
+            && gpu[i] < STARVED_GPU_PCT
+            && (cpu[i].1 >= BUSY_CPU_PCT || iowait[i].1 >= HIGH_IOWAIT_PCT)
+        {
+            run.push(i);
+            continue;
+        }
+        if run.len() >= MIN_POINTS {
+            let mean = |value: &dyn Fn(usize) -> f64| {
+                run.iter().map(|i| value(*i)).sum::<f64>() / run.len() as f64
+            };
+            let iowait_mean = mean(&|i| iowait[i].1);
+            stalls.push(Stall {
+                first_step: cpu[run[0]].0,
+                last_step: cpu[run[run.len() - 1]].0,
+                cause: if iowait_mean >= HIGH_IOWAIT_PCT {
+                    StallCause::Disk
+                } else {
+                    StallCause::Cpu
+                },
+                gpu_util: mean(&|i| gpu[i]),
+                cpu_util: mean(&|i| cpu[i].1),
+                iowait: iowait_mean,
+            });
+        }
+        run.clear();
+    }
+    stalls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StallCause, detect};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn finds_starved_gpu_stretches_and_their_cause() {
+        let steps = [
+            100.0, 110.0, 120.0, 120.0, 130.0, 140.0, 150.0, 160.0, 170.0,
+        ];
+        let series =
+            |values: [f64; 9]| -> Vec<(f64, f64)> { steps.into_iter().zip(values).collect() };
+        let mut scalars = BTreeMap::new();
+        scalars.insert(
+            "sys/gpu0/util_pct".to_string(),
+            series([98.0, 10.0, 5.0, 8.0, 97.0, 20.0, 15.0, 10.0, 99.0]),
+        );
+        scalars.insert(
+            "sys/gpu1/util_pct".to_string(),
+            series([96.0, 14.0, 9.0, 10.0, 99.0, 20.0, 25.0, 30.0, 98.0]),
+        );
+        scalars.insert(
+            "sys/cpu/util_pct".to_string(),
+            series([40.0, 30.0, 25.0, 30.0, 40.0, 99.0, 98.0, 97.0, 50.0]),
+        );
+        scalars.insert(
+            "sys/cpu/iowait_pct".to_string(),
+            series([1.0, 40.0, 45.0, 35.0, 2.0, 1.0, 2.0, 3.0, 1.0]),
+        );
+
+        let stalls = detect(&scalars);
+        assert_eq!(stalls.len(), 2);
+        assert_eq!((stalls[0].first_step, stalls[0].last_step), (110.0, 120.0));
+        assert_eq!(stalls[0].cause, StallCause::Disk);
+        assert_eq!((stalls[1].first_step, stalls[1].last_step), (140.0, 160.0));
+        assert_eq!(stalls[1].cause, StallCause::Cpu);
+        assert_eq!(
+            stalls[1].describe(),
+            "dataloader stall suspected between steps 140–160: GPU util 20%, CPU 98%, iowait 2% (CPU-bound)"
+        );
+
+        scalars.remove("sys/cpu/iowait_pct");
+        assert!(detect(&scalars).is_empty());
+    }
+}
@@ -0,0 +1,391 @@
+//! Kubernetes context for runs launched in a pod. The pod's namespace, name,
+//! node and the training container's resource requests and limits are
+//! recorded in `og_meta.json` at launch. `og k8s events` reads the pod's
+//! events and container terminations through `kubectl` and appends them to
+//! the run as structured events, so an OOMKilled container or an eviction
+//! shows up in the events tab at the step it interrupted.
+
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+use crate::cgroup::Cgroup;
+use crate::metrics_jsonl;
+use crate::run_meta::K8sPod;
+use crate::tfevents::ScalarEvent;
+
+/// Store pod events are appended to; read like any `*.metrics.jsonl`.
+pub const FILE_NAME: &str = "k8s.metrics.jsonl";
+/// `type` of the structured events written for pod events.
+pub const EVENT_TYPE: &str = "pod_event";
+const SERVICE_ACCOUNT_NAMESPACE: &str = "/var/run/secrets/kubernetes.io/serviceaccount/namespace";
+
+/// One thing that happened to the pod, from its events or container status.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct PodEvent {
+    pub unix: f64,
+    /// `OOMKilled`, `Evicted`, `BackOff`, ...
+    pub reason: String,
+    pub message: String,
+    /// Kubernetes `Warning` events and abnormal terminations
+    pub warning: bool,
+}
+
+/// The pod this process runs in, or None outside Kubernetes. The pod name
+/// and namespace come from the downward API (`POD_NAME`, `POD_NAMESPACE`,
+/// `NODE_NAME`), else the hostname and the service account. Resources come
+/// from the pod spec when `kubectl` can read it, else the limits from the
+/// container's cgroup.
+pub fn detect() -> Option<K8sPod> {
+    let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+    var("KUBERNETES_SERVICE_HOST")?;
+    let pod = var("POD_NAME").or_else(|| var("HOSTNAME"))?;
+    let namespace = var("POD_NAMESPACE")
+        .or_else(|| {
+            fs::read_to_string(SERVICE_ACCOUNT_NAMESPACE)
+                .ok()
+                .map(|namespace| namespace.trim().to_string())
+        })
+        .unwrap_or_else(|| "default".to_string());
+    let mut meta = K8sPod {
+        node: var("NODE_NAME"),
+        ..K8sPod::default()
+    };
+    match kubectl(&["get", "pod", &pod, "-n", &namespace, "-o", "json"]) {
+        Ok(spec) => apply_pod_spec(&mut meta, &spec, var("CONTAINER_NAME").as_deref()),
+        Err(_) => {
+            let limits = Cgroup::detect()
+                .map(|group| group.limits())
+                .unwrap_or_default();
+            if let Some(cpus) = limits.cpus {
+                meta.limits
+                    .insert("cpu".to_string(), format!("{}m", (cpus * 1000.0).round()));
+            }
+            if let Some(bytes) = limits.memory_bytes {
+                meta.limits
+                    .insert("memory".to_string(), format!("{}Mi", bytes >> 20));
+            }
+        }
+    }
+    meta.pod = pod;
+    meta.namespace = namespace;
+    Some(meta)
+}
+
+/// `kubectl <args>` output parsed as JSON.
+fn kubectl(args: &[&str]) -> Result<Value> {
+    let output = Command::new("kubectl")
+        .args(args)
+        .output()
+        .context("running kubectl")?;
+    if !output.status.success() {
+        bail!(
+            "kubectl {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    serde_json::from_slice(&output.stdout).context("parsing kubectl output")
+}
+
+/// Fill in the node and the resources of `container` (else the first
+/// container) from `kubectl get pod -o json`.
+pub fn apply_pod_spec(meta: &mut K8sPod, spec: &Value, container: Option<&str>) {
+    if let Some(node) = spec["spec"]["nodeName"].as_str() {
+        meta.node = Some(node.to_string());
+    }
+    let containers = spec["spec"]["containers"].as_array();
+    let chosen = containers.and_then(|containers| {
+        containers
+            .iter()
+            .find(|c| container.is_some_and(|name| c["name"] == name))
+            .or_else(|| containers.first())
+    });
+    let Some(chosen) = chosen else {
+        return;
+    };
+    let quantities = |kind: &str| {
+        chosen["resources"][kind]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(name, quantity)| Some((name.clone(), quantity.as_str()?.to_string())))
+            .collect()
+    };
+    meta.requests = quantities("requests");
+    meta.limits = quantities("limits");
+}
+
+/// Events of the pod and the terminations its container statuses record.
+/// An OOMKilled container or an eviction is reported on the pod status, not
+/// always as an event, so both are read.
+pub fn fetch(namespace: &str, pod: &str) -> Result<Vec<PodEvent>> {
+    let selector = format!("involvedObject.name={pod}");
+    let events = kubectl(&[
+        "get",
+        "events",
+        "-n",
+        namespace,
+        "--field-selector",
+        &selector,
+        "-o",
+        "json",
+    ])?;
+    // A deleted pod has no status left, but its events outlive it for a while.
+    let status = kubectl(&["get", "pod", pod, "-n", namespace, "-o", "json"]).ok();
+    Ok(pod_events(&events, status.as_ref()))
+}
+
+/// Pod events from `kubectl get events -o json` and `kubectl get pod -o
+/// json`, oldest first.
+pub fn pod_events(events: &Value, pod: Option<&Value>) -> Vec<PodEvent> {
+    let mut out: Vec<PodEvent> = events["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| {
+            let unix = ["lastTimestamp", "eventTime", "firstTimestamp"]
+                .iter()
+                .find_map(|field| unix_from_rfc3339(item[*field].as_str()?))?;
+            Some(PodEvent {
+                unix,
+                reason: item["reason"].as_str().unwrap_or("Unknown").to_string(),
+                message: item["message"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+                warning: item["type"] == "Warning",
+            })
+        })
+        .collect();
+    if let Some(pod) = pod {
+        let status = &pod["status"];
+        if let Some(reason) = status["reason"].as_str() {
+            let started = status["startTime"].as_str().and_then(unix_from_rfc3339);
+            let unix = status["conditions"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|c| unix_from_rfc3339(c["lastTransitionTime"].as_str()?))
+                .reduce(f64::max)
+                .or(started);
+            if let Some(unix) = unix {
+                out.push(PodEvent {
+                    unix,
+                    reason: reason.to_string(),
+                    message: status["message"].as_str().unwrap_or_default().to_string(),
+                    warning: true,
+                });
+            }
+        }
+        for container in status["containerStatuses"].as_array().into_iter().flatten() {
+            for state in [&container["lastState"], &container["state"]] {
+                let terminated = &state["terminated"];
+                let Some(unix) = terminated["finishedAt"]
+                    .as_str()
+                    .and_then(unix_from_rfc3339)
+                else {
+                    continue;
+                };
+                let code = terminated["exitCode"].as_i64().unwrap_or_default();
+                let reason = terminated["reason"].as_str().unwrap_or("Terminated");
+                out.push(PodEvent {
+                    unix,
+                    reason: reason.to_string(),
+                    message: format!(
+                        "container {} exited with code {code}",
+                        container["name"].as_str().unwrap_or("?")
+                    ),
+                    warning: reason != "Completed" || code != 0,
+                });
+            }
+        }
+    }
+    out.sort_by(|a, b| a.unix.total_cmp(&b.unix));
+    out.dedup();
+    out
+}
+
+/// Seconds since the epoch of a UTC timestamp such as `2024-03-04T09:00:00Z`
+/// or `2024-03-04T09:00:00.123456Z`, as Kubernetes writes them.
+pub fn unix_from_rfc3339(text: &str) -> Option<f64> {
+    let text = text.strip_suffix('Z')?;
+    let (date, time) = text.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let mut time = time.splitn(3, ':');
+    let hour: i64 = time.next()?.parse().ok()?;
+    let minute: i64 = time.next()?.parse().ok()?;
+    let second: f64 = time.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    // Howard Hinnant's days_from_civil.
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    Some((days * 86_400 + hour * 3600 + minute * 60) as f64 + second)
+}
+
+/// Step the run had reached at `unix`: the highest step logged by then.
+pub fn step_at(events: &[ScalarEvent], unix: f64) -> i64 {
+    events
+        .iter()
+        .filter(|event| event.wall_time > 0.0 && event.wall_time <= unix)
+        .map(|event| event.step)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Append the pod events `run_dir` has not recorded yet to its
+/// `k8s.metrics.jsonl`, each at the step the run had reached. Returns how
+/// many were new.
+pub fn record(run_dir: &Path, pod_events: &[PodEvent], events: &[ScalarEvent]) -> Result<usize> {
+    let path = run_dir.join(FILE_NAME);
+    let recorded = fs::read(&path)
+        .map(|bytes| metrics_jsonl::parse_structured(&bytes))
+        .unwrap_or_default();
+    let mut lines = String::new();
+    let mut added = 0;
+    for event in pod_events {
+        let seen = recorded.iter().any(|known| {
+            known.wall_time == event.unix
+                && known.payload.get("reason").and_then(Value::as_str) == Some(&event.reason)
+        });
+        if seen {
+            continue;
+        }
+        let line = serde_json::json!({
+            "step": step_at(events, event.unix),
+            "wall_time": event.unix,
+            "event": {
+                "type": EVENT_TYPE,
+                "reason": event.reason,
+                "message": event.message,
+                "warning": event.warning,
+            },
+        });
+        lines.push_str(&line.to_string());
+        lines.push('\n');
+        added += 1;
+    }
+    if added > 0 {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()))
+            .with_context(|| format!("appending to {}", path.display()))?;
+    }
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PodEvent, apply_pod_spec, pod_events, record, unix_from_rfc3339};
+    use crate::metrics_jsonl;
+    use crate::run_meta::K8sPod;
+    use crate::tfevents::ScalarEvent;
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    // 2024-03-04 00:00 UTC
+    const MONDAY: f64 = 1_709_510_400.0;
+
+    #[test]
+    fn reads_node_resources_and_terminations_from_kubectl_json() {
+        assert_eq!(unix_from_rfc3339("1970-01-01T00:00:00Z"), Some(0.0));
+        assert_eq!(
+            unix_from_rfc3339("2024-03-04T00:00:01.5Z"),
+            Some(MONDAY + 1.5)
+        );
+        assert_eq!(
+            unix_from_rfc3339("2024-02-29T00:00:00Z"),
+            Some(MONDAY - 4.0 * 86_400.0)
+        );
+        assert_eq!(unix_from_rfc3339("2024-03-04 00:00:00"), None);
+
+        let pod = serde_json::json!({
+            "spec": {
+                "nodeName": "gpu-node-7",
+                "containers": [
+                    {"name": "sidecar", "resources": {"limits": {"cpu": "100m"}}},
+                    {"name": "trainer", "resources": {
+                        "requests": {"cpu": "4", "memory": "16Gi"},
+                        "limits": {"memory": "32Gi", "nvidia.com/gpu": "1"},
+                    }},
+                ],
+            },
+            "status": {
+                "containerStatuses": [{
+                    "name": "trainer",
+                    "lastState": {"terminated": {
+                        "reason": "OOMKilled", "exitCode": 137, "finishedAt": "2024-03-04T02:00:00Z",
+                    }},
+                    "state": {"running": {"startedAt": "2024-03-04T02:00:05Z"}},
+                }],
+            },
+        });
+        let mut meta = K8sPod::default();
+        apply_pod_spec(&mut meta, &pod, Some("trainer"));
+        assert_eq!(meta.node.as_deref(), Some("gpu-node-7"));
+        assert_eq!(meta.requests["memory"], "16Gi");
+        assert_eq!(meta.limits["nvidia.com/gpu"], "1");
+        apply_pod_spec(&mut meta, &pod, None);
+        assert_eq!(meta.limits["cpu"], "100m", "first container without a name");
+
+        let events = serde_json::json!({"items": [
+            {"type": "Warning", "reason": "BackOff", "message": "Back-off restarting",
+             "lastTimestamp": "2024-03-04T02:00:03Z"},
+            {"type": "Normal", "reason": "Pulled", "message": "image pulled",
+             "firstTimestamp": "2024-03-04T00:00:00Z", "lastTimestamp": null},
+        ]});
+        let events = pod_events(&events, Some(&pod));
+        let reasons: Vec<&str> = events.iter().map(|e| e.reason.as_str()).collect();
+        assert_eq!(reasons, ["Pulled", "OOMKilled", "BackOff"]);
+        assert!(events[1].warning && !events[0].warning);
+        assert_eq!(events[1].message, "container trainer exited with code 137");
+    }
+
+    #[test]
+    fn records_each_pod_event_once_at_the_step_it_interrupted() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("ogtui-k8s-{nonce}"));
+        fs::create_dir_all(&dir).expect("create run dir");
+        let scalar = |step: i64, wall_time: f64| ScalarEvent {
+            tag: "train/loss".to_string(),
+            step,
+            wall_time,
+            value: 1.0,
+        };
+        let scalars = [scalar(100, MONDAY), scalar(200, MONDAY + 60.0)];
+        let evicted = [PodEvent {
+            unix: MONDAY + 90.0,
+            reason: "Evicted".to_string(),
+            message: "The node was low on resource: memory.".to_string(),
+            warning: true,
+        }];
+
+        assert_eq!(record(&dir, &evicted, &scalars).expect("record"), 1);
+        assert_eq!(record(&dir, &evicted, &scalars).expect("record"), 0);
+        let events = metrics_jsonl::load_structured(&dir).expect("load");
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            (events[0].step, events[0].kind.as_str()),
+            (200, "pod_event")
+        );
+        assert_eq!(events[0].payload["reason"], "Evicted");
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,286 @@
+//! Bounded storage for the logs pane. The newest `cap` lines stay in memory;
+//! older ones are appended to the run's console log and read back a page at
+//! a time when the user scrolls past the top of what is in memory.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom, Write};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use crate::log_entry::{LogEntry, LogLevel, LogSource};
+
+/// Log lines kept in memory unless `--log-lines` says otherwise.
+pub const DEFAULT_LOG_LINES: usize = 10_000;
+/// Chat messages kept in memory unless `--chat-messages` says otherwise.
+pub const DEFAULT_CHAT_MESSAGES: usize = 500;
+/// Console log in the run directory that evicted live log lines go to.
+pub const CONSOLE_LOG_FILE_NAME: &str = "og_console.log";
+/// Lines read back from the console log per scroll past the top.
+pub const LOAD_PAGE: usize = 1_000;
+/// Every this many spilled lines, the byte offset is remembered so a page can
+/// be read back without scanning the file from the start.
+const INDEX_STRIDE: usize = 1_024;
+
+/// Log entries with a cap, derefs to the entries in memory.
+#[derive(Debug)]
+pub struct LogBuffer {
+    /// Slots before `head` held lines that have been evicted since the last
+    /// compaction; keeping them makes eviction O(1) without a ring index.
+    slots: Vec<LogEntry>,
+    head: usize,
+    cap: usize,
+    /// Lines loaded back from disk, allowed past `cap` until `trim`.
+    extra: usize,
+    /// Number of the first line in memory, counting from the last `replace`.
+    first: usize,
+    spill: Option<Spill>,
+}
+
+/// Where evicted lines go. Lines `base..written` are in the file.
+#[derive(Debug)]
+struct Spill {
+    path: PathBuf,
+    file: Option<File>,
+    base: usize,
+    written: usize,
+    /// Byte offset of line `base + i * INDEX_STRIDE`.
+    index: Vec<u64>,
+}
+
+impl Spill {
+    fn new(path: PathBuf, first: usize) -> Self {
+        Self {
+            path,
+            file: None,
+            base: first,
+            written: first,
+            index: Vec::new(),
+        }
+    }
+
+    fn write(&mut self, entry: &LogEntry) -> io::Result<()> {
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => self.file.insert(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)?,
+            ),
+        };
+        if (self.written - self.base).is_multiple_of(INDEX_STRIDE) {
+            self.index.push(file.seek(SeekFrom::End(0))?);
+        }
+        let mut bytes = entry.to_console_line().into_bytes();
+        bytes.push(b'\n');
+        file.write_all(&bytes)?;
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Lines `start..start + count` read back from the file.
+    fn read(&self, start: usize, count: usize) -> io::Result<Vec<LogEntry>> {
+        let offset = start - self.base;
+        let stride = offset / INDEX_STRIDE;
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.index[stride]))?;
+        let entries: Vec<LogEntry> = BufReader::new(file)
+            .lines()
+            .skip(offset - stride * INDEX_STRIDE)
+            .take(count)
+            .map(|line| {
+                Ok(LogEntry::from_console_line(&line?)
+                    .unwrap_or_else(|| LogEntry::new(LogLevel::Info, LogSource::File, "")))
+            })
+            .collect::<io::Result<_>>()?;
+        if entries.len() < count {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("{} is shorter than expected", self.path.display()),
+            ));
+        }
+        Ok(entries)
+    }
+}
+
+impl LogBuffer {
+    pub fn new(cap: usize) -> Self {
+        Self {
+            slots: Vec::new(),
+            head: 0,
+            cap: cap.max(1),
+            extra: 0,
+            first: 0,
+            spill: None,
+        }
+    }
+
+    /// Change the cap, evicting from the front if it shrank. Returns the
+    /// number of lines evicted.
+    pub fn set_cap(&mut self, cap: usize) -> usize {
+        self.cap = cap.max(1);
+        self.trim()
+    }
+
+    /// Append evicted lines to `path` from now on (None drops them).
+    pub fn set_spill(&mut self, path: Option<&Path>) {
+        self.spill = path.map(|path| Spill::new(path.to_path_buf(), self.first));
+    }
+
+    /// Replace every line. Lines past the cap are dropped from the front
+    /// without spilling: callers replace with views they can rebuild.
+    pub fn replace(&mut self, lines: Vec<LogEntry>) {
+        let dropped = lines.len().saturating_sub(self.cap);
+        self.slots = lines;
+        self.slots.drain(..dropped);
+        self.head = 0;
+        self.extra = 0;
+        self.first = dropped;
+        if let Some(spill) = self.spill.take() {
+            self.spill = Some(Spill::new(spill.path, self.first));
+        }
+    }
+
+    /// Append a line, evicting from the front past the cap. Returns the
+    /// number of lines evicted.
+    pub fn push(&mut self, line: LogEntry) -> usize {
+        self.slots.push(line);
+        self.evict_over(self.cap + self.extra)
+    }
+
+    /// Drop lines loaded back by `load_earlier` once the user is back at the
+    /// tail. Returns the number of lines evicted.
+    pub fn trim(&mut self) -> usize {
+        self.extra = 0;
+        self.evict_over(self.cap)
+    }
+
+    /// Lines before the first one in memory that `load_earlier` can bring back.
+    pub fn earlier(&self) -> usize {
+        self.spill
+            .as_ref()
+            .map_or(0, |spill| self.first.saturating_sub(spill.base))
+    }
+
+    /// Lines before the first one in memory that are gone for good.
+    pub fn dropped(&self) -> usize {
+        self.first - self.earlier()
+    }
+
+    /// Read up to `count` lines preceding the first in memory back from the
+    /// console log and put them in front. Returns the number loaded.
+    pub fn load_earlier(&mut self, count: usize) -> io::Result<usize> {
+        let count = count.min(self.earlier());
+        let Some(spill) = self.spill.as_ref().filter(|_| count > 0) else {
+            return Ok(0);
+        };
+        let loaded = spill.read(self.first - count, count)?;
+        self.slots.splice(self.head..self.head, loaded);
+        self.first -= count;
+        self.extra += count;
+        Ok(count)
+    }
+
+    fn evict_over(&mut self, limit: usize) -> usize {
+        let evicted = self.len().saturating_sub(limit);
+        for _ in 0..evicted {
+            let line = std::mem::replace(
+                &mut self.slots[self.head],
+                LogEntry::new(LogLevel::Info, LogSource::Og, String::new()),
+            );
+            self.head += 1;
+            if let Some(spill) = self.spill.as_mut()
+                && self.first == spill.written
+                && spill.write(&line).is_err()
+            {
+                // Without a console log there is nothing to load back.
+                self.spill = None;
+            }
+            self.first += 1;
+        }
+        if self.head > self.cap {
+            self.slots.drain(..self.head);
+            self.head = 0;
+        }
+        evicted
+    }
+}
+
+impl Deref for LogBuffer {
+    type Target = [LogEntry];
+
+    fn deref(&self) -> &[LogEntry] {
+        &self.slots[self.head..]
+    }
+}
+
+impl FromIterator<LogEntry> for LogBuffer {
+    fn from_iter<I: IntoIterator<Item = LogEntry>>(lines: I) -> Self {
+        let mut buffer = Self::new(DEFAULT_LOG_LINES);
+        buffer.replace(lines.into_iter().collect());
+        buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LogBuffer;
+    use crate::log_entry::{LogEntry, LogLevel, LogSource};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn line(idx: usize) -> LogEntry {
+        LogEntry::new(LogLevel::Info, LogSource::Og, format!("line {idx}"))
+    }
+
+    fn lines(range: std::ops::Range<usize>) -> Vec<LogEntry> {
+        range.map(line).collect()
+    }
+
+    #[test]
+    fn push_keeps_the_newest_lines_and_spills_the_rest() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("ogtui-log-buffer-{nonce}.log"));
+        let mut buffer = LogBuffer::new(100);
+        buffer.set_spill(Some(&path));
+        let evicted: usize = lines(0..5_000).into_iter().map(|l| buffer.push(l)).sum();
+        assert_eq!(evicted, 4_900);
+        assert_eq!(buffer.len(), 100);
+        assert_eq!(buffer[0], line(4900));
+        assert_eq!(buffer.earlier(), 4_900);
+        assert_eq!(buffer.dropped(), 0);
+
+        // Scrolling back reads pages from the console log, across index strides.
+        assert_eq!(buffer.load_earlier(2_000).expect("load"), 2_000);
+        assert_eq!(buffer[0], line(2900));
+        assert_eq!(buffer[2_000], line(4900));
+        // New lines while scrolled back only evict lines already on disk.
+        assert_eq!(buffer.push(line(5000)), 1);
+        assert_eq!(buffer[0], line(2901));
+        assert_eq!(buffer.load_earlier(10_000).expect("load"), 2_901);
+        assert_eq!(buffer[..], lines(0..5_001)[..]);
+
+        // Back at the tail: loaded lines go again, without being written twice.
+        assert_eq!(buffer.trim(), 4_901);
+        assert_eq!(buffer[0], line(4901));
+        let written = std::fs::read_to_string(&path).expect("console log");
+        assert_eq!(written.lines().count(), 4_901);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn replace_and_unspilled_eviction_drop_lines_for_good() {
+        let mut buffer = LogBuffer::new(3);
+        buffer.replace(lines(0..10));
+        assert_eq!(buffer[..], lines(7..10)[..]);
+        assert_eq!(buffer.dropped(), 7);
+        buffer.push(line(10));
+        assert_eq!(buffer.dropped(), 8);
+        assert_eq!(buffer.earlier(), 0);
+        assert_eq!(buffer.load_earlier(5).expect("nothing to load"), 0);
+        assert_eq!(buffer.set_cap(1), 2);
+        assert_eq!(buffer[..], [line(10)]);
+    }
+}
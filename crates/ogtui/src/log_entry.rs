@@ -0,0 +1,380 @@
+//! Log lines as data: a level, where the line came from, when it was logged
+//! and the message. Lines arriving as text (daemon log tails, console logs,
+//! old session recordings) are classified once, here, and everything after
+//! that works on the fields.
+
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Severity, in increasing order so filters can keep "this level and up".
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Info,
+    Success,
+    Warn,
+    Important,
+    Error,
+}
+
+impl LogLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Info => "info",
+            LogLevel::Success => "success",
+            LogLevel::Warn => "warn",
+            LogLevel::Important => "important",
+            LogLevel::Error => "error",
+        }
+    }
+
+    /// Level named by a `[tag]` prefix. `sucess` is how og spelled it before
+    /// levels were data, and old recordings still carry it.
+    fn from_tag(tag: &str) -> Option<LogLevel> {
+        Some(match tag {
+            "info" => LogLevel::Info,
+            "success" | "sucess" => LogLevel::Success,
+            "warn" | "warning" => LogLevel::Warn,
+            "important" | "alert" | "auto" => LogLevel::Important,
+            "error" => LogLevel::Error,
+            _ => return None,
+        })
+    }
+
+    /// Next minimum level for the logs tab filter: all, warn+, error, all.
+    pub fn next_filter(filter: Option<LogLevel>) -> Option<LogLevel> {
+        match filter {
+            None => Some(LogLevel::Warn),
+            Some(LogLevel::Error) => None,
+            Some(_) => Some(LogLevel::Error),
+        }
+    }
+}
+
+/// Where a line entered og.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogSource {
+    /// og itself: alerts, step progress, warnings.
+    Og,
+    /// The agent daemon's log tail, which includes training output.
+    Daemon,
+    /// Points parsed from the run's event files.
+    Events,
+    /// A plain text file read by `og tail`.
+    File,
+}
+
+impl LogSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogSource::Og => "og",
+            LogSource::Daemon => "daemon",
+            LogSource::Events => "events",
+            LogSource::File => "file",
+        }
+    }
+
+    fn parse(text: &str) -> Option<LogSource> {
+        Some(match text {
+            "og" => LogSource::Og,
+            "daemon" => LogSource::Daemon,
+            "events" => LogSource::Events,
+            "file" => LogSource::File,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogEntry {
+    pub level: LogLevel,
+    pub source: LogSource,
+    /// Unix seconds; None when the source carries no time.
+    pub timestamp: Option<f64>,
+    pub message: String,
+}
+
+impl LogEntry {
+    pub fn new(level: LogLevel, source: LogSource, message: impl Into<String>) -> Self {
+        Self {
+            level,
+            source,
+            timestamp: None,
+            message: message.into(),
+        }
+    }
+
+    /// An entry stamped with the current time.
+    pub fn now(level: LogLevel, source: LogSource, message: impl Into<String>) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs_f64())
+            .ok();
+        Self {
+            timestamp: now,
+            ..Self::new(level, source, message)
+        }
+    }
+
+    /// Classify a line that arrived as text. Known `[tag]` prefixes are
+    /// stripped and set the level; untagged lines that look like failures or
+    /// completions are promoted. Blank lines yield None.
+    pub fn classify(raw: &str, source: LogSource) -> Option<LogEntry> {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+        let (tagged, stripped) = strip_known_tags(trimmed);
+        let message = if stripped.is_empty() {
+            trimmed
+        } else {
+            stripped
+        };
+        let lower = trimmed.to_ascii_lowercase();
+        let level = match tagged {
+            Some(LogLevel::Important) => LogLevel::Important,
+            _ if tagged == Some(LogLevel::Error)
+                || lower.contains("traceback")
+                || lower.contains("exception")
+                || lower.contains("panic")
+                || lower.contains("fatal")
+                || lower.contains("failed")
+                || lower.contains("error")
+                || (lower.contains("exited with code") && !lower.contains("code 0")) =>
+            {
+                LogLevel::Error
+            }
+            Some(LogLevel::Warn) => LogLevel::Warn,
+            _ if tagged == Some(LogLevel::Success)
+                || lower.contains("succeeded")
+                || lower.contains("completed")
+                || lower.contains("restarted")
+                || lower.contains("exited with code 0") =>
+            {
+                LogLevel::Success
+            }
+            _ => LogLevel::Info,
+        };
+        Some(LogEntry::new(level, source, message))
+    }
+
+    /// One line of the console log: `<unix secs|-> <level> <source> <message>`.
+    pub fn to_console_line(&self) -> String {
+        let timestamp = self
+            .timestamp
+            .map_or_else(|| "-".to_string(), |secs| format!("{secs:.3}"));
+        format!(
+            "{timestamp} {} {} {}",
+            self.level.as_str(),
+            self.source.as_str(),
+            self.message.replace('\n', " ")
+        )
+    }
+
+    /// Read back a `to_console_line` line; anything else is classified as
+    /// text from a file.
+    pub fn from_console_line(line: &str) -> Option<LogEntry> {
+        let mut fields = line.splitn(4, ' ');
+        let parsed = (|| {
+            let timestamp = match fields.next()? {
+                "-" => None,
+                secs => Some(secs.parse().ok()?),
+            };
+            let level = LogLevel::from_tag(fields.next()?)?;
+            let source = LogSource::parse(fields.next()?)?;
+            Some(LogEntry {
+                level,
+                source,
+                timestamp,
+                message: fields.next().unwrap_or_default().to_string(),
+            })
+        })();
+        parsed.or_else(|| LogEntry::classify(line, LogSource::File))
+    }
+
+    /// `HH:MM:SS` (UTC) of the timestamp, for the logs tab.
+    pub fn clock(&self) -> Option<String> {
+        let secs = self.timestamp.filter(|secs| *secs >= 0.0)? as u64 % 86_400;
+        Some(format!(
+            "{:02}:{:02}:{:02}",
+            secs / 3_600,
+            secs / 60 % 60,
+            secs % 60
+        ))
+    }
+
+    /// Whether the level is worth printing: rows parsed from event files
+    /// and `--` section headers read better bare.
+    pub fn shows_level(&self) -> bool {
+        !(self.level == LogLevel::Info && self.source == LogSource::Events
+            || self.message.starts_with("--"))
+    }
+}
+
+/// `[level] message` (or the bare message, see `shows_level`), the form
+/// copied to the clipboard.
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.shows_level() {
+            write!(f, "[{}] ", self.level.as_str())?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+impl Hash for LogEntry {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.level.hash(state);
+        self.source.hash(state);
+        self.timestamp.map(f64::to_bits).hash(state);
+        self.message.hash(state);
+    }
+}
+
+/// Session recordings made before entries were structured hold `[tag] text`
+/// strings; those are classified on the way in.
+impl<'de> Deserialize<'de> for LogEntry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Stored {
+            Entry {
+                level: LogLevel,
+                source: LogSource,
+                #[serde(default)]
+                timestamp: Option<f64>,
+                message: String,
+            },
+            Text(String),
+        }
+        Ok(match Stored::deserialize(deserializer)? {
+            Stored::Entry {
+                level,
+                source,
+                timestamp,
+                message,
+            } => LogEntry {
+                level,
+                source,
+                timestamp,
+                message,
+            },
+            Stored::Text(text) => LogEntry::classify(&text, LogSource::Events)
+                .unwrap_or_else(|| LogEntry::new(LogLevel::Info, LogSource::Events, text)),
+        })
+    }
+}
+
+/// Strip leading `[tag]` prefixes og or the daemon know about, returning the
+/// first level among them and the rest of the line.
+fn strip_known_tags(mut line: &str) -> (Option<LogLevel>, &str) {
+    let mut level = None;
+    loop {
+        let trimmed = line.trim_start();
+        let Some((tag, rest)) = trimmed
+            .strip_prefix('[')
+            .and_then(|after| after.split_once(']'))
+        else {
+            return (level, trimmed);
+        };
+        let tag = tag.trim().to_ascii_lowercase();
+        match LogLevel::from_tag(&tag) {
+            Some(tagged) => {
+                level = level.or(Some(tagged));
+            }
+            None if tag == "daemon" || tag == "system" => {}
+            None => return (level, trimmed),
+        }
+        line = rest;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LogEntry, LogLevel, LogSource};
+
+    fn classify(line: &str) -> Option<(LogLevel, String)> {
+        LogEntry::classify(line, LogSource::Daemon).map(|entry| (entry.level, entry.message))
+    }
+
+    #[test]
+    fn classify_strips_known_tags_and_promotes_failures() {
+        assert_eq!(
+            classify("[daemon] [info] training warmup started"),
+            Some((LogLevel::Info, "training warmup started".to_string()))
+        );
+        assert_eq!(
+            classify("[system] training restarted (pid=1234)"),
+            Some((
+                LogLevel::Success,
+                "training restarted (pid=1234)".to_string()
+            ))
+        );
+        assert_eq!(
+            classify("Traceback (most recent call last):"),
+            Some((
+                LogLevel::Error,
+                "Traceback (most recent call last):".to_string()
+            ))
+        );
+        assert_eq!(
+            classify("[sucess] step 3 completed"),
+            Some((LogLevel::Success, "step 3 completed".to_string()))
+        );
+        assert_eq!(
+            classify("[warn] slow dataloader"),
+            Some((LogLevel::Warn, "slow dataloader".to_string()))
+        );
+        assert_eq!(
+            classify("[alert] loss > 2.0"),
+            Some((LogLevel::Important, "loss > 2.0".to_string()))
+        );
+        assert_eq!(
+            classify("[rank0] hello"),
+            Some((LogLevel::Info, "[rank0] hello".to_string()))
+        );
+        assert_eq!(classify("   "), None);
+    }
+
+    #[test]
+    fn entries_round_trip_through_console_lines_and_json() {
+        let entry = LogEntry {
+            level: LogLevel::Warn,
+            source: LogSource::Og,
+            timestamp: Some(1_717_243_205.5),
+            message: "two  spaces\nand a newline".to_string(),
+        };
+        let back = LogEntry::from_console_line(&entry.to_console_line()).expect("entry");
+        assert_eq!(back.message, "two  spaces and a newline");
+        assert_eq!((back.level, back.source), (entry.level, entry.source));
+        assert_eq!(back.clock().as_deref(), Some("12:00:05"));
+        assert_eq!(entry.to_string(), "[warn] two  spaces\nand a newline");
+        let row = LogEntry::new(LogLevel::Info, LogSource::Events, "step 1 │ loss │ 0.5");
+        assert_eq!(row.to_string(), "step 1 │ loss │ 0.5");
+
+        let json = serde_json::to_string(&entry).expect("json");
+        assert_eq!(
+            serde_json::from_str::<LogEntry>(&json).expect("entry"),
+            entry
+        );
+        let legacy: LogEntry =
+            serde_json::from_str(r#""[sucess] step 4 completed (+1)""#).expect("legacy");
+        assert_eq!(legacy.level, LogLevel::Success);
+        assert_eq!(legacy.message, "step 4 completed (+1)");
+    }
+}
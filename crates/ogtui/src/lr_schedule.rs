@@ -0,0 +1,269 @@
+//! Learning-rate schedule checks: which curve the logged `lr` follows after
+//! warmup, where warmup ends, and the usual schedule mistakes (an LR that
+//! never decays, an LR of zero, an LR jump right before a loss spike).
+
+/// Fewer points than this after the peak say nothing about decay.
+const MIN_DECAY_POINTS: usize = 20;
+/// A warmup starts below this fraction of the peak LR.
+const WARMUP_START_FRACTION: f64 = 0.5;
+/// Relative change per point that counts as a jump in a step schedule, or
+/// (upwards) as an LR spike.
+const JUMP_FRACTION: f64 = 0.1;
+const SPIKE_FRACTION: f64 = 0.2;
+/// Logged LR points after a spike within which a loss anomaly counts as caused by it.
+const SPIKE_LAG_POINTS: usize = 3;
+/// Mean squared error, on the curve normalised to [0, 1], below which a
+/// decay shape counts as matched.
+const SHAPE_FIT_MSE: f64 = 0.005;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleShape {
+    Constant,
+    Linear,
+    Cosine,
+    Step,
+    Other,
+}
+
+impl ScheduleShape {
+    pub fn label(self) -> &'static str {
+        match self {
+            ScheduleShape::Constant => "constant",
+            ScheduleShape::Linear => "linear decay",
+            ScheduleShape::Cosine => "cosine decay",
+            ScheduleShape::Step => "step decay",
+            ScheduleShape::Other => "irregular",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LrWarningKind {
+    NeverDecays,
+    Zero,
+    SpikeBeforeLossSpike,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LrWarning {
+    pub kind: LrWarningKind,
+    /// Step the warning points at; None when it is about the whole run.
+    pub step: Option<f64>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LrReport {
+    pub tag: String,
+    pub shape: ScheduleShape,
+    /// Step the LR peaked at after ramping up; None without a warmup.
+    pub warmup_end: Option<f64>,
+    pub peak: f64,
+    pub peak_step: f64,
+    pub last: f64,
+    pub warnings: Vec<LrWarning>,
+}
+
+/// The learning-rate tag among `tags`: a path segment named `lr` or
+/// `learning_rate` (`lr`, `train/lr`, `optim/learning_rate/group0`).
+pub fn find_lr_tag<'a>(tags: impl IntoIterator<Item = &'a String>) -> Option<&'a str> {
+    tags.into_iter().map(String::as_str).find(|tag| {
+        tag.split('/').any(|segment| {
+            segment.eq_ignore_ascii_case("lr") || segment.eq_ignore_ascii_case("learning_rate")
+        })
+    })
+}
+
+/// Judge the LR `series` of `tag`. `loss_anomaly_steps` are steps where the
+/// loss spiked, to match against LR jumps. Returns None with fewer than two
+/// finite points.
+pub fn analyze(tag: &str, series: &[(f64, f64)], loss_anomaly_steps: &[f64]) -> Option<LrReport> {
+    let points: Vec<(f64, f64)> = series
+        .iter()
+        .copied()
+        .filter(|(_, value)| value.is_finite())
+        .collect();
+    if points.len() < 2 {
+        return None;
+    }
+    // The schedule peaks where the LR first stops rising; later jumps above
+    // that are spikes, not the peak.
+    let peak_idx = points
+        .windows(2)
+        .position(|pair| pair[1].1 <= pair[0].1)
+        .unwrap_or(points.len() - 1);
+    let (peak_step, peak) = points[peak_idx];
+    let (_, last) = points[points.len() - 1];
+    let warmup_end =
+        (peak_idx > 0 && points[0].1 < WARMUP_START_FRACTION * peak).then_some(peak_step);
+    let decay = &points[peak_idx..];
+    let shape = classify(decay, peak);
+
+    let mut warnings = Vec::new();
+    if peak <= 0.0 {
+        warnings.push(LrWarning {
+            kind: LrWarningKind::Zero,
+            step: None,
+            message: "LR is zero for the whole run".to_string(),
+        });
+    } else if let Some((step, _)) = decay[..decay.len() - 1].iter().find(|(_, v)| *v <= 0.0) {
+        warnings.push(LrWarning {
+            kind: LrWarningKind::Zero,
+            step: Some(*step),
+            message: format!("LR hits zero at step {step}"),
+        });
+    }
+    if shape == ScheduleShape::Constant && decay.len() >= MIN_DECAY_POINTS && peak > 0.0 {
+        warnings.push(LrWarning {
+            kind: LrWarningKind::NeverDecays,
+            step: None,
+            message: format!("LR never decays ({} points at {peak:.3e})", decay.len()),
+        });
+    }
+    for idx in peak_idx + 1..points.len() {
+        let (step, value) = points[idx];
+        let previous = points[idx - 1].1;
+        if previous <= 0.0 || value <= previous * (1.0 + SPIKE_FRACTION) {
+            continue;
+        }
+        let until = points[(idx + SPIKE_LAG_POINTS).min(points.len() - 1)].0;
+        if let Some(loss_step) = loss_anomaly_steps
+            .iter()
+            .find(|loss_step| **loss_step >= step && **loss_step <= until)
+        {
+            warnings.push(LrWarning {
+                kind: LrWarningKind::SpikeBeforeLossSpike,
+                step: Some(step),
+                message: format!(
+                    "LR jumps {previous:.3e} → {value:.3e} at step {step}; loss spikes at {loss_step}"
+                ),
+            });
+        }
+    }
+
+    Some(LrReport {
+        tag: tag.to_string(),
+        shape,
+        warmup_end,
+        peak,
+        peak_step,
+        last,
+        warnings,
+    })
+}
+
+/// Shape of the curve from its peak on.
+fn classify(decay: &[(f64, f64)], peak: f64) -> ScheduleShape {
+    let floor = decay.iter().map(|(_, v)| *v).fold(f64::INFINITY, f64::min);
+    if peak <= 0.0 || (peak - floor) <= 0.01 * peak.abs() {
+        return ScheduleShape::Constant;
+    }
+    if decay.len() < 3 {
+        return ScheduleShape::Other;
+    }
+    let (first_step, _) = decay[0];
+    let (last_step, last) = decay[decay.len() - 1];
+    let span = last_step - first_step;
+    if span <= 0.0 || peak - last <= 0.0 {
+        return ScheduleShape::Other;
+    }
+
+    // Mostly flat with a few large drops.
+    let mut jumps = 0;
+    let mut flat = 0;
+    for pair in decay.windows(2) {
+        let change = (pair[1].1 - pair[0].1) / pair[0].1.abs().max(f64::MIN_POSITIVE);
+        if change < -JUMP_FRACTION {
+            jumps += 1;
+        } else if change.abs() < 1e-3 {
+            flat += 1;
+        }
+    }
+    if jumps > 0 && flat * 5 >= (decay.len() - 1) * 4 {
+        return ScheduleShape::Step;
+    }
+
+    let (mut linear, mut cosine) = (0.0, 0.0);
+    for (step, value) in decay {
+        let t = (step - first_step) / span;
+        let y = (value - last) / (peak - last);
+        linear += (y - (1.0 - t)).powi(2);
+        cosine += (y - 0.5 * (1.0 + (std::f64::consts::PI * t).cos())).powi(2);
+    }
+    let n = decay.len() as f64;
+    let (linear, cosine) = (linear / n, cosine / n);
+    if linear.min(cosine) > SHAPE_FIT_MSE {
+        ScheduleShape::Other
+    } else if cosine < linear {
+        ScheduleShape::Cosine
+    } else {
+        ScheduleShape::Linear
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LrWarningKind, ScheduleShape, analyze, find_lr_tag};
+
+    fn series(values: impl IntoIterator<Item = f64>) -> Vec<(f64, f64)> {
+        values
+            .into_iter()
+            .enumerate()
+            .map(|(step, value)| (step as f64, value))
+            .collect()
+    }
+
+    #[test]
+    fn detects_warmup_and_decay_shapes() {
+        let tags = ["train/loss".to_string(), "optim/lr".to_string()];
+        assert_eq!(find_lr_tag(&tags), Some("optim/lr"));
+        assert_eq!(find_lr_tag(&["train/lrx".to_string()]), None);
+
+        let warmup = (0..10).map(|i| 1e-3 * (i + 1) as f64 / 10.0);
+        let cosine =
+            (1..=100).map(|i| 1e-3 * 0.5 * (1.0 + (std::f64::consts::PI * i as f64 / 100.0).cos()));
+        let report = analyze("lr", &series(warmup.chain(cosine)), &[]).expect("report");
+        assert_eq!(report.shape, ScheduleShape::Cosine);
+        assert_eq!(report.warmup_end, Some(9.0));
+        assert!(report.warnings.is_empty());
+
+        let linear = (0..100).map(|i| 1e-3 * (1.0 - i as f64 / 100.0));
+        let report = analyze("lr", &series(linear), &[]).expect("report");
+        assert_eq!(
+            (report.shape, report.warmup_end),
+            (ScheduleShape::Linear, None)
+        );
+
+        let steps = (0..90).map(|i| 0.1 * 0.1f64.powi(i / 30));
+        let report = analyze("lr", &series(steps), &[]).expect("report");
+        assert_eq!(report.shape, ScheduleShape::Step);
+    }
+
+    #[test]
+    fn warns_about_flat_zero_and_spiking_lr() {
+        let report = analyze("lr", &series([3e-4; 40]), &[]).expect("report");
+        assert_eq!(report.shape, ScheduleShape::Constant);
+        assert_eq!(report.warnings[0].kind, LrWarningKind::NeverDecays);
+
+        let zero = analyze("lr", &series([1e-3, 5e-4, 0.0, 0.0]), &[]).expect("report");
+        assert_eq!(zero.warnings[0].kind, LrWarningKind::Zero);
+        assert_eq!(zero.warnings[0].step, Some(2.0));
+        let all_zero = analyze("lr", &series([0.0; 5]), &[]).expect("report");
+        assert_eq!(all_zero.warnings[0].step, None);
+
+        let mut values: Vec<f64> = (0..50).map(|i| 1e-3 * (1.0 - i as f64 / 100.0)).collect();
+        values[30] = 5e-3;
+        let spiking = series(values);
+        let report = analyze("lr", &spiking, &[32.0]).expect("report");
+        let spikes: Vec<Option<f64>> = report
+            .warnings
+            .iter()
+            .filter(|w| w.kind == LrWarningKind::SpikeBeforeLossSpike)
+            .map(|w| w.step)
+            .collect();
+        assert_eq!(spikes, vec![Some(30.0)]);
+        // A loss spike long after the LR jump is not blamed on it.
+        let report = analyze("lr", &spiking, &[45.0]).expect("report");
+        assert!(report.warnings.is_empty());
+    }
+}
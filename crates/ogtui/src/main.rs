@@ -1,28 +1,97 @@
+mod anomaly;
 mod app;
+mod audio;
+mod backoff;
+mod bench;
+mod bundle;
+mod cgroup;
+mod checkpoints;
+mod clipboard;
+mod command_line;
+mod control;
+mod convergence;
+mod cost;
+mod cron;
+mod csv_import;
+mod daemon_ctl;
+mod dashboard;
+mod debug_log;
+mod digest;
+mod discover;
+mod disk_usage;
+mod dist_health;
+mod doctor;
+mod env_capture;
+mod format;
+mod gc;
+mod grad_health;
+mod graph_filter;
+mod histogram;
+mod input_stall;
+mod k8s;
+mod log_buffer;
+mod log_entry;
+mod lr_schedule;
+mod metric_stats;
+mod oom_forecast;
+mod patch;
+mod porcelain;
+mod power;
+mod presets;
+mod project_config;
+mod query_server;
+mod redraw;
+mod regression;
+mod rollup;
+mod run_diff;
+mod run_index;
+mod run_name;
+mod scaffold;
+mod seed_band;
+mod session;
+mod shutdown;
+mod slurm;
+mod snapshot;
 mod socket_client;
-mod tfevents;
+mod sys_sampler;
+mod sys_strip;
+mod table;
+mod tb_import;
+mod thermal;
+mod timeline;
 mod ui;
 
 use anyhow::{Context, Result, bail};
-use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
 use crossterm::{
     event::{self, Event, KeyCode, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind},
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
 use ratatui::prelude::*;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::cmp::Ordering;
 use std::collections::BTreeMap;
 use std::fs;
-use std::io;
+use std::io::{self, IsTerminal, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
-use std::process::{Child, Command, Stdio};
-use std::sync::mpsc;
+use std::process::{Child, Command, ExitCode, Stdio};
+use std::sync::atomic::AtomicI64;
+use std::sync::{Arc, mpsc};
 use std::time::{Duration, Instant};
 
-use app::{App, ProcessSnapshot, ProcessSort};
+use app::{App, NotifyMode, ProcessSnapshot, ProcessSort, ToastLevel};
+use log_entry::{LogEntry, LogLevel, LogSource};
+use og_core::error::OgError;
+use og_core::query::{
+    self, CompareAxis, contains_tfevents, contains_tfevents_direct, latest_mtime_unix,
+    list_immediate_dirs, list_run_dirs, project_base, resolve_qualified_run_path, resolve_run_path,
+    run_color_hex, run_status,
+};
+use og_core::{
+    alias, compression, epoch, git_state, looks_system_metric, metrics_jsonl, par_load, readers,
+    run_color, run_meta, run_query, tfevents, tfrecord,
+};
 
 #[derive(Debug, Clone, Args)]
 struct TuiArgs {
@@ -34,6 +103,10 @@ struct TuiArgs {
     #[arg(long = "graph", visible_alias = "graphs")]
     graph: Option<String>,
 
+    /// Fail when a --graph selector matches no metric instead of showing all metrics
+    #[arg(long)]
+    graph_strict: bool,
+
     /// Metric display labels JSON or CSV mapping (e.g. '{"train/loss":"Loss"}' or 'train/loss=Loss')
     #[arg(long, env = "OG_GRAPH_LABELS")]
     graph_labels: Option<String>,
@@ -54,6 +127,10 @@ struct TuiArgs {
     #[arg(long)]
     fresh_run: bool,
 
+    /// Keep the agent daemon and training running after the TUI exits; reconnect with `og attach`
+    #[arg(long)]
+    detach: bool,
+
     /// Codebase root for agent indexing (default: current dir)
     #[arg(long, default_value = ".")]
     codebase_root: PathBuf,
@@ -85,6 +162,96 @@ struct TuiArgs {
     /// Maximum number of running/exited processes retained in the procs tab
     #[arg(long = "procs-limit", default_value_t = 300)]
     procs_limit: usize,
+
+    /// Log lines kept in memory; older live lines go to og_console.log in the run directory
+    #[arg(long, env = "OG_LOG_LINES", default_value_t = log_buffer::DEFAULT_LOG_LINES)]
+    log_lines: usize,
+
+    /// Chat messages kept in memory; older ones stay in the daemon's history
+    #[arg(long, env = "OG_CHAT_MESSAGES", default_value_t = log_buffer::DEFAULT_CHAT_MESSAGES)]
+    chat_messages: usize,
+
+    /// Also ring the terminal bell or send a desktop notification (OSC 777) for toasts
+    #[arg(long, value_enum, env = "OG_NOTIFY", default_value = "off")]
+    notify: NotifyMode,
+
+    /// Name template for new run directories ({date}, {time}, {branch}, {counter}, {adjective}, {noun})
+    #[arg(long = "run-name-template", env = "OG_RUN_NAME_TEMPLATE", default_value = run_name::DEFAULT_TEMPLATE)]
+    run_name_template: String,
+
+    /// Save uncommitted changes (`git diff HEAD`) as git.patch in the run directory
+    #[arg(long)]
+    git_patch: bool,
+
+    /// Record daemon messages and event-file refreshes to a session file for `og replay`
+    #[arg(long, value_name = "FILE")]
+    record: Option<PathBuf>,
+
+    /// Write tracing of the event loop, daemon requests and readers to a rotating log file
+    #[arg(long, value_name = "FILE", env = "OG_DEBUG_LOG")]
+    debug_log: Option<PathBuf>,
+
+    /// Upper bound on redraws per second; the screen is only redrawn when something changed
+    #[arg(long, env = "OG_MAX_FPS", default_value_t = redraw::DEFAULT_MAX_FPS,
+        value_parser = clap::value_parser!(u32).range(1..=240))]
+    max_fps: u32,
+
+    /// Keep polling at --refresh-ms even when no new data arrives for a while
+    #[arg(long)]
+    no_idle_backoff: bool,
+
+    /// Flag runs larger than this in the dashboard tab (e.g. 50GiB)
+    #[arg(long, env = "OG_RUN_SIZE_WARN", default_value = "50GiB", value_parser = disk_usage::parse_bytes)]
+    run_size_warn: u64,
+
+    /// Alert when free space on the runs filesystem drops below this (e.g. 10GiB)
+    #[arg(long, env = "OG_MIN_FREE_SPACE", default_value = "10GiB", value_parser = disk_usage::parse_bytes)]
+    min_free_space: u64,
+
+    /// Comma-separated metric selectors watched for spikes and step changes ("" turns it off)
+    #[arg(
+        long,
+        env = "OG_ANOMALY_METRICS",
+        value_delimiter = ',',
+        default_value = "loss"
+    )]
+    anomaly_metrics: Vec<String>,
+
+    /// Robust z-score at which a metric point counts as anomalous
+    #[arg(long, env = "OG_ANOMALY_Z", default_value_t = anomaly::DEFAULT_Z_THRESHOLD)]
+    anomaly_z: f64,
+
+    /// Metric watched for convergence (default: the project's objective metric)
+    #[arg(long)]
+    early_stop_metric: Option<String>,
+
+    /// Whether lower or higher values of that metric are better (default: the objective's mode, else min)
+    #[arg(long, value_enum)]
+    early_stop_mode: Option<project_config::ObjectiveMode>,
+
+    /// Evaluations without improvement before an early stop is suggested (0 turns it off)
+    #[arg(long, default_value_t = convergence::DEFAULT_PATIENCE)]
+    early_stop_patience: usize,
+
+    /// Smallest change of the metric that counts as an improvement
+    #[arg(long, default_value_t = convergence::DEFAULT_MIN_DELTA)]
+    early_stop_min_delta: f64,
+
+    /// Gradient norm above which `grad_norm`-like metrics count as exploding
+    #[arg(long, env = grad_health::EXPLODE_ENV_VAR, default_value_t = grad_health::DEFAULT_EXPLODE)]
+    grad_explode: f64,
+
+    /// Gradient norm below which `grad_norm`-like metrics count as vanishing
+    #[arg(long, env = grad_health::VANISH_ENV_VAR, default_value_t = grad_health::DEFAULT_VANISH)]
+    grad_vanish: f64,
+
+    /// Logged points in a row below --grad-vanish before the norm counts as vanishing
+    #[arg(long, env = grad_health::VANISH_POINTS_ENV_VAR, default_value_t = grad_health::DEFAULT_VANISH_POINTS)]
+    grad_vanish_points: usize,
+
+    /// System sampling interval in milliseconds for runs started here (0 disables it)
+    #[arg(long = "sys-interval-ms", default_value_t = sys_sampler::DEFAULT_INTERVAL_MS)]
+    sys_interval_ms: u64,
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum, Serialize)]
@@ -102,7 +269,7 @@ impl AutoModeArg {
     }
 }
 
-#[derive(Debug, Clone, Copy, ValueEnum, Serialize)]
+#[derive(Debug, Clone, Copy, ValueEnum, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 enum RuntimeArg {
     Local,
@@ -122,6 +289,15 @@ impl RuntimeArg {
 struct GraphFilter {
     metrics: Vec<String>,
     sys: Vec<String>,
+    /// Show only what matched, even nothing (`--graph-strict`)
+    #[serde(skip)]
+    strict: bool,
+}
+
+impl GraphFilter {
+    fn selectors(&self) -> impl Iterator<Item = &str> {
+        self.metrics.iter().chain(&self.sys).map(String::as_str)
+    }
 }
 
 #[derive(Debug, Clone, Args)]
@@ -137,6 +313,10 @@ struct RunArgs {
     #[arg(long = "graph", visible_alias = "graphs")]
     graph: Option<String>,
 
+    /// Fail when a --graph selector matches no metric instead of showing all metrics
+    #[arg(long)]
+    graph_strict: bool,
+
     /// Metric display labels JSON or CSV mapping (e.g. '{"train/loss":"Loss"}' or 'train/loss=Loss')
     #[arg(long, env = "OG_GRAPH_LABELS")]
     graph_labels: Option<String>,
@@ -165,6 +345,10 @@ struct RunArgs {
     #[arg(long, env = "OGD_SOCKET")]
     socket: Option<PathBuf>,
 
+    /// Keep the agent daemon and training running after the TUI exits; reconnect with `og attach`
+    #[arg(long)]
+    detach: bool,
+
     /// Refresh interval for live reload in milliseconds (0 disables live reload)
     #[arg(long, default_value_t = 1000)]
     refresh_ms: u64,
@@ -180,6 +364,96 @@ struct RunArgs {
     /// Maximum number of running/exited processes retained in the procs tab
     #[arg(long = "procs-limit", default_value_t = 300)]
     procs_limit: usize,
+
+    /// Log lines kept in memory; older live lines go to og_console.log in the run directory
+    #[arg(long, env = "OG_LOG_LINES", default_value_t = log_buffer::DEFAULT_LOG_LINES)]
+    log_lines: usize,
+
+    /// Chat messages kept in memory; older ones stay in the daemon's history
+    #[arg(long, env = "OG_CHAT_MESSAGES", default_value_t = log_buffer::DEFAULT_CHAT_MESSAGES)]
+    chat_messages: usize,
+
+    /// Also ring the terminal bell or send a desktop notification (OSC 777) for toasts
+    #[arg(long, value_enum, env = "OG_NOTIFY", default_value = "off")]
+    notify: NotifyMode,
+
+    /// Name template for new run directories ({date}, {time}, {branch}, {counter}, {adjective}, {noun})
+    #[arg(long = "run-name-template", env = "OG_RUN_NAME_TEMPLATE", default_value = run_name::DEFAULT_TEMPLATE)]
+    run_name_template: String,
+
+    /// Save uncommitted changes (`git diff HEAD`) as git.patch in the run directory
+    #[arg(long)]
+    git_patch: bool,
+
+    /// Record daemon messages and event-file refreshes to a session file for `og replay`
+    #[arg(long, value_name = "FILE")]
+    record: Option<PathBuf>,
+
+    /// Write tracing of the event loop, daemon requests and readers to a rotating log file
+    #[arg(long, value_name = "FILE", env = "OG_DEBUG_LOG")]
+    debug_log: Option<PathBuf>,
+
+    /// Upper bound on redraws per second; the screen is only redrawn when something changed
+    #[arg(long, env = "OG_MAX_FPS", default_value_t = redraw::DEFAULT_MAX_FPS,
+        value_parser = clap::value_parser!(u32).range(1..=240))]
+    max_fps: u32,
+
+    /// Keep polling at --refresh-ms even when no new data arrives for a while
+    #[arg(long)]
+    no_idle_backoff: bool,
+
+    /// Flag runs larger than this in the dashboard tab (e.g. 50GiB)
+    #[arg(long, env = "OG_RUN_SIZE_WARN", default_value = "50GiB", value_parser = disk_usage::parse_bytes)]
+    run_size_warn: u64,
+
+    /// Alert when free space on the runs filesystem drops below this (e.g. 10GiB)
+    #[arg(long, env = "OG_MIN_FREE_SPACE", default_value = "10GiB", value_parser = disk_usage::parse_bytes)]
+    min_free_space: u64,
+
+    /// Comma-separated metric selectors watched for spikes and step changes ("" turns it off)
+    #[arg(
+        long,
+        env = "OG_ANOMALY_METRICS",
+        value_delimiter = ',',
+        default_value = "loss"
+    )]
+    anomaly_metrics: Vec<String>,
+
+    /// Robust z-score at which a metric point counts as anomalous
+    #[arg(long, env = "OG_ANOMALY_Z", default_value_t = anomaly::DEFAULT_Z_THRESHOLD)]
+    anomaly_z: f64,
+
+    /// Metric watched for convergence (default: the project's objective metric)
+    #[arg(long)]
+    early_stop_metric: Option<String>,
+
+    /// Whether lower or higher values of that metric are better (default: the objective's mode, else min)
+    #[arg(long, value_enum)]
+    early_stop_mode: Option<project_config::ObjectiveMode>,
+
+    /// Evaluations without improvement before an early stop is suggested (0 turns it off)
+    #[arg(long, default_value_t = convergence::DEFAULT_PATIENCE)]
+    early_stop_patience: usize,
+
+    /// Smallest change of the metric that counts as an improvement
+    #[arg(long, default_value_t = convergence::DEFAULT_MIN_DELTA)]
+    early_stop_min_delta: f64,
+
+    /// Gradient norm above which `grad_norm`-like metrics count as exploding
+    #[arg(long, env = grad_health::EXPLODE_ENV_VAR, default_value_t = grad_health::DEFAULT_EXPLODE)]
+    grad_explode: f64,
+
+    /// Gradient norm below which `grad_norm`-like metrics count as vanishing
+    #[arg(long, env = grad_health::VANISH_ENV_VAR, default_value_t = grad_health::DEFAULT_VANISH)]
+    grad_vanish: f64,
+
+    /// Logged points in a row below --grad-vanish before the norm counts as vanishing
+    #[arg(long, env = grad_health::VANISH_POINTS_ENV_VAR, default_value_t = grad_health::DEFAULT_VANISH_POINTS)]
+    grad_vanish_points: usize,
+
+    /// System sampling interval in milliseconds for runs started here (0 disables it)
+    #[arg(long = "sys-interval-ms", default_value_t = sys_sampler::DEFAULT_INTERVAL_MS)]
+    sys_interval_ms: u64,
 }
 
 #[derive(Debug, Clone, Args)]
@@ -191,11 +465,43 @@ struct TailArgs {
     #[arg(long, default_value_t = 120)]
     lines: usize,
 
+    /// Only lines at this level or above
+    #[arg(long, value_enum)]
+    level: Option<LogLevel>,
+
     /// Root runs directory for run-id resolution
     #[arg(long, default_value = "runs/")]
     path: PathBuf,
 }
 
+#[derive(Debug, Clone, Args)]
+struct K8sArgs {
+    #[command(subcommand)]
+    cmd: K8sSubcommand,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum K8sSubcommand {
+    /// Read the run's pod events through kubectl and add them to its events
+    Events(K8sEventsArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+struct K8sEventsArgs {
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    #[arg(long)]
+    run: String,
+    /// Pod to read (default: the pod recorded when the run was launched)
+    #[arg(long)]
+    pod: Option<String>,
+    /// Namespace of the pod (default: the recorded one, else `default`)
+    #[arg(long)]
+    namespace: Option<String>,
+}
+
 #[derive(Debug, Clone, Args)]
 struct ResumeArgs {
     /// Run id to resume
@@ -231,6 +537,15 @@ enum ListSubcommand {
     /// List system metrics in a run
     #[command(name = "system-metrics")]
     SystemMetrics(ListMetricsArgs),
+    /// List registered data readers, or show which one reads a file
+    Readers(ListReadersArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+struct ListReadersArgs {
+    /// Event/log file to summarize with the reader that claims it
+    #[arg(long)]
+    file: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Args)]
@@ -247,10 +562,27 @@ struct ListRunsArgs {
     project: Option<String>,
     #[arg(long = "tag")]
     tag: Vec<String>,
+    /// Config predicate such as `lr<1e-3` or `optimizer=adamw` (repeatable, all must match)
     #[arg(long = "config")]
     config: Vec<String>,
     #[arg(long)]
     status: Option<String>,
+    /// Runs launched in this SLURM job (or array job)
+    #[arg(long)]
+    slurm_job: Option<String>,
+    /// Ask sacct / squeue how runs' SLURM jobs ended, so preempted, cancelled
+    /// and timed-out jobs show as such
+    #[arg(long)]
+    slurm_state: bool,
+    /// Runs parsed in parallel (default: available cores, at most 8)
+    #[arg(long, env = "OG_LOAD_JOBS")]
+    jobs: Option<usize>,
+    /// Flag runs larger than this (e.g. 50GiB)
+    #[arg(long, env = "OG_RUN_SIZE_WARN", default_value = "50GiB", value_parser = disk_usage::parse_bytes)]
+    run_size_warn: u64,
+    /// Warn when free space on the runs filesystem is below this (e.g. 10GiB)
+    #[arg(long, env = "OG_MIN_FREE_SPACE", default_value = "10GiB", value_parser = disk_usage::parse_bytes)]
+    min_free_space: u64,
 }
 
 #[derive(Debug, Clone, Args)]
@@ -261,6 +593,9 @@ struct ListMetricsArgs {
     project: Option<String>,
     #[arg(long)]
     run: String,
+    /// Also list non-scalar tags (audio, tables) with their kind and entry count
+    #[arg(long)]
+    kinds: bool,
 }
 
 #[derive(Debug, Clone, Args)]
@@ -275,6 +610,8 @@ enum GetSubcommand {
     Run(GetRunArgs),
     /// Get metric series from a run
     Metric(GetMetricArgs),
+    /// Get table summaries (confusion matrices, per-class tables) from a run
+    Table(GetTableArgs),
 }
 
 #[derive(Debug, Clone, Args)]
@@ -285,6 +622,12 @@ struct GetRunArgs {
     project: Option<String>,
     #[arg(long)]
     run: String,
+    /// Include the environment captured at launch (packages, env vars)
+    #[arg(long)]
+    env: bool,
+    /// Report a single rank's metrics instead of the merged multi-rank view
+    #[arg(long)]
+    rank: Option<u32>,
 }
 
 #[derive(Debug, Clone, Args)]
@@ -299,14 +642,135 @@ struct GetMetricArgs {
     metric: String,
 }
 
+#[derive(Debug, Clone, Args)]
+struct GetTableArgs {
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    #[arg(long)]
+    run: String,
+    /// Table tag or event name (default: every table in the run)
+    #[arg(long)]
+    table: Option<String>,
+    /// Show the version logged at or before this step (default: the latest)
+    #[arg(long)]
+    step: Option<i64>,
+}
+
 #[derive(Debug, Clone, Args)]
 struct CompareArgs {
-    /// Comma-separated run ids or paths
+    /// Comma-separated run ids, `project/run` ids or paths
     #[arg(long, value_delimiter = ',')]
     runs: Vec<String>,
-    /// Metric to compare
+    /// Metric to compare (default: the project's objective metric)
+    #[arg(long)]
+    metric: Option<String>,
+    /// Report when each run first reached this value, fastest first
+    #[arg(long)]
+    target: Option<f64>,
+    /// What "fastest" means with --target
+    #[arg(long, value_enum, default_value_t = CompareAxis::WallTime, requires = "target")]
+    x: CompareAxis,
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum LeaderboardMode {
+    Max,
+    Min,
+}
+
+#[derive(Debug, Clone, Args)]
+struct LeaderboardArgs {
+    /// Metric used to rank runs (default: the project's objective metric)
+    #[arg(long)]
+    metric: Option<String>,
+    /// Whether higher or lower values rank first (default: the objective's mode, else the
+    /// direction recorded with `og describe`, else max)
+    #[arg(long, value_enum)]
+    mode: Option<LeaderboardMode>,
+    /// Number of runs to show
+    #[arg(long, default_value_t = 10)]
+    top: usize,
+    /// Comma-separated hparam columns (default: every recorded config key)
+    #[arg(long, value_delimiter = ',')]
+    hparams: Vec<String>,
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    /// Runs parsed in parallel (default: available cores, at most 8)
+    #[arg(long, env = "OG_LOAD_JOBS")]
+    jobs: Option<usize>,
+}
+
+#[derive(Debug, Clone, Args)]
+struct StatsArgs {
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    #[arg(long)]
+    run: String,
     #[arg(long)]
     metric: String,
+    /// Trailing points used for the slope, plateau and divergence checks
+    #[arg(long, default_value_t = metric_stats::DEFAULT_WINDOW)]
+    window: usize,
+}
+
+#[derive(Debug, Clone, Args)]
+struct RegressArgs {
+    /// Known-good run id or path
+    #[arg(long)]
+    baseline: String,
+    /// Run id or path checked against the baseline
+    #[arg(long)]
+    candidate: String,
+    /// Metric to check (default: the project's objective metric)
+    #[arg(long)]
+    metric: Option<String>,
+    /// How much worse the candidate may be: relative ("1%") or absolute ("0.05")
+    #[arg(long, default_value = "1%")]
+    tolerance: regression::Tolerance,
+    /// Trailing matched steps the check averages over
+    #[arg(long, default_value_t = regression::DEFAULT_WINDOW)]
+    window: usize,
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+}
+
+#[derive(Debug, Clone, Args)]
+struct DigestArgs {
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    /// Project to summarize; with --scheduled, every project under --path by default
+    #[arg(long)]
+    project: Option<String>,
+    /// Hours the digest looks back over
+    #[arg(long, default_value_t = 24)]
+    hours: u64,
+    /// Deliver the digest to the project's [digest] webhook or SMTP server
+    #[arg(long)]
+    send: bool,
+    /// Stay in the foreground, sending each project's digest on its [digest] schedule
+    #[arg(long, conflicts_with = "send")]
+    scheduled: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+struct DiffArgs {
+    /// Baseline run id or path
+    run_a: String,
+    /// Run id or path to compare against the baseline
+    run_b: String,
     #[arg(long, default_value = "runs/")]
     path: PathBuf,
     #[arg(long)]
@@ -323,6 +787,19 @@ struct SearchArgs {
 enum SearchSubcommand {
     /// Search metric names
     Metrics(SearchMetricsArgs),
+    /// Search runs by hyperparameter predicates
+    Runs(SearchRunsArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+struct SearchRunsArgs {
+    /// Predicate over run metadata, e.g. "lr<1e-3 and optimizer=adamw"
+    #[arg(long = "where")]
+    where_clause: String,
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
 }
 
 #[derive(Debug, Clone, Args)]
@@ -333,845 +810,5111 @@ struct SearchMetricsArgs {
     path: PathBuf,
     #[arg(long)]
     project: Option<String>,
+    /// Runs parsed in parallel (default: available cores, at most 8)
+    #[arg(long, env = "OG_LOAD_JOBS")]
+    jobs: Option<usize>,
 }
 
-#[derive(Debug, Clone, Subcommand)]
-enum OgCommand {
-    /// Launch run in TUI
-    Run(RunArgs),
-    /// Tail logs/event stream
-    Tail(TailArgs),
-    /// Resolve resume checkpoint info
-    Resume(ResumeArgs),
-    /// List entities
-    List(ListArgs),
-    /// Get details
-    Get(GetArgs),
-    /// Compare metric across runs
-    Compare(CompareArgs),
-    /// Search entities
-    Search(SearchArgs),
+#[derive(Debug, Clone, Args)]
+struct AnnotateArgs {
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    #[arg(long)]
+    run: String,
+    /// Step the annotation is attached to
+    #[arg(long)]
+    step: i64,
+    /// Annotation category
+    #[arg(long, value_enum, default_value = "note")]
+    kind: run_meta::AnnotationKind,
+    /// Annotation text
+    message: String,
 }
 
-/// OpenGraphs command surface.
-#[derive(Parser, Debug, Clone)]
-#[command(name = "og", version, about = "OpenGraphs CLI + TUI")]
-struct Cli {
-    /// Output JSON instead of plain text
-    #[arg(long, global = true)]
-    json: bool,
+#[derive(Debug, Clone, Args)]
+struct SetArgs {
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    #[arg(long)]
+    run: String,
+    /// Parameters to change (e.g. lr=1e-4)
+    #[arg(value_name = "KEY=VALUE", required = true)]
+    assignments: Vec<String>,
+    /// Step the change is annotated at (default: the run's latest step)
+    #[arg(long)]
+    step: Option<i64>,
+    /// Set keys the training script did not declare live-tunable
+    #[arg(long)]
+    force: bool,
+}
 
+#[derive(Debug, Clone, Args)]
+struct NoteArgs {
     #[command(subcommand)]
-    command: Option<OgCommand>,
+    cmd: NoteSubcommand,
+}
 
-    #[command(flatten)]
-    tui: TuiArgs,
+#[derive(Debug, Clone, Subcommand)]
+enum NoteSubcommand {
+    /// Append a note to a run's journal
+    Add(NoteAddArgs),
+    /// List a run's notes, oldest first
+    List(NoteListArgs),
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct CommandOutput {
-    command: String,
-    data: Value,
+#[derive(Debug, Clone, Args)]
+struct NoteAddArgs {
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    #[arg(long)]
+    run: String,
+    /// Step the note refers to (defaults to the run's latest step)
+    #[arg(long)]
+    step: Option<i64>,
+    /// Note text
     text: String,
 }
 
-struct ViewData {
-    scalars: BTreeMap<String, Vec<(f64, f64)>>,
-    log_lines: Vec<String>,
-    total_events: usize,
-    max_step: i64,
+#[derive(Debug, Clone, Args)]
+struct NoteListArgs {
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    #[arg(long)]
+    run: String,
 }
 
-fn load_view_data(path: &Path) -> Result<ViewData> {
-    let loaded = tfevents::load_run(path)
-        .with_context(|| format!("loading events from {}", path.display()))?;
-
-    let mut sorted_events = loaded.events;
-    sorted_events.sort_by_key(|e| e.step);
-    let total_events = sorted_events.len();
-    let max_step = sorted_events.iter().map(|e| e.step).max().unwrap_or(0);
-
-    let mut log_lines = vec!["-- parsed events log --".to_string(), String::new()];
-    for ev in &sorted_events {
-        log_lines.push(format!(
-            "step {:>6} │ {:<30} │ {:.6}",
-            ev.step, ev.tag, ev.value
-        ));
-    }
+#[derive(Debug, Clone, Args)]
+struct TagArgs {
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    #[arg(long)]
+    run: String,
+    /// Tags to add (or remove with --remove)
+    #[arg(value_name = "TAG", required = true)]
+    tags: Vec<String>,
+    #[arg(long)]
+    remove: bool,
+}
 
-    Ok(ViewData {
-        scalars: loaded.scalars,
-        log_lines,
-        total_events,
-        max_step,
-    })
+#[derive(Debug, Clone, Args)]
+struct AliasArgs {
+    #[command(subcommand)]
+    cmd: AliasSubcommand,
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
-    if let Some(command) = cli.command.clone() {
-        return execute_cli_command(command, cli.json);
-    }
+#[derive(Debug, Clone, Subcommand)]
+enum AliasSubcommand {
+    /// Point a short name at a run, usable wherever a run is expected
+    Set(AliasSetArgs),
+    /// List a project's aliases and what `latest` is now
+    List(AliasListArgs),
+    /// Forget an alias
+    Rm(AliasRmArgs),
+}
 
-    let clean_start = cli.tui.path.is_none();
-    let graph_filter = cli
-        .tui
-        .graph
-        .as_deref()
-        .map(parse_graph_filter)
-        .transpose()?;
-    run_tui(&cli.tui, None, graph_filter, clean_start)
+#[derive(Debug, Clone, Args)]
+struct AliasSetArgs {
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    /// Alias name
+    name: String,
+    /// Run it stands for (`latest` pins the current newest run)
+    run: String,
 }
 
-fn execute_cli_command(command: OgCommand, json: bool) -> Result<()> {
+#[derive(Debug, Clone, Args)]
+struct AliasListArgs {
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+}
+
+#[derive(Debug, Clone, Args)]
+struct AliasRmArgs {
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    /// Alias name
+    name: String,
+}
+
+#[derive(Debug, Clone, Args)]
+struct DescribeArgs {
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    #[arg(long)]
+    run: String,
+    /// Metric tag the attributes apply to
+    #[arg(long)]
+    metric: String,
+    /// Unit of the values, shown on axes and next to values (e.g. %, s, tok/s)
+    #[arg(long)]
+    unit: Option<String>,
+    /// Larger values are better (best-value arrows, leaderboard order)
+    #[arg(long, conflicts_with = "lower_is_better")]
+    higher_is_better: bool,
+    /// Smaller values are better
+    #[arg(long)]
+    lower_is_better: bool,
+    /// Axis scale the metric is charted on
+    #[arg(long, value_enum)]
+    scale: Option<run_meta::Scale>,
+    /// Name shown instead of the tag
+    #[arg(long)]
+    display_name: Option<String>,
+    /// Lowest and highest possible value, e.g. 0,1 for accuracy
+    #[arg(long, value_name = "LO,HI", value_parser = parse_bounds)]
+    bounds: Option<[f64; 2]>,
+    /// Draw the metric's card as a line chart or as a gauge between its bounds
+    #[arg(long, value_enum)]
+    card: Option<run_meta::Card>,
+    /// Drop every attribute recorded for the metric
+    #[arg(long)]
+    clear: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+struct ForkArgs {
+    /// Run id (or path) to fork
+    run: String,
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    /// Config override for the new run (repeatable)
+    #[arg(long = "set", value_name = "KEY=VALUE")]
+    set: Vec<String>,
+    /// Id of the new run (default: <run>-fork-<unix time>)
+    #[arg(long)]
+    name: Option<String>,
+    /// Launch the new run in the TUI with the source run's recorded training file
+    #[arg(long)]
+    launch: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+struct CompactArgs {
+    /// Run to compact (default: every finished run under --path / --project)
+    #[arg(long)]
+    run: Option<String>,
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    #[arg(long, value_enum, default_value = "gzip")]
+    codec: compression::Codec,
+    /// Also rewrite scalar tags keeping one point per N (see --rollup-mode)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(2..))]
+    rollup: Option<u64>,
+    /// How each bucket of N points is reduced when rolling up
+    #[arg(long, value_enum, default_value = "every")]
+    rollup_mode: rollup::RollupMode,
+    /// Tag kept at full resolution by --rollup (repeatable; `prefix/*` matches a prefix)
+    #[arg(long = "keep", value_name = "TAG")]
+    keep: Vec<String>,
+    /// Report what would be compressed without touching any file
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+struct GcArgs {
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    /// Delete untagged runs last written more than DAYS days ago
+    #[arg(long, value_name = "DAYS")]
+    older_than: Option<u64>,
+    /// Keep only the newest N registered checkpoints of each run
+    #[arg(long, value_name = "N")]
+    keep_checkpoints: Option<usize>,
+    /// Report what would be deleted without touching any file
+    #[arg(long)]
+    dry_run: bool,
+    /// Also delete registered checkpoints that live outside the run directory
+    #[arg(long)]
+    external_checkpoints: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+struct ExportArgs {
+    #[command(subcommand)]
+    cmd: ExportSubcommand,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum ExportSubcommand {
+    /// Write a run's audio summaries out as audio files
+    Audio(ExportAudioArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+struct ExportAudioArgs {
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    #[arg(long)]
+    run: String,
+    /// Audio tag to export (default: every audio tag)
+    #[arg(long)]
+    tag: Option<String>,
+    /// Directory the clips are written to
+    #[arg(long)]
+    out: PathBuf,
+}
+
+#[derive(Debug, Clone, Args)]
+struct BundleArgs {
+    #[command(subcommand)]
+    cmd: BundleSubcommand,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum BundleSubcommand {
+    /// Archive a run's events, metadata, notes, logs and chosen checkpoints
+    Create(BundleCreateArgs),
+    /// Unpack a bundle into a project as a new run
+    Import(BundleImportArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+struct BundleCreateArgs {
+    /// Run id or path
+    run: String,
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    /// Archive to write; `.tar.zst` and `.tar.gz` are compressed (default: <run>.tar.zst)
+    #[arg(short, long)]
+    out: Option<PathBuf>,
+    /// Checkpoints to include: none, latest, all, or ids/steps separated by commas
+    #[arg(long, default_value = "latest")]
+    checkpoints: bundle::CheckpointSelection,
+}
+
+#[derive(Debug, Clone, Args)]
+struct BundleImportArgs {
+    /// Bundle written by `og bundle create`
+    file: PathBuf,
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    /// Run name to import as (default: the bundled run's name)
+    #[arg(long)]
+    name: Option<String>,
+}
+
+#[derive(Debug, Clone, Args)]
+struct ImportArgs {
+    #[command(subcommand)]
+    cmd: ImportSubcommand,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum ImportSubcommand {
+    /// Convert a CSV metrics log (header row, one step per row) into a run
+    Csv(ImportCsvArgs),
+    /// Bring the runs of a TensorBoard log dir into a project
+    Tensorboard(ImportTensorboardArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+struct ImportTensorboardArgs {
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    /// TensorBoard log dir; every directory in it holding event files becomes a run
+    #[arg(long)]
+    logdir: PathBuf,
+    /// Symlink the event files instead of copying them
+    #[arg(long)]
+    link: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+struct ImportCsvArgs {
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    /// CSV file to import
+    #[arg(long)]
+    file: PathBuf,
+    /// Run id to create
+    #[arg(long)]
+    run: String,
+    /// Column holding the step
+    #[arg(long, default_value = "step")]
+    step_column: String,
+    /// Column holding unix wall time in seconds (default: the file's mtime for every row)
+    #[arg(long)]
+    time_column: Option<String>,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum OgCommand {
+    /// Create a project: runs directory, project.toml, sample alerts, logging snippet
+    Init(InitArgs),
+    /// Check the daemon, Python agent, GPU tools, runs dir and terminal, with fixes
+    Doctor(DoctorArgs),
+    /// Run the agent daemon in the background, independent of the TUI
+    Daemon(DaemonArgs),
+    /// Reopen the TUI on a run whose daemon was left running by `--detach`
+    Attach(AttachArgs),
+    /// Find ogd instances advertising themselves on the LAN over mDNS
+    Discover(DiscoverArgs),
+    /// Launch run in TUI
+    Run(Box<RunArgs>),
+    /// Tail logs/event stream
+    Tail(TailArgs),
+    /// Kubernetes pod events of a run (OOMKilled, eviction, restarts)
+    K8s(K8sArgs),
+    /// Resolve resume checkpoint info
+    Resume(ResumeArgs),
+    /// List entities
+    List(ListArgs),
+    /// Get details
+    Get(GetArgs),
+    /// Compare metric across runs
+    Compare(CompareArgs),
+    /// Diff config, git state and environment between two runs
+    Diff(DiffArgs),
+    /// Fail when a run is worse than a baseline run at matched steps
+    Regress(RegressArgs),
+    /// Rank a project's runs by the best value of a metric
+    Leaderboard(LeaderboardArgs),
+    /// Distribution and trend statistics for one metric of a run
+    Stats(StatsArgs),
+    /// Summarize a project's recent runs, or send it on the project's [digest] schedule
+    Digest(DigestArgs),
+    /// Search entities
+    Search(SearchArgs),
+    /// Attach an annotation to a run's metric timeline
+    Annotate(AnnotateArgs),
+    /// Change live-tunable parameters of a running job
+    Set(SetArgs),
+    /// Per-run experiment journal
+    Note(NoteArgs),
+    /// Add or remove labels on a run
+    Tag(TagArgs),
+    /// Short names for runs; `latest` always names the newest run
+    Alias(AliasArgs),
+    /// Record a metric's unit, direction and display hints for a run
+    Describe(DescribeArgs),
+    /// Copy a run's config into a new run, with overrides
+    Fork(ForkArgs),
+    /// Compress the event files of finished runs in place
+    Compact(CompactArgs),
+    /// Report disk usage per run and apply retention policies
+    Gc(GcArgs),
+    /// Convert external metric logs into runs
+    Import(ImportArgs),
+    /// Write non-scalar summaries of a run out as files
+    Export(ExportArgs),
+    /// Pack a run into one archive for a collaborator, or unpack one
+    Bundle(BundleArgs),
+    /// Apply a unified diff to the codebase, snapshotting touched files first
+    Apply(ApplyArgs),
+    /// Undo a local apply from its snapshot
+    Rollback(RollbackArgs),
+    /// Render the graphs view of a run to text, without a terminal
+    Snapshot(SnapshotArgs),
+    /// Play a session recorded with --record back into the TUI
+    Replay(ReplayArgs),
+    /// Answer read-only queries (list/get/compare/...) from the agent over a unix socket
+    ServeQueries(ServeQueriesArgs),
+    /// Print a shell completion script
+    Completions(CompletionsArgs),
+    /// Print the og(1) man page, or write a page per subcommand with --out
+    Man(ManArgs),
+    /// Time the event-file reader (parse, poll, downsample) on a file
+    #[command(hide = true)]
+    Bench(BenchArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+struct InitArgs {
+    /// Root runs directory the project is created in
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    /// Project name (default: the current directory's name)
+    #[arg(long)]
+    project: Option<String>,
+    /// Objective metric written to project.toml, e.g. val/loss
+    #[arg(long)]
+    objective: Option<String>,
+    /// Whether lower or higher objective values are better (default: guessed from the name)
+    #[arg(long, value_enum, requires = "objective")]
+    mode: Option<project_config::ObjectiveMode>,
+    /// Also write a Python snippet that logs metrics for og
+    #[arg(long, value_name = "FILE", num_args = 0..=1,
+        default_missing_value = scaffold::PYTHON_FILE_NAME)]
+    python: Option<PathBuf>,
+    /// Ask no questions; use the flags and defaults
+    #[arg(short = 'y', long)]
+    yes: bool,
+    /// Overwrite files a previous init wrote
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+struct DoctorArgs {
+    /// Root runs directory to check
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    /// Codebase root whose .venv and python/agent-chat `og run` would use
+    #[arg(long, default_value = ".")]
+    codebase_root: PathBuf,
+    /// Unix socket path for daemon communication
+    #[arg(long, env = "OGD_SOCKET")]
+    socket: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Args)]
+struct DaemonArgs {
+    #[command(subcommand)]
+    cmd: DaemonSubcommand,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum DaemonSubcommand {
+    /// Start the daemon in the background, with a pidfile and a log next to its socket
+    Start(DaemonStartArgs),
+    /// Stop the daemon `og daemon start` started
+    Stop(DaemonStopArgs),
+    /// Show whether a daemon runs and answers on the socket
+    Status(DaemonSocketArgs),
+    /// Stop the daemon and start it again the way it was started
+    Restart(DaemonStopArgs),
+    /// Print the daemon's log
+    Logs(DaemonLogsArgs),
+    /// List the daemons running on this machine and the runs they supervise
+    List,
+}
+
+#[derive(Debug, Clone, Args, Serialize, Deserialize)]
+struct DaemonStartArgs {
+    /// Training script the agent watches and launches
+    #[arg(long)]
+    training_file: PathBuf,
+    /// Command used to launch training process
+    #[arg(long)]
+    training_cmd: Option<String>,
+    /// Launch training as soon as the daemon is up
+    #[arg(long)]
+    start_training: bool,
+    /// Run directory, or a project directory to create a new run in
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    /// Codebase root for agent indexing
+    #[arg(long, default_value = ".")]
+    codebase_root: PathBuf,
+    /// Runtime backend for training control
+    #[arg(long, value_enum, default_value = "local")]
+    runtime: RuntimeArg,
+    /// Let the agent apply fixes on its own
+    #[arg(long)]
+    auto: bool,
+    /// Unix socket path for daemon communication
+    #[arg(long, env = "OGD_SOCKET")]
+    socket: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Args)]
+struct DaemonStopArgs {
+    /// Seconds to wait after SIGTERM before sending SIGKILL [default: the
+    /// training stop timeout, OG_STOP_TIMEOUT_SECS (30), plus 10]
+    #[arg(long)]
+    timeout_secs: Option<u64>,
+    /// Unix socket path for daemon communication
+    #[arg(long, env = "OGD_SOCKET")]
+    socket: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Args)]
+struct AttachArgs {
+    /// Run id (or path) of the detached run (default: the only one running)
+    run: Option<String>,
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    /// Unix socket of the daemon to attach to (default: any managed daemon next to the default socket)
+    #[arg(long)]
+    socket: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Args)]
+struct DiscoverArgs {
+    /// How long to wait for answers, in milliseconds
+    #[arg(long, default_value_t = 2000)]
+    timeout_ms: u64,
+}
+
+#[derive(Debug, Clone, Args)]
+struct DaemonSocketArgs {
+    /// Unix socket path for daemon communication
+    #[arg(long, env = "OGD_SOCKET")]
+    socket: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Args)]
+struct DaemonLogsArgs {
+    /// Number of lines to print from the end of the log
+    #[arg(long, default_value_t = 100)]
+    lines: usize,
+    /// Keep printing lines as the daemon writes them
+    #[arg(short, long)]
+    follow: bool,
+    /// Unix socket path for daemon communication
+    #[arg(long, env = "OGD_SOCKET")]
+    socket: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Args)]
+struct CompletionsArgs {
+    /// Shell to complete for, e.g. `og completions zsh > ~/.zfunc/_og`
+    #[arg(value_enum)]
+    shell: clap_complete::Shell,
+}
+
+#[derive(Debug, Clone, Args)]
+struct ManArgs {
+    /// Directory to write og.1 and one og-<subcommand>.1 page per subcommand into
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Args)]
+struct ReplayArgs {
+    /// Session file written by --record
+    file: PathBuf,
+    /// Playback speed multiplier (2 plays twice as fast)
+    #[arg(long, default_value_t = 1.0)]
+    speed: f64,
+    /// Metric filter, same syntax as the TUI's --graph
+    #[arg(long)]
+    graph: Option<String>,
+}
+
+#[derive(Debug, Clone, Args)]
+struct SnapshotArgs {
+    /// Runs directory, run directory or event file
+    #[arg(long, default_value = "runs/")]
+    path: PathBuf,
+    #[arg(long)]
+    project: Option<String>,
+    /// Run to render (default: everything under --path, as the TUI loads it)
+    #[arg(long)]
+    run: Option<String>,
+    /// Render this metric fullscreen instead of the graphs grid
+    #[arg(long)]
+    metric: Option<String>,
+    /// Metric filter, same syntax as the TUI's --graph
+    #[arg(long)]
+    graph: Option<String>,
+    #[arg(long, default_value_t = 120)]
+    width: u16,
+    #[arg(long, default_value_t = 40)]
+    height: u16,
+    /// Keep colors and bold as ANSI escapes
+    #[arg(long)]
+    ansi: bool,
+    /// Write the snapshot to a file instead of stdout
+    #[arg(long, short = 'o')]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Args)]
+struct BenchArgs {
+    #[command(subcommand)]
+    cmd: BenchSubcommand,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum BenchSubcommand {
+    /// Benchmark parsing one event file
+    Parse(BenchParseArgs),
+    /// Write a synthetic event file to benchmark against
+    Gen(BenchGenArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+struct BenchParseArgs {
+    /// Event file to read (.tfevents, optionally .gz / .zst)
+    file: PathBuf,
+    #[arg(long, default_value_t = 5)]
+    iterations: usize,
+    /// Records decoded by the incremental-poll benchmark
+    #[arg(long, default_value_t = 1000)]
+    poll_points: usize,
+}
+
+#[derive(Debug, Clone, Args)]
+struct BenchGenArgs {
+    /// Output event file
+    file: PathBuf,
+    #[arg(long, default_value_t = 1_000_000)]
+    events: usize,
+    #[arg(long, default_value_t = 500)]
+    tags: usize,
+}
+
+#[derive(Debug, Clone, Args)]
+struct ApplyArgs {
+    /// Diff file to apply ('-' reads stdin)
+    diff: PathBuf,
+    /// Directory the diff's paths are relative to
+    #[arg(long, default_value = ".")]
+    codebase_root: PathBuf,
+    /// Snapshot directory (relative paths are under --codebase-root)
+    #[arg(long, default_value = ".og_checkpoints")]
+    checkpoint_dir: PathBuf,
+    /// Check that every hunk applies without writing anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+struct RollbackArgs {
+    /// Snapshot id or 'latest' (the newest apply not rolled back yet)
+    #[arg(default_value = "latest")]
+    checkpoint: String,
+    #[arg(long, default_value = ".")]
+    codebase_root: PathBuf,
+    /// Snapshot directory (relative paths are under --codebase-root)
+    #[arg(long, default_value = ".og_checkpoints")]
+    checkpoint_dir: PathBuf,
+}
+
+#[derive(Debug, Clone, Args)]
+struct ServeQueriesArgs {
+    /// Socket to listen on (default: next to the daemon socket)
+    #[arg(long, env = "OG_QUERY_SOCKET")]
+    socket: Option<PathBuf>,
+}
+
+/// OpenGraphs command surface.
+#[derive(Parser, Debug, Clone)]
+#[command(name = "og", version, about = "OpenGraphs CLI + TUI")]
+struct Cli {
+    /// Output JSON instead of plain text
+    #[arg(long, global = true, conflicts_with_all = ["porcelain", "quiet"])]
+    json: bool,
+
+    /// Output stable `path<TAB>value` lines for scripts
+    #[arg(long, global = true, conflicts_with = "quiet")]
+    porcelain: bool,
+
+    /// Print nothing; check the exit code
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    #[command(subcommand)]
+    command: Option<OgCommand>,
+
+    #[command(flatten)]
+    tui: TuiArgs,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct CommandOutput {
+    command: String,
+    data: Value,
+    text: String,
+}
+
+struct ViewData {
+    scalars: BTreeMap<String, Vec<(f64, f64)>>,
+    log_lines: Vec<LogEntry>,
+    total_events: usize,
+    max_step: i64,
+    /// Distributed ranks found under the run (empty for single-process runs).
+    ranks: Vec<u32>,
+    rank_progress: BTreeMap<u32, tfevents::RankProgress>,
+    /// Seconds between the first and last event (None without wall times).
+    wall_secs: Option<f64>,
+    /// Long wall-clock pauses between events.
+    gaps: Vec<timeline::Gap>,
+    /// Latest hardware readings and step rate, taken before any graph filter.
+    sys_strip: sys_strip::SysStrip,
+    /// Step to epoch mapping, taken before any graph filter hides the counter.
+    epoch_scale: Option<epoch::EpochScale>,
+}
+
+fn load_view_data(path: &Path) -> Result<ViewData> {
+    load_view_data_for_rank(path, None)
+}
+
+/// Like `load_view_data`, but shows one rank's series when `rank` is set.
+#[tracing::instrument(name = "load_view", level = "debug", skip_all, fields(path = %path.display(), ?rank), err)]
+fn load_view_data_for_rank(path: &Path, rank: Option<u32>) -> Result<ViewData> {
+    let loaded = tfevents::load_run(path)
+        .with_context(|| format!("loading events from {}", path.display()))?;
+    let ranks: Vec<u32> = loaded.ranks.keys().copied().collect();
+    let scalars = loaded.view(rank).clone();
+    let sys_strip = sys_strip::SysStrip::read(&scalars, &loaded.events);
+    let epoch_scale =
+        epoch::EpochScale::for_run(&scalars, &run_meta::load(path).unwrap_or_default());
+    let rank_progress = loaded.rank_progress;
+    let corruption = loaded.corruption;
+    let wall_secs = cost::wall_secs(&loaded.events);
+    let gaps = timeline::gaps(&loaded.events);
+
+    let mut sorted_events = loaded.events;
+    sorted_events.sort_by_key(|e| e.step);
+    let total_events = sorted_events.len();
+    let max_step = sorted_events.iter().map(|e| e.step).max().unwrap_or(0);
+
+    let mut log_lines = vec![
+        LogEntry::new(LogLevel::Info, LogSource::Events, "-- parsed events log --"),
+        LogEntry::new(LogLevel::Info, LogSource::Events, ""),
+    ];
+    if !corruption.is_clean() {
+        log_lines.push(LogEntry::new(
+            LogLevel::Warn,
+            LogSource::Events,
+            describe_corruption(&corruption),
+        ));
+    }
+    log_lines.extend(sorted_events.iter().map(event_log_entry));
+
+    Ok(ViewData {
+        scalars,
+        log_lines,
+        total_events,
+        max_step,
+        ranks,
+        rank_progress,
+        wall_secs,
+        gaps,
+        sys_strip,
+        epoch_scale,
+    })
+}
+
+/// `recovered 2 damaged event files: 3 resyncs, 120 bytes skipped, 40 truncated`.
+/// One parsed event as a logs-pane row, stamped with its wall time.
+fn event_log_entry(event: &tfevents::ScalarEvent) -> LogEntry {
+    LogEntry {
+        timestamp: (event.wall_time > 0.0).then_some(event.wall_time),
+        ..LogEntry::new(
+            LogLevel::Info,
+            LogSource::Events,
+            format!(
+                "step {:>6} │ {:<30} │ {:.6}",
+                event.step, event.tag, event.value
+            ),
+        )
+    }
+}
+
+fn describe_corruption(stats: &tfevents::CorruptionStats) -> String {
+    format!(
+        "recovered {} damaged event file{}: {} resync{}, {} bytes skipped, {} truncated",
+        stats.damaged_files,
+        if stats.damaged_files == 1 { "" } else { "s" },
+        stats.resyncs,
+        if stats.resyncs == 1 { "" } else { "s" },
+        stats.skipped_bytes,
+        stats.truncated_bytes
+    )
+}
+
+/// How a command prints its result and its errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputMode {
+    Text,
+    Json,
+    Porcelain,
+    Quiet,
+}
+
+impl Cli {
+    fn output_mode(&self) -> OutputMode {
+        if self.json {
+            OutputMode::Json
+        } else if self.porcelain {
+            OutputMode::Porcelain
+        } else if self.quiet {
+            OutputMode::Quiet
+        } else {
+            OutputMode::Text
+        }
+    }
+}
+
+/// Exit code for a command line clap rejects, kept apart from the codes in
+/// `OgError::exit_code` (sysexits' `EX_USAGE`).
+const USAGE_EXIT_CODE: u8 = 64;
+
+fn main() -> ExitCode {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) => {
+            let _ = err.print();
+            return if err.use_stderr() {
+                ExitCode::from(USAGE_EXIT_CODE)
+            } else {
+                ExitCode::SUCCESS
+            };
+        }
+    };
+    match run_cli(&cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => report_error(&err, cli.output_mode()),
+    }
+}
+
+fn run_cli(cli: &Cli) -> Result<()> {
+    format::init(format::load()?);
+    if let Some(command) = cli.command.clone() {
+        return execute_cli_command(command, cli.output_mode());
+    }
+
+    let clean_start = cli.tui.path.is_none();
+    run_tui(&cli.tui, None, clean_start)
+}
+
+/// Print a failed command's error with its hint, on stdout in the command's
+/// format under `--json` and `--porcelain`, and pick the exit code: the typed
+/// error's own, else 1.
+fn report_error(err: &anyhow::Error, mode: OutputMode) -> ExitCode {
+    let typed = typed_error(err);
+    let hint = typed.as_ref().and_then(OgError::hint);
+    let code = typed.as_ref().map_or(1, OgError::exit_code);
+    let output = CommandOutput {
+        command: "error".to_string(),
+        data: serde_json::json!({
+            "error": format!("{err:#}"),
+            "kind": typed.as_ref().map_or("error", OgError::kind),
+            "hint": hint,
+            "exit_code": code,
+        }),
+        text: String::new(),
+    };
+    match mode {
+        OutputMode::Json | OutputMode::Porcelain => {
+            let _ = print_command_output(&output, mode);
+        }
+        OutputMode::Text => {
+            eprintln!("Error: {err:?}");
+            if let Some(hint) = hint {
+                eprintln!("hint: {hint}");
+            }
+        }
+        OutputMode::Quiet => {}
+    }
+    ExitCode::from(code)
+}
+
+/// The typed error in `err`, as its context or anywhere in its chain. A
+/// daemon socket that is missing or refuses connections counts as
+/// `DaemonUnreachable`.
+fn typed_error(err: &anyhow::Error) -> Option<OgError> {
+    if let Some(typed) = err.downcast_ref::<OgError>() {
+        return Some(typed.clone());
+    }
+    err.chain().find_map(|cause| {
+        cause.downcast_ref::<OgError>().cloned().or_else(|| {
+            cause
+                .downcast_ref::<socket_client::ClientError>()
+                .and_then(socket_client::ClientError::as_unreachable)
+        })
+    })
+}
+
+fn execute_cli_command(command: OgCommand, mode: OutputMode) -> Result<()> {
+    match command {
+        OgCommand::Run(args) => {
+            let tui = run_args_to_tui(&args);
+            run_tui(&tui, args.prompt.clone(), false)
+        }
+        OgCommand::Fork(args) if args.launch => {
+            let forked = fork_run(&args)?;
+            let Some(launch) = forked.meta.launch.clone() else {
+                bail!(
+                    "forked {} but '{}' has no recorded training file to launch",
+                    forked.path.display(),
+                    forked.source_id
+                );
+            };
+            let tui = TuiArgs {
+                training_file: Some(launch.training_file),
+                training_cmd: launch.training_cmd,
+                start_training: true,
+                codebase_root: launch.codebase_root,
+                ..default_tui_args(forked.path.clone())
+            };
+            run_tui(&tui, None, false)
+        }
+        OgCommand::Attach(args) => {
+            let (paths, launch) = find_attach_target(&args)?;
+            let tui = TuiArgs {
+                socket: Some(paths.socket),
+                codebase_root: launch.codebase_root,
+                ..default_tui_args(launch.path)
+            };
+            run_tui(&tui, None, false)
+        }
+        OgCommand::Replay(args) => run_replay(&args),
+        OgCommand::Init(args) if !args.yes && io::stdin().is_terminal() => {
+            let plan = scaffold::ask(&mut io::stdin().lock(), &mut io::stderr(), init_plan(&args))?;
+            let output = create_project(&args, &plan)?;
+            print_command_output(&output, mode)
+        }
+        OgCommand::Daemon(DaemonArgs {
+            cmd: DaemonSubcommand::Logs(args),
+        }) if args.follow => follow_daemon_log(&args),
+        OgCommand::Completions(args) => write_completions(args.shell, &mut io::stdout()),
+        OgCommand::Man(ManArgs { out: None }) => write_man_page(&mut io::stdout()),
+        OgCommand::Man(ManArgs { out: Some(dir) }) => {
+            let pages = write_man_pages(&dir)?;
+            if mode != OutputMode::Quiet {
+                eprintln!("wrote {pages} man pages to {}", dir.display());
+            }
+            Ok(())
+        }
+        OgCommand::Digest(args) if args.scheduled => run_digest_schedule(&args),
+        OgCommand::Regress(args) => {
+            let output = execute_regress(args)?;
+            print_command_output(&output, mode)?;
+            if output.data["regressed"] == true {
+                bail!(
+                    "{} regressed on {}",
+                    output.data["candidate"].as_str().unwrap_or("candidate"),
+                    output.data["metric"].as_str().unwrap_or("the metric")
+                );
+            }
+            Ok(())
+        }
+        OgCommand::ServeQueries(args) => {
+            let socket = args
+                .socket
+                .unwrap_or_else(|| query_server::socket_path_for(&socket_client::socket_path()));
+            eprintln!("serving queries on {}", socket.display());
+            query_server::serve(&socket, Arc::new(run_agent_query))
+        }
+        other => {
+            let output = execute_query_command(other)?;
+            print_command_output(&output, mode)
+        }
+    }
+}
+
+fn write_completions(shell: clap_complete::Shell, out: &mut impl Write) -> Result<()> {
+    clap_complete::generate(shell, &mut Cli::command(), "og", out);
+    Ok(())
+}
+
+fn write_man_page(out: &mut impl Write) -> Result<()> {
+    clap_mangen::Man::new(Cli::command())
+        .render(out)
+        .context("rendering man page")
+}
+
+/// Write `og.1` and a page for every visible subcommand, nested ones
+/// included (`og-list-runs.1`). Returns how many pages were written.
+fn write_man_pages(dir: &Path) -> Result<usize> {
+    fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    clap_mangen::generate_to(Cli::command(), dir)
+        .with_context(|| format!("writing man pages to {}", dir.display()))?;
+    Ok(fs::read_dir(dir)?
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "1"))
+        .count())
+}
+
+/// Query entry point for the agent: `argv` as typed after `og`, answered with
+/// the same payload `og --json` prints.
+fn run_agent_query(argv: &[String]) -> Result<Value> {
+    let cli = Cli::try_parse_from(std::iter::once("og".to_string()).chain(argv.iter().cloned()))?;
+    let Some(command) = cli.command else {
+        bail!("missing command");
+    };
+    Ok(serde_json::to_value(execute_query_command(command)?)?)
+}
+
+/// The TUI flags' defaults (honouring their env vars) for commands that open
+/// the TUI on a run without going through the top-level flags.
+fn default_tui_args(path: PathBuf) -> TuiArgs {
+    TuiArgs {
+        path: Some(path),
+        graph: None,
+        graph_strict: false,
+        graph_labels: std::env::var("OG_GRAPH_LABELS").ok(),
+        training_file: None,
+        training_cmd: None,
+        start_training: false,
+        fresh_run: false,
+        detach: false,
+        codebase_root: PathBuf::from("."),
+        auto: false,
+        runtime: RuntimeArg::Local,
+        socket: std::env::var_os("OGD_SOCKET").map(PathBuf::from),
+        refresh_ms: 1000,
+        procs_sort: ProcessSort::Cpu,
+        procs_interval_ms: 1000,
+        procs_limit: 300,
+        log_lines: std::env::var("OG_LOG_LINES")
+            .ok()
+            .and_then(|raw| raw.parse().ok())
+            .unwrap_or(log_buffer::DEFAULT_LOG_LINES),
+        chat_messages: std::env::var("OG_CHAT_MESSAGES")
+            .ok()
+            .and_then(|raw| raw.parse().ok())
+            .unwrap_or(log_buffer::DEFAULT_CHAT_MESSAGES),
+        notify: NotifyMode::Off,
+        run_name_template: run_name::DEFAULT_TEMPLATE.to_string(),
+        git_patch: false,
+        record: None,
+        debug_log: std::env::var_os("OG_DEBUG_LOG").map(PathBuf::from),
+        max_fps: std::env::var("OG_MAX_FPS")
+            .ok()
+            .and_then(|raw| raw.parse().ok())
+            .unwrap_or(redraw::DEFAULT_MAX_FPS),
+        no_idle_backoff: false,
+        run_size_warn: app::DEFAULT_RUN_SIZE_WARN_BYTES,
+        min_free_space: app::DEFAULT_MIN_FREE_BYTES,
+        anomaly_metrics: vec!["loss".to_string()],
+        anomaly_z: anomaly::DEFAULT_Z_THRESHOLD,
+        early_stop_metric: None,
+        early_stop_mode: None,
+        early_stop_patience: convergence::DEFAULT_PATIENCE,
+        early_stop_min_delta: convergence::DEFAULT_MIN_DELTA,
+        grad_explode: grad_health::DEFAULT_EXPLODE,
+        grad_vanish: grad_health::DEFAULT_VANISH,
+        grad_vanish_points: grad_health::DEFAULT_VANISH_POINTS,
+        sys_interval_ms: sys_sampler::DEFAULT_INTERVAL_MS,
+    }
+}
+
+fn run_args_to_tui(args: &RunArgs) -> TuiArgs {
+    TuiArgs {
+        path: Some(args.path.clone()),
+        graph: args.graph.clone(),
+        graph_strict: args.graph_strict,
+        graph_labels: args.graph_labels.clone(),
+        training_file: Some(args.file.clone()),
+        training_cmd: args.training_cmd.clone(),
+        start_training: true,
+        fresh_run: false,
+        detach: args.detach,
+        codebase_root: args.codebase_root.clone(),
+        auto: args.auto_mode.daemon_auto_enabled(),
+        runtime: args.runtime,
+        socket: args.socket.clone(),
+        refresh_ms: args.refresh_ms,
+        procs_sort: args.procs_sort,
+        procs_interval_ms: args.procs_interval_ms,
+        procs_limit: args.procs_limit,
+        log_lines: args.log_lines,
+        chat_messages: args.chat_messages,
+        notify: args.notify,
+        run_name_template: args.run_name_template.clone(),
+        git_patch: args.git_patch,
+        record: args.record.clone(),
+        debug_log: args.debug_log.clone(),
+        max_fps: args.max_fps,
+        no_idle_backoff: args.no_idle_backoff,
+        run_size_warn: args.run_size_warn,
+        min_free_space: args.min_free_space,
+        anomaly_metrics: args.anomaly_metrics.clone(),
+        anomaly_z: args.anomaly_z,
+        early_stop_metric: args.early_stop_metric.clone(),
+        early_stop_mode: args.early_stop_mode,
+        early_stop_patience: args.early_stop_patience,
+        early_stop_min_delta: args.early_stop_min_delta,
+        grad_explode: args.grad_explode,
+        grad_vanish: args.grad_vanish,
+        grad_vanish_points: args.grad_vanish_points,
+        sys_interval_ms: args.sys_interval_ms,
+    }
+}
+
+/// Convergence check for the TUI: `--early-stop-*` flags, falling back to the
+/// project's objective. None when there is no metric or patience is 0.
+fn early_stop_policy(
+    tui: &TuiArgs,
+    objective: Option<&project_config::Objective>,
+) -> Option<app::EarlyStopPolicy> {
+    if tui.early_stop_patience == 0 {
+        return None;
+    }
+    let metric = tui
+        .early_stop_metric
+        .clone()
+        .or_else(|| objective.map(|objective| objective.metric.clone()))?;
+    let mode = tui.early_stop_mode.unwrap_or(match objective {
+        Some(objective) if objective.metric == metric => objective.mode,
+        _ => project_config::ObjectiveMode::Min,
+    });
+    Some(app::EarlyStopPolicy {
+        metric,
+        mode,
+        min_delta: tui.early_stop_min_delta.abs(),
+        patience: tui.early_stop_patience,
+    })
+}
+
+fn run_tui(tui: &TuiArgs, startup_prompt: Option<String>, clean_start: bool) -> Result<()> {
+    if let Some(path) = tui.debug_log.as_deref() {
+        debug_log::init(path)?;
+    }
+    if !(tui.anomaly_z.is_finite() && tui.anomaly_z > 0.0) {
+        bail!("--anomaly-z must be a positive number");
+    }
+    if !(tui.grad_explode.is_finite() && tui.grad_vanish.is_finite() && tui.grad_vanish >= 0.0) {
+        bail!("--grad-explode and --grad-vanish must be finite, --grad-vanish not negative");
+    }
+    let requested_path =
+        query::resolve_alias_path(tui.path.as_deref().unwrap_or(Path::new("runs/")));
+    let (mut graph_presets, presets_warning) = match presets::load() {
+        Ok(presets) => (presets, None),
+        Err(err) => (Vec::new(), Some(format!("{err:#}"))),
+    };
+    let (project, project_warning) = match project_config::load(&requested_path) {
+        Ok(project) => (project, None),
+        Err(err) => (
+            project_config::ProjectConfig::default(),
+            Some(format!("{err:#}")),
+        ),
+    };
+    // Project metric groups win over user presets of the same name.
+    graph_presets.retain(|preset| {
+        !project
+            .metric_groups
+            .iter()
+            .any(|group| group.name == preset.name)
+    });
+    graph_presets.extend(project.metric_groups.iter().cloned());
+    let graph_filter = match tui.graph.as_deref() {
+        Some(raw) => {
+            let mut filter =
+                resolve_graph_filter(raw, &graph_presets).with_context(
+                    || match &presets_warning {
+                        Some(warning) => format!("config: {warning}"),
+                        None => "resolving --graph".to_string(),
+                    },
+                )?;
+            filter.strict = tui.graph_strict;
+            Some(filter)
+        }
+        None => None,
+    };
+    let metric_labels = parse_graph_labels(tui.graph_labels.as_deref())?;
+    let events_path = if tui.training_file.is_some() && tui.start_training {
+        resolve_live_run_path(&requested_path, &tui.run_name_template)?
+    } else {
+        requested_path.clone()
+    };
+    // An explicit --socket (or OGD_SOCKET) wins. Otherwise a TUI that spawns
+    // a daemon gives it a socket of the run's own, and one opening an
+    // existing run reconnects to the daemon recorded in its metadata.
+    let daemon_socket = match (&tui.socket, &tui.training_file) {
+        (Some(socket), _) => Some(socket.clone()),
+        (None, Some(_)) => Some(socket_client::socket_path_for_run(&run_meta::run_dir_for(
+            &events_path,
+        ))),
+        (None, None) if !clean_start => recorded_daemon_socket(&events_path),
+        (None, None) => None,
+    };
+    let daemon_expected = daemon_socket.is_some();
+    let mut initial = if clean_start {
+        ViewData {
+            scalars: BTreeMap::new(),
+            log_lines: vec![
+                LogEntry::new(LogLevel::Info, LogSource::Og, "-- clean view --"),
+                LogEntry::new(LogLevel::Info, LogSource::Og, "no run loaded yet"),
+                LogEntry::new(
+                    LogLevel::Info,
+                    LogSource::Og,
+                    "use `og run <file>` or pass --path to load existing runs",
+                ),
+            ],
+            total_events: 0,
+            max_step: 0,
+            ranks: Vec::new(),
+            rank_progress: BTreeMap::new(),
+            wall_secs: None,
+            gaps: Vec::new(),
+            sys_strip: sys_strip::SysStrip::default(),
+            epoch_scale: None,
+        }
+    } else {
+        load_view_data(&events_path)?
+    };
+    let recorder = match tui.record.as_deref() {
+        Some(path) => {
+            let mut recorder = session::Recorder::create(path, &events_path, unix_now_secs())?;
+            if !clean_start {
+                recorder.refresh(
+                    &initial.scalars,
+                    &initial.log_lines,
+                    initial.total_events,
+                    initial.max_step,
+                )?;
+            }
+            Some(recorder)
+        }
+        None => None,
+    };
+
+    let mut graph_misses = Vec::new();
+    if let Some(filter) = graph_filter.as_ref() {
+        graph_misses = graph_filter_misses(filter, &initial.scalars);
+        if filter.strict && !graph_misses.is_empty() {
+            let misses: Vec<String> = graph_misses.iter().map(ToString::to_string).collect();
+            bail!("--graph-strict: {}", misses.join("; "));
+        }
+        initial.scalars = filter_scalars(initial.scalars, filter);
+    }
+
+    let app_path = if clean_start {
+        PathBuf::from("(clean)")
+    } else {
+        events_path.clone()
+    };
+    let mut app = App::new(
+        initial.scalars,
+        metric_labels,
+        initial.log_lines,
+        app_path,
+        initial.total_events,
+        initial.max_step,
+    );
+    app.set_process_preferences(tui.procs_sort, tui.procs_limit);
+    app.set_buffer_limits(tui.log_lines, tui.chat_messages);
+    app.notify_mode = tui.notify;
+    app.run_size_warn_bytes = tui.run_size_warn;
+    app.min_free_bytes = tui.min_free_space;
+    app.anomaly_metrics = tui
+        .anomaly_metrics
+        .iter()
+        .map(|selector| selector.trim().to_string())
+        .filter(|selector| !selector.is_empty())
+        .collect();
+    app.anomaly_z = tui.anomaly_z;
+    app.early_stop = early_stop_policy(tui, project.objective.as_ref());
+    match cost::Pricing::resolve(&project) {
+        Ok(pricing) => app.pricing = pricing,
+        Err(err) => app.append_live_log(LogLevel::Warn, format!("no spend estimate: {err:#}")),
+    }
+    app.grad_limits = grad_health::Limits {
+        explode: tui.grad_explode,
+        vanish: tui.grad_vanish,
+        vanish_points: tui.grad_vanish_points,
+    };
+    app.graph_preset = tui
+        .graph
+        .as_deref()
+        .and_then(|raw| raw.strip_prefix(presets::PRESET_PREFIX))
+        .map(str::to_string);
+    app.graph_strict = tui.graph_strict;
+    if let Some(warning) = presets_warning {
+        app.append_live_log(LogLevel::Warn, format!("ignoring graph presets: {warning}"));
+    }
+    if let Some(warning) = project_warning {
+        app.append_live_log(
+            LogLevel::Warn,
+            format!("ignoring {}: {warning}", project_config::FILE_NAME),
+        );
+    }
+    report_graph_misses(&mut app, &graph_misses);
+    app.set_ranks(initial.ranks);
+    app.set_rank_progress(initial.rank_progress);
+    app.wall_gaps = initial.gaps;
+    app.epoch_scale = initial.epoch_scale;
+    app.dashboard_root = requested_path;
+    if !clean_start && let Ok(meta) = run_meta::load(&events_path) {
+        app.apply_run_meta(meta);
+    }
+    if !daemon_expected {
+        app.chat_status = "No daemon (optional)".to_string();
+    }
+
+    if let Some(socket) = daemon_socket {
+        app.daemon_socket = socket;
+    }
+    app.codebase_root = tui.codebase_root.clone();
+
+    // ── Spawn agent daemon if --training-file is provided ───────────────
+    let mut daemon_child: Option<Child> = None;
+    let mut detached_pid: Option<u32> = None;
+    let mut owns_training = false;
+    if let Some(ref training_file) = tui.training_file {
+        let start_training = tui.start_training || tui.training_cmd.is_some();
+        if start_training {
+            match record_launch_metadata(
+                tui,
+                training_file,
+                &events_path,
+                &app.daemon_socket,
+                &project,
+            ) {
+                Ok(meta) => app.apply_run_meta(meta),
+                Err(err) => app.append_live_log(
+                    LogLevel::Error,
+                    format!("failed to record run launch: {err}"),
+                ),
+            }
+            if let Err(err) = record_environment(tui, &events_path) {
+                app.append_live_log(
+                    LogLevel::Error,
+                    format!("failed to capture environment: {err}"),
+                );
+            }
+            if tui.sys_interval_ms > 0 {
+                let step = Arc::new(AtomicI64::new(app.max_step));
+                if sys_sampler::spawn(
+                    run_meta::run_dir_for(&events_path),
+                    Duration::from_millis(tui.sys_interval_ms),
+                    step.clone(),
+                ) {
+                    app.sampler_step = Some(step);
+                } else {
+                    app.append_live_log(
+                        LogLevel::Info,
+                        "no GPU or CPU readings; not sampling system metrics",
+                    );
+                }
+            }
+            if app.dirty_tree {
+                app.append_live_log(
+                    LogLevel::Important,
+                    "run started from a git tree with uncommitted changes",
+                );
+            }
+        }
+        let query_socket = query_server::socket_path_for(&app.daemon_socket);
+        if let Err(err) = query_server::spawn(&query_socket, Arc::new(run_agent_query)) {
+            app.append_live_log(
+                LogLevel::Warn,
+                format!("agent query socket unavailable: {err:#}"),
+            );
+        }
+        if tui.detach {
+            match spawn_detached_daemon(
+                tui,
+                training_file,
+                &events_path,
+                start_training,
+                &app.daemon_socket,
+                app.grad_limits,
+            ) {
+                Ok(pid) => {
+                    detached_pid = Some(pid);
+                    app.chat_status = format!("Daemon starting in the background (pid {pid})...");
+                }
+                Err(e) => {
+                    app.chat_status = format!("Daemon failed: {e:#}");
+                }
+            }
+        } else {
+            match spawn_daemon(
+                training_file,
+                &tui.codebase_root,
+                &events_path,
+                tui.auto,
+                tui.runtime,
+                start_training,
+                tui.fresh_run,
+                tui.training_cmd.as_deref(),
+                &app.daemon_socket,
+                app.grad_limits,
+            ) {
+                Ok(child) => {
+                    daemon_child = Some(child);
+                    // The daemon stops with the TUI, and training with it.
+                    owns_training = start_training;
+                    app.owns_training = start_training;
+                    app.chat_status = "Daemon starting...".to_string();
+                }
+                Err(e) => {
+                    app.chat_status = format!("Daemon failed: {}", e);
+                }
+            }
+        }
+    }
+
+    if let Err(err) = shutdown::install() {
+        app.append_live_log(
+            LogLevel::Warn,
+            format!("signal handlers unavailable: {err}"),
+        );
+    }
+    let mut terminal = setup_terminal()?;
+
+    let result = run_app(
+        &mut terminal,
+        app,
+        if clean_start {
+            None
+        } else {
+            Some(events_path.as_path())
+        },
+        tui.refresh_ms,
+        tui.procs_interval_ms,
+        tui.max_fps,
+        !tui.no_idle_backoff,
+        startup_prompt,
+        graph_filter,
+        graph_presets,
+        daemon_expected,
+        recorder,
+        None,
+    );
+
+    // After a SIGHUP the terminal is gone and restoring it fails; the
+    // training process still has to be stopped.
+    if let Err(err) = restore_terminal(&mut terminal) {
+        tracing::warn!("restoring the terminal: {err:#}");
+    }
+
+    // ── Stop the daemon child if we spawned it ──────────────────────────
+    // SIGTERM lets it forward SIGINT to training and wait for a checkpoint
+    // before killing anything.
+    if let Some(ref mut child) = daemon_child {
+        let reason = match shutdown::received() {
+            Some(signal) => format!("TUI received {signal}"),
+            None => "quit from the TUI".to_string(),
+        };
+        let timeout = daemon_ctl::stop_timeout();
+        if owns_training {
+            stderr_line(format_args!(
+                "stopping training ({reason}); waiting up to {}s for it to exit...",
+                timeout.as_secs()
+            ));
+        }
+        match daemon_ctl::stop_child(child, timeout) {
+            Ok(true) => stderr_line(format_args!(
+                "daemon did not exit within {}s; killed it",
+                timeout.as_secs()
+            )),
+            Ok(false) => {}
+            Err(err) => stderr_line(format_args!("stopping daemon: {err:#}")),
+        }
+        if owns_training {
+            let run_dir = run_meta::run_dir_for(&events_path);
+            if let Err(err) =
+                run_meta::update(&run_dir, |meta| meta.record_stop(&reason, unix_now_secs()))
+            {
+                stderr_line(format_args!("recording why the run stopped: {err:#}"));
+            }
+        }
+    }
+    if let Some(pid) = detached_pid {
+        stderr_line(format_args!(
+            "daemon (pid {pid}) keeps running for {}; reattach with `og attach`, stop it with `og daemon stop`",
+            events_path.display()
+        ));
+    }
+
+    if let Err(e) = result {
+        stderr_line(format_args!("Error: {e:?}"));
+    }
+
+    Ok(())
+}
+
+type TuiTerminal = Terminal<CrosstermBackend<io::Stdout>>;
+
+fn setup_terminal() -> Result<TuiTerminal> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        crossterm::event::EnableMouseCapture
+    )?;
+    Ok(Terminal::new(CrosstermBackend::new(stdout))?)
+}
+
+/// A line on stderr that is dropped when stderr is closed, as it is after a
+/// SIGHUP, where `eprintln!` would panic.
+fn stderr_line(args: std::fmt::Arguments) {
+    let _ = writeln!(io::stderr(), "{args}");
+}
+
+fn restore_terminal(terminal: &mut TuiTerminal) -> Result<()> {
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        crossterm::event::DisableMouseCapture
+    )?;
+    terminal.show_cursor()?;
+    Ok(())
+}
+
+/// Open the TUI on a recorded session: no daemon, event-file polling or
+/// process sampling, only what the recording saw, on its original clock.
+fn run_replay(args: &ReplayArgs) -> Result<()> {
+    if !(args.speed.is_finite() && args.speed > 0.0) {
+        bail!("--speed must be a positive number");
+    }
+    let (header, entries) = session::read::<BgMessage>(&args.file)?;
+    let graph_presets = presets::load()?;
+    let graph_filter = args
+        .graph
+        .as_deref()
+        .map(|raw| resolve_graph_filter(raw, &graph_presets))
+        .transpose()?;
+    let mut app = App::new(
+        BTreeMap::new(),
+        BTreeMap::new(),
+        vec![LogEntry::new(
+            LogLevel::Info,
+            LogSource::Og,
+            format!(
+                "-- replaying {} ({} entries, {}x) --",
+                args.file.display(),
+                entries.len(),
+                args.speed
+            ),
+        )],
+        PathBuf::from(&header.path),
+        0,
+        0,
+    );
+    app.chat_status = format!("Replaying {}", args.file.display());
+
+    let mut terminal = setup_terminal()?;
+    let result = run_app(
+        &mut terminal,
+        app,
+        None,
+        0,
+        0,
+        redraw::DEFAULT_MAX_FPS,
+        false,
+        None,
+        graph_filter,
+        graph_presets,
+        false,
+        None,
+        Some(Replay {
+            entries,
+            speed: args.speed,
+        }),
+    );
+    restore_terminal(&mut terminal)?;
+    result
+}
+
+fn print_command_output(output: &CommandOutput, mode: OutputMode) -> Result<()> {
+    match mode {
+        OutputMode::Json => println!("{}", serde_json::to_string_pretty(&output.data)?),
+        OutputMode::Porcelain => print!("{}", porcelain::render(&output.data)),
+        OutputMode::Text if !output.text.is_empty() => println!("{}", output.text),
+        OutputMode::Text | OutputMode::Quiet => {}
+    }
+    Ok(())
+}
+
+/// `--graph` value: `preset:<name>` from the config file, or a JSON filter.
+fn resolve_graph_filter(raw: &str, graph_presets: &[presets::GraphPreset]) -> Result<GraphFilter> {
+    match raw.trim().strip_prefix(presets::PRESET_PREFIX) {
+        Some(name) => Ok(preset_filter(presets::find(graph_presets, name.trim())?)),
+        None => parse_graph_filter(raw),
+    }
+}
+
+fn preset_filter(preset: &presets::GraphPreset) -> GraphFilter {
+    GraphFilter {
+        metrics: preset.patterns.clone(),
+        sys: Vec::new(),
+        strict: false,
+    }
+}
+
+fn parse_graph_filter(raw: &str) -> Result<GraphFilter> {
+    fn parse_string_or_array(value: Option<&Value>, key: &str) -> Result<Vec<String>> {
+        let Some(value) = value else {
+            return Ok(Vec::new());
+        };
+        match value {
+            Value::String(s) => {
+                graph_filter::validate_selector(key, s)?;
+                Ok(vec![s.clone()])
+            }
+            Value::Array(items) => {
+                let mut out = Vec::new();
+                for item in items {
+                    let Some(s) = item.as_str() else {
+                        bail!("'{}' array must contain only strings", key);
+                    };
+                    graph_filter::validate_selector(key, s)?;
+                    out.push(s.to_string());
+                }
+                Ok(out)
+            }
+            _ => bail!("'{}' must be a string or an array of strings", key),
+        }
+    }
+
+    let parsed: Value =
+        serde_json::from_str(raw).with_context(|| OgError::parse_failed("--graph JSON"))?;
+    let obj = parsed
+        .as_object()
+        .ok_or_else(|| anyhow::anyhow!("--graph must be a JSON object"))?;
+    if let Some(key) = obj
+        .keys()
+        .find(|key| !matches!(key.as_str(), "metrics" | "sys"))
+    {
+        bail!("unknown --graph key '{key}' (expected 'metrics' or 'sys')");
+    }
+    let metrics = parse_string_or_array(obj.get("metrics"), "metrics")?;
+    let sys = parse_string_or_array(obj.get("sys"), "sys")?;
+    Ok(GraphFilter {
+        metrics,
+        sys,
+        strict: false,
+    })
+}
+
+fn parse_graph_labels(raw: Option<&str>) -> Result<BTreeMap<String, String>> {
+    let Some(raw) = raw else {
+        return Ok(BTreeMap::new());
+    };
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+
+    if trimmed.starts_with('{') {
+        let parsed: Value = serde_json::from_str(trimmed)
+            .with_context(|| OgError::parse_failed("--graph-labels JSON"))?;
+        let obj = parsed
+            .as_object()
+            .ok_or_else(|| anyhow::anyhow!("--graph-labels JSON must be an object"))?;
+        let mut out = BTreeMap::new();
+        for (key, value) in obj {
+            let label = value
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("graph label for '{}' must be a string", key))?
+                .trim();
+            if !label.is_empty() {
+                out.insert(key.trim().to_string(), label.to_string());
+            }
+        }
+        return Ok(out);
+    }
+
+    let mut out = BTreeMap::new();
+    for pair in trimmed.split(',') {
+        let part = pair.trim();
+        if part.is_empty() {
+            continue;
+        }
+        let (metric, label) = part.split_once('=').ok_or_else(|| {
+            anyhow::anyhow!("invalid graph label '{}'; expected metric=Label", part)
+        })?;
+        let metric = metric.trim();
+        let label = label.trim();
+        if metric.is_empty() || label.is_empty() {
+            bail!(
+                "invalid graph label '{}'; metric and label must be non-empty",
+                part
+            );
+        }
+        out.insert(metric.to_string(), label.to_string());
+    }
+    Ok(out)
+}
+
+fn filter_scalars(
+    scalars: BTreeMap<String, Vec<(f64, f64)>>,
+    filter: &GraphFilter,
+) -> BTreeMap<String, Vec<(f64, f64)>> {
+    if filter.metrics.is_empty() && filter.sys.is_empty() {
+        return scalars;
+    }
+
+    let mut filtered = BTreeMap::new();
+    for (tag, series) in scalars.iter() {
+        if metric_matches_filter(tag, filter) {
+            filtered.insert(tag.clone(), series.clone());
+        }
+    }
+
+    // Without --graph-strict a filter that matches nothing shows everything;
+    // `graph_filter_misses` reports it so the fallback is never silent.
+    if filtered.is_empty() && !filter.strict {
+        scalars
+    } else {
+        filtered
+    }
+}
+
+fn graph_filter_misses(
+    filter: &GraphFilter,
+    scalars: &BTreeMap<String, Vec<(f64, f64)>>,
+) -> Vec<graph_filter::Unmatched> {
+    if scalars.is_empty() {
+        return Vec::new();
+    }
+    let tags: Vec<&str> = scalars.keys().map(String::as_str).collect();
+    graph_filter::unmatched(filter.selectors(), &tags)
+}
+
+/// Log each selector that matched nothing and raise one toast for them.
+fn report_graph_misses(app: &mut App, misses: &[graph_filter::Unmatched]) {
+    let Some(first) = misses.first() else {
+        return;
+    };
+    for miss in misses {
+        app.append_live_log(LogLevel::Warn, format!("--graph: {miss}"));
+    }
+    let mut message = format!("--graph: {first}");
+    if misses.len() > 1 {
+        message.push_str(&format!(" (+{} more in logs)", misses.len() - 1));
+    }
+    app.toast(ToastLevel::Alert, message);
+}
+
+fn metric_matches_filter(metric: &str, filter: &GraphFilter) -> bool {
+    if filter.metrics.is_empty() && filter.sys.is_empty() {
+        return true;
+    }
+    filter
+        .selectors()
+        .any(|selector| graph_filter::selector_matches(metric, selector))
+}
+
+fn execute_query_command(command: OgCommand) -> Result<CommandOutput> {
     match command {
-        OgCommand::Run(args) => {
-            let graph_filter = args.graph.as_deref().map(parse_graph_filter).transpose()?;
-            let tui = run_args_to_tui(&args);
-            run_tui(&tui, args.prompt.clone(), graph_filter, false)
+        OgCommand::Run(_) => bail!("run must be executed in run mode"),
+        OgCommand::Replay(_) => bail!("replay opens the TUI; start it from the shell"),
+        OgCommand::Attach(_) => bail!("attach opens the TUI; start it from the shell"),
+        OgCommand::Completions(_) | OgCommand::Man(_) => {
+            bail!("completions and man pages print to stdout; run them from the shell")
+        }
+        OgCommand::ServeQueries(_) => {
+            bail!("serve-queries runs in the foreground; start it from the shell")
+        }
+        OgCommand::Init(args) => execute_init(args),
+        OgCommand::Doctor(args) => execute_doctor(args),
+        OgCommand::Daemon(args) => execute_daemon(args),
+        OgCommand::Discover(args) => execute_discover(args),
+        OgCommand::Tail(args) => execute_tail(args),
+        OgCommand::K8s(args) => execute_k8s(args),
+        OgCommand::Resume(args) => execute_resume(args),
+        OgCommand::List(args) => execute_list(args),
+        OgCommand::Get(args) => execute_get(args),
+        OgCommand::Compare(args) => execute_compare(args),
+        OgCommand::Diff(args) => execute_diff(args),
+        OgCommand::Regress(args) => execute_regress(args),
+        OgCommand::Leaderboard(args) => execute_leaderboard(args),
+        OgCommand::Stats(args) => execute_stats(args),
+        OgCommand::Digest(args) => execute_digest(args),
+        OgCommand::Search(args) => execute_search(args),
+        OgCommand::Annotate(args) => execute_annotate(args),
+        OgCommand::Set(args) => execute_set(args),
+        OgCommand::Note(args) => execute_note(args),
+        OgCommand::Tag(args) => execute_tag(args),
+        OgCommand::Alias(args) => execute_alias(args),
+        OgCommand::Describe(args) => execute_describe(args),
+        OgCommand::Fork(args) => execute_fork(args),
+        OgCommand::Compact(args) => execute_compact(args),
+        OgCommand::Gc(args) => execute_gc(args),
+        OgCommand::Import(args) => execute_import(args),
+        OgCommand::Export(args) => execute_export(args),
+        OgCommand::Bundle(args) => execute_bundle(args),
+        OgCommand::Apply(args) => execute_apply(args),
+        OgCommand::Rollback(args) => execute_rollback(args),
+        OgCommand::Snapshot(args) => execute_snapshot(args),
+        OgCommand::Bench(args) => execute_bench(args),
+    }
+}
+
+fn execute_snapshot(args: SnapshotArgs) -> Result<CommandOutput> {
+    if args.width < ui::MIN_WIDTH || args.height < ui::MIN_HEIGHT {
+        bail!(
+            "snapshot must be at least {}x{}",
+            ui::MIN_WIDTH,
+            ui::MIN_HEIGHT
+        );
+    }
+    let path = match args.run.as_deref() {
+        Some(run) => resolve_run_path(&args.path, args.project.as_deref(), run),
+        None => project_base(&args.path, args.project.as_deref()),
+    };
+    if !path.exists() {
+        bail!("'{}' not found", path.display());
+    }
+    let graph_presets = presets::load()?;
+    let mut view = load_view_data(&path)?;
+    if let Some(raw) = args.graph.as_deref() {
+        let filter = resolve_graph_filter(raw, &graph_presets)?;
+        view.scalars = filter_scalars(view.scalars, &filter);
+    }
+    let mut app = App::new(
+        view.scalars,
+        BTreeMap::new(),
+        view.log_lines,
+        path.clone(),
+        view.total_events,
+        view.max_step,
+    );
+    app.set_ranks(view.ranks);
+    app.set_rank_progress(view.rank_progress);
+    app.wall_secs = view.wall_secs;
+    app.wall_gaps = view.gaps;
+    app.sys_strip = view.sys_strip;
+    app.epoch_scale = view.epoch_scale;
+    if let Ok(meta) = run_meta::load(&path) {
+        app.apply_run_meta(meta);
+    }
+    app.active_tab = app::Tab::Graphs;
+    if let Some(metric) = args.metric.as_deref() {
+        let Some(index) = app.tags.iter().position(|tag| tag == metric) else {
+            let tags: Vec<&str> = app.tags.iter().map(String::as_str).collect();
+            let miss = graph_filter::Unmatched {
+                selector: metric.to_string(),
+                suggestions: og_core::error::suggest(metric, &tags),
+            };
+            bail!("--metric: {miss}");
+        };
+        app.focus_metric(index);
+    }
+
+    let buffer = snapshot::render(&mut app, args.width, args.height)?;
+    let rendered = if args.ansi {
+        snapshot::to_ansi(&buffer)
+    } else {
+        snapshot::to_plain(&buffer)
+    };
+    let data = serde_json::json!({
+        "path": path.display().to_string(),
+        "metric": args.metric,
+        "width": args.width,
+        "height": args.height,
+        "output": args.output.as_ref().map(|p| p.display().to_string()),
+        "lines": rendered.lines().collect::<Vec<_>>(),
+    });
+    let text = match &args.output {
+        Some(output) => {
+            fs::write(output, &rendered)
+                .with_context(|| format!("writing {}", output.display()))?;
+            format!(
+                "wrote {}x{} snapshot of {} to {}",
+                args.width,
+                args.height,
+                path.display(),
+                output.display()
+            )
+        }
+        None => rendered.trim_end_matches('\n').to_string(),
+    };
+    Ok(CommandOutput {
+        command: "snapshot".to_string(),
+        data,
+        text,
+    })
+}
+
+fn execute_bench(args: BenchArgs) -> Result<CommandOutput> {
+    match args.cmd {
+        BenchSubcommand::Parse(a) => {
+            let report = bench::run(&a.file, a.iterations, a.poll_points)?;
+            let mut text_lines = vec![format!(
+                "{}: {} bytes, {} points, {} tags",
+                report.file, report.bytes, report.points, report.tags
+            )];
+            for m in &report.measurements {
+                text_lines.push(format!(
+                    "{:<18} {:>10.2} ms median {:>10.2} ms min {:>14.0} points/s",
+                    m.name, m.median_ms, m.min_ms, m.points_per_sec
+                ));
+            }
+            Ok(CommandOutput {
+                command: "bench.parse".to_string(),
+                data: serde_json::to_value(&report)?,
+                text: text_lines.join("\n"),
+            })
+        }
+        BenchSubcommand::Gen(a) => {
+            let bytes = bench::generate_fixture(&a.file, a.events, a.tags)?;
+            Ok(CommandOutput {
+                command: "bench.gen".to_string(),
+                data: serde_json::json!({
+                    "file": a.file.display().to_string(),
+                    "events": a.events,
+                    "tags": a.tags,
+                    "bytes": bytes,
+                }),
+                text: format!(
+                    "wrote {} ({} events over {} tags, {} bytes)",
+                    a.file.display(),
+                    a.events,
+                    a.tags,
+                    bytes
+                ),
+            })
+        }
+    }
+}
+
+fn execute_tail(args: TailArgs) -> Result<CommandOutput> {
+    let target_path = {
+        let explicit = PathBuf::from(&args.target);
+        if explicit.exists() {
+            explicit
+        } else {
+            args.path.join(&args.target)
+        }
+    };
+
+    if !target_path.exists() {
+        bail!("target '{}' not found", target_path.display());
+    }
+
+    // Each row as printed, with its entry; `--level` filters before the tail.
+    let mut rows: Vec<(String, LogEntry)> = Vec::new();
+    let kind: &str;
+    if target_path.is_dir() {
+        kind = "run_events";
+        let view = load_view_data(&target_path)?;
+        rows.extend(
+            view.log_lines
+                .into_iter()
+                .map(|entry| (entry.message.clone(), entry)),
+        );
+    } else if target_path.is_file() && readers::is_run_file(&target_path) {
+        kind = if metrics_jsonl::is_metrics_file(&target_path) {
+            "metrics_jsonl"
+        } else {
+            "tfevents"
+        };
+        let (events, _) = readers::read_file(&target_path)?;
+        rows.extend(events.iter().map(|ev| {
+            let entry = event_log_entry(ev);
+            (entry.message.clone(), entry)
+        }));
+    } else {
+        kind = "text";
+        let raw = fs::read_to_string(&target_path)
+            .with_context(|| format!("reading {}", target_path.display()))?;
+        rows.extend(raw.lines().map(|line| {
+            let entry = LogEntry::classify(line, LogSource::File)
+                .unwrap_or_else(|| LogEntry::new(LogLevel::Info, LogSource::File, ""));
+            (line.to_string(), entry)
+        }));
+    }
+    if let Some(level) = args.level {
+        rows.retain(|(_, entry)| entry.level >= level);
+    }
+    let start = rows.len().saturating_sub(args.lines);
+    let (lines, entries): (Vec<String>, Vec<LogEntry>) = rows.drain(start..).unzip();
+
+    let data = serde_json::json!({
+        "target": target_path.display().to_string(),
+        "kind": kind,
+        "line_count": lines.len(),
+        "lines": lines,
+        "entries": entries,
+    });
+
+    let text = lines.join("\n");
+    Ok(CommandOutput {
+        command: "tail".to_string(),
+        data,
+        text,
+    })
+}
+
+fn execute_resume(args: ResumeArgs) -> Result<CommandOutput> {
+    let run_path = resolve_run_path(&args.path, None, &args.run_id);
+    let checkpoint_path = resolve_checkpoint_path(&args.checkpoint_dir, &args.checkpoint)?;
+
+    let mut snapshot_files = Vec::new();
+    for entry in fs::read_dir(&checkpoint_path)? {
+        let entry = entry?;
+        if entry.path().is_file() && entry.file_name() != "state.json" {
+            snapshot_files.push(entry.file_name().to_string_lossy().to_string());
+        }
+    }
+    snapshot_files.sort();
+
+    let data = serde_json::json!({
+        "run_id": args.run_id,
+        "run_path": run_path.display().to_string(),
+        "run_exists": run_path.exists(),
+        "checkpoint": args.checkpoint,
+        "checkpoint_path": checkpoint_path.display().to_string(),
+        "snapshot_files": snapshot_files,
+        "note": "Checkpoint resolution is available. Resume apply/restore is handled by the agent refactor loop."
+    });
+
+    let text = format!(
+        "run: {}\ncheckpoint: {}\nfiles: {}\nnote: resume metadata resolved; apply/restore is agent-managed.",
+        run_path.display(),
+        checkpoint_path.display(),
+        snapshot_files.join(", ")
+    );
+    Ok(CommandOutput {
+        command: "resume".to_string(),
+        data,
+        text,
+    })
+}
+
+fn execute_apply(args: ApplyArgs) -> Result<CommandOutput> {
+    let diff = if args.diff.as_os_str() == "-" {
+        io::read_to_string(io::stdin()).context("reading diff from stdin")?
+    } else {
+        fs::read_to_string(&args.diff)
+            .with_context(|| format!("reading {}", args.diff.display()))?
+    };
+    let checkpoint_dir = args.codebase_root.join(&args.checkpoint_dir);
+    let applied = patch::apply(&args.codebase_root, &checkpoint_dir, &diff, args.dry_run)?;
+
+    let mut text_lines: Vec<String> = applied
+        .changes
+        .iter()
+        .map(|change| {
+            let verb = match change.kind {
+                patch::ChangeKind::Modified => "modified",
+                patch::ChangeKind::Created => "created",
+                patch::ChangeKind::Deleted => "deleted",
+            };
+            format!(
+                "{}: {} ({} hunk{})",
+                change.path,
+                verb,
+                change.hunks,
+                if change.hunks == 1 { "" } else { "s" }
+            )
+        })
+        .collect();
+    match &applied.checkpoint {
+        Some(checkpoint) => text_lines.push(format!(
+            "snapshot: {} (undo with `og rollback`)",
+            checkpoint.display()
+        )),
+        None => text_lines.push("dry run: no files written".to_string()),
+    }
+    Ok(CommandOutput {
+        command: "apply".to_string(),
+        data: serde_json::json!({
+            "dry_run": args.dry_run,
+            "checkpoint": applied.checkpoint.as_ref().map(|p| p.display().to_string()),
+            "changes": applied.changes,
+        }),
+        text: text_lines.join("\n"),
+    })
+}
+
+fn execute_rollback(args: RollbackArgs) -> Result<CommandOutput> {
+    let checkpoint_dir = args.codebase_root.join(&args.checkpoint_dir);
+    let snapshot = if args.checkpoint == "latest" {
+        patch::latest_snapshot(&checkpoint_dir)?
+    } else {
+        resolve_checkpoint_path(&checkpoint_dir, &args.checkpoint)?
+    };
+    let rolled_back = patch::rollback(&snapshot)?;
+
+    let mut text_lines = vec![format!("rolled back {}", snapshot.display())];
+    text_lines.extend(
+        rolled_back
+            .restored
+            .iter()
+            .map(|p| format!("restored: {p}")),
+    );
+    text_lines.extend(rolled_back.removed.iter().map(|p| format!("removed: {p}")));
+    Ok(CommandOutput {
+        command: "rollback".to_string(),
+        data: serde_json::json!({
+            "checkpoint": rolled_back.checkpoint.display().to_string(),
+            "restored": rolled_back.restored,
+            "removed": rolled_back.removed,
+        }),
+        text: text_lines.join("\n"),
+    })
+}
+
+fn execute_list(args: ListArgs) -> Result<CommandOutput> {
+    match args.cmd {
+        ListSubcommand::Projects(a) => execute_list_projects(a),
+        ListSubcommand::Runs(a) => execute_list_runs(a),
+        ListSubcommand::Metrics(a) => execute_list_metrics(a, false),
+        ListSubcommand::SystemMetrics(a) => execute_list_metrics(a, true),
+        ListSubcommand::Readers(a) => execute_list_readers(a),
+    }
+}
+
+fn execute_get(args: GetArgs) -> Result<CommandOutput> {
+    match args.cmd {
+        GetSubcommand::Run(a) => execute_get_run(a),
+        GetSubcommand::Metric(a) => execute_get_metric(a),
+        GetSubcommand::Table(a) => execute_get_table(a),
+    }
+}
+
+fn execute_search(args: SearchArgs) -> Result<CommandOutput> {
+    match args.cmd {
+        SearchSubcommand::Metrics(a) => execute_search_metrics(a),
+        SearchSubcommand::Runs(a) => execute_search_runs(a),
+    }
+}
+
+fn execute_annotate(args: AnnotateArgs) -> Result<CommandOutput> {
+    let run_path = resolve_run_path(&args.path, args.project.as_deref(), &args.run);
+    if !run_path.exists() {
+        return Err(OgError::run_not_found(&args.run, &run_path).into());
+    }
+    let message = args.message.trim().to_string();
+    if message.is_empty() {
+        bail!("annotation message must be non-empty");
+    }
+    let annotation = run_meta::Annotation {
+        step: args.step,
+        kind: args.kind,
+        message,
+        created_unix: unix_now_secs(),
+    };
+    let count = run_meta::update(&run_path, |meta| {
+        meta.add_annotation(annotation.clone());
+        meta.annotations.len()
+    })?;
+
+    let data = serde_json::json!({
+        "run": run_path.display().to_string(),
+        "annotation": annotation,
+        "annotation_count": count,
+    });
+    Ok(CommandOutput {
+        command: "annotate".to_string(),
+        data,
+        text: format!(
+            "annotated {} at step {} ({}): {}",
+            run_path.display(),
+            annotation.step,
+            annotation.kind.label(),
+            annotation.message
+        ),
+    })
+}
+
+fn execute_set(args: SetArgs) -> Result<CommandOutput> {
+    let run_path = resolve_run_path(&args.path, args.project.as_deref(), &args.run);
+    if !run_path.exists() {
+        return Err(OgError::run_not_found(&args.run, &run_path).into());
+    }
+    let assignments = args
+        .assignments
+        .iter()
+        .map(|raw| control::parse_assignment(raw))
+        .collect::<Result<Vec<_>>>()?;
+    let now = unix_now_secs();
+    let (version, changes) = control::set(&run_path, &assignments, args.force, now)?;
+
+    if !changes.is_empty() {
+        let step = match args.step {
+            Some(step) => step,
+            None => load_view_data(&run_path)
+                .map(|view| view.max_step)
+                .unwrap_or(0),
+        };
+        run_meta::update(&run_path, |meta| {
+            for change in &changes {
+                let kind = if is_lr_param(&change.key) {
+                    run_meta::AnnotationKind::LrChange
+                } else {
+                    run_meta::AnnotationKind::Hparam
+                };
+                meta.add_annotation(run_meta::Annotation {
+                    step,
+                    kind,
+                    message: format_param_change(change),
+                    created_unix: now,
+                });
+            }
+        })?;
+    }
+
+    let text = if changes.is_empty() {
+        format!(
+            "no change for {} (control version {version})",
+            run_path.display()
+        )
+    } else {
+        let summary: Vec<String> = changes.iter().map(format_param_change).collect();
+        format!(
+            "set {} on {} (control version {version})",
+            summary.join(", "),
+            run_path.display()
+        )
+    };
+    let data = serde_json::json!({
+        "run": run_path.display().to_string(),
+        "control_file": control::control_path(&run_path).display().to_string(),
+        "version": version,
+        "changes": changes,
+    });
+    Ok(CommandOutput {
+        command: "set".to_string(),
+        data,
+        text,
+    })
+}
+
+fn is_lr_param(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    key == "lr" || key.ends_with("_lr") || key.starts_with("lr_") || key.contains("learning_rate")
+}
+
+fn format_param_change(change: &control::ParamChange) -> String {
+    match &change.old {
+        Some(old) => format!("{} {} -> {}", change.key, old, change.new),
+        None => format!("{}={}", change.key, change.new),
+    }
+}
+
+fn execute_note(args: NoteArgs) -> Result<CommandOutput> {
+    match args.cmd {
+        NoteSubcommand::Add(a) => execute_note_add(a),
+        NoteSubcommand::List(a) => execute_note_list(a),
+    }
+}
+
+fn execute_note_add(args: NoteAddArgs) -> Result<CommandOutput> {
+    let run_path = resolve_run_path(&args.path, args.project.as_deref(), &args.run);
+    if !run_path.exists() {
+        return Err(OgError::run_not_found(&args.run, &run_path).into());
+    }
+    let step = match args.step {
+        Some(step) => Some(step),
+        None => load_view_data(&run_path).ok().map(|view| view.max_step),
+    };
+    let note = append_run_note(&run_path, &args.text, step)?;
+
+    let data = serde_json::json!({
+        "run": run_path.display().to_string(),
+        "note": note,
+    });
+    Ok(CommandOutput {
+        command: "note.add".to_string(),
+        data,
+        text: format!("noted on {}: {}", run_path.display(), note.text),
+    })
+}
+
+fn execute_note_list(args: NoteListArgs) -> Result<CommandOutput> {
+    let run_path = resolve_run_path(&args.path, args.project.as_deref(), &args.run);
+    if !run_path.exists() {
+        return Err(OgError::run_not_found(&args.run, &run_path).into());
+    }
+    let meta = run_meta::load(&run_path)?;
+
+    let mut text_lines = vec![format!("notes for {}", run_path.display())];
+    for note in &meta.notes {
+        let step = note
+            .step
+            .map(|s| format!(" step {}", s))
+            .unwrap_or_default();
+        text_lines.push(format!("- [{}{}] {}", note.created_unix, step, note.text));
+    }
+    if meta.notes.is_empty() {
+        text_lines.push("- none".to_string());
+    }
+
+    let data = serde_json::json!({
+        "run": run_path.display().to_string(),
+        "count": meta.notes.len(),
+        "notes": meta.notes,
+    });
+    Ok(CommandOutput {
+        command: "note.list".to_string(),
+        data,
+        text: text_lines.join("\n"),
+    })
+}
+
+/// Append a timestamped note to the run journal in the metadata sidecar.
+fn append_run_note(run_path: &Path, text: &str, step: Option<i64>) -> Result<run_meta::Note> {
+    let text = text.trim();
+    if text.is_empty() {
+        bail!("note text must be non-empty");
+    }
+    let note = run_meta::Note {
+        created_unix: unix_now_secs(),
+        step,
+        text: text.to_string(),
+    };
+    run_meta::update(run_path, |meta| meta.notes.push(note.clone()))?;
+    Ok(note)
+}
+
+fn execute_tag(args: TagArgs) -> Result<CommandOutput> {
+    let run_path = resolve_run_path(&args.path, args.project.as_deref(), &args.run);
+    if !run_path.exists() {
+        return Err(OgError::run_not_found(&args.run, &run_path).into());
+    }
+    let requested: Vec<String> = args
+        .tags
+        .iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    if requested.is_empty() {
+        bail!("tags must be non-empty");
+    }
+    let tags = run_meta::update(&run_path, |meta| {
+        if args.remove {
+            meta.tags.retain(|tag| !requested.contains(tag));
+        } else {
+            for tag in &requested {
+                if !meta.tags.contains(tag) {
+                    meta.tags.push(tag.clone());
+                }
+            }
         }
-        other => {
-            let output = execute_query_command(other)?;
-            print_command_output(&output, json)
+        meta.tags.clone()
+    })?;
+
+    let data = serde_json::json!({
+        "run": run_path.display().to_string(),
+        "tags": tags,
+    });
+    Ok(CommandOutput {
+        command: "tag".to_string(),
+        data,
+        text: format!(
+            "tags on {}: {}",
+            run_path.display(),
+            if tags.is_empty() {
+                "none".to_string()
+            } else {
+                tags.join(", ")
+            }
+        ),
+    })
+}
+
+fn execute_k8s(args: K8sArgs) -> Result<CommandOutput> {
+    match args.cmd {
+        K8sSubcommand::Events(a) => execute_k8s_events(a),
+    }
+}
+
+fn execute_k8s_events(args: K8sEventsArgs) -> Result<CommandOutput> {
+    let run_path = resolve_run_path(&args.path, args.project.as_deref(), &args.run);
+    if !run_path.is_dir() {
+        return Err(OgError::run_not_found(&args.run, &run_path).into());
+    }
+    let recorded = run_meta::load(&run_path)?.k8s;
+    let Some(pod) = args
+        .pod
+        .or_else(|| recorded.as_ref().map(|meta| meta.pod.clone()))
+    else {
+        bail!(
+            "run '{}' was not launched in a Kubernetes pod; name one with --pod",
+            args.run
+        );
+    };
+    let namespace = args
+        .namespace
+        .or_else(|| recorded.map(|meta| meta.namespace))
+        .unwrap_or_else(|| "default".to_string());
+    let pod_events = k8s::fetch(&namespace, &pod)?;
+    let scalars = tfevents::load_run(&run_path)
+        .map(|run| run.events)
+        .unwrap_or_default();
+    let added = k8s::record(&run_meta::run_dir_for(&run_path), &pod_events, &scalars)?;
+
+    let mut text_lines = vec![format!(
+        "pod {namespace}/{pod}: {} events, {added} new",
+        pod_events.len()
+    )];
+    for event in &pod_events {
+        text_lines.push(format!(
+            "- step {} [{}] {}: {}",
+            k8s::step_at(&scalars, event.unix),
+            if event.warning { "warning" } else { "normal" },
+            event.reason,
+            event.message
+        ));
+    }
+    let data = serde_json::json!({
+        "run": args.run,
+        "namespace": namespace,
+        "pod": pod,
+        "events": pod_events,
+        "added": added,
+    });
+    Ok(CommandOutput {
+        command: "k8s.events".to_string(),
+        data,
+        text: text_lines.join("\n"),
+    })
+}
+
+fn execute_alias(args: AliasArgs) -> Result<CommandOutput> {
+    match args.cmd {
+        AliasSubcommand::Set(a) => execute_alias_set(a),
+        AliasSubcommand::List(a) => execute_alias_list(a),
+        AliasSubcommand::Rm(a) => execute_alias_rm(a),
+    }
+}
+
+fn execute_alias_set(args: AliasSetArgs) -> Result<CommandOutput> {
+    let base = project_base(&args.path, args.project.as_deref());
+    let run_path = resolve_run_path(&args.path, args.project.as_deref(), &args.run);
+    if !run_path.is_dir() {
+        return Err(OgError::run_not_found(&args.run, &run_path).into());
+    }
+    let target = alias::set(&base, &args.name, &run_path)?;
+    let data = serde_json::json!({
+        "alias": args.name,
+        "run": target,
+        "path": run_path.display().to_string(),
+    });
+    Ok(CommandOutput {
+        command: "alias.set".to_string(),
+        data,
+        text: format!("{} -> {}", args.name, target),
+    })
+}
+
+fn execute_alias_list(args: AliasListArgs) -> Result<CommandOutput> {
+    let base = project_base(&args.path, args.project.as_deref());
+    let aliases = alias::load(&base)?;
+    let latest = alias::latest(&base).map(|path| {
+        path.strip_prefix(&base)
+            .unwrap_or(&path)
+            .display()
+            .to_string()
+    });
+
+    let mut text_lines = vec![format!("aliases in {}", base.display())];
+    text_lines.push(format!(
+        "- {} -> {}",
+        alias::LATEST,
+        latest.as_deref().unwrap_or("(no runs)")
+    ));
+    for (name, target) in &aliases {
+        text_lines.push(format!("- {name} -> {target}"));
+    }
+    let data = serde_json::json!({
+        "path": base.display().to_string(),
+        "latest": latest,
+        "aliases": aliases,
+    });
+    Ok(CommandOutput {
+        command: "alias.list".to_string(),
+        data,
+        text: text_lines.join("\n"),
+    })
+}
+
+fn execute_alias_rm(args: AliasRmArgs) -> Result<CommandOutput> {
+    let base = project_base(&args.path, args.project.as_deref());
+    if !alias::remove(&base, &args.name)? {
+        bail!("no alias '{}' in {}", args.name, base.display());
+    }
+    Ok(CommandOutput {
+        command: "alias.rm".to_string(),
+        data: serde_json::json!({ "alias": args.name, "removed": true }),
+        text: format!("removed alias {}", args.name),
+    })
+}
+
+/// The project `og init` creates from its flags, before any questions.
+fn init_plan(args: &InitArgs) -> scaffold::Plan {
+    let project = args.project.clone().unwrap_or_else(|| {
+        std::env::current_dir()
+            .ok()
+            .and_then(|dir| {
+                dir.file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+            })
+            .filter(|name| scaffold::check_project_name(name).is_ok())
+            .unwrap_or_else(|| "default".to_string())
+    });
+    let objective = args
+        .objective
+        .clone()
+        .map(|metric| project_config::Objective {
+            mode: args.mode.unwrap_or_else(|| scaffold::guess_mode(&metric)),
+            metric,
+        });
+    scaffold::Plan {
+        project,
+        objective,
+        python: args.python.clone(),
+    }
+}
+
+fn execute_init(args: InitArgs) -> Result<CommandOutput> {
+    let plan = init_plan(&args);
+    create_project(&args, &plan)
+}
+
+fn create_project(args: &InitArgs, plan: &scaffold::Plan) -> Result<CommandOutput> {
+    let files = scaffold::create(&args.path, plan, args.force)?;
+    let project_dir = args.path.join(&plan.project);
+    let mut text_lines = vec![format!(
+        "created project {} in {}",
+        plan.project,
+        project_dir.display()
+    )];
+    text_lines.extend(files.iter().map(|file| format!("- {}", file.display())));
+    text_lines.push(format!(
+        "next: og run train.py --path {}",
+        project_dir.join("baseline").display()
+    ));
+    Ok(CommandOutput {
+        command: "init".to_string(),
+        data: serde_json::json!({
+            "project": plan.project,
+            "path": project_dir.display().to_string(),
+            "objective": plan.objective,
+            "files": files.iter().map(|file| file.display().to_string()).collect::<Vec<_>>(),
+        }),
+        text: text_lines.join("\n"),
+    })
+}
+
+fn execute_doctor(args: DoctorArgs) -> Result<CommandOutput> {
+    // Without --socket, check every daemon socket: each run has its own.
+    let sockets = match args.socket {
+        Some(socket) => vec![socket],
+        None => {
+            let found = daemon_ctl::sockets_in(&socket_client::socket_dir());
+            if found.is_empty() {
+                vec![socket_client::socket_path()]
+            } else {
+                found
+            }
+        }
+    };
+    let agent_src = args.codebase_root.join("python").join("agent-chat");
+    let term = std::env::var("TERM").ok();
+    let colorterm = std::env::var("COLORTERM").ok();
+    let mut checks: Vec<doctor::Check> = sockets
+        .iter()
+        .map(|socket| doctor::daemon(socket))
+        .collect();
+    checks.extend([
+        doctor::python(
+            &find_python(&args.codebase_root),
+            Some(agent_src.as_path()).filter(|src| src.is_dir()),
+        ),
+        doctor::gpu(),
+        doctor::runs_dir(&args.path),
+    ]);
+    checks.extend(doctor::terminal(
+        term.as_deref(),
+        colorterm.as_deref(),
+        io::stdout().is_terminal(),
+    ));
+    let query_sockets: Vec<PathBuf> = sockets
+        .iter()
+        .map(|socket| query_server::socket_path_for(socket))
+        .collect();
+    checks.push(doctor::stale_files(&query_sockets, &args.path));
+
+    let count = |status| checks.iter().filter(|check| check.status == status).count();
+    let (warnings, failures) = (count(doctor::Status::Warn), count(doctor::Status::Fail));
+    let mut text_lines = Vec::new();
+    for check in &checks {
+        text_lines.push(format!(
+            "{:<6} {}: {}",
+            check.status.label(),
+            check.name,
+            check.detail
+        ));
+        if let Some(fix) = &check.fix {
+            text_lines.push(format!("       fix: {fix}"));
+        }
+    }
+    text_lines.push(match (failures, warnings) {
+        (0, 0) => "everything looks fine".to_string(),
+        _ => format!("{failures} problem(s), {warnings} warning(s)"),
+    });
+    Ok(CommandOutput {
+        command: "doctor".to_string(),
+        data: serde_json::json!({
+            "checks": checks,
+            "problems": failures,
+            "warnings": warnings,
+        }),
+        text: text_lines.join("\n"),
+    })
+}
+
+/// How long `og daemon start` waits for the daemon to answer on its socket.
+const DAEMON_START_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The daemon an `og daemon` subcommand acts on: `--socket` when given,
+/// else the only managed daemon running, else the default socket.
+fn daemon_target(socket: Option<&Path>) -> Result<daemon_ctl::Paths> {
+    if let Some(socket) = socket {
+        return Ok(daemon_ctl::Paths::for_socket(socket));
+    }
+    let mut running = daemon_ctl::running_in(&socket_client::socket_dir());
+    match running.len() {
+        0 => Ok(daemon_ctl::Paths::for_socket(&socket_client::socket_path())),
+        1 => Ok(running.remove(0).0),
+        _ => {
+            let sockets: Vec<String> = running
+                .iter()
+                .map(|(paths, _)| paths.socket.display().to_string())
+                .collect();
+            bail!(
+                "several daemons are running, pick one with --socket: {}",
+                sockets.join(", ")
+            )
+        }
+    }
+}
+
+/// `og daemon list`: managed daemons, plus sockets a TUI's own daemon
+/// answers on.
+fn list_daemons() -> Result<CommandOutput> {
+    let dir = socket_client::socket_dir();
+    let managed = daemon_ctl::running_in(&dir);
+    let mut rows = Vec::new();
+    let mut text_lines = Vec::new();
+    for (paths, pid) in &managed {
+        let launch: Option<DaemonStartArgs> = daemon_ctl::load_launch(paths).ok();
+        let responding = socket_client::ping(&paths.socket).unwrap_or(false);
+        text_lines.push(format!(
+            "{pid:>8}  {}  {}{}",
+            paths.socket.display(),
+            launch.as_ref().map_or_else(
+                || "?".to_string(),
+                |launch| launch.path.display().to_string()
+            ),
+            if responding { "" } else { "  (not answering)" }
+        ));
+        rows.push(serde_json::json!({
+            "socket": paths.socket.display().to_string(),
+            "pid": pid,
+            "managed": true,
+            "responding": responding,
+            "run_dir": launch.as_ref().map(|launch| launch.path.display().to_string()),
+            "training_file": launch.as_ref().map(|launch| launch.training_file.display().to_string()),
+        }));
+    }
+    for socket in daemon_ctl::sockets_in(&dir) {
+        if managed.iter().any(|(paths, _)| paths.socket == socket)
+            || !socket_client::ping(&socket).unwrap_or(false)
+        {
+            continue;
+        }
+        text_lines.push(format!(
+            "{:>8}  {}  (started by a TUI)",
+            "-",
+            socket.display()
+        ));
+        rows.push(serde_json::json!({
+            "socket": socket.display().to_string(),
+            "pid": null,
+            "managed": false,
+            "responding": true,
+            "run_dir": null,
+            "training_file": null,
+        }));
+    }
+    if text_lines.is_empty() {
+        text_lines.push(format!("no daemons in {}", dir.display()));
+    }
+    Ok(CommandOutput {
+        command: "daemon.list".to_string(),
+        data: serde_json::json!({ "daemons": rows }),
+        text: text_lines.join("\n"),
+    })
+}
+
+fn execute_discover(args: DiscoverArgs) -> Result<CommandOutput> {
+    let instances = discover::browse(Duration::from_millis(args.timeout_ms))?;
+    let mut text_lines: Vec<String> = instances
+        .iter()
+        .map(|instance| {
+            format!(
+                "{}  {}  {}  {}",
+                instance.host,
+                instance
+                    .endpoint()
+                    .map_or_else(|| format!("?:{}", instance.port), |addr| addr.to_string()),
+                instance
+                    .version
+                    .as_deref()
+                    .map_or_else(|| "-".to_string(), |version| format!("v{version}")),
+                if instance.projects.is_empty() {
+                    "(no projects)".to_string()
+                } else {
+                    instance.projects.join(", ")
+                }
+            )
+        })
+        .collect();
+    if text_lines.is_empty() {
+        text_lines.push(format!(
+            "no ogd instances answered within {}",
+            format::duration(args.timeout_ms as f64 / 1000.0)
+        ));
+    }
+    Ok(CommandOutput {
+        command: "discover".to_string(),
+        data: serde_json::json!({ "instances": instances }),
+        text: text_lines.join("\n"),
+    })
+}
+
+fn execute_daemon(args: DaemonArgs) -> Result<CommandOutput> {
+    match args.cmd {
+        DaemonSubcommand::Start(args) => start_daemon(args),
+        DaemonSubcommand::List => list_daemons(),
+        DaemonSubcommand::Stop(args) => {
+            let paths = daemon_target(args.socket.as_deref())?;
+            let Some(pid) = daemon_ctl::running_pid(&paths) else {
+                let _ = fs::remove_file(&paths.pid);
+                bail!(
+                    "no daemon started by `og daemon start` runs on {}",
+                    paths.socket.display()
+                );
+            };
+            let timeout = args
+                .timeout_secs
+                .map_or_else(daemon_ctl::stop_timeout, Duration::from_secs);
+            let killed = daemon_ctl::stop(&paths, pid, timeout)?;
+            Ok(CommandOutput {
+                command: "daemon.stop".to_string(),
+                data: serde_json::json!({ "pid": pid, "killed": killed }),
+                text: if killed {
+                    format!(
+                        "killed daemon (pid {pid}) after {}s without exiting",
+                        timeout.as_secs()
+                    )
+                } else {
+                    format!("stopped daemon (pid {pid})")
+                },
+            })
+        }
+        DaemonSubcommand::Status(args) => {
+            let paths = daemon_target(args.socket.as_deref())?;
+            let pid = daemon_ctl::running_pid(&paths);
+            let responding = socket_client::ping(&paths.socket).unwrap_or(false);
+            let launch: Option<DaemonStartArgs> =
+                pid.and_then(|_| daemon_ctl::load_launch(&paths).ok());
+            let state = match (pid, responding) {
+                (Some(pid), true) => format!("running (pid {pid}), answering"),
+                (Some(pid), false) => format!("running (pid {pid}), not answering yet"),
+                (None, true) => "answering, but not started by `og daemon start`".to_string(),
+                (None, false) => "not running".to_string(),
+            };
+            let mut text_lines = vec![format!("daemon on {}: {state}", paths.socket.display())];
+            if let Some(launch) = &launch {
+                text_lines.push(format!("training file: {}", launch.training_file.display()));
+                text_lines.push(format!("run dir: {}", launch.path.display()));
+            }
+            if paths.log.exists() {
+                text_lines.push(format!("log: {}", paths.log.display()));
+            }
+            Ok(CommandOutput {
+                command: "daemon.status".to_string(),
+                data: serde_json::json!({
+                    "socket": paths.socket.display().to_string(),
+                    "pid": pid,
+                    "running": pid.is_some(),
+                    "responding": responding,
+                    "launch": launch,
+                    "log": paths.log.display().to_string(),
+                }),
+                text: text_lines.join("\n"),
+            })
+        }
+        DaemonSubcommand::Restart(args) => {
+            let paths = daemon_target(args.socket.as_deref())?;
+            let launch: DaemonStartArgs = daemon_ctl::load_launch(&paths).with_context(|| {
+                format!(
+                    "no daemon was started with `og daemon start` on {}",
+                    paths.socket.display()
+                )
+            })?;
+            if let Some(pid) = daemon_ctl::running_pid(&paths) {
+                let timeout = args
+                    .timeout_secs
+                    .map_or_else(daemon_ctl::stop_timeout, Duration::from_secs);
+                daemon_ctl::stop(&paths, pid, timeout)?;
+            }
+            let mut output = start_daemon(launch)?;
+            output.command = "daemon.restart".to_string();
+            Ok(output)
+        }
+        DaemonSubcommand::Logs(args) => {
+            if args.follow {
+                bail!("daemon logs --follow streams to stdout; run it from the shell");
+            }
+            let paths = daemon_target(args.socket.as_deref())?;
+            let lines = daemon_ctl::tail_log(&paths, args.lines)?;
+            Ok(CommandOutput {
+                command: "daemon.logs".to_string(),
+                data: serde_json::json!({
+                    "log": paths.log.display().to_string(),
+                    "lines": lines,
+                }),
+                text: lines.join("\n"),
+            })
+        }
+    }
+}
+
+fn start_daemon(args: DaemonStartArgs) -> Result<CommandOutput> {
+    let run_dir = resolve_live_run_path(&args.path, run_name::DEFAULT_TEMPLATE)?;
+    let paths =
+        daemon_ctl::Paths::for_socket(&args.socket.clone().unwrap_or_else(|| {
+            socket_client::socket_path_for_run(&run_meta::run_dir_for(&run_dir))
+        }));
+    if daemon_ctl::running_pid(&paths).is_none()
+        && socket_client::ping(&paths.socket).unwrap_or(false)
+    {
+        bail!(
+            "a daemon not started by `og daemon start` already answers on {}",
+            paths.socket.display()
+        );
+    }
+    let (command, python) = daemon_command(
+        &args.training_file,
+        &args.codebase_root,
+        &run_dir,
+        args.auto,
+        args.runtime,
+        args.start_training,
+        false,
+        args.training_cmd.as_deref(),
+        &paths.socket,
+        None,
+    )?;
+    // Restarts reuse the run directory instead of starting another run.
+    let launch = DaemonStartArgs {
+        path: run_dir.clone(),
+        socket: Some(paths.socket.clone()),
+        ..args
+    };
+    let pid = daemon_ctl::spawn(command, &paths, &launch).with_context(|| {
+        format!("failed to start the daemon with '{python}'. Is og-agent-chat installed?")
+    })?;
+    let up = daemon_ctl::wait_until_up(&paths, pid, DAEMON_START_TIMEOUT);
+    if !up && !daemon_ctl::is_alive(pid) {
+        let _ = fs::remove_file(&paths.pid);
+        let log = daemon_ctl::tail_log(&paths, 10).unwrap_or_default();
+        bail!(
+            "the daemon exited during startup; the end of {}:\n{}",
+            paths.log.display(),
+            log.join("\n")
+        );
+    }
+    let mut text_lines = vec![format!(
+        "started daemon (pid {pid}) on {}{}",
+        paths.socket.display(),
+        if up { "" } else { "; not answering yet" }
+    )];
+    text_lines.push(format!("run dir: {}", run_dir.display()));
+    text_lines.push(format!("log: {}", paths.log.display()));
+    Ok(CommandOutput {
+        command: "daemon.start".to_string(),
+        data: serde_json::json!({
+            "pid": pid,
+            "socket": paths.socket.display().to_string(),
+            "responding": up,
+            "run_dir": run_dir.display().to_string(),
+            "log": paths.log.display().to_string(),
+        }),
+        text: text_lines.join("\n"),
+    })
+}
+
+/// `og daemon logs --follow`: the tail, then new lines as they are written.
+fn follow_daemon_log(args: &DaemonLogsArgs) -> Result<()> {
+    let paths = daemon_target(args.socket.as_deref())?;
+    for line in daemon_ctl::tail_log(&paths, args.lines)? {
+        println!("{line}");
+    }
+    let mut file = fs::File::open(&paths.log)?;
+    let mut offset = file.metadata()?.len();
+    let mut stdout = io::stdout();
+    loop {
+        let len = file.metadata()?.len();
+        if len < offset {
+            // Truncated or replaced: start over from the top.
+            offset = 0;
+        }
+        if len > offset {
+            file.seek(SeekFrom::Start(offset))?;
+            let mut chunk = Vec::new();
+            (&mut file).take(len - offset).read_to_end(&mut chunk)?;
+            stdout.write_all(&chunk)?;
+            stdout.flush()?;
+            offset = len;
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+}
+
+/// `og attach`: the detached daemon to reconnect to and how it was started.
+/// Looks at `--socket` alone when given, otherwise at every managed daemon
+/// next to the default socket.
+fn find_attach_target(args: &AttachArgs) -> Result<(daemon_ctl::Paths, DaemonStartArgs)> {
+    let candidates = match &args.socket {
+        Some(socket) => {
+            let paths = daemon_ctl::Paths::for_socket(socket);
+            daemon_ctl::running_pid(&paths)
+                .map(|pid| vec![(paths, pid)])
+                .unwrap_or_default()
+        }
+        None => {
+            let socket = socket_client::socket_path();
+            daemon_ctl::running_in(socket.parent().unwrap_or(Path::new(".")))
+        }
+    };
+    let mut running = Vec::new();
+    for (paths, _) in candidates {
+        let launch: DaemonStartArgs = daemon_ctl::load_launch(&paths)?;
+        running.push((paths, launch));
+    }
+    let describe = |running: &[(daemon_ctl::Paths, DaemonStartArgs)]| {
+        running
+            .iter()
+            .map(|(paths, launch)| {
+                format!("{} ({})", launch.path.display(), paths.socket.display())
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let Some(run) = &args.run else {
+        return match running.len() {
+            0 => bail!(
+                "no detached daemon is running; start one with `og run --detach` or `og daemon start`"
+            ),
+            1 => Ok(running.remove(0)),
+            _ => bail!(
+                "several detached daemons are running, name a run: {}",
+                describe(&running)
+            ),
+        };
+    };
+    let wanted = resolve_qualified_run_path(&args.path, args.project.as_deref(), run);
+    let wanted = fs::canonicalize(&wanted).unwrap_or(wanted);
+    let position = running.iter().position(|(_, launch)| {
+        fs::canonicalize(&launch.path).is_ok_and(|path| path == wanted)
+            || launch.path.file_name() == Some(std::ffi::OsStr::new(run))
+    });
+    match position {
+        Some(index) => Ok(running.remove(index)),
+        None if running.is_empty() => bail!("no detached daemon is running"),
+        None => bail!(
+            "no detached daemon runs '{run}'; running: {}",
+            describe(&running)
+        ),
+    }
+}
+
+/// `--detach`: start the daemon the way `og daemon start` does, so it and the
+/// training it launches outlive the TUI.
+fn spawn_detached_daemon(
+    tui: &TuiArgs,
+    training_file: &Path,
+    run_dir: &Path,
+    start_training: bool,
+    socket: &Path,
+    grad_limits: grad_health::Limits,
+) -> Result<u32> {
+    let (command, python) = daemon_command(
+        training_file,
+        &tui.codebase_root,
+        run_dir,
+        tui.auto,
+        tui.runtime,
+        start_training,
+        tui.fresh_run,
+        tui.training_cmd.as_deref(),
+        socket,
+        Some(grad_limits),
+    )?;
+    let launch = DaemonStartArgs {
+        training_file: training_file.to_path_buf(),
+        training_cmd: tui.training_cmd.clone(),
+        start_training,
+        path: run_dir.to_path_buf(),
+        codebase_root: tui.codebase_root.clone(),
+        runtime: tui.runtime,
+        auto: tui.auto,
+        socket: Some(socket.to_path_buf()),
+    };
+    daemon_ctl::spawn(command, &daemon_ctl::Paths::for_socket(socket), &launch).with_context(|| {
+        format!("failed to start the daemon with '{python}'. Is og-agent-chat installed?")
+    })
+}
+
+/// `--bounds 0,1`: two finite values, the first below the second.
+fn parse_bounds(raw: &str) -> Result<[f64; 2]> {
+    let parse = |part: &str| {
+        part.trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+    };
+    match raw.split_once(',').map(|(lo, hi)| (parse(lo), parse(hi))) {
+        Some((Some(lo), Some(hi))) if lo < hi => Ok([lo, hi]),
+        _ => bail!("expected LO,HI with LO below HI, got '{raw}'"),
+    }
+}
+
+fn execute_describe(args: DescribeArgs) -> Result<CommandOutput> {
+    let run_path = resolve_run_path(&args.path, args.project.as_deref(), &args.run);
+    if !run_path.exists() {
+        return Err(OgError::run_not_found(&args.run, &run_path).into());
+    }
+    let metric = args.metric.trim().to_string();
+    if metric.is_empty() {
+        bail!("metric must be non-empty");
+    }
+    let given = run_meta::MetricAttrs {
+        unit: args.unit.map(|unit| unit.trim().to_string()),
+        higher_is_better: if args.higher_is_better {
+            Some(true)
+        } else if args.lower_is_better {
+            Some(false)
+        } else {
+            None
+        },
+        scale: args.scale,
+        display_name: args.display_name.map(|name| name.trim().to_string()),
+        bounds: args.bounds,
+        card: args.card,
+    };
+    let attrs = run_meta::update(&run_path, |meta| {
+        if args.clear {
+            meta.metrics.remove(&metric);
+        }
+        if given != run_meta::MetricAttrs::default() {
+            meta.metrics.entry(metric.clone()).or_default().merge(given);
+        }
+        meta.metrics.get(&metric).cloned().unwrap_or_default()
+    })?;
+
+    let mut parts = Vec::new();
+    if let Some(name) = &attrs.display_name {
+        parts.push(format!("display name \"{name}\""));
+    }
+    if let Some(unit) = &attrs.unit {
+        parts.push(format!("unit {unit}"));
+    }
+    match attrs.higher_is_better {
+        Some(true) => parts.push("higher is better".to_string()),
+        Some(false) => parts.push("lower is better".to_string()),
+        None => {}
+    }
+    match attrs.scale {
+        Some(run_meta::Scale::Log) => parts.push("log scale".to_string()),
+        Some(run_meta::Scale::Linear) => parts.push("linear scale".to_string()),
+        None => {}
+    }
+    if let Some([lo, hi]) = attrs.bounds {
+        parts.push(format!(
+            "bounds {}–{}",
+            format::value(lo),
+            format::value(hi)
+        ));
+    }
+    match attrs.card {
+        Some(run_meta::Card::Gauge) => parts.push("gauge card".to_string()),
+        Some(run_meta::Card::Line) => parts.push("line card".to_string()),
+        None => {}
+    }
+    let data = serde_json::json!({
+        "run": run_path.display().to_string(),
+        "metric": metric,
+        "attrs": attrs,
+    });
+    Ok(CommandOutput {
+        command: "describe".to_string(),
+        data,
+        text: format!(
+            "{metric} on {}: {}",
+            run_path.display(),
+            if parts.is_empty() {
+                "no attributes".to_string()
+            } else {
+                parts.join(", ")
+            }
+        ),
+    })
+}
+
+struct ForkedRun {
+    source_id: String,
+    path: PathBuf,
+    meta: run_meta::RunMeta,
+}
+
+fn execute_fork(args: ForkArgs) -> Result<CommandOutput> {
+    if args.launch {
+        bail!("fork --launch starts a new TUI session; run it from the shell");
+    }
+    let forked = fork_run(&args)?;
+
+    let mut text_lines = vec![format!(
+        "forked {} -> {}",
+        forked.source_id,
+        forked.path.display()
+    )];
+    for (key, value) in &forked.meta.config {
+        text_lines.push(format!("  {} = {}", key, value));
+    }
+    match &forked.meta.launch {
+        Some(launch) => text_lines.push(format!(
+            "launch: og run {} --path {}",
+            launch.training_file.display(),
+            forked.path.display()
+        )),
+        None => text_lines.push("launch: no training file recorded for source run".to_string()),
+    }
+
+    let data = serde_json::json!({
+        "source": forked.source_id,
+        "run": forked
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned()),
+        "path": forked.path.display().to_string(),
+        "config": forked.meta.config,
+        "launch": forked.meta.launch,
+    });
+    Ok(CommandOutput {
+        command: "fork".to_string(),
+        data,
+        text: text_lines.join("\n"),
+    })
+}
+
+/// Allocate a sibling run directory and write the forked metadata into it.
+fn fork_run(args: &ForkArgs) -> Result<ForkedRun> {
+    let source = resolve_run_path(&args.path, args.project.as_deref(), &args.run);
+    if !source.exists() {
+        return Err(OgError::run_not_found(&args.run, &source).into());
+    }
+    let source_dir = run_meta::run_dir_for(&source);
+    let source_id = source_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| args.run.clone());
+    let overrides = parse_config_overrides(&args.set)?;
+
+    let new_id = args
+        .name
+        .clone()
+        .unwrap_or_else(|| format!("{}-fork-{}", source_id, unix_now_secs()));
+    if new_id.trim().is_empty() || new_id.contains(['/', '\\']) {
+        bail!("invalid run name '{}'", new_id);
+    }
+    let parent = source_dir
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let path = parent.join(&new_id);
+    if path.exists() {
+        bail!("run directory {} already exists", path.display());
+    }
+
+    let mut meta = run_meta::load(&source)?.fork(&source_id, &overrides);
+    project_config::load(&parent)?.apply_to_new_run(&mut meta);
+    run_meta::save(&path, &meta)?;
+    Ok(ForkedRun {
+        source_id,
+        path,
+        meta,
+    })
+}
+
+fn parse_config_overrides(raw: &[String]) -> Result<BTreeMap<String, String>> {
+    let mut overrides = BTreeMap::new();
+    for item in raw {
+        let Some((key, value)) = item.split_once('=') else {
+            bail!("invalid --set '{}': expected key=value", item);
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            bail!("invalid --set '{}': key must be non-empty", item);
         }
+        overrides.insert(key.to_string(), value.trim().to_string());
     }
+    Ok(overrides)
 }
 
-fn run_args_to_tui(args: &RunArgs) -> TuiArgs {
-    TuiArgs {
-        path: Some(args.path.clone()),
-        graph: args.graph.clone(),
-        graph_labels: args.graph_labels.clone(),
-        training_file: Some(args.file.clone()),
-        training_cmd: args.training_cmd.clone(),
-        start_training: true,
-        fresh_run: false,
-        codebase_root: args.codebase_root.clone(),
-        auto: args.auto_mode.daemon_auto_enabled(),
-        runtime: args.runtime,
-        socket: args.socket.clone(),
-        refresh_ms: args.refresh_ms,
-        procs_sort: args.procs_sort,
-        procs_interval_ms: args.procs_interval_ms,
-        procs_limit: args.procs_limit,
+fn execute_list_projects(args: ListProjectsArgs) -> Result<CommandOutput> {
+    let base = args.path;
+    let projects = query::list_projects(&base)?;
+
+    let mut text_lines = vec![format!("projects in {}", base.display())];
+    for p in &projects {
+        text_lines.push(format!("- {} ({})", p.name, p.run_count));
     }
+    if projects.is_empty() {
+        text_lines.push("- none".to_string());
+    }
+
+    let data = serde_json::json!({
+        "root": base.display().to_string(),
+        "projects": projects,
+    });
+    Ok(CommandOutput {
+        command: "list.projects".to_string(),
+        data,
+        text: text_lines.join("\n"),
+    })
 }
 
-fn run_tui(
-    tui: &TuiArgs,
-    startup_prompt: Option<String>,
-    graph_filter: Option<GraphFilter>,
-    clean_start: bool,
-) -> Result<()> {
-    let daemon_expected = tui.training_file.is_some() || tui.socket.is_some();
-    let metric_labels = parse_graph_labels(tui.graph_labels.as_deref())?;
-    let requested_path = tui.path.clone().unwrap_or_else(|| PathBuf::from("runs/"));
-    let events_path = if tui.training_file.is_some() && tui.start_training {
-        resolve_live_run_path(&requested_path)?
-    } else {
-        requested_path
-    };
-    let mut initial = if clean_start {
-        ViewData {
-            scalars: BTreeMap::new(),
-            log_lines: vec![
-                "-- clean view --".to_string(),
-                "[info] no run loaded yet".to_string(),
-                "[info] use `og run <file>` or pass --path to load existing runs".to_string(),
-            ],
-            total_events: 0,
-            max_step: 0,
+fn execute_list_readers(args: ListReadersArgs) -> Result<CommandOutput> {
+    let names: Vec<&str> = readers::registry()
+        .iter()
+        .map(|reader| reader.name())
+        .collect();
+    let mut text_lines = vec!["readers (first match wins)".to_string()];
+    text_lines.extend(names.iter().map(|name| format!("- {}", name)));
+
+    let mut data = serde_json::json!({ "readers": names });
+    if let Some(file) = args.file {
+        let Some(reader) = readers::reader_for(&file) else {
+            bail!("no reader recognizes {}", file.display());
+        };
+        let summary = reader.summarize(&file)?;
+        text_lines.push(format!(
+            "{}: read by {}, {} points over {} tags, last step {}",
+            file.display(),
+            reader.name(),
+            summary.points,
+            summary.tags.len(),
+            summary
+                .last_step
+                .map(|step| step.to_string())
+                .unwrap_or_else(|| "-".to_string())
+        ));
+        if !summary.corruption.is_clean() {
+            text_lines.push(describe_corruption(&summary.corruption));
         }
-    } else {
-        load_view_data(&events_path)?
+        data["file"] = serde_json::json!({
+            "path": file.display().to_string(),
+            "reader": reader.name(),
+            "summary": summary,
+        });
+    }
+    Ok(CommandOutput {
+        command: "list.readers".to_string(),
+        data,
+        text: text_lines.join("\n"),
+    })
+}
+
+fn execute_list_runs(args: ListRunsArgs) -> Result<CommandOutput> {
+    let base = project_base(&args.path, args.project.as_deref());
+    // Job states can change a run's status, so filter on it afterwards.
+    let status_after_slurm = args.status.clone().filter(|_| args.slurm_state);
+    let filter = query::RunFilter {
+        status: args.status.clone().filter(|_| !args.slurm_state),
+        tags: args.tag.clone(),
+        config: args
+            .config
+            .iter()
+            .map(|raw| run_query::parse(raw))
+            .collect::<Result<Vec<_>>>()?,
+        slurm_job: args.slurm_job.clone(),
     };
+    let progress = par_load::stderr_progress("loading runs");
+    let mut runs = query::list_runs(
+        &base,
+        &filter,
+        args.jobs.unwrap_or_else(par_load::default_jobs),
+        Some(&progress),
+    )?;
 
-    if let Some(filter) = graph_filter.as_ref() {
-        initial.scalars = filter_scalars(initial.scalars, filter);
+    if args.slurm_state {
+        apply_slurm_states(&mut runs);
+        if let Some(status) = &status_after_slurm {
+            runs.retain(|run| run.status.eq_ignore_ascii_case(status));
+        }
     }
 
-    let app_path = if clean_start {
-        PathBuf::from("(clean)")
+    let sizes = run_index::run_sizes(&base, &list_run_dirs(&base)?);
+    for run in &mut runs {
+        run.size_bytes = sizes.get(&run.id).copied();
+    }
+    let mut text_lines = vec![format!("runs in {}", base.display())];
+    for run in &runs {
+        let mut line = format!(
+            "- {} | status={} | metrics={} | step={}",
+            run.id, run.status, run.metric_count, run.max_step
+        );
+        if let Some(size) = run.size_bytes {
+            line.push_str(&format!(" | size={}", format::bytes(size)));
+            if size > args.run_size_warn {
+                line.push_str(" (large)");
+            }
+        }
+        text_lines.push(line);
+    }
+    if runs.is_empty() {
+        text_lines.push("- none".to_string());
+    }
+    let free_bytes = disk_usage::free_space(&base);
+    if let Some(free) = free_bytes
+        && free < args.min_free_space
+    {
+        text_lines.push(format!(
+            "warning: only {} free on the filesystem holding {} (floor {})",
+            format::bytes(free),
+            base.display(),
+            format::bytes(args.min_free_space)
+        ));
+    }
+
+    let data = serde_json::json!({
+        "base": base.display().to_string(),
+        "count": runs.len(),
+        "runs": runs,
+        "run_size_warn_bytes": args.run_size_warn,
+        "free_bytes": free_bytes,
+    });
+    Ok(CommandOutput {
+        command: "list.runs".to_string(),
+        data,
+        text: text_lines.join("\n"),
+    })
+}
+
+/// Fold the state of each run's recorded SLURM job into its status.
+fn apply_slurm_states(runs: &mut [query::RunSummary]) {
+    let jobs: Vec<Option<String>> = runs
+        .iter()
+        .map(|run| {
+            run_meta::load(Path::new(&run.path))
+                .ok()?
+                .slurm
+                .map(|job| job.job_id)
+        })
+        .collect();
+    let mut ids: Vec<String> = jobs.iter().flatten().cloned().collect();
+    ids.sort();
+    ids.dedup();
+    let states = slurm::job_states(&ids);
+    for (run, job) in runs.iter_mut().zip(jobs) {
+        if let Some(job_status) = job
+            .and_then(|job| states.get(&job))
+            .and_then(|state| slurm::status_for(state))
+        {
+            run.status = slurm::merged_status(&run.status, job_status).to_string();
+        }
+    }
+}
+
+fn execute_list_metrics(args: ListMetricsArgs, system_only: bool) -> Result<CommandOutput> {
+    let run_path = resolve_run_path(&args.path, args.project.as_deref(), &args.run);
+    let metrics = query::list_metrics(&run_path, system_only)?;
+    let names: Vec<String> = metrics.keys().cloned().collect();
+
+    let header = if system_only {
+        "system metrics"
     } else {
-        events_path.clone()
+        "metrics"
     };
-    let mut app = App::new(
-        initial.scalars,
-        metric_labels,
-        initial.log_lines,
-        app_path,
-        initial.total_events,
-        initial.max_step,
-    );
-    app.set_process_preferences(tui.procs_sort, tui.procs_limit);
-    if !daemon_expected {
-        app.chat_status = "No daemon (optional)".to_string();
+    let mut text_lines = vec![format!("{} in {}", header, run_path.display())];
+    if !args.kinds {
+        for name in &names {
+            text_lines.push(format!("- {}", name));
+        }
     }
-
-    // Override socket path if provided
-    if let Some(ref sock) = tui.socket {
-        app.daemon_socket = sock.clone();
+    if names.is_empty() && !args.kinds {
+        text_lines.push("- none".to_string());
     }
 
-    // ── Spawn agent daemon if --training-file is provided ───────────────
-    let mut daemon_child: Option<Child> = None;
-    if let Some(ref training_file) = tui.training_file {
-        let start_training = tui.start_training || tui.training_cmd.is_some();
-        match spawn_daemon(
-            training_file,
-            &tui.codebase_root,
-            &events_path,
-            tui.auto,
-            tui.runtime,
-            start_training,
-            tui.fresh_run,
-            tui.training_cmd.as_deref(),
-            &app.daemon_socket,
-        ) {
-            Ok(child) => {
-                daemon_child = Some(child);
-                app.chat_status = "Daemon starting...".to_string();
-            }
-            Err(e) => {
-                app.chat_status = format!("Daemon failed: {}", e);
+    let mut data = serde_json::json!({
+        "run": run_path.display().to_string(),
+        "system_only": system_only,
+        "count": names.len(),
+        "metrics": names,
+    });
+    if args.kinds {
+        // (tag, kind, entries): scalar points, audio clips, table versions.
+        let mut kinds: Vec<(String, &str, usize)> = metrics
+            .iter()
+            .map(|(name, &points)| (name.clone(), "scalar", points))
+            .collect();
+        if !system_only {
+            kinds.extend(
+                audio::count_by_tag(&audio::load(&run_path)?)
+                    .into_iter()
+                    .map(|(tag, count)| (tag, "audio", count)),
+            );
+            let mut tables: BTreeMap<String, usize> = BTreeMap::new();
+            for table in table::load(&run_path)? {
+                *tables.entry(table.name).or_default() += 1;
             }
+            kinds.extend(
+                tables
+                    .into_iter()
+                    .map(|(name, count)| (name, "table", count)),
+            );
+        }
+        kinds.sort();
+        let tag_w = kinds.iter().map(|(tag, _, _)| tag.len()).max().unwrap_or(0);
+        for (tag, kind, count) in &kinds {
+            text_lines.push(format!("- {tag:<tag_w$}  {kind:<6}  {count}"));
         }
+        if kinds.is_empty() {
+            text_lines.push("- none".to_string());
+        }
+        data["kinds"] = kinds
+            .iter()
+            .map(|(tag, kind, count)| serde_json::json!({"tag": tag, "kind": kind, "entries": count}))
+            .collect();
     }
-
-    // ── Setup terminal ──────────────────────────────────────────────────
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(
-        stdout,
-        EnterAlternateScreen,
-        crossterm::event::EnableMouseCapture
-    )?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-
-    let result = run_app(
-        &mut terminal,
-        app,
-        if clean_start {
-            None
+    Ok(CommandOutput {
+        command: if system_only {
+            "list.system-metrics".to_string()
         } else {
-            Some(events_path.as_path())
+            "list.metrics".to_string()
         },
-        tui.refresh_ms,
-        tui.procs_interval_ms,
-        startup_prompt,
-        graph_filter,
-        daemon_expected,
-    );
+        data,
+        text: text_lines.join("\n"),
+    })
+}
 
-    // ── Restore terminal ────────────────────────────────────────────────
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        crossterm::event::DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+fn execute_get_run(args: GetRunArgs) -> Result<CommandOutput> {
+    let run_path = resolve_run_path(&args.path, args.project.as_deref(), &args.run);
+    let detail = query::get_run(&run_path, args.rank)?;
+    let summary = &detail.summary;
+    let events = tfevents::load_run(&run_path)?.events;
+    let energy = power::energy(&events);
+    let meta = run_meta::load(&run_path)?;
+    let pricing = cost::Pricing::resolve(&project_config::load(&run_path)?)?;
+    let spend = cost::wall_secs(&events)
+        .zip(cost::run_gpus(&meta, detail.ranks.len()))
+        .and_then(|(secs, gpus)| cost::estimate(secs, gpus, pricing));
+    let environment = if args.env {
+        env_capture::load(&run_path)?
+    } else {
+        None
+    };
 
-    // ── Kill daemon child if we spawned it ──────────────────────────────
-    if let Some(ref mut child) = daemon_child {
-        let _ = child.kill();
-        let _ = child.wait();
+    let mut text_lines = vec![
+        format!("run {}", summary.id),
+        format!("path: {}", summary.path),
+        format!("status: {}", summary.status),
+        format!("metrics: {}", summary.metric_count),
+        format!("events: {}", summary.event_count),
+        format!("max_step: {}", summary.max_step),
+    ];
+    if !detail.ranks.is_empty() {
+        let ranks: Vec<String> = detail.ranks.iter().map(u32::to_string).collect();
+        text_lines.push(format!("ranks: {}", ranks.join(", ")));
+    }
+    text_lines.push(match args.rank {
+        Some(rank) => format!("latest metrics (rank {}):", rank),
+        None => "latest metrics:".to_string(),
+    });
+    for (metric, point) in &detail.latest_metrics {
+        text_lines.push(format!("- {}: {}", metric, serde_json::json!(point)));
+    }
+    if !detail.corruption.is_clean() {
+        text_lines.push(format!(
+            "corruption: {}",
+            describe_corruption(&detail.corruption)
+        ));
+    }
+    if let Some(spend) = &spend {
+        text_lines.push(format!("spend: {}", spend.describe()));
     }
+    if let Some(energy) = &energy {
+        text_lines.push(format!("energy: {}", energy.describe()));
+    }
+    if let Some(git) = &meta.git {
+        text_lines.push(format!(
+            "git: {} ({}){}",
+            git.short_commit(),
+            git.branch.as_deref().unwrap_or("detached"),
+            if git.dirty { " dirty" } else { "" }
+        ));
+    }
+    if let Some(job) = &meta.slurm {
+        let mut line = format!("slurm: job {}", job.job_id);
+        if let Some(nodes) = &job.node_list {
+            line.push_str(&format!(" on {nodes}"));
+        }
+        if let Some(partition) = &job.partition {
+            line.push_str(&format!(" ({partition})"));
+        }
+        text_lines.push(line);
+    }
+    if let Some(pod) = &meta.k8s {
+        let mut line = format!("k8s: pod {}/{}", pod.namespace, pod.pod);
+        if let Some(node) = &pod.node {
+            line.push_str(&format!(" on {node}"));
+        }
+        let quantities = |map: &BTreeMap<String, String>| {
+            map.iter()
+                .map(|(name, quantity)| format!("{name}={quantity}"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        if !pod.requests.is_empty() {
+            line.push_str(&format!(" | requests {}", quantities(&pod.requests)));
+        }
+        if !pod.limits.is_empty() {
+            line.push_str(&format!(" | limits {}", quantities(&pod.limits)));
+        }
+        text_lines.push(line);
+    }
+    if !meta.annotations.is_empty() {
+        text_lines.push("annotations:".to_string());
+        for annotation in &meta.annotations {
+            text_lines.push(format!(
+                "- step {} [{}] {}",
+                annotation.step,
+                annotation.kind.label(),
+                annotation.message
+            ));
+        }
+    }
+
+    if args.env {
+        match &environment {
+            Some(env) => {
+                text_lines.push(format!(
+                    "python: {}",
+                    env.python.as_deref().unwrap_or("unknown")
+                ));
+                text_lines.push("env vars:".to_string());
+                for (name, value) in &env.env_vars {
+                    text_lines.push(format!("- {}={}", name, value));
+                }
+                text_lines.push(format!(
+                    "packages ({}):",
+                    env.package_manager.as_deref().unwrap_or("none")
+                ));
+                for (name, version) in &env.packages {
+                    text_lines.push(format!("- {}=={}", name, version));
+                }
+            }
+            None => text_lines.push("environment: not captured".to_string()),
+        }
+    }
+
+    let mut data = serde_json::to_value(&detail)?;
+    data["color"] = serde_json::json!(run_color_hex(&run_path));
+    data["annotations"] = serde_json::to_value(&meta.annotations)?;
+    data["git"] = serde_json::to_value(&meta.git)?;
+    data["slurm"] = serde_json::to_value(&meta.slurm)?;
+    data["k8s"] = serde_json::to_value(&meta.k8s)?;
+    data["spend"] = serde_json::to_value(spend)?;
+    data["energy"] = serde_json::to_value(energy)?;
+    if args.env {
+        data["environment"] = serde_json::to_value(&environment)?;
+    }
+    Ok(CommandOutput {
+        command: "get.run".to_string(),
+        data,
+        text: text_lines.join("\n"),
+    })
+}
+
+fn execute_get_metric(args: GetMetricArgs) -> Result<CommandOutput> {
+    let run_path = resolve_run_path(&args.path, args.project.as_deref(), &args.run);
+    let series = query::get_metric(&run_path, &args.metric)?;
+    let tail = &series.points[series.count.saturating_sub(20)..];
 
-    if let Err(e) = result {
-        eprintln!("Error: {e:?}");
+    let mut text_lines = vec![
+        format!("run: {}", series.run),
+        format!("metric: {}", series.metric),
+        format!("count: {}", series.count),
+        format!("min: {}", format::value(series.min)),
+        format!("max: {}", format::value(series.max)),
+        format!("last: {}", format::value(series.last)),
+        "tail:".to_string(),
+    ];
+    for point in tail {
+        text_lines.push(format!(
+            "- step {} => {}",
+            point.step,
+            format::value(point.value)
+        ));
     }
 
-    Ok(())
+    Ok(CommandOutput {
+        command: "get.metric".to_string(),
+        data: serde_json::to_value(&series)?,
+        text: text_lines.join("\n"),
+    })
 }
 
-fn print_command_output(output: &CommandOutput, json: bool) -> Result<()> {
-    if json {
-        println!("{}", serde_json::to_string_pretty(&output.data)?);
-    } else if !output.text.is_empty() {
-        println!("{}", output.text);
+fn execute_get_table(args: GetTableArgs) -> Result<CommandOutput> {
+    let run_path = resolve_run_path(&args.path, args.project.as_deref(), &args.run);
+    if !run_path.exists() {
+        return Err(OgError::run_not_found(&args.run, &run_path).into());
     }
-    Ok(())
-}
-
-fn parse_graph_filter(raw: &str) -> Result<GraphFilter> {
-    fn parse_string_or_array(value: Option<&Value>, key: &str) -> Result<Vec<String>> {
-        let Some(value) = value else {
-            return Ok(Vec::new());
-        };
-        match value {
-            Value::String(s) => Ok(vec![s.clone()]),
-            Value::Array(items) => {
-                let mut out = Vec::new();
-                for item in items {
-                    let Some(s) = item.as_str() else {
-                        bail!("'{}' array must contain only strings", key);
-                    };
-                    out.push(s.to_string());
-                }
-                Ok(out)
-            }
-            _ => bail!("'{}' must be a string or an array of strings", key),
+    let logged: Vec<table::Table> = table::load(&run_path)?
+        .into_iter()
+        .filter(|t| args.table.as_ref().is_none_or(|name| &t.name == name))
+        .filter(|t| args.step.is_none_or(|step| t.step <= step))
+        .collect();
+    let tables = table::latest(logged);
+    if tables.is_empty() {
+        match &args.table {
+            Some(name) => bail!("table '{}' not found in run {}", name, run_path.display()),
+            None => bail!("no tables found in run {}", run_path.display()),
         }
     }
 
-    let parsed: Value =
-        serde_json::from_str(raw).with_context(|| "parsing --graph JSON".to_string())?;
-    let obj = parsed
-        .as_object()
-        .ok_or_else(|| anyhow::anyhow!("--graph must be a JSON object"))?;
-    let metrics = parse_string_or_array(obj.get("metrics"), "metrics")?;
-    let sys = parse_string_or_array(obj.get("sys"), "sys")?;
-    Ok(GraphFilter { metrics, sys })
+    let text = tables
+        .iter()
+        .map(table::Table::render_text)
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let data = serde_json::json!({
+        "run": run_path.display().to_string(),
+        "tables": tables,
+    });
+    Ok(CommandOutput {
+        command: "get.table".to_string(),
+        data,
+        text,
+    })
 }
 
-fn parse_graph_labels(raw: Option<&str>) -> Result<BTreeMap<String, String>> {
-    let Some(raw) = raw else {
-        return Ok(BTreeMap::new());
+fn execute_compare(args: CompareArgs) -> Result<CommandOutput> {
+    if args.runs.is_empty() {
+        bail!("--runs must include at least one run id/path");
+    }
+    let base = project_base(&args.path, args.project.as_deref());
+    let objective = project_config::load(&base)?.objective;
+    let metric = match args.metric {
+        Some(metric) => metric,
+        None => match &objective {
+            Some(objective) => objective.metric.clone(),
+            None => bail!(
+                "--metric is required (or set [objective] metric in {})",
+                base.join(project_config::FILE_NAME).display()
+            ),
+        },
     };
-    let trimmed = raw.trim();
-    if trimmed.is_empty() {
-        return Ok(BTreeMap::new());
+    if args.target.is_some_and(|target| !target.is_finite()) {
+        bail!("--target must be a finite number");
     }
 
-    if trimmed.starts_with('{') {
-        let parsed: Value = serde_json::from_str(trimmed)
-            .with_context(|| "parsing --graph-labels JSON".to_string())?;
-        let obj = parsed
-            .as_object()
-            .ok_or_else(|| anyhow::anyhow!("--graph-labels JSON must be an object"))?;
-        let mut out = BTreeMap::new();
-        for (key, value) in obj {
-            let label = value
-                .as_str()
-                .ok_or_else(|| anyhow::anyhow!("graph label for '{}' must be a string", key))?
-                .trim();
-            if !label.is_empty() {
-                out.insert(key.trim().to_string(), label.to_string());
-            }
+    let mut run_paths = Vec::new();
+    for run in &args.runs {
+        let run_path = resolve_qualified_run_path(&args.path, args.project.as_deref(), run);
+        if !run_path.exists() {
+            return Err(OgError::run_not_found(run, &run_path).into());
         }
-        return Ok(out);
+        run_paths.push(run_path);
     }
-
-    let mut out = BTreeMap::new();
-    for pair in trimmed.split(',') {
-        let part = pair.trim();
-        if part.is_empty() {
-            continue;
-        }
-        let (metric, label) = part.split_once('=').ok_or_else(|| {
-            anyhow::anyhow!("invalid graph label '{}'; expected metric=Label", part)
-        })?;
-        let metric = metric.trim();
-        let label = label.trim();
-        if metric.is_empty() || label.is_empty() {
+    let options = query::CompareOptions {
+        objective_higher_is_better: objective
+            .as_ref()
+            .filter(|objective| objective.metric == metric)
+            .map(|objective| objective.mode == project_config::ObjectiveMode::Max),
+        metric,
+        target: args.target,
+        x: args.x,
+    };
+    let comparisons = query::compare(&run_paths, &options)?;
+    // Runs that cannot be placed along epochs rank last; say which, and
+    // refuse a ranking where none of them can.
+    let mut warnings = Vec::new();
+    if args.target.is_some() && args.x == CompareAxis::Epoch {
+        let without_epochs: Vec<&str> = comparisons
+            .iter()
+            .filter(|comparison| comparison.target.is_some_and(|target| !target.epoch_scale))
+            .map(|comparison| comparison.run.as_str())
+            .collect();
+        if !without_epochs.is_empty()
+            && without_epochs.len() == comparisons.iter().filter(|c| c.found).count()
+        {
             bail!(
-                "invalid graph label '{}'; metric and label must be non-empty",
-                part
+                "--x epoch needs epoch data: no run logs an `epoch` tag or sets \
+                 steps_per_epoch in its config"
             );
         }
-        out.insert(metric.to_string(), label.to_string());
+        warnings.extend(without_epochs.into_iter().map(|run| {
+            format!("{run} has no epoch counter or steps_per_epoch; ranked after runs with one")
+        }));
     }
-    Ok(out)
-}
 
-fn filter_scalars(
-    scalars: BTreeMap<String, Vec<(f64, f64)>>,
-    filter: &GraphFilter,
-) -> BTreeMap<String, Vec<(f64, f64)>> {
-    if filter.metrics.is_empty() && filter.sys.is_empty() {
-        return scalars;
+    let mut text_lines = vec![match args.target {
+        Some(target) => format!(
+            "compare metric '{}' to target {}",
+            options.metric,
+            format::value(target)
+        ),
+        None => format!("compare metric '{}'", options.metric),
+    }];
+    for comparison in &comparisons {
+        let Some(stats) = &comparison.stats else {
+            text_lines.push(format!(
+                "- {}: metric '{}' not found",
+                comparison.run, options.metric
+            ));
+            continue;
+        };
+        text_lines.push(match &comparison.target {
+            None => format!(
+                "- {} | first={} last={} delta={} min={} max={}",
+                comparison.run,
+                format::value(stats.first),
+                format::value(stats.last),
+                format::value(stats.delta),
+                format::value(stats.min),
+                format::value(stats.max)
+            ),
+            Some(target) => match (target.step, target.wall_secs, target.value_at) {
+                (Some(step), Some(wall_secs), Some(value)) => format!(
+                    "- {} | reached {} after {} at step {}{} (value {})",
+                    comparison.run,
+                    format::value(target.value),
+                    format::duration(wall_secs),
+                    step,
+                    target
+                        .epoch
+                        .map(|epoch| format!(", epoch {}", format::epoch(epoch)))
+                        .unwrap_or_default(),
+                    format::value(value)
+                ),
+                _ => format!(
+                    "- {} | did not reach {} ({} {}, last {})",
+                    comparison.run,
+                    format::value(target.value),
+                    if target.higher_is_better {
+                        "max"
+                    } else {
+                        "min"
+                    },
+                    format::value(if target.higher_is_better {
+                        stats.max
+                    } else {
+                        stats.min
+                    }),
+                    format::value(stats.last)
+                ),
+            },
+        });
     }
 
-    let metrics: Vec<String> = filter
-        .metrics
-        .iter()
-        .map(|m| m.to_ascii_lowercase())
-        .collect();
-    let sys: Vec<String> = filter.sys.iter().map(|m| m.to_ascii_lowercase()).collect();
-    let mut filtered = BTreeMap::new();
+    let mut data = serde_json::json!({
+        "metric": options.metric,
+        "comparisons": comparisons,
+    });
+    if let Some(target) = args.target {
+        data["target"] = serde_json::json!(target);
+        data["x"] = serde_json::json!(args.x);
+    }
+    if !warnings.is_empty() {
+        text_lines.extend(warnings.iter().map(|warning| format!("warning: {warning}")));
+        data["warnings"] = serde_json::json!(warnings);
+    }
+    Ok(CommandOutput {
+        command: "compare".to_string(),
+        data,
+        text: text_lines.join("\n"),
+    })
+}
 
-    for (tag, series) in scalars.iter() {
-        let tag_l = tag.to_ascii_lowercase();
-        let metric_match = metrics.iter().any(|m| tag_l.contains(m));
-        let sys_match = sys.iter().any(|m| tag_l.contains(m));
-        if metric_match || sys_match {
-            filtered.insert(tag.clone(), series.clone());
+#[derive(Debug, Clone, Serialize)]
+struct LeaderboardEntry {
+    rank: usize,
+    run: String,
+    path: String,
+    best: f64,
+    best_step: i64,
+    last: f64,
+    hparams: BTreeMap<String, String>,
+}
+
+fn execute_leaderboard(args: LeaderboardArgs) -> Result<CommandOutput> {
+    let base = project_base(&args.path, args.project.as_deref());
+    let objective = project_config::load(&base)?.objective;
+    let Some(metric) = args
+        .metric
+        .clone()
+        .or_else(|| objective.as_ref().map(|objective| objective.metric.clone()))
+    else {
+        bail!(
+            "--metric is required (or set [objective] metric in {})",
+            base.join(project_config::FILE_NAME).display()
+        );
+    };
+    let run_dirs = list_run_dirs(&base)?;
+    // An explicit --metric other than the objective ranks by the direction
+    // `og describe` recorded for it, and by its name (`loss` is minimized)
+    // when no run records one.
+    let objective_mode = match objective {
+        Some(objective) if objective.metric == metric => objective.mode,
+        _ => {
+            let higher_is_better = run_dirs.iter().find_map(|run_dir| {
+                run_meta::load(run_dir)
+                    .ok()?
+                    .metrics
+                    .get(&metric)?
+                    .higher_is_better
+            });
+            match higher_is_better {
+                Some(true) => project_config::ObjectiveMode::Max,
+                Some(false) => project_config::ObjectiveMode::Min,
+                None => scaffold::guess_mode(&metric),
+            }
         }
+    };
+    let mode = args.mode.unwrap_or(match objective_mode {
+        project_config::ObjectiveMode::Min => LeaderboardMode::Min,
+        project_config::ObjectiveMode::Max => LeaderboardMode::Max,
+    });
+    // Values come from each run's full event files, so the runs are parsed
+    // in parallel. One unreadable run is skipped rather than failing the board.
+    let progress = par_load::stderr_progress("loading runs");
+    let views = par_load::map_bounded(
+        &run_dirs,
+        args.jobs.unwrap_or_else(par_load::default_jobs),
+        Some(&progress),
+        |run_dir| load_view_data(run_dir),
+    );
+    let mut entries = Vec::new();
+    let mut skipped = Vec::new();
+    for (run_dir, view) in run_dirs.into_iter().zip(views) {
+        let view = match view {
+            Ok(view) => view,
+            Err(err) => {
+                let err = format!("{err:#}");
+                eprintln!("warning: skipping {}: {err}", run_dir.display());
+                skipped.push(serde_json::json!({
+                    "path": run_dir.display().to_string(),
+                    "error": err,
+                }));
+                continue;
+            }
+        };
+        let Some(series) = view.scalars.get(&metric) else {
+            continue;
+        };
+        let best = series
+            .iter()
+            .filter(|(_, value)| value.is_finite())
+            .copied()
+            .reduce(|best, point| {
+                let better = match mode {
+                    LeaderboardMode::Max => point.1 > best.1,
+                    LeaderboardMode::Min => point.1 < best.1,
+                };
+                if better { point } else { best }
+            });
+        let Some((best_step, best)) = best else {
+            continue;
+        };
+        let meta = run_meta::load(&run_dir).unwrap_or_default();
+        entries.push(LeaderboardEntry {
+            rank: 0,
+            run: run_dir
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| run_dir.display().to_string()),
+            path: run_dir.display().to_string(),
+            best,
+            best_step: best_step as i64,
+            last: series.last().map(|(_, value)| *value).unwrap_or(best),
+            hparams: meta.config,
+        });
     }
 
-    if filtered.is_empty() {
-        scalars
-    } else {
-        filtered
+    entries.sort_by(|a, b| {
+        let order = match mode {
+            LeaderboardMode::Max => b.best.total_cmp(&a.best),
+            LeaderboardMode::Min => a.best.total_cmp(&b.best),
+        };
+        order.then_with(|| a.run.cmp(&b.run))
+    });
+    entries.truncate(args.top);
+    for (idx, entry) in entries.iter_mut().enumerate() {
+        entry.rank = idx + 1;
     }
-}
 
-fn metric_matches_filter(metric: &str, filter: &GraphFilter) -> bool {
-    if filter.metrics.is_empty() && filter.sys.is_empty() {
-        return true;
-    }
-    let metric_l = metric.to_ascii_lowercase();
-    filter
-        .metrics
-        .iter()
-        .any(|needle| metric_l.contains(&needle.to_ascii_lowercase()))
-        || filter
-            .sys
+    let columns: Vec<String> = if args.hparams.is_empty() {
+        let mut keys: Vec<String> = entries
             .iter()
-            .any(|needle| metric_l.contains(&needle.to_ascii_lowercase()))
-}
+            .flat_map(|entry| entry.hparams.keys().cloned())
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    } else {
+        args.hparams.clone()
+    };
+    for entry in &mut entries {
+        entry.hparams.retain(|key, _| columns.contains(key));
+    }
 
-#[derive(Debug, Clone, Serialize)]
-struct RunSummaryData {
-    id: String,
-    path: String,
-    metric_count: usize,
-    event_count: usize,
-    max_step: i64,
-    status: String,
-    last_updated_unix: Option<u64>,
-}
+    let mut header = vec![
+        "#".to_string(),
+        "run".to_string(),
+        format!("best {}", metric),
+        "step".to_string(),
+        "last".to_string(),
+    ];
+    header.extend(columns.iter().cloned());
+    let mut rows = vec![header];
+    for entry in &entries {
+        let mut row = vec![
+            entry.rank.to_string(),
+            entry.run.clone(),
+            format::value(entry.best),
+            entry.best_step.to_string(),
+            format::value(entry.last),
+        ];
+        row.extend(columns.iter().map(|key| {
+            entry
+                .hparams
+                .get(key)
+                .cloned()
+                .unwrap_or_else(|| "-".to_string())
+        }));
+        rows.push(row);
+    }
 
-fn execute_query_command(command: OgCommand) -> Result<CommandOutput> {
-    match command {
-        OgCommand::Run(_) => bail!("run must be executed in run mode"),
-        OgCommand::Tail(args) => execute_tail(args),
-        OgCommand::Resume(args) => execute_resume(args),
-        OgCommand::List(args) => execute_list(args),
-        OgCommand::Get(args) => execute_get(args),
-        OgCommand::Compare(args) => execute_compare(args),
-        OgCommand::Search(args) => execute_search(args),
+    let mut text_lines = vec![format!(
+        "leaderboard for '{}' ({}) in {}",
+        metric,
+        match mode {
+            LeaderboardMode::Max => "max",
+            LeaderboardMode::Min => "min",
+        },
+        base.display()
+    )];
+    if entries.is_empty() {
+        text_lines.push("- no runs report this metric".to_string());
+    } else {
+        text_lines.extend(render_table(&rows));
     }
+
+    let data = serde_json::json!({
+        "base": base.display().to_string(),
+        "metric": metric,
+        "mode": mode,
+        "hparams": columns,
+        "entries": entries,
+        "skipped": skipped,
+    });
+    Ok(CommandOutput {
+        command: "leaderboard".to_string(),
+        data,
+        text: text_lines.join("\n"),
+    })
 }
 
-fn execute_tail(args: TailArgs) -> Result<CommandOutput> {
-    let target_path = {
-        let explicit = PathBuf::from(&args.target);
-        if explicit.exists() {
-            explicit
-        } else {
-            args.path.join(&args.target)
+fn execute_regress(args: RegressArgs) -> Result<CommandOutput> {
+    let base = project_base(&args.path, args.project.as_deref());
+    let objective = project_config::load(&base)?.objective;
+    let metric = match args.metric {
+        Some(metric) => metric,
+        None => match &objective {
+            Some(objective) => objective.metric.clone(),
+            None => bail!(
+                "--metric is required (or set [objective] metric in {})",
+                base.join(project_config::FILE_NAME).display()
+            ),
+        },
+    };
+    let load = |run: &str| -> Result<(PathBuf, Vec<(f64, f64)>)> {
+        let run_path = resolve_qualified_run_path(&args.path, args.project.as_deref(), run);
+        if !run_path.exists() {
+            return Err(OgError::run_not_found(run, &run_path).into());
         }
+        let mut view = load_view_data(&run_path)?;
+        let Some(series) = view.scalars.remove(&metric) else {
+            return Err(OgError::metric_not_found(&metric, &run_path, view.scalars.keys()).into());
+        };
+        Ok((run_path, series))
+    };
+    let (baseline_path, baseline) = load(&args.baseline)?;
+    let (candidate_path, candidate) = load(&args.candidate)?;
+    // What `og describe` recorded, then the objective, then the metric's name.
+    let higher_is_better = [&baseline_path, &candidate_path]
+        .iter()
+        .find_map(|run_path| {
+            run_meta::load(run_path)
+                .ok()?
+                .metrics
+                .get(&metric)?
+                .higher_is_better
+        })
+        .or_else(|| {
+            let objective = objective.as_ref().filter(|o| o.metric == metric)?;
+            Some(objective.mode == project_config::ObjectiveMode::Max)
+        })
+        .unwrap_or_else(|| scaffold::guess_mode(&metric) == project_config::ObjectiveMode::Max);
+    let Some(verdict) = regression::check(
+        &baseline,
+        &candidate,
+        higher_is_better,
+        args.tolerance,
+        args.window,
+    ) else {
+        bail!(
+            "{} and {} have no step in common for '{}'",
+            baseline_path.display(),
+            candidate_path.display(),
+            metric
+        );
     };
 
-    if !target_path.exists() {
-        bail!("target '{}' not found", target_path.display());
+    let mut text_lines = vec![
+        format!("baseline: {}", baseline_path.display()),
+        format!("candidate: {}", candidate_path.display()),
+        format!(
+            "metric: {} ({} is better)",
+            metric,
+            if higher_is_better { "higher" } else { "lower" }
+        ),
+        format!(
+            "last {} of {} matched steps: baseline mean={} candidate mean={}",
+            verdict.window,
+            verdict.matched_steps,
+            format::value(verdict.baseline_mean),
+            format::value(verdict.candidate_mean)
+        ),
+        format!(
+            "candidate worse by {} (allowed {}) at {} of {} steps",
+            format::value(verdict.worse_by),
+            format::value(verdict.allowed),
+            verdict.worse_steps,
+            verdict.window
+        ),
+    ];
+    if let Some(worst) = verdict.worst.filter(|_| verdict.worse_steps > 0) {
+        text_lines.push(format!(
+            "furthest behind at step {}: baseline={} candidate={}",
+            worst.step,
+            format::value(worst.baseline),
+            format::value(worst.candidate)
+        ));
     }
-
-    let mut lines: Vec<String> = Vec::new();
-    let kind: &str;
-    if target_path.is_dir() {
-        kind = "run_events";
-        let view = load_view_data(&target_path)?;
-        let start = view.log_lines.len().saturating_sub(args.lines);
-        lines.extend(view.log_lines[start..].iter().cloned());
-    } else if target_path.is_file() && tfevents::is_tfevents_file(&target_path) {
-        kind = "tfevents";
-        let events = tfevents::parse_events_file(&target_path)?;
-        let start = events.len().saturating_sub(args.lines);
-        for ev in &events[start..] {
-            lines.push(format!(
-                "step {:>6} │ {:<30} │ {:.6}",
-                ev.step, ev.tag, ev.value
-            ));
-        }
-    } else {
-        kind = "text";
-        let raw = fs::read_to_string(&target_path)
-            .with_context(|| format!("reading {}", target_path.display()))?;
-        let all: Vec<&str> = raw.lines().collect();
-        let start = all.len().saturating_sub(args.lines);
-        lines.extend(all[start..].iter().map(|s| s.to_string()));
+    if verdict.non_finite {
+        text_lines.push("candidate logged NaN/inf at a matched step".to_string());
     }
+    text_lines.push(
+        if verdict.regressed {
+            "result: REGRESSED"
+        } else {
+            "result: ok"
+        }
+        .to_string(),
+    );
 
     let data = serde_json::json!({
-        "target": target_path.display().to_string(),
-        "kind": kind,
-        "line_count": lines.len(),
-        "lines": lines,
+        "baseline": baseline_path.display().to_string(),
+        "candidate": candidate_path.display().to_string(),
+        "metric": metric,
+        "higher_is_better": higher_is_better,
+        "tolerance": args.tolerance,
+        "regressed": verdict.regressed,
+        "verdict": verdict,
     });
+    Ok(CommandOutput {
+        command: "regress".to_string(),
+        data,
+        text: text_lines.join("\n"),
+    })
+}
 
-    let text = lines.join("\n");
+fn execute_digest(args: DigestArgs) -> Result<CommandOutput> {
+    if args.scheduled {
+        bail!("digest --scheduled runs in the foreground; start it from the shell");
+    }
+    let base = project_base(&args.path, args.project.as_deref());
+    let now = unix_now_secs();
+    let digest = build_digest(&base, now.saturating_sub(args.hours * 3600), now)?;
+    let mut text = digest.text();
+    let mut data = serde_json::json!({ "digest": digest });
+    if args.send {
+        let Some(config) = project_config::load(&base)?.digest else {
+            bail!(
+                "no [digest] table in {}",
+                base.join(project_config::FILE_NAME).display()
+            );
+        };
+        let sent = digest::deliver(&config, &digest)?;
+        text.push_str(&format!("\nsent to {}", sent.join(", ")));
+        data["sent"] = serde_json::json!(sent);
+    }
     Ok(CommandOutput {
-        command: "tail".to_string(),
+        command: "digest".to_string(),
         data,
         text,
     })
 }
 
-fn execute_resume(args: ResumeArgs) -> Result<CommandOutput> {
-    let run_path = args.path.join(&args.run_id);
-    let checkpoint_path = resolve_checkpoint_path(&args.checkpoint_dir, &args.checkpoint)?;
+/// The digest of the runs under `base` over `[since_unix, until_unix)`.
+fn build_digest(base: &Path, since_unix: u64, until_unix: u64) -> Result<digest::Digest> {
+    let objective = project_config::load(base)?.objective;
+    let mut runs = Vec::new();
+    for run_dir in list_run_dirs(base)? {
+        let Ok(loaded) = tfevents::load_run(&run_dir) else {
+            continue;
+        };
+        let meta = run_meta::load(&run_dir).unwrap_or_default();
+        let started = loaded
+            .events
+            .iter()
+            .map(|event| event.wall_time)
+            .filter(|time| time.is_finite() && *time > 0.0)
+            .fold(f64::INFINITY, f64::min);
+        let best = objective.as_ref().and_then(|objective| {
+            loaded
+                .scalars
+                .get(&objective.metric)?
+                .iter()
+                .filter(|(_, value)| value.is_finite())
+                .copied()
+                .reduce(|best, point| {
+                    let better = match objective.mode {
+                        project_config::ObjectiveMode::Max => point.1 > best.1,
+                        project_config::ObjectiveMode::Min => point.1 < best.1,
+                    };
+                    if better { point } else { best }
+                })
+                .map(|(step, value)| (step as i64, value))
+        });
+        runs.push(digest::RunSnapshot {
+            id: run_dir
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            started_unix: started.is_finite().then_some(started as u64),
+            last_updated_unix: latest_mtime_unix(&run_dir).ok().flatten(),
+            status: meta.status,
+            best,
+            alerts: digest::read_alerts(&run_dir)?,
+        });
+    }
+    let project = base
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| base.display().to_string());
+    Ok(digest::Digest::collect(
+        project, objective, since_unix, until_unix, runs,
+    ))
+}
 
-    let mut snapshot_files = Vec::new();
-    for entry in fs::read_dir(&checkpoint_path)? {
-        let entry = entry?;
-        if entry.path().is_file() && entry.file_name() != "state.json" {
-            snapshot_files.push(entry.file_name().to_string_lossy().to_string());
+/// `og digest --scheduled`: check every project's `[digest]` schedule twice a
+/// minute and send the digest covering the time since the last one. Config
+/// changes are picked up without a restart.
+fn run_digest_schedule(args: &DigestArgs) -> Result<()> {
+    let started = unix_now_secs();
+    let mut last_sent: BTreeMap<PathBuf, u64> = BTreeMap::new();
+    eprintln!("sending digests on their [digest] schedules (UTC); Ctrl-C to stop");
+    loop {
+        let projects = match args.project.as_deref() {
+            Some(project) => vec![project_base(&args.path, Some(project))],
+            None => list_immediate_dirs(&args.path)?,
+        };
+        let now = unix_now_secs();
+        for base in projects {
+            if !base.join(project_config::FILE_NAME).is_file() {
+                continue;
+            }
+            let config = match project_config::load(&base) {
+                Ok(config) => config,
+                Err(err) => {
+                    eprintln!("skipping {}: {err:#}", base.display());
+                    continue;
+                }
+            };
+            let Some(config) = config.digest else {
+                continue;
+            };
+            let last = last_sent.get(&base).copied();
+            let due = config.schedule.next_after(last.unwrap_or(started));
+            if due.is_none_or(|due| due > now) {
+                continue;
+            }
+            last_sent.insert(base.clone(), now);
+            let since = last.unwrap_or(now.saturating_sub(args.hours * 3600));
+            match build_digest(&base, since, now).and_then(|d| digest::deliver(&config, &d)) {
+                Ok(sent) => eprintln!("sent {} digest to {}", base.display(), sent.join(", ")),
+                Err(err) => eprintln!("digest for {} failed: {err:#}", base.display()),
+            }
         }
+        std::thread::sleep(Duration::from_secs(30));
+    }
+}
+
+fn execute_stats(args: StatsArgs) -> Result<CommandOutput> {
+    if args.window < 2 {
+        bail!("--window must be at least 2");
+    }
+    let run_path = resolve_run_path(&args.path, args.project.as_deref(), &args.run);
+    if !run_path.exists() {
+        return Err(OgError::run_not_found(&args.run, &run_path).into());
+    }
+    let view = load_view_data(&run_path)?;
+    let Some(series) = view.scalars.get(&args.metric) else {
+        return Err(OgError::metric_not_found(&args.metric, &run_path, view.scalars.keys()).into());
+    };
+    let Some(stats) = metric_stats::analyze(series, args.window) else {
+        bail!("metric '{}' has no finite values", args.metric);
+    };
+
+    let optional = |value: Option<f64>| match value {
+        Some(value) => format::value(value),
+        None => "-".to_string(),
+    };
+    let p = &stats.percentiles;
+    let mut text_lines = vec![
+        format!("run: {}", run_path.display()),
+        format!("metric: {}", args.metric),
+        format!("count: {}", stats.count),
+        format!("mean: {}", format::value(stats.mean)),
+        format!("std: {}", format::value(stats.std)),
+        format!("min: {}", format::value(stats.min)),
+        format!("max: {}", format::value(stats.max)),
+        format!("last: {}", format::value(stats.last)),
+        format!(
+            "percentiles: p5={} p25={} p50={} p75={} p95={}",
+            format::value(p.p5),
+            format::value(p.p25),
+            format::value(p.p50),
+            format::value(p.p75),
+            format::value(p.p95)
+        ),
+        format!(
+            "last {} points: slope={} autocorrelation={}",
+            stats.window,
+            optional(stats.slope),
+            optional(stats.autocorrelation)
+        ),
+        format!("plateau: {}", if stats.plateau { "yes" } else { "no" }),
+        format!("diverging: {}", if stats.diverging { "yes" } else { "no" }),
+    ];
+    if stats.non_finite > 0 {
+        text_lines.insert(3, format!("non-finite: {}", stats.non_finite));
     }
-    snapshot_files.sort();
 
     let data = serde_json::json!({
-        "run_id": args.run_id,
-        "run_path": run_path.display().to_string(),
-        "run_exists": run_path.exists(),
-        "checkpoint": args.checkpoint,
-        "checkpoint_path": checkpoint_path.display().to_string(),
-        "snapshot_files": snapshot_files,
-        "note": "Checkpoint resolution is available. Resume apply/restore is handled by the agent refactor loop."
+        "run": run_path.display().to_string(),
+        "metric": args.metric,
+        "stats": stats,
     });
-
-    let text = format!(
-        "run: {}\ncheckpoint: {}\nfiles: {}\nnote: resume metadata resolved; apply/restore is agent-managed.",
-        run_path.display(),
-        checkpoint_path.display(),
-        snapshot_files.join(", ")
-    );
     Ok(CommandOutput {
-        command: "resume".to_string(),
+        command: "stats".to_string(),
         data,
-        text,
+        text: text_lines.join("\n"),
     })
 }
 
-fn execute_list(args: ListArgs) -> Result<CommandOutput> {
-    match args.cmd {
-        ListSubcommand::Projects(a) => execute_list_projects(a),
-        ListSubcommand::Runs(a) => execute_list_runs(a),
-        ListSubcommand::Metrics(a) => execute_list_metrics(a, false),
-        ListSubcommand::SystemMetrics(a) => execute_list_metrics(a, true),
-    }
+/// Left-align `rows` into columns separated by two spaces.
+fn render_table(rows: &[Vec<String>]) -> Vec<String> {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|col| {
+            rows.iter()
+                .filter_map(|row| row.get(col))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .enumerate()
+                .map(|(col, cell)| format!("{:<width$}", cell, width = widths[col]))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect()
 }
 
-fn execute_get(args: GetArgs) -> Result<CommandOutput> {
-    match args.cmd {
-        GetSubcommand::Run(a) => execute_get_run(a),
-        GetSubcommand::Metric(a) => execute_get_metric(a),
-    }
+#[derive(Debug, Clone, Serialize)]
+struct CompactedRun {
+    run: String,
+    path: String,
+    files: usize,
+    bytes_before: u64,
+    bytes_after: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rollup: Option<rollup::RollupStats>,
 }
 
-fn execute_search(args: SearchArgs) -> Result<CommandOutput> {
-    match args.cmd {
-        SearchSubcommand::Metrics(a) => execute_search_metrics(a),
-    }
+fn total_file_size(files: &[PathBuf]) -> Result<u64> {
+    files.iter().try_fold(0, |total, file| {
+        let len = fs::metadata(file)
+            .with_context(|| format!("reading {}", file.display()))?
+            .len();
+        Ok(total + len)
+    })
 }
 
-fn execute_list_projects(args: ListProjectsArgs) -> Result<CommandOutput> {
-    let base = args.path;
-    let mut projects = Vec::new();
-    for dir in list_immediate_dirs(&base)? {
-        let runs = list_run_dirs(&dir)?;
-        projects.push(serde_json::json!({
-            "name": dir.file_name().and_then(|n| n.to_str()).unwrap_or_default(),
-            "path": dir.display().to_string(),
-            "run_count": runs.len(),
-        }));
-    }
-    projects.sort_by(|a, b| {
-        let an = a.get("name").and_then(|v| v.as_str()).unwrap_or_default();
-        let bn = b.get("name").and_then(|v| v.as_str()).unwrap_or_default();
-        an.cmp(bn)
+fn execute_compact(args: CompactArgs) -> Result<CommandOutput> {
+    let run_dirs = match args.run.as_deref() {
+        Some(run) => vec![resolve_run_path(&args.path, args.project.as_deref(), run)],
+        None => list_run_dirs(&project_base(&args.path, args.project.as_deref()))?,
+    };
+
+    let options = args.rollup.map(|every| rollup::RollupOptions {
+        every: every as usize,
+        mode: args.rollup_mode,
+        keep: args.keep.clone(),
     });
+    let mut compacted = Vec::new();
+    let mut skipped = Vec::new();
+    for run_dir in run_dirs {
+        let run = run_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| run_dir.display().to_string());
+        let heartbeat = run_meta::load(&run_dir).unwrap_or_default().status;
+        if run_status(heartbeat.as_ref(), latest_mtime_unix(&run_dir)?) == "running" {
+            // The writer may still append to these files.
+            skipped.push(serde_json::json!({"run": run, "reason": "running"}));
+            continue;
+        }
+        let is_compressed = |file: &PathBuf| {
+            file.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(compression::Codec::from_name)
+                .is_some()
+        };
+        let mut files = tfevents::discover_event_files(&run_dir)?;
+        // Files linked in by `og import tensorboard --link` belong to the source log dir.
+        files.retain(|file| !file.is_symlink());
+        if options.is_none() && files.iter().all(is_compressed) {
+            skipped.push(serde_json::json!({"run": run, "reason": "already compacted"}));
+            continue;
+        }
 
-    let mut text_lines = vec![format!("projects in {}", base.display())];
-    for p in &projects {
-        let name = p.get("name").and_then(|v| v.as_str()).unwrap_or_default();
-        let runs = p.get("run_count").and_then(|v| v.as_u64()).unwrap_or(0);
-        text_lines.push(format!("- {} ({})", name, runs));
+        let mut entry = CompactedRun {
+            run,
+            path: run_dir.display().to_string(),
+            files: files.len(),
+            bytes_before: total_file_size(&files)?,
+            bytes_after: 0,
+            rollup: None,
+        };
+        if let Some(options) = options.as_ref() {
+            let mut totals = rollup::RollupStats::default();
+            // Rollup rewrites TF records; metrics.jsonl files are only compressed.
+            for file in files
+                .iter_mut()
+                .filter(|file| tfevents::is_tfevents_file(file))
+            {
+                let (stats, rewritten) = rollup::rollup_file(file, options, args.dry_run)?;
+                totals.points_before += stats.points_before;
+                totals.points_after += stats.points_after;
+                if let Some(rewritten) = rewritten {
+                    *file = rewritten;
+                }
+            }
+            entry.rollup = Some(totals);
+        }
+        if !args.dry_run {
+            for file in files.iter_mut().filter(|file| !is_compressed(file)) {
+                *file = compression::compress_in_place(file, args.codec)?;
+            }
+        }
+        entry.bytes_after = total_file_size(&files)?;
+        compacted.push(entry);
     }
-    if projects.is_empty() {
-        text_lines.push("- none".to_string());
+
+    let bytes_before: u64 = compacted.iter().map(|run| run.bytes_before).sum();
+    let bytes_after: u64 = compacted.iter().map(|run| run.bytes_after).sum();
+    let verb = if args.dry_run {
+        "would compact"
+    } else {
+        "compacted"
+    };
+    let mut text_lines = vec![format!(
+        "{} {} run{} with {}: {} -> {} bytes",
+        verb,
+        compacted.len(),
+        if compacted.len() == 1 { "" } else { "s" },
+        args.codec.program(),
+        bytes_before,
+        bytes_after
+    )];
+    for run in &compacted {
+        let mut line = format!(
+            "- {}: {} file{}, {} -> {} bytes",
+            run.run,
+            run.files,
+            if run.files == 1 { "" } else { "s" },
+            run.bytes_before,
+            run.bytes_after
+        );
+        if let Some(stats) = run.rollup {
+            line.push_str(&format!(
+                ", {} -> {} points",
+                stats.points_before, stats.points_after
+            ));
+        }
+        text_lines.push(line);
+    }
+    for skip in &skipped {
+        text_lines.push(format!(
+            "- {}: skipped ({})",
+            skip["run"].as_str().unwrap_or_default(),
+            skip["reason"].as_str().unwrap_or_default()
+        ));
     }
 
-    let data = serde_json::json!({
-        "root": base.display().to_string(),
-        "projects": projects,
-    });
     Ok(CommandOutput {
-        command: "list.projects".to_string(),
-        data,
+        command: "compact".to_string(),
+        data: serde_json::json!({
+            "codec": args.codec,
+            "dry_run": args.dry_run,
+            "runs": compacted,
+            "skipped": skipped,
+            "bytes_before": bytes_before,
+            "bytes_after": bytes_after,
+        }),
         text: text_lines.join("\n"),
     })
 }
 
-fn execute_list_runs(args: ListRunsArgs) -> Result<CommandOutput> {
+#[derive(Debug, Clone, Serialize)]
+struct GcRun {
+    run: String,
+    path: String,
+    status: String,
+    tags: Vec<String>,
+    usage: gc::RunUsage,
+    /// `delete`, `prune`, `keep` or `skip`
+    action: &'static str,
+    checkpoints_pruned: usize,
+    /// Checkpoints outside the run dir left in place without `--external-checkpoints`
+    external_checkpoints_kept: usize,
+    bytes_reclaimed: u64,
+    /// Why a skipped run was left alone
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+fn execute_gc(args: GcArgs) -> Result<CommandOutput> {
     let base = project_base(&args.path, args.project.as_deref());
+    let now = unix_now_secs();
     let mut runs = Vec::new();
     for run_dir in list_run_dirs(&base)? {
-        let summary = summarize_run(&run_dir)?;
-        if let Some(status_filter) = args.status.as_deref() {
-            if !summary.status.eq_ignore_ascii_case(status_filter) {
+        let mut registered = checkpoints::load(&run_dir).unwrap_or_default();
+        let usage = gc::run_usage(&run_dir, &registered)?;
+        let last_updated = gc::last_event_write_unix(&run_dir)?;
+        let mut entry = GcRun {
+            run: run_dir
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| run_dir.display().to_string()),
+            path: run_dir.display().to_string(),
+            status: String::new(),
+            tags: Vec::new(),
+            usage,
+            action: "keep",
+            checkpoints_pruned: 0,
+            external_checkpoints_kept: 0,
+            bytes_reclaimed: 0,
+            reason: None,
+        };
+        // Tags protect a run, so one whose metadata cannot be read is never
+        // judged untagged.
+        let meta = match run_meta::load(&run_dir) {
+            Ok(meta) => meta,
+            Err(err) => {
+                entry.status = "unknown".to_string();
+                entry.action = "skip";
+                entry.reason = Some(format!("{err:#}"));
+                runs.push(entry);
                 continue;
             }
-        }
-        let id_l = summary.id.to_ascii_lowercase();
-        if !args
-            .tag
-            .iter()
-            .all(|needle| id_l.contains(&needle.to_ascii_lowercase()))
-        {
+        };
+        let status = run_status(meta.status.as_ref(), last_updated);
+        entry.status = status.to_string();
+        entry.tags = meta.tags;
+        // A running job may still write to the run, so it is only reported.
+        if status == "running" {
+            runs.push(entry);
             continue;
         }
-        if !args
-            .config
-            .iter()
-            .all(|needle| id_l.contains(&needle.to_ascii_lowercase()))
-        {
-            continue;
+        // A registry can name paths anywhere (absolute paths from another
+        // host, or a bundle's); those are only deleted when asked for.
+        let deletable = |checkpoint: &&checkpoints::Checkpoint| {
+            args.external_checkpoints || gc::inside(&run_dir, &checkpoint.path)
+        };
+
+        let delete_run = args
+            .older_than
+            .is_some_and(|days| entry.tags.is_empty() && gc::expired(last_updated, now, days));
+        if delete_run {
+            let (doomed, external): (Vec<&checkpoints::Checkpoint>, Vec<_>) = registered
+                .iter()
+                .filter(|checkpoint| !gc::inside(&run_dir, &checkpoint.path))
+                .partition(deletable);
+            entry.action = "delete";
+            entry.checkpoints_pruned = registered.len() - external.len();
+            entry.external_checkpoints_kept = external.len();
+            entry.bytes_reclaimed = usage.total().saturating_sub(
+                external
+                    .iter()
+                    .map(|checkpoint| disk_usage::path_size(&checkpoint.path))
+                    .sum(),
+            );
+            if !args.dry_run {
+                for checkpoint in doomed {
+                    gc::remove_checkpoint(&checkpoint.path)?;
+                }
+                fs::remove_dir_all(&run_dir)
+                    .with_context(|| format!("removing {}", run_dir.display()))?;
+            }
+        } else if let Some(keep) = args.keep_checkpoints {
+            checkpoints::measure(&mut registered);
+            let (_, pruned) = gc::split_checkpoints(&registered, keep);
+            let (pruned, external): (Vec<&checkpoints::Checkpoint>, Vec<_>) =
+                pruned.iter().partition(deletable);
+            entry.external_checkpoints_kept = external.len();
+            if !pruned.is_empty() {
+                entry.action = "prune";
+                entry.checkpoints_pruned = pruned.len();
+                entry.bytes_reclaimed = pruned.iter().filter_map(|c| c.size_bytes).sum();
+                if !args.dry_run {
+                    for checkpoint in &pruned {
+                        gc::remove_checkpoint(&checkpoint.path)?;
+                    }
+                    let kept: Vec<checkpoints::Checkpoint> = registered
+                        .iter()
+                        .filter(|checkpoint| {
+                            !pruned.iter().any(|gone| gone.path == checkpoint.path)
+                        })
+                        .cloned()
+                        .collect();
+                    checkpoints::save(&run_dir, &kept)?;
+                }
+            }
         }
-        runs.push(summary);
+        runs.push(entry);
     }
 
-    runs.sort_by(|a, b| match b.last_updated_unix.cmp(&a.last_updated_unix) {
-        Ordering::Equal => a.id.cmp(&b.id),
-        other => other,
-    });
-
-    let mut text_lines = vec![format!("runs in {}", base.display())];
+    let mut total = gc::RunUsage::default();
     for run in &runs {
-        text_lines.push(format!(
-            "- {} | status={} | metrics={} | step={}",
-            run.id, run.status, run.metric_count, run.max_step
-        ));
+        total.add(&run.usage);
+    }
+    let reclaimed: u64 = runs.iter().map(|run| run.bytes_reclaimed).sum();
+    let size = format::bytes;
+    let mut rows = vec![
+        [
+            "run",
+            "status",
+            "events",
+            "checkpoints",
+            "logs",
+            "artifacts",
+            "total",
+            "action",
+        ]
+        .map(String::from)
+        .to_vec(),
+    ];
+    for run in &runs {
+        let action = match run.action {
+            "keep" => "-".to_string(),
+            "prune" => format!(
+                "prune {} checkpoint{} ({})",
+                run.checkpoints_pruned,
+                if run.checkpoints_pruned == 1 { "" } else { "s" },
+                size(run.bytes_reclaimed)
+            ),
+            "skip" => "skip: unreadable og_meta.json".to_string(),
+            other => format!("{} ({})", other, size(run.bytes_reclaimed)),
+        };
+        rows.push(vec![
+            run.run.clone(),
+            run.status.clone(),
+            size(run.usage.events),
+            size(run.usage.checkpoints),
+            size(run.usage.console_logs),
+            size(run.usage.artifacts),
+            size(run.usage.total()),
+            action,
+        ]);
     }
+    rows.push(vec![
+        "total".to_string(),
+        String::new(),
+        size(total.events),
+        size(total.checkpoints),
+        size(total.console_logs),
+        size(total.artifacts),
+        size(total.total()),
+        String::new(),
+    ]);
+
+    let verb = if args.dry_run {
+        "would reclaim"
+    } else {
+        "reclaimed"
+    };
+    let mut text_lines = vec![format!("disk usage of runs in {}", base.display())];
     if runs.is_empty() {
         text_lines.push("- none".to_string());
+    } else {
+        text_lines.extend(render_table(&rows));
+    }
+    if args.older_than.is_some() || args.keep_checkpoints.is_some() {
+        text_lines.push(format!("{} {}", verb, size(reclaimed)));
+    }
+    for run in &runs {
+        if let Some(reason) = &run.reason {
+            text_lines.push(format!("skipped {}: {reason}", run.run));
+        }
+        if run.external_checkpoints_kept > 0 {
+            text_lines.push(format!(
+                "kept {} checkpoint{} of {} outside its run dir (delete with --external-checkpoints)",
+                run.external_checkpoints_kept,
+                if run.external_checkpoints_kept == 1 { "" } else { "s" },
+                run.run
+            ));
+        }
     }
 
-    let data = serde_json::json!({
-        "base": base.display().to_string(),
-        "count": runs.len(),
-        "runs": runs,
-    });
     Ok(CommandOutput {
-        command: "list.runs".to_string(),
-        data,
+        command: "gc".to_string(),
+        data: serde_json::json!({
+            "base": base.display().to_string(),
+            "dry_run": args.dry_run,
+            "runs": runs,
+            "total": total,
+            "bytes_reclaimed": reclaimed,
+        }),
         text: text_lines.join("\n"),
     })
 }
 
-fn execute_list_metrics(args: ListMetricsArgs, system_only: bool) -> Result<CommandOutput> {
-    let run_path = resolve_run_path(&args.path, args.project.as_deref(), &args.run);
-    let view = load_view_data(&run_path)?;
-    let mut names: Vec<String> = view.scalars.keys().cloned().collect();
-    names.sort();
-    if system_only {
-        names.retain(|n| looks_system_metric(n));
+fn execute_export(args: ExportArgs) -> Result<CommandOutput> {
+    match args.cmd {
+        ExportSubcommand::Audio(a) => execute_export_audio(a),
     }
+}
 
-    let header = if system_only {
-        "system metrics"
-    } else {
-        "metrics"
-    };
-    let mut text_lines = vec![format!("{} in {}", header, run_path.display())];
-    for name in &names {
-        text_lines.push(format!("- {}", name));
+fn execute_export_audio(args: ExportAudioArgs) -> Result<CommandOutput> {
+    let run_path = resolve_run_path(&args.path, args.project.as_deref(), &args.run);
+    if !run_path.exists() {
+        return Err(OgError::run_not_found(&args.run, &run_path).into());
     }
-    if names.is_empty() {
-        text_lines.push("- none".to_string());
+    let mut clips = audio::load(&run_path)?;
+    if let Some(tag) = &args.tag {
+        clips.retain(|clip| &clip.tag == tag);
     }
+    if clips.is_empty() {
+        match &args.tag {
+            Some(tag) => bail!(
+                "audio tag '{}' not found in run {}",
+                tag,
+                run_path.display()
+            ),
+            None => bail!("no audio summaries found in run {}", run_path.display()),
+        }
+    }
+    let written = audio::export(&clips, &args.out)?;
 
+    let files: Vec<Value> = clips
+        .iter()
+        .zip(&written)
+        .map(|(clip, path)| {
+            let mut entry = serde_json::to_value(clip).unwrap_or(Value::Null);
+            entry["file"] = Value::String(path.display().to_string());
+            entry
+        })
+        .collect();
     let data = serde_json::json!({
         "run": run_path.display().to_string(),
-        "system_only": system_only,
-        "count": names.len(),
-        "metrics": names,
+        "out": args.out.display().to_string(),
+        "count": written.len(),
+        "files": files,
     });
     Ok(CommandOutput {
-        command: if system_only {
-            "list.system-metrics".to_string()
-        } else {
-            "list.metrics".to_string()
-        },
+        command: "export.audio".to_string(),
         data,
-        text: text_lines.join("\n"),
+        text: format!(
+            "exported {} audio clip(s) to {}",
+            written.len(),
+            args.out.display()
+        ),
     })
 }
 
-fn execute_get_run(args: GetRunArgs) -> Result<CommandOutput> {
+fn execute_bundle(args: BundleArgs) -> Result<CommandOutput> {
+    match args.cmd {
+        BundleSubcommand::Create(a) => execute_bundle_create(a),
+        BundleSubcommand::Import(a) => execute_bundle_import(a),
+    }
+}
+
+fn execute_bundle_create(args: BundleCreateArgs) -> Result<CommandOutput> {
+    let run_path = resolve_qualified_run_path(&args.path, args.project.as_deref(), &args.run);
+    if !run_path.is_dir() {
+        return Err(OgError::run_not_found(&args.run, &run_path).into());
+    }
+    let run_path = run_path.canonicalize().unwrap_or(run_path);
+    let run = run_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| args.run.clone());
+    let out = args
+        .out
+        .unwrap_or_else(|| PathBuf::from(format!("{run}.tar.zst")));
+    let manifest = bundle::create(&run_path, &run, &args.checkpoints, &out, unix_now_secs())?;
+    Ok(CommandOutput {
+        command: "bundle.create".to_string(),
+        data: serde_json::json!({
+            "run": run_path.display().to_string(),
+            "out": out.display().to_string(),
+            "manifest": manifest,
+        }),
+        text: format!(
+            "bundled {} ({} file{}, {}, {} checkpoint{}) into {}",
+            run,
+            manifest.files,
+            if manifest.files == 1 { "" } else { "s" },
+            format::bytes(manifest.bytes),
+            manifest.checkpoints.len(),
+            if manifest.checkpoints.len() == 1 {
+                ""
+            } else {
+                "s"
+            },
+            out.display()
+        ),
+    })
+}
+
+fn execute_bundle_import(args: BundleImportArgs) -> Result<CommandOutput> {
+    let base = project_base(&args.path, args.project.as_deref());
+    let (dest, manifest) = bundle::import(&args.file, &base, args.name.as_deref())?;
+    let source = args
+        .file
+        .canonicalize()
+        .unwrap_or_else(|_| args.file.clone());
+    run_meta::update(&dest, |meta| {
+        meta.record_import(run_meta::ImportSource {
+            kind: run_meta::ImportKind::Bundle,
+            path: source.clone(),
+            imported_unix: unix_now_secs(),
+        });
+    })?;
+    Ok(CommandOutput {
+        command: "bundle.import".to_string(),
+        data: serde_json::json!({
+            "path": dest.display().to_string(),
+            "source": source.display().to_string(),
+            "manifest": manifest,
+        }),
+        text: format!(
+            "imported {} as {} ({} file{}, {} checkpoint{})",
+            args.file.display(),
+            dest.display(),
+            manifest.files,
+            if manifest.files == 1 { "" } else { "s" },
+            manifest.checkpoints.len(),
+            if manifest.checkpoints.len() == 1 {
+                ""
+            } else {
+                "s"
+            }
+        ),
+    })
+}
+
+fn execute_import(args: ImportArgs) -> Result<CommandOutput> {
+    match args.cmd {
+        ImportSubcommand::Csv(a) => execute_import_csv(a),
+        ImportSubcommand::Tensorboard(a) => execute_import_tensorboard(a),
+    }
+}
+
+fn execute_import_csv(args: ImportCsvArgs) -> Result<CommandOutput> {
+    let raw = fs::read_to_string(&args.file)
+        .with_context(|| format!("reading {}", args.file.display()))?;
+    let file_mtime = fs::metadata(&args.file)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|elapsed| elapsed.as_secs_f64())
+        .unwrap_or_else(unix_now_secs_f64);
+    let imported = csv_import::convert(
+        &raw,
+        &args.step_column,
+        args.time_column.as_deref(),
+        file_mtime,
+    )
+    .with_context(|| format!("importing {}", args.file.display()))?;
+    if imported.rows.is_empty() {
+        bail!(
+            "no rows with a step and numeric metrics in {}",
+            args.file.display()
+        );
+    }
+
     let run_path = resolve_run_path(&args.path, args.project.as_deref(), &args.run);
-    let view = load_view_data(&run_path)?;
-    let mut latest = serde_json::Map::new();
-    for (metric, series) in &view.scalars {
-        if let Some((step, value)) = series.last() {
-            latest.insert(
-                metric.clone(),
-                serde_json::json!({"step": step, "value": value}),
-            );
-        }
+    if contains_tfevents(&run_path)? {
+        bail!("run '{}' already has metric data", run_path.display());
     }
-    let summary = summarize_run(&run_path)?;
+    fs::create_dir_all(&run_path)
+        .with_context(|| format!("creating run directory {}", run_path.display()))?;
+    let body: String = imported
+        .rows
+        .iter()
+        .map(|row| metrics_jsonl::format_line(row.step, row.wall_time, &row.metrics))
+        .collect();
+    let store = run_path.join(metrics_jsonl::FILE_NAME);
+    fs::write(&store, body).with_context(|| format!("writing {}", store.display()))?;
+    let source = args
+        .file
+        .canonicalize()
+        .unwrap_or_else(|_| args.file.clone());
+    run_meta::update(&run_path, |meta| {
+        meta.record_import(run_meta::ImportSource {
+            kind: run_meta::ImportKind::Csv,
+            path: source.clone(),
+            imported_unix: unix_now_secs(),
+        });
+    })?;
 
-    let mut text_lines = vec![
-        format!("run {}", summary.id),
-        format!("path: {}", summary.path),
-        format!("status: {}", summary.status),
-        format!("metrics: {}", summary.metric_count),
-        format!("events: {}", summary.event_count),
-        format!("max_step: {}", summary.max_step),
-        "latest metrics:".to_string(),
-    ];
-    let mut keys: Vec<String> = latest.keys().cloned().collect();
-    keys.sort();
-    for k in keys {
-        if let Some(v) = latest.get(&k) {
-            text_lines.push(format!("- {}: {}", k, v));
-        }
+    let points: usize = imported.rows.iter().map(|row| row.metrics.len()).sum();
+    let mut text_lines = vec![format!(
+        "imported {} rows ({} points, {} metrics) from {} into {}",
+        imported.rows.len(),
+        points,
+        imported.columns.len(),
+        args.file.display(),
+        run_path.display()
+    )];
+    if imported.skipped_rows > 0 {
+        text_lines.push(format!(
+            "skipped {} row{} without a step or numeric values",
+            imported.skipped_rows,
+            if imported.skipped_rows == 1 { "" } else { "s" }
+        ));
+    }
+    Ok(CommandOutput {
+        command: "import.csv".to_string(),
+        data: serde_json::json!({
+            "run": run_path.display().to_string(),
+            "file": source.display().to_string(),
+            "rows": imported.rows.len(),
+            "points": points,
+            "skipped_rows": imported.skipped_rows,
+            "metrics": imported.columns,
+        }),
+        text: text_lines.join("\n"),
+    })
+}
+
+fn execute_import_tensorboard(args: ImportTensorboardArgs) -> Result<CommandOutput> {
+    let runs = tb_import::discover_runs(&args.logdir)?;
+    if runs.is_empty() {
+        bail!("no event files found under {}", args.logdir.display());
     }
 
-    let data = serde_json::json!({
-        "run": summary,
-        "latest_metrics": latest,
-    });
+    let mut imported = Vec::new();
+    let mut skipped = Vec::new();
+    let mut text_lines = Vec::new();
+    for run in &runs {
+        let dest = resolve_run_path(&args.path, args.project.as_deref(), &run.name);
+        if contains_tfevents(&dest)? {
+            skipped.push(serde_json::json!({"run": run.name, "reason": "already has metric data"}));
+            text_lines.push(format!("- {}: skipped (already has metric data)", run.name));
+            continue;
+        }
+        let bytes = tb_import::import_run(run, &dest, args.link)?;
+        let source = run.dir.canonicalize().unwrap_or_else(|_| run.dir.clone());
+        run_meta::update(&dest, |meta| {
+            meta.record_import(run_meta::ImportSource {
+                kind: run_meta::ImportKind::Tensorboard,
+                path: source.clone(),
+                imported_unix: unix_now_secs(),
+            });
+        })?;
+        text_lines.push(format!(
+            "- {}: {} file{}, {} bytes",
+            run.name,
+            run.files.len(),
+            if run.files.len() == 1 { "" } else { "s" },
+            bytes
+        ));
+        imported.push(serde_json::json!({
+            "run": run.name,
+            "path": dest.display().to_string(),
+            "source": source.display().to_string(),
+            "files": run.files.len(),
+            "bytes": bytes,
+        }));
+    }
+    text_lines.insert(
+        0,
+        format!(
+            "{} {} run{} from {}",
+            if args.link { "linked" } else { "copied" },
+            imported.len(),
+            if imported.len() == 1 { "" } else { "s" },
+            args.logdir.display()
+        ),
+    );
     Ok(CommandOutput {
-        command: "get.run".to_string(),
-        data,
+        command: "import.tensorboard".to_string(),
+        data: serde_json::json!({
+            "logdir": args.logdir.display().to_string(),
+            "linked": args.link,
+            "runs": imported,
+            "skipped": skipped,
+        }),
         text: text_lines.join("\n"),
     })
 }
 
-fn execute_get_metric(args: GetMetricArgs) -> Result<CommandOutput> {
-    let run_path = resolve_run_path(&args.path, args.project.as_deref(), &args.run);
-    let view = load_view_data(&run_path)?;
-    let Some(series) = view.scalars.get(&args.metric) else {
-        bail!(
-            "metric '{}' not found in run {}",
-            args.metric,
-            run_path.display()
-        );
-    };
-
-    let count = series.len();
-    let (min, max, last) = summarize_series(series);
-    let tail_n = 20usize.min(count);
-    let tail = &series[count.saturating_sub(tail_n)..];
-
-    let mut text_lines = vec![
-        format!("run: {}", run_path.display()),
-        format!("metric: {}", args.metric),
-        format!("count: {}", count),
-        format!("min: {:.6}", min),
-        format!("max: {:.6}", max),
-        format!("last: {:.6}", last),
-        "tail:".to_string(),
-    ];
-    for (step, value) in tail {
-        text_lines.push(format!("- step {} => {:.6}", step, value));
+fn execute_diff(args: DiffArgs) -> Result<CommandOutput> {
+    let path_a = resolve_run_path(&args.path, args.project.as_deref(), &args.run_a);
+    let path_b = resolve_run_path(&args.path, args.project.as_deref(), &args.run_b);
+    for (run, path) in [(&args.run_a, &path_a), (&args.run_b, &path_b)] {
+        if !path.exists() {
+            return Err(OgError::run_not_found(run, path).into());
+        }
+    }
+    let meta_a = run_meta::load(&path_a)?;
+    let meta_b = run_meta::load(&path_b)?;
+    let env_a = env_capture::load(&path_a)?.unwrap_or_default();
+    let env_b = env_capture::load(&path_b)?.unwrap_or_default();
+
+    let config = env_capture::diff_maps(&meta_a.config, &meta_b.config);
+    let env_vars = env_capture::diff_maps(&env_a.env_vars, &env_b.env_vars);
+    let packages = env_capture::diff_maps(&env_a.packages, &env_b.packages);
+    let git_a = run_diff::git_label(&meta_a.git);
+    let git_b = run_diff::git_label(&meta_b.git);
+
+    let mut text_lines = vec![format!("diff {} -> {}", path_a.display(), path_b.display())];
+    if git_a != git_b {
+        text_lines.push(format!("git: {} -> {}", git_a, git_b));
+    }
+    for (section, diff) in [
+        ("config", &config),
+        ("env vars", &env_vars),
+        ("packages", &packages),
+    ] {
+        if diff.is_empty() {
+            continue;
+        }
+        text_lines.push(format!("{}:", section));
+        text_lines.extend(diff.lines().into_iter().map(|line| format!("  {}", line)));
+    }
+    if text_lines.len() == 1 {
+        text_lines.push("no differences in config, git state or environment".to_string());
     }
 
-    let points: Vec<Value> = series
-        .iter()
-        .map(|(step, value)| serde_json::json!({"step": step, "value": value}))
-        .collect();
     let data = serde_json::json!({
-        "run": run_path.display().to_string(),
-        "metric": args.metric,
-        "count": count,
-        "min": min,
-        "max": max,
-        "last": last,
-        "points": points,
+        "from": path_a.display().to_string(),
+        "to": path_b.display().to_string(),
+        "git": {"from": meta_a.git, "to": meta_b.git},
+        "config": config,
+        "env_vars": env_vars,
+        "packages": packages,
     });
     Ok(CommandOutput {
-        command: "get.metric".to_string(),
+        command: "diff".to_string(),
         data,
         text: text_lines.join("\n"),
     })
 }
 
-fn execute_compare(args: CompareArgs) -> Result<CommandOutput> {
-    if args.runs.is_empty() {
-        bail!("--runs must include at least one run id/path");
-    }
+fn execute_search_runs(args: SearchRunsArgs) -> Result<CommandOutput> {
+    let query = run_query::parse(&args.where_clause)?;
+    let base = project_base(&args.path, args.project.as_deref());
+    let columns = query.keys();
+    let matches = query::search_runs(&base, &query)?;
 
-    let mut comparisons = Vec::new();
-    let mut text_lines = vec![format!("compare metric '{}'", args.metric)];
-    for run in &args.runs {
-        let run_path = resolve_run_path(&args.path, args.project.as_deref(), run);
-        let view = load_view_data(&run_path)?;
-        let Some(series) = view.scalars.get(&args.metric) else {
-            text_lines.push(format!(
-                "- {}: metric '{}' not found",
-                run_path.display(),
-                args.metric
-            ));
-            comparisons.push(serde_json::json!({
-                "run": run_path.display().to_string(),
-                "found": false,
-            }));
-            continue;
-        };
-        let (min, max, last) = summarize_series(series);
-        let first = series.first().map(|(_, v)| *v).unwrap_or(last);
-        let delta = last - first;
-        text_lines.push(format!(
-            "- {} | first={:.6} last={:.6} delta={:.6} min={:.6} max={:.6}",
-            run_path.display(),
-            first,
-            last,
-            delta,
-            min,
-            max
-        ));
-        comparisons.push(serde_json::json!({
-            "run": run_path.display().to_string(),
-            "found": true,
-            "count": series.len(),
-            "first": first,
-            "last": last,
-            "delta": delta,
-            "min": min,
-            "max": max,
+    let mut rows = vec![
+        ["run", "status", "step"]
+            .iter()
+            .map(|s| s.to_string())
+            .chain(columns.iter().cloned())
+            .collect::<Vec<_>>(),
+    ];
+    for found in &matches {
+        let mut row = vec![
+            found.run.id.clone(),
+            found.run.status.clone(),
+            found.run.max_step.to_string(),
+        ];
+        row.extend(columns.iter().map(|key| {
+            found
+                .fields
+                .get(key)
+                .cloned()
+                .unwrap_or_else(|| "-".to_string())
         }));
+        rows.push(row);
+    }
+
+    let mut text_lines = vec![format!("search runs where {}", args.where_clause)];
+    if matches.is_empty() {
+        text_lines.push("- none".to_string());
+    } else {
+        text_lines.extend(render_table(&rows));
     }
 
     let data = serde_json::json!({
-        "metric": args.metric,
-        "comparisons": comparisons,
+        "where": args.where_clause,
+        "count": matches.len(),
+        "matches": matches,
     });
     Ok(CommandOutput {
-        command: "compare".to_string(),
+        command: "search.runs".to_string(),
         data,
         text: text_lines.join("\n"),
     })
 }
 
 fn execute_search_metrics(args: SearchMetricsArgs) -> Result<CommandOutput> {
-    let query = args.query.to_ascii_lowercase();
     let base = project_base(&args.path, args.project.as_deref());
-    let mut matches = Vec::new();
-    for run_dir in list_run_dirs(&base)? {
-        let view = load_view_data(&run_dir)?;
-        let run_id = run_dir
-            .file_name()
-            .and_then(|s| s.to_str())
-            .unwrap_or_default()
-            .to_string();
-        for name in view.scalars.keys() {
-            if name.to_ascii_lowercase().contains(&query) {
-                matches.push(serde_json::json!({
-                    "metric": name,
-                    "run": run_id,
-                    "path": run_dir.display().to_string(),
-                }));
-            }
-        }
-    }
-    matches.sort_by(|a, b| {
-        let am = a.get("metric").and_then(|v| v.as_str()).unwrap_or_default();
-        let bm = b.get("metric").and_then(|v| v.as_str()).unwrap_or_default();
-        am.cmp(bm)
-    });
+    let progress = par_load::stderr_progress("searching runs");
+    let matches = query::search_metrics(
+        &base,
+        &args.query,
+        args.jobs.unwrap_or_else(par_load::default_jobs),
+        Some(&progress),
+    )?;
 
     let mut text_lines = vec![format!("search metrics query='{}'", args.query)];
     for m in &matches {
-        let metric = m.get("metric").and_then(|v| v.as_str()).unwrap_or_default();
-        let run = m.get("run").and_then(|v| v.as_str()).unwrap_or_default();
-        text_lines.push(format!("- {} ({})", metric, run));
+        text_lines.push(format!("- {} ({})", m.metric, m.run));
     }
     if matches.is_empty() {
         text_lines.push("- none".to_string());
@@ -1189,68 +5932,7 @@ fn execute_search_metrics(args: SearchMetricsArgs) -> Result<CommandOutput> {
     })
 }
 
-fn project_base(path: &Path, project: Option<&str>) -> PathBuf {
-    if let Some(project) = project {
-        path.join(project)
-    } else {
-        path.to_path_buf()
-    }
-}
-
-fn resolve_run_path(path: &Path, project: Option<&str>, run: &str) -> PathBuf {
-    let direct = PathBuf::from(run);
-    if direct.exists() {
-        return direct;
-    }
-    project_base(path, project).join(run)
-}
-
-fn list_immediate_dirs(path: &Path) -> Result<Vec<PathBuf>> {
-    let mut dirs = Vec::new();
-    if !path.exists() {
-        return Ok(dirs);
-    }
-    for entry in fs::read_dir(path)? {
-        let entry = entry?;
-        let entry_path = entry.path();
-        if entry_path.is_dir() {
-            dirs.push(entry_path);
-        }
-    }
-    dirs.sort();
-    Ok(dirs)
-}
-
-fn list_run_dirs(path: &Path) -> Result<Vec<PathBuf>> {
-    let mut runs = Vec::new();
-    for dir in list_immediate_dirs(path)? {
-        if contains_tfevents(&dir)? {
-            runs.push(dir);
-        }
-    }
-
-    if runs.is_empty() && path.exists() && contains_tfevents(path)? {
-        runs.push(path.to_path_buf());
-    }
-    runs.sort();
-    Ok(runs)
-}
-
-fn contains_tfevents_direct(path: &Path) -> Result<bool> {
-    if !path.exists() || !path.is_dir() {
-        return Ok(false);
-    }
-    for entry in fs::read_dir(path)? {
-        let entry = entry?;
-        let entry_path = entry.path();
-        if entry_path.is_file() && tfevents::is_tfevents_file(&entry_path) {
-            return Ok(true);
-        }
-    }
-    Ok(false)
-}
-
-fn resolve_live_run_path(path: &Path) -> Result<PathBuf> {
+fn resolve_live_run_path(path: &Path, name_template: &str) -> Result<PathBuf> {
     if path.is_file() {
         return Ok(path.to_path_buf());
     }
@@ -1272,7 +5954,7 @@ fn resolve_live_run_path(path: &Path) -> Result<PathBuf> {
 
     let has_nested_tfevents = contains_tfevents(path)?;
     if has_nested_tfevents {
-        let child = path.join(format!("run-{}", unix_now_secs()));
+        let child = path.join(run_name::generate(name_template, path, unix_now_secs()));
         fs::create_dir_all(&child)
             .with_context(|| format!("creating run directory {}", child.display()))?;
         return Ok(child);
@@ -1281,107 +5963,110 @@ fn resolve_live_run_path(path: &Path) -> Result<PathBuf> {
     Ok(path.to_path_buf())
 }
 
-fn contains_tfevents(path: &Path) -> Result<bool> {
-    if path.is_file() {
-        return Ok(tfevents::is_tfevents_file(path));
-    }
-
-    if !path.exists() || !path.is_dir() {
-        return Ok(false);
+/// The runs root above `path`, whose directories the project picker offers:
+/// two levels up from a run, one from a project, else `path` itself.
+fn projects_root(path: &Path) -> PathBuf {
+    let parent = |path: &Path| {
+        path.parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+            .to_path_buf()
+    };
+    if contains_tfevents_direct(path).unwrap_or(false) {
+        return parent(&parent(path));
     }
-
-    for entry in fs::read_dir(path)? {
-        let entry = entry?;
-        let entry_path = entry.path();
-        if entry_path.is_dir() {
-            if contains_tfevents(&entry_path)? {
-                return Ok(true);
-            }
-        } else if tfevents::is_tfevents_file(&entry_path) {
-            return Ok(true);
-        }
+    let holds_runs = list_immediate_dirs(path)
+        .unwrap_or_default()
+        .iter()
+        .any(|dir| contains_tfevents_direct(dir).unwrap_or(false));
+    if holds_runs {
+        parent(path)
+    } else {
+        path.to_path_buf()
     }
-    Ok(false)
 }
 
-fn summarize_run(path: &Path) -> Result<RunSummaryData> {
-    let view = load_view_data(path)?;
-    let id = path
-        .file_name()
-        .and_then(|s| s.to_str())
-        .map(|s| s.to_string())
-        .unwrap_or_else(|| path.display().to_string());
-    let last_updated_unix = latest_mtime_unix(path)?;
-    let status = match last_updated_unix {
-        Some(last) if unix_now_secs().saturating_sub(last) <= 120 => "running",
-        Some(_) => "inactive",
-        None => "unknown",
-    }
-    .to_string();
-
-    Ok(RunSummaryData {
-        id,
-        path: path.display().to_string(),
-        metric_count: view.scalars.len(),
-        event_count: view.total_events,
-        max_step: view.max_step,
-        status,
-        last_updated_unix,
-    })
+/// What `og list projects` reports for `root`, without hidden directories.
+fn list_switchable_projects(root: &Path) -> Result<Vec<query::ProjectSummary>> {
+    let mut projects = query::list_projects(root)?;
+    projects.retain(|project| !project.name.starts_with('.'));
+    Ok(projects)
 }
 
-fn latest_mtime_unix(path: &Path) -> Result<Option<u64>> {
-    fn inner(path: &Path, best: &mut Option<u64>) -> Result<()> {
-        if path.is_file() {
-            let mtime = fs::metadata(path)
-                .ok()
-                .and_then(|m| m.modified().ok())
-                .and_then(|ts| ts.duration_since(std::time::UNIX_EPOCH).ok())
-                .map(|d| d.as_secs());
-            if let Some(mtime) = mtime {
-                *best = Some(best.map_or(mtime, |current| current.max(mtime)));
-            }
-            return Ok(());
-        }
-        if !path.exists() {
-            return Ok(());
-        }
-        for entry in fs::read_dir(path)? {
-            let entry = entry?;
-            inner(&entry.path(), best)?;
-        }
-        Ok(())
+/// Why `P` cannot switch projects right now, if it cannot.
+fn project_switch_blocker(app: &App, replaying: bool, recording: bool) -> Option<&'static str> {
+    if replaying {
+        Some("replaying a session")
+    } else if recording {
+        Some("recording a session")
+    } else if app.live_logs_active {
+        Some("following a live run")
+    } else {
+        None
     }
-
-    let mut best = None;
-    inner(path, &mut best)?;
-    Ok(best)
-}
-
-fn looks_system_metric(name: &str) -> bool {
-    let n = name.to_ascii_lowercase();
-    n.starts_with("sys/")
-        || n.contains("gpu")
-        || n.contains("vram")
-        || n.contains("cpu")
-        || n.contains("memory")
-        || n.contains("/mem")
-        || n.contains("disk")
-        || n.contains("net")
 }
 
-fn summarize_series(series: &[(f64, f64)]) -> (f64, f64, f64) {
-    if series.is_empty() {
-        return (0.0, 0.0, 0.0);
+/// Load `path` and show it in place of the current project.
+fn open_project(app: &mut App, path: &Path, graph_filter: Option<&GraphFilter>) -> Result<()> {
+    let mut view = load_view_data(path)?;
+    if let Some(filter) = graph_filter {
+        view.scalars = filter_scalars(view.scalars, filter);
     }
-    let mut min = f64::INFINITY;
-    let mut max = f64::NEG_INFINITY;
-    for (_, value) in series {
-        min = min.min(*value);
-        max = max.max(*value);
+    app.switch_project(path.to_path_buf());
+    app.set_ranks(view.ranks);
+    app.set_rank_progress(view.rank_progress);
+    app.wall_secs = view.wall_secs;
+    app.wall_gaps = view.gaps;
+    app.sys_strip = view.sys_strip;
+    app.epoch_scale = view.epoch_scale;
+    app.replace_data(
+        view.scalars,
+        view.log_lines,
+        view.total_events,
+        view.max_step,
+    );
+    if let Ok(meta) = run_meta::load(path) {
+        app.apply_run_meta(meta);
     }
-    let last = series.last().map(|(_, v)| *v).unwrap_or(0.0);
-    (min, max, last)
+    Ok(())
+}
+
+/// Summarise every run under `root` for the dashboard tab, running runs first.
+fn load_dashboard_rows(root: &Path) -> Vec<dashboard::DashboardRow> {
+    let run_dirs = list_run_dirs(root).unwrap_or_default();
+    let mut rows: Vec<dashboard::DashboardRow> =
+        par_load::map_bounded(&run_dirs, par_load::default_jobs(), None, |run_dir| {
+            let loaded = tfevents::load_run(run_dir).ok()?;
+            let id = run_dir
+                .file_name()
+                .and_then(|s| s.to_str())
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| run_dir.display().to_string());
+            let meta = run_meta::load(run_dir).unwrap_or_default();
+            let status = run_status(
+                meta.status.as_ref(),
+                latest_mtime_unix(run_dir).ok().flatten(),
+            );
+            let color = run_color::for_run(&id, meta.color.as_deref());
+            let mut row =
+                dashboard::DashboardRow::from_loaded(id, &loaded, status.to_string(), color);
+            let pricing = project_config::load(run_dir)
+                .and_then(|project| cost::Pricing::resolve(&project))
+                .unwrap_or_default();
+            row.spend = cost::wall_secs(&loaded.events)
+                .zip(cost::run_gpus(&meta, loaded.ranks.len()))
+                .and_then(|(secs, gpus)| cost::estimate(secs, gpus, pricing));
+            Some(row)
+        })
+        .into_iter()
+        .flatten()
+        .collect();
+    rows.sort_by(|a, b| {
+        (a.status != "running")
+            .cmp(&(b.status != "running"))
+            .then_with(|| a.run.cmp(&b.run))
+    });
+    rows
 }
 
 fn resolve_checkpoint_path(checkpoint_dir: &Path, checkpoint: &str) -> Result<PathBuf> {
@@ -1418,17 +6103,61 @@ fn resolve_checkpoint_path(checkpoint_dir: &Path, checkpoint: &str) -> Result<Pa
 }
 
 /// Spawn the Python agent daemon as a child process.
+#[allow(clippy::too_many_arguments)]
 fn spawn_daemon(
-    training_file: &PathBuf,
-    codebase_root: &PathBuf,
-    run_dir: &PathBuf,
+    training_file: &Path,
+    codebase_root: &Path,
+    run_dir: &Path,
     auto_mode: bool,
     runtime: RuntimeArg,
     start_training: bool,
     fresh_run: bool,
     training_cmd: Option<&str>,
-    socket_path: &PathBuf,
+    socket_path: &Path,
+    grad_limits: grad_health::Limits,
 ) -> Result<Child> {
+    let (mut cmd, python) = daemon_command(
+        training_file,
+        codebase_root,
+        run_dir,
+        auto_mode,
+        runtime,
+        start_training,
+        fresh_run,
+        training_cmd,
+        socket_path,
+        Some(grad_limits),
+    )?;
+    let child = cmd
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| {
+            format!(
+                "Failed to spawn daemon with '{}'. Is og-agent-chat installed?",
+                python
+            )
+        })?;
+
+    Ok(child)
+}
+
+/// The command line and environment of the Python agent daemon, and the
+/// interpreter it runs under. Without `grad_limits` the daemon reads its
+/// gradient-norm limits from the inherited environment.
+#[allow(clippy::too_many_arguments)]
+fn daemon_command(
+    training_file: &Path,
+    codebase_root: &Path,
+    run_dir: &Path,
+    auto_mode: bool,
+    runtime: RuntimeArg,
+    start_training: bool,
+    fresh_run: bool,
+    training_cmd: Option<&str>,
+    socket_path: &Path,
+    grad_limits: Option<grad_health::Limits>,
+) -> Result<(Command, String)> {
     // Prefer virtualenv Python for daemon dependencies, then fall back to system Python.
     let python = find_python(codebase_root);
     let agent_src = codebase_root.join("python").join("agent-chat");
@@ -1436,10 +6165,10 @@ fn spawn_daemon(
     if agent_src.is_dir() {
         pythonpath_parts.push(agent_src.display().to_string());
     }
-    if let Ok(existing) = std::env::var("PYTHONPATH") {
-        if !existing.trim().is_empty() {
-            pythonpath_parts.push(existing);
-        }
+    if let Ok(existing) = std::env::var("PYTHONPATH")
+        && !existing.trim().is_empty()
+    {
+        pythonpath_parts.push(existing);
     }
     let merged_pythonpath = if pythonpath_parts.is_empty() {
         None
@@ -1463,9 +6192,43 @@ fn spawn_daemon(
     if let Some(ref py_path) = merged_pythonpath {
         cmd.env("PYTHONPATH", py_path);
     }
-    cmd.current_dir(codebase_root)
-        .stdout(Stdio::null())
-        .stderr(Stdio::null());
+    let query_socket = query_server::socket_path_for(socket_path);
+    if query_socket.exists() {
+        cmd.env(query_server::SOCKET_ENV_VAR, &query_socket);
+    }
+    // The daemon passes its environment through to the training process.
+    cmd.env(control::CONTROL_ENV_VAR, control::control_path(run_dir));
+    if let Ok(meta) = run_meta::load(run_dir)
+        && !meta.config.is_empty()
+    {
+        cmd.env(
+            run_meta::CONFIG_ENV_VAR,
+            serde_json::to_string(&meta.config)?,
+        );
+    }
+    // Rules from the project's `project.toml`, unless the user set their own.
+    if std::env::var_os(project_config::ALERT_RULES_ENV_VAR).is_none()
+        && let Ok(project) = project_config::load(run_dir)
+        && !project.alerts.is_empty()
+    {
+        cmd.env(
+            project_config::ALERT_RULES_ENV_VAR,
+            serde_json::to_string(&project.alerts)?,
+        );
+    }
+    // The daemon's built-in gradient-norm rules use the TUI's limits.
+    if let Some(grad_limits) = grad_limits {
+        cmd.env(
+            grad_health::EXPLODE_ENV_VAR,
+            grad_limits.explode.to_string(),
+        )
+        .env(grad_health::VANISH_ENV_VAR, grad_limits.vanish.to_string())
+        .env(
+            grad_health::VANISH_POINTS_ENV_VAR,
+            grad_limits.vanish_points.to_string(),
+        );
+    }
+    cmd.current_dir(codebase_root);
 
     if let Some(training_cmd) = training_cmd.map(str::trim).filter(|s| !s.is_empty()) {
         cmd.arg("--training-cmd").arg(training_cmd);
@@ -1481,14 +6244,7 @@ fn spawn_daemon(
         cmd.arg("--fresh-run");
     }
 
-    let child = cmd.spawn().with_context(|| {
-        format!(
-            "Failed to spawn daemon with '{}'. Is og-agent-chat installed?",
-            python
-        )
-    })?;
-
-    Ok(child)
+    Ok((cmd, python))
 }
 
 /// Find a working Python interpreter.
@@ -1565,6 +6321,7 @@ fn parse_process_line(line: &str) -> Option<ProcessSnapshot> {
         cpu_pct,
         mem_pct,
         command,
+        gpu_mem_mb: None,
     })
 }
 
@@ -1592,7 +6349,7 @@ fn parse_elapsed_secs(etime: &str) -> Option<u64> {
     Some(days.saturating_mul(86_400).saturating_add(clock_secs))
 }
 
-fn sample_processes() -> Result<Vec<ProcessSnapshot>> {
+fn sample_processes(limits: Option<cgroup::Limits>) -> Result<Vec<ProcessSnapshot>> {
     let output = Command::new("ps")
         .args(["-x", "-o", "pid=,ppid=,state=,etime=,%cpu=,%mem=,command="])
         .output()
@@ -1614,10 +6371,54 @@ fn sample_processes() -> Result<Vec<ProcessSnapshot>> {
             processes.push(process);
         }
     }
+    if let Some(gpu_memory) = sys_sampler::gpu_memory_by_pid() {
+        for process in &mut processes {
+            process.gpu_mem_mb = gpu_memory.get(&process.pid).copied();
+        }
+    }
+    if let Some(limits) = limits {
+        let host_memory_mb = sys_sampler::host_memory_mb().map(|(_, total)| total);
+        rescale_to_limits(&mut processes, limits, host_memory_mb);
+    }
 
     Ok(processes)
 }
 
+/// Turn `ps` percentages of one core and of host memory into shares of a
+/// container's CPU quota and memory limit.
+fn rescale_to_limits(
+    processes: &mut [ProcessSnapshot],
+    limits: cgroup::Limits,
+    host_memory_mb: Option<f64>,
+) {
+    const MIB: f64 = 1024.0 * 1024.0;
+    let memory_scale = limits
+        .memory_bytes
+        .zip(host_memory_mb)
+        .map(|(limit, host)| host / (limit as f64 / MIB))
+        .filter(|scale| scale.is_finite() && *scale > 0.0);
+    for process in processes {
+        if let Some(cpus) = limits.cpus {
+            process.cpu_pct /= cpus as f32;
+        }
+        if let Some(scale) = memory_scale {
+            process.mem_pct *= scale as f32;
+        }
+    }
+}
+
+/// Processes the processes tab filter keeps, with everything they started:
+/// this TUI, the agent daemon on its socket, and the training process the
+/// loaded run's heartbeat names.
+fn training_roots(app: &App) -> Vec<i32> {
+    let daemon = daemon_ctl::recorded_pid(&daemon_ctl::Paths::for_socket(&app.daemon_socket));
+    [Some(std::process::id()), daemon, app.training_pid]
+        .into_iter()
+        .flatten()
+        .filter_map(|pid| i32::try_from(pid).ok())
+        .collect()
+}
+
 fn tail_overlap(previous: &[String], current: &[String]) -> usize {
     let max_overlap = previous.len().min(current.len());
     for overlap in (0..=max_overlap).rev() {
@@ -1628,122 +6429,282 @@ fn tail_overlap(previous: &[String], current: &[String]) -> usize {
     0
 }
 
-fn is_known_log_prefix(token: &str) -> bool {
-    matches!(
-        token,
-        "daemon"
-            | "system"
-            | "info"
-            | "sucess"
-            | "success"
-            | "error"
-            | "important"
-            | "alert"
-            | "auto"
+/// The daemon logs `[system] training exited with code N` when training
+/// stops; turn that into a toast (success for code 0, an alert otherwise).
+fn run_exit_toast(line: &str) -> Option<(ToastLevel, String)> {
+    let (_, rest) = line.split_once("training exited with code")?;
+    let code: i64 = rest.split_whitespace().next()?.parse().ok()?;
+    Some(if code == 0 {
+        (ToastLevel::Success, "training finished".to_string())
+    } else {
+        (
+            ToastLevel::Alert,
+            format!("training exited with code {code}"),
+        )
+    })
+}
+
+fn format_alert_log_line(alert: &socket_client::AlertInfo) -> String {
+    format!(
+        "alert {}: {} (current {:.6}, threshold {:.6})",
+        alert.metric, alert.message, alert.current, alert.threshold
     )
 }
 
-fn strip_known_log_prefixes(mut line: &str) -> &str {
-    loop {
-        let trimmed = line.trim_start();
-        let Some(after_bracket) = trimmed.strip_prefix('[') else {
-            return trimmed;
-        };
-        let Some(end) = after_bracket.find(']') else {
-            return trimmed;
-        };
-        let token = after_bracket[..end].trim().to_ascii_lowercase();
-        if !is_known_log_prefix(token.as_str()) {
-            return trimmed;
-        }
-        line = after_bracket[end + 1..].trim_start();
+fn update_auto_mode(app: &mut App, auto_mode: bool) -> bool {
+    let changed = app.auto_mode != auto_mode;
+    app.auto_mode = auto_mode;
+    if changed && app.live_logs_active {
+        let state = if auto_mode { "enabled" } else { "disabled" };
+        app.append_live_log(LogLevel::Important, format!("auto mode {}", state));
     }
+    changed
 }
 
-fn normalize_live_log_line(raw_line: &str) -> Option<String> {
-    let trimmed = raw_line.trim();
-    if trimmed.is_empty() {
-        return None;
+/// Remember how this run was started (command and git state) so it can be
+/// reproduced and `og fork --launch` can start a sibling.
+fn record_launch_metadata(
+    tui: &TuiArgs,
+    training_file: &Path,
+    events_path: &Path,
+    socket: &Path,
+    project: &project_config::ProjectConfig,
+) -> Result<run_meta::RunMeta> {
+    let launch = run_meta::LaunchSpec {
+        socket: Some(socket.to_path_buf()),
+        training_file: fs::canonicalize(training_file).unwrap_or_else(|_| training_file.into()),
+        training_cmd: tui.training_cmd.clone(),
+        codebase_root: fs::canonicalize(&tui.codebase_root)
+            .unwrap_or_else(|_| tui.codebase_root.clone()),
+    };
+    let mut git = git_state::capture(&tui.codebase_root);
+    let gpu_count = cost::detect_local_gpus();
+    if tui.git_patch
+        && let Some(state) = git.as_mut()
+        && state.dirty
+    {
+        state.patch =
+            git_state::write_patch(&tui.codebase_root, &run_meta::run_dir_for(events_path))?;
     }
+    run_meta::update(events_path, |meta| {
+        meta.launch = Some(launch);
+        meta.git = git;
+        meta.gpu_count = gpu_count;
+        meta.slurm = run_meta::SlurmJob::from_env();
+        meta.k8s = k8s::detect();
+        project.apply_to_new_run(meta);
+        meta.clone()
+    })
+}
 
-    let lower = trimmed.to_ascii_lowercase();
-    let stripped = strip_known_log_prefixes(trimmed);
-    let message = if stripped.is_empty() {
-        trimmed
-    } else {
-        stripped
-    };
+/// The daemon socket recorded for the run at `events_path`, if a daemon
+/// still answers on it.
+fn recorded_daemon_socket(events_path: &Path) -> Option<PathBuf> {
+    let socket = run_meta::load(events_path).ok()?.launch?.socket?;
+    socket_client::ping(&socket)
+        .unwrap_or(false)
+        .then_some(socket)
+}
 
-    let level = if lower.starts_with("[important]")
-        || lower.starts_with("[alert]")
-        || lower.starts_with("[auto]")
-    {
-        "important"
-    } else if lower.starts_with("[error]")
-        || lower.contains("traceback")
-        || lower.contains("exception")
-        || lower.contains("panic")
-        || lower.contains("fatal")
-        || lower.contains("failed")
-        || lower.contains("error")
-        || (lower.contains("exited with code") && !lower.contains("code 0"))
-    {
-        "error"
-    } else if lower.starts_with("[sucess]")
-        || lower.starts_with("[success]")
-        || lower.contains("succeeded")
-        || lower.contains("completed")
-        || lower.contains("restarted")
-        || lower.contains("exited with code 0")
-    {
-        "sucess"
-    } else {
-        "info"
+/// Snapshot packages and launch-relevant env vars into the run directory.
+fn record_environment(tui: &TuiArgs, events_path: &Path) -> Result<()> {
+    let python = find_python(&tui.codebase_root);
+    let snapshot = env_capture::capture(&python, &tui.codebase_root);
+    env_capture::save(
+        &run_meta::run_dir_for(events_path),
+        &tui.codebase_root,
+        &snapshot,
+    )
+}
+
+/// Persist a restart marker so it survives TUI restarts and shows up in `og get run`.
+fn record_restart_annotation(app: &mut App, events_path: &Path, from_step: i64, to_step: i64) {
+    let annotation = run_meta::Annotation {
+        step: to_step,
+        kind: run_meta::AnnotationKind::Restart,
+        message: format!("step counter reset from {}", from_step),
+        created_unix: unix_now_secs(),
     };
+    match run_meta::update(events_path, |meta| {
+        meta.add_annotation(annotation.clone());
+        meta.annotations.clone()
+    }) {
+        Ok(annotations) => app.annotations = annotations,
+        Err(err) => {
+            app.append_live_log(
+                LogLevel::Error,
+                format!("failed to record restart annotation: {err}"),
+            );
+            app.annotations.push(annotation);
+        }
+    }
+}
+
+fn set_copy_mode(app: &mut App, enabled: bool) -> Result<()> {
+    if app.copy_mode == enabled {
+        return Ok(());
+    }
 
-    Some(format!("[{}] {}", level, message))
+    let mut stdout = io::stdout();
+    if enabled {
+        execute!(stdout, crossterm::event::DisableMouseCapture)?;
+        app.copy_mode = true;
+        app.chat_status =
+            "Copy mode enabled — drag mouse to highlight text, press F6 to resume".to_string();
+    } else {
+        execute!(stdout, crossterm::event::EnableMouseCapture)?;
+        app.copy_mode = false;
+        app.chat_status = "Copy mode disabled — interactive mouse controls restored".to_string();
+    }
+    Ok(())
 }
 
-fn format_alert_log_line(alert: &socket_client::AlertInfo) -> String {
-    format!(
-        "[important] alert {}: {} (current {:.6}, threshold {:.6})",
-        alert.metric, alert.message, alert.current, alert.threshold
-    )
+/// Copy a metric's stats, or with `as_csv` the tail of its series.
+fn copy_metric(app: &mut App, index: usize, as_csv: bool) {
+    let Some(tag) = app.tags.get(index).cloned() else {
+        app.chat_status = "No metric selected".to_string();
+        return;
+    };
+    let data = app.scalars.get(&tag).cloned().unwrap_or_default();
+    if as_csv {
+        let text = clipboard::series_csv(&tag, &data, clipboard::MAX_CSV_POINTS);
+        let points = data.len().min(clipboard::MAX_CSV_POINTS);
+        app.copy_to_clipboard(&text, &format!("{points} points of {tag} as CSV"));
+    } else {
+        app.copy_to_clipboard(
+            &clipboard::metric_stats(&tag, &data),
+            &format!("{tag} stats"),
+        );
+    }
 }
 
-fn update_auto_mode(app: &mut App, auto_mode: bool) -> bool {
-    let changed = app.auto_mode != auto_mode;
-    app.auto_mode = auto_mode;
-    if changed && app.live_logs_active {
-        let state = if auto_mode { "enabled" } else { "disabled" };
-        app.append_live_log(format!("[important] auto mode {}", state));
+/// Copy the selected log lines (leaving selection mode), or without a
+/// selection the lines currently on screen.
+fn copy_log_lines(app: &mut App) {
+    let range = match app.log_selection.take() {
+        Some(selection) => selection.range(),
+        None => {
+            let visible = app.visible_log_range();
+            visible.start..=visible.end.saturating_sub(1)
+        }
+    };
+    let lines: Vec<String> = app
+        .log_lines
+        .get(range)
+        .unwrap_or_default()
+        .iter()
+        .filter(|entry| app.shows_log(entry))
+        .map(LogEntry::to_string)
+        .collect();
+    if lines.is_empty() {
+        app.chat_status = "No log lines to copy".to_string();
+        return;
+    }
+    let count = lines.len();
+    let text = lines.join("\n");
+    let suffix = if count == 1 { "" } else { "s" };
+    app.copy_to_clipboard(&text, &format!("{count} log line{suffix}"));
+}
+
+/// Ask the daemon to restart training from the checkpoint selected in the panel.
+fn resume_from_selected_checkpoint(app: &mut App, bg_tx: &mpsc::Sender<BgMessage>) {
+    let Some(checkpoint) = app.selected_checkpoint().cloned() else {
+        return;
+    };
+    if checkpoint.size_bytes.is_none() {
+        app.chat_status = format!("{} no longer exists", checkpoint.path.display());
+        return;
     }
-    changed
+    if !app.daemon_connected {
+        app.chat_status = "Daemon not connected — cannot restart training".to_string();
+        return;
+    }
+    app.checkpoint_panel = None;
+    app.chat_status = format!("Restarting from step {}...", checkpoint.step);
+    let tx = bg_tx.clone();
+    let sock = app.daemon_socket.clone();
+    std::thread::spawn(move || {
+        let _ = match socket_client::resume_training(&checkpoint.path, &sock) {
+            Ok(()) => tx.send(BgMessage::ResumeStarted {
+                step: checkpoint.step,
+                checkpoint: checkpoint.path,
+            }),
+            Err(e) => tx.send(BgMessage::ResumeError(e.to_string())),
+        };
+    });
 }
 
-fn set_copy_mode(app: &mut App, enabled: bool) -> Result<()> {
-    if app.copy_mode == enabled {
-        return Ok(());
+/// Act on a newly detected plateau of the objective metric: record the
+/// decision in the run metadata and, in autonomous mode, stop training.
+fn handle_convergence(app: &mut App, events_path: Option<&Path>, bg_tx: &mpsc::Sender<BgMessage>) {
+    let Some(plateau) = app.check_convergence() else {
+        return;
+    };
+    let Some(policy) = app.early_stop.clone() else {
+        return;
+    };
+    let stop = app.auto_mode && app.daemon_connected;
+    let decision = run_meta::EarlyStop {
+        metric: policy.metric.clone(),
+        action: if stop {
+            run_meta::EarlyStopAction::StopRequested
+        } else {
+            run_meta::EarlyStopAction::Suggested
+        },
+        step: plateau.step as i64,
+        best: plateau.best,
+        best_step: plateau.best_step as i64,
+        patience: policy.patience,
+        min_delta: policy.min_delta,
+        decided_unix: unix_now_secs(),
+    };
+    if let Some(events_path) = events_path
+        && let Err(err) = run_meta::update(events_path, |meta| meta.early_stop = Some(decision))
+    {
+        app.append_live_log(
+            LogLevel::Error,
+            format!("failed to record early stop: {err}"),
+        );
+    }
+    if !stop {
+        return;
     }
+    app.chat_status = "Stopping training (converged)...".to_string();
+    let reason = format!(
+        "{} plateaued for {} evaluations",
+        policy.metric, plateau.evaluations
+    );
+    let tx = bg_tx.clone();
+    let sock = app.daemon_socket.clone();
+    std::thread::spawn(move || {
+        let _ = match socket_client::stop_training(&reason, &sock) {
+            Ok(()) => tx.send(BgMessage::TrainingStopped),
+            Err(e) => tx.send(BgMessage::StopError(e.to_string())),
+        };
+    });
+}
 
-    let mut stdout = io::stdout();
-    if enabled {
-        execute!(stdout, crossterm::event::DisableMouseCapture)?;
-        app.copy_mode = true;
-        app.chat_status =
-            "Copy mode enabled — drag mouse to highlight text, press F6 to resume".to_string();
-    } else {
-        execute!(stdout, crossterm::event::EnableMouseCapture)?;
-        app.copy_mode = false;
-        app.chat_status = "Copy mode disabled — interactive mouse controls restored".to_string();
+/// Log newly suspected dataloader stalls and pass them on to the agent.
+fn report_input_stalls(app: &mut App) {
+    let diagnostics = app.check_input_stalls();
+    if diagnostics.is_empty() || !app.daemon_connected {
+        return;
     }
-    Ok(())
+    let sock = app.daemon_socket.clone();
+    std::thread::spawn(move || {
+        for diagnostic in diagnostics {
+            let _ = socket_client::append_log(&format!("[system] {diagnostic}"), &sock);
+        }
+    });
 }
 
 /// Messages from background threads to the main event loop.
+#[derive(Serialize, Deserialize)]
 enum BgMessage {
     DaemonConnected(bool),
     ChatHistory(Vec<socket_client::ChatMessage>),
+    /// Partial agent reply while a chat request is still running.
+    ChatStream(socket_client::ChatStreamEvent),
     ChatSendResult {
         plan: socket_client::ActionPlanResponse,
         messages: Vec<socket_client::ChatMessage>,
@@ -1752,11 +6713,44 @@ enum BgMessage {
     RunStateUpdate {
         auto_mode: bool,
     },
+    /// Refactor applied in-process by `patch::apply`; the daemon only restarts training.
     RefactorApplied {
-        success: bool,
-        messages: Vec<socket_client::ChatMessage>,
+        checkpoint: PathBuf,
+        files: usize,
+        restarted: bool,
     },
     RefactorError(String),
+    /// Daemon accepted a restart from the checkpoint saved at `step`.
+    ResumeStarted {
+        step: i64,
+        checkpoint: PathBuf,
+    },
+    ResumeError(String),
+    /// Daemon stopped training after an early-stop decision.
+    TrainingStopped,
+    StopError(String),
+    /// Run rows for the dashboard tab, summarised under `root`.
+    DashboardRows {
+        root: PathBuf,
+        rows: Vec<dashboard::DashboardRow>,
+    },
+    /// Seed groups of the project at `path`.
+    SeedGroups {
+        path: PathBuf,
+        groups: Vec<seed_band::SeedGroup>,
+    },
+    /// Histograms of the run at `path`, or why they could not be read.
+    Histograms {
+        path: PathBuf,
+        histograms: Result<BTreeMap<String, Vec<histogram::Histogram>>, String>,
+    },
+    /// A `ps` sample for the processes tab; None when `ps` failed.
+    Processes(Option<Vec<ProcessSnapshot>>),
+    /// Run sizes under the dashboard root and free space on its filesystem.
+    DiskUsage {
+        sizes: BTreeMap<String, u64>,
+        free_bytes: Option<u64>,
+    },
     LiveMetrics {
         metrics: serde_json::Map<String, serde_json::Value>,
         logs: Vec<String>,
@@ -1764,6 +6758,88 @@ enum BgMessage {
         current_step: i64,
         auto_mode: bool,
     },
+    /// Event-file view rebuilt from a recorded session.
+    ReplayRefresh {
+        scalars: BTreeMap<String, Vec<(f64, f64)>>,
+        log_lines: Vec<LogEntry>,
+        total_events: usize,
+        max_step: i64,
+    },
+    ReplayFinished,
+}
+
+impl BgMessage {
+    fn kind(&self) -> &'static str {
+        match self {
+            BgMessage::DaemonConnected(_) => "daemon_connected",
+            BgMessage::ChatHistory(_) => "chat_history",
+            BgMessage::ChatStream(_) => "chat_stream",
+            BgMessage::ChatSendResult { .. } => "chat_send_result",
+            BgMessage::ChatSendError(_) => "chat_send_error",
+            BgMessage::RunStateUpdate { .. } => "run_state_update",
+            BgMessage::RefactorApplied { .. } => "refactor_applied",
+            BgMessage::RefactorError(_) => "refactor_error",
+            BgMessage::ResumeStarted { .. } => "resume_started",
+            BgMessage::ResumeError(_) => "resume_error",
+            BgMessage::TrainingStopped => "training_stopped",
+            BgMessage::StopError(_) => "stop_error",
+            BgMessage::DashboardRows { .. } => "dashboard_rows",
+            BgMessage::SeedGroups { .. } => "seed_groups",
+            BgMessage::Histograms { .. } => "histograms",
+            BgMessage::Processes(_) => "processes",
+            BgMessage::DiskUsage { .. } => "disk_usage",
+            BgMessage::LiveMetrics { .. } => "live_metrics",
+            BgMessage::ReplayRefresh { .. } => "replay_refresh",
+            BgMessage::ReplayFinished => "replay_finished",
+        }
+    }
+}
+
+/// A recorded session played back into the TUI instead of live sources.
+struct Replay {
+    entries: Vec<session::Entry<BgMessage>>,
+    speed: f64,
+}
+
+/// Feed recorded entries to the TUI, keeping their original spacing divided by `speed`.
+fn spawn_replay(replay: Replay, tx: mpsc::Sender<BgMessage>) {
+    std::thread::spawn(move || {
+        let started = Instant::now();
+        let mut view = session::ReplayView::default();
+        for entry in replay.entries {
+            let due = Duration::from_secs_f64(entry.t_ms() as f64 / 1000.0 / replay.speed);
+            if let Some(wait) = due.checked_sub(started.elapsed()) {
+                std::thread::sleep(wait);
+            }
+            let msg = match entry {
+                session::Entry::Message { message, .. } => message,
+                session::Entry::Refresh { refresh, .. } => {
+                    let (total_events, max_step) = (refresh.total_events, refresh.max_step);
+                    view.apply(refresh);
+                    BgMessage::ReplayRefresh {
+                        scalars: view.scalars.clone(),
+                        log_lines: view.log_lines.clone(),
+                        total_events,
+                        max_step,
+                    }
+                }
+            };
+            if tx.send(msg).is_err() {
+                return;
+            }
+        }
+        let _ = tx.send(BgMessage::ReplayFinished);
+    });
+}
+
+/// Recording is best-effort: a write failure stops it instead of the session.
+fn stop_recording(app: &mut App, recorder: &mut Option<session::Recorder>, err: &anyhow::Error) {
+    *recorder = None;
+    app.append_live_log(
+        LogLevel::Error,
+        format!("session recording stopped: {err:#}"),
+    );
+    app.toast(ToastLevel::Alert, "Session recording stopped");
 }
 
 fn unix_now_secs_f64() -> f64 {
@@ -1773,15 +6849,31 @@ fn unix_now_secs_f64() -> f64 {
         .unwrap_or(0.0)
 }
 
+/// Send a chat message on a background thread, forwarding streamed chunks as
+/// they arrive and the final reply when the agent is done.
+fn spawn_chat_request(app: &mut App, bg_tx: &mpsc::Sender<BgMessage>, content: String) {
+    app.agent_thinking = true;
+    app.chat_stream.clear();
+    let tx = bg_tx.clone();
+    let sock = app.daemon_socket.clone();
+    std::thread::spawn(move || {
+        let reply = socket_client::stream_chat_message(&content, &sock, |event| {
+            let _ = tx.send(BgMessage::ChatStream(event));
+        });
+        let _ = match reply {
+            Ok((plan, messages)) => tx.send(BgMessage::ChatSendResult { plan, messages }),
+            Err(e) => tx.send(BgMessage::ChatSendError(e.to_string())),
+        };
+    });
+}
+
 fn push_chat_message(app: &mut App, sender: &str, content: String) {
-    app.chat_messages.push(socket_client::ChatMessage {
+    app.push_chat_message(socket_client::ChatMessage {
         sender: sender.to_string(),
         content,
         timestamp: unix_now_secs_f64(),
     });
     app.chat_follow_tail = true;
-    let len = app.chat_messages.len();
-    app.chat_scroll = len.saturating_sub(1) as u16;
 }
 
 fn parse_bang_og_cli(content: &str) -> Result<Cli> {
@@ -1807,6 +6899,186 @@ fn parse_bang_og_cli(content: &str) -> Result<Cli> {
     Cli::try_parse_from(argv).map_err(|e| anyhow::anyhow!(e.to_string()))
 }
 
+/// Preset after `current` in config order; after the last one, no preset (all metrics).
+fn next_graph_preset<'a>(
+    graph_presets: &'a [presets::GraphPreset],
+    current: Option<&str>,
+) -> Option<&'a presets::GraphPreset> {
+    let idx = match current.and_then(|name| graph_presets.iter().position(|p| p.name == name)) {
+        Some(idx) => idx + 1,
+        None if current.is_some() => graph_presets.len(),
+        None => 0,
+    };
+    graph_presets.get(idx)
+}
+
+/// Show the metrics `graph_filter` selects: reload them from `events_path`,
+/// or without one, narrow the metrics already loaded. Returns the filter's
+/// selectors that matched nothing.
+fn apply_graph_filter(
+    app: &mut App,
+    events_path: Option<&Path>,
+    graph_filter: Option<&GraphFilter>,
+) -> Vec<graph_filter::Unmatched> {
+    if let Some(events_path) = events_path {
+        return reload_view(app, events_path, graph_filter);
+    }
+    if let Some(filter) = graph_filter {
+        let scalars = std::mem::take(&mut app.scalars);
+        let log_lines = app.log_lines.to_vec();
+        app.replace_data(
+            filter_scalars(scalars, filter),
+            log_lines,
+            app.total_events,
+            app.max_step,
+        );
+    }
+    Vec::new()
+}
+
+/// Run the text typed after `:`. View commands act on the TUI; the rest go
+/// through the same `og` command registry as `!og` in the chat, whose reply
+/// lands in the chat tab.
+fn run_command_line(
+    app: &mut App,
+    line: &str,
+    events_path: Option<&Path>,
+    graph_filter: &mut Option<GraphFilter>,
+    bg_tx: &mpsc::Sender<BgMessage>,
+) -> Result<()> {
+    let parsed = command_line::parse(line, |line| {
+        parse_bang_og_cli(&format!("!og {line}")).is_ok_and(|cli| cli.command.is_some())
+    })?;
+    let command = match parsed {
+        command_line::Parsed::Og(content) => {
+            push_chat_message(app, "user", content.clone());
+            handle_in_app_og_command(&content, app, bg_tx)?;
+            app.active_tab = app::Tab::Chat;
+            return Ok(());
+        }
+        command_line::Parsed::View(command) => command,
+    };
+    match command {
+        command_line::ViewCommand::Tab { tab } => {
+            app.unfocus_metric();
+            app.active_tab = tab;
+        }
+        command_line::ViewCommand::Focus { metric } => {
+            let index = command_line::find_metric(&app.tags, &metric)?;
+            app.active_tab = app::Tab::Graphs;
+            app.selected_metric = index;
+            app.focus_metric(index);
+        }
+        command_line::ViewCommand::Filter { patterns } => {
+            for pattern in &patterns {
+                graph_filter::validate_selector("metrics", pattern)?;
+            }
+            *graph_filter = (!patterns.is_empty()).then(|| GraphFilter {
+                metrics: patterns.clone(),
+                sys: Vec::new(),
+                strict: app.graph_strict,
+            });
+            app.graph_preset = None;
+            app.unfocus_metric();
+            let misses = apply_graph_filter(app, events_path, graph_filter.as_ref());
+            app.chat_status = match misses.first() {
+                Some(miss) => format!("Filter: {miss}"),
+                None if patterns.is_empty() => "Showing all metrics".to_string(),
+                None => format!("Filter: {}", patterns.join(" ")),
+            };
+        }
+        command_line::ViewCommand::Export {
+            format: command_line::ExportFormat::Csv,
+            path,
+        } => {
+            let path = path.unwrap_or_else(|| {
+                let run = run_meta::run_dir_for(&app.events_path)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| "metrics".to_string());
+                PathBuf::from(format!("{run}.csv"))
+            });
+            fs::write(&path, clipboard::scalars_csv(&app.scalars))
+                .with_context(|| format!("writing {}", path.display()))?;
+            app.chat_status = format!(
+                "Exported {} metrics to {}",
+                app.scalars.len(),
+                path.display()
+            );
+        }
+        command_line::ViewCommand::Diff { run: None, .. } => {
+            app.run_diff = None;
+            app.chat_status = "Comparison closed".to_string();
+        }
+        command_line::ViewCommand::Diff {
+            run: Some(run),
+            overlay,
+        } => {
+            let path = diff_run_path(&app.events_path, &run)?;
+            let layout = if overlay {
+                run_diff::Layout::Overlay
+            } else {
+                run_diff::Layout::SideBySide
+            };
+            let diff = run_diff::RunDiff::load(&app.events_path, &path, layout)?;
+            app.chat_status = format!("Comparing with {}", diff.name);
+            app.run_diff = Some(diff);
+            app.active_tab = app::Tab::Graphs;
+            if app.focused_metric.is_none() {
+                app.focus_metric(app.selected_metric);
+            }
+        }
+        command_line::ViewCommand::Quit => app.request_quit(),
+    }
+    Ok(())
+}
+
+/// The run `:diff <run>` names: a path as typed, else a run, alias or
+/// `latest` beside the loaded run.
+fn diff_run_path(loaded: &Path, run: &str) -> Result<PathBuf> {
+    if Path::new(run).exists() {
+        return Ok(PathBuf::from(run));
+    }
+    let loaded = run_meta::run_dir_for(loaded);
+    let parent = loaded
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let path = query::resolve_alias_path(&parent.join(run));
+    if !path.exists() {
+        return Err(OgError::run_not_found(run, &path).into());
+    }
+    Ok(path)
+}
+
+/// Reload the event files for the selected rank and apply the graph filter.
+/// Returns the filter's selectors that matched nothing.
+fn reload_view(
+    app: &mut App,
+    events_path: &Path,
+    graph_filter: Option<&GraphFilter>,
+) -> Vec<graph_filter::Unmatched> {
+    let Ok(mut updated) = load_view_data_for_rank(events_path, app.selected_rank) else {
+        return Vec::new();
+    };
+    let mut misses = Vec::new();
+    if let Some(filter) = graph_filter {
+        misses = graph_filter_misses(filter, &updated.scalars);
+        updated.scalars = filter_scalars(updated.scalars, filter);
+    }
+    app.set_ranks(updated.ranks);
+    app.set_rank_progress(updated.rank_progress);
+    app.sys_strip = updated.sys_strip;
+    app.epoch_scale = updated.epoch_scale;
+    app.replace_data(
+        updated.scalars,
+        updated.log_lines,
+        updated.total_events,
+        updated.max_step,
+    );
+    misses
+}
+
 fn execute_in_app_run_command(
     args: RunArgs,
     app: &mut App,
@@ -1816,7 +7088,10 @@ fn execute_in_app_run_command(
         bail!("daemon is not connected; start og with --training-file first");
     }
 
-    let graph_filter = args.graph.as_deref().map(parse_graph_filter).transpose()?;
+    let graph_filter = match args.graph.as_deref() {
+        Some(raw) => Some(resolve_graph_filter(raw, &presets::load()?)?),
+        None => None,
+    };
 
     let runtime = args.runtime.as_str();
     let resolved_runtime = socket_client::set_runtime(runtime, &app.daemon_socket)?;
@@ -1827,20 +7102,8 @@ fn execute_in_app_run_command(
     socket_client::start_training(&app.daemon_socket)?;
 
     if let Some(prompt) = args.prompt.clone() {
-        app.agent_thinking = true;
         app.chat_status = "Sending prompt...".to_string();
-        let tx = bg_tx.clone();
-        let sock = app.daemon_socket.clone();
-        std::thread::spawn(
-            move || match socket_client::send_chat_message(&prompt, &sock) {
-                Ok((plan, messages)) => {
-                    let _ = tx.send(BgMessage::ChatSendResult { plan, messages });
-                }
-                Err(e) => {
-                    let _ = tx.send(BgMessage::ChatSendError(e.to_string()));
-                }
-            },
-        );
+        spawn_chat_request(app, bg_tx, prompt);
     }
 
     let mode = match args.auto_mode {
@@ -1881,35 +7144,244 @@ fn handle_in_app_og_command(
     bg_tx: &mpsc::Sender<BgMessage>,
 ) -> Result<()> {
     let cli = parse_bang_og_cli(content)?;
+    let mode = cli.output_mode();
     let Some(command) = cli.command else {
-        bail!("usage: !og <run|tail|resume|list|get|compare|search> ...");
+        bail!(
+            "usage: !og <run|tail|resume|list|get|compare|diff|leaderboard|search|annotate|note|fork|compact|import|apply|rollback> ..."
+        );
     };
 
     let output = match command {
-        OgCommand::Run(args) => execute_in_app_run_command(args, app, bg_tx)?,
+        OgCommand::Run(args) => execute_in_app_run_command(*args, app, bg_tx)?,
         other => execute_query_command(other)?,
     };
 
-    let rendered = if cli.json {
-        serde_json::to_string_pretty(&output.data)?
-    } else {
-        output.text
+    let rendered = match mode {
+        OutputMode::Json => serde_json::to_string_pretty(&output.data)?,
+        OutputMode::Porcelain => porcelain::render(&output.data),
+        OutputMode::Text => output.text,
+        OutputMode::Quiet => String::new(),
     };
-    push_chat_message(app, "system", rendered);
+    if !rendered.is_empty() {
+        push_chat_message(app, "system", rendered);
+    }
     app.chat_status = "Command executed".to_string();
     Ok(())
 }
 
+/// How often the dashboard tab rescans runs while it is visible.
+const DASHBOARD_REFRESH: Duration = Duration::from_secs(2);
+/// How often the distributions tab rereads histograms while it is visible.
+const HISTOGRAM_REFRESH: Duration = Duration::from_secs(2);
+/// How often a multi-run project's seed groups are reread while the graphs
+/// tab is visible.
+const SEED_BAND_REFRESH: Duration = Duration::from_secs(5);
+/// How often run sizes and free space under the runs root are measured.
+const DISK_USAGE_REFRESH: Duration = Duration::from_secs(60);
+
+fn spawn_dashboard_rows(root: PathBuf, tx: mpsc::Sender<BgMessage>) {
+    std::thread::spawn(move || {
+        let rows = load_dashboard_rows(&root);
+        let _ = tx.send(BgMessage::DashboardRows { root, rows });
+    });
+}
+
+/// Projects that fail to load report no groups, which also clears the
+/// pending flag.
+fn spawn_seed_groups(path: PathBuf, tx: mpsc::Sender<BgMessage>) {
+    std::thread::spawn(move || {
+        let groups = seed_band::load(&path).unwrap_or_default();
+        let _ = tx.send(BgMessage::SeedGroups { path, groups });
+    });
+}
+
+fn spawn_histograms(path: PathBuf, tx: mpsc::Sender<BgMessage>) {
+    std::thread::spawn(move || {
+        let histograms = histogram::load(&path).map_err(|err| format!("{err:#}"));
+        let _ = tx.send(BgMessage::Histograms { path, histograms });
+    });
+}
+
+/// `ps` and `nvidia-smi` can hang with a wedged driver, so sampling stays
+/// off the event loop.
+fn spawn_processes(limits: Option<cgroup::Limits>, tx: mpsc::Sender<BgMessage>) {
+    std::thread::spawn(move || {
+        let _ = tx.send(BgMessage::Processes(sample_processes(limits).ok()));
+    });
+}
+
+fn spawn_disk_usage(root: PathBuf, tx: mpsc::Sender<BgMessage>) {
+    std::thread::spawn(move || {
+        let run_dirs = list_run_dirs(&root).unwrap_or_default();
+        let sizes = run_index::run_sizes(&root, &run_dirs);
+        let free_bytes = disk_usage::free_space(&root);
+        let _ = tx.send(BgMessage::DiskUsage { sizes, free_bytes });
+    });
+}
+
+fn describe_backoff(backoff: &backoff::IdleBackoff, base: Duration, now: Instant) -> String {
+    if backoff.level(now) == 0 {
+        return format!(
+            "new activity; polling every {} again",
+            backoff::describe(base)
+        );
+    }
+    format!(
+        "no new data for {}; polling every {}",
+        backoff::describe(Duration::from_secs(backoff.idle_for(now).as_secs())),
+        backoff::describe(backoff.interval(base, now))
+    )
+}
+
+/// Cheap summary of what a refresh can change, to tell whether it needs a redraw.
+fn view_fingerprint(app: &App) -> (usize, i64, usize, usize, usize) {
+    let points = app.scalars.values().map(Vec::len).sum();
+    (
+        app.total_events,
+        app.max_step,
+        app.tags.len(),
+        app.log_lines.len(),
+        points,
+    )
+}
+
+/// Fold a freshly loaded view into the app, logging new events and steps and
+/// noting restarts. Shared by the event-file poll and session replay.
+fn apply_refresh(
+    app: &mut App,
+    mut updated: ViewData,
+    events_path: Option<&Path>,
+    graph_filter: Option<&GraphFilter>,
+    graph_filter_checked: &mut bool,
+) {
+    if let Some(filter) = graph_filter {
+        // Runs that had no metrics at startup are checked once they do.
+        if !*graph_filter_checked && !updated.scalars.is_empty() {
+            let misses = graph_filter_misses(filter, &updated.scalars);
+            report_graph_misses(app, &misses);
+            *graph_filter_checked = true;
+        }
+        updated.scalars = filter_scalars(updated.scalars, filter);
+    }
+    app.set_ranks(updated.ranks);
+    app.set_rank_progress(updated.rank_progress);
+    if updated.wall_secs.is_some() {
+        app.wall_secs = updated.wall_secs;
+        app.wall_gaps = updated.gaps;
+    }
+    app.sys_strip = updated.sys_strip;
+    app.epoch_scale = updated.epoch_scale;
+    if let Some(step) = &app.sampler_step {
+        step.store(updated.max_step, std::sync::atomic::Ordering::Relaxed);
+    }
+    if let Some(events_path) = events_path
+        && let Ok(meta) = run_meta::load(events_path)
+    {
+        app.apply_run_meta(meta);
+    }
+    // The open panel keeps the list it measured; markers catch up once it closes.
+    if let Some(events_path) = events_path
+        && app.checkpoint_panel.is_none()
+        && let Ok(list) = checkpoints::load(events_path)
+    {
+        app.checkpoints = list;
+    }
+    if let Some(events_path) = events_path
+        && let Ok(events) = metrics_jsonl::load_structured(events_path)
+    {
+        app.set_structured_events(events);
+    }
+    if let Some(events_path) = events_path
+        && app.table_panel.is_some()
+        && let Ok(tables) = table::load(events_path)
+    {
+        app.set_tables(table::latest(tables));
+    }
+    let prev_events = app.total_events;
+    let prev_step = app.max_step;
+    let events_grew = updated.total_events > prev_events;
+    let step_changed = updated.max_step != prev_step;
+    let daemon_live_metrics_active = app.daemon_connected && app.live_logs_active;
+    if daemon_live_metrics_active {
+        // Keep daemon-fed metrics visible even when event-file refresh is empty.
+        app.total_events = app.total_events.max(updated.total_events);
+        app.max_step = app.max_step.max(updated.max_step);
+    } else {
+        app.replace_data(
+            updated.scalars,
+            updated.log_lines,
+            updated.total_events,
+            updated.max_step,
+        );
+    }
+
+    // Event-file refresh is also a live source (even when daemon is connected).
+    if !app.live_logs_active && (events_grew || step_changed) {
+        app.activate_live_logs();
+        app.append_live_log(
+            LogLevel::Important,
+            "live mode: watching event stream updates",
+        );
+        app.last_logged_step = prev_step;
+    }
+
+    if app.live_logs_active {
+        if updated.total_events > prev_events {
+            let delta = updated.total_events - prev_events;
+            let suffix = if delta == 1 { "" } else { "s" };
+            app.append_live_log(
+                LogLevel::Info,
+                format!(
+                    "{} new event{} parsed (total {})",
+                    delta, suffix, updated.total_events
+                ),
+            );
+        }
+
+        if !app.daemon_connected && updated.max_step < app.last_logged_step {
+            app.append_live_log(
+                LogLevel::Info,
+                format!("step counter reset to {}", updated.max_step),
+            );
+            if let Some(events_path) = events_path {
+                let from_step = app.last_logged_step;
+                record_restart_annotation(app, events_path, from_step, updated.max_step);
+            }
+            app.last_logged_step = updated.max_step;
+        } else if updated.max_step > app.last_logged_step {
+            let delta = updated.max_step - app.last_logged_step;
+            app.append_live_log(
+                LogLevel::Success,
+                format!("step {} completed (+{})", updated.max_step, delta),
+            );
+            app.last_logged_step = updated.max_step;
+        }
+    }
+    app.scan_anomalies();
+    app.check_budget();
+    app.check_thermal();
+    app.check_oom();
+    report_input_stalls(app);
+}
+
+#[allow(clippy::too_many_arguments)]
 fn run_app<B: Backend>(
     terminal: &mut Terminal<B>,
     mut app: App,
-    events_path: Option<&Path>,
+    initial_path: Option<&Path>,
     refresh_ms: u64,
     procs_interval_ms: u64,
+    max_fps: u32,
+    idle_backoff: bool,
     startup_prompt: Option<String>,
-    graph_filter: Option<GraphFilter>,
+    mut graph_filter: Option<GraphFilter>,
+    graph_presets: Vec<presets::GraphPreset>,
     daemon_expected: bool,
+    mut recorder: Option<session::Recorder>,
+    replay: Option<Replay>,
 ) -> Result<()> {
+    // Switching projects with `P` points the loop at another path.
+    let mut events_path = initial_path.map(Path::to_path_buf);
     // Track layout regions for mouse hit-testing
     let mut layout = ui::LayoutRegions::default();
     let refresh_interval = (refresh_ms > 0).then(|| Duration::from_millis(refresh_ms));
@@ -1923,12 +7395,31 @@ fn run_app<B: Backend>(
     let poll_interval = Duration::from_millis(500);
     let process_poll_interval =
         (procs_interval_ms > 0).then(|| Duration::from_millis(procs_interval_ms));
-    let mut last_process_poll = Instant::now();
+    let mut last_process_poll: Option<Instant> = None;
+    let mut processes_pending = false;
+    let mut last_dashboard_refresh: Option<Instant> = None;
+    let mut last_histogram_refresh: Option<Instant> = None;
+    let mut last_seed_band_refresh: Option<Instant> = None;
+    let mut last_disk_usage: Option<Instant> = None;
+    let mut disk_usage_pending = false;
+    let mut dashboard_pending = false;
+    let mut seed_band_pending = false;
+    let mut histogram_pending = false;
     let tick_rate = Duration::from_millis(100);
+    let mut redraw = redraw::RedrawGate::new(max_fps);
+    let mut backoff = backoff::IdleBackoff::new(idle_backoff, Instant::now());
+    let mut backoff_level = 0;
     let mut startup_prompt = startup_prompt;
+    // run_tui already checked the filter against metrics present at startup.
+    let mut graph_filter_checked = !app.scalars.is_empty();
+
+    let replaying = replay.is_some();
+    if let Some(replay) = replay {
+        spawn_replay(replay, bg_tx.clone());
+    }
 
     // Initial daemon connection check
-    {
+    if !replaying {
         let tx = bg_tx.clone();
         let sock = app.daemon_socket.clone();
         std::thread::spawn(move || {
@@ -1947,94 +7438,147 @@ fn run_app<B: Backend>(
         });
     }
 
-    if process_poll_interval.is_some() {
-        if let Ok(processes) = sample_processes() {
-            app.update_processes(processes, unix_now_secs());
-        }
-    }
+    app.container_limits = cgroup::Cgroup::detect().map(|group| group.limits());
 
     loop {
-        if let Some(interval) = process_poll_interval {
-            if last_process_poll.elapsed() >= interval {
-                if let Ok(processes) = sample_processes() {
-                    app.update_processes(processes, unix_now_secs());
+        if shutdown::received().is_some() {
+            return Ok(());
+        }
+        if let Some(interval) = process_poll_interval
+            && !processes_pending
+            && last_process_poll.is_none_or(|at| at.elapsed() >= interval)
+        {
+            processes_pending = true;
+            last_process_poll = Some(Instant::now());
+            spawn_processes(app.container_limits, bg_tx.clone());
+        }
+
+        let now = Instant::now();
+        let level = backoff.level(now);
+        if level != backoff_level {
+            app.append_live_log(
+                LogLevel::Info,
+                describe_backoff(&backoff, refresh_interval.unwrap_or(poll_interval), now),
+            );
+            backoff_level = level;
+            redraw.mark();
+        }
+
+        if let Some(interval) = refresh_interval.map(|base| backoff.interval(base, now))
+            && last_refresh.elapsed() >= interval
+        {
+            let _refresh = tracing::debug_span!("refresh").entered();
+            let before = view_fingerprint(&app);
+            if let Some(events_path) = events_path.as_deref()
+                && let Ok(updated) = load_view_data_for_rank(events_path, app.selected_rank)
+            {
+                if let Some(rec) = recorder.as_mut()
+                    && let Err(err) = rec.refresh(
+                        &updated.scalars,
+                        &updated.log_lines,
+                        updated.total_events,
+                        updated.max_step,
+                    )
+                {
+                    stop_recording(&mut app, &mut recorder, &err);
                 }
-                last_process_poll = Instant::now();
+                apply_refresh(
+                    &mut app,
+                    updated,
+                    Some(events_path),
+                    graph_filter.as_ref(),
+                    &mut graph_filter_checked,
+                );
+                handle_convergence(&mut app, Some(events_path), &bg_tx);
             }
+            if view_fingerprint(&app) != before {
+                redraw.mark();
+                backoff.activity(Instant::now());
+            }
+            last_refresh = Instant::now();
         }
 
-        if let Some(interval) = refresh_interval {
-            if last_refresh.elapsed() >= interval {
-                if let Some(events_path) = events_path {
-                    if let Ok(mut updated) = load_view_data(events_path) {
-                        if let Some(filter) = graph_filter.as_ref() {
-                            updated.scalars = filter_scalars(updated.scalars, filter);
-                        }
-                        let prev_events = app.total_events;
-                        let prev_step = app.max_step;
-                        let events_grew = updated.total_events > prev_events;
-                        let step_changed = updated.max_step != prev_step;
-                        let daemon_live_metrics_active =
-                            app.daemon_connected && app.live_logs_active;
-                        if daemon_live_metrics_active {
-                            // Keep daemon-fed metrics visible even when event-file refresh is empty.
-                            app.total_events = app.total_events.max(updated.total_events);
-                            app.max_step = app.max_step.max(updated.max_step);
-                        } else {
-                            app.replace_data(
-                                updated.scalars,
-                                updated.log_lines,
-                                updated.total_events,
-                                updated.max_step,
-                            );
-                        }
+        // Sizing runs walks their directories, so it stays off the event loop.
+        if !replaying
+            && !disk_usage_pending
+            && last_disk_usage.is_none_or(|at| at.elapsed() >= DISK_USAGE_REFRESH)
+        {
+            disk_usage_pending = true;
+            last_disk_usage = Some(Instant::now());
+            spawn_disk_usage(app.dashboard_root.clone(), bg_tx.clone());
+        }
 
-                        // Event-file refresh is also a live source (even when daemon is connected).
-                        if !app.live_logs_active && (events_grew || step_changed) {
-                            app.activate_live_logs();
-                            app.append_live_log(
-                                "[important] live mode: watching event stream updates".to_string(),
-                            );
-                            app.last_logged_step = prev_step;
-                        }
+        // Summarising runs parses every one of them, so it runs off the event loop too.
+        if app.active_tab == app::Tab::Dashboard
+            && !dashboard_pending
+            && last_dashboard_refresh.is_none_or(|at| at.elapsed() >= DASHBOARD_REFRESH)
+        {
+            dashboard_pending = true;
+            last_dashboard_refresh = Some(Instant::now());
+            spawn_dashboard_rows(app.dashboard_root.clone(), bg_tx.clone());
+        }
 
-                        if app.live_logs_active {
-                            if updated.total_events > prev_events {
-                                let delta = updated.total_events - prev_events;
-                                let suffix = if delta == 1 { "" } else { "s" };
-                                app.append_live_log(format!(
-                                    "[info] {} new event{} parsed (total {})",
-                                    delta, suffix, updated.total_events
-                                ));
-                            }
+        // Each refresh reparses every event file, so it runs off the event loop.
+        if app.active_tab == app::Tab::Distributions
+            && !histogram_pending
+            && last_histogram_refresh.is_none_or(|at| at.elapsed() >= HISTOGRAM_REFRESH)
+        {
+            histogram_pending = true;
+            last_histogram_refresh = Some(Instant::now());
+            spawn_histograms(app.events_path.clone(), bg_tx.clone());
+        }
 
-                            if !app.daemon_connected && updated.max_step < app.last_logged_step {
-                                app.append_live_log(format!(
-                                    "[info] step counter reset to {}",
-                                    updated.max_step
-                                ));
-                                app.last_logged_step = updated.max_step;
-                            } else if updated.max_step > app.last_logged_step {
-                                let delta = updated.max_step - app.last_logged_step;
-                                app.append_live_log(format!(
-                                    "[sucess] step {} completed (+{})",
-                                    updated.max_step, delta
-                                ));
-                                app.last_logged_step = updated.max_step;
-                            }
-                        }
-                    }
-                }
-                last_refresh = Instant::now();
-            }
+        // Every sibling run is reread, so that happens off the event loop.
+        if app.active_tab == app::Tab::Graphs
+            && !seed_band_pending
+            && last_seed_band_refresh.is_none_or(|at| at.elapsed() >= SEED_BAND_REFRESH)
+        {
+            seed_band_pending = true;
+            last_seed_band_refresh = Some(Instant::now());
+            spawn_seed_groups(app.events_path.clone(), bg_tx.clone());
         }
 
-        terminal.draw(|f| {
-            layout = ui::draw(f, &mut app);
-        })?;
+        let now = Instant::now();
+        redraw.mark_if(app.expire_toasts(now));
+        // The streaming reply's typing cursor animates.
+        redraw.mark_if(app.agent_thinking);
+        if redraw.should_draw(now) {
+            tracing::debug_span!("draw", tab = ?app.active_tab).in_scope(|| {
+                terminal.draw(|f| {
+                    layout = ui::draw(f, &mut app);
+                })
+            })?;
+            redraw.drawn(now);
+        }
+        if !app.pending_terminal_notifications.is_empty() {
+            let mut out = io::stdout();
+            for sequence in app.pending_terminal_notifications.drain(..) {
+                out.write_all(sequence.as_bytes())?;
+            }
+            out.flush()?;
+        }
 
         // Drain background messages
         while let Ok(msg) = bg_rx.try_recv() {
+            tracing::debug!(kind = msg.kind(), "background message");
+            // The daemon poll reports its connection state every 500ms; only a change shows.
+            redraw.mark_if(match &msg {
+                BgMessage::DaemonConnected(c) => *c != app.daemon_connected,
+                BgMessage::SeedGroups { path, groups } => {
+                    *path == app.events_path && *groups != app.seed_groups
+                }
+                BgMessage::Histograms { path, .. } => *path == app.events_path,
+                BgMessage::Processes(processes) => {
+                    processes.is_some() && app.active_tab == app::Tab::Processes
+                }
+                _ => true,
+            });
+            let before = view_fingerprint(&app);
+            if let Some(rec) = recorder.as_mut()
+                && let Err(err) = rec.message(&msg)
+            {
+                stop_recording(&mut app, &mut recorder, &err);
+            }
             match msg {
                 BgMessage::DaemonConnected(c) => {
                     let was_connected = app.daemon_connected;
@@ -2047,37 +7591,33 @@ fn run_app<B: Backend>(
                         "No daemon (optional)".to_string()
                     };
                     if was_connected != c && app.live_logs_active && (daemon_expected || c) {
-                        let line = if c {
-                            "[info] daemon connected"
+                        let (level, message) = if c {
+                            (LogLevel::Info, "daemon connected")
                         } else {
-                            "[error] daemon disconnected"
+                            (LogLevel::Error, "daemon disconnected")
                         };
-                        app.append_live_log(line);
+                        app.append_live_log(level, message);
                     }
                     if !c {
                         app.last_daemon_log_tail.clear();
                     } else if let Some(prompt) = startup_prompt.take() {
-                        app.agent_thinking = true;
                         app.chat_status = "Sending startup prompt...".to_string();
-                        let tx = bg_tx.clone();
-                        let sock = app.daemon_socket.clone();
-                        std::thread::spawn(move || {
-                            match socket_client::send_chat_message(&prompt, &sock) {
-                                Ok((plan, messages)) => {
-                                    let _ = tx.send(BgMessage::ChatSendResult { plan, messages });
-                                }
-                                Err(e) => {
-                                    let _ = tx.send(BgMessage::ChatSendError(e.to_string()));
-                                }
-                            }
-                        });
+                        spawn_chat_request(&mut app, &bg_tx, prompt);
                     }
                 }
                 BgMessage::ChatHistory(messages) => {
                     app.update_chat_messages(messages);
                 }
+                BgMessage::ChatStream(event) => match event {
+                    socket_client::ChatStreamEvent::Chunk(text) => {
+                        app.chat_stream.push_str(&text);
+                        app.chat_status = "Agent typing...".to_string();
+                    }
+                    socket_client::ChatStreamEvent::Status(text) => app.chat_status = text,
+                },
                 BgMessage::ChatSendResult { plan, messages } => {
                     app.agent_thinking = false;
+                    app.chat_stream.clear();
                     app.update_chat_messages(messages);
                     // Store pending refactor if agent proposed code changes (non-auto)
                     if plan.action == "refactor" && !plan.code_changes.is_empty() && !app.auto_mode
@@ -2085,31 +7625,118 @@ fn run_app<B: Backend>(
                         app.pending_refactor = Some(plan);
                         app.chat_status =
                             "Refactor proposed — press y to apply, n to reject".to_string();
+                        app.toast(
+                            ToastLevel::Info,
+                            "Refactor proposed — press y in chat to apply",
+                        );
                     } else {
                         app.chat_status = "Connected".to_string();
                     }
                 }
                 BgMessage::ChatSendError(err) => {
                     app.agent_thinking = false;
+                    app.chat_stream.clear();
                     app.chat_status = format!("Error: {}", err);
                 }
                 BgMessage::RunStateUpdate { auto_mode } => {
                     update_auto_mode(&mut app, auto_mode);
                 }
-                BgMessage::RefactorApplied { success, messages } => {
+                BgMessage::RefactorApplied {
+                    checkpoint,
+                    files,
+                    restarted,
+                } => {
                     app.agent_thinking = false;
                     app.pending_refactor = None;
-                    app.update_chat_messages(messages);
-                    app.chat_status = if success {
-                        "Refactor applied successfully".to_string()
-                    } else {
-                        "Refactor failed (rolled back)".to_string()
-                    };
+                    let id = checkpoint
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    app.chat_status = format!(
+                        "Refactor applied to {} file{} (snapshot {}; `og rollback` to undo){}",
+                        files,
+                        if files == 1 { "" } else { "s" },
+                        id,
+                        if restarted {
+                            ""
+                        } else {
+                            "; training not restarted"
+                        }
+                    );
+                    app.append_live_log(
+                        LogLevel::Success,
+                        format!(
+                            "agent refactor applied locally, snapshot {}",
+                            checkpoint.display()
+                        ),
+                    );
                 }
                 BgMessage::RefactorError(err) => {
                     app.agent_thinking = false;
                     app.chat_status = format!("Refactor error: {}", err);
                 }
+                BgMessage::ResumeStarted { step, checkpoint } => {
+                    app.chat_status = format!("Restarting training from step {step}");
+                    app.append_live_log(
+                        LogLevel::Important,
+                        format!(
+                            "restarting training from checkpoint at step {} ({})",
+                            step,
+                            checkpoint.display()
+                        ),
+                    );
+                    app.toast(
+                        ToastLevel::Success,
+                        format!("Restarting from checkpoint at step {step}"),
+                    );
+                }
+                BgMessage::ResumeError(err) => {
+                    app.chat_status = format!("Restart failed: {err}");
+                    app.toast(ToastLevel::Alert, "Restart from checkpoint failed");
+                }
+                BgMessage::TrainingStopped => {
+                    app.chat_status = "Training stopped early".to_string();
+                    app.append_live_log(LogLevel::Important, "training stopped early".to_string());
+                    app.toast(ToastLevel::Success, "Training stopped early");
+                }
+                BgMessage::StopError(err) => {
+                    app.chat_status = format!("Stop failed: {err}");
+                    app.append_live_log(LogLevel::Error, format!("failed to stop training: {err}"));
+                    app.toast(ToastLevel::Alert, "Stopping training failed");
+                }
+                BgMessage::DashboardRows { root, rows } => {
+                    dashboard_pending = false;
+                    // Rows of a project switched away from are dropped.
+                    if root == app.dashboard_root {
+                        app.set_dashboard_rows(rows);
+                    }
+                }
+                BgMessage::SeedGroups { path, groups } => {
+                    seed_band_pending = false;
+                    if path == app.events_path {
+                        app.seed_groups = groups;
+                    }
+                }
+                BgMessage::Histograms { path, histograms } => {
+                    histogram_pending = false;
+                    if path == app.events_path {
+                        match histograms {
+                            Ok(histograms) => app.set_histograms(histograms),
+                            Err(err) => app.histogram_error = Some(err),
+                        }
+                    }
+                }
+                BgMessage::Processes(processes) => {
+                    processes_pending = false;
+                    if let Some(processes) = processes {
+                        app.process_roots = training_roots(&app);
+                        app.update_processes(processes, unix_now_secs());
+                    }
+                }
+                BgMessage::DiskUsage { sizes, free_bytes } => {
+                    disk_usage_pending = false;
+                    app.apply_disk_usage(sizes, free_bytes);
+                }
                 BgMessage::LiveMetrics {
                     metrics,
                     logs,
@@ -2124,7 +7751,7 @@ fn run_app<B: Backend>(
                     let auto_mode_changed = update_auto_mode(&mut app, auto_mode);
                     if just_activated && !auto_mode_changed {
                         let state = if auto_mode { "enabled" } else { "disabled" };
-                        app.append_live_log(format!("[important] auto mode {}", state));
+                        app.append_live_log(LogLevel::Important, format!("auto mode {}", state));
                     }
 
                     if current_step < app.last_logged_step {
@@ -2134,20 +7761,29 @@ fn run_app<B: Backend>(
                         app.selected_metric = 0;
                         app.focused_metric = None;
                         app.metrics_scroll = 0;
-                        app.append_live_log(format!(
-                            "[info] step counter reset to {}",
-                            current_step
-                        ));
+                        app.append_live_log(
+                            LogLevel::Info,
+                            format!("step counter reset to {}", current_step),
+                        );
+                        if let Some(events_path) = events_path.as_deref() {
+                            let from_step = app.last_logged_step;
+                            record_restart_annotation(
+                                &mut app,
+                                events_path,
+                                from_step,
+                                current_step,
+                            );
+                        }
                         app.last_logged_step = current_step;
                         app.max_step = current_step;
                     }
 
                     // Merge daemon metrics into TUI scalars
                     for (metric, values) in &metrics {
-                        if let Some(filter) = graph_filter.as_ref() {
-                            if !metric_matches_filter(metric, filter) {
-                                continue;
-                            }
+                        if let Some(filter) = graph_filter.as_ref()
+                            && !metric_matches_filter(metric, filter)
+                        {
+                            continue;
                         }
                         if let Some(arr) = values.as_array() {
                             let total = arr.len() as i64;
@@ -2173,13 +7809,18 @@ fn run_app<B: Backend>(
                     }
                     // Update tags list
                     app.tags = app.scalars.keys().cloned().collect();
+                    app.scan_anomalies();
+                    handle_convergence(&mut app, events_path.as_deref(), &bg_tx);
 
                     // Merge daemon logs using overlap to handle tail window shifts.
                     if !logs.is_empty() {
                         let overlap = tail_overlap(&app.last_daemon_log_tail, &logs);
                         for line in logs.iter().skip(overlap) {
-                            if let Some(formatted) = normalize_live_log_line(line) {
-                                app.append_live_log(formatted);
+                            if let Some((level, message)) = run_exit_toast(line) {
+                                app.toast(level, message);
+                            }
+                            if let Some(entry) = LogEntry::classify(line, LogSource::Daemon) {
+                                app.push_log(entry);
                             }
                         }
                         app.last_daemon_log_tail = logs;
@@ -2190,27 +7831,67 @@ fn run_app<B: Backend>(
                         app.seen_alert_count = 0;
                     }
                     for alert in alerts.iter().skip(app.seen_alert_count) {
-                        app.append_live_log(format_alert_log_line(alert));
+                        app.append_live_log(LogLevel::Important, format_alert_log_line(alert));
+                        app.toast(
+                            ToastLevel::Alert,
+                            format!("{}: {}", alert.metric, alert.message),
+                        );
                     }
                     app.seen_alert_count = alerts.len();
 
                     if current_step > app.last_logged_step {
                         let delta = current_step - app.last_logged_step;
-                        app.append_live_log(format!(
-                            "[sucess] step {} completed (+{})",
-                            current_step, delta
-                        ));
+                        app.append_live_log(
+                            LogLevel::Success,
+                            format!("step {} completed (+{})", current_step, delta),
+                        );
                         app.last_logged_step = current_step;
                     }
                     if current_step > app.max_step {
                         app.max_step = current_step;
                     }
                 }
+                BgMessage::ReplayRefresh {
+                    scalars,
+                    log_lines,
+                    total_events,
+                    max_step,
+                } => {
+                    let sys_strip = sys_strip::SysStrip::read(&scalars, &[]);
+                    let epoch_scale =
+                        epoch::EpochScale::for_run(&scalars, &run_meta::RunMeta::default());
+                    let updated = ViewData {
+                        scalars,
+                        log_lines,
+                        total_events,
+                        max_step,
+                        ranks: Vec::new(),
+                        rank_progress: BTreeMap::new(),
+                        wall_secs: None,
+                        gaps: Vec::new(),
+                        sys_strip,
+                        epoch_scale,
+                    };
+                    apply_refresh(
+                        &mut app,
+                        updated,
+                        None,
+                        graph_filter.as_ref(),
+                        &mut graph_filter_checked,
+                    );
+                }
+                BgMessage::ReplayFinished => {
+                    app.chat_status = "Replay finished".to_string();
+                    app.toast(ToastLevel::Info, "Replay finished");
+                }
+            }
+            if view_fingerprint(&app) != before {
+                backoff.activity(Instant::now());
             }
         }
 
         // Periodic daemon poll
-        if last_poll.elapsed() >= poll_interval {
+        if !replaying && last_poll.elapsed() >= backoff.interval(poll_interval, Instant::now()) {
             last_poll = Instant::now();
             let tx = bg_tx.clone();
             let sock = app.daemon_socket.clone();
@@ -2219,10 +7900,8 @@ fn run_app<B: Backend>(
                 let connected = socket_client::ping(&sock).is_ok();
                 let _ = tx.send(BgMessage::DaemonConnected(connected));
                 if connected {
-                    if !thinking {
-                        if let Ok(history) = socket_client::get_chat_history(&sock) {
-                            let _ = tx.send(BgMessage::ChatHistory(history));
-                        }
+                    if !thinking && let Ok(history) = socket_client::get_chat_history(&sock) {
+                        let _ = tx.send(BgMessage::ChatHistory(history));
                     }
                     if let Ok(rs) = socket_client::get_run_state(&sock) {
                         let _ = tx.send(BgMessage::RunStateUpdate {
@@ -2241,15 +7920,19 @@ fn run_app<B: Backend>(
         }
 
         // Poll for events with timeout so we can process bg messages
-        if !event::poll(tick_rate)? {
+        if !event::poll(redraw.timeout(Instant::now(), tick_rate))? {
             continue;
         }
 
-        match event::read()? {
+        let input = event::read()?;
+        redraw.mark();
+        backoff.activity(Instant::now());
+        match input {
             Event::Key(key) => {
                 if key.kind != KeyEventKind::Press {
                     continue;
                 }
+                tracing::trace!(code = ?key.code, modifiers = ?key.modifiers, "key");
 
                 if key.code == KeyCode::F(6) {
                     let enable_copy_mode = !app.copy_mode;
@@ -2261,6 +7944,18 @@ fn run_app<B: Backend>(
                     continue;
                 }
 
+                // Quitting would stop training: q, y or Ctrl-C confirms, anything else stays
+                if app.quit_pending {
+                    app.quit_pending = false;
+                    let ctrl_c = key.code == KeyCode::Char('c')
+                        && key.modifiers.contains(KeyModifiers::CONTROL);
+                    if ctrl_c || matches!(key.code, KeyCode::Char('q' | 'y' | 'Y')) {
+                        app.should_quit = true;
+                        return Ok(());
+                    }
+                    continue;
+                }
+
                 // Help modal intercepts keys
                 if app.show_help {
                     match key.code {
@@ -2270,6 +7965,181 @@ fn run_app<B: Backend>(
                     continue;
                 }
 
+                // Note input intercepts all keys while open
+                if let Some(input) = app.note_input.as_mut() {
+                    match key.code {
+                        KeyCode::Esc => app.note_input = None,
+                        KeyCode::Enter => {
+                            let text = app.note_input.take().unwrap_or_default();
+                            if !text.trim().is_empty() {
+                                let step = Some(app.max_step);
+                                app.chat_status =
+                                    match append_run_note(&app.events_path, &text, step) {
+                                        Ok(_) => "Note saved to run journal".to_string(),
+                                        Err(err) => format!("Note not saved: {}", err),
+                                    };
+                            }
+                        }
+                        KeyCode::Backspace => {
+                            input.pop();
+                        }
+                        KeyCode::Char(c) => input.push(c),
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                // Command line intercepts all keys while open
+                if let Some(input) = app.command_input.as_mut() {
+                    match key.code {
+                        KeyCode::Esc => app.command_input = None,
+                        KeyCode::Backspace if input.is_empty() => app.command_input = None,
+                        KeyCode::Backspace => {
+                            input.pop();
+                        }
+                        KeyCode::Enter => {
+                            let line = app.command_input.take().unwrap_or_default();
+                            if line.trim().is_empty() {
+                                continue;
+                            }
+                            if let Err(err) = run_command_line(
+                                &mut app,
+                                &line,
+                                events_path.as_deref(),
+                                &mut graph_filter,
+                                &bg_tx,
+                            ) {
+                                app.chat_status = format!("Command error: {err:#}");
+                            }
+                            if app.should_quit {
+                                return Ok(());
+                            }
+                        }
+                        KeyCode::Char(c) => input.push(c),
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                // Checkpoints panel intercepts all keys while open
+                if app.checkpoint_panel.is_some() {
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Char('K') => app.checkpoint_panel = None,
+                        KeyCode::Char('j') | KeyCode::Down => app.move_checkpoint_selection(1),
+                        KeyCode::Char('k') | KeyCode::Up => app.move_checkpoint_selection(-1),
+                        KeyCode::Enter => resume_from_selected_checkpoint(&mut app, &bg_tx),
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                // Learning-rate panel closes on Esc or L
+                if app.lr_panel {
+                    if matches!(key.code, KeyCode::Esc | KeyCode::Char('L')) {
+                        app.lr_panel = false;
+                    }
+                    continue;
+                }
+
+                // Tables panel intercepts all keys while open
+                if app.table_panel.is_some() {
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Char('T') => app.table_panel = None,
+                        KeyCode::Char('j')
+                        | KeyCode::Down
+                        | KeyCode::Char('l')
+                        | KeyCode::Right => app.move_table_selection(1),
+                        KeyCode::Char('k') | KeyCode::Up | KeyCode::Char('h') | KeyCode::Left => {
+                            app.move_table_selection(-1)
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                // Project picker intercepts all keys while open
+                if app.project_panel.is_some() {
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Char('P') => app.project_panel = None,
+                        KeyCode::Char('j') | KeyCode::Down => app.move_project_selection(1),
+                        KeyCode::Char('k') | KeyCode::Up => app.move_project_selection(-1),
+                        KeyCode::Enter => {
+                            let Some(project) = app.selected_project().cloned() else {
+                                app.project_panel = None;
+                                continue;
+                            };
+                            let path = PathBuf::from(&project.path);
+                            match open_project(&mut app, &path, graph_filter.as_ref()) {
+                                Ok(()) => {
+                                    events_path = Some(path);
+                                    graph_filter_checked = !app.scalars.is_empty();
+                                    last_dashboard_refresh = None;
+                                    last_histogram_refresh = None;
+                                    last_seed_band_refresh = None;
+                                    last_disk_usage = None;
+                                    app.chat_status = format!("Showing project {}", project.name);
+                                }
+                                Err(err) => {
+                                    app.chat_status =
+                                        format!("Project {} not loaded: {err:#}", project.name)
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                let typing_in_chat = app.chat_input_focused && app.active_tab == app::Tab::Chat;
+                if key.code == KeyCode::Char('P') && !typing_in_chat {
+                    if let Some(reason) =
+                        project_switch_blocker(&app, replaying, recorder.is_some())
+                    {
+                        app.chat_status = format!("Can't switch projects while {reason}");
+                    } else {
+                        match list_switchable_projects(&projects_root(&app.dashboard_root)) {
+                            Ok(projects) => app.open_projects(projects),
+                            Err(err) => app.chat_status = format!("Projects not listed: {err:#}"),
+                        }
+                    }
+                    continue;
+                }
+                if key.code == KeyCode::Char('L') && !typing_in_chat {
+                    app.lr_panel = true;
+                    continue;
+                }
+                if key.code == KeyCode::Char('T') && !typing_in_chat {
+                    match table::load(&app.events_path) {
+                        Ok(tables) => app.open_tables(table::latest(tables)),
+                        Err(err) => app.chat_status = format!("Tables not loaded: {err:#}"),
+                    }
+                    continue;
+                }
+                if key.code == KeyCode::Char(':') && !typing_in_chat {
+                    app.command_input = Some(String::new());
+                    continue;
+                }
+                if key.code == KeyCode::Char('N') && !typing_in_chat {
+                    app.open_note_input();
+                    continue;
+                }
+                if key.code == KeyCode::Char('K') && !typing_in_chat {
+                    match checkpoints::load(&app.events_path) {
+                        Ok(mut list) => {
+                            checkpoints::measure(&mut list);
+                            app.open_checkpoints(list);
+                        }
+                        Err(err) => app.chat_status = format!("Checkpoints not loaded: {err:#}"),
+                    }
+                    continue;
+                }
+
+                if key.code == KeyCode::Char('b') && !typing_in_chat && !app.seed_groups.is_empty()
+                {
+                    app.band_kind = app.band_kind.toggle();
+                    continue;
+                }
+
                 // Focused metric view intercepts Esc
                 if app.focused_metric.is_some() {
                     match key.code {
@@ -2278,19 +8148,69 @@ fn run_app<B: Backend>(
                             continue;
                         }
                         KeyCode::Char('q') => {
-                            app.should_quit = true;
-                            return Ok(());
+                            app.request_quit();
+                            if app.should_quit {
+                                return Ok(());
+                            }
+                        }
+                        KeyCode::Char(c @ ('c' | 'C')) => {
+                            if let Some(index) = app.focused_metric {
+                                copy_metric(&mut app, index, c == 'C');
+                            }
+                            continue;
+                        }
+                        KeyCode::Char('[') => {
+                            app.move_marker_selection(-1);
+                            continue;
+                        }
+                        KeyCode::Char(']') => {
+                            app.move_marker_selection(1);
+                            continue;
+                        }
+                        KeyCode::Char('e') => {
+                            app.toggle_epoch_axis();
+                            continue;
+                        }
+                        KeyCode::Char(c @ ('o' | '+' | '=' | '-' | '<' | '>' | '0'))
+                            if app.run_diff.is_some() =>
+                        {
+                            app.run_diff_key(c);
+                            continue;
                         }
                         _ => continue,
                     }
                 }
 
+                // Visual log selection intercepts movement and yank
+                if app.log_selection.is_some() && app.active_tab == app::Tab::Logs {
+                    match key.code {
+                        KeyCode::Char('j') | KeyCode::Down => app.move_log_cursor(1),
+                        KeyCode::Char('k') | KeyCode::Up => app.move_log_cursor(-1),
+                        KeyCode::PageDown => app.move_log_cursor(app.logs_viewport_rows as isize),
+                        KeyCode::PageUp => app.move_log_cursor(-(app.logs_viewport_rows as isize)),
+                        KeyCode::Char('g') | KeyCode::Home => app.move_log_cursor(isize::MIN),
+                        KeyCode::Char('G') | KeyCode::End => app.move_log_cursor(isize::MAX),
+                        KeyCode::Char('y') | KeyCode::Enter => copy_log_lines(&mut app),
+                        KeyCode::Esc | KeyCode::Char('v') => app.log_selection = None,
+                        KeyCode::Char('q') => {
+                            app.request_quit();
+                            if app.should_quit {
+                                return Ok(());
+                            }
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+
                 // Chat input mode intercepts all keys
                 if app.chat_input_focused && app.active_tab == app::Tab::Chat {
                     match key.code {
                         KeyCode::Esc => {
                             app.chat_input_focused = false;
                         }
+                        KeyCode::Tab => app.focus_next_pane(),
+                        KeyCode::BackTab => app.focus_prev_pane(),
                         KeyCode::Enter => {
                             let content = app.chat_input_take();
                             if content.trim().is_empty() {
@@ -2314,21 +8234,8 @@ fn run_app<B: Backend>(
                             }
 
                             if app.daemon_connected {
-                                app.agent_thinking = true;
                                 app.chat_status = "Sending...".to_string();
-                                let tx = bg_tx.clone();
-                                let sock = app.daemon_socket.clone();
-                                std::thread::spawn(move || match socket_client::send_chat_message(
-                                    &content, &sock,
-                                ) {
-                                    Ok((plan, messages)) => {
-                                        let _ =
-                                            tx.send(BgMessage::ChatSendResult { plan, messages });
-                                    }
-                                    Err(e) => {
-                                        let _ = tx.send(BgMessage::ChatSendError(e.to_string()));
-                                    }
-                                });
+                                spawn_chat_request(&mut app, &bg_tx, content);
                             } else {
                                 app.chat_status =
                                     "Daemon not connected (message not sent)".to_string();
@@ -2347,28 +8254,109 @@ fn run_app<B: Backend>(
 
                 match key.code {
                     KeyCode::Char('q') => {
-                        app.should_quit = true;
-                        return Ok(());
+                        app.request_quit();
+                        if app.should_quit {
+                            return Ok(());
+                        }
                     }
                     KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                        app.should_quit = true;
-                        return Ok(());
+                        app.request_quit();
+                        if app.should_quit {
+                            return Ok(());
+                        }
                     }
                     KeyCode::Char('?') => app.toggle_help(),
-                    KeyCode::Tab => app.cycle_tab(),
-                    KeyCode::BackTab => app.cycle_tab(),
+                    KeyCode::Char(c @ ('c' | 'C')) if app.active_tab == app::Tab::Graphs => {
+                        let index = app.selected_metric;
+                        copy_metric(&mut app, index, c == 'C');
+                    }
+                    KeyCode::Char('v') if app.active_tab == app::Tab::Logs => {
+                        app.start_log_selection();
+                    }
+                    KeyCode::Char('y') if app.active_tab == app::Tab::Logs => {
+                        copy_log_lines(&mut app)
+                    }
+                    KeyCode::Char('f') if app.active_tab == app::Tab::Logs => {
+                        app.cycle_log_filter()
+                    }
+                    KeyCode::Char('f') if app.active_tab == app::Tab::Processes => {
+                        app.toggle_process_filter()
+                    }
+                    KeyCode::Char('r') if !app.ranks.is_empty() => {
+                        app.cycle_rank();
+                        if let Some(events_path) = events_path.as_deref() {
+                            reload_view(&mut app, events_path, graph_filter.as_ref());
+                        }
+                        app.chat_status = match app.selected_rank {
+                            Some(rank) => format!("Showing rank {}", rank),
+                            None => "Showing all ranks (merged)".to_string(),
+                        };
+                    }
+                    KeyCode::Char('g') if graph_presets.is_empty() => {
+                        app.chat_status = format!(
+                            "No graph presets (add preset.<name> = [...] to {})",
+                            presets::config_path()
+                                .map(|path| path.display().to_string())
+                                .unwrap_or_else(|| presets::CONFIG_ENV_VAR.to_string())
+                        );
+                    }
+                    KeyCode::Char('g') => {
+                        let next = next_graph_preset(&graph_presets, app.graph_preset.as_deref());
+                        graph_filter = next.map(|preset| GraphFilter {
+                            strict: app.graph_strict,
+                            ..preset_filter(preset)
+                        });
+                        app.graph_preset = next.map(|preset| preset.name.clone());
+                        let misses = apply_graph_filter(
+                            &mut app,
+                            events_path.as_deref(),
+                            graph_filter.as_ref(),
+                        );
+                        app.chat_status = match (&app.graph_preset, misses.first()) {
+                            (Some(name), Some(miss)) => format!("Graph preset {name}: {miss}"),
+                            (Some(name), None) => format!("Graph preset: {name}"),
+                            (None, _) => "Showing all metrics".to_string(),
+                        };
+                    }
+                    KeyCode::Tab => app.focus_next_pane(),
+                    KeyCode::BackTab => app.focus_prev_pane(),
                     KeyCode::Char('j') | KeyCode::Down => match app.active_tab {
+                        app::Tab::Graphs if app.focused_pane() == app::Pane::Side => {
+                            app.scroll_stats_down()
+                        }
                         app::Tab::Graphs => app.scroll_metrics_down(),
+                        app::Tab::Dashboard => app.scroll_dashboard_down(),
                         app::Tab::Logs => app.scroll_logs_down(),
+                        app::Tab::Events => app.scroll_events_down(),
+                        app::Tab::Distributions => app.move_histogram_step(1),
                         app::Tab::Processes => app.scroll_processes_down(),
                         app::Tab::Chat => app.scroll_chat_down(),
                     },
                     KeyCode::Char('k') | KeyCode::Up => match app.active_tab {
+                        app::Tab::Graphs if app.focused_pane() == app::Pane::Side => {
+                            app.scroll_stats_up()
+                        }
                         app::Tab::Graphs => app.scroll_metrics_up(),
+                        app::Tab::Dashboard => app.scroll_dashboard_up(),
                         app::Tab::Logs => app.scroll_logs_up(),
+                        app::Tab::Events => app.scroll_events_up(),
+                        app::Tab::Distributions => app.move_histogram_step(-1),
                         app::Tab::Processes => app.scroll_processes_up(),
                         app::Tab::Chat => app.scroll_chat_up(),
                     },
+                    KeyCode::Char('t') if app.active_tab == app::Tab::Events => {
+                        app.cycle_events_filter();
+                    }
+                    KeyCode::Char('l') | KeyCode::Right
+                        if app.active_tab == app::Tab::Distributions =>
+                    {
+                        app.move_histogram_tag(1)
+                    }
+                    KeyCode::Char('h') | KeyCode::Left
+                        if app.active_tab == app::Tab::Distributions =>
+                    {
+                        app.move_histogram_tag(-1)
+                    }
                     KeyCode::Char('l') | KeyCode::Right => app.next_metric(),
                     KeyCode::Char('h') | KeyCode::Left => app.prev_metric(),
                     KeyCode::Char('i') if app.active_tab == app::Tab::Chat => {
@@ -2382,14 +8370,26 @@ fn run_app<B: Backend>(
                             app.chat_status = "Applying refactor...".to_string();
                             let tx = bg_tx.clone();
                             let sock = app.daemon_socket.clone();
+                            let root = app.codebase_root.clone();
                             std::thread::spawn(move || {
-                                match socket_client::apply_refactor(&plan, &sock) {
-                                    Ok((success, messages)) => {
-                                        let _ = tx
-                                            .send(BgMessage::RefactorApplied { success, messages });
+                                let checkpoint_dir = root.join(".og_checkpoints");
+                                match patch::apply(
+                                    &root,
+                                    &checkpoint_dir,
+                                    &plan.code_changes,
+                                    false,
+                                ) {
+                                    Ok(applied) => {
+                                        let restarted =
+                                            socket_client::start_training(&sock).is_ok();
+                                        let _ = tx.send(BgMessage::RefactorApplied {
+                                            checkpoint: applied.checkpoint.unwrap_or_default(),
+                                            files: applied.changes.len(),
+                                            restarted,
+                                        });
                                     }
                                     Err(e) => {
-                                        let _ = tx.send(BgMessage::RefactorError(e.to_string()));
+                                        let _ = tx.send(BgMessage::RefactorError(format!("{e:#}")));
                                     }
                                 }
                             });
@@ -2430,10 +8430,9 @@ fn run_app<B: Backend>(
                                 && x < tab_rect.x + tab_rect.width
                                 && y >= tab_rect.y
                                 && y < tab_rect.y + tab_rect.height
+                                && let Some(&tab) = app::Tab::ALL.get(i)
                             {
-                                if let Some(&tab) = app::Tab::ALL.get(i) {
-                                    app.active_tab = tab;
-                                }
+                                app.active_tab = tab;
                             }
                         }
 
@@ -2447,6 +8446,7 @@ fn run_app<B: Backend>(
                                 && y < card_rect.y + card_rect.height
                             {
                                 let actual_idx = scroll_offset + vis_i;
+                                app.pane_focus = app::Pane::Main;
                                 if app.selected_metric == actual_idx {
                                     // Already selected → focus (enlarge)
                                     app.focus_metric(actual_idx);
@@ -2458,19 +8458,31 @@ fn run_app<B: Backend>(
                     }
                     MouseEventKind::ScrollDown => match app.active_tab {
                         app::Tab::Graphs => app.scroll_metrics_down(),
+                        app::Tab::Dashboard => app.scroll_dashboard_down(),
                         app::Tab::Logs => app.scroll_logs_down(),
+                        app::Tab::Events => app.scroll_events_down(),
+                        app::Tab::Distributions => app.move_histogram_step(1),
                         app::Tab::Processes => app.scroll_processes_down(),
                         app::Tab::Chat => app.scroll_chat_down(),
                     },
                     MouseEventKind::ScrollUp => match app.active_tab {
                         app::Tab::Graphs => app.scroll_metrics_up(),
+                        app::Tab::Dashboard => app.scroll_dashboard_up(),
                         app::Tab::Logs => app.scroll_logs_up(),
+                        app::Tab::Events => app.scroll_events_up(),
+                        app::Tab::Distributions => app.move_histogram_step(-1),
                         app::Tab::Processes => app.scroll_processes_up(),
                         app::Tab::Chat => app.scroll_chat_up(),
                     },
                     _ => {}
                 }
             }
+            Event::Resize(width, height) => {
+                tracing::debug!(width, height, "resize");
+                // Hit-test regions describe the old layout until the next draw.
+                layout = ui::LayoutRegions::default();
+                redraw.force();
+            }
             _ => {}
         }
     }
@@ -2479,13 +8491,20 @@ fn run_app<B: Backend>(
 #[cfg(test)]
 mod tests {
     use super::{
-        AutoModeArg, Cli, ListArgs, ListSubcommand, OgCommand, RuntimeArg,
-        handle_in_app_og_command, normalize_live_log_line, parse_bang_og_cli, parse_elapsed_secs,
-        parse_graph_filter, parse_graph_labels, parse_process_line, resolve_live_run_path,
-        tail_overlap,
+        AutoModeArg, BgMessage, Cli, DescribeArgs, ExportAudioArgs, GetRunArgs, GetTableArgs,
+        LeaderboardArgs, ListArgs, ListMetricsArgs, ListSubcommand, OgCommand, Replay, RuntimeArg,
+        ViewData, apply_refresh, execute_describe, execute_export_audio, execute_get_run,
+        execute_get_table, execute_leaderboard, execute_list_metrics, filter_scalars,
+        graph_filter_misses, handle_in_app_og_command, metric_matches_filter, next_graph_preset,
+        parse_bang_og_cli, parse_bounds, parse_elapsed_secs, parse_graph_filter,
+        parse_graph_labels, parse_process_line, presets, projects_root, rescale_to_limits,
+        resolve_graph_filter, resolve_live_run_path, run_exit_toast, session, spawn_replay,
+        tail_overlap, write_completions, write_man_page, write_man_pages,
     };
-    use crate::app::App;
+    use crate::app::{App, ToastLevel};
+    use crate::{metrics_jsonl, tfrecord};
     use clap::Parser;
+    use og_core::throughput;
     use std::collections::BTreeMap;
     use std::fs;
     use std::path::PathBuf;
@@ -2510,39 +8529,19 @@ mod tests {
     }
 
     #[test]
-    fn normalize_strips_known_prefixes_and_keeps_info() {
-        let line = "[daemon] [info] training warmup started";
-        assert_eq!(
-            normalize_live_log_line(line),
-            Some("[info] training warmup started".to_string())
-        );
-    }
-
-    #[test]
-    fn normalize_marks_restart_as_success() {
-        let line = "[system] training restarted (pid=1234)";
-        assert_eq!(
-            normalize_live_log_line(line),
-            Some("[sucess] training restarted (pid=1234)".to_string())
-        );
-    }
-
-    #[test]
-    fn normalize_marks_traceback_as_error() {
-        let line = "Traceback (most recent call last):";
+    fn training_exit_lines_become_toasts() {
         assert_eq!(
-            normalize_live_log_line(line),
-            Some("[error] Traceback (most recent call last):".to_string())
+            run_exit_toast("[system] training exited with code 0"),
+            Some((ToastLevel::Success, "training finished".to_string()))
         );
-    }
-
-    #[test]
-    fn normalize_marks_alert_as_important() {
-        let line = "[alert] val/loss crossed threshold";
         assert_eq!(
-            normalize_live_log_line(line),
-            Some("[important] val/loss crossed threshold".to_string())
+            run_exit_toast("[system] training exited with code 137"),
+            Some((
+                ToastLevel::Alert,
+                "training exited with code 137".to_string()
+            ))
         );
+        assert_eq!(run_exit_toast("[system] training restarted (pid=1)"), None);
     }
 
     #[test]
@@ -2559,6 +8558,22 @@ mod tests {
         assert_eq!(parsed.command, "python train.py --epochs 10");
     }
 
+    #[test]
+    fn rescale_to_limits_reads_ps_percentages_against_the_container() {
+        let line = "1234 1 R 00:12 150.0 10.0 python train.py";
+        let mut processes = vec![parse_process_line(line).expect("process row")];
+        let limits = crate::cgroup::Limits {
+            cpus: Some(2.0),
+            memory_bytes: Some(8 << 30),
+        };
+        rescale_to_limits(&mut processes, limits, Some(32.0 * 1024.0));
+        assert!((processes[0].cpu_pct - 75.0).abs() < 0.001);
+        assert!((processes[0].mem_pct - 40.0).abs() < 0.001);
+
+        rescale_to_limits(&mut processes, crate::cgroup::Limits::default(), None);
+        assert!((processes[0].cpu_pct - 75.0).abs() < 0.001, "no limits");
+    }
+
     #[test]
     fn parse_process_line_rejects_incomplete_row() {
         let line = "1234 1 R 00:12";
@@ -2640,6 +8655,57 @@ mod tests {
         assert_eq!(parsed.sys, vec!["gpu", "vram"]);
     }
 
+    #[test]
+    fn strict_graph_filters_do_not_fall_back_to_all_metrics() {
+        assert!(parse_graph_filter(r#"{"metrics":"", "sys":"gpu"}"#).is_err());
+        assert!(parse_graph_filter(r#"{"metrics":["loss"],"sis":"gpu"}"#).is_err());
+
+        let scalars: BTreeMap<String, Vec<(f64, f64)>> = ["train/loss", "sys/gpu_util"]
+            .into_iter()
+            .map(|tag| (tag.to_string(), vec![(1.0, 1.0)]))
+            .collect();
+        let mut filter = parse_graph_filter(r#"{"metrics":"acc"}"#).expect("parse");
+        let misses = graph_filter_misses(&filter, &scalars);
+        assert_eq!(misses.len(), 1);
+        assert_eq!(misses[0].selector, "acc");
+        assert_eq!(filter_scalars(scalars.clone(), &filter).len(), 2);
+        filter.strict = true;
+        assert!(filter_scalars(scalars, &filter).is_empty());
+    }
+
+    #[test]
+    fn graph_presets_resolve_and_cycle_back_to_all_metrics() {
+        let graph_presets = vec![
+            presets::GraphPreset {
+                name: "losses".to_string(),
+                patterns: vec!["loss".to_string()],
+            },
+            presets::GraphPreset {
+                name: "sys".to_string(),
+                patterns: vec!["sys/*".to_string()],
+            },
+        ];
+        let filter = resolve_graph_filter("preset:sys", &graph_presets).expect("preset");
+        assert_eq!(filter.metrics, vec!["sys/*"]);
+        assert!(metric_matches_filter("sys/CPU_pct", &filter));
+        assert!(!metric_matches_filter("train/sys/x", &filter));
+        assert!(resolve_graph_filter("preset:gpu", &graph_presets).is_err());
+        assert_eq!(
+            resolve_graph_filter(r#"{"metrics":"loss"}"#, &graph_presets)
+                .expect("json")
+                .metrics,
+            vec!["loss"]
+        );
+
+        let names: Vec<Option<&str>> = [None, Some("losses"), Some("sys"), Some("gone")]
+            .into_iter()
+            .map(|current| {
+                next_graph_preset(&graph_presets, current).map(|preset| preset.name.as_str())
+            })
+            .collect();
+        assert_eq!(names, vec![Some("losses"), Some("sys"), None, None]);
+    }
+
     #[test]
     fn parse_graph_labels_accepts_json_mapping() {
         let raw = r#"{"train/loss":"Loss","train/accuracy":"Acc"}"#;
@@ -2686,14 +8752,251 @@ mod tests {
         fs::create_dir_all(&old_run).expect("create old run");
         fs::write(old_run.join("events.out.tfevents.1"), b"x").expect("write marker");
 
-        let resolved = resolve_live_run_path(&runs).expect("resolve run path");
+        let resolved =
+            resolve_live_run_path(&runs, "{adjective}-{noun}-{counter}").expect("resolve run path");
         assert!(resolved.starts_with(&runs));
         assert_ne!(resolved, runs);
         assert!(resolved.exists());
+        let name = resolved
+            .file_name()
+            .and_then(|n| n.to_str())
+            .expect("run name");
+        assert_eq!(name.split('-').count(), 3);
+        assert!(name.ends_with("-2"));
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn projects_root_climbs_from_a_run_or_project_to_the_runs_root() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("ogtui-projects-root-{nonce}"));
+        let run = root.join("alpha").join("r1");
+        fs::create_dir_all(&run).expect("create run");
+        fs::write(run.join("events.out.tfevents.1"), b"x").expect("write marker");
+
+        assert_eq!(projects_root(&run), root);
+        assert_eq!(projects_root(&root.join("alpha")), root);
+        assert_eq!(projects_root(&root), root);
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn get_run_reports_throughput_derived_from_token_counter() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let run = std::env::temp_dir().join(format!("ogtui-throughput-{nonce}"));
+        fs::create_dir_all(&run).expect("create run");
+        let lines: String = (0..3)
+            .map(|step| {
+                metrics_jsonl::format_line(
+                    step,
+                    100.0 + 2.0 * step as f64,
+                    &[
+                        ("train/tokens".to_string(), 1000.0 * step as f64),
+                        ("train/loss".to_string(), 1.0),
+                    ],
+                )
+            })
+            .collect();
+        fs::write(run.join(metrics_jsonl::FILE_NAME), lines).expect("write metrics");
+
+        let output = execute_get_run(GetRunArgs {
+            path: run.clone(),
+            project: None,
+            run: run.display().to_string(),
+            env: false,
+            rank: None,
+        })
+        .expect("get run");
+        assert_eq!(
+            output.data["latest_metrics"][throughput::TAG],
+            serde_json::json!({"step": 2.0, "value": 500.0})
+        );
+        assert!(output.text.contains("derived/throughput"));
+        fs::remove_dir_all(&run).ok();
+    }
 
+    #[test]
+    fn export_audio_writes_clips_and_list_metrics_counts_them() {
+        use prost::Message;
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("ogtui-export-audio-{nonce}"));
+        let run = root.join("tts");
+        fs::create_dir_all(&run).expect("create run");
+        let value = |tag: &str, audio: Option<&[u8]>, scalar: Option<f32>| tfrecord::SummaryValue {
+            tag: tag.to_string(),
+            simple_value: scalar,
+            histo: None,
+            audio: audio.map(|clip| tfrecord::Audio {
+                sample_rate: 16000.0,
+                encoded_audio_string: clip.to_vec(),
+                content_type: "audio/wav".to_string(),
+            }),
+            tensor: None,
+            metadata: None,
+        };
+        let bytes: Vec<u8> = (1..=2)
+            .flat_map(|step| {
+                tfrecord::encode_record(
+                    &tfrecord::Event {
+                        wall_time: step as f64,
+                        step,
+                        file_version: None,
+                        summary: Some(tfrecord::Summary {
+                            value: vec![
+                                value(
+                                    "samples/audio",
+                                    Some(format!("RIFF{step}").as_bytes()),
+                                    None,
+                                ),
+                                value("train/loss", None, Some(1.0)),
+                            ],
+                        }),
+                    }
+                    .encode_to_vec(),
+                )
+            })
+            .collect();
+        fs::write(run.join("events.out.tfevents.1.host"), bytes).expect("write events");
+
+        let list = execute_list_metrics(
+            ListMetricsArgs {
+                path: root.clone(),
+                project: None,
+                run: "tts".to_string(),
+                kinds: true,
+            },
+            false,
+        )
+        .expect("list metrics");
+        assert!(list.text.contains("- samples/audio  audio   2"));
+        assert!(list.text.contains("- train/loss     scalar  2"));
+        assert_eq!(list.data["kinds"][0]["entries"], 2);
+
+        let out = root.join("wavs");
+        let export = execute_export_audio(ExportAudioArgs {
+            path: root.clone(),
+            project: None,
+            run: "tts".to_string(),
+            tag: Some("samples/audio".to_string()),
+            out: out.clone(),
+        })
+        .expect("export audio");
+        assert_eq!(export.data["count"], 2);
+        assert_eq!(
+            fs::read(out.join("samples_audio-step2-0.wav")).expect("clip"),
+            b"RIFF2"
+        );
         fs::remove_dir_all(&root).ok();
     }
 
+    #[test]
+    fn get_table_dumps_the_latest_table_at_or_before_a_step() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let run = std::env::temp_dir().join(format!("ogtui-get-table-{nonce}"));
+        fs::create_dir_all(&run).expect("create run");
+        let line = |step: i64, rows: serde_json::Value| {
+            serde_json::json!({
+                "step": step,
+                "event": {"type": "table", "name": "val/confusion", "rows": rows, "labels": ["a", "b"]},
+            })
+            .to_string()
+                + "\n"
+        };
+        let lines = line(10, serde_json::json!([[5, 1], [2, 4]]))
+            + &line(20, serde_json::json!([[6, 0], [1, 5]]));
+        fs::write(run.join(metrics_jsonl::FILE_NAME), lines).expect("write metrics");
+        let args = |table: Option<&str>, step: Option<i64>| GetTableArgs {
+            path: run.clone(),
+            project: None,
+            run: run.display().to_string(),
+            table: table.map(str::to_string),
+            step,
+        };
+
+        let output = execute_get_table(args(None, None)).expect("get table");
+        assert_eq!(
+            output.text,
+            "val/confusion @ step 20\n   a  b\na  6  0\nb  1  5"
+        );
+        assert_eq!(
+            output.data["tables"][0]["rows"],
+            serde_json::json!([[6.0, 0.0], [1.0, 5.0]])
+        );
+        let output = execute_get_table(args(Some("val/confusion"), Some(15))).expect("get table");
+        assert_eq!(output.data["tables"][0]["step"], 10);
+        assert!(execute_get_table(args(Some("missing"), None)).is_err());
+        fs::remove_dir_all(&run).ok();
+    }
+
+    #[test]
+    fn describe_records_attrs_that_set_the_leaderboard_direction() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let base = std::env::temp_dir().join(format!("ogtui-describe-{nonce}"));
+        for (run, gap) in [("run-a", 0.5), ("run-b", 0.9)] {
+            fs::create_dir_all(base.join(run)).expect("create run");
+            let line = serde_json::json!({"step": 1, "metrics": {"val/gap": gap}}).to_string();
+            fs::write(base.join(run).join(metrics_jsonl::FILE_NAME), line + "\n")
+                .expect("write metrics");
+        }
+        let leaderboard = || {
+            execute_leaderboard(LeaderboardArgs {
+                metric: Some("val/gap".to_string()),
+                mode: None,
+                top: 10,
+                hparams: Vec::new(),
+                path: base.clone(),
+                project: None,
+                jobs: None,
+            })
+            .expect("leaderboard")
+        };
+        assert_eq!(leaderboard().data["entries"][0]["run"], "run-b");
+
+        let output = execute_describe(DescribeArgs {
+            path: base.clone(),
+            project: None,
+            run: "run-a".to_string(),
+            metric: "val/gap".to_string(),
+            unit: Some("nats".to_string()),
+            higher_is_better: false,
+            lower_is_better: true,
+            scale: Some(crate::run_meta::Scale::Log),
+            display_name: Some("Val gap".to_string()),
+            bounds: None,
+            card: None,
+            clear: false,
+        })
+        .expect("describe");
+        assert!(
+            output
+                .text
+                .ends_with("display name \"Val gap\", unit nats, lower is better, log scale")
+        );
+        let meta = crate::run_meta::load(&base.join("run-a")).expect("load meta");
+        assert_eq!(meta.metrics["val/gap"].unit.as_deref(), Some("nats"));
+        assert_eq!(parse_bounds(" 0, 1").expect("bounds"), [0.0, 1.0]);
+        assert!(parse_bounds("1,0").is_err());
+        assert!(parse_bounds("0").is_err());
+        assert_eq!(leaderboard().data["entries"][0]["run"], "run-a");
+        fs::remove_dir_all(&base).ok();
+    }
+
     #[test]
     fn resolve_live_run_path_keeps_direct_tfevents_directory() {
         let nonce = SystemTime::now()
@@ -2704,9 +9007,122 @@ mod tests {
         fs::create_dir_all(&root).expect("create root");
         fs::write(root.join("events.out.tfevents.1"), b"x").expect("write marker");
 
-        let resolved = resolve_live_run_path(&root).expect("resolve run path");
+        let resolved = resolve_live_run_path(&root, "ignored").expect("resolve run path");
         assert_eq!(resolved, root);
 
         fs::remove_dir_all(&resolved).ok();
     }
+
+    #[test]
+    fn recorded_sessions_replay_messages_and_refreshes() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("ogtui-replay-{nonce}.ogr"));
+        let mut recorder =
+            session::Recorder::create(&path, &PathBuf::from("runs/demo"), 1).expect("create");
+        let mut scalars = BTreeMap::new();
+        scalars.insert("train/loss".to_string(), vec![(1.0, 0.9)]);
+        recorder
+            .refresh(&scalars, &[], 1, 1)
+            .expect("first refresh");
+        recorder
+            .message(&BgMessage::ChatStream(
+                crate::socket_client::ChatStreamEvent::Chunk("hi".to_string()),
+            ))
+            .expect("message");
+        scalars.insert("train/loss".to_string(), vec![(1.0, 0.9), (2.0, 0.5)]);
+        recorder
+            .refresh(&scalars, &[], 2, 2)
+            .expect("second refresh");
+
+        let (_, entries) = session::read::<BgMessage>(&path).expect("read session");
+        let (tx, rx) = mpsc::channel();
+        spawn_replay(
+            Replay {
+                entries,
+                speed: 1000.0,
+            },
+            tx,
+        );
+        let received: Vec<BgMessage> = rx.iter().collect();
+        assert_eq!(received.len(), 4);
+        assert!(matches!(
+            &received[1],
+            BgMessage::ChatStream(crate::socket_client::ChatStreamEvent::Chunk(text)) if text == "hi"
+        ));
+        assert!(matches!(received[3], BgMessage::ReplayFinished));
+
+        let mut app = App::new(
+            BTreeMap::new(),
+            BTreeMap::new(),
+            Vec::new(),
+            PathBuf::from("runs/demo"),
+            0,
+            0,
+        );
+        let mut checked = false;
+        for msg in received {
+            if let BgMessage::ReplayRefresh {
+                scalars,
+                log_lines,
+                total_events,
+                max_step,
+            } = msg
+            {
+                let sys_strip = crate::sys_strip::SysStrip::read(&scalars, &[]);
+                let view = ViewData {
+                    scalars,
+                    log_lines,
+                    total_events,
+                    max_step,
+                    ranks: Vec::new(),
+                    rank_progress: BTreeMap::new(),
+                    wall_secs: None,
+                    gaps: Vec::new(),
+                    sys_strip,
+                    epoch_scale: None,
+                };
+                apply_refresh(&mut app, view, None, None, &mut checked);
+            }
+        }
+        assert_eq!(app.scalars["train/loss"], vec![(1.0, 0.9), (2.0, 0.5)]);
+        assert_eq!((app.total_events, app.max_step), (2, 2));
+        assert!(app.live_logs_active);
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn completions_and_man_pages_cover_the_subcommands() {
+        for shell in [
+            clap_complete::Shell::Bash,
+            clap_complete::Shell::Zsh,
+            clap_complete::Shell::Fish,
+        ] {
+            let mut out = Vec::new();
+            write_completions(shell, &mut out).expect("completions");
+            let script = String::from_utf8(out).expect("utf8");
+            assert!(script.contains("leaderboard"), "{shell:?}");
+            assert!(script.contains("describe"), "{shell:?}");
+        }
+
+        let mut out = Vec::new();
+        write_man_page(&mut out).expect("man page");
+        let page = String::from_utf8(out).expect("utf8");
+        assert!(page.contains(".TH og 1"));
+        assert!(page.contains("leaderboard"));
+
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("ogtui-man-{nonce}"));
+        let written = write_man_pages(&dir).expect("man pages");
+        assert!(written > 10);
+        assert!(dir.join("og.1").exists());
+        assert!(dir.join("og-list-runs.1").exists());
+        assert!(!dir.join("og-bench.1").exists());
+        fs::remove_dir_all(&dir).ok();
+    }
 }
@@ -0,0 +1,218 @@
+//! Distribution summary and trend heuristics for one metric series, shared by
+//! `og stats` and anything else that needs to judge a curve (agent queries,
+//! alert rules).
+
+use serde::Serialize;
+
+/// Default number of trailing points the trend figures look at.
+pub const DEFAULT_WINDOW: usize = 50;
+/// Lag-1 autocorrelation below which the trailing window reads as noise
+/// around a level rather than a trend.
+const PLATEAU_AUTOCORRELATION: f64 = 0.5;
+/// Most the fitted line may move across a plateau window, in standard
+/// deviations of the window's values; noise around a slow trend still trends.
+const PLATEAU_DRIFT_STDS: f64 = 1.0;
+/// How many interquartile ranges the last value may sit from the median
+/// before the series counts as diverging.
+const DIVERGENCE_IQRS: f64 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Percentiles {
+    pub p5: f64,
+    pub p25: f64,
+    pub p50: f64,
+    pub p75: f64,
+    pub p95: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricStats {
+    /// Finite points; NaN and infinite values are counted in `non_finite`.
+    pub count: usize,
+    pub non_finite: usize,
+    pub mean: f64,
+    /// Population standard deviation.
+    pub std: f64,
+    pub min: f64,
+    pub max: f64,
+    pub last: f64,
+    pub percentiles: Percentiles,
+    /// Points the trend figures below were computed over.
+    pub window: usize,
+    /// Least-squares change per step over the window; None for a single point.
+    pub slope: Option<f64>,
+    /// Lag-1 autocorrelation over the window; None when the window is constant.
+    pub autocorrelation: Option<f64>,
+    pub plateau: bool,
+    pub diverging: bool,
+}
+
+/// Summarize `(step, value)` points in step order. Returns None when no
+/// value is finite.
+pub fn analyze(series: &[(f64, f64)], window: usize) -> Option<MetricStats> {
+    let finite: Vec<(f64, f64)> = series
+        .iter()
+        .copied()
+        .filter(|(_, value)| value.is_finite())
+        .collect();
+    let non_finite = series.len() - finite.len();
+    let values: Vec<f64> = finite.iter().map(|(_, value)| *value).collect();
+    let last = *values.last()?;
+    let (mean, std) = mean_std(&values);
+
+    let mut sorted = values.clone();
+    sorted.sort_by(f64::total_cmp);
+    let percentiles = Percentiles {
+        p5: percentile(&sorted, 5.0),
+        p25: percentile(&sorted, 25.0),
+        p50: percentile(&sorted, 50.0),
+        p75: percentile(&sorted, 75.0),
+        p95: percentile(&sorted, 95.0),
+    };
+
+    let tail = &finite[finite.len().saturating_sub(window.max(2))..];
+    let slope = slope(tail);
+    let tail_values: Vec<f64> = tail.iter().map(|(_, value)| *value).collect();
+    let autocorrelation = lag1_autocorrelation(&tail_values);
+    let (_, tail_std) = mean_std(&tail_values);
+    let span = tail[tail.len() - 1].0 - tail[0].0;
+    // A constant window is the flattest plateau there is.
+    let plateau = tail.len() >= 3
+        && autocorrelation.is_none_or(|autocorrelation| autocorrelation < PLATEAU_AUTOCORRELATION)
+        && slope.is_none_or(|slope| (slope * span).abs() <= PLATEAU_DRIFT_STDS * tail_std);
+
+    // NaN/inf after real values, or a last value far outside the bulk of the
+    // run and still moving away from it.
+    let iqr = percentiles.p75 - percentiles.p25;
+    let offset = last - percentiles.p50;
+    let drifting_away = iqr > 0.0
+        && offset.abs() > DIVERGENCE_IQRS * iqr
+        && slope.is_some_and(|slope| slope * offset > 0.0);
+    let diverging = series.last().is_some_and(|(_, v)| !v.is_finite()) || drifting_away;
+
+    Some(MetricStats {
+        count: values.len(),
+        non_finite,
+        mean,
+        std,
+        min: sorted[0],
+        max: sorted[sorted.len() - 1],
+        last,
+        percentiles,
+        window: tail.len(),
+        slope,
+        autocorrelation,
+        plateau,
+        diverging,
+    })
+}
+
+fn mean_std(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    (mean, variance.sqrt())
+}
+
+/// Linear interpolation between closest ranks of an ascending slice.
+fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    let rank = pct / 100.0 * (sorted.len() - 1) as f64;
+    let lo = rank.floor() as usize;
+    let hi = rank.ceil() as usize;
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
+}
+
+fn slope(points: &[(f64, f64)]) -> Option<f64> {
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (mut cov, mut var) = (0.0, 0.0);
+    for (x, y) in points {
+        cov += (x - mean_x) * (y - mean_y);
+        var += (x - mean_x).powi(2);
+    }
+    (var > 0.0).then(|| cov / var)
+}
+
+fn lag1_autocorrelation(values: &[f64]) -> Option<f64> {
+    if values.len() < 3 {
+        return None;
+    }
+    let (mean, std) = mean_std(values);
+    if std == 0.0 {
+        return None;
+    }
+    let covariance = values
+        .windows(2)
+        .map(|pair| (pair[0] - mean) * (pair[1] - mean))
+        .sum::<f64>();
+    Some(covariance / (std * std * values.len() as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::analyze;
+
+    fn series(values: impl IntoIterator<Item = f64>) -> Vec<(f64, f64)> {
+        values
+            .into_iter()
+            .enumerate()
+            .map(|(step, value)| (step as f64, value))
+            .collect()
+    }
+
+    #[test]
+    fn summarizes_distribution_and_trend() {
+        let stats = analyze(&series((1..=101).map(f64::from)), 20).expect("stats");
+        assert_eq!(stats.count, 101);
+        assert_eq!(stats.mean, 51.0);
+        assert_eq!(stats.percentiles.p50, 51.0);
+        assert_eq!(stats.percentiles.p5, 6.0);
+        assert_eq!(stats.window, 20);
+        assert!((stats.slope.expect("slope") - 1.0).abs() < 1e-9);
+        assert!(!stats.plateau);
+        assert!(!stats.diverging);
+
+        assert!(analyze(&series([f64::NAN]), 20).is_none());
+        assert!(analyze(&[], 20).is_none());
+    }
+
+    #[test]
+    fn detects_plateaus_and_divergence() {
+        // A decaying loss that flattens into alternating noise.
+        let flat = series(
+            (0..100)
+                .map(|i| 1.0 / (1.0 + i as f64))
+                .chain((0..60).map(|i| 0.01 + if i % 2 == 0 { 1e-4 } else { -1e-4 })),
+        );
+        let stats = analyze(&flat, 50).expect("stats");
+        assert!(stats.plateau);
+        assert!(stats.autocorrelation.expect("autocorrelation") < 0.0);
+        assert!(!stats.diverging);
+        assert!(analyze(&series([0.5; 10]), 50).expect("stats").plateau);
+
+        // Noisy enough to decorrelate neighbours, but still sliding down.
+        let sliding =
+            series((0..50).map(|i| 1.0 - i as f64 * 0.002 + if i % 2 == 0 { 0.05 } else { -0.05 }));
+        let stats = analyze(&sliding, 50).expect("stats");
+        assert!(stats.autocorrelation.expect("autocorrelation") < 0.5);
+        assert!(!stats.plateau);
+
+        let exploding = series((0..100).map(|i| {
+            if i < 90 {
+                1.0 - i as f64 * 0.001
+            } else {
+                10.0 * i as f64
+            }
+        }));
+        assert!(analyze(&exploding, 20).expect("stats").diverging);
+
+        let nan_tail = series([1.0, 0.9, 0.8, f64::NAN]);
+        let stats = analyze(&nan_tail, 20).expect("stats");
+        assert_eq!((stats.count, stats.non_finite), (3, 1));
+        assert!(stats.diverging);
+    }
+}
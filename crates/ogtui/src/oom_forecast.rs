@@ -0,0 +1,167 @@
+//! Out-of-memory forecasting: when a memory series has grown steadily over its
+//! recent points, never going down (the classic leak pattern), fit a line
+//! through them and project the step at which it reaches the device's total.
+
+use std::collections::BTreeMap;
+
+use crate::format;
+
+/// Recent points the trend is fitted over.
+const WINDOW: usize = 20;
+/// Points needed before a trend counts.
+const MIN_POINTS: usize = 8;
+/// Growth across the window, as a share of the total, below which the series
+/// counts as flat.
+const MIN_GROWTH_SHARE: f64 = 0.01;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Forecast {
+    /// The memory-used metric.
+    pub tag: String,
+    pub step: f64,
+    pub used: f64,
+    pub total: f64,
+    /// Growth per step of the fitted line.
+    pub per_step: f64,
+    /// Step at which the fitted line reaches `total`.
+    pub exhausted_at: f64,
+}
+
+impl Forecast {
+    /// `sys/gpu0/mem_used_mb reaches 81559 around step 18000 (~2h 30m)`; the
+    /// time is left out without a pace to convert steps with.
+    pub fn describe(&self, name: &str, secs_per_step: Option<f64>) -> String {
+        let mut text = format!(
+            "{name} reaches {:.0} around step {:.0}",
+            self.total, self.exhausted_at
+        );
+        if let Some(secs) = secs_per_step.filter(|secs| *secs > 0.0) {
+            text.push_str(&format!(
+                " (~{})",
+                format::duration((self.exhausted_at - self.step) * secs)
+            ));
+        }
+        text
+    }
+}
+
+/// The total-memory metric next to a used-memory one: `sys/gpu0/mem_used_mb`
+/// pairs with `sys/gpu0/mem_total_mb`, `sys/ram_used_mb` with `sys/ram_total_mb`.
+pub fn total_tag(used_tag: &str) -> Option<String> {
+    let (dir, name) = used_tag.rsplit_once('/').unwrap_or(("", used_tag));
+    if !(name.contains("mem") || name.contains("ram")) || !name.contains("_used") {
+        return None;
+    }
+    let total = name.replacen("_used", "_total", 1);
+    Some(if dir.is_empty() {
+        total
+    } else {
+        format!("{dir}/{total}")
+    })
+}
+
+/// Forecast for one used/total pair; None unless the recent points never
+/// went down, rose between most of them, and grew by a meaningful amount. A
+/// single jump followed by a plateau (a caching allocator warming up) is not
+/// a trend.
+pub fn forecast(tag: &str, used: &[(f64, f64)], total: f64) -> Option<Forecast> {
+    if used.len() < MIN_POINTS || total.is_nan() || total <= 0.0 {
+        return None;
+    }
+    let window = &used[used.len().saturating_sub(WINDOW)..];
+    if window.windows(2).any(|pair| pair[1].1 < pair[0].1) {
+        return None;
+    }
+    let rises = window
+        .windows(2)
+        .filter(|pair| pair[1].1 > pair[0].1)
+        .count();
+    if rises * 2 < window.len() - 1 {
+        return None;
+    }
+    let (first, last) = (window[0], window[window.len() - 1]);
+    if last.1 - first.1 < total * MIN_GROWTH_SHARE || last.0 <= first.0 {
+        return None;
+    }
+    let n = window.len() as f64;
+    let mean_x = window.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = window.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (cov, var) = window.iter().fold((0.0, 0.0), |(cov, var), (x, y)| {
+        (
+            cov + (x - mean_x) * (y - mean_y),
+            var + (x - mean_x) * (x - mean_x),
+        )
+    });
+    let per_step = cov / var;
+    if per_step.is_nan() || per_step <= 0.0 {
+        return None;
+    }
+    Some(Forecast {
+        tag: tag.to_string(),
+        step: last.0,
+        used: last.1,
+        total,
+        per_step,
+        exhausted_at: last.0 + (total - last.1).max(0.0) / per_step,
+    })
+}
+
+/// Forecasts for every used-memory metric with a total next to it.
+pub fn scan(scalars: &BTreeMap<String, Vec<(f64, f64)>>) -> Vec<Forecast> {
+    scalars
+        .iter()
+        .filter_map(|(tag, used)| {
+            let total = scalars.get(&total_tag(tag)?)?.last()?.1;
+            forecast(tag, used, total)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{scan, total_tag};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn projects_exhaustion_for_steadily_growing_memory() {
+        assert_eq!(
+            total_tag("sys/gpu0/mem_used_mb").as_deref(),
+            Some("sys/gpu0/mem_total_mb")
+        );
+        assert_eq!(
+            total_tag("sys/ram_used_mb").as_deref(),
+            Some("sys/ram_total_mb")
+        );
+        assert_eq!(total_tag("train/loss"), None);
+
+        let mut scalars = BTreeMap::new();
+        let leak: Vec<(f64, f64)> = (0..10)
+            .map(|i| (i as f64 * 100.0, 1000.0 + i as f64 * 500.0))
+            .collect();
+        scalars.insert("sys/gpu0/mem_used_mb".to_string(), leak.clone());
+        scalars.insert("sys/gpu0/mem_total_mb".to_string(), vec![(0.0, 10_000.0)]);
+        // The caching allocator's usual shape: a jump, then flat.
+        let cached: Vec<(f64, f64)> = (0..10)
+            .map(|i| (i as f64 * 100.0, if i < 2 { 1000.0 } else { 8000.0 }))
+            .collect();
+        scalars.insert("sys/gpu1/mem_used_mb".to_string(), cached);
+        scalars.insert("sys/gpu1/mem_total_mb".to_string(), vec![(0.0, 10_000.0)]);
+        // Growth that dipped once is not a leak.
+        let mut dipped = leak;
+        dipped[5].1 = 2900.0;
+        scalars.insert("sys/ram_used_mb".to_string(), dipped);
+        scalars.insert("sys/ram_total_mb".to_string(), vec![(0.0, 10_000.0)]);
+
+        let forecasts = scan(&scalars);
+        assert_eq!(forecasts.len(), 1);
+        let leak = &forecasts[0];
+        assert_eq!(leak.tag, "sys/gpu0/mem_used_mb");
+        assert!((leak.per_step - 5.0).abs() < 1e-9);
+        // 4500 MiB left at 5 MiB per step after step 900.
+        assert!((leak.exhausted_at - 1800.0).abs() < 1e-6);
+        assert_eq!(
+            leak.describe("gpu0 memory", Some(6.0)),
+            "gpu0 memory reaches 10000 around step 1800 (~1h 30m)"
+        );
+    }
+}
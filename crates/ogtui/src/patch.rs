@@ -0,0 +1,640 @@
+use anyhow::{Context, Result, bail};
+use og_core::error::OgError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Manifest written into every snapshot taken before a local apply. Snapshots
+/// share the checkpoint directory with the agent daemon's `ckpt_<unix>` ones;
+/// only directories holding this file can be rolled back by `og rollback`.
+pub const MANIFEST_FILE: &str = "refactor.json";
+
+#[derive(Debug, Clone, PartialEq)]
+enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Hunk {
+    /// 1-based first line of the hunk in the original file (0 for new files).
+    old_start: usize,
+    lines: Vec<HunkLine>,
+}
+
+impl Hunk {
+    fn before(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Remove(text) => Some(text.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect()
+    }
+
+    fn after(&self) -> Vec<String> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Add(text) => Some(text.clone()),
+                HunkLine::Remove(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// One file section of a unified diff. `None` paths are `/dev/null`.
+#[derive(Debug, Clone, PartialEq)]
+struct FilePatch {
+    old_path: Option<String>,
+    new_path: Option<String>,
+    hunks: Vec<Hunk>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Modified,
+    Created,
+    Deleted,
+}
+
+/// A file touched by a diff, relative to the codebase root.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    #[serde(default)]
+    pub hunks: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SnapshotFile {
+    path: String,
+    existed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Manifest {
+    created_unix: f64,
+    codebase_root: PathBuf,
+    files: Vec<SnapshotFile>,
+    changes: Vec<FileChange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rolled_back_unix: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Applied {
+    /// Snapshot directory (`None` for a dry run).
+    pub checkpoint: Option<PathBuf>,
+    pub changes: Vec<FileChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RolledBack {
+    pub checkpoint: PathBuf,
+    pub restored: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Strip what LLMs wrap diffs in: code fences and any prose before the first
+/// `diff --git` / `---` line. Mirrors the agent daemon's normalization.
+pub fn normalize_diff_text(diff: &str) -> String {
+    let lines: Vec<&str> = diff
+        .lines()
+        .filter(|line| !line.trim().starts_with("```"))
+        .collect();
+    let start = lines
+        .iter()
+        .position(|line| line.starts_with("diff --git ") || line.starts_with("--- "))
+        .unwrap_or(0);
+    let normalized = lines[start..].join("\n");
+    let normalized = normalized.trim_end_matches('\n');
+    if normalized.trim().is_empty() {
+        String::new()
+    } else {
+        format!("{normalized}\n")
+    }
+}
+
+/// `a/src/train.py`, `./train.py`, `"b/x.py"\t2024-01-01` -> plain relative paths.
+fn normalize_patch_path(raw: &str) -> Option<String> {
+    let path = raw.split('\t').next().unwrap_or(raw);
+    let path = path.trim().trim_matches('"').trim_matches('\'');
+    let path = path.strip_prefix("file://").unwrap_or(path);
+    let mut path = path.replace('\\', "/");
+    while path.starts_with("a/") || path.starts_with("b/") {
+        path.drain(..2);
+    }
+    while path.starts_with("./") {
+        path.drain(..2);
+    }
+    (!path.is_empty() && path != "/dev/null").then_some(path)
+}
+
+/// `@@ -12,5 +12,6 @@ fn main` -> 12.
+fn parse_hunk_header(line: &str) -> Result<usize> {
+    let old = line
+        .strip_prefix("@@ -")
+        .and_then(|rest| rest.split_whitespace().next())
+        .with_context(|| format!("malformed hunk header '{line}'"))?;
+    let start = old.split(',').next().unwrap_or(old);
+    start
+        .parse()
+        .with_context(|| format!("malformed hunk header '{line}'"))
+}
+
+fn parse_diff(diff: &str) -> Result<Vec<FilePatch>> {
+    let normalized = normalize_diff_text(diff);
+    let lines: Vec<&str> = normalized.lines().collect();
+    let mut patches: Vec<FilePatch> = Vec::new();
+    let mut idx = 0;
+    while idx < lines.len() {
+        let line = lines[idx];
+        if let Some(old) = line.strip_prefix("--- ")
+            && let Some(new) = lines.get(idx + 1).and_then(|l| l.strip_prefix("+++ "))
+        {
+            patches.push(FilePatch {
+                old_path: normalize_patch_path(old),
+                new_path: normalize_patch_path(new),
+                hunks: Vec::new(),
+            });
+            idx += 2;
+            continue;
+        }
+        if line.starts_with("@@ ") {
+            let Some(patch) = patches.last_mut() else {
+                bail!("hunk before any '---'/'+++' file header");
+            };
+            let mut hunk = Hunk {
+                old_start: parse_hunk_header(line)?,
+                lines: Vec::new(),
+            };
+            idx += 1;
+            while idx < lines.len() {
+                let line = lines[idx];
+                let next_is_header = line.starts_with("--- ")
+                    && lines.get(idx + 1).is_some_and(|l| l.starts_with("+++ "));
+                if next_is_header || line.starts_with("@@ ") || line.starts_with("diff --git ") {
+                    break;
+                }
+                match line.chars().next() {
+                    Some('+') => hunk.lines.push(HunkLine::Add(line[1..].to_string())),
+                    Some('-') => hunk.lines.push(HunkLine::Remove(line[1..].to_string())),
+                    Some(' ') => hunk.lines.push(HunkLine::Context(line[1..].to_string())),
+                    // Editors and LLMs drop the space of blank context lines.
+                    None => hunk.lines.push(HunkLine::Context(String::new())),
+                    Some('\\') => {}
+                    Some(_) => break,
+                }
+                idx += 1;
+            }
+            while hunk.lines.last() == Some(&HunkLine::Context(String::new())) {
+                hunk.lines.pop();
+            }
+            patch.hunks.push(hunk);
+            continue;
+        }
+        idx += 1;
+    }
+    if patches.is_empty() {
+        bail!("no file changes found in diff");
+    }
+    if let Some(patch) = patches.iter().find(|patch| patch.hunks.is_empty()) {
+        bail!(
+            "diff for '{}' has no hunks",
+            patch
+                .new_path
+                .as_deref()
+                .or(patch.old_path.as_deref())
+                .unwrap_or("?")
+        );
+    }
+    Ok(patches)
+}
+
+fn lines_match(file: &[String], at: usize, before: &[&str]) -> bool {
+    at + before.len() <= file.len()
+        && before
+            .iter()
+            .zip(&file[at..])
+            .all(|(want, have)| want.trim_end() == have.trim_end())
+}
+
+/// Apply hunks in order. Each hunk is tried at its stated line (shifted by
+/// what earlier hunks added or removed), then at the nearest position where
+/// its context matches, never before the end of the previous hunk.
+fn apply_hunks(path: &str, original: &[String], hunks: &[Hunk]) -> Result<Vec<String>> {
+    let mut result = Vec::new();
+    let mut cursor = 0usize;
+    let mut offset = 0isize;
+    for (number, hunk) in hunks.iter().enumerate() {
+        let before = hunk.before();
+        let expected = (hunk.old_start.saturating_sub(1) as isize + offset).max(cursor as isize);
+        let expected = (expected as usize).min(original.len());
+        let candidates = (0..=original.len()).flat_map(|distance| {
+            let later = expected + distance;
+            let earlier = expected.checked_sub(distance).filter(|at| *at >= cursor);
+            [Some(later), earlier.filter(|_| distance > 0)]
+        });
+        let Some(at) = candidates
+            .flatten()
+            .find(|at| *at <= original.len() && lines_match(original, *at, &before))
+        else {
+            bail!(
+                "hunk {} of {} does not match the file (expected near line {})",
+                number + 1,
+                path,
+                hunk.old_start
+            );
+        };
+        result.extend_from_slice(&original[cursor..at]);
+        let after = hunk.after();
+        offset += after.len() as isize - before.len() as isize + (at as isize - expected as isize);
+        result.extend(after);
+        cursor = at + before.len();
+    }
+    result.extend_from_slice(&original[cursor..]);
+    Ok(result)
+}
+
+/// Reject paths that would escape the codebase root.
+fn resolve_in_root(root: &Path, path: &str) -> Result<PathBuf> {
+    let candidate = Path::new(path);
+    let relative = if candidate.is_absolute() {
+        candidate
+            .strip_prefix(root)
+            .with_context(|| format!("refusing to patch '{path}' outside {}", root.display()))?
+    } else {
+        candidate
+    };
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        bail!("refusing to patch '{path}' outside {}", root.display());
+    }
+    Ok(relative.to_path_buf())
+}
+
+struct TextFile {
+    lines: Vec<String>,
+    crlf: bool,
+    trailing_newline: bool,
+}
+
+impl TextFile {
+    fn read(path: &Path) -> Result<Self> {
+        let text =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        Ok(Self {
+            crlf: text.contains("\r\n"),
+            trailing_newline: text.is_empty() || text.ends_with('\n'),
+            lines: text
+                .lines()
+                .map(|line| line.trim_end_matches('\r').to_string())
+                .collect(),
+        })
+    }
+
+    fn render(&self, lines: &[String]) -> String {
+        let mut text = lines.join(if self.crlf { "\r\n" } else { "\n" });
+        if self.trailing_newline && !lines.is_empty() {
+            text.push_str(if self.crlf { "\r\n" } else { "\n" });
+        }
+        text
+    }
+}
+
+/// A computed change, kept in memory until every file has patched cleanly.
+struct Planned {
+    change: FileChange,
+    /// New contents; `None` deletes the file.
+    contents: Option<String>,
+    /// Extra file a rename removes.
+    removes: Option<String>,
+}
+
+fn plan(root: &Path, patches: &[FilePatch]) -> Result<Vec<Planned>> {
+    let mut planned: Vec<Planned> = Vec::new();
+    for patch in patches {
+        let (source, target) = (patch.old_path.clone(), patch.new_path.clone());
+        let (kind, path) = match (&source, &target) {
+            (None, Some(new)) => (ChangeKind::Created, new.clone()),
+            (Some(old), None) => (ChangeKind::Deleted, old.clone()),
+            (Some(_), Some(new)) => (ChangeKind::Modified, new.clone()),
+            (None, None) => bail!("diff section has no file paths"),
+        };
+        let read_from = source.clone().unwrap_or_else(|| path.clone());
+        let full = root.join(resolve_in_root(root, &read_from)?);
+        let file = if kind == ChangeKind::Created {
+            if full.exists() {
+                bail!("diff creates '{path}', which already exists");
+            }
+            TextFile {
+                lines: Vec::new(),
+                crlf: false,
+                trailing_newline: true,
+            }
+        } else {
+            TextFile::read(&full)?
+        };
+        let lines = apply_hunks(&path, &file.lines, &patch.hunks)?;
+        let contents = (kind != ChangeKind::Deleted).then(|| file.render(&lines));
+        let removes = source.filter(|old| target.as_ref().is_some_and(|new| new != old));
+        planned.push(Planned {
+            change: FileChange {
+                path: resolve_in_root(root, &path)?.to_string_lossy().into_owned(),
+                kind,
+                hunks: patch.hunks.len(),
+            },
+            contents,
+            removes: removes
+                .map(|old| resolve_in_root(root, &old).map(|p| p.to_string_lossy().into_owned()))
+                .transpose()?,
+        });
+    }
+    Ok(planned)
+}
+
+fn unix_now() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
+/// `ckpt_<unix millis>`: sorts after the daemon's `ckpt_<unix secs>` names
+/// taken in the same second, so `latest` stays chronological.
+fn new_snapshot_dir(checkpoint_dir: &Path) -> Result<PathBuf> {
+    let mut millis = (unix_now() * 1000.0) as u128;
+    loop {
+        let dir = checkpoint_dir.join(format!("ckpt_{millis}"));
+        if !dir.exists() {
+            fs::create_dir_all(&dir).with_context(|| format!("creating {}", dir.display()))?;
+            return Ok(dir);
+        }
+        millis += 1;
+    }
+}
+
+fn write_atomic(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("creating {}", parent.display()))?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".og_tmp");
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, contents).with_context(|| format!("writing {}", tmp.display()))?;
+    if let Ok(meta) = fs::metadata(path) {
+        fs::set_permissions(&tmp, meta.permissions()).ok();
+    }
+    fs::rename(&tmp, path).with_context(|| format!("replacing {}", path.display()))
+}
+
+/// Apply a unified diff under `codebase_root`. Every hunk is checked before
+/// anything is written; touched files are copied into a new snapshot under
+/// `checkpoint_dir` first, and if a write fails midway the snapshot is
+/// restored. With `dry_run` nothing is written and no snapshot is taken.
+pub fn apply(
+    codebase_root: &Path,
+    checkpoint_dir: &Path,
+    diff: &str,
+    dry_run: bool,
+) -> Result<Applied> {
+    let root = codebase_root
+        .canonicalize()
+        .with_context(|| format!("resolving codebase root {}", codebase_root.display()))?;
+    let patches = parse_diff(diff)?;
+    let planned = plan(&root, &patches)?;
+    let changes: Vec<FileChange> = planned.iter().map(|p| p.change.clone()).collect();
+    if dry_run {
+        return Ok(Applied {
+            checkpoint: None,
+            changes,
+        });
+    }
+
+    let snapshot = new_snapshot_dir(checkpoint_dir)?;
+    let mut files: Vec<SnapshotFile> = Vec::new();
+    let touched = planned
+        .iter()
+        .flat_map(|p| std::iter::once(&p.change.path).chain(p.removes.as_ref()));
+    for path in touched {
+        if files.iter().any(|file| &file.path == path) {
+            continue;
+        }
+        let source = root.join(path);
+        let existed = source.is_file();
+        if existed {
+            let dest = snapshot.join("files").join(path);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&source, &dest)
+                .with_context(|| format!("snapshotting {}", source.display()))?;
+        }
+        files.push(SnapshotFile {
+            path: path.clone(),
+            existed,
+        });
+    }
+    let manifest = Manifest {
+        created_unix: unix_now(),
+        codebase_root: root.clone(),
+        files,
+        changes: changes.clone(),
+        rolled_back_unix: None,
+    };
+    write_manifest(&snapshot, &manifest)?;
+    fs::write(snapshot.join("changes.diff"), normalize_diff_text(diff))?;
+
+    let written = planned.iter().try_for_each(|p| {
+        let target = root.join(&p.change.path);
+        match &p.contents {
+            Some(contents) => write_atomic(&target, contents)?,
+            None => fs::remove_file(&target)
+                .with_context(|| format!("removing {}", target.display()))?,
+        }
+        if let Some(old) = &p.removes {
+            fs::remove_file(root.join(old)).with_context(|| format!("removing {old}"))?;
+        }
+        Ok::<_, anyhow::Error>(())
+    });
+    if let Err(err) = written {
+        restore(&snapshot, &manifest).ok();
+        return Err(err.context("applying diff (changes were rolled back)"));
+    }
+    Ok(Applied {
+        checkpoint: Some(snapshot),
+        changes,
+    })
+}
+
+fn write_manifest(snapshot: &Path, manifest: &Manifest) -> Result<()> {
+    let path = snapshot.join(MANIFEST_FILE);
+    fs::write(&path, serde_json::to_string_pretty(manifest)?)
+        .with_context(|| format!("writing {}", path.display()))
+}
+
+fn read_manifest(snapshot: &Path) -> Result<Manifest> {
+    let path = snapshot.join(MANIFEST_FILE);
+    if !path.exists() {
+        bail!(
+            "{} was not taken by a local apply (no {MANIFEST_FILE}); agent daemon checkpoints are restored with `og resume`",
+            snapshot.display()
+        );
+    }
+    let raw = fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+    serde_json::from_str(&raw).with_context(|| OgError::parse_failed(path.display()))
+}
+
+fn restore(snapshot: &Path, manifest: &Manifest) -> Result<RolledBack> {
+    let mut restored = Vec::new();
+    let mut removed = Vec::new();
+    for file in &manifest.files {
+        let target = manifest.codebase_root.join(&file.path);
+        if file.existed {
+            let saved = fs::read_to_string(snapshot.join("files").join(&file.path))
+                .with_context(|| format!("reading snapshot of {}", file.path))?;
+            write_atomic(&target, &saved)?;
+            restored.push(file.path.clone());
+        } else if target.exists() {
+            fs::remove_file(&target).with_context(|| format!("removing {}", target.display()))?;
+            removed.push(file.path.clone());
+        }
+    }
+    Ok(RolledBack {
+        checkpoint: snapshot.to_path_buf(),
+        restored,
+        removed,
+    })
+}
+
+/// Newest local-apply snapshot that has not been rolled back yet, so
+/// repeated `og rollback` calls step back one apply at a time.
+pub fn latest_snapshot(checkpoint_dir: &Path) -> Result<PathBuf> {
+    let mut snapshots = Vec::new();
+    if checkpoint_dir.is_dir() {
+        for entry in fs::read_dir(checkpoint_dir)? {
+            let path = entry?.path();
+            if let Ok(manifest) = read_manifest(&path)
+                && manifest.rolled_back_unix.is_none()
+            {
+                snapshots.push(path);
+            }
+        }
+    }
+    snapshots.sort();
+    snapshots.pop().with_context(|| {
+        format!(
+            "no local refactor snapshots under {}",
+            checkpoint_dir.display()
+        )
+    })
+}
+
+/// Put every file a snapshot covers back the way it was, and mark the
+/// snapshot as rolled back.
+pub fn rollback(snapshot: &Path) -> Result<RolledBack> {
+    let mut manifest = read_manifest(snapshot)?;
+    let rolled_back = restore(snapshot, &manifest)?;
+    manifest.rolled_back_unix = Some(unix_now());
+    write_manifest(snapshot, &manifest)?;
+    Ok(rolled_back)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ChangeKind, apply, latest_snapshot, normalize_diff_text, normalize_patch_path, parse_diff,
+        rollback,
+    };
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn normalizes_fenced_diffs_and_paths() {
+        let raw = "Here is the fix:\n```diff\n--- a/train.py\n+++ b/train.py\n@@ -1 +1 @@\n-lr = 1e-3\n+lr = 3e-4\n```\n";
+        assert_eq!(
+            normalize_diff_text(raw),
+            "--- a/train.py\n+++ b/train.py\n@@ -1 +1 @@\n-lr = 1e-3\n+lr = 3e-4\n"
+        );
+        assert_eq!(
+            normalize_patch_path("\"a/./src/x.py\"\t2024-01-01"),
+            Some("src/x.py".to_string())
+        );
+        assert_eq!(normalize_patch_path("/dev/null"), None);
+        assert_eq!(
+            normalize_patch_path("file://b/model.py"),
+            Some("model.py".to_string())
+        );
+
+        let patches = parse_diff(raw).expect("parse");
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].hunks[0].old_start, 1);
+        assert!(parse_diff("no diff here").is_err());
+    }
+
+    #[test]
+    fn applies_shifted_hunks_snapshots_and_rolls_back() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("ogtui-patch-{nonce}"));
+        fs::create_dir_all(&root).expect("create root");
+        let original = "import torch\n\nlr = 1e-3\nbatch = 32\n\ndef train():\n    pass\n";
+        fs::write(root.join("train.py"), original).expect("write train.py");
+        let checkpoints = root.join(".og_checkpoints");
+
+        // Line numbers are off by two, and a new file is created alongside.
+        let diff = "--- a/train.py\n+++ b/train.py\n@@ -5,2 +5,2 @@\n lr = 1e-3\n-batch = 32\n+batch = 64\n--- /dev/null\n+++ b/sched.py\n@@ -0,0 +1 @@\n+warmup = 100\n";
+        let dry = apply(&root, &checkpoints, diff, true).expect("dry run");
+        assert!(dry.checkpoint.is_none());
+        assert_eq!(fs::read_to_string(root.join("train.py")).unwrap(), original);
+
+        let applied = apply(&root, &checkpoints, diff, false).expect("apply");
+        assert_eq!(
+            applied
+                .changes
+                .iter()
+                .map(|c| (c.path.as_str(), c.kind))
+                .collect::<Vec<_>>(),
+            vec![
+                ("train.py", ChangeKind::Modified),
+                ("sched.py", ChangeKind::Created)
+            ]
+        );
+        assert!(
+            fs::read_to_string(root.join("train.py"))
+                .unwrap()
+                .contains("batch = 64\n\ndef train")
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("sched.py")).unwrap(),
+            "warmup = 100\n"
+        );
+
+        // A hunk that no longer matches leaves every file untouched.
+        let stale = "--- a/train.py\n+++ b/train.py\n@@ -1 +1 @@\n-import jax\n+import flax\n";
+        let err = apply(&root, &checkpoints, stale, false).expect_err("stale hunk");
+        assert!(err.to_string().contains("does not match"));
+        let escape = "--- a/../x.py\n+++ b/../x.py\n@@ -1 +1 @@\n-a\n+b\n";
+        assert!(apply(&root, &checkpoints, escape, false).is_err());
+
+        let snapshot = latest_snapshot(&checkpoints).expect("latest");
+        assert_eq!(Some(&snapshot), applied.checkpoint.as_ref());
+        let rolled = rollback(&snapshot).expect("rollback");
+        assert_eq!(rolled.restored, vec!["train.py"]);
+        assert_eq!(rolled.removed, vec!["sched.py"]);
+        assert_eq!(fs::read_to_string(root.join("train.py")).unwrap(), original);
+        assert!(!root.join("sched.py").exists());
+        assert!(latest_snapshot(&checkpoints).is_err());
+
+        fs::remove_dir_all(&root).ok();
+    }
+}
@@ -0,0 +1,91 @@
+//! `--porcelain` output: the same data `--json` prints, flattened to one
+//! `path<TAB>value` line per leaf so scripts can `cut`/`awk` it. Paths join
+//! object keys and array indexes with `.` (`runs.0.id`). The format only grows
+//! new lines; existing paths keep their meaning.
+
+use serde_json::Value;
+
+pub fn render(data: &Value) -> String {
+    let mut out = String::new();
+    flatten(data, &mut String::new(), &mut out);
+    out
+}
+
+fn flatten(value: &Value, path: &mut String, out: &mut String) {
+    let len = path.len();
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                push_segment(path, len, &escape(key));
+                flatten(value, path, out);
+            }
+        }
+        Value::Array(items) => {
+            for (idx, value) in items.iter().enumerate() {
+                push_segment(path, len, &idx.to_string());
+                flatten(value, path, out);
+            }
+        }
+        leaf => {
+            out.push_str(path);
+            out.push('\t');
+            match leaf {
+                Value::Null => {}
+                Value::String(s) => out.push_str(&escape(s)),
+                other => out.push_str(&other.to_string()),
+            }
+            out.push('\n');
+        }
+    }
+    path.truncate(len);
+}
+
+fn push_segment(path: &mut String, len: usize, segment: &str) {
+    path.truncate(len);
+    if len > 0 {
+        path.push('.');
+    }
+    path.push_str(segment);
+}
+
+/// Backslash escapes for the characters that would break a line or a field.
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use serde_json::json;
+
+    #[test]
+    fn leaves_become_path_tab_value_lines() {
+        let data = json!({
+            "runs": [
+                {"id": "r1", "max_step": 20, "size_bytes": null},
+                {"id": "two\twords\n", "max_step": 5, "tags": []},
+            ],
+            "done": true,
+        });
+        assert_eq!(
+            render(&data),
+            "done\ttrue\n\
+             runs.0.id\tr1\n\
+             runs.0.max_step\t20\n\
+             runs.0.size_bytes\t\n\
+             runs.1.id\ttwo\\twords\\n\n\
+             runs.1.max_step\t5\n"
+        );
+        assert_eq!(render(&json!("bare")), "\tbare\n");
+    }
+}
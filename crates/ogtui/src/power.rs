@@ -0,0 +1,221 @@
+//! Power draw and energy use of a run. The system sampler logs GPU board
+//! power and CPU package power (RAPL energy counters, where the kernel lets us
+//! read them) as `sys/power/*`; energy is those series integrated over
+//! wall-clock time.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::tfevents::ScalarEvent;
+
+/// Prefix of power series, in watts.
+pub const TAG_PREFIX: &str = "sys/power/";
+pub const GPU_TAG: &str = "sys/power/gpu_w";
+pub const CPU_TAG: &str = "sys/power/cpu_w";
+/// Longer gaps between samples (the TUI was closed) add no energy.
+const MAX_GAP_SECS: f64 = 300.0;
+
+/// One RAPL package counter (`intel-rapl:0`, also used for AMD packages).
+struct RaplDomain {
+    energy_path: PathBuf,
+    max_uj: u64,
+    last_uj: u64,
+}
+
+/// CPU package power from RAPL counters: energy read twice, over elapsed time.
+pub struct Rapl {
+    domains: Vec<RaplDomain>,
+    last: Instant,
+}
+
+impl Rapl {
+    /// Package domains under `root` whose counters are readable; None when
+    /// there are none (no RAPL, or `energy_uj` is root-only).
+    pub fn open(root: &Path) -> Option<Rapl> {
+        let mut domains = Vec::new();
+        for entry in fs::read_dir(root).ok()?.flatten() {
+            let dir = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            // Sub-domains (`intel-rapl:0:0`, the cores) are inside the package.
+            if !name.starts_with("intel-rapl:") || name.matches(':').count() != 1 {
+                continue;
+            }
+            let is_package = fs::read_to_string(dir.join("name"))
+                .is_ok_and(|kind| kind.trim().starts_with("package"));
+            let energy_path = dir.join("energy_uj");
+            let Some(last_uj) = read_uj(&energy_path) else {
+                continue;
+            };
+            if is_package {
+                domains.push(RaplDomain {
+                    energy_path,
+                    max_uj: read_uj(&dir.join("max_energy_range_uj")).unwrap_or(u64::MAX),
+                    last_uj,
+                });
+            }
+        }
+        (!domains.is_empty()).then(|| Rapl {
+            domains,
+            last: Instant::now(),
+        })
+    }
+
+    /// Average watts across all packages since the previous call.
+    pub fn watts(&mut self) -> Option<f64> {
+        let now = Instant::now();
+        let secs = now.duration_since(self.last).as_secs_f64();
+        self.last = now;
+        let mut joules = 0.0;
+        for domain in &mut self.domains {
+            let uj = read_uj(&domain.energy_path)?;
+            // The counter wraps at max_energy_range_uj.
+            let delta = if uj >= domain.last_uj {
+                uj - domain.last_uj
+            } else {
+                domain.max_uj.saturating_sub(domain.last_uj) + uj
+            };
+            domain.last_uj = uj;
+            joules += delta as f64 / 1e6;
+        }
+        (secs > 0.0).then(|| joules / secs)
+    }
+}
+
+fn read_uj(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Energy a run used, from its `sys/power/*` series.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Energy {
+    pub kwh: f64,
+    /// kWh per power series.
+    pub by_metric: BTreeMap<String, f64>,
+}
+
+impl Energy {
+    /// `1.250 kWh (sys/power/cpu_w 0.250, sys/power/gpu_w 1.000)`.
+    pub fn describe(&self) -> String {
+        let parts: Vec<String> = self
+            .by_metric
+            .iter()
+            .map(|(tag, kwh)| format!("{tag} {kwh:.3}"))
+            .collect();
+        format!("{:.3} kWh ({})", self.kwh, parts.join(", "))
+    }
+}
+
+/// Integrate each `sys/power/*` series (watts) over wall time with the
+/// trapezoid rule; None when the run logged no power.
+pub fn energy(events: &[ScalarEvent]) -> Option<Energy> {
+    let mut series: BTreeMap<&str, Vec<(f64, f64)>> = BTreeMap::new();
+    for event in events {
+        if event.tag.starts_with(TAG_PREFIX) && event.wall_time > 0.0 && event.value.is_finite() {
+            series
+                .entry(&event.tag)
+                .or_default()
+                .push((event.wall_time, event.value));
+        }
+    }
+    if series.is_empty() {
+        return None;
+    }
+    let by_metric: BTreeMap<String, f64> = series
+        .into_iter()
+        .map(|(tag, mut points)| {
+            points.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let joules: f64 = points
+                .windows(2)
+                .map(|pair| {
+                    let ((t0, w0), (t1, w1)) = (pair[0], pair[1]);
+                    let dt = t1 - t0;
+                    if dt > MAX_GAP_SECS {
+                        0.0
+                    } else {
+                        dt * (w0 + w1) / 2.0
+                    }
+                })
+                .sum();
+            (tag.to_string(), joules / 3.6e6)
+        })
+        .collect();
+    Some(Energy {
+        kwh: by_metric.values().sum(),
+        by_metric,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CPU_TAG, GPU_TAG, Rapl, energy};
+    use crate::tfevents::ScalarEvent;
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn event(tag: &str, wall_time: f64, value: f64) -> ScalarEvent {
+        ScalarEvent {
+            tag: tag.to_string(),
+            step: 0,
+            wall_time,
+            value,
+        }
+    }
+
+    #[test]
+    fn integrates_power_series_to_kwh() {
+        let t0 = 1.7e9;
+        let events = vec![
+            event(GPU_TAG, t0, 300.0),
+            event(GPU_TAG, t0 + 240.0, 500.0),
+            // The TUI was closed for an hour: no energy for the gap.
+            event(GPU_TAG, t0 + 3840.0, 500.0),
+            event(GPU_TAG, t0 + 3960.0, 500.0),
+            event(CPU_TAG, t0, 100.0),
+            event(CPU_TAG, t0 + 180.0, 100.0),
+            event("train/loss", t0 + 180.0, 1.0),
+        ];
+        let used = energy(&events).expect("energy");
+        // (240 s × 400 W + 120 s × 500 W) and 180 s × 100 W, in kWh.
+        assert!((used.by_metric[GPU_TAG] - 156_000.0 / 3.6e6).abs() < 1e-12);
+        assert!((used.by_metric[CPU_TAG] - 0.005).abs() < 1e-12);
+        assert!((used.kwh - 174_000.0 / 3.6e6).abs() < 1e-12);
+        assert_eq!(
+            used.describe(),
+            "0.048 kWh (sys/power/cpu_w 0.005, sys/power/gpu_w 0.043)"
+        );
+        assert_eq!(energy(&events[6..]), None);
+    }
+
+    #[test]
+    fn reads_rapl_package_counters_across_a_wrap() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("ogtui-rapl-{nonce}"));
+        let package = root.join("intel-rapl:0");
+        let core = root.join("intel-rapl:0:0");
+        fs::create_dir_all(&package).expect("package dir");
+        fs::create_dir_all(&core).expect("core dir");
+        fs::write(package.join("name"), "package-0\n").expect("name");
+        fs::write(package.join("energy_uj"), "900000\n").expect("energy");
+        fs::write(package.join("max_energy_range_uj"), "1000000\n").expect("range");
+        fs::write(core.join("name"), "core\n").expect("core name");
+        fs::write(core.join("energy_uj"), "5\n").expect("core energy");
+
+        let mut rapl = Rapl::open(&root).expect("rapl");
+        assert_eq!(rapl.domains.len(), 1);
+        fs::write(package.join("energy_uj"), "400000\n").expect("energy");
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let watts = rapl.watts().expect("watts");
+        // 0.5 J over at least 20 ms.
+        assert!(watts > 0.0 && watts <= 25.0, "{watts}");
+        assert_eq!(rapl.domains[0].last_uj, 400000);
+
+        fs::remove_dir_all(&root).ok();
+        assert!(Rapl::open(&root).is_none());
+    }
+}
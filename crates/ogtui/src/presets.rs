@@ -0,0 +1,207 @@
+use anyhow::{Context, Result, bail};
+use og_core::error::OgError;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable overriding the config file location.
+pub const CONFIG_ENV_VAR: &str = "OG_CONFIG";
+
+/// Prefix selecting a preset in `--graph`, e.g. `--graph preset:losses`.
+pub const PRESET_PREFIX: &str = "preset:";
+
+/// A named list of metric patterns. Patterns match tags by substring, or as a
+/// glob when they contain `*` (`sys/*`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GraphPreset {
+    pub name: String,
+    pub patterns: Vec<String>,
+}
+
+/// `$OG_CONFIG`, else `$XDG_CONFIG_HOME/opengraphs/config.toml`, else
+/// `~/.config/opengraphs/config.toml`.
+pub fn config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(CONFIG_ENV_VAR).filter(|p| !p.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("opengraphs").join("config.toml"))
+}
+
+/// Presets from the config file; no file means no presets.
+pub fn load() -> Result<Vec<GraphPreset>> {
+    match config_path() {
+        Some(path) if path.exists() => load_file(&path),
+        _ => Ok(Vec::new()),
+    }
+}
+
+pub fn load_file(path: &Path) -> Result<Vec<GraphPreset>> {
+    let raw = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    parse(&raw).with_context(|| OgError::parse_failed(path.display()))
+}
+
+pub fn find<'a>(presets: &'a [GraphPreset], name: &str) -> Result<&'a GraphPreset> {
+    presets.iter().find(|p| p.name == name).with_context(|| {
+        let known: Vec<&str> = presets.iter().map(|p| p.name.as_str()).collect();
+        if known.is_empty() {
+            format!("graph preset '{name}' not found (no presets configured)")
+        } else {
+            format!(
+                "graph preset '{name}' not found (known: {})",
+                known.join(", ")
+            )
+        }
+    })
+}
+
+/// Read presets from the subset of TOML the config uses: `preset.<name> = [...]`
+/// at the top level, or `<name> = [...]` under a `[preset]` table. Arrays may
+/// span lines. Other keys and tables are left for other settings.
+pub fn parse(text: &str) -> Result<Vec<GraphPreset>> {
+    let mut presets: Vec<GraphPreset> = Vec::new();
+    let mut table = String::new();
+    let mut lines = text.lines().enumerate();
+    while let Some((idx, line)) = lines.next() {
+        let line = strip_comment(line).trim().to_string();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            table = name.trim().to_string();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            bail!("line {}: expected key = value", idx + 1);
+        };
+        let key = key.trim().trim_matches('"');
+        let name = match (table.as_str(), key.strip_prefix("preset.")) {
+            ("", Some(name)) => name,
+            ("preset", None) => key,
+            _ => continue,
+        };
+        let mut value = value.trim().to_string();
+        while value.starts_with('[') && !array_closed(&value) {
+            let Some((_, next)) = lines.next() else {
+                bail!("line {}: unterminated array for preset '{name}'", idx + 1);
+            };
+            value.push(' ');
+            value.push_str(strip_comment(next).trim());
+        }
+        let patterns =
+            parse_strings(&value).with_context(|| format!("line {}: preset '{name}'", idx + 1))?;
+        if patterns.is_empty() {
+            bail!("line {}: preset '{name}' has no patterns", idx + 1);
+        }
+        let name = name.trim_matches('"').to_string();
+        match presets.iter_mut().find(|p| p.name == name) {
+            Some(existing) => existing.patterns = patterns,
+            None => presets.push(GraphPreset { name, patterns }),
+        }
+    }
+    Ok(presets)
+}
+
+/// Drop a `#` comment unless it sits inside a quoted string.
+pub(crate) fn strip_comment(line: &str) -> &str {
+    let mut quote: Option<char> = None;
+    for (idx, ch) in line.char_indices() {
+        match (quote, ch) {
+            (None, '#') => return &line[..idx],
+            (None, '"' | '\'') => quote = Some(ch),
+            (Some(q), c) if c == q => quote = None,
+            _ => {}
+        }
+    }
+    line
+}
+
+pub(crate) fn array_closed(value: &str) -> bool {
+    let mut quote: Option<char> = None;
+    for ch in value.chars() {
+        match (quote, ch) {
+            (None, ']') => return true,
+            (None, '"' | '\'') => quote = Some(ch),
+            (Some(q), c) if c == q => quote = None,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// A quoted string or an array of quoted strings (`"..."` or `'...'`).
+pub(crate) fn parse_strings(value: &str) -> Result<Vec<String>> {
+    let value = value.trim();
+    let inner = match value.strip_prefix('[') {
+        Some(rest) => rest
+            .trim_end()
+            .strip_suffix(']')
+            .context("expected ']' at the end of the array")?,
+        None => value,
+    };
+    let mut out = Vec::new();
+    let mut chars = inner.chars().peekable();
+    loop {
+        while chars.peek().is_some_and(|c| c.is_whitespace() || *c == ',') {
+            chars.next();
+        }
+        let Some(quote) = chars.next() else {
+            break;
+        };
+        if quote != '"' && quote != '\'' {
+            bail!("expected a quoted string, found '{quote}'");
+        }
+        let mut item = String::new();
+        loop {
+            match chars.next() {
+                Some('\\') if quote == '"' => item.extend(chars.next()),
+                Some(c) if c == quote => break,
+                Some(c) => item.push(c),
+                None => bail!("unterminated string"),
+            }
+        }
+        out.push(item);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find, parse};
+
+    #[test]
+    fn parses_dotted_keys_tables_and_multiline_arrays() {
+        let presets = parse(
+            r#"
+# graph presets
+theme = "dark"
+preset.losses = ["train/loss", "val/loss"] # both splits
+preset.sys = ['sys/*']
+
+[preset]
+"gpu" = [
+  "gpu/util",   # utilisation
+  "gpu/mem#used",
+]
+
+[other]
+losses = ["ignored"]
+"#,
+        )
+        .expect("parse presets");
+        let names: Vec<&str> = presets.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["losses", "sys", "gpu"]);
+        assert_eq!(presets[0].patterns, vec!["train/loss", "val/loss"]);
+        assert_eq!(presets[1].patterns, vec!["sys/*"]);
+        assert_eq!(presets[2].patterns, vec!["gpu/util", "gpu/mem#used"]);
+
+        let err = find(&presets, "nope").expect_err("unknown preset");
+        assert!(err.to_string().contains("known: losses, sys, gpu"));
+        assert!(parse("preset.x = [\"a\"").is_err());
+        assert!(parse("preset.x = []").is_err());
+        assert!(parse("preset.x = [loss]").is_err());
+    }
+}
@@ -0,0 +1,402 @@
+//! Project defaults from a `project.toml` at the project root, the directory
+//! holding a project's runs (`runs/<project>/project.toml`):
+//!
+//! ```toml
+//! tags = ["sweep-3"]
+//!
+//! [objective]
+//! metric = "val/loss"
+//! mode = "min"
+//!
+//! [metric_groups]
+//! losses = ["train/loss", "val/loss"]
+//!
+//! [[alerts]]
+//! metric = "train/loss"
+//! threshold = 5.0
+//! comparison = "gt"
+//!
+//! [cost]
+//! gpu_hour_price = 2.5
+//! budget = 200.0
+//!
+//! [digest]
+//! schedule = "0 9 * * 1-5"
+//! webhook = "https://hooks.slack.com/services/..."
+//! ```
+
+use anyhow::{Context, Result, bail};
+use og_core::error::OgError;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cost::Pricing;
+use crate::cron::Schedule;
+use crate::digest::DigestConfig;
+use crate::presets::{GraphPreset, array_closed, parse_strings, strip_comment};
+use crate::run_meta::RunMeta;
+
+pub const FILE_NAME: &str = "project.toml";
+
+/// Environment variable the agent daemon reads its alert rules from (JSON list).
+pub const ALERT_RULES_ENV_VAR: &str = "OG_ALERT_RULES";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectiveMode {
+    Min,
+    Max,
+}
+
+/// The metric a project optimises; the default for `og leaderboard` and `og compare`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Objective {
+    pub metric: String,
+    pub mode: ObjectiveMode,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ProjectConfig {
+    /// Tags every new run starts with.
+    pub tags: Vec<String>,
+    pub objective: Option<Objective>,
+    /// Named metric lists, offered as `--graph preset:<name>` next to the user's presets.
+    pub metric_groups: Vec<GraphPreset>,
+    /// Alert rules in the daemon's `OG_ALERT_RULES` shape.
+    pub alerts: Vec<Map<String, Value>>,
+    /// Dollars per GPU-hour and per-run budget for spend estimates.
+    pub cost: Pricing,
+    /// When and where `og digest` sends the project's digest.
+    pub digest: Option<DigestConfig>,
+}
+
+impl ProjectConfig {
+    /// Add the project's default tags to a new run's metadata.
+    pub fn apply_to_new_run(&self, meta: &mut RunMeta) {
+        for tag in &self.tags {
+            if !meta.tags.contains(tag) {
+                meta.tags.push(tag.clone());
+            }
+        }
+    }
+}
+
+/// `project.toml` governing `path`: the one in `path` itself (a project root)
+/// or in its parent (a run dir). Event files start the search at their run dir.
+pub fn find_file(path: &Path) -> Option<PathBuf> {
+    let start = if path.is_file() { path.parent()? } else { path };
+    [Some(start), start.parent()]
+        .into_iter()
+        .flatten()
+        .map(|dir| dir.join(FILE_NAME))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Config for `path`; no `project.toml` means no project defaults.
+pub fn load(path: &Path) -> Result<ProjectConfig> {
+    match find_file(path) {
+        Some(file) => {
+            let raw =
+                fs::read_to_string(&file).with_context(|| format!("reading {}", file.display()))?;
+            parse(&raw).with_context(|| OgError::parse_failed(file.display()))
+        }
+        None => Ok(ProjectConfig::default()),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Section {
+    Root,
+    Objective,
+    MetricGroups,
+    Alert,
+    Cost,
+    Digest,
+}
+
+/// Parse the subset of TOML `project.toml` uses. Unlike the user config,
+/// unknown keys are errors, since a typo would silently drop a default.
+pub fn parse(text: &str) -> Result<ProjectConfig> {
+    let mut config = ProjectConfig::default();
+    let mut objective_metric: Option<String> = None;
+    let mut objective_mode: Option<ObjectiveMode> = None;
+    let mut digest_schedule: Option<Schedule> = None;
+    let mut digest = DigestFields::default();
+    let mut section = Section::Root;
+    let mut lines = text.lines().enumerate();
+    while let Some((idx, line)) = lines.next() {
+        let line = strip_comment(line).trim().to_string();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix("[[").and_then(|l| l.strip_suffix("]]")) {
+            if name.trim() != "alerts" {
+                bail!("line {}: unknown table [[{}]]", idx + 1, name.trim());
+            }
+            config.alerts.push(Map::new());
+            section = Section::Alert;
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = match name.trim() {
+                "objective" => Section::Objective,
+                "metric_groups" => Section::MetricGroups,
+                "cost" => Section::Cost,
+                "digest" => {
+                    digest.present = true;
+                    Section::Digest
+                }
+                other => bail!("line {}: unknown table [{other}]", idx + 1),
+            };
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            bail!("line {}: expected key = value", idx + 1);
+        };
+        let key = key.trim().trim_matches('"');
+        let mut value = value.trim().to_string();
+        while value.starts_with('[') && !array_closed(&value) {
+            let Some((_, next)) = lines.next() else {
+                bail!("line {}: unterminated array for '{key}'", idx + 1);
+            };
+            value.push(' ');
+            value.push_str(strip_comment(next).trim());
+        }
+        let at = || format!("line {}: '{key}'", idx + 1);
+        match (section, key) {
+            (Section::Root, "tags") => config.tags = parse_strings(&value).with_context(at)?,
+            (Section::Objective, "metric") => {
+                objective_metric = Some(parse_string(&value).with_context(at)?)
+            }
+            (Section::Objective, "mode") => {
+                objective_mode = Some(match parse_string(&value).with_context(at)?.as_str() {
+                    "min" => ObjectiveMode::Min,
+                    "max" => ObjectiveMode::Max,
+                    other => bail!("{}: expected \"min\" or \"max\", found \"{other}\"", at()),
+                })
+            }
+            (Section::MetricGroups, name) => {
+                let patterns = parse_strings(&value).with_context(at)?;
+                if patterns.is_empty() {
+                    bail!("{}: metric group has no patterns", at());
+                }
+                config.metric_groups.push(GraphPreset {
+                    name: name.to_string(),
+                    patterns,
+                });
+            }
+            (Section::Alert, key) => {
+                let rule = config.alerts.last_mut().expect("alert table opened");
+                rule.insert(key.to_string(), parse_scalar(&value).with_context(at)?);
+            }
+            (Section::Cost, "gpu_hour_price") => {
+                config.cost.gpu_hour_price = Some(parse_amount(&value).with_context(at)?)
+            }
+            (Section::Cost, "budget") => {
+                config.cost.budget = Some(parse_amount(&value).with_context(at)?)
+            }
+            (Section::Digest, "schedule") => {
+                let schedule = parse_string(&value).with_context(at)?;
+                digest_schedule = Some(Schedule::parse(&schedule).with_context(at)?)
+            }
+            (Section::Digest, "webhook") => {
+                digest.webhook = Some(parse_string(&value).with_context(at)?)
+            }
+            (Section::Digest, "smtp") => digest.smtp = Some(parse_string(&value).with_context(at)?),
+            (Section::Digest, "from") => digest.from = Some(parse_string(&value).with_context(at)?),
+            (Section::Digest, "to") => digest.to = parse_strings(&value).with_context(at)?,
+            (_, key) => bail!("line {}: unknown key '{key}'", idx + 1),
+        }
+    }
+
+    match (objective_metric, objective_mode) {
+        (Some(metric), mode) => {
+            config.objective = Some(Objective {
+                metric,
+                mode: mode.unwrap_or(ObjectiveMode::Max),
+            })
+        }
+        (None, Some(_)) => bail!("[objective] sets a mode but no metric"),
+        (None, None) => {}
+    }
+    if digest.present {
+        let Some(schedule) = digest_schedule else {
+            bail!("[digest] has no schedule");
+        };
+        if digest.webhook.is_none() && digest.smtp.is_none() {
+            bail!("[digest] needs a webhook or an smtp server to send to");
+        }
+        if digest.smtp.is_some() && (digest.from.is_none() || digest.to.is_empty()) {
+            bail!("[digest] smtp needs from and to addresses");
+        }
+        config.digest = Some(DigestConfig {
+            schedule,
+            webhook: digest.webhook,
+            smtp: digest.smtp,
+            from: digest.from,
+            to: digest.to,
+        });
+    }
+    for (idx, rule) in config.alerts.iter().enumerate() {
+        if !rule.get("metric").is_some_and(Value::is_string) {
+            bail!("[[alerts]] #{} has no metric", idx + 1);
+        }
+    }
+    Ok(config)
+}
+
+/// `[digest]` keys as they are read, checked once the file is parsed.
+#[derive(Default)]
+struct DigestFields {
+    present: bool,
+    webhook: Option<String>,
+    smtp: Option<String>,
+    from: Option<String>,
+    to: Vec<String>,
+}
+
+fn parse_string(value: &str) -> Result<String> {
+    if value.trim_start().starts_with('[') {
+        bail!("expected a string, found an array");
+    }
+    let mut strings = parse_strings(value)?;
+    match strings.len() {
+        1 => Ok(strings.remove(0)),
+        _ => bail!("expected one quoted string"),
+    }
+}
+
+/// A non-negative number of dollars.
+fn parse_amount(value: &str) -> Result<f64> {
+    match parse_scalar(value)?.as_f64() {
+        Some(amount) if amount >= 0.0 => Ok(amount),
+        _ => bail!("expected a non-negative number"),
+    }
+}
+
+/// A quoted string, `true` / `false`, or a number.
+fn parse_scalar(value: &str) -> Result<Value> {
+    let value = value.trim();
+    if value.starts_with('"') || value.starts_with('\'') {
+        return Ok(Value::String(parse_string(value)?));
+    }
+    match value {
+        "true" => return Ok(Value::Bool(true)),
+        "false" => return Ok(Value::Bool(false)),
+        _ => {}
+    }
+    let number: f64 = value
+        .replace('_', "")
+        .parse()
+        .with_context(|| format!("expected a string, boolean or number, found '{value}'"))?;
+    serde_json::Number::from_f64(number)
+        .map(Value::Number)
+        .context("number must be finite")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FILE_NAME, ObjectiveMode, find_file, parse};
+    use crate::cron::Schedule;
+    use crate::run_meta::RunMeta;
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn parses_tags_objective_groups_and_alerts() {
+        let config = parse(
+            r#"
+tags = ["sweep-3", "baseline"]
+
+[objective]
+metric = "val/loss"   # lower is better
+mode = "min"
+
+[metric_groups]
+losses = [
+  "train/loss",
+  "val/loss",
+]
+
+[[alerts]]
+metric = "train/loss"
+threshold = 5.0
+comparison = "gt"
+
+[[alerts]]
+type = "stall"
+metric = "val/loss"
+window = 50
+
+[cost]
+gpu_hour_price = 2.5
+budget = 1_000
+
+[digest]
+schedule = "0 9 * * 1-5"
+smtp = "smtps://mail.example.com"
+from = "og@example.com"
+to = ["lead@example.com"]
+"#,
+        )
+        .expect("parse project config");
+        assert_eq!(config.tags, vec!["sweep-3", "baseline"]);
+        let objective = config.objective.as_ref().expect("objective");
+        assert_eq!(objective.metric, "val/loss");
+        assert_eq!(objective.mode, ObjectiveMode::Min);
+        assert_eq!(config.metric_groups[0].name, "losses");
+        assert_eq!(
+            config.metric_groups[0].patterns,
+            vec!["train/loss", "val/loss"]
+        );
+        assert_eq!(config.alerts.len(), 2);
+        assert_eq!(config.alerts[0]["threshold"], 5.0);
+        assert_eq!(config.alerts[1]["window"], 50.0);
+        assert_eq!(config.cost.gpu_hour_price, Some(2.5));
+        assert_eq!(config.cost.budget, Some(1000.0));
+        let digest = config.digest.as_ref().expect("digest");
+        assert_eq!(digest.schedule, Schedule::parse("0 9 * * 1-5").unwrap());
+        assert_eq!(digest.webhook, None);
+        assert_eq!(digest.to, vec!["lead@example.com"]);
+
+        let mut meta = RunMeta {
+            tags: vec!["baseline".to_string()],
+            ..RunMeta::default()
+        };
+        config.apply_to_new_run(&mut meta);
+        assert_eq!(meta.tags, vec!["baseline", "sweep-3"]);
+
+        assert!(parse("tag = [\"typo\"]").is_err());
+        assert!(parse("[objective]\nmode = \"min\"").is_err());
+        assert!(parse("[objective]\nmetric = \"x\"\nmode = \"best\"").is_err());
+        assert!(parse("[[alerts]]\nthreshold = 1").is_err());
+        assert!(parse("[[alerts]]\nmetric = \"x\"\nthreshold = high").is_err());
+        assert!(parse("[cost]\nbudget = -5").is_err());
+        assert!(parse("[digest]\nwebhook = \"http://x\"").is_err());
+        assert!(parse("[digest]\nschedule = \"@daily\"").is_err());
+        assert!(parse("[digest]\nschedule = \"9am\"\nwebhook = \"http://x\"").is_err());
+        assert!(parse("[digest]\nschedule = \"@daily\"\nsmtp = \"smtp://x\"").is_err());
+    }
+
+    #[test]
+    fn find_file_checks_the_path_and_its_parent() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let project = std::env::temp_dir().join(format!("ogtui-project-{nonce}"));
+        let run_dir = project.join("run-a");
+        fs::create_dir_all(&run_dir).expect("create run dir");
+        assert_eq!(find_file(&run_dir), None);
+
+        fs::write(project.join(FILE_NAME), "tags = [\"x\"]\n").expect("write config");
+        let event_file = run_dir.join("events.out.tfevents.1.host");
+        fs::write(&event_file, b"").expect("write events");
+        assert_eq!(find_file(&project), Some(project.join(FILE_NAME)));
+        assert_eq!(find_file(&run_dir), Some(project.join(FILE_NAME)));
+        assert_eq!(find_file(&event_file), Some(project.join(FILE_NAME)));
+        fs::remove_dir_all(&project).expect("cleanup");
+    }
+}
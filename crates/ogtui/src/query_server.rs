@@ -0,0 +1,208 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Environment variable telling the agent daemon where to send queries.
+pub const SOCKET_ENV_VAR: &str = "OG_QUERY_SOCKET";
+
+/// Read-only commands the agent may run; nothing here writes to a run.
+pub const QUERY_COMMANDS: &[&str] = &[
+    "list",
+    "get",
+    "compare",
+    "diff",
+    "leaderboard",
+    "stats",
+    "search",
+    "tail",
+];
+
+/// Longest request line accepted; real requests are an argv of a few words.
+pub const MAX_REQUEST_BYTES: u64 = 1 << 20;
+
+/// Runs an allowed `og` argv (without the program name) and returns its JSON payload.
+pub type QueryFn = dyn Fn(&[String]) -> Result<Value> + Send + Sync;
+
+/// Query socket the TUI serves next to a daemon socket (`ogd.sock` -> `ogd.sock.query`).
+pub fn socket_path_for(daemon_socket: &Path) -> PathBuf {
+    let mut path = daemon_socket.as_os_str().to_owned();
+    path.push(".query");
+    PathBuf::from(path)
+}
+
+/// Answer one request line. Same JSON-lines shape as the daemon protocol:
+/// `{"type": "query", "argv": ["list", "runs", "--project", "p"]}` returns
+/// `{"ok": true, "command": "list.runs", "data": {...}}`.
+pub fn handle_request(line: &str, query: &QueryFn) -> Value {
+    let payload: Value = match serde_json::from_str(line) {
+        Ok(payload) => payload,
+        Err(e) => return error(format!("invalid_json: {e}")),
+    };
+    match payload.get("type").and_then(Value::as_str) {
+        Some("ping") => serde_json::json!({"ok": true, "type": "pong"}),
+        Some("query") => {
+            let Some(argv) = payload
+                .get("argv")
+                .and_then(Value::as_array)
+                .and_then(|argv| {
+                    argv.iter()
+                        .map(|arg| arg.as_str().map(str::to_string))
+                        .collect::<Option<Vec<String>>>()
+                })
+            else {
+                return error("missing_argv".to_string());
+            };
+            match argv.first() {
+                Some(command) if QUERY_COMMANDS.contains(&command.as_str()) => {}
+                Some(command) => return error(format!("command_not_allowed: {command}")),
+                None => return error("missing_argv".to_string()),
+            }
+            match query(&argv) {
+                Ok(Value::Object(mut output)) => {
+                    output.insert("ok".to_string(), Value::Bool(true));
+                    Value::Object(output)
+                }
+                Ok(data) => serde_json::json!({"ok": true, "data": data}),
+                Err(e) => error(format!("{e:#}")),
+            }
+        }
+        _ => error("unknown_type".to_string()),
+    }
+}
+
+fn error(message: String) -> Value {
+    serde_json::json!({"ok": false, "error": message})
+}
+
+fn serve_connection(stream: UnixStream, query: &QueryFn) -> Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let read = (&mut reader)
+            .take(MAX_REQUEST_BYTES + 1)
+            .read_until(b'\n', &mut line)?;
+        if read == 0 {
+            return Ok(());
+        }
+        let oversized = line.len() as u64 > MAX_REQUEST_BYTES;
+        let response = if oversized {
+            error("request_too_large".to_string())
+        } else {
+            match std::str::from_utf8(&line).map(str::trim) {
+                Ok("") => continue,
+                Ok(text) => handle_request(text, query),
+                Err(_) => error("invalid_utf8".to_string()),
+            }
+        };
+        let mut response = response.to_string();
+        response.push('\n');
+        writer.write_all(response.as_bytes())?;
+        if oversized {
+            // The rest of the line is still unread; there is no way to resync.
+            return Ok(());
+        }
+    }
+}
+
+fn bind(path: &Path) -> Result<UnixListener> {
+    if path.exists() {
+        std::fs::remove_file(path)
+            .with_context(|| format!("removing stale socket {}", path.display()))?;
+    }
+    UnixListener::bind(path).with_context(|| format!("binding {}", path.display()))
+}
+
+fn accept_loop(listener: UnixListener, query: Arc<QueryFn>) {
+    for stream in listener.incoming().flatten() {
+        let query = Arc::clone(&query);
+        std::thread::spawn(move || serve_connection(stream, query.as_ref()));
+    }
+}
+
+/// Bind `path` (replacing a stale socket) and answer queries on background
+/// threads for the life of the process.
+pub fn spawn(path: &Path, query: Arc<QueryFn>) -> Result<()> {
+    let listener = bind(path)?;
+    std::thread::spawn(move || accept_loop(listener, query));
+    Ok(())
+}
+
+/// Like `spawn`, but serve from the calling thread until the process exits.
+pub fn serve(path: &Path, query: Arc<QueryFn>) -> Result<()> {
+    accept_loop(bind(path)?, query);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{handle_request, socket_path_for, spawn};
+    use serde_json::{Value, json};
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn echo(argv: &[String]) -> anyhow::Result<Value> {
+        if argv.iter().any(|arg| arg == "--fail") {
+            anyhow::bail!("run 'x' not found");
+        }
+        Ok(json!({"command": argv.join("."), "data": {"argv": argv}}))
+    }
+
+    #[test]
+    fn only_read_only_commands_are_served() {
+        let ok = handle_request(r#"{"type": "query", "argv": ["list", "runs"]}"#, &echo);
+        assert_eq!(ok["ok"], true);
+        assert_eq!(ok["command"], "list.runs");
+
+        let denied = handle_request(r#"{"type": "query", "argv": ["fork", "a"]}"#, &echo);
+        assert_eq!(
+            denied,
+            json!({"ok": false, "error": "command_not_allowed: fork"})
+        );
+
+        let failed = handle_request(
+            r#"{"type": "query", "argv": ["get", "run", "--fail"]}"#,
+            &echo,
+        );
+        assert_eq!(failed["error"], "run 'x' not found");
+        assert_eq!(handle_request("{", &echo)["ok"], false);
+        let listed = |_: &[String]| Ok(json!(["a", "b"]));
+        assert_eq!(
+            handle_request(r#"{"type": "query", "argv": ["list", "runs"]}"#, &listed),
+            json!({"ok": true, "data": ["a", "b"]})
+        );
+        assert_eq!(handle_request(r#"{"type": "ping"}"#, &echo)["type"], "pong");
+        assert_eq!(
+            socket_path_for(Path::new("/tmp/ogd.sock")),
+            Path::new("/tmp/ogd.sock.query")
+        );
+    }
+
+    #[test]
+    fn spawned_server_answers_json_lines() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("ogtui-query-{nonce}.sock"));
+        spawn(&path, Arc::new(echo)).expect("spawn server");
+
+        let mut stream = UnixStream::connect(&path).expect("connect");
+        stream
+            .write_all(b"{\"type\": \"query\", \"argv\": [\"search\", \"metrics\"]}\n")
+            .expect("send");
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).expect("read");
+        let response: Value = serde_json::from_str(&line).expect("json response");
+        assert_eq!(response["command"], "search.metrics");
+
+        std::fs::remove_file(&path).ok();
+    }
+}
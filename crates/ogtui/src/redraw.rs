@@ -0,0 +1,99 @@
+use std::time::{Duration, Instant};
+
+/// Frame cap used when `--max-fps` is not given.
+pub const DEFAULT_MAX_FPS: u32 = 30;
+
+/// Longest the screen goes without a redraw, so relative times ("3s ago")
+/// and anything else derived from the clock keep moving while idle.
+pub const IDLE_REDRAW: Duration = Duration::from_secs(1);
+
+/// Decides when the event loop redraws: only after something marked the
+/// screen dirty, and at most `max_fps` times a second.
+#[derive(Debug)]
+pub struct RedrawGate {
+    dirty: bool,
+    last_draw: Option<Instant>,
+    min_interval: Duration,
+}
+
+impl RedrawGate {
+    pub fn new(max_fps: u32) -> Self {
+        Self {
+            dirty: true,
+            last_draw: None,
+            min_interval: Duration::from_secs(1) / max_fps.max(1),
+        }
+    }
+
+    pub fn mark(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn mark_if(&mut self, changed: bool) {
+        self.dirty |= changed;
+    }
+
+    /// Draw on the next pass regardless of the frame cap (e.g. after a
+    /// resize, when the old frame no longer fits the terminal).
+    pub fn force(&mut self) {
+        self.dirty = true;
+        self.last_draw = None;
+    }
+
+    pub fn should_draw(&self, now: Instant) -> bool {
+        let Some(last) = self.last_draw else {
+            return true;
+        };
+        let since = now.saturating_duration_since(last);
+        (self.dirty && since >= self.min_interval) || since >= IDLE_REDRAW
+    }
+
+    pub fn drawn(&mut self, now: Instant) {
+        self.dirty = false;
+        self.last_draw = Some(now);
+    }
+
+    /// How long to wait for input: `tick` normally, less when a throttled
+    /// frame is due sooner.
+    pub fn timeout(&self, now: Instant, tick: Duration) -> Duration {
+        match self.last_draw {
+            Some(last) if self.dirty => (last + self.min_interval)
+                .saturating_duration_since(now)
+                .min(tick),
+            _ => tick,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IDLE_REDRAW, RedrawGate};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn draws_only_when_dirty_and_within_the_frame_cap() {
+        let start = Instant::now();
+        let ms = |n: u64| start + Duration::from_millis(n);
+        let tick = Duration::from_millis(100);
+        let mut gate = RedrawGate::new(20);
+
+        assert!(gate.should_draw(start));
+        gate.drawn(start);
+        assert!(!gate.should_draw(ms(10)));
+        assert_eq!(gate.timeout(ms(10), tick), tick);
+
+        gate.mark();
+        assert!(!gate.should_draw(ms(10)), "capped at one frame per 50ms");
+        assert_eq!(gate.timeout(ms(10), tick), Duration::from_millis(40));
+        assert!(gate.should_draw(ms(50)));
+        gate.drawn(ms(50));
+
+        gate.mark_if(false);
+        assert!(!gate.should_draw(ms(500)));
+        assert!(gate.should_draw(ms(50) + IDLE_REDRAW));
+
+        gate.drawn(ms(60));
+        gate.force();
+        assert!(gate.should_draw(ms(61)), "forced frames skip the cap");
+    }
+}
@@ -0,0 +1,238 @@
+//! Baseline-vs-candidate check behind `og regress`: the two runs' values at
+//! the steps both logged, judged over the trailing window so a nightly CI
+//! job fails on a commit that trains worse, not on one noisy eval.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Default number of trailing matched steps compared.
+pub const DEFAULT_WINDOW: usize = 10;
+
+/// How much worse the candidate may be before it counts as a regression.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum Tolerance {
+    /// Fraction of the baseline value; `1%` parses to 0.01.
+    Relative(f64),
+    Absolute(f64),
+}
+
+impl Tolerance {
+    fn allowed(self, baseline: f64) -> f64 {
+        match self {
+            Tolerance::Relative(fraction) => fraction * baseline.abs(),
+            Tolerance::Absolute(amount) => amount,
+        }
+    }
+}
+
+impl FromStr for Tolerance {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let (number, percent) = match text.strip_suffix('%') {
+            Some(number) => (number.trim(), true),
+            None => (text, false),
+        };
+        let value: f64 = number
+            .parse()
+            .map_err(|_| format!("'{text}' is not a number or percentage"))?;
+        if !value.is_finite() || value < 0.0 {
+            return Err(format!("'{text}' must be zero or positive"));
+        }
+        Ok(if percent {
+            Tolerance::Relative(value / 100.0)
+        } else {
+            Tolerance::Absolute(value)
+        })
+    }
+}
+
+/// One matched step.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct StepPair {
+    pub step: i64,
+    pub baseline: f64,
+    pub candidate: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Verdict {
+    /// Steps both runs logged a value at.
+    pub matched_steps: usize,
+    /// Trailing matched steps the means below cover.
+    pub window: usize,
+    pub baseline_mean: f64,
+    pub candidate_mean: f64,
+    /// How much worse the candidate's mean is; negative when it is better.
+    pub worse_by: f64,
+    /// Largest `worse_by` the tolerance allows.
+    pub allowed: f64,
+    /// Window steps where the candidate is worse than the baseline.
+    pub worse_steps: usize,
+    /// Window step where the candidate is furthest behind.
+    pub worst: Option<StepPair>,
+    /// The candidate logged NaN/inf where the baseline has a value.
+    pub non_finite: bool,
+    pub regressed: bool,
+}
+
+/// Compare two `(step, value)` series at the steps they share. The
+/// candidate regressed when it went non-finite, or when its window mean is
+/// worse than the tolerance allows and it is behind at most of the window's
+/// steps,
+/// so a single bad point cannot fail the check on its own. Returns None
+/// when the runs share no step with a finite baseline value.
+pub fn check(
+    baseline: &[(f64, f64)],
+    candidate: &[(f64, f64)],
+    higher_is_better: bool,
+    tolerance: Tolerance,
+    window: usize,
+) -> Option<Verdict> {
+    // A resumed run logs some steps twice; its last value is the one kept.
+    let by_step = |series: &[(f64, f64)]| -> BTreeMap<i64, f64> {
+        series
+            .iter()
+            .filter(|(step, _)| step.is_finite())
+            .map(|(step, value)| (*step as i64, *value))
+            .collect()
+    };
+    let candidate = by_step(candidate);
+    let matched: Vec<StepPair> = by_step(baseline)
+        .into_iter()
+        .filter(|(_, baseline)| baseline.is_finite())
+        .filter_map(|(step, baseline)| {
+            Some(StepPair {
+                step,
+                baseline,
+                candidate: *candidate.get(&step)?,
+            })
+        })
+        .collect();
+    if matched.is_empty() {
+        return None;
+    }
+    let non_finite = matched.iter().any(|pair| !pair.candidate.is_finite());
+    let tail = &matched[matched.len().saturating_sub(window.max(1))..];
+    // Positive when the candidate is behind.
+    let worse = |baseline: f64, candidate: f64| {
+        if higher_is_better {
+            baseline - candidate
+        } else {
+            candidate - baseline
+        }
+    };
+    let mean = |values: &mut dyn Iterator<Item = f64>| {
+        let (sum, count) = values.fold((0.0, 0usize), |(sum, count), v| (sum + v, count + 1));
+        sum / count as f64
+    };
+    let baseline_mean = mean(&mut tail.iter().map(|pair| pair.baseline));
+    let candidate_mean = mean(&mut tail.iter().map(|pair| pair.candidate));
+    let worse_by = worse(baseline_mean, candidate_mean);
+    let allowed = tolerance.allowed(baseline_mean);
+    let worse_steps = tail
+        .iter()
+        .filter(|pair| !pair.candidate.is_finite() || worse(pair.baseline, pair.candidate) > 0.0)
+        .count();
+    let worst = tail
+        .iter()
+        .filter(|pair| pair.candidate.is_finite())
+        .max_by(|a, b| worse(a.baseline, a.candidate).total_cmp(&worse(b.baseline, b.candidate)))
+        .copied();
+    let regressed = non_finite || (worse_by > allowed && worse_steps * 2 > tail.len());
+    Some(Verdict {
+        matched_steps: matched.len(),
+        window: tail.len(),
+        baseline_mean,
+        candidate_mean,
+        worse_by,
+        allowed,
+        worse_steps,
+        worst,
+        non_finite,
+        regressed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Tolerance, check};
+
+    fn series(points: &[(i64, f64)]) -> Vec<(f64, f64)> {
+        points
+            .iter()
+            .map(|(step, value)| (*step as f64, *value))
+            .collect()
+    }
+
+    #[test]
+    fn tolerance_parses_percentages_and_absolute_amounts() {
+        assert_eq!("1%".parse(), Ok(Tolerance::Relative(0.01)));
+        assert_eq!(" 0.05 ".parse(), Ok(Tolerance::Absolute(0.05)));
+        assert!("-1%".parse::<Tolerance>().is_err());
+        assert!("lots".parse::<Tolerance>().is_err());
+    }
+
+    #[test]
+    fn candidate_regresses_only_when_consistently_worse_beyond_tolerance() {
+        let baseline = series(&[(0, 4.0), (100, 3.0), (200, 2.5), (300, 2.0), (400, 1.9)]);
+        // Logged every 50 steps; only the baseline's steps are compared.
+        let slower = series(&[
+            (0, 4.0),
+            (50, 3.8),
+            (100, 3.2),
+            (150, 3.0),
+            (200, 2.8),
+            (250, 2.6),
+            (300, 2.2),
+            (350, 2.1),
+            (400, 2.05),
+        ]);
+        let verdict =
+            check(&baseline, &slower, false, Tolerance::Relative(0.01), 3).expect("shared steps");
+        assert_eq!((verdict.matched_steps, verdict.window), (5, 3));
+        assert_eq!(verdict.worse_steps, 3);
+        assert_eq!(verdict.worst.map(|pair| pair.step), Some(200));
+        assert!(verdict.regressed);
+
+        // Within 15%, and a single noisy step does not fail the check.
+        assert!(
+            !check(&baseline, &slower, false, Tolerance::Relative(0.15), 3)
+                .unwrap()
+                .regressed
+        );
+        let one_spike = series(&[(0, 4.0), (100, 3.0), (200, 2.4), (300, 2.6), (400, 1.8)]);
+        assert!(
+            !check(&baseline, &one_spike, false, Tolerance::Absolute(0.0), 3)
+                .unwrap()
+                .regressed
+        );
+        // The same gap reads as better when higher is better.
+        assert!(
+            !check(&baseline, &slower, true, Tolerance::Absolute(0.0), 3)
+                .unwrap()
+                .regressed
+        );
+    }
+
+    #[test]
+    fn non_finite_candidates_regress_and_disjoint_runs_have_no_verdict() {
+        let baseline = series(&[(0, 4.0), (100, 3.0), (200, 2.5)]);
+        let diverged = series(&[(0, 4.0), (100, f64::NAN), (200, 2.4)]);
+        let verdict = check(&baseline, &diverged, false, Tolerance::Relative(0.5), 1).unwrap();
+        assert!(verdict.non_finite && verdict.regressed);
+        assert_eq!(
+            check(
+                &baseline,
+                &series(&[(50, 3.0)]),
+                false,
+                Tolerance::Absolute(0.0),
+                10
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use prost::Message;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::tfrecord::{Event, Summary, SummaryValue, decode_records, encode_record};
+
+/// How each bucket of `every` consecutive points collapses into one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RollupMode {
+    /// Keep the last point of each bucket.
+    Every,
+    Mean,
+    Min,
+    Max,
+}
+
+#[derive(Debug, Clone)]
+pub struct RollupOptions {
+    pub every: usize,
+    pub mode: RollupMode,
+    /// Tags kept at full resolution; a trailing `*` matches a prefix.
+    pub keep: Vec<String>,
+}
+
+impl RollupOptions {
+    fn keeps(&self, tag: &str) -> bool {
+        self.keep
+            .iter()
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => tag.starts_with(prefix),
+                None => tag == pattern,
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct RollupStats {
+    pub points_before: usize,
+    pub points_after: usize,
+}
+
+struct Point {
+    record: usize,
+    step: i64,
+    wall_time: f64,
+    value: f32,
+}
+
+/// Collapse consecutive points into buckets of `every`, each emitted at the
+/// position (record, step, wall time) of the bucket's last point.
+fn roll_up(points: &[Point], every: usize, mode: RollupMode) -> Vec<Point> {
+    points
+        .chunks(every.max(1))
+        .map(|bucket| {
+            let last = &bucket[bucket.len() - 1];
+            let values = bucket.iter().map(|p| p.value);
+            let value = match mode {
+                RollupMode::Every => last.value,
+                RollupMode::Mean => values.sum::<f32>() / bucket.len() as f32,
+                RollupMode::Min => values.fold(f32::INFINITY, f32::min),
+                RollupMode::Max => values.fold(f32::NEG_INFINITY, f32::max),
+            };
+            Point {
+                record: last.record,
+                step: last.step,
+                wall_time: last.wall_time,
+                value,
+            }
+        })
+        .collect()
+}
+
+/// Scalar-only record that re-encodes byte for byte, so rewriting it loses nothing.
+fn is_rewritable(data: &[u8], event: &Event) -> bool {
+    event.summary.as_ref().is_some_and(|summary| {
+        !summary.value.is_empty() && summary.value.iter().all(|v| v.simple_value.is_some())
+    }) && event.encode_to_vec() == data
+}
+
+/// Roll up the scalar tags of one event file. Records carrying anything other
+/// than plain scalars, and whitelisted tags, are copied through untouched.
+/// Returns the rewritten file path (compressed inputs are written back
+/// uncompressed, for the caller to re-compress), or `None` on a dry run.
+pub fn rollup_file(
+    path: &Path,
+    options: &RollupOptions,
+    dry_run: bool,
+) -> Result<(RollupStats, Option<PathBuf>)> {
+    let bytes = crate::compression::read(path)?;
+    let (records, _) = decode_records(&bytes, true)?;
+
+    let mut raw: Vec<Option<&[u8]>> = Vec::with_capacity(records.len());
+    let mut series: BTreeMap<String, Vec<Point>> = BTreeMap::new();
+    let mut kept: BTreeMap<usize, Vec<SummaryValue>> = BTreeMap::new();
+    let mut stats = RollupStats::default();
+    for (idx, (data, event)) in records.iter().enumerate() {
+        if !is_rewritable(data, event) {
+            raw.push(Some(*data));
+            continue;
+        }
+        raw.push(None);
+        for value in event.summary.iter().flat_map(|s| &s.value) {
+            stats.points_before += 1;
+            if options.keeps(&value.tag) {
+                stats.points_after += 1;
+                kept.entry(idx).or_default().push(value.clone());
+            } else {
+                series.entry(value.tag.clone()).or_default().push(Point {
+                    record: idx,
+                    step: event.step,
+                    wall_time: event.wall_time,
+                    value: value.simple_value.unwrap_or_default(),
+                });
+            }
+        }
+    }
+
+    let mut emitted: BTreeMap<usize, Vec<(String, Point)>> = BTreeMap::new();
+    for (tag, points) in &series {
+        for point in roll_up(points, options.every, options.mode) {
+            stats.points_after += 1;
+            emitted
+                .entry(point.record)
+                .or_default()
+                .push((tag.clone(), point));
+        }
+    }
+    if dry_run {
+        return Ok((stats, None));
+    }
+
+    let mut out = Vec::with_capacity(bytes.len());
+    for (idx, (_, event)) in records.iter().enumerate() {
+        if let Some(data) = raw[idx] {
+            out.extend(encode_record(data));
+            continue;
+        }
+        let mut values = kept.remove(&idx).unwrap_or_default();
+        values.extend(
+            emitted
+                .remove(&idx)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(tag, point)| SummaryValue {
+                    tag,
+                    simple_value: Some(point.value),
+                    histo: None,
+                    audio: None,
+                    tensor: None,
+                    metadata: None,
+                }),
+        );
+        if values.is_empty() {
+            continue;
+        }
+        let rewritten = Event {
+            wall_time: event.wall_time,
+            step: event.step,
+            file_version: None,
+            summary: Some(Summary { value: values }),
+        };
+        out.extend(encode_record(&rewritten.encode_to_vec()));
+    }
+
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .context("event file name is not valid UTF-8")?;
+    let target = path.with_file_name(crate::compression::strip_extension(name));
+    // Not named like an event file, so an interrupted rewrite is never loaded.
+    let tmp = path.with_file_name(format!("og-rollup-{}.tmp", std::process::id()));
+    fs::write(&tmp, out).with_context(|| format!("writing {}", tmp.display()))?;
+    // Keep the original mtime so the rewrite does not make the run look live.
+    if let Ok(modified) = fs::metadata(path).and_then(|meta| meta.modified()) {
+        fs::File::options()
+            .write(true)
+            .open(&tmp)
+            .and_then(|file| file.set_modified(modified))
+            .with_context(|| format!("setting mtime of {}", tmp.display()))?;
+    }
+    fs::rename(&tmp, &target).with_context(|| format!("replacing {}", target.display()))?;
+    if target != path {
+        fs::remove_file(path).with_context(|| format!("removing {}", path.display()))?;
+    }
+    Ok((stats, Some(target)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Point, RollupMode, RollupOptions, roll_up};
+
+    fn points(values: &[f32]) -> Vec<Point> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(idx, value)| Point {
+                record: idx,
+                step: idx as i64 + 1,
+                wall_time: idx as f64,
+                value: *value,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn buckets_aggregate_at_last_point_of_each_bucket() {
+        let input = points(&[4.0, 2.0, 6.0, 1.0, 9.0]);
+        let summarize = |mode| {
+            roll_up(&input, 2, mode)
+                .iter()
+                .map(|p| (p.step, p.value))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            summarize(RollupMode::Every),
+            vec![(2, 2.0), (4, 1.0), (5, 9.0)]
+        );
+        assert_eq!(
+            summarize(RollupMode::Mean),
+            vec![(2, 3.0), (4, 3.5), (5, 9.0)]
+        );
+        assert_eq!(
+            summarize(RollupMode::Max),
+            vec![(2, 4.0), (4, 6.0), (5, 9.0)]
+        );
+        assert_eq!(
+            summarize(RollupMode::Min),
+            vec![(2, 2.0), (4, 1.0), (5, 9.0)]
+        );
+    }
+
+    #[test]
+    fn keep_patterns_match_exact_tags_and_prefixes() {
+        let options = RollupOptions {
+            every: 10,
+            mode: RollupMode::Every,
+            keep: vec!["eval/*".to_string(), "lr".to_string()],
+        };
+        assert!(options.keeps("eval/acc"));
+        assert!(options.keeps("lr"));
+        assert!(!options.keeps("lr/warmup"));
+        assert!(!options.keeps("train/loss"));
+    }
+}
@@ -0,0 +1,226 @@
+//! The TUI's comparison view, opened with `:diff <run>`: the focused metric
+//! of the loaded run next to the same metric of a second run, either side by
+//! side or overlaid with their difference charted underneath. Both charts
+//! share one step window (`+` / `-` zoom, `<` / `>` pan) and one value
+//! axis, and the config and git differences `og diff` reports sit beside
+//! them. On the epoch axis (`e`) the window is in epochs, so runs with
+//! different batch sizes line up.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::env_capture;
+use crate::epoch::EpochScale;
+use crate::git_state::GitState;
+use crate::run_meta::{self, RunMeta};
+use crate::tfevents;
+
+/// Narrowest step window `+` zooms to.
+const MIN_WINDOW_STEPS: f64 = 4.0;
+/// Share of the window `<` / `>` move it by.
+const PAN_SHARE: f64 = 0.25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    SideBySide,
+    /// Both runs on one chart, with other minus loaded charted underneath
+    Overlay,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunDiff {
+    /// Run compared against the loaded one
+    pub path: PathBuf,
+    pub name: String,
+    /// tag → sorted (step, value) pairs of the compared run
+    pub scalars: BTreeMap<String, Vec<(f64, f64)>>,
+    /// How the compared run's steps map to epochs
+    pub epoch_scale: Option<EpochScale>,
+    /// `og diff` lines for git state and config, loaded run first
+    pub differences: Vec<String>,
+    pub layout: Layout,
+    /// Steps both charts show (None = every step either run logged)
+    pub window: Option<(f64, f64)>,
+}
+
+impl RunDiff {
+    /// Read `path` to compare against the run at `loaded`.
+    pub fn load(loaded: &Path, path: &Path, layout: Layout) -> Result<Self> {
+        let run = tfevents::load_run(path)
+            .with_context(|| format!("loading events from {}", path.display()))?;
+        let meta = run_meta::load(path)?;
+        let differences = differences(&run_meta::load(loaded)?, &meta);
+        let name = run_meta::run_dir_for(path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        Ok(Self {
+            path: path.to_path_buf(),
+            name,
+            epoch_scale: EpochScale::for_run(run.view(None), &meta),
+            scalars: run.view(None).clone(),
+            differences,
+            layout,
+            window: None,
+        })
+    }
+
+    pub fn toggle_layout(&mut self) {
+        self.layout = match self.layout {
+            Layout::SideBySide => Layout::Overlay,
+            Layout::Overlay => Layout::SideBySide,
+        };
+    }
+
+    /// Steps shown out of `full`: the zoom window, else all of it.
+    pub fn visible(&self, full: (f64, f64)) -> (f64, f64) {
+        self.window
+            .map(|(lo, hi)| (lo.max(full.0), hi.min(full.1)))
+            .filter(|(lo, hi)| lo < hi)
+            .unwrap_or(full)
+    }
+
+    /// Scale the window by `factor` around its middle (below 1 zooms in).
+    /// A window as wide as `full` goes back to showing everything.
+    pub fn zoom(&mut self, full: (f64, f64), factor: f64) {
+        let (lo, hi) = self.visible(full);
+        let width = ((hi - lo) * factor).max(MIN_WINDOW_STEPS);
+        if width >= full.1 - full.0 {
+            self.window = None;
+            return;
+        }
+        let lo = ((lo + hi) / 2.0 - width / 2.0)
+            .min(full.1 - width)
+            .max(full.0);
+        self.window = Some((lo, lo + width));
+    }
+
+    /// Move a zoomed window a quarter of its width; `direction` is -1 or 1.
+    pub fn pan(&mut self, full: (f64, f64), direction: f64) {
+        if self.window.is_none() {
+            return;
+        }
+        let (lo, hi) = self.visible(full);
+        let width = hi - lo;
+        let lo = (lo + width * PAN_SHARE * direction)
+            .min(full.1 - width)
+            .max(full.0);
+        self.window = Some((lo, lo + width));
+    }
+}
+
+/// `abc1234`, `abc1234 (dirty)`, or `unknown` without recorded git state.
+pub fn git_label(git: &Option<GitState>) -> String {
+    match git {
+        Some(git) if git.dirty => format!("{} (dirty)", git.short_commit()),
+        Some(git) => git.short_commit().to_string(),
+        None => "unknown".to_string(),
+    }
+}
+
+/// Git and config differences from `from` to `to`, as `og diff` prints them.
+pub fn differences(from: &RunMeta, to: &RunMeta) -> Vec<String> {
+    let mut lines = Vec::new();
+    let (git_from, git_to) = (git_label(&from.git), git_label(&to.git));
+    if git_from != git_to {
+        lines.push(format!("git: {git_from} -> {git_to}"));
+    }
+    lines.extend(env_capture::diff_maps(&from.config, &to.config).lines());
+    lines
+}
+
+/// First and last step logged by either series.
+pub fn span(a: &[(f64, f64)], b: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let firsts = a
+        .first()
+        .into_iter()
+        .chain(b.first())
+        .map(|(step, _)| *step);
+    let lasts = a.last().into_iter().chain(b.last()).map(|(step, _)| *step);
+    let lo = firsts.fold(f64::INFINITY, f64::min);
+    let hi = lasts.fold(f64::NEG_INFINITY, f64::max);
+    (lo <= hi).then_some((lo, hi.max(lo + 1.0)))
+}
+
+/// The points of a step-sorted series inside `lo..=hi`.
+pub fn clip(series: &[(f64, f64)], (lo, hi): (f64, f64)) -> &[(f64, f64)] {
+    let start = series.partition_point(|(step, _)| *step < lo);
+    let end = series.partition_point(|(step, _)| *step <= hi);
+    &series[start..end.max(start)]
+}
+
+/// `other - loaded` at each step of `other` within the loaded run's logged
+/// steps, reading the loaded run between its points by linear interpolation
+/// so runs that log at different steps still line up.
+pub fn delta(loaded: &[(f64, f64)], other: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    other
+        .iter()
+        .filter_map(|&(step, value)| Some((step, value - interpolate(loaded, step)?)))
+        .collect()
+}
+
+/// The value of a step-sorted series at `step`, linear between its points;
+/// None outside the steps it logged.
+pub fn interpolate(series: &[(f64, f64)], step: f64) -> Option<f64> {
+    let idx = series.partition_point(|(s, _)| *s < step);
+    let (s1, v1) = *series.get(idx)?;
+    if s1 == step {
+        return Some(v1);
+    }
+    let (s0, v0) = *series.get(idx.checked_sub(1)?)?;
+    Some(v0 + (v1 - v0) * (step - s0) / (s1 - s0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Layout, RunDiff, clip, delta, span};
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    fn diff() -> RunDiff {
+        RunDiff {
+            path: PathBuf::from("runs/b"),
+            name: "b".to_string(),
+            scalars: BTreeMap::new(),
+            epoch_scale: None,
+            differences: Vec::new(),
+            layout: Layout::SideBySide,
+            window: None,
+        }
+    }
+
+    #[test]
+    fn delta_interpolates_the_loaded_run_between_its_points() {
+        let loaded = [(0.0, 1.0), (10.0, 2.0)];
+        let other = [(0.0, 1.5), (5.0, 1.0), (10.0, 2.0), (15.0, 0.0)];
+        assert_eq!(
+            delta(&loaded, &other),
+            vec![(0.0, 0.5), (5.0, -0.5), (10.0, 0.0)]
+        );
+        assert_eq!(span(&loaded, &other), Some((0.0, 15.0)));
+        assert_eq!(clip(&other, (1.0, 10.0)), &[(5.0, 1.0), (10.0, 2.0)]);
+    }
+
+    #[test]
+    fn zoom_and_pan_stay_inside_the_logged_steps() {
+        let full = (0.0, 100.0);
+        let mut diff = diff();
+        diff.pan(full, 1.0);
+        assert_eq!(diff.window, None, "nothing to pan until zoomed");
+
+        diff.zoom(full, 0.5);
+        assert_eq!(diff.visible(full), (25.0, 75.0));
+        diff.pan(full, 1.0);
+        assert_eq!(diff.visible(full), (37.5, 87.5));
+        diff.pan(full, 1.0);
+        assert_eq!(diff.visible(full), (50.0, 100.0));
+        diff.zoom(full, 0.01);
+        assert_eq!(diff.visible(full), (73.0, 77.0));
+        diff.zoom(full, 100.0);
+        assert_eq!(diff.window, None);
+
+        diff.toggle_layout();
+        assert_eq!(diff.layout, Layout::Overlay);
+    }
+}
@@ -0,0 +1,136 @@
+//! Per-root cache of run facts that are slow to compute, kept in
+//! `<runs root>/.og_index.json`.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Index file written at the top of a runs directory.
+pub const INDEX_FILE_NAME: &str = ".og_index.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunIndex {
+    #[serde(default)]
+    pub runs: BTreeMap<String, IndexedRun>,
+}
+
+/// What is known about one run, valid while its event files are unchanged.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexedRun {
+    /// Newest event-file mtime when the entry was computed.
+    pub last_event_write_unix: Option<u64>,
+    pub size_bytes: u64,
+}
+
+pub fn index_path(root: &Path) -> PathBuf {
+    root.join(INDEX_FILE_NAME)
+}
+
+/// Load the index; a missing or unreadable one is empty, since it only caches.
+pub fn load(root: &Path) -> RunIndex {
+    fs::read_to_string(index_path(root))
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+/// Persist the index atomically (write to a temp file, then rename).
+pub fn save(root: &Path, index: &RunIndex) -> Result<()> {
+    let path = index_path(root);
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(index)?)
+        .with_context(|| format!("writing {}", tmp.display()))?;
+    fs::rename(&tmp, &path).with_context(|| format!("writing {}", path.display()))
+}
+
+fn run_id(run_dir: &Path) -> String {
+    run_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| run_dir.display().to_string())
+}
+
+/// On-disk size of each run by id (see `gc::run_usage`). Runs whose event
+/// files have not changed since they were last measured reuse the cached
+/// size; the index under `root` is updated with the rest.
+pub fn run_sizes(root: &Path, run_dirs: &[PathBuf]) -> BTreeMap<String, u64> {
+    let mut index = load(root);
+    let mut changed = false;
+    let mut sizes = BTreeMap::new();
+    for run_dir in run_dirs {
+        let id = run_id(run_dir);
+        let last_write = crate::gc::last_event_write_unix(run_dir).ok().flatten();
+        let size_bytes = match index.runs.get(&id) {
+            Some(cached) if cached.last_event_write_unix == last_write => cached.size_bytes,
+            _ => {
+                let registered = crate::checkpoints::load(run_dir).unwrap_or_default();
+                let Ok(usage) = crate::gc::run_usage(run_dir, &registered) else {
+                    continue;
+                };
+                index.runs.insert(
+                    id.clone(),
+                    IndexedRun {
+                        last_event_write_unix: last_write,
+                        size_bytes: usage.total(),
+                    },
+                );
+                changed = true;
+                usage.total()
+            }
+        };
+        sizes.insert(id, size_bytes);
+    }
+    // Forget runs that were deleted or moved away.
+    let before = index.runs.len();
+    index.runs.retain(|id, _| sizes.contains_key(id));
+    changed |= index.runs.len() != before;
+    // The cache is an optimisation; a read-only runs dir just recomputes next time.
+    if changed && root.is_dir() {
+        let _ = save(root, &index);
+    }
+    sizes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{INDEX_FILE_NAME, load, run_sizes};
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn run_sizes_are_cached_until_event_files_change() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("ogtui-run-index-{nonce}"));
+        let run_dir = root.join("run-a");
+        fs::create_dir_all(&run_dir).expect("create run dir");
+        fs::write(run_dir.join("events.out.tfevents.1.host"), [0u8; 100]).expect("events");
+
+        let sizes = run_sizes(&root, std::slice::from_ref(&run_dir));
+        assert_eq!(sizes.get("run-a"), Some(&100));
+        assert!(root.join(INDEX_FILE_NAME).exists());
+
+        // Files other than event files do not invalidate the cached size.
+        fs::write(run_dir.join("notes.txt"), [0u8; 50]).expect("artifact");
+        let sizes = run_sizes(&root, std::slice::from_ref(&run_dir));
+        assert_eq!(sizes.get("run-a"), Some(&100));
+
+        let mut index = load(&root);
+        index
+            .runs
+            .get_mut("run-a")
+            .expect("indexed")
+            .last_event_write_unix = Some(0);
+        super::save(&root, &index).expect("save");
+        let sizes = run_sizes(&root, std::slice::from_ref(&run_dir));
+        assert_eq!(sizes.get("run-a"), Some(&150));
+
+        assert!(run_sizes(&root, &[]).is_empty());
+        assert!(load(&root).runs.is_empty());
+        fs::remove_dir_all(&root).expect("cleanup");
+    }
+}
@@ -0,0 +1,190 @@
+use std::path::Path;
+
+use crate::git_state;
+
+/// Template used for new run directories when none is configured.
+///
+/// Placeholders: `{date}` (UTC, `YYYY-MM-DD`), `{time}` (UTC, `HHMMSS`), `{branch}`
+/// (current git branch), `{counter}` (existing runs + 1), `{adjective}` and `{noun}`.
+pub const DEFAULT_TEMPLATE: &str = "{date}_{adjective}-{noun}-{counter}";
+
+const ADJECTIVES: &[&str] = &[
+    "amber", "brave", "calm", "dusty", "eager", "fuzzy", "frosty", "gentle", "hazy", "icy",
+    "jolly", "keen", "lunar", "misty", "nimble", "olive", "proud", "quiet", "rapid", "silent",
+    "sunny", "tidy", "vivid", "witty",
+];
+
+const NOUNS: &[&str] = &[
+    "badger", "bison", "crane", "falcon", "gecko", "heron", "ibis", "koala", "lemur", "lynx",
+    "marten", "newt", "otter", "panda", "quail", "raven", "salmon", "tapir", "turtle", "walrus",
+    "wombat", "yak", "zebra", "finch",
+];
+
+/// Values substituted into a run name template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameParts {
+    pub unix_secs: u64,
+    pub branch: String,
+    pub counter: usize,
+    pub seed: u64,
+}
+
+/// Substitute `parts` into `template` and make the result safe to use as a directory name.
+pub fn render(template: &str, parts: &NameParts) -> String {
+    let (date, time) = utc_date_time(parts.unix_secs);
+    let adjective = ADJECTIVES[(parts.seed % ADJECTIVES.len() as u64) as usize];
+    let noun = NOUNS[((parts.seed / ADJECTIVES.len() as u64) % NOUNS.len() as u64) as usize];
+    let rendered = template
+        .replace("{date}", &date)
+        .replace("{time}", &time)
+        .replace("{branch}", &parts.branch)
+        .replace("{counter}", &parts.counter.to_string())
+        .replace("{adjective}", adjective)
+        .replace("{noun}", noun);
+    sanitize(&rendered)
+}
+
+/// Pick a fresh run directory name under `parent` following `template`.
+pub fn generate(template: &str, parent: &Path, unix_secs: u64) -> String {
+    let existing = std::fs::read_dir(parent)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .count()
+        })
+        .unwrap_or(0);
+    let mut parts = NameParts {
+        unix_secs,
+        branch: git_state::current_branch(Path::new(".")).unwrap_or_else(|| "nogit".to_string()),
+        counter: existing + 1,
+        seed: name_seed(),
+    };
+    loop {
+        let mut name = render(template, &parts);
+        if name.is_empty() {
+            name = "run".to_string();
+        }
+        if !parent.join(&name).exists() {
+            return name;
+        }
+        // Templates without `{counter}` can collide; fall back to a numeric suffix.
+        let bumped = format!("{}-{}", name, parts.counter);
+        if !parent.join(&bumped).exists() {
+            return bumped;
+        }
+        parts.counter += 1;
+        parts.seed = parts.seed.wrapping_add(1);
+    }
+}
+
+fn name_seed() -> u64 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    // splitmix64 finaliser so consecutive timestamps map to unrelated words.
+    let mut z = nanos.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>()
+        .trim_matches(|c| c == '-' || c == '.')
+        .to_string()
+}
+
+/// Format a unix timestamp as UTC (`YYYY-MM-DD`, `HHMMSS`).
+fn utc_date_time(unix_secs: u64) -> (String, String) {
+    let days = (unix_secs / 86_400) as i64;
+    let secs_of_day = unix_secs % 86_400;
+    // Civil-from-days (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (
+        format!("{year:04}-{month:02}-{day:02}"),
+        format!(
+            "{:02}{:02}{:02}",
+            secs_of_day / 3_600,
+            (secs_of_day / 60) % 60,
+            secs_of_day % 60
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DEFAULT_TEMPLATE, NameParts, generate, render, utc_date_time};
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn parts() -> NameParts {
+        NameParts {
+            unix_secs: 1_717_243_200, // 2024-06-01 12:00:00 UTC
+            branch: "feature/lr-sweep".to_string(),
+            counter: 17,
+            seed: 6,
+        }
+    }
+
+    #[test]
+    fn utc_date_time_formats_known_timestamps() {
+        assert_eq!(
+            utc_date_time(0),
+            ("1970-01-01".to_string(), "000000".to_string())
+        );
+        assert_eq!(
+            utc_date_time(1_717_243_200),
+            ("2024-06-01".to_string(), "120000".to_string())
+        );
+        assert_eq!(utc_date_time(951_782_400).0, "2000-02-29");
+    }
+
+    #[test]
+    fn render_default_template_is_memorable() {
+        assert_eq!(
+            render(DEFAULT_TEMPLATE, &parts()),
+            "2024-06-01_frosty-badger-17"
+        );
+    }
+
+    #[test]
+    fn render_sanitizes_branch_names() {
+        assert_eq!(
+            render("{branch}-{counter}", &parts()),
+            "feature-lr-sweep-17"
+        );
+    }
+
+    #[test]
+    fn generate_skips_existing_directories() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("ogtui-run-name-{nonce}"));
+        fs::create_dir_all(root.join("fixed")).expect("create existing run");
+
+        let name = generate("fixed", &root, 0);
+        assert_eq!(name, "fixed-2");
+
+        fs::remove_dir_all(&root).ok();
+    }
+}
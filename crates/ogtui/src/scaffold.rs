@@ -0,0 +1,411 @@
+//! `og init`: a project directory under the runs root with a starter
+//! `project.toml`, a sample alert-rule file and, on request, a Python
+//! snippet that logs metrics where `og` reads them.
+
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::project_config::{self, Objective, ObjectiveMode};
+
+/// Sample rules next to `project.toml`; `[[alerts]]` tables to copy over.
+pub const ALERTS_FILE_NAME: &str = "alerts.example.toml";
+
+/// Where the Python snippet goes when `--python` names no file.
+pub const PYTHON_FILE_NAME: &str = "og_logging.py";
+
+/// Objective offered when the user has not named one.
+const SUGGESTED_OBJECTIVE: &str = "val/loss";
+
+/// What `og init` creates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    pub project: String,
+    pub objective: Option<Objective>,
+    /// Python logging snippet to write, relative to the working directory.
+    pub python: Option<PathBuf>,
+}
+
+/// Lower is better for losses, errors and perplexities; higher for the rest.
+pub fn guess_mode(metric: &str) -> ObjectiveMode {
+    let metric = metric.to_ascii_lowercase();
+    let lower_is_better = [
+        "loss",
+        "err",
+        "perplexity",
+        "ppl",
+        "wer",
+        "cer",
+        "mse",
+        "mae",
+    ]
+    .iter()
+    .any(|word| metric.contains(word));
+    if lower_is_better {
+        ObjectiveMode::Min
+    } else {
+        ObjectiveMode::Max
+    }
+}
+
+/// Project names become a directory under the runs root.
+pub fn check_project_name(name: &str) -> Result<()> {
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        bail!("'{name}' is not a valid project name (use a plain directory name)");
+    }
+    Ok(())
+}
+
+/// Ask for the project name, objective and Python snippet, offering the
+/// values in `plan` as defaults. An empty answer (or end of input) keeps the
+/// default; questions about invalid answers are asked again.
+pub fn ask(input: &mut impl BufRead, out: &mut impl Write, plan: Plan) -> Result<Plan> {
+    let project = loop {
+        let answer = prompt(input, out, "Project name", &plan.project)?;
+        match check_project_name(&answer) {
+            Ok(()) => break answer,
+            Err(err) => writeln!(out, "{err}")?,
+        }
+    };
+
+    let suggested = plan
+        .objective
+        .as_ref()
+        .map_or(SUGGESTED_OBJECTIVE, |objective| objective.metric.as_str());
+    let metric = prompt(input, out, "Objective metric (- for none)", suggested)?;
+    let objective = if metric == "-" {
+        None
+    } else {
+        let default_mode = match &plan.objective {
+            Some(objective) if objective.metric == metric => objective.mode,
+            _ => guess_mode(&metric),
+        };
+        let mode = loop {
+            let answer = prompt(
+                input,
+                out,
+                "Is lower (min) or higher (max) better?",
+                mode_name(default_mode),
+            )?;
+            match answer.as_str() {
+                "min" | "lower" => break ObjectiveMode::Min,
+                "max" | "higher" => break ObjectiveMode::Max,
+                _ => writeln!(out, "answer min or max")?,
+            }
+        };
+        Some(Objective { metric, mode })
+    };
+
+    let python_path = plan
+        .python
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(PYTHON_FILE_NAME));
+    let python = loop {
+        let answer = prompt(
+            input,
+            out,
+            &format!(
+                "Write a Python logging snippet to {}?",
+                python_path.display()
+            ),
+            if plan.python.is_some() { "y" } else { "n" },
+        )?;
+        match answer.to_ascii_lowercase().as_str() {
+            "y" | "yes" => break Some(python_path),
+            "n" | "no" => break None,
+            _ => writeln!(out, "answer y or n")?,
+        }
+    };
+
+    Ok(Plan {
+        project,
+        objective,
+        python,
+    })
+}
+
+fn prompt(
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+    question: &str,
+    default: &str,
+) -> Result<String> {
+    write!(out, "{question} [{default}]: ")?;
+    out.flush()?;
+    let mut line = String::new();
+    input.read_line(&mut line).context("reading answer")?;
+    let answer = line.trim();
+    Ok(if answer.is_empty() { default } else { answer }.to_string())
+}
+
+fn mode_name(mode: ObjectiveMode) -> &'static str {
+    match mode {
+        ObjectiveMode::Min => "min",
+        ObjectiveMode::Max => "max",
+    }
+}
+
+/// Create `<runs_root>/<project>/` with `project.toml` and the sample alert
+/// rules, plus the Python snippet when planned. Existing files are kept
+/// unless `force` is set. Returns the written files.
+pub fn create(runs_root: &Path, plan: &Plan, force: bool) -> Result<Vec<PathBuf>> {
+    check_project_name(&plan.project)?;
+    let project_dir = runs_root.join(&plan.project);
+    let mut files = vec![
+        (
+            project_dir.join(project_config::FILE_NAME),
+            project_toml(plan),
+        ),
+        (project_dir.join(ALERTS_FILE_NAME), alerts_example(plan)),
+    ];
+    if let Some(path) = &plan.python {
+        files.push((path.clone(), python_snippet(runs_root, plan)));
+    }
+    if !force && let Some((existing, _)) = files.iter().find(|(path, _)| path.exists()) {
+        bail!(
+            "{} already exists (pass --force to overwrite)",
+            existing.display()
+        );
+    }
+
+    fs::create_dir_all(&project_dir)
+        .with_context(|| format!("creating {}", project_dir.display()))?;
+    for (path, contents) in &files {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).with_context(|| format!("creating {}", parent.display()))?;
+        }
+        fs::write(path, contents).with_context(|| format!("writing {}", path.display()))?;
+    }
+    Ok(files.into_iter().map(|(path, _)| path).collect())
+}
+
+fn project_toml(plan: &Plan) -> String {
+    let objective = match &plan.objective {
+        Some(objective) => format!(
+            "# The default metric of `og leaderboard` and `og compare`, and the one\n\
+             # watched for early stopping.\n\
+             [objective]\n\
+             metric = {}\n\
+             mode = \"{}\"\n",
+            quoted(&objective.metric),
+            mode_name(objective.mode)
+        ),
+        None => "# The default metric of `og leaderboard` and `og compare`, and the one\n\
+                 # watched for early stopping.\n\
+                 # [objective]\n\
+                 # metric = \"val/loss\"\n\
+                 # mode = \"min\"\n"
+            .to_string(),
+    };
+    format!(
+        "# Defaults for the runs of {project}.\n\
+         \n\
+         # Tags every new run starts with.\n\
+         tags = []\n\
+         \n\
+         {objective}\
+         \n\
+         # Metric lists offered as --graph preset:<name>.\n\
+         [metric_groups]\n\
+         losses = [\"loss\"]\n\
+         \n\
+         # Alert rules for the agent daemon; see {ALERTS_FILE_NAME} for examples.\n\
+         \n\
+         # Dollars per GPU-hour and per-run budget for spend estimates.\n\
+         # [cost]\n\
+         # gpu_hour_price = 2.5\n\
+         # budget = 200.0\n",
+        project = plan.project,
+    )
+}
+
+fn alerts_example(plan: &Plan) -> String {
+    let (metric, direction) = match &plan.objective {
+        Some(objective) => (
+            objective.metric.as_str(),
+            match objective.mode {
+                ObjectiveMode::Min => "decrease",
+                ObjectiveMode::Max => "increase",
+            },
+        ),
+        None => (SUGGESTED_OBJECTIVE, "decrease"),
+    };
+    format!(
+        "# Sample alert rules. Copy the [[alerts]] tables you want into\n\
+         # {project_toml}; `og run` hands them to the agent daemon.\n\
+         \n\
+         # {metric} has not improved by at least min_delta over the last 50 points.\n\
+         [[alerts]]\n\
+         type = \"stall\"\n\
+         metric = {quoted_metric}\n\
+         window = 50\n\
+         min_delta = 0.001\n\
+         direction = \"{direction}\"\n\
+         \n\
+         # train/loss went above 10 (comparison: gt, gte, lt or lte).\n\
+         [[alerts]]\n\
+         metric = \"train/loss\"\n\
+         threshold = 10.0\n\
+         comparison = \"gt\"\n\
+         cooldown_secs = 120\n\
+         message = \"train/loss is diverging\"\n",
+        project_toml = project_config::FILE_NAME,
+        quoted_metric = quoted(metric),
+    )
+}
+
+fn python_snippet(runs_root: &Path, plan: &Plan) -> String {
+    let fallback = runs_root.join(&plan.project).join("local");
+    let example = plan
+        .objective
+        .as_ref()
+        .map_or(SUGGESTED_OBJECTIVE, |objective| objective.metric.as_str());
+    format!(
+        r#""""Log metrics for OpenGraphs.
+
+    from og_logging import log_metrics
+
+    log_metrics(step, {{"train/loss": loss, {example}: value}})
+
+Each call appends a line to metrics.jsonl in the run directory, which `og`
+reads like TensorBoard event files. `og run` sets TB_LOG_DIR to the run
+directory; outside of it, metrics go to {fallback}.
+"""
+
+import json
+import os
+import time
+
+RUN_DIR = os.getenv("TB_LOG_DIR", {fallback_literal})
+
+
+def log_metrics(step, metrics):
+    os.makedirs(RUN_DIR, exist_ok=True)
+    line = {{
+        "step": int(step),
+        "wall_time": time.time(),
+        "metrics": {{name: float(value) for name, value in metrics.items()}},
+    }}
+    with open(os.path.join(RUN_DIR, "metrics.jsonl"), "a") as f:
+        f.write(json.dumps(line) + "\n")
+    # Live updates for the agent daemon, when og started this process.
+    try:
+        from og_agent_chat.client import send_metric
+
+        for name, value in line["metrics"].items():
+            send_metric(name, value, step=line["step"])
+    except Exception:
+        pass
+"#,
+        example = quoted(example),
+        fallback = fallback.display(),
+        fallback_literal = quoted(&fallback.display().to_string()),
+    )
+}
+
+/// A double-quoted string that TOML and Python read back unchanged.
+fn quoted(value: &str) -> String {
+    serde_json::Value::String(value.to_string()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ALERTS_FILE_NAME, Plan, ask, create, guess_mode};
+    use crate::project_config::{self, Objective, ObjectiveMode};
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn ask_keeps_defaults_and_re_asks_invalid_answers() {
+        let plan = Plan {
+            project: "demo".to_string(),
+            objective: None,
+            python: None,
+        };
+        let mut out = Vec::new();
+        let answered = ask(
+            &mut "a/b\nsweeps\nval/acc\nbest\n\ny\n".as_bytes(),
+            &mut out,
+            plan.clone(),
+        )
+        .expect("ask");
+        assert_eq!(
+            answered,
+            Plan {
+                project: "sweeps".to_string(),
+                objective: Some(Objective {
+                    metric: "val/acc".to_string(),
+                    mode: ObjectiveMode::Max,
+                }),
+                python: Some(PathBuf::from("og_logging.py")),
+            }
+        );
+        let transcript = String::from_utf8(out).expect("utf8");
+        assert!(transcript.contains("'a/b' is not a valid project name"));
+        assert!(transcript.contains("Is lower (min) or higher (max) better? [max]"));
+        assert!(transcript.contains("answer min or max"));
+
+        // End of input takes every default: the suggested objective, no snippet.
+        let answered = ask(&mut "".as_bytes(), &mut Vec::new(), plan).expect("defaults");
+        assert_eq!(answered.project, "demo");
+        let objective = answered.objective.expect("objective");
+        assert_eq!(
+            (objective.metric.as_str(), objective.mode),
+            ("val/loss", ObjectiveMode::Min)
+        );
+        assert_eq!(answered.python, None);
+        assert_eq!(guess_mode("eval/WER"), ObjectiveMode::Min);
+    }
+
+    #[test]
+    fn create_writes_files_that_project_config_accepts() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let base = std::env::temp_dir().join(format!("ogtui-init-{nonce}"));
+        let runs = base.join("runs");
+        let plan = Plan {
+            project: "vision".to_string(),
+            objective: Some(Objective {
+                metric: "val/acc".to_string(),
+                mode: ObjectiveMode::Max,
+            }),
+            python: Some(base.join("og_logging.py")),
+        };
+        let files = create(&runs, &plan, false).expect("create");
+        assert_eq!(files.len(), 3);
+
+        let config = project_config::load(&runs.join("vision")).expect("project.toml");
+        assert_eq!(config.objective, plan.objective);
+        assert_eq!(config.metric_groups[0].name, "losses");
+        assert!(config.alerts.is_empty());
+        let alerts =
+            fs::read_to_string(runs.join("vision").join(ALERTS_FILE_NAME)).expect("alerts file");
+        let sample = project_config::parse(&alerts).expect("alert tables");
+        assert_eq!(sample.alerts.len(), 2);
+        assert_eq!(sample.alerts[0]["metric"], "val/acc");
+        assert_eq!(sample.alerts[0]["direction"], "increase");
+        let snippet = fs::read_to_string(base.join("og_logging.py")).expect("snippet");
+        assert!(snippet.contains("def log_metrics(step, metrics):"));
+        assert!(snippet.contains("vision/local\")"));
+
+        let err = create(&runs, &plan, false).expect_err("existing files are kept");
+        assert!(err.to_string().contains("already exists"));
+        create(&runs, &plan, true).expect("--force overwrites");
+        assert!(
+            create(
+                &runs,
+                &Plan {
+                    project: "..".to_string(),
+                    ..plan
+                },
+                true
+            )
+            .is_err()
+        );
+        fs::remove_dir_all(&base).ok();
+    }
+}
@@ -0,0 +1,308 @@
+//! Shaded bands for projects holding many runs of one config. Opening a
+//! project merges every run into one series, which with 10+ seeds is a
+//! tangle of lines; instead runs whose config differs only in seed-like
+//! keys form a group, and each group is charted as its mean with a min–max
+//! or p25–p75 band filled in around it.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use crate::query::{contains_tfevents_direct, list_run_dirs};
+use crate::run_diff::interpolate;
+use crate::run_meta;
+use crate::tfevents;
+
+/// Spread drawn around a group's mean.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BandKind {
+    MinMax,
+    /// 25th to 75th percentile
+    Quartiles,
+}
+
+impl BandKind {
+    pub fn toggle(self) -> Self {
+        match self {
+            Self::MinMax => Self::Quartiles,
+            Self::Quartiles => Self::MinMax,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::MinMax => "min–max",
+            Self::Quartiles => "p25–p75",
+        }
+    }
+}
+
+/// Runs of a project that differ only in their seed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeedGroup {
+    /// Config keys setting this group apart, or `all runs` for a single group
+    pub label: String,
+    pub runs: usize,
+    /// tag → one step-sorted series per run that logged it
+    pub scalars: BTreeMap<String, Vec<Vec<(f64, f64)>>>,
+}
+
+/// A group's mean and spread, sampled at evenly spaced steps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Band {
+    pub mean: Vec<(f64, f64)>,
+    pub lo: Vec<(f64, f64)>,
+    pub hi: Vec<(f64, f64)>,
+}
+
+impl Band {
+    /// Points filling the band column by column, `step` apart in value, so
+    /// a braille scatter of them reads as shading.
+    pub fn fill(&self, step: f64) -> Vec<(f64, f64)> {
+        let mut points = Vec::new();
+        if step <= 0.0 || !step.is_finite() {
+            return points;
+        }
+        for (&(x, lo), &(_, hi)) in self.lo.iter().zip(&self.hi) {
+            let mut y = lo;
+            while y < hi {
+                points.push((x, y));
+                y += step;
+            }
+            points.push((x, hi));
+        }
+        points
+    }
+
+    pub fn map_values(mut self, f: impl Fn(f64) -> f64) -> Self {
+        for series in [&mut self.mean, &mut self.lo, &mut self.hi] {
+            for point in series.iter_mut() {
+                point.1 = f(point.1);
+            }
+        }
+        self
+    }
+}
+
+/// Whether a config key only picks a seed: `seed`, `data_seed`, `train.seed`.
+pub fn is_seed_key(key: &str) -> bool {
+    key.rsplit(['.', '/', '_', '-'])
+        .next()
+        .is_some_and(|last| last.eq_ignore_ascii_case("seed"))
+}
+
+/// Seed groups of the project at `path`, or none when `path` is a single
+/// run or holds fewer than two runs. Rank directories of a distributed run
+/// are not separate runs.
+pub fn load(path: &Path) -> Result<Vec<SeedGroup>> {
+    if !path.is_dir() || contains_tfevents_direct(path)? {
+        return Ok(Vec::new());
+    }
+    let runs: Vec<PathBuf> = list_run_dirs(path)?
+        .into_iter()
+        .filter(|dir| dir != path)
+        .filter(|dir| {
+            dir.file_name()
+                .is_none_or(|name| tfevents::rank_from_path(Path::new(name)).is_none())
+        })
+        .collect();
+    if runs.len() < 2 {
+        return Ok(Vec::new());
+    }
+    let configs = runs
+        .into_iter()
+        .map(|run| {
+            let config = run_meta::load(&run)
+                .map(|meta| meta.config)
+                .unwrap_or_default();
+            (run, config)
+        })
+        .collect();
+    let mut groups = Vec::new();
+    for (label, runs) in group(configs) {
+        let mut scalars: BTreeMap<String, Vec<Vec<(f64, f64)>>> = BTreeMap::new();
+        let mut loaded = 0;
+        for run in &runs {
+            let Ok(run) = tfevents::load_run(run) else {
+                continue;
+            };
+            loaded += 1;
+            for (tag, series) in run.scalars {
+                scalars.entry(tag).or_default().push(series);
+            }
+        }
+        if loaded > 0 {
+            groups.push(SeedGroup {
+                label,
+                runs: loaded,
+                scalars,
+            });
+        }
+    }
+    Ok(groups)
+}
+
+/// Runs grouped by config without seed keys, labelled by the `key=value`
+/// pairs that tell the groups apart.
+pub fn group(configs: Vec<(PathBuf, BTreeMap<String, String>)>) -> Vec<(String, Vec<PathBuf>)> {
+    let mut by_config: BTreeMap<BTreeMap<String, String>, Vec<PathBuf>> = BTreeMap::new();
+    for (run, config) in configs {
+        let config = config
+            .into_iter()
+            .filter(|(key, _)| !is_seed_key(key))
+            .collect();
+        by_config.entry(config).or_default().push(run);
+    }
+    if by_config.len() == 1 {
+        return by_config
+            .into_values()
+            .map(|runs| ("all runs".to_string(), runs))
+            .collect();
+    }
+    let configs: Vec<&BTreeMap<String, String>> = by_config.keys().collect();
+    let varying: Vec<&String> = configs
+        .iter()
+        .flat_map(|config| config.keys())
+        .filter(|key| {
+            configs
+                .iter()
+                .any(|config| config.get(*key) != configs[0].get(*key))
+        })
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    by_config
+        .iter()
+        .map(|(config, runs)| {
+            let label = varying
+                .iter()
+                .map(|key| format!("{key}={}", config.get(*key).map_or("-", String::as_str)))
+                .collect::<Vec<_>>()
+                .join(" ");
+            (label, runs.clone())
+        })
+        .collect()
+}
+
+/// `series`' mean and spread at `samples` evenly spaced steps over `lo..=hi`,
+/// reading each run between its points by linear interpolation. Steps no
+/// run covers are left out.
+pub fn band(
+    series: &[Vec<(f64, f64)>],
+    kind: BandKind,
+    (lo, hi): (f64, f64),
+    samples: usize,
+) -> Option<Band> {
+    let mut band = Band {
+        mean: Vec::new(),
+        lo: Vec::new(),
+        hi: Vec::new(),
+    };
+    let samples = samples.max(2);
+    for idx in 0..samples {
+        let step = lo + (hi - lo) * idx as f64 / (samples - 1) as f64;
+        let mut values: Vec<f64> = series
+            .iter()
+            .filter_map(|run| interpolate(run, step))
+            .filter(|value| value.is_finite())
+            .collect();
+        if values.is_empty() {
+            continue;
+        }
+        values.sort_by(f64::total_cmp);
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let (low, high) = match kind {
+            BandKind::MinMax => (values[0], values[values.len() - 1]),
+            BandKind::Quartiles => (percentile(&values, 0.25), percentile(&values, 0.75)),
+        };
+        band.mean.push((step, mean));
+        band.lo.push((step, low));
+        band.hi.push((step, high));
+    }
+    (!band.mean.is_empty()).then_some(band)
+}
+
+/// Linear percentile of sorted, non-empty `values`.
+fn percentile(values: &[f64], share: f64) -> f64 {
+    let rank = share * (values.len() - 1) as f64;
+    let below = rank.floor() as usize;
+    let above = rank.ceil() as usize;
+    values[below] + (values[above] - values[below]) * (rank - below as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BandKind, band, group, is_seed_key};
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    #[test]
+    fn bands_spread_around_the_mean_of_the_runs_covering_each_step() {
+        let runs = vec![
+            vec![(0.0, 0.0), (10.0, 10.0)],
+            vec![(0.0, 2.0), (10.0, 12.0)],
+            vec![(0.0, 4.0), (10.0, 14.0)],
+            vec![(0.0, 6.0), (5.0, 11.0)],
+        ];
+        let min_max = band(&runs, BandKind::MinMax, (0.0, 10.0), 3).expect("band");
+        assert_eq!(min_max.mean, vec![(0.0, 3.0), (5.0, 8.0), (10.0, 12.0)]);
+        assert_eq!(min_max.lo, vec![(0.0, 0.0), (5.0, 5.0), (10.0, 10.0)]);
+        assert_eq!(min_max.hi, vec![(0.0, 6.0), (5.0, 11.0), (10.0, 14.0)]);
+
+        let quartiles = band(&runs, BandKind::Quartiles, (0.0, 10.0), 3).expect("band");
+        assert_eq!(quartiles.lo[0], (0.0, 1.5));
+        assert_eq!(quartiles.hi[0], (0.0, 4.5));
+        // Only three runs reach step 10.
+        assert_eq!(quartiles.lo[2], (10.0, 11.0));
+        assert_eq!(quartiles.hi[2], (10.0, 13.0));
+
+        let fill = min_max.fill(2.5);
+        assert_eq!(
+            &fill[..4],
+            &[(0.0, 0.0), (0.0, 2.5), (0.0, 5.0), (0.0, 6.0)],
+            "each column is filled from low to high"
+        );
+        assert!(band(&runs, BandKind::MinMax, (20.0, 30.0), 3).is_none());
+    }
+
+    #[test]
+    fn runs_group_by_config_without_their_seeds() {
+        assert!(is_seed_key("seed"));
+        assert!(is_seed_key("data_seed"));
+        assert!(is_seed_key("train.Seed"));
+        assert!(!is_seed_key("seeds_per_batch"));
+
+        let config = |pairs: &[(&str, &str)]| -> BTreeMap<String, String> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        let groups = group(vec![
+            (PathBuf::from("a"), config(&[("lr", "0.1"), ("seed", "1")])),
+            (PathBuf::from("b"), config(&[("lr", "0.1"), ("seed", "2")])),
+            (
+                PathBuf::from("c"),
+                config(&[("lr", "0.01"), ("seed", "1"), ("opt", "adam")]),
+            ),
+        ]);
+        assert_eq!(
+            groups,
+            vec![
+                ("lr=0.01 opt=adam".to_string(), vec![PathBuf::from("c")]),
+                (
+                    "lr=0.1 opt=-".to_string(),
+                    vec![PathBuf::from("a"), PathBuf::from("b")]
+                ),
+            ]
+        );
+
+        let single = group(vec![
+            (PathBuf::from("a"), config(&[("seed", "1")])),
+            (PathBuf::from("b"), BTreeMap::new()),
+        ]);
+        assert_eq!(single[0].0, "all runs");
+        assert_eq!(single[0].1.len(), 2);
+    }
+}
@@ -0,0 +1,340 @@
+use anyhow::{Context, Result, bail};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use crate::log_entry::LogEntry;
+
+/// Format version written in the first line of every `.ogr` file.
+pub const SESSION_VERSION: u32 = 1;
+
+type Series = BTreeMap<String, Vec<(f64, f64)>>;
+
+/// First line of a recording: what was being watched and when.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Header {
+    pub version: u32,
+    pub started_unix: u64,
+    pub path: String,
+}
+
+/// One event-file refresh. `scalars` holds only the points appended since the
+/// previous refresh, unless `full` is set (first frame, or a series shrank or
+/// was rewritten), in which case it is the whole view. `log_lines` is only
+/// present when the log panel changed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Refresh {
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub full: bool,
+    #[serde(with = "nan_as_null")]
+    pub scalars: Series,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_lines: Option<Vec<LogEntry>>,
+    pub total_events: usize,
+    pub max_step: i64,
+}
+
+/// JSON has no NaN or infinity; diverged losses are written as `null` and
+/// read back as NaN.
+mod nan_as_null {
+    use super::Series;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::BTreeMap;
+
+    type Nullable = BTreeMap<String, Vec<(f64, Option<f64>)>>;
+
+    pub fn serialize<S: Serializer>(series: &Series, serializer: S) -> Result<S::Ok, S::Error> {
+        let nullable: Nullable = series
+            .iter()
+            .map(|(tag, points)| {
+                let points = points
+                    .iter()
+                    .map(|(step, value)| (*step, value.is_finite().then_some(*value)))
+                    .collect();
+                (tag.clone(), points)
+            })
+            .collect();
+        nullable.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Series, D::Error> {
+        let nullable = Nullable::deserialize(deserializer)?;
+        Ok(nullable
+            .into_iter()
+            .map(|(tag, points)| {
+                let points = points
+                    .into_iter()
+                    .map(|(step, value)| (step, value.unwrap_or(f64::NAN)))
+                    .collect();
+                (tag, points)
+            })
+            .collect())
+    }
+}
+
+/// Every line after the header, stamped with milliseconds since recording began.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Entry<M> {
+    Message { t_ms: u64, message: M },
+    Refresh { t_ms: u64, refresh: Refresh },
+}
+
+impl<M> Entry<M> {
+    pub fn t_ms(&self) -> u64 {
+        match self {
+            Entry::Message { t_ms, .. } | Entry::Refresh { t_ms, .. } => *t_ms,
+        }
+    }
+}
+
+/// Appends a session to a `.ogr` file (JSON lines), flushing every entry so a
+/// crashed TUI still leaves a replayable file.
+pub struct Recorder {
+    out: File,
+    started: Instant,
+    /// Length and last point of each series as of the previous refresh.
+    seen: BTreeMap<String, (usize, (f64, f64))>,
+    logs_hash: Option<u64>,
+    last_counts: Option<(usize, i64)>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, watched: &Path, started_unix: u64) -> Result<Self> {
+        let out = File::create(path).with_context(|| format!("creating {}", path.display()))?;
+        let mut recorder = Self {
+            out,
+            started: Instant::now(),
+            seen: BTreeMap::new(),
+            logs_hash: None,
+            last_counts: None,
+        };
+        recorder.write_line(&Header {
+            version: SESSION_VERSION,
+            started_unix,
+            path: watched.display().to_string(),
+        })?;
+        Ok(recorder)
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    fn write_line<T: Serialize>(&mut self, value: &T) -> Result<()> {
+        let mut line = serde_json::to_vec(value)?;
+        line.push(b'\n');
+        self.out.write_all(&line)?;
+        self.out.flush()?;
+        Ok(())
+    }
+
+    pub fn message<M: Serialize>(&mut self, message: &M) -> Result<()> {
+        let entry = Entry::Message {
+            t_ms: self.elapsed_ms(),
+            message,
+        };
+        self.write_line(&entry)
+    }
+
+    /// Record the view a refresh loaded, as a delta against the previous one.
+    /// Refreshes that changed nothing are not written.
+    pub fn refresh(
+        &mut self,
+        scalars: &Series,
+        log_lines: &[LogEntry],
+        total_events: usize,
+        max_step: i64,
+    ) -> Result<()> {
+        let refresh = self.delta(scalars, log_lines, total_events, max_step);
+        let unchanged = !refresh.full
+            && refresh.scalars.is_empty()
+            && refresh.log_lines.is_none()
+            && self.last_counts == Some((total_events, max_step));
+        self.last_counts = Some((total_events, max_step));
+        if unchanged {
+            return Ok(());
+        }
+        let entry: Entry<()> = Entry::Refresh {
+            t_ms: self.elapsed_ms(),
+            refresh,
+        };
+        self.write_line(&entry)
+    }
+
+    fn delta(
+        &mut self,
+        scalars: &Series,
+        log_lines: &[LogEntry],
+        total_events: usize,
+        max_step: i64,
+    ) -> Refresh {
+        let appends_only = self.last_counts.is_some()
+            && self.seen.keys().all(|tag| scalars.contains_key(tag))
+            && self.seen.iter().all(|(tag, (len, last))| {
+                let series = &scalars[tag];
+                // Compare bits so a NaN point still counts as unchanged.
+                series.len() >= *len
+                    && series.get(len - 1).is_some_and(|point| {
+                        point.0.to_bits() == last.0.to_bits()
+                            && point.1.to_bits() == last.1.to_bits()
+                    })
+            });
+        let mut refresh = Refresh {
+            full: !appends_only,
+            total_events,
+            max_step,
+            ..Refresh::default()
+        };
+        for (tag, series) in scalars {
+            let from = match self.seen.get(tag) {
+                Some((len, _)) if appends_only => *len,
+                _ => 0,
+            };
+            if series.len() > from {
+                refresh.scalars.insert(tag.clone(), series[from..].to_vec());
+            }
+        }
+        self.seen = scalars
+            .iter()
+            .filter_map(|(tag, series)| Some((tag.clone(), (series.len(), *series.last()?))))
+            .collect();
+
+        let mut hasher = DefaultHasher::new();
+        log_lines.hash(&mut hasher);
+        let logs_hash = hasher.finish();
+        if refresh.full || self.logs_hash != Some(logs_hash) {
+            refresh.log_lines = Some(log_lines.to_vec());
+        }
+        self.logs_hash = Some(logs_hash);
+        refresh
+    }
+}
+
+/// Read a recording. A final line cut short by a crash is dropped.
+pub fn read<M: DeserializeOwned>(path: &Path) -> Result<(Header, Vec<Entry<M>>)> {
+    let file = File::open(path).with_context(|| format!("opening {}", path.display()))?;
+    let mut lines = BufReader::new(file).lines();
+    let first = lines
+        .next()
+        .transpose()?
+        .with_context(|| format!("{} is empty", path.display()))?;
+    let header: Header = serde_json::from_str(&first)
+        .with_context(|| format!("{} is not an og session recording", path.display()))?;
+    if header.version != SESSION_VERSION {
+        bail!(
+            "{} has session format {}, this og reads {}",
+            path.display(),
+            header.version,
+            SESSION_VERSION
+        );
+    }
+    let lines: Vec<String> = lines.collect::<std::io::Result<_>>()?;
+    let mut entries = Vec::with_capacity(lines.len());
+    for (idx, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(entry) => entries.push(entry),
+            Err(_) if idx + 1 == lines.len() => break,
+            Err(err) => {
+                return Err(err).with_context(|| format!("{} line {}", path.display(), idx + 2));
+            }
+        }
+    }
+    Ok((header, entries))
+}
+
+/// Rebuilds the full view from a stream of refresh deltas.
+#[derive(Debug, Default)]
+pub struct ReplayView {
+    pub scalars: Series,
+    pub log_lines: Vec<LogEntry>,
+}
+
+impl ReplayView {
+    pub fn apply(&mut self, refresh: Refresh) {
+        if refresh.full {
+            self.scalars = refresh.scalars;
+        } else {
+            for (tag, points) in refresh.scalars {
+                self.scalars.entry(tag).or_default().extend(points);
+            }
+        }
+        if let Some(log_lines) = refresh.log_lines {
+            self.log_lines = log_lines;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entry, Recorder, ReplayView, read};
+    use crate::log_entry::{LogEntry, LogLevel, LogSource};
+    use std::collections::BTreeMap;
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn refresh_deltas_replay_into_the_recorded_view() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("ogtui-session-{nonce}.ogr"));
+        let mut recorder =
+            Recorder::create(&path, std::path::Path::new("runs/demo"), 1).expect("create");
+
+        let mut scalars = BTreeMap::new();
+        scalars.insert("loss".to_string(), vec![(1.0, 0.9), (2.0, 0.8)]);
+        let logs = vec![LogEntry::new(LogLevel::Info, LogSource::Events, "step 2")];
+        recorder.refresh(&scalars, &logs, 2, 2).expect("first");
+        recorder.refresh(&scalars, &logs, 2, 2).expect("unchanged");
+        recorder.message(&"ping").expect("message");
+        scalars.get_mut("loss").expect("loss").push((3.0, f64::NAN));
+        scalars.insert("acc".to_string(), vec![(3.0, 0.5)]);
+        recorder.refresh(&scalars, &logs, 4, 3).expect("append");
+        scalars.get_mut("loss").expect("loss").push((4.0, 0.7));
+        recorder.refresh(&scalars, &logs, 4, 3).expect("append");
+        scalars.insert("loss".to_string(), vec![(1.0, 1.0)]);
+        recorder.refresh(&scalars, &logs, 1, 1).expect("restart");
+        // A crash mid-write leaves half a line behind.
+        let mut file = OpenOptions::new().append(true).open(&path).expect("open");
+        file.write_all(b"{\"kind\":\"refr").expect("truncate");
+
+        let (header, entries) = read::<String>(&path).expect("read");
+        assert_eq!(header.path, "runs/demo");
+        let kinds: Vec<&str> = entries
+            .iter()
+            .map(|entry| match entry {
+                Entry::Message { .. } => "message",
+                Entry::Refresh { refresh, .. } if refresh.full => "full",
+                Entry::Refresh { .. } => "delta",
+            })
+            .collect();
+        assert_eq!(kinds, vec!["full", "message", "delta", "delta", "full"]);
+
+        let mut view = ReplayView::default();
+        for entry in entries.into_iter().take(4) {
+            if let Entry::Refresh { refresh, .. } = entry {
+                assert!(refresh.log_lines.is_some() == refresh.full);
+                view.apply(refresh);
+            }
+        }
+        let loss = &view.scalars["loss"];
+        assert_eq!(loss.len(), 4);
+        assert!(loss[2].1.is_nan());
+        assert_eq!(loss[3], (4.0, 0.7));
+        assert_eq!(view.scalars["acc"], vec![(3.0, 0.5)]);
+        assert_eq!(view.log_lines, logs);
+        fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,40 @@
+//! Signals that end the TUI. SIGTERM, SIGHUP (terminal closed) and SIGINT
+//! (raw mode keeps Ctrl-C from raising it, so it comes from `kill`) quit
+//! the event loop like `q` does, so the daemon and training still get their
+//! graceful stop instead of being orphaned. A second signal while og waits
+//! for that stop exits at once.
+
+use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+const SIGNALS: [(i32, &str); 3] = [(SIGTERM, "SIGTERM"), (SIGHUP, "SIGHUP"), (SIGINT, "SIGINT")];
+
+static RECEIVED: OnceLock<Arc<AtomicUsize>> = OnceLock::new();
+
+/// Start recording the signals above instead of dying on them.
+pub fn install() -> std::io::Result<()> {
+    if RECEIVED.get().is_some() {
+        return Ok(());
+    }
+    let received = Arc::new(AtomicUsize::new(0));
+    let again = Arc::new(AtomicBool::new(false));
+    for (signal, _) in SIGNALS {
+        // Runs before `again` is set, so only exits from the second signal on.
+        signal_hook::flag::register_conditional_shutdown(signal, 1, again.clone())?;
+        signal_hook::flag::register(signal, again.clone())?;
+        signal_hook::flag::register_usize(signal, received.clone(), signal as usize)?;
+    }
+    let _ = RECEIVED.set(received);
+    Ok(())
+}
+
+/// Name of the last signal received, if any.
+pub fn received() -> Option<&'static str> {
+    let signal = RECEIVED.get()?.load(Ordering::Relaxed);
+    SIGNALS
+        .iter()
+        .find(|(number, _)| *number as usize == signal)
+        .map(|(_, name)| *name)
+}
@@ -0,0 +1,97 @@
+//! SLURM job state for `og list runs --slurm-state`. Runs launched inside an
+//! allocation record their job in `og_meta.json`; without asking the
+//! scheduler, a preempted or cancelled job just looks like a run whose files
+//! stopped changing. `sacct` knows how every job ended; `squeue` is the
+//! fallback on clusters without accounting and only knows live jobs.
+
+use std::collections::HashMap;
+use std::process::Command;
+
+/// State word by job id for `job_ids`, from `sacct`, else `squeue`. Jobs
+/// neither knows are left out.
+pub fn job_states(job_ids: &[String]) -> HashMap<String, String> {
+    if job_ids.is_empty() {
+        return HashMap::new();
+    }
+    let ids = job_ids.join(",");
+    let query = |program: &str, args: &[&str]| -> Option<HashMap<String, String>> {
+        let output = Command::new(program).args(args).output().ok()?;
+        output
+            .status
+            .success()
+            .then(|| parse_states(&String::from_utf8_lossy(&output.stdout)))
+    };
+    query(
+        "sacct",
+        &["-n", "-P", "-X", "-o", "JobID,State", "-j", &ids],
+    )
+    .filter(|states| !states.is_empty())
+    .or_else(|| query("squeue", &["-h", "-o", "%i|%T", "-j", &ids]))
+    .unwrap_or_default()
+}
+
+/// `id|STATE` lines; reasons after the state word (`CANCELLED by 1000`) and
+/// job steps (`4242.batch`) are dropped.
+pub fn parse_states(text: &str) -> HashMap<String, String> {
+    text.lines()
+        .filter_map(|line| {
+            let (id, state) = line.trim().split_once('|')?;
+            let state = state.split_whitespace().next()?;
+            (!id.contains('.')).then(|| (id.to_string(), state.to_string()))
+        })
+        .collect()
+}
+
+/// Run status a job state stands for; None for states og has no word for.
+pub fn status_for(state: &str) -> Option<&'static str> {
+    Some(match state.trim_end_matches('+') {
+        "RUNNING" | "COMPLETING" => "running",
+        "PENDING" | "CONFIGURING" | "REQUEUED" | "REQUEUE_HOLD" | "REQUEUE_FED" => "queued",
+        "SUSPENDED" | "STOPPED" => "suspended",
+        "PREEMPTED" => "preempted",
+        "CANCELLED" => "cancelled",
+        "TIMEOUT" | "DEADLINE" => "timeout",
+        "COMPLETED" => "finished",
+        "FAILED" | "NODE_FAIL" | "OUT_OF_MEMORY" | "BOOT_FAIL" => "crashed",
+        _ => return None,
+    })
+}
+
+/// A run's status with its job's folded in. Queue states and kills by the
+/// scheduler say why a run stopped, unless the run recorded its own end. A
+/// job's end only stands in for file times (`inactive`, `unknown`), since one
+/// job may run several runs; a running job says nothing the run does not.
+pub fn merged_status<'a>(status: &'a str, job_status: &'a str) -> &'a str {
+    match job_status {
+        "running" => status,
+        "finished" | "crashed" if matches!(status, "inactive" | "unknown") => job_status,
+        "finished" | "crashed" => status,
+        _ if matches!(status, "finished" | "crashed") => status,
+        _ => job_status,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merged_status, parse_states, status_for};
+
+    #[test]
+    fn job_states_override_what_file_times_cannot_tell() {
+        let states = parse_states(
+            "4242|CANCELLED by 1000\n4242.batch|CANCELLED\n4300_3|PREEMPTED\n4301|RUNNING\n",
+        );
+        assert_eq!(states.len(), 3);
+        assert_eq!(states["4242"], "CANCELLED");
+        assert_eq!(status_for(&states["4300_3"]), Some("preempted"));
+        assert_eq!(status_for("OUT_OF_MEMORY"), Some("crashed"));
+        assert_eq!(status_for("SPECIAL_EXIT"), None);
+
+        assert_eq!(merged_status("inactive", "preempted"), "preempted");
+        assert_eq!(merged_status("killed", "timeout"), "timeout");
+        assert_eq!(merged_status("running", "queued"), "queued");
+        assert_eq!(merged_status("crashed", "cancelled"), "crashed");
+        assert_eq!(merged_status("inactive", "finished"), "finished");
+        assert_eq!(merged_status("killed", "finished"), "killed");
+        assert_eq!(merged_status("inactive", "running"), "inactive");
+    }
+}
@@ -0,0 +1,150 @@
+use anyhow::{Context, Result};
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::app::App;
+
+/// Render one frame of `app` into an off-screen buffer of the given size.
+pub fn render(app: &mut App, width: u16, height: u16) -> Result<Buffer> {
+    let mut terminal =
+        Terminal::new(TestBackend::new(width, height)).context("creating snapshot backend")?;
+    terminal
+        .draw(|f| {
+            crate::ui::draw(f, app);
+        })
+        .context("rendering snapshot")?;
+    Ok(terminal.backend().buffer().clone())
+}
+
+/// The buffer as plain text, one line per row, trailing blanks trimmed so
+/// golden files diff cleanly.
+pub fn to_plain(buffer: &Buffer) -> String {
+    let mut out = String::new();
+    for y in 0..buffer.area.height {
+        let mut line = String::new();
+        for x in 0..buffer.area.width {
+            line.push_str(buffer[(x, y)].symbol());
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// The buffer with SGR escapes for colors and modifiers; escapes are only
+/// emitted where the style changes, and every line ends with a reset.
+pub fn to_ansi(buffer: &Buffer) -> String {
+    let mut out = String::new();
+    for y in 0..buffer.area.height {
+        let mut current = Style::default();
+        for x in 0..buffer.area.width {
+            let cell = &buffer[(x, y)];
+            let style = cell.style();
+            if style != current {
+                out.push_str(&sgr(style));
+                current = style;
+            }
+            out.push_str(cell.symbol());
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+fn sgr(style: Style) -> String {
+    let mut codes = vec!["0".to_string()];
+    let modifiers = [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::CROSSED_OUT, "9"),
+    ];
+    for (modifier, code) in modifiers {
+        if style.add_modifier.contains(modifier) {
+            codes.push(code.to_string());
+        }
+    }
+    if let Some(fg) = style.fg.and_then(|c| color_code(c, false)) {
+        codes.push(fg);
+    }
+    if let Some(bg) = style.bg.and_then(|c| color_code(c, true)) {
+        codes.push(bg);
+    }
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+fn color_code(color: Color, background: bool) -> Option<String> {
+    let base = if background { 40 } else { 30 };
+    let code = match color {
+        Color::Reset => return None,
+        Color::Black => base.to_string(),
+        Color::Red => (base + 1).to_string(),
+        Color::Green => (base + 2).to_string(),
+        Color::Yellow => (base + 3).to_string(),
+        Color::Blue => (base + 4).to_string(),
+        Color::Magenta => (base + 5).to_string(),
+        Color::Cyan => (base + 6).to_string(),
+        Color::Gray => (base + 7).to_string(),
+        Color::DarkGray => (base + 60).to_string(),
+        Color::LightRed => (base + 61).to_string(),
+        Color::LightGreen => (base + 62).to_string(),
+        Color::LightYellow => (base + 63).to_string(),
+        Color::LightBlue => (base + 64).to_string(),
+        Color::LightMagenta => (base + 65).to_string(),
+        Color::LightCyan => (base + 66).to_string(),
+        Color::White => (base + 67).to_string(),
+        Color::Indexed(idx) => format!("{};5;{idx}", base + 8),
+        Color::Rgb(r, g, b) => format!("{};2;{r};{g};{b}", base + 8),
+    };
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, to_ansi, to_plain};
+    use crate::app::{App, Tab};
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    #[test]
+    fn renders_graphs_and_focused_metric_as_text() {
+        let mut scalars = BTreeMap::new();
+        scalars.insert(
+            "train/loss".to_string(),
+            (0..50)
+                .map(|s| (s as f64, 1.0 / (1.0 + s as f64)))
+                .collect(),
+        );
+        scalars.insert(
+            "val/acc".to_string(),
+            (0..50).map(|s| (s as f64, s as f64 / 50.0)).collect(),
+        );
+        let mut app = App::new(
+            scalars,
+            BTreeMap::new(),
+            Vec::new(),
+            PathBuf::from("runs/demo"),
+            100,
+            49,
+        );
+        app.active_tab = Tab::Graphs;
+
+        let grid = to_plain(&render(&mut app, 100, 30).expect("render grid"));
+        assert_eq!(grid.lines().count(), 30);
+        assert!(grid.contains("train/loss") && grid.contains("val/acc"));
+        assert!(grid.lines().all(|line| line == line.trim_end()));
+
+        app.focus_metric(1);
+        let buffer = render(&mut app, 100, 30).expect("render focused");
+        let focused = to_plain(&buffer);
+        assert!(focused.contains("val/acc") && !focused.contains("train/loss"));
+
+        let ansi = to_ansi(&buffer);
+        assert!(ansi.contains("\x1b[0;"));
+        assert_eq!(ansi.matches("\x1b[0m\n").count(), 30);
+    }
+}
@@ -1,18 +1,22 @@
-use std::io::{BufRead, BufReader, Write};
+use std::io::{BufRead, BufReader, Read, Write};
 use std::os::unix::net::UnixStream;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
+use og_core::error::OgError;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+/// File name prefix of the sockets og creates for daemons.
+pub const SOCKET_PREFIX: &str = "opengraphs-ogd";
+
 /// Default socket path matching the Python daemon.
 fn default_socket_path() -> PathBuf {
     let tmpdir = std::env::var("TMPDIR")
         .or_else(|_| std::env::var("TEMP"))
         .or_else(|_| std::env::var("TMP"))
         .unwrap_or_else(|_| "/tmp".to_string());
-    PathBuf::from(tmpdir).join("opengraphs-ogd.sock")
+    PathBuf::from(tmpdir).join(format!("{SOCKET_PREFIX}.sock"))
 }
 
 /// Resolve the socket path from env or default.
@@ -22,6 +26,45 @@ pub fn socket_path() -> PathBuf {
         .unwrap_or_else(|_| default_socket_path())
 }
 
+/// Directory daemon sockets live in, where `og daemon list` looks for them.
+pub fn socket_dir() -> PathBuf {
+    socket_path()
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+}
+
+/// Socket of the daemon supervising `run_dir`, so daemons for different runs
+/// do not collide: `opengraphs-ogd-<run>-<hash>.sock`, the hash taken over
+/// the canonical run path. Kept short; unix socket paths max out near 100
+/// bytes.
+pub fn socket_path_for_run(run_dir: &Path) -> PathBuf {
+    let canonical = std::fs::canonicalize(run_dir).unwrap_or_else(|_| run_dir.to_path_buf());
+    let name: String = canonical
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(24)
+        .collect();
+    // FNV-1a: stable across builds, unlike the std hasher.
+    let hash = canonical
+        .as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+        });
+    socket_dir().join(format!("{SOCKET_PREFIX}-{name}-{:08x}.sock", hash as u32))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
     pub sender: String,
@@ -66,7 +109,7 @@ pub struct RunStateResponse {
 #[derive(Debug)]
 pub enum ClientError {
     SocketNotFound(PathBuf),
-    ConnectionFailed(std::io::Error),
+    ConnectionFailed(PathBuf, std::io::Error),
     SendFailed(std::io::Error),
     RecvFailed(std::io::Error),
     InvalidResponse(String),
@@ -77,7 +120,7 @@ impl std::fmt::Display for ClientError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ClientError::SocketNotFound(p) => write!(f, "Socket not found: {}", p.display()),
-            ClientError::ConnectionFailed(e) => write!(f, "Connection failed: {e}"),
+            ClientError::ConnectionFailed(_, e) => write!(f, "Connection failed: {e}"),
             ClientError::SendFailed(e) => write!(f, "Send failed: {e}"),
             ClientError::RecvFailed(e) => write!(f, "Recv failed: {e}"),
             ClientError::InvalidResponse(s) => write!(f, "Invalid response: {s}"),
@@ -88,14 +131,34 @@ impl std::fmt::Display for ClientError {
 
 impl std::error::Error for ClientError {}
 
-/// Send a JSON request to the daemon and return the parsed response.
-fn send_request(payload: &Value, sock_path: &Path) -> Result<Value, ClientError> {
+impl ClientError {
+    /// The typed CLI error when no daemon is there to answer at all.
+    pub fn as_unreachable(&self) -> Option<OgError> {
+        let (socket, reason) = match self {
+            ClientError::SocketNotFound(p) => (p, "socket not found".to_string()),
+            ClientError::ConnectionFailed(p, e) => (p, e.to_string()),
+            _ => return None,
+        };
+        Some(OgError::DaemonUnreachable {
+            socket: socket.clone(),
+            reason,
+        })
+    }
+}
+
+/// Connect and write one JSON request line.
+fn open_request(
+    payload: &Value,
+    sock_path: &Path,
+    read_timeout: Duration,
+) -> Result<BufReader<UnixStream>, ClientError> {
     if !sock_path.exists() {
         return Err(ClientError::SocketNotFound(sock_path.to_path_buf()));
     }
 
-    let mut stream = UnixStream::connect(sock_path).map_err(ClientError::ConnectionFailed)?;
-    stream.set_read_timeout(Some(Duration::from_secs(30))).ok();
+    let mut stream = UnixStream::connect(sock_path)
+        .map_err(|e| ClientError::ConnectionFailed(sock_path.to_path_buf(), e))?;
+    stream.set_read_timeout(Some(read_timeout)).ok();
     stream.set_write_timeout(Some(Duration::from_secs(5))).ok();
 
     let mut msg = serde_json::to_string(payload).unwrap();
@@ -104,14 +167,33 @@ fn send_request(payload: &Value, sock_path: &Path) -> Result<Value, ClientError>
         .write_all(msg.as_bytes())
         .map_err(ClientError::SendFailed)?;
     stream.flush().map_err(ClientError::SendFailed)?;
+    Ok(BufReader::new(stream))
+}
+
+/// Longest response line accepted. Run state with long log tails runs to a
+/// few megabytes; a peer sending more without a newline is not a daemon.
+pub const MAX_RESPONSE_BYTES: u64 = 64 << 20;
 
-    let mut reader = BufReader::new(stream);
-    let mut line = String::new();
+/// Read one response line, turning `"ok": false` into `DaemonError`.
+fn read_response(reader: &mut BufReader<UnixStream>) -> Result<Value, ClientError> {
+    let mut line = Vec::new();
     reader
-        .read_line(&mut line)
+        .by_ref()
+        .take(MAX_RESPONSE_BYTES + 1)
+        .read_until(b'\n', &mut line)
         .map_err(ClientError::RecvFailed)?;
+    if line.len() as u64 > MAX_RESPONSE_BYTES {
+        return Err(ClientError::InvalidResponse(format!(
+            "response line longer than {MAX_RESPONSE_BYTES} bytes"
+        )));
+    }
+    decode_response(&line)
+}
 
-    let resp: Value = serde_json::from_str(line.trim())
+/// Decode one response line of the daemon protocol. Total over arbitrary
+/// bytes: anything but a JSON object with `"ok": true` is an error.
+pub fn decode_response(line: &[u8]) -> Result<Value, ClientError> {
+    let resp: Value = serde_json::from_slice(line.trim_ascii())
         .map_err(|e| ClientError::InvalidResponse(e.to_string()))?;
 
     if resp.get("ok").and_then(|v| v.as_bool()) != Some(true) {
@@ -126,6 +208,13 @@ fn send_request(payload: &Value, sock_path: &Path) -> Result<Value, ClientError>
     Ok(resp)
 }
 
+/// Send a JSON request to the daemon and return the parsed response.
+#[tracing::instrument(level = "debug", skip_all, fields(request = %payload["type"]), err(level = "debug"))]
+fn send_request(payload: &Value, sock_path: &Path) -> Result<Value, ClientError> {
+    let mut reader = open_request(payload, sock_path, Duration::from_secs(30))?;
+    read_response(&mut reader)
+}
+
 /// Ping the daemon.
 pub fn ping(sock_path: &Path) -> Result<bool, ClientError> {
     let resp = send_request(&serde_json::json!({"type": "ping"}), sock_path)?;
@@ -144,16 +233,16 @@ pub fn get_chat_history(sock_path: &Path) -> Result<Vec<ChatMessage>, ClientErro
     Ok(messages)
 }
 
-/// Send a chat message to the daemon and get the response.
-pub fn send_chat_message(
-    content: &str,
-    sock_path: &Path,
-) -> Result<(ActionPlanResponse, Vec<ChatMessage>), ClientError> {
-    let resp = send_request(
-        &serde_json::json!({"type": "chat_message", "content": content}),
-        sock_path,
-    )?;
+/// Incremental output of a streamed chat reply.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ChatStreamEvent {
+    /// Next piece of the agent's answer text.
+    Chunk(String),
+    /// Progress note, e.g. which tool the agent is calling.
+    Status(String),
+}
 
+fn parse_chat_reply(resp: &Value) -> Result<(ActionPlanResponse, Vec<ChatMessage>), ClientError> {
     let plan: ActionPlanResponse = resp
         .get("response")
         .cloned()
@@ -172,34 +261,36 @@ pub fn send_chat_message(
     Ok((plan, history))
 }
 
-/// Apply a refactor plan (approve from TUI).
-pub fn apply_refactor(
-    plan: &ActionPlanResponse,
+/// Send a chat message and stream the reply. The daemon answers with
+/// `{"type": "chunk"|"status", "text": ...}` lines while the agent works and
+/// a final line carrying `response` and `chat_history`; daemons without
+/// streaming send only the final line. The read timeout applies per line, so
+/// a long answer is fine as long as something arrives every two minutes.
+#[tracing::instrument(level = "debug", skip_all, err(level = "debug"))]
+pub fn stream_chat_message(
+    content: &str,
     sock_path: &Path,
-) -> Result<(bool, Vec<ChatMessage>), ClientError> {
-    let resp = send_request(
-        &serde_json::json!({
-            "type": "apply_refactor",
-            "diagnosis": plan.diagnosis,
-            "action": plan.action,
-            "code_changes": plan.code_changes,
-            "raw_output": plan.raw_output,
-        }),
+    mut on_event: impl FnMut(ChatStreamEvent),
+) -> Result<(ActionPlanResponse, Vec<ChatMessage>), ClientError> {
+    let mut reader = open_request(
+        &serde_json::json!({"type": "chat_message", "content": content, "stream": true}),
         sock_path,
+        Duration::from_secs(120),
     )?;
-
-    let success = resp
-        .get("success")
-        .and_then(|v| v.as_bool())
-        .unwrap_or(false);
-    let history_val = resp
-        .get("chat_history")
-        .cloned()
-        .unwrap_or(Value::Array(vec![]));
-    let history: Vec<ChatMessage> = serde_json::from_value(history_val)
-        .map_err(|e| ClientError::InvalidResponse(e.to_string()))?;
-
-    Ok((success, history))
+    loop {
+        let resp = read_response(&mut reader)?;
+        let text = || {
+            resp.get("text")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+        match resp.get("type").and_then(|v| v.as_str()) {
+            Some("chunk") => on_event(ChatStreamEvent::Chunk(text())),
+            Some("status") => on_event(ChatStreamEvent::Status(text())),
+            _ => return parse_chat_reply(&resp),
+        }
+    }
 }
 
 /// Update daemon training file path.
@@ -245,6 +336,37 @@ pub fn start_training(sock_path: &Path) -> Result<(), ClientError> {
     Ok(())
 }
 
+/// Restart training from a saved checkpoint; the daemon passes its path to
+/// the training process as `OG_RESUME_CHECKPOINT`.
+pub fn resume_training(checkpoint: &Path, sock_path: &Path) -> Result<(), ClientError> {
+    let _resp = send_request(
+        &serde_json::json!({
+            "type": "start_training",
+            "resume_from": checkpoint.to_string_lossy().to_string(),
+        }),
+        sock_path,
+    )?;
+    Ok(())
+}
+
+/// Ask the daemon to stop the training process; `reason` goes to its log.
+pub fn stop_training(reason: &str, sock_path: &Path) -> Result<(), ClientError> {
+    let _resp = send_request(
+        &serde_json::json!({"type": "stop_training", "reason": reason}),
+        sock_path,
+    )?;
+    Ok(())
+}
+
+/// Add a line to the daemon's run log, which the agent reads as context.
+pub fn append_log(line: &str, sock_path: &Path) -> Result<(), ClientError> {
+    let _resp = send_request(
+        &serde_json::json!({"type": "log_append", "line": line}),
+        sock_path,
+    )?;
+    Ok(())
+}
+
 /// Get run state from the daemon.
 pub fn get_run_state(sock_path: &Path) -> Result<RunStateResponse, ClientError> {
     let resp = send_request(
@@ -352,7 +474,26 @@ mod tests {
         fs::write(&temp.socket, b"not a socket").unwrap();
 
         let err = ping(&temp.socket).unwrap_err();
-        assert!(matches!(err, ClientError::ConnectionFailed(_)));
+        assert!(matches!(err, ClientError::ConnectionFailed(..)));
+        let typed = err.as_unreachable().expect("unreachable");
+        assert_eq!(typed.exit_code(), 4);
+        assert!(typed.hint().expect("hint").contains("og daemon start"));
+    }
+
+    #[test]
+    fn run_sockets_are_short_and_distinct_per_run() {
+        let a = socket_path_for_run(Path::new("/runs/proj/2024-01-01 run"));
+        let b = socket_path_for_run(Path::new("/runs/other/2024-01-01 run"));
+        assert_ne!(a, b);
+        assert_eq!(
+            a,
+            socket_path_for_run(Path::new("/runs/proj/2024-01-01 run"))
+        );
+        let name = a.file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.starts_with("opengraphs-ogd-2024-01-01_run-"), "{name}");
+        assert!(name.ends_with(".sock"), "{name}");
+        let long = socket_path_for_run(&Path::new("/runs").join("x".repeat(200)));
+        assert!(long.file_name().unwrap().len() < 60);
     }
 
     #[test]
@@ -361,7 +502,7 @@ mod tests {
             with_server(|_| json_line(json!({"ok": true, "type": "pong"})), ping);
 
         assert_eq!(request, json!({"type": "ping"}));
-        assert_eq!(result.unwrap(), true);
+        assert!(result.unwrap());
     }
 
     #[test]
@@ -387,7 +528,7 @@ mod tests {
     }
 
     #[test]
-    fn send_chat_message_round_trips_request_and_structured_response() {
+    fn stream_chat_message_accepts_single_line_replies() {
         let (request, result) = with_server(
             |_| {
                 json_line(json!({
@@ -412,12 +553,16 @@ mod tests {
                     ]
                 }))
             },
-            |sock_path| send_chat_message("fix this", sock_path),
+            |sock_path| {
+                stream_chat_message("fix this", sock_path, |event| {
+                    panic!("unexpected stream event {event:?}")
+                })
+            },
         );
 
         assert_eq!(
             request,
-            json!({"type": "chat_message", "content": "fix this"})
+            json!({"type": "chat_message", "content": "fix this", "stream": true})
         );
 
         let (plan, history) = result.unwrap();
@@ -430,46 +575,44 @@ mod tests {
     }
 
     #[test]
-    fn apply_refactor_sends_plan_fields_and_returns_history() {
-        let plan = ActionPlanResponse {
-            diagnosis: "bad state".into(),
-            action: "apply fix".into(),
-            code_changes: "patch body".into(),
-            raw_output: "stderr".into(),
-        };
-
-        let (request, result) = with_server(
+    fn stream_chat_message_reports_chunks_before_the_final_reply() {
+        let (_, result) = with_server(
             |_| {
-                json_line(json!({
-                    "ok": true,
-                    "success": true,
-                    "chat_history": [
-                        {
-                            "sender": "system",
-                            "content": "applied",
-                            "timestamp": 3.0
-                        }
-                    ]
-                }))
+                [
+                    json_line(json!({"ok": true, "type": "status", "text": "Calling metric_tail"})),
+                    json_line(json!({"ok": true, "type": "chunk", "text": "Loss "})),
+                    json_line(json!({"ok": true, "type": "chunk", "text": "diverged."})),
+                    json_line(json!({
+                        "ok": true,
+                        "type": "done",
+                        "response": {
+                            "diagnosis": "Loss diverged.",
+                            "action": "explain",
+                            "code_changes": "",
+                            "raw_output": ""
+                        },
+                        "chat_history": []
+                    })),
+                ]
+                .concat()
+            },
+            |sock_path| {
+                let mut events = Vec::new();
+                let reply = stream_chat_message("why?", sock_path, |event| events.push(event));
+                (events, reply)
             },
-            |sock_path| apply_refactor(&plan, sock_path),
         );
 
+        let (events, reply) = result;
         assert_eq!(
-            request,
-            json!({
-                "type": "apply_refactor",
-                "diagnosis": "bad state",
-                "action": "apply fix",
-                "code_changes": "patch body",
-                "raw_output": "stderr"
-            })
+            events,
+            vec![
+                ChatStreamEvent::Status("Calling metric_tail".into()),
+                ChatStreamEvent::Chunk("Loss ".into()),
+                ChatStreamEvent::Chunk("diverged.".into()),
+            ]
         );
-
-        let (success, history) = result.unwrap();
-        assert!(success);
-        assert_eq!(history.len(), 1);
-        assert_eq!(history[0].content, "applied");
+        assert_eq!(reply.unwrap().0.diagnosis, "Loss diverged.");
     }
 
     #[test]
@@ -534,6 +677,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn decode_response_rejects_anything_but_ok_objects() {
+        assert!(decode_response(b"{\"ok\": true}\r\n").is_ok());
+        for line in [
+            &b""[..],
+            b"\xff\xfe",
+            b"[1, 2]",
+            b"null",
+            b"{\"ok\": 1}",
+            b"{\"ok\": true",
+        ] {
+            assert!(decode_response(line).is_err(), "{line:?}");
+        }
+        assert!(matches!(
+            decode_response(b"{\"ok\": false, \"error\": 3}"),
+            Err(ClientError::DaemonError(message)) if message == "unknown error"
+        ));
+    }
+
     #[test]
     fn send_request_rejects_invalid_json_responses() {
         let (_request, result) = with_server(|_| "not-json\n".to_string(), ping);
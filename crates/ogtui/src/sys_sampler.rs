@@ -0,0 +1,379 @@
+//! System sampler for runs the TUI starts: a background thread that reads GPU
+//! power, temperature, clocks, utilization and memory (NVML, through
+//! `nvidia-smi`), the CPU's package power (RAPL), temperature, clock,
+//! utilization and I/O wait, and host memory, and appends them as `sys/*`
+//! metrics to the run's `sys.metrics.jsonl`. Inside a container with a cgroup
+//! v2 CPU quota or memory limit, CPU utilization and memory are read against
+//! those instead of the host's.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::cgroup::Cgroup;
+use crate::power::{self, Rapl};
+
+/// Store the sampler appends to; read like any `*.metrics.jsonl`.
+pub const FILE_NAME: &str = "sys.metrics.jsonl";
+/// Default `--sys-interval-ms`.
+pub const DEFAULT_INTERVAL_MS: u64 = 5000;
+const RAPL_ROOT: &str = "/sys/class/powercap";
+const HWMON_ROOT: &str = "/sys/class/hwmon";
+const CPU_ROOT: &str = "/sys/devices/system/cpu";
+/// hwmon drivers that report CPU package or die temperatures.
+const CPU_HWMON_DRIVERS: [&str; 4] = ["coretemp", "k10temp", "zenpower", "cpu_thermal"];
+
+/// One row of `nvidia-smi --query-gpu`; fields it reports as `[N/A]` are None.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpuReading {
+    pub index: u32,
+    pub power_w: Option<f64>,
+    pub temp_c: Option<f64>,
+    pub sm_clock_mhz: Option<f64>,
+    pub util_pct: Option<f64>,
+    pub mem_used_mb: Option<f64>,
+    pub mem_total_mb: Option<f64>,
+}
+
+const GPU_QUERY: &str = "--query-gpu=index,power.draw,temperature.gpu,clocks.sm,utilization.gpu,memory.used,memory.total";
+
+fn query_gpus() -> Option<Vec<GpuReading>> {
+    let output = Command::new("nvidia-smi")
+        .args([GPU_QUERY, "--format=csv,noheader,nounits"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let gpus = parse_gpu_csv(&String::from_utf8_lossy(&output.stdout));
+    (!gpus.is_empty()).then_some(gpus)
+}
+
+/// Rows of `GPU_QUERY` output in `csv,noheader,nounits` format.
+pub fn parse_gpu_csv(text: &str) -> Vec<GpuReading> {
+    text.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [
+                index,
+                power_w,
+                temp_c,
+                sm_clock_mhz,
+                util_pct,
+                mem_used_mb,
+                mem_total_mb,
+            ] = fields[..]
+            else {
+                return None;
+            };
+            let number = |raw: &str| raw.parse::<f64>().ok().filter(|v| v.is_finite());
+            Some(GpuReading {
+                index: index.parse().ok()?,
+                power_w: number(power_w),
+                temp_c: number(temp_c),
+                sm_clock_mhz: number(sm_clock_mhz),
+                util_pct: number(util_pct),
+                mem_used_mb: number(mem_used_mb),
+                mem_total_mb: number(mem_total_mb),
+            })
+        })
+        .collect()
+}
+
+const GPU_APPS_QUERY: &str = "--query-compute-apps=pid,used_memory";
+
+/// GPU memory each process holds in MiB, summed over GPUs, for the
+/// processes tab. None without a working `nvidia-smi`.
+pub fn gpu_memory_by_pid() -> Option<HashMap<i32, f64>> {
+    let output = Command::new("nvidia-smi")
+        .args([GPU_APPS_QUERY, "--format=csv,noheader,nounits"])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| parse_gpu_apps_csv(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Rows of `GPU_APPS_QUERY` output in `csv,noheader,nounits` format.
+pub fn parse_gpu_apps_csv(text: &str) -> HashMap<i32, f64> {
+    let mut by_pid = HashMap::new();
+    for line in text.lines() {
+        let Some((pid, used)) = line.split_once(',') else {
+            continue;
+        };
+        if let (Ok(pid), Ok(used)) = (pid.trim().parse::<i32>(), used.trim().parse::<f64>())
+            && used.is_finite()
+        {
+            *by_pid.entry(pid).or_insert(0.0) += used;
+        }
+    }
+    by_pid
+}
+
+/// Jiffies from the aggregate `cpu` line of `/proc/stat`.
+#[derive(Debug, Clone, Copy)]
+struct CpuJiffies {
+    busy: u64,
+    iowait: u64,
+    total: u64,
+}
+
+fn cpu_jiffies() -> Option<CpuJiffies> {
+    let stat = fs::read_to_string("/proc/stat").ok()?;
+    let fields: Vec<u64> = stat
+        .lines()
+        .next()?
+        .strip_prefix("cpu ")?
+        .split_whitespace()
+        .filter_map(|field| field.parse().ok())
+        .collect();
+    // user nice system idle iowait irq softirq steal ...
+    let iowait = fields.get(4).copied().unwrap_or(0);
+    let idle = fields.get(3)? + iowait;
+    let total: u64 = fields.iter().take(8).sum();
+    Some(CpuJiffies {
+        busy: total - idle,
+        iowait,
+        total,
+    })
+}
+
+/// Used and total host memory from `/proc/meminfo`, in MiB. Used is what is
+/// not available, so reclaimable page cache does not count.
+pub fn host_memory_mb() -> Option<(f64, f64)> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    let field = |name: &str| -> Option<f64> {
+        let line = meminfo.lines().find(|line| line.starts_with(name))?;
+        let kib: f64 = line[name.len()..].split_whitespace().next()?.parse().ok()?;
+        Some(kib / 1024.0)
+    };
+    let total = field("MemTotal:")?;
+    Some((total - field("MemAvailable:")?, total))
+}
+
+/// Hottest CPU sensor any known hwmon driver reports, in °C.
+fn cpu_temp_c() -> Option<f64> {
+    let mut hottest: Option<f64> = None;
+    for entry in fs::read_dir(HWMON_ROOT).ok()?.flatten() {
+        let dir = entry.path();
+        let driver = fs::read_to_string(dir.join("name")).unwrap_or_default();
+        if !CPU_HWMON_DRIVERS.contains(&driver.trim()) {
+            continue;
+        }
+        for sensor in fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let name = sensor.file_name().to_string_lossy().into_owned();
+            if !(name.starts_with("temp") && name.ends_with("_input")) {
+                continue;
+            }
+            let Some(milli) = fs::read_to_string(sensor.path())
+                .ok()
+                .and_then(|raw| raw.trim().parse::<f64>().ok())
+            else {
+                continue;
+            };
+            let celsius = milli / 1000.0;
+            hottest = Some(hottest.map_or(celsius, |hot| hot.max(celsius)));
+        }
+    }
+    hottest
+}
+
+/// Mean current frequency over the cores cpufreq reports, in MHz.
+fn cpu_clock_mhz() -> Option<f64> {
+    let khz: Vec<f64> = fs::read_dir(CPU_ROOT)
+        .ok()?
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            name.strip_prefix("cpu")
+                .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+        })
+        .filter_map(|entry| {
+            fs::read_to_string(entry.path().join("cpufreq/scaling_cur_freq"))
+                .ok()?
+                .trim()
+                .parse()
+                .ok()
+        })
+        .collect();
+    (!khz.is_empty()).then(|| khz.iter().sum::<f64>() / khz.len() as f64 / 1000.0)
+}
+
+/// State carried between samples for counters read as deltas.
+struct Sampler {
+    rapl: Option<Rapl>,
+    cpu_jiffies: Option<CpuJiffies>,
+    /// Group whose limits CPU and memory are read against
+    cgroup: Option<Cgroup>,
+    /// The group's CPU time in microseconds, and when it was read
+    cgroup_cpu: Option<(u64, Instant)>,
+}
+
+impl Sampler {
+    fn readings(&mut self) -> Vec<(String, f64)> {
+        let mut readings = Vec::new();
+        if let Some(gpus) = query_gpus() {
+            let draws: Vec<f64> = gpus.iter().filter_map(|gpu| gpu.power_w).collect();
+            if !draws.is_empty() {
+                readings.push((power::GPU_TAG.to_string(), draws.iter().sum()));
+            }
+            for gpu in &gpus {
+                let i = gpu.index;
+                for (name, value) in [
+                    ("temp_c", gpu.temp_c),
+                    ("sm_clock_mhz", gpu.sm_clock_mhz),
+                    ("util_pct", gpu.util_pct),
+                    ("mem_used_mb", gpu.mem_used_mb),
+                    ("mem_total_mb", gpu.mem_total_mb),
+                ] {
+                    if let Some(value) = value {
+                        readings.push((format!("sys/gpu{i}/{name}"), value));
+                    }
+                }
+            }
+        }
+        if let Some(watts) = self.rapl.as_mut().and_then(Rapl::watts) {
+            readings.push((power::CPU_TAG.to_string(), watts));
+        }
+        let limits = self.cgroup.as_ref().map(Cgroup::limits).unwrap_or_default();
+        let jiffies = cpu_jiffies();
+        if let (Some(before), Some(now)) = (self.cpu_jiffies, jiffies)
+            && now.total > before.total
+        {
+            let elapsed = (now.total - before.total) as f64;
+            let share =
+                |after: u64, prior: u64| after.saturating_sub(prior) as f64 / elapsed * 100.0;
+            if limits.cpus.is_none() {
+                readings.push(("sys/cpu/util_pct".to_string(), share(now.busy, before.busy)));
+            }
+            readings.push((
+                "sys/cpu/iowait_pct".to_string(),
+                share(now.iowait, before.iowait),
+            ));
+        }
+        self.cpu_jiffies = jiffies;
+        if let (Some(group), Some(cpus)) = (&self.cgroup, limits.cpus) {
+            let usage = group.cpu_usage_usec().map(|usec| (usec, Instant::now()));
+            if let (Some((before, at)), Some((now, now_at))) = (self.cgroup_cpu, usage) {
+                let elapsed_usec = now_at.duration_since(at).as_micros() as f64;
+                if elapsed_usec > 0.0 {
+                    let used = now.saturating_sub(before) as f64;
+                    readings.push((
+                        "sys/cpu/util_pct".to_string(),
+                        (used / (elapsed_usec * cpus) * 100.0).min(100.0),
+                    ));
+                }
+            }
+            readings.push(("sys/cpu/limit_cores".to_string(), cpus));
+            self.cgroup_cpu = usage;
+        }
+        const MIB: f64 = 1024.0 * 1024.0;
+        let container_memory = self
+            .cgroup
+            .as_ref()
+            .and_then(|group| Some((group.memory_used_bytes()?, limits.memory_bytes?)));
+        if let Some((used, limit)) = container_memory {
+            readings.push(("sys/ram_used_mb".to_string(), used as f64 / MIB));
+            readings.push(("sys/ram_total_mb".to_string(), limit as f64 / MIB));
+        } else if let Some((used, total)) = host_memory_mb() {
+            readings.push(("sys/ram_used_mb".to_string(), used));
+            readings.push(("sys/ram_total_mb".to_string(), total));
+        }
+        if let Some(temp) = cpu_temp_c() {
+            readings.push(("sys/cpu/temp_c".to_string(), temp));
+        }
+        if let Some(clock) = cpu_clock_mhz() {
+            readings.push(("sys/cpu/clock_mhz".to_string(), clock));
+        }
+        readings
+    }
+}
+
+/// Append one line of readings at `step` to `<run_dir>/sys.metrics.jsonl`.
+fn append(run_dir: &Path, step: i64, readings: &[(String, f64)]) -> Result<()> {
+    let path = run_dir.join(FILE_NAME);
+    let metrics: serde_json::Map<String, serde_json::Value> = readings
+        .iter()
+        .map(|(tag, value)| (tag.clone(), serde_json::json!(value)))
+        .collect();
+    let mut line = serde_json::to_vec(&serde_json::json!({
+        "step": step,
+        "wall_time": crate::unix_now_secs_f64(),
+        "metrics": metrics,
+    }))?;
+    line.push(b'\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(&line))
+        .with_context(|| format!("appending to {}", path.display()))
+}
+
+/// Sample every `interval` on a background thread for as long as the process
+/// runs. Lines carry the run's latest step, read from `step`. Returns false
+/// when there is nothing to sample.
+pub fn spawn(run_dir: PathBuf, interval: Duration, step: Arc<AtomicI64>) -> bool {
+    let cgroup = Cgroup::detect();
+    let cgroup_cpu = cgroup
+        .as_ref()
+        .and_then(Cgroup::cpu_usage_usec)
+        .map(|usec| (usec, Instant::now()));
+    let mut sampler = Sampler {
+        rapl: Rapl::open(Path::new(RAPL_ROOT)),
+        cpu_jiffies: cpu_jiffies(),
+        cgroup,
+        cgroup_cpu,
+    };
+    if sampler.rapl.is_none() && sampler.cpu_jiffies.is_none() && query_gpus().is_none() {
+        return false;
+    }
+    std::thread::spawn(move || {
+        loop {
+            std::thread::sleep(interval);
+            let readings = sampler.readings();
+            if readings.is_empty() {
+                continue;
+            }
+            if fs::create_dir_all(&run_dir).is_err()
+                || append(&run_dir, step.load(Ordering::Relaxed), &readings).is_err()
+            {
+                return;
+            }
+        }
+    });
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_gpu_apps_csv, parse_gpu_csv};
+
+    #[test]
+    fn parses_nvidia_smi_rows_with_missing_fields() {
+        let gpus = parse_gpu_csv(
+            "0, 251.30, 71, 1980, 99, 30120, 81559\n1, [N/A], 84, 1410, 100, 512, 81559\nbogus\n",
+        );
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(gpus[0].power_w, Some(251.3));
+        assert_eq!(gpus[0].sm_clock_mhz, Some(1980.0));
+        assert_eq!((gpus[1].index, gpus[1].power_w), (1, None));
+        assert_eq!(gpus[1].temp_c, Some(84.0));
+        assert_eq!(gpus[1].mem_used_mb, Some(512.0));
+    }
+
+    #[test]
+    fn sums_gpu_memory_per_process_across_gpus() {
+        let by_pid = parse_gpu_apps_csv("4242, 30000\n4242, 1000\n77, [N/A]\n99, 512\n");
+        assert_eq!(by_pid.len(), 2);
+        assert_eq!(by_pid[&4242], 31000.0);
+        assert_eq!(by_pid[&99], 512.0);
+    }
+}
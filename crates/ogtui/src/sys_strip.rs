@@ -0,0 +1,107 @@
+//! The hardware strip under the header: the latest GPU utilization and
+//! memory, CPU and RAM readings the system sampler logged, plus the run's
+//! step rate, so they stay in view on every tab.
+
+use crate::dashboard;
+use crate::tfevents::{ScalarEvent, SeriesByTag};
+
+/// Latest readings, as percentages except the step rate.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SysStrip {
+    /// Mean utilization across GPUs
+    pub gpu_util: Option<f64>,
+    /// Memory used across GPUs, as a share of their total
+    pub gpu_mem: Option<f64>,
+    pub cpu: Option<f64>,
+    pub ram: Option<f64>,
+    pub steps_per_sec: Option<f64>,
+}
+
+impl SysStrip {
+    /// Read from a run's series before any graph filter hides the `sys/*` tags.
+    pub fn read(scalars: &SeriesByTag, events: &[ScalarEvent]) -> Self {
+        let latest = |tag: &str| scalars.get(tag)?.last().map(|(_, value)| *value);
+        let mut gpu_util = Vec::new();
+        let (mut gpu_used, mut gpu_total) = (0.0, 0.0);
+        for (tag, series) in scalars {
+            let Some((_, value)) = series.last() else {
+                continue;
+            };
+            match gpu_reading(tag) {
+                Some("util_pct") => gpu_util.push(*value),
+                Some("mem_used_mb") => gpu_used += value,
+                Some("mem_total_mb") => gpu_total += value,
+                _ => {}
+            }
+        }
+        Self {
+            gpu_util: (!gpu_util.is_empty())
+                .then(|| gpu_util.iter().sum::<f64>() / gpu_util.len() as f64),
+            gpu_mem: share(gpu_used, gpu_total),
+            cpu: latest("sys/cpu/util_pct"),
+            ram: share(
+                latest("sys/ram_used_mb").unwrap_or(0.0),
+                latest("sys/ram_total_mb").unwrap_or(0.0),
+            ),
+            steps_per_sec: dashboard::steps_per_sec(events),
+        }
+    }
+
+    /// Nothing to show: no sampler readings and no step rate.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The percentage gauges, labelled, in strip order.
+    pub fn gauges(&self) -> [(&'static str, Option<f64>); 4] {
+        [
+            ("GPU", self.gpu_util),
+            ("VRAM", self.gpu_mem),
+            ("CPU", self.cpu),
+            ("RAM", self.ram),
+        ]
+    }
+}
+
+/// `util_pct` for `sys/gpu3/util_pct`; None for other tags.
+fn gpu_reading(tag: &str) -> Option<&str> {
+    let (index, name) = tag.strip_prefix("sys/gpu")?.split_once('/')?;
+    (!index.is_empty() && index.bytes().all(|b| b.is_ascii_digit())).then_some(name)
+}
+
+fn share(used: f64, total: f64) -> Option<f64> {
+    (total > 0.0).then(|| (used / total * 100.0).clamp(0.0, 100.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SysStrip;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn reads_the_latest_sampler_values_across_gpus() {
+        let scalars = BTreeMap::from(
+            [
+                ("sys/gpu0/util_pct", 20.0),
+                ("sys/gpu1/util_pct", 60.0),
+                ("sys/gpu0/mem_used_mb", 1000.0),
+                ("sys/gpu1/mem_used_mb", 3000.0),
+                ("sys/gpu0/mem_total_mb", 8000.0),
+                ("sys/gpu1/mem_total_mb", 8000.0),
+                ("sys/gpu_power_w", 300.0),
+                ("sys/cpu/util_pct", 12.5),
+                ("sys/ram_used_mb", 4096.0),
+                ("train/loss", 0.5),
+            ]
+            .map(|(tag, value)| (tag.to_string(), vec![(0.0, 99.0), (1.0, value)])),
+        );
+        let strip = SysStrip::read(&scalars, &[]);
+        assert_eq!(strip.gpu_util, Some(40.0));
+        assert_eq!(strip.gpu_mem, Some(25.0));
+        assert_eq!(strip.cpu, Some(12.5));
+        assert_eq!(strip.ram, None, "no total, no share");
+        assert_eq!(strip.steps_per_sec, None);
+        assert!(!strip.is_empty());
+        assert!(SysStrip::read(&BTreeMap::new(), &[]).is_empty());
+    }
+}
@@ -0,0 +1,335 @@
+//! Small 2-D summaries: confusion matrices, per-class accuracy tables and the
+//! like. They are read from numeric 2-D tensors in `.tfevents` files and from
+//! structured events whose payload holds a `rows` matrix:
+//! `{"type": "table", "name": "val/confusion", "rows": [[50, 2], [3, 45]], "labels": ["cat", "dog"]}`.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::metrics_jsonl::{self, StructuredEvent};
+use crate::tfevents;
+use crate::tfrecord;
+
+/// Tables with more rows or columns than this are not "small" and are skipped.
+pub const MAX_DIM: usize = 64;
+/// TensorBoard plugins whose 2-D tensors are not tables.
+const NON_TABLE_PLUGINS: [&str; 3] = ["histograms", "pr_curves", "mesh"];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Table {
+    /// Tag, or the event's `name` (its type when it has none).
+    pub name: String,
+    pub step: i64,
+    pub row_labels: Vec<String>,
+    pub col_labels: Vec<String>,
+    pub rows: Vec<Vec<f64>>,
+}
+
+impl Table {
+    /// None unless `rows` is a non-empty rectangle within `MAX_DIM`. Labels
+    /// that don't match the shape are replaced by indices.
+    fn new(
+        name: String,
+        step: i64,
+        rows: Vec<Vec<f64>>,
+        row_labels: Option<Vec<String>>,
+        col_labels: Option<Vec<String>>,
+    ) -> Option<Table> {
+        let cols = rows.first()?.len();
+        if cols == 0 || rows.len() > MAX_DIM || cols > MAX_DIM {
+            return None;
+        }
+        if rows.iter().any(|row| row.len() != cols) {
+            return None;
+        }
+        let labels = |given: Option<Vec<String>>, n: usize| match given {
+            Some(labels) if labels.len() == n => labels,
+            _ => (0..n).map(|i| i.to_string()).collect(),
+        };
+        Some(Table {
+            name,
+            step,
+            row_labels: labels(row_labels, rows.len()),
+            col_labels: labels(col_labels, cols),
+            rows,
+        })
+    }
+
+    /// Lowest and highest finite cell.
+    pub fn range(&self) -> Option<(f64, f64)> {
+        self.rows
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|v| v.is_finite())
+            .fold(None, |range, v| match range {
+                None => Some((v, v)),
+                Some((lo, hi)) => Some((lo.min(v), hi.max(v))),
+            })
+    }
+
+    /// Aligned text grid with a `name @ step N` title line.
+    pub fn render_text(&self) -> String {
+        let cells: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|v| format_cell(*v)).collect())
+            .collect();
+        let label_w = self
+            .row_labels
+            .iter()
+            .map(|label| label.chars().count())
+            .max()
+            .unwrap_or(0);
+        let col_w: Vec<usize> = self
+            .col_labels
+            .iter()
+            .enumerate()
+            .map(|(j, label)| {
+                cells
+                    .iter()
+                    .map(|row| row[j].chars().count())
+                    .chain([label.chars().count()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let mut lines = vec![format!("{} @ step {}", self.name, self.step)];
+        let header: Vec<String> = self
+            .col_labels
+            .iter()
+            .zip(&col_w)
+            .map(|(label, w)| format!("{label:>w$}"))
+            .collect();
+        lines.push(format!("{:label_w$}  {}", "", header.join("  ")));
+        for (label, row) in self.row_labels.iter().zip(&cells) {
+            let row: Vec<String> = row
+                .iter()
+                .zip(&col_w)
+                .map(|(cell, w)| format!("{cell:>w$}"))
+                .collect();
+            lines.push(format!("{label:<label_w$}  {}", row.join("  ")));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Counts print as integers, everything else with three decimals.
+pub fn format_cell(v: f64) -> String {
+    if v.is_finite() && v.fract() == 0.0 && v.abs() < 1e9 {
+        format!("{v:.0}")
+    } else {
+        format!("{v:.3}")
+    }
+}
+
+/// The table in a structured event, if its payload has a numeric `rows`
+/// matrix. `labels` names both axes (a confusion matrix); `row_labels` and
+/// `col_labels` override it per axis.
+pub fn from_event(event: &StructuredEvent) -> Option<Table> {
+    let rows: Vec<Vec<f64>> = event
+        .payload
+        .get("rows")?
+        .as_array()?
+        .iter()
+        .map(|row| row.as_array()?.iter().map(|v| v.as_f64()).collect())
+        .collect::<Option<_>>()?;
+    let strings = |key: &str| -> Option<Vec<String>> {
+        event
+            .payload
+            .get(key)?
+            .as_array()?
+            .iter()
+            .map(|label| match label {
+                serde_json::Value::String(label) => Some(label.clone()),
+                other => other.as_f64().map(|_| other.to_string()),
+            })
+            .collect()
+    };
+    let name = event
+        .payload
+        .get("name")
+        .and_then(|name| name.as_str())
+        .unwrap_or(&event.kind)
+        .to_string();
+    Table::new(
+        name,
+        event.step,
+        rows,
+        strings("row_labels").or_else(|| strings("labels")),
+        strings("col_labels").or_else(|| strings("labels")),
+    )
+}
+
+/// 2-D numeric tensors in event file bytes, skipping those a TensorBoard
+/// plugin such as histograms claims.
+pub fn parse_tfevents(bytes: &[u8]) -> Vec<Table> {
+    let Ok((records, _)) = tfrecord::decode_records(bytes, true) else {
+        return Vec::new();
+    };
+    let mut plugins: HashMap<String, String> = HashMap::new();
+    let mut tables = Vec::new();
+    for (_, event) in records {
+        for value in event.summary.into_iter().flat_map(|summary| summary.value) {
+            if let Some(plugin) = value.metadata.and_then(|meta| meta.plugin_data) {
+                plugins.insert(value.tag.clone(), plugin.plugin_name);
+            }
+            if plugins
+                .get(&value.tag)
+                .is_some_and(|plugin| NON_TABLE_PLUGINS.contains(&plugin.as_str()))
+            {
+                continue;
+            }
+            if let Some(table) = value
+                .tensor
+                .and_then(|tensor| tensor.matrix())
+                .and_then(|rows| Table::new(value.tag, event.step, rows, None, None))
+            {
+                tables.push(table);
+            }
+        }
+    }
+    tables
+}
+
+/// Every table logged under `run_path`, ordered by step. Unreadable files are
+/// skipped; `load_run` already warns about them.
+pub fn load(run_path: &Path) -> Result<Vec<Table>> {
+    let mut tables = Vec::new();
+    for path in tfevents::discover_event_files(run_path)? {
+        if tfevents::is_tfevents_file(&path)
+            && let Ok(bytes) = crate::compression::read(&path)
+        {
+            tables.extend(parse_tfevents(&bytes));
+        }
+    }
+    tables.extend(
+        metrics_jsonl::load_structured(run_path)?
+            .iter()
+            .filter_map(from_event),
+    );
+    tables.sort_by_key(|table| table.step);
+    Ok(tables)
+}
+
+/// The last version of each table, by name.
+pub fn latest(tables: Vec<Table>) -> Vec<Table> {
+    let mut by_name = BTreeMap::new();
+    for table in tables {
+        by_name.insert(table.name.clone(), table);
+    }
+    by_name.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_event, latest, parse_tfevents};
+    use crate::metrics_jsonl::StructuredEvent;
+    use crate::tfrecord::{
+        Event, PluginData, Summary, SummaryMetadata, SummaryValue, TensorProto, TensorShape,
+        TensorShapeDim, encode_record,
+    };
+    use prost::Message;
+
+    fn matrix_value(tag: &str, plugin: Option<&str>) -> SummaryValue {
+        SummaryValue {
+            tag: tag.to_string(),
+            simple_value: None,
+            histo: None,
+            audio: None,
+            tensor: Some(TensorProto {
+                dtype: 3, // DT_INT32
+                tensor_shape: Some(TensorShape {
+                    dim: vec![TensorShapeDim { size: 2 }, TensorShapeDim { size: 3 }],
+                }),
+                int_val: vec![5, 1, 0, 2, 7, 1],
+                ..Default::default()
+            }),
+            metadata: plugin.map(|name| SummaryMetadata {
+                plugin_data: Some(PluginData {
+                    plugin_name: name.to_string(),
+                }),
+            }),
+        }
+    }
+
+    #[test]
+    fn reads_matrix_tensors_but_not_histograms() {
+        let record = |step: i64, values: Vec<SummaryValue>| {
+            encode_record(
+                &Event {
+                    wall_time: 1.0,
+                    step,
+                    file_version: None,
+                    summary: Some(Summary { value: values }),
+                }
+                .encode_to_vec(),
+            )
+        };
+        let mut bytes = record(
+            1,
+            vec![
+                matrix_value("val/confusion", None),
+                matrix_value("weights", Some("histograms")),
+            ],
+        );
+        // TF2 writes the plugin name with a tag's first value only.
+        bytes.extend(record(2, vec![matrix_value("weights", None)]));
+
+        let tables = parse_tfevents(&bytes);
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].name, "val/confusion");
+        assert_eq!(
+            tables[0].rows,
+            vec![vec![5.0, 1.0, 0.0], vec![2.0, 7.0, 1.0]]
+        );
+        assert_eq!(tables[0].col_labels, vec!["0", "1", "2"]);
+        assert_eq!(tables[0].range(), Some((0.0, 7.0)));
+    }
+
+    #[test]
+    fn reads_tables_from_structured_events() {
+        let event = |step: i64, payload: serde_json::Value| StructuredEvent {
+            step,
+            wall_time: 0.0,
+            kind: "table".to_string(),
+            payload: payload.as_object().cloned().expect("object"),
+        };
+        let confusion = event(
+            10,
+            serde_json::json!({
+                "name": "val/confusion",
+                "rows": [[50, 2], [3, 45]],
+                "labels": ["cat", "dog"],
+            }),
+        );
+        let table = from_event(&confusion).expect("table");
+        assert_eq!(table.row_labels, vec!["cat", "dog"]);
+        assert_eq!(
+            table.render_text(),
+            "val/confusion @ step 10\n     cat  dog\ncat   50    2\ndog    3   45"
+        );
+
+        let accuracy = event(
+            20,
+            serde_json::json!({"rows": [[0.9, 0.75]], "col_labels": ["cat", "dog"]}),
+        );
+        let table = from_event(&accuracy).expect("table");
+        assert_eq!(table.name, "table");
+        assert_eq!(
+            table.render_text(),
+            "table @ step 20\n     cat    dog\n0  0.900  0.750"
+        );
+
+        let ragged = event(30, serde_json::json!({"rows": [[1, 2], [3]]}));
+        assert_eq!(from_event(&ragged), None);
+
+        let mut newer = from_event(&confusion).expect("table");
+        newer.step = 40;
+        let kept = latest(vec![from_event(&confusion).expect("table"), newer]);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].step, 40);
+    }
+}
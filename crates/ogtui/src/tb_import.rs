@@ -0,0 +1,137 @@
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::tfevents::is_tfevents_file;
+
+/// A TensorBoard run: a directory holding event files directly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TbRun {
+    /// Run name derived from the directory's path below the log dir.
+    pub name: String,
+    pub dir: PathBuf,
+    pub files: Vec<PathBuf>,
+}
+
+/// Find runs the way TensorBoard does: every directory under `logdir` with
+/// event files directly inside is one run, named by its relative path. Nested
+/// paths are joined with `-` (`resnet/train` becomes `resnet-train`) so each
+/// run lands directly under the project.
+pub fn discover_runs(logdir: &Path) -> Result<Vec<TbRun>> {
+    if !logdir.is_dir() {
+        bail!("log dir '{}' is not a directory", logdir.display());
+    }
+    let mut runs = Vec::new();
+    let mut pending = vec![logdir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&dir).with_context(|| format!("reading {}", dir.display()))? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if is_tfevents_file(&path) {
+                files.push(path);
+            }
+        }
+        if files.is_empty() {
+            continue;
+        }
+        files.sort();
+        let relative = dir.strip_prefix(logdir).unwrap_or(&dir);
+        let name = if relative.as_os_str().is_empty() {
+            logdir
+                .canonicalize()
+                .ok()
+                .and_then(|dir| dir.file_name().map(|n| n.to_string_lossy().into_owned()))
+                .unwrap_or_else(|| "run".to_string())
+        } else {
+            relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("-")
+        };
+        runs.push(TbRun { name, dir, files });
+    }
+    runs.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(runs)
+}
+
+/// Copy (keeping mtimes, so finished runs stay finished) or symlink a run's
+/// event files into `dest`. Returns the number of bytes brought in.
+pub fn import_run(run: &TbRun, dest: &Path, link: bool) -> Result<u64> {
+    fs::create_dir_all(dest)
+        .with_context(|| format!("creating run directory {}", dest.display()))?;
+    let mut bytes = 0;
+    for file in &run.files {
+        let name = file.file_name().context("event file without a name")?;
+        let target = dest.join(name);
+        let meta = fs::metadata(file).with_context(|| format!("reading {}", file.display()))?;
+        bytes += meta.len();
+        if link {
+            let source = file
+                .canonicalize()
+                .with_context(|| format!("resolving {}", file.display()))?;
+            symlink(&source, &target)?;
+            continue;
+        }
+        fs::copy(file, &target)
+            .with_context(|| format!("copying {} to {}", file.display(), target.display()))?;
+        if let Ok(modified) = meta.modified() {
+            fs::File::options()
+                .write(true)
+                .open(&target)
+                .and_then(|out| out.set_modified(modified))
+                .with_context(|| format!("setting mtime of {}", target.display()))?;
+        }
+    }
+    Ok(bytes)
+}
+
+#[cfg(unix)]
+fn symlink(source: &Path, target: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(source, target)
+        .with_context(|| format!("linking {} to {}", target.display(), source.display()))
+}
+
+#[cfg(not(unix))]
+fn symlink(_source: &Path, _target: &Path) -> Result<()> {
+    bail!("--link needs symlink support; import without it to copy the files")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::discover_runs;
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn runs_are_directories_with_event_files_named_by_relative_path() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos();
+        let logdir = std::env::temp_dir().join(format!("ogtui-tb-import-{nonce}"));
+        for dir in ["resnet/train", "resnet/validation", "empty/nested"] {
+            fs::create_dir_all(logdir.join(dir)).expect("create dir");
+        }
+        fs::write(logdir.join("events.out.tfevents.1.host"), b"").expect("write");
+        fs::write(logdir.join("resnet/train/events.out.tfevents.2.host"), b"").expect("write");
+        fs::write(
+            logdir.join("resnet/validation/events.out.tfevents.3.host"),
+            b"",
+        )
+        .expect("write");
+        fs::write(logdir.join("empty/nested/notes.txt"), b"").expect("write");
+
+        let runs = discover_runs(&logdir).expect("discover");
+        let names: Vec<&str> = runs.iter().map(|run| run.name.as_str()).collect();
+        let root_name = format!("ogtui-tb-import-{nonce}");
+        let mut expected = vec![root_name.as_str(), "resnet-train", "resnet-validation"];
+        expected.sort();
+        assert_eq!(names, expected);
+        assert!(runs.iter().all(|run| run.files.len() == 1));
+
+        fs::remove_dir_all(&logdir).ok();
+    }
+}
@@ -0,0 +1,162 @@
+//! Thermal throttling: a device whose clock has dropped well below the clock
+//! it reached earlier while just as busy. The system sampler logs the series
+//! this reads (`sys/gpu<i>/sm_clock_mhz`, `sys/cpu/clock_mhz`, with
+//! `util_pct` and `temp_c` next to them).
+
+use std::collections::BTreeMap;
+
+/// Utilization at or above which a device counts as busy.
+const BUSY_UTIL_PCT: f64 = 80.0;
+/// A busy clock below this share of the busy peak is throttled.
+const THROTTLED_SHARE: f64 = 0.85;
+/// Busy samples in a row the low clock has to last.
+const RECENT_POINTS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Throttle {
+    /// `gpu0` or `cpu`, as in the metric names.
+    pub device: String,
+    pub clock_mhz: f64,
+    pub peak_mhz: f64,
+    pub temp_c: Option<f64>,
+}
+
+impl Throttle {
+    /// `GPU 0` or `CPU`.
+    pub fn label(&self) -> String {
+        match self.device.strip_prefix("gpu") {
+            Some(index) => format!("GPU {index}"),
+            None => self.device.to_ascii_uppercase(),
+        }
+    }
+
+    /// `GPU 0 at 1410 MHz (busy peak 1980 MHz, 87°C)`.
+    pub fn describe(&self) -> String {
+        let temp = self
+            .temp_c
+            .map(|temp| format!(", {temp:.0}°C"))
+            .unwrap_or_default();
+        format!(
+            "{} at {:.0} MHz (busy peak {:.0} MHz{temp})",
+            self.label(),
+            self.clock_mhz,
+            self.peak_mhz
+        )
+    }
+}
+
+/// Device a clock tag belongs to: `gpu0` for `sys/gpu0/sm_clock_mhz`.
+fn clock_device(tag: &str) -> Option<&str> {
+    let rest = tag.strip_prefix("sys/")?;
+    rest.strip_suffix("/sm_clock_mhz")
+        .filter(|device| device.starts_with("gpu"))
+        .or_else(|| {
+            rest.strip_suffix("/clock_mhz")
+                .filter(|device| *device == "cpu")
+        })
+}
+
+/// Devices throttling now: their last `RECENT_POINTS` samples are all busy
+/// and clocked below `THROTTLED_SHARE` of the highest busy clock before them.
+pub fn detect(scalars: &BTreeMap<String, Vec<(f64, f64)>>) -> Vec<Throttle> {
+    let mut throttled = Vec::new();
+    for (tag, clocks) in scalars {
+        let Some(device) = clock_device(tag) else {
+            continue;
+        };
+        let Some(util) = scalars.get(&format!("sys/{device}/util_pct")) else {
+            continue;
+        };
+        // The sampler writes clock and utilization on the same line, so they
+        // line up by position; trim to the shorter series from the end.
+        let n = clocks.len().min(util.len());
+        let busy: Vec<(f64, bool)> = clocks[clocks.len() - n..]
+            .iter()
+            .zip(&util[util.len() - n..])
+            .map(|((_, clock), (_, util))| (*clock, *util >= BUSY_UTIL_PCT))
+            .collect();
+        if busy.len() <= RECENT_POINTS {
+            continue;
+        }
+        let (before, recent) = busy.split_at(busy.len() - RECENT_POINTS);
+        let Some(peak) = before
+            .iter()
+            .filter(|(_, busy)| *busy)
+            .map(|(clock, _)| *clock)
+            .reduce(f64::max)
+        else {
+            continue;
+        };
+        if recent
+            .iter()
+            .all(|(clock, busy)| *busy && *clock < peak * THROTTLED_SHARE)
+        {
+            throttled.push(Throttle {
+                device: device.to_string(),
+                clock_mhz: recent[RECENT_POINTS - 1].0,
+                peak_mhz: peak,
+                temp_c: scalars
+                    .get(&format!("sys/{device}/temp_c"))
+                    .and_then(|temps| temps.last())
+                    .map(|(_, temp)| *temp),
+            });
+        }
+    }
+    throttled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::detect;
+    use std::collections::BTreeMap;
+
+    fn series(values: &[f64]) -> Vec<(f64, f64)> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(step, value)| (step as f64, *value))
+            .collect()
+    }
+
+    #[test]
+    fn flags_clock_drops_under_load_but_not_idle_downclocking() {
+        let mut scalars = BTreeMap::new();
+        scalars.insert(
+            "sys/gpu0/sm_clock_mhz".to_string(),
+            series(&[1980.0, 1980.0, 1410.0, 1395.0, 1410.0]),
+        );
+        scalars.insert(
+            "sys/gpu0/util_pct".to_string(),
+            series(&[99.0, 100.0, 99.0, 100.0, 100.0]),
+        );
+        scalars.insert("sys/gpu0/temp_c".to_string(), series(&[80.0, 88.0]));
+        // Idle GPU: low clocks, low utilization.
+        scalars.insert(
+            "sys/gpu1/sm_clock_mhz".to_string(),
+            series(&[1980.0, 1980.0, 210.0, 210.0, 210.0]),
+        );
+        scalars.insert(
+            "sys/gpu1/util_pct".to_string(),
+            series(&[99.0, 99.0, 0.0, 0.0, 0.0]),
+        );
+        scalars.insert(
+            "sys/cpu/clock_mhz".to_string(),
+            series(&[4200.0, 4100.0, 4150.0, 4000.0, 4200.0]),
+        );
+        scalars.insert("sys/cpu/util_pct".to_string(), series(&[95.0; 5]));
+
+        let throttled = detect(&scalars);
+        assert_eq!(throttled.len(), 1);
+        assert_eq!(
+            throttled[0].describe(),
+            "GPU 0 at 1410 MHz (busy peak 1980 MHz, 88°C)"
+        );
+
+        scalars.insert(
+            "sys/cpu/clock_mhz".to_string(),
+            series(&[4200.0, 4100.0, 2800.0, 2900.0, 2800.0]),
+        );
+        let devices: Vec<String> = detect(&scalars).iter().map(|t| t.label()).collect();
+        assert_eq!(devices, vec!["CPU", "GPU 0"]);
+    }
+}
@@ -0,0 +1,111 @@
+//! Wall-clock gaps in a run: stretches between consecutive events that took
+//! far longer than the run usually takes between events, such as eval
+//! pauses, crashes followed by a resume, or node preemptions. Computed from
+//! event wall times, so they show where an overnight run spent its time.
+
+use crate::tfevents::ScalarEvent;
+
+/// Pauses shorter than this are never gaps, however regular the run is.
+pub const MIN_GAP_SECS: f64 = 60.0;
+/// A pause is a gap once it is this many times the run's median pause.
+const GAP_FACTOR: f64 = 10.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gap {
+    /// Step of the last event before the gap.
+    pub from_step: i64,
+    /// Step of the first event after it; lower than `from_step` when the
+    /// run resumed from an earlier checkpoint.
+    pub to_step: i64,
+    pub secs: f64,
+}
+
+impl Gap {
+    /// The run went back to an earlier step after the gap.
+    pub fn resumed(&self) -> bool {
+        self.to_step < self.from_step
+    }
+
+    /// Steps the gap covers on a chart's x axis, low end first.
+    pub fn step_span(&self) -> (f64, f64) {
+        let (a, b) = (self.from_step as f64, self.to_step as f64);
+        (a.min(b), a.max(b))
+    }
+}
+
+/// Gaps between events in wall-time order, oldest first.
+pub fn gaps(events: &[ScalarEvent]) -> Vec<Gap> {
+    let mut times: Vec<(f64, i64)> = events
+        .iter()
+        .filter(|event| event.wall_time.is_finite() && event.wall_time > 0.0)
+        .map(|event| (event.wall_time, event.step))
+        .collect();
+    times.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+    let mut pauses: Vec<f64> = times
+        .windows(2)
+        .map(|pair| pair[1].0 - pair[0].0)
+        .filter(|delta| *delta > 0.0)
+        .collect();
+    if pauses.is_empty() {
+        return Vec::new();
+    }
+    pauses.sort_by(f64::total_cmp);
+    let threshold = (pauses[pauses.len() / 2] * GAP_FACTOR).max(MIN_GAP_SECS);
+    times
+        .windows(2)
+        .filter(|pair| pair[1].0 - pair[0].0 > threshold)
+        .map(|pair| Gap {
+            from_step: pair[0].1,
+            to_step: pair[1].1,
+            secs: pair[1].0 - pair[0].0,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Gap, gaps};
+    use crate::tfevents::ScalarEvent;
+
+    fn event(step: i64, wall_time: f64) -> ScalarEvent {
+        ScalarEvent {
+            tag: "train/loss".to_string(),
+            step,
+            wall_time,
+            value: 1.0,
+        }
+    }
+
+    #[test]
+    fn long_pauses_between_events_are_gaps() {
+        // A step every 2s, a 10 minute eval after step 50, then a crash after
+        // step 80 and a resume from step 60 an hour later.
+        let mut events: Vec<ScalarEvent> = (0..=50).map(|s| event(s, s as f64 * 2.0)).collect();
+        events.extend((51..=80).map(|s| event(s, 700.0 + s as f64 * 2.0)));
+        events.extend((60..=70).map(|s| event(s, 4500.0 + s as f64 * 2.0)));
+
+        let found = gaps(&events);
+        assert_eq!(found.len(), 2, "{found:?}");
+        assert_eq!((found[0].from_step, found[0].to_step), (50, 51));
+        assert!((found[0].secs - 702.0).abs() < 1e-9);
+        assert!(!found[0].resumed());
+        assert_eq!(
+            found[1],
+            Gap {
+                from_step: 80,
+                to_step: 60,
+                secs: 4500.0 + 120.0 - (700.0 + 160.0),
+            }
+        );
+        assert!(found[1].resumed());
+        assert_eq!(found[1].step_span(), (60.0, 80.0));
+    }
+
+    #[test]
+    fn slow_but_steady_runs_and_missing_wall_times_have_no_gaps() {
+        let steady: Vec<ScalarEvent> = (0..20).map(|s| event(s, s as f64 * 300.0)).collect();
+        assert!(gaps(&steady).is_empty());
+        let untimed: Vec<ScalarEvent> = (0..20).map(|s| event(s, 0.0)).collect();
+        assert!(gaps(&untimed).is_empty());
+    }
+}
@@ -1,18 +1,39 @@
 use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use ratatui::{
     Frame,
+    buffer::Buffer,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     symbols,
     text::{Line, Span},
-    widgets::{Axis, Block, Borders, Chart, Clear, Dataset, GraphType, Paragraph, Tabs, Wrap},
+    widgets::{
+        Axis, Block, Borders, Chart, Clear, Dataset, Gauge, GraphType, Paragraph, Tabs, Wrap,
+    },
 };
 
-use crate::app::{App, ProcessSort, Tab};
+use crate::app::{
+    App, ChartMarker, MarkerKind, PROCESS_HISTORY_LEN, Pane, ProcessHistory, ProcessSnapshot,
+    ProcessSort, Tab, Toast, ToastLevel, is_loss_tag,
+};
+use crate::dist_health::{IssueKind, RankStatus};
+use crate::format;
+use crate::grad_health::GradState;
+use crate::histogram::{self, Histogram};
+use crate::log_entry::{LogEntry, LogLevel};
+use crate::oom_forecast::Forecast;
+use crate::run_diff::{self, RunDiff};
+use crate::run_meta::{self, Annotation, AnnotationKind, Scale};
+use crate::seed_band::{self, Band};
+use crate::sys_strip::SysStrip;
+use crate::table;
+use crate::timeline::Gap;
 
 // ── Colors (matching the TypeScript TUI) ────────────────────────────────────
+// The loaded run's own color (see `run_color`) replaces CHART_RAW when known.
 const GREEN: Color = Color::Rgb(46, 204, 113); // #2ecc71
 const BORDER: Color = Color::Rgb(244, 250, 255); // crystal white
 const TEXT_DIM: Color = Color::Rgb(155, 163, 175); // #9BA3AF
@@ -23,6 +44,21 @@ const CHART_SMOOTH: Color = Color::Rgb(255, 165, 0); // orange for smoothed
 const LOG_INFO_ASH: Color = Color::Rgb(148, 163, 184); // ash
 const LOG_ERROR: Color = Color::Rgb(248, 113, 113); // bright red
 const LOG_IMPORTANT: Color = Color::Rgb(251, 191, 36); // bright amber
+const LOG_SELECTION_BG: Color = Color::Rgb(30, 58, 95); // muted navy
+const MARKER_CHECKPOINT: Color = Color::Rgb(167, 139, 250); // violet
+const MARKER_EVAL: Color = Color::Rgb(34, 211, 238); // cyan
+const MARKER_LR: Color = Color::Rgb(244, 114, 182); // pink
+// Tab accents: the active tab's label background and its focused pane's border.
+const ACCENT_CHAT: Color = GREEN;
+const ACCENT_GRAPHS: Color = CHART_RAW;
+const ACCENT_DASHBOARD: Color = Color::Rgb(45, 212, 191); // teal
+const ACCENT_PROCESSES: Color = Color::Rgb(251, 146, 60); // soft orange
+const ACCENT_LOGS: Color = MARKER_LR;
+const ACCENT_EVENTS: Color = MARKER_CHECKPOINT;
+const ACCENT_DISTRIBUTIONS: Color = MARKER_EVAL;
+// Wall-clock gaps under the focused chart: a dim amber background.
+const GAP_SHADE: Color = Color::Rgb(58, 46, 20);
+const GAP_SHADE_FG: Color = Color::Rgb(180, 140, 60);
 const LOGO_GREEN_TOP: Color = Color::Rgb(134, 239, 172);
 const LOGO_GREEN_MID: Color = Color::Rgb(46, 204, 113);
 const LOGO_GREEN_BOTTOM: Color = Color::Rgb(12, 104, 58);
@@ -35,6 +71,63 @@ const OPENGRAPHS_LOGO_ROWS: [&str; 6] = [
     "  ╚═════╝  ╚═╝      ╚══════╝ ╚═╝  ╚═══╝  ╚═════╝  ╚═╝  ╚═╝ ╚═╝  ╚═╝ ╚═╝      ╚═╝  ╚═╝ ╚══════╝",
 ];
 
+/// Smallest terminal the layout fits in: header (3) + sys strip (1) +
+/// body (10) + footer (1), plus room for the side column and the footer key hints.
+pub const MIN_WIDTH: u16 = 60;
+pub const MIN_HEIGHT: u16 = 16;
+
+fn draw_too_small(f: &mut Frame, area: Rect) {
+    let lines = vec![
+        Line::from(Span::styled(
+            "terminal too small",
+            Style::default()
+                .fg(LOG_IMPORTANT)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            format!("{}x{}", area.width, area.height),
+            Style::default().fg(TEXT_LIGHT),
+        )),
+        Line::from(Span::styled(
+            format!("need {MIN_WIDTH}x{MIN_HEIGHT}"),
+            Style::default().fg(TEXT_DIM),
+        )),
+    ];
+    let top = area.height.saturating_sub(lines.len() as u16) / 2;
+    let body = Rect {
+        y: area.y + top,
+        height: area.height - top,
+        ..area
+    };
+    f.render_widget(
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        body,
+    );
+}
+
+fn tab_accent(tab: Tab) -> Color {
+    match tab {
+        Tab::Chat => ACCENT_CHAT,
+        Tab::Graphs => ACCENT_GRAPHS,
+        Tab::Dashboard => ACCENT_DASHBOARD,
+        Tab::Processes => ACCENT_PROCESSES,
+        Tab::Logs => ACCENT_LOGS,
+        Tab::Events => ACCENT_EVENTS,
+        Tab::Distributions => ACCENT_DISTRIBUTIONS,
+    }
+}
+
+/// Border of a pane on the active tab: the tab's accent while it has focus.
+fn pane_border(app: &App, pane: Pane) -> Style {
+    if app.focused_pane() == pane {
+        Style::default().fg(tab_accent(app.active_tab))
+    } else {
+        Style::default().fg(BORDER)
+    }
+}
+
 /// Clickable regions tracked for mouse hit-testing.
 #[derive(Default, Clone)]
 pub struct LayoutRegions {
@@ -48,26 +141,42 @@ pub struct LayoutRegions {
 pub fn draw(f: &mut Frame, app: &mut App) -> LayoutRegions {
     let size = f.area();
     let mut regions = LayoutRegions::default();
+    if size.width < MIN_WIDTH || size.height < MIN_HEIGHT {
+        draw_too_small(f, size);
+        return regions;
+    }
 
-    // Root vertical layout: header (3) | body (fill) | footer (1)
-    let root_chunks = Layout::default()
+    // Root vertical layout: header (3) | sys strip (0 or 1) | body (fill) | footer (1)
+    let strip_h: u16 = if app.sys_strip.is_empty() { 0 } else { 1 };
+    let [header_area, strip_area, body_area, footer_area] = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3), // header tabs
-            Constraint::Min(10),   // body
-            Constraint::Length(1), // footer
+            Constraint::Length(3),       // header tabs
+            Constraint::Length(strip_h), // hardware gauges
+            Constraint::Min(10),         // body
+            Constraint::Length(1),       // footer
         ])
-        .split(size);
+        .areas(size);
+    let root_chunks = [header_area, body_area, footer_area];
 
     draw_header(f, app, root_chunks[0], &mut regions);
+    if strip_h > 0 {
+        draw_sys_strip(f, &app.sys_strip, strip_area);
+    }
 
     // Fullscreen focused metric takes over the body
     if let Some(idx) = app.focused_metric {
-        draw_focused_metric(f, app, idx, root_chunks[1]);
+        match &app.run_diff {
+            Some(diff) => draw_run_diff(f, app, diff, idx, root_chunks[1]),
+            None => draw_focused_metric(f, app, idx, root_chunks[1]),
+        }
     } else {
         match app.active_tab {
             Tab::Graphs => draw_graphs_tab(f, app, root_chunks[1], &mut regions),
+            Tab::Dashboard => draw_dashboard_tab(f, app, root_chunks[1]),
             Tab::Logs => draw_logs_tab(f, app, root_chunks[1]),
+            Tab::Events => draw_events_tab(f, app, root_chunks[1]),
+            Tab::Distributions => draw_distributions_tab(f, app, root_chunks[1]),
             Tab::Processes => draw_processes_tab(f, app, root_chunks[1]),
             Tab::Chat => draw_chat_tab(f, app, root_chunks[1]),
         }
@@ -80,11 +189,89 @@ pub fn draw(f: &mut Frame, app: &mut App) -> LayoutRegions {
         draw_help_modal(f, size);
     }
 
+    if let Some(input) = app.note_input.as_deref() {
+        draw_note_input(f, input, size);
+    }
+
+    if let Some(selected) = app.checkpoint_panel {
+        draw_checkpoints_panel(f, app, selected, size);
+    }
+
+    if app.lr_panel {
+        draw_lr_panel(f, app, size);
+    }
+
+    if let Some(selected) = app.table_panel {
+        draw_table_panel(f, app, selected, size);
+    }
+
+    if let Some(selected) = app.project_panel {
+        draw_projects_panel(f, app, selected, size);
+    }
+
+    draw_toasts(f, &app.toasts, size);
+
     regions
 }
 
 // ── Header ──────────────────────────────────────────────────────────────────
 
+/// Cells in each mini-gauge of the sys strip.
+const STRIP_GAUGE_CELLS: usize = 8;
+
+/// One line of mini-gauges under the header, dimmed to `--` for readings the
+/// run does not have.
+fn draw_sys_strip(f: &mut Frame, strip: &SysStrip, area: Rect) {
+    let mut spans = vec![Span::raw(" ")];
+    for (label, pct) in strip.gauges() {
+        spans.push(Span::styled(
+            format!("{label} "),
+            Style::default().fg(TEXT_DIM),
+        ));
+        match pct {
+            Some(pct) => {
+                let color = if pct >= 90.0 {
+                    LOG_ERROR
+                } else if pct >= 70.0 {
+                    LOG_IMPORTANT
+                } else {
+                    GREEN
+                };
+                spans.push(Span::styled(
+                    gauge_bar(pct, STRIP_GAUGE_CELLS),
+                    Style::default().fg(color),
+                ));
+                spans.push(Span::styled(
+                    format!(" {pct:>3.0}%   "),
+                    Style::default().fg(TEXT_LIGHT),
+                ));
+            }
+            None => spans.push(Span::styled("--   ", Style::default().fg(TEXT_DIM))),
+        }
+    }
+    spans.push(Span::styled(
+        match strip.steps_per_sec {
+            Some(rate) => format!("{} step/s", format::value(rate)),
+            None => "-- step/s".to_string(),
+        },
+        Style::default().fg(TEXT_LIGHT),
+    ));
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// `pct` of `cells` filled with eighth blocks over a dotted track.
+fn gauge_bar(pct: f64, cells: usize) -> String {
+    const PARTIAL: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+    let eighths = ((pct.clamp(0.0, 100.0) / 100.0) * (cells * 8) as f64).round() as usize;
+    let (full, rest) = (eighths / 8, eighths % 8);
+    let mut bar = "█".repeat(full);
+    if full < cells {
+        bar.push(if rest == 0 { '·' } else { PARTIAL[rest] });
+        bar.push_str(&"·".repeat(cells - full - 1));
+    }
+    bar
+}
+
 fn draw_header(f: &mut Frame, app: &App, area: Rect, regions: &mut LayoutRegions) {
     // Keep the tab box tight to exactly the visible tab labels.
     let tab_padding: u16 = 1; // ratatui Tabs default padding per side
@@ -137,7 +324,7 @@ fn draw_header(f: &mut Frame, app: &App, area: Rect, regions: &mut LayoutRegions
         .iter()
         .map(|t| {
             let style = if *t == app.active_tab {
-                Style::default().fg(GREEN).add_modifier(Modifier::BOLD)
+                active_tab_style(*t)
             } else {
                 Style::default().fg(TEXT_DIM)
             };
@@ -156,7 +343,7 @@ fn draw_header(f: &mut Frame, app: &App, area: Rect, regions: &mut LayoutRegions
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(BORDER)),
         )
-        .highlight_style(Style::default().fg(GREEN))
+        .highlight_style(active_tab_style(app.active_tab))
         .select(active_idx)
         .divider(Span::styled("│", Style::default().fg(BORDER)));
 
@@ -167,15 +354,75 @@ fn draw_header(f: &mut Frame, app: &App, area: Rect, regions: &mut LayoutRegions
         app,
         header_chunks[1].width.saturating_sub(4),
     ))
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(BORDER))
-            .title(Span::styled(" step progress ", Style::default().fg(BORDER))),
-    );
+    .block(step_progress_block(app));
     f.render_widget(step_block, header_chunks[1]);
 }
 
+fn active_tab_style(tab: Tab) -> Style {
+    Style::default()
+        .fg(BG_DARK)
+        .bg(tab_accent(tab))
+        .add_modifier(Modifier::BOLD)
+}
+
+fn step_progress_block(app: &App) -> Block<'static> {
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(BORDER))
+        .title(Span::styled(" step progress ", Style::default().fg(BORDER)));
+    if !app.ranks.is_empty() {
+        let label = match app.selected_rank {
+            Some(rank) => format!(" rank {}/{} ", rank, app.ranks.len()),
+            None => format!(" all {} ranks ", app.ranks.len()),
+        };
+        block = block
+            .title(Line::from(Span::styled(label, Style::default().fg(GREEN))).right_aligned());
+    }
+    if let Some(summary) = grad_health_title(app) {
+        block = block.title(summary.right_aligned());
+    }
+    if app.dirty_tree {
+        block = block.title(
+            Line::from(Span::styled(
+                " ⚠ dirty tree ",
+                Style::default()
+                    .fg(LOG_IMPORTANT)
+                    .add_modifier(Modifier::BOLD),
+            ))
+            .right_aligned(),
+        );
+    }
+    block
+}
+
+/// One-line gradient-norm watchdog summary: the worst metric's state.
+fn grad_health_title(app: &App) -> Option<Line<'static>> {
+    let health = app.grad_health();
+    let worst = health.iter().max_by_key(|h| h.state)?;
+    let line = match worst.state {
+        GradState::Ok => Line::from(Span::styled(
+            format!(" grad ok {} ", format::value(worst.last)),
+            Style::default().fg(GREEN),
+        )),
+        state => Line::from(Span::styled(
+            format!(
+                " ⚠ grad {}: {} {} ",
+                state.label(),
+                app.metric_display_name(&worst.tag),
+                format::value(worst.last)
+            ),
+            Style::default()
+                .fg(if state == GradState::Exploding {
+                    LOG_ERROR
+                } else {
+                    LOG_IMPORTANT
+                })
+                .add_modifier(Modifier::BOLD),
+        )),
+    };
+    Some(line)
+}
+
 fn current_metric_step(app: &App) -> i64 {
     app.tags
         .get(app.selected_metric)
@@ -193,9 +440,9 @@ fn build_step_meter_line(app: &App, available_width: u16) -> Line<'static> {
     let prefix = format!(
         " {} tags │ {} events │ step {}/{} ",
         app.tags.len(),
-        app.total_events,
-        current,
-        total,
+        format::count(app.total_events as f64),
+        format::count(current as f64),
+        format::count(total as f64),
     );
     let prefix_width = prefix.chars().count() as u16;
     let bar_width = available_width.saturating_sub(prefix_width) as usize;
@@ -222,7 +469,8 @@ fn build_step_meter_line(app: &App, available_width: u16) -> Line<'static> {
 }
 
 fn opengraphs_inline_brand() -> Line<'static> {
-    Line::from(Span::styled(" opengraphs ", Style::default().fg(BORDER))).alignment(Alignment::Right)
+    Line::from(Span::styled(" opengraphs ", Style::default().fg(BORDER)))
+        .alignment(Alignment::Right)
 }
 
 // ── Graphs Tab ──────────────────────────────────────────────────────────────
@@ -249,11 +497,16 @@ fn draw_metrics_grid(f: &mut Frame, app: &mut App, area: Rect, regions: &mut Lay
     let cols = (inner.width / card_width).max(1) as usize;
     let card_height: u16 = 12;
     let rows_available = (inner.height / card_height).max(1) as usize;
-    let total_rows = (app.tags.len() + cols - 1) / cols;
+    let total_rows = app.tags.len().div_ceil(cols);
 
-    // Update app with grid dimensions for auto-scroll
+    // Update app with grid dimensions for auto-scroll; a new geometry (resize)
+    // keeps the selected card on screen.
+    let geometry_changed = app.metrics_visible_rows != rows_available || app.metrics_cols != cols;
     app.metrics_visible_rows = rows_available;
     app.metrics_cols = cols;
+    if geometry_changed {
+        app.ensure_metric_visible();
+    }
 
     // Clamp scroll
     let max_scroll = total_rows.saturating_sub(rows_available);
@@ -262,20 +515,25 @@ fn draw_metrics_grid(f: &mut Frame, app: &mut App, area: Rect, regions: &mut Lay
     }
 
     // Build title with scroll indicator
+    let preset = app
+        .graph_preset
+        .as_deref()
+        .map(|name| format!(" · {name}"))
+        .unwrap_or_default();
     let title = if total_rows > rows_available {
         format!(
-            " metrics [{}-{}/{}] ",
+            " metrics{preset} [{}-{}/{}] ",
             app.metrics_scroll * cols + 1,
             ((app.metrics_scroll + rows_available) * cols).min(app.tags.len()),
             app.tags.len(),
         )
     } else {
-        format!(" metrics ({}) ", app.tags.len())
+        format!(" metrics{preset} ({}) ", app.tags.len())
     };
 
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(BORDER))
+        .border_style(pane_border(app, Pane::Main))
         .title(Span::styled(title, Style::default().fg(BORDER)));
 
     let inner = block.inner(area);
@@ -330,7 +588,7 @@ fn draw_metrics_grid(f: &mut Frame, app: &mut App, area: Rect, regions: &mut Lay
             let card_area = col_areas[col];
             regions.metric_card_rects.push(card_area);
             let is_selected = i == app.selected_metric;
-            draw_metric_card(f, &app, &app.tags[i].clone(), card_area, is_selected);
+            draw_metric_card(f, app, &app.tags[i].clone(), card_area, is_selected);
         }
     }
 }
@@ -382,9 +640,17 @@ fn draw_metric_card(f: &mut Frame, app: &App, tag: &str, area: Rect, selected: b
             return;
         }
 
+        if let Some(bounds) = app.metric_gauge(tag) {
+            draw_gauge_card(f, app, tag, data, bounds, inner);
+            return;
+        }
+
         // Show latest value as text at bottom
         let latest = data.last().unwrap();
-        let latest_text = format_value(latest.1);
+        let latest_text = match app.metric_unit(tag) {
+            Some(unit) => format!("{} {unit}", format::value(latest.1)),
+            None => format::value(latest.1),
+        };
 
         // Compute bounds
         let x_min = data.first().unwrap().0;
@@ -398,6 +664,12 @@ fn draw_metric_card(f: &mut Frame, app: &App, tag: &str, area: Rect, selected: b
         } else {
             y_max + y_margin
         };
+        let projection = oom_projection(app, tag);
+        let (x_max, y_hi) = match &projection {
+            Some(forecast) => (x_max.max(forecast.exhausted_at), y_hi.max(forecast.total)),
+            None => (x_max, y_hi),
+        };
+        let projection_points = projection.as_ref().map(projection_line);
 
         // Chart area = inner minus 1 line for value text
         let chart_area_height = inner.height.saturating_sub(1);
@@ -418,10 +690,23 @@ fn draw_metric_card(f: &mut Frame, app: &App, tag: &str, area: Rect, selected: b
         let dataset = Dataset::default()
             .marker(symbols::Marker::Braille)
             .graph_type(GraphType::Line)
-            .style(Style::default().fg(CHART_RAW))
+            .style(Style::default().fg(run_chart_color(app)))
             .data(data);
 
-        let chart = Chart::new(vec![dataset])
+        let bands = seed_band_layers(app, tag, (x_min, x_max), (y_lo, y_hi), chart_area, |v| v);
+        let markers = app.chart_markers();
+        let marker_points = chart_marker_positions(&markers, tag, x_min, x_max, y_hi);
+        let anomalies = anomaly_positions(app, tag, x_min, x_max, y_hi, |v| v);
+        let mut datasets = if bands.is_empty() {
+            vec![dataset]
+        } else {
+            band_datasets(&bands)
+        };
+        datasets.extend(marker_datasets(&marker_points));
+        datasets.extend(anomaly_dataset(&anomalies));
+        datasets.extend(projection_points.as_ref().map(projection_dataset));
+
+        let chart = Chart::new(datasets)
             .x_axis(
                 Axis::default()
                     .bounds([x_min, x_max])
@@ -450,11 +735,96 @@ fn draw_metric_card(f: &mut Frame, app: &App, tag: &str, area: Rect, selected: b
     }
 }
 
-fn draw_side_column(f: &mut Frame, app: &App, area: Rect) {
+/// A bounded metric's card: its latest value as a bar between the bounds,
+/// with the recent trend as a sparkline above it.
+fn draw_gauge_card(
+    f: &mut Frame,
+    app: &App,
+    tag: &str,
+    data: &[(f64, f64)],
+    (lo, hi): (f64, f64),
+    inner: Rect,
+) {
+    let latest = data.last().map_or(lo, |(_, value)| *value);
+    let ratio = ((latest - lo) / (hi - lo)).clamp(0.0, 1.0);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(1), // sparkline
+            Constraint::Length(1), // gauge
+            Constraint::Length(1), // bounds and value
+        ])
+        .split(inner);
+
+    let values: Vec<f64> = data.iter().map(|(_, value)| *value).collect();
+    f.render_widget(
+        Paragraph::new(sparkline_text(&values, inner.width as usize))
+            .style(Style::default().fg(TEXT_DIM))
+            .alignment(Alignment::Right),
+        rows[1],
+    );
+    f.render_widget(
+        Gauge::default()
+            .ratio(ratio)
+            .label(Span::styled(
+                format!("{:.0}%", ratio * 100.0),
+                Style::default().fg(TEXT_LIGHT).add_modifier(Modifier::BOLD),
+            ))
+            .gauge_style(Style::default().fg(run_chart_color(app)).bg(BG_DARK)),
+        rows[2],
+    );
+
+    let value = match app.metric_unit(tag) {
+        Some(unit) => format!("{} {unit}", format::value(latest)),
+        None => format::value(latest),
+    };
+    let (range, gap) = gauge_footer((lo, hi), &value, inner.width as usize);
+    f.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled(range, Style::default().fg(TEXT_DIM)),
+            Span::raw(" ".repeat(gap)),
+            Span::styled(
+                value,
+                Style::default()
+                    .fg(CHART_SMOOTH)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ])),
+        rows[3],
+    );
+}
+
+/// Bounds for the left of a gauge card's bottom row and the gap before the
+/// right-aligned value. Where both don't fit with a space between them, the
+/// bounds lose their trailing zeros, then go, so the value always shows.
+fn gauge_footer((lo, hi): (f64, f64), value: &str, width: usize) -> (String, usize) {
+    let trimmed = |bound: f64| {
+        let text = format::value(bound);
+        if text.contains('.') && !text.contains('e') {
+            let text = text.trim_end_matches('0').trim_end_matches('.');
+            if text == "-0" { "0" } else { text }.to_string()
+        } else {
+            text
+        }
+    };
+    let value_len = value.chars().count();
+    let range = [
+        format!("{}–{}", format::value(lo), format::value(hi)),
+        format!("{}–{}", trimmed(lo), trimmed(hi)),
+    ]
+    .into_iter()
+    .find(|range| range.chars().count() + 1 + value_len <= width)
+    .unwrap_or_default();
+    let gap = width.saturating_sub(range.chars().count() + value_len);
+    (range, gap)
+}
+
+fn draw_side_column(f: &mut Frame, app: &mut App, area: Rect) {
     draw_stats_panel(f, app, area);
 }
 
-fn draw_stats_panel(f: &mut Frame, app: &App, area: Rect) {
+fn draw_stats_panel(f: &mut Frame, app: &mut App, area: Rect) {
     let lines = vec![
         Line::from(Span::styled(
             format!("path:   {}", app.events_path.display()),
@@ -476,49 +846,76 @@ fn draw_stats_panel(f: &mut Frame, app: &App, area: Rect) {
 
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(BORDER))
+        .border_style(pane_border(app, Pane::Side))
         .title(Span::styled(" stats ", Style::default().fg(BORDER)))
         .title_bottom(opengraphs_inline_brand());
 
-    let paragraph = Paragraph::new(lines)
-        .block(block)
-        .wrap(Wrap { trim: false });
-
-    f.render_widget(paragraph, area);
+    let inner = block.inner(area);
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false });
+    let max_scroll = paragraph
+        .line_count(inner.width)
+        .saturating_sub(inner.height as usize)
+        .min(u16::MAX as usize) as u16;
+    app.set_stats_max_scroll(max_scroll);
+    f.render_widget(block, area);
+    f.render_widget(paragraph.scroll((app.stats_scroll, 0)), inner);
 }
 
 // ── Logs Tab ────────────────────────────────────────────────────────────────
 
-fn style_for_log_line(line: &str) -> Style {
-    let trimmed = line.trim_start();
-    let lower = trimmed.to_ascii_lowercase();
-
-    if lower.starts_with("[error]") {
-        return Style::default().fg(LOG_ERROR).add_modifier(Modifier::BOLD);
-    }
-    if lower.starts_with("[sucess]") || lower.starts_with("[success]") {
-        return Style::default().fg(GREEN).add_modifier(Modifier::BOLD);
-    }
-    if lower.starts_with("[important]") {
-        return Style::default()
+fn style_for_log_entry(entry: &LogEntry) -> Style {
+    match entry.level {
+        LogLevel::Error => Style::default().fg(LOG_ERROR).add_modifier(Modifier::BOLD),
+        LogLevel::Success => Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+        LogLevel::Important => Style::default()
             .fg(LOG_IMPORTANT)
-            .add_modifier(Modifier::BOLD);
-    }
-    if lower.starts_with("[info]") {
-        return Style::default().fg(LOG_INFO_ASH);
-    }
-    if trimmed.starts_with("--") {
-        return Style::default().fg(BORDER);
+            .add_modifier(Modifier::BOLD),
+        LogLevel::Warn => Style::default().fg(LOG_IMPORTANT),
+        LogLevel::Info if entry.message.starts_with("--") => Style::default().fg(BORDER),
+        LogLevel::Info if entry.shows_level() => Style::default().fg(LOG_INFO_ASH),
+        LogLevel::Info => Style::default().fg(TEXT_LIGHT),
     }
+}
 
-    Style::default().fg(TEXT_LIGHT)
+/// A log entry as a row: the UTC clock time, dim, then the entry.
+fn log_entry_line(entry: &LogEntry, style: Style) -> Line<'static> {
+    let mut spans = Vec::with_capacity(2);
+    if let Some(clock) = entry.clock() {
+        spans.push(Span::styled(
+            format!("{clock} "),
+            Style::default().fg(TEXT_DIM),
+        ));
+    }
+    spans.push(Span::styled(entry.to_string(), style));
+    Line::from(spans)
 }
 
 fn draw_logs_tab(f: &mut Frame, app: &mut App, area: Rect) {
+    let title = match app.log_selection {
+        Some(selection) => Span::styled(
+            format!(" logs · VISUAL {} lines ", selection.range().count()),
+            Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+        ),
+        None if app.log_lines.earlier() > 0 => Span::styled(
+            format!(" logs · {} earlier lines on disk ", app.log_lines.earlier()),
+            Style::default().fg(BORDER),
+        ),
+        None if app.log_lines.dropped() > 0 => Span::styled(
+            format!(" logs · last {} lines ", app.log_lines.len()),
+            Style::default().fg(BORDER),
+        ),
+        None => Span::styled(" logs ", Style::default().fg(BORDER)),
+    };
+    let filter = app.log_level_filter.map(|level| {
+        Span::styled(
+            format!(" {}+ ", level.as_str()),
+            Style::default().fg(LOG_IMPORTANT),
+        )
+    });
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(BORDER))
-        .title(Span::styled(" logs ", Style::default().fg(BORDER)))
+        .border_style(pane_border(app, Pane::Main))
+        .title(Line::from_iter(std::iter::once(title).chain(filter)))
         .title_bottom(opengraphs_inline_brand());
 
     if app.log_lines.is_empty() {
@@ -531,1096 +928,4105 @@ fn draw_logs_tab(f: &mut Frame, app: &mut App, area: Rect) {
     }
 
     let inner = block.inner(area);
-    let viewport_rows = inner.height as usize;
-    app.set_logs_viewport_rows(viewport_rows);
-    let scroll_y = app.logs_scroll;
-
-    let lines: Vec<Line> = app
+    let line_rows = app
         .log_lines
         .iter()
-        .map(|line| {
-            let style = style_for_log_line(line);
-            Line::from(Span::styled(line.as_str(), style))
+        .map(|entry| {
+            if !app.shows_log(entry) {
+                return 0;
+            }
+            Paragraph::new(log_entry_line(entry, Style::default()))
+                .wrap(Wrap { trim: false })
+                .line_count(inner.width)
         })
         .collect();
+    app.set_logs_viewport(inner.height as usize, line_rows);
 
-    let paragraph = Paragraph::new(lines)
+    let paragraph = logs_paragraph(app)
         .block(block)
-        .scroll((scroll_y, 0))
-        .wrap(Wrap { trim: false });
+        .scroll((app.logs_scroll, 0));
     f.render_widget(paragraph, area);
 }
 
-// ── Processes Tab ───────────────────────────────────────────────────────────
-
-fn state_style(state: &str) -> Style {
-    let first = state.chars().next().unwrap_or('S');
-    match first {
-        'R' => Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
-        'D' | 'Z' => Style::default().fg(LOG_ERROR).add_modifier(Modifier::BOLD),
-        'T' => Style::default().fg(LOG_IMPORTANT),
-        _ => Style::default().fg(TEXT_DIM),
-    }
+fn logs_paragraph(app: &App) -> Paragraph<'static> {
+    let selection = app.log_selection;
+    let lines: Vec<Line> = app
+        .log_lines
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| app.shows_log(entry))
+        .map(|(idx, entry)| {
+            let mut style = style_for_log_entry(entry);
+            if let Some(selection) = selection
+                && selection.range().contains(&idx)
+            {
+                style = style.bg(LOG_SELECTION_BG);
+                if idx == selection.cursor {
+                    style = style.add_modifier(Modifier::UNDERLINED);
+                }
+            }
+            log_entry_line(entry, style)
+        })
+        .collect();
+    Paragraph::new(lines).wrap(Wrap { trim: false })
 }
 
-fn state_label(state: &str) -> &'static str {
-    match state.chars().next().unwrap_or('S') {
-        'R' => "running",
-        'S' => "sleeping",
-        'D' => "io-wait",
-        'T' => "stopped",
-        'Z' => "zombie",
-        'I' => "idle",
-        _ => "other",
-    }
-}
+fn draw_events_tab(f: &mut Frame, app: &mut App, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(pane_border(app, Pane::Main))
+        .title(Span::styled(" events ", Style::default().fg(BORDER)))
+        .title_bottom(opengraphs_inline_brand());
+    let inner = block.inner(area);
+    f.render_widget(block, area);
 
-fn truncate_text(input: &str, max_len: usize) -> String {
-    if max_len == 0 {
-        return String::new();
+    if inner.width < 20 || inner.height < 3 {
+        return;
     }
-    let count = input.chars().count();
-    if count <= max_len {
-        return input.to_string();
+
+    let active = Style::default().fg(GREEN).add_modifier(Modifier::BOLD);
+    let idle = Style::default().fg(TEXT_DIM);
+    let types = app.event_types();
+    let mut filters = vec![Span::styled(
+        format!("all {}", app.structured_events.len()),
+        if app.events_filter.is_none() {
+            active
+        } else {
+            idle
+        },
+    )];
+    for (kind, count) in &types {
+        filters.push(Span::styled(" · ", Style::default().fg(BORDER)));
+        filters.push(Span::styled(
+            format!("{kind} {count}"),
+            if app.events_filter.as_deref() == Some(*kind) {
+                active
+            } else {
+                idle
+            },
+        ));
     }
-    if max_len <= 3 {
-        return ".".repeat(max_len);
+
+    const STEP_W: usize = 9;
+    let type_w = types
+        .keys()
+        .map(|kind| kind.chars().count())
+        .max()
+        .unwrap_or(4)
+        .clamp(4, 24);
+    let payload_w = (inner.width as usize).saturating_sub(STEP_W + type_w + 2);
+    let mut lines = vec![
+        Line::from(filters),
+        Line::from(Span::styled(
+            format!("{:>STEP_W$} {:<type_w$} payload", "step", "type"),
+            Style::default().fg(TEXT_LIGHT).add_modifier(Modifier::BOLD),
+        )),
+    ];
+
+    let events = app.filtered_events();
+    if events.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "no structured events logged",
+            Style::default().fg(TEXT_DIM),
+        )));
     }
-    let keep = max_len - 3;
-    let mut out = String::new();
-    for ch in input.chars().take(keep) {
-        out.push(ch);
+    let visible = (inner.height as usize).saturating_sub(2);
+    for event in events.iter().skip(app.events_scroll).take(visible) {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{:>STEP_W$} ", event.step),
+                Style::default().fg(TEXT_DIM),
+            ),
+            Span::styled(
+                format!("{:<type_w$} ", truncate_text(&event.kind, type_w)),
+                Style::default().fg(MARKER_EVAL),
+            ),
+            Span::styled(
+                truncate_text(&event.summary(), payload_w),
+                Style::default().fg(TEXT_LIGHT),
+            ),
+        ]));
     }
-    out.push_str("...");
-    out
+    f.render_widget(Paragraph::new(lines), inner);
 }
 
-fn unix_now_secs() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_secs())
-        .unwrap_or(0)
-}
+// ── Distributions Tab ───────────────────────────────────────────────────────
 
-fn format_ago(delta_secs: u64) -> String {
-    if delta_secs < 60 {
-        format!("{}s ago", delta_secs)
-    } else if delta_secs < 3600 {
-        format!("{}m ago", delta_secs / 60)
-    } else if delta_secs < 86400 {
-        format!("{}h ago", delta_secs / 3600)
-    } else {
-        format!("{}d ago", delta_secs / 86400)
-    }
-}
+/// Most bins the single-step view splits a distribution into.
+const MAX_DETAIL_BINS: usize = 32;
 
-fn draw_processes_tab(f: &mut Frame, app: &mut App, area: Rect) {
+fn draw_distributions_tab(f: &mut Frame, app: &mut App, area: Rect) {
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(BORDER))
-        .title(Span::styled(" processes ", Style::default().fg(BORDER)))
+        .border_style(pane_border(app, Pane::Main))
+        .title(Span::styled(" distributions ", Style::default().fg(BORDER)))
         .title_bottom(opengraphs_inline_brand());
-
     let inner = block.inner(area);
     f.render_widget(block, area);
 
-    if inner.width < 10 || inner.height < 3 {
+    if inner.width < 20 || inner.height < 4 {
+        return;
+    }
+    if let Some(err) = &app.histogram_error {
+        let failed = Paragraph::new(vec![
+            Line::from(Span::styled(
+                "histograms could not be read",
+                Style::default().fg(LOG_ERROR),
+            )),
+            Line::from(Span::styled(err.as_str(), Style::default().fg(TEXT_DIM))),
+        ])
+        .wrap(Wrap { trim: true });
+        f.render_widget(failed, inner);
+        return;
+    }
+    let (Some((tag, histograms)), Some(selected)) =
+        (app.selected_histograms(), app.histogram_step_index())
+    else {
+        let empty = Paragraph::new(vec![
+            Line::from(Span::styled(
+                "no histograms logged",
+                Style::default().fg(TEXT_DIM),
+            )),
+            Line::from(Span::styled(
+                "log them with tf.summary.histogram or SummaryWriter.add_histogram",
+                Style::default().fg(TEXT_DIM),
+            )),
+        ]);
+        f.render_widget(empty, inner);
         return;
+    };
+    let range = histogram::range(histograms).unwrap_or((0.0, 1.0));
+
+    let active = Style::default().fg(GREEN).add_modifier(Modifier::BOLD);
+    let idle = Style::default().fg(TEXT_DIM);
+    let mut tags = Vec::new();
+    for (idx, name) in app.histograms.keys().enumerate() {
+        if idx > 0 {
+            tags.push(Span::styled(" · ", Style::default().fg(BORDER)));
+        }
+        tags.push(Span::styled(
+            name.clone(),
+            if name == tag { active } else { idle },
+        ));
     }
 
-    let mut lines: Vec<Line> = Vec::new();
-    let line_width = inner.width.saturating_sub(2) as usize;
-
-    let mut running = app.running_processes.clone();
-    running.sort_by(|a, b| match app.process_sort {
-        ProcessSort::Cpu => b
-            .cpu_pct
-            .partial_cmp(&a.cpu_pct)
-            .unwrap_or(Ordering::Equal)
-            .then_with(|| b.mem_pct.partial_cmp(&a.mem_pct).unwrap_or(Ordering::Equal))
-            .then_with(|| a.pid.cmp(&b.pid)),
-        ProcessSort::Mem => b
-            .mem_pct
-            .partial_cmp(&a.mem_pct)
-            .unwrap_or(Ordering::Equal)
-            .then_with(|| b.cpu_pct.partial_cmp(&a.cpu_pct).unwrap_or(Ordering::Equal))
-            .then_with(|| a.pid.cmp(&b.pid)),
-        ProcessSort::Pid => a
-            .pid
-            .cmp(&b.pid)
-            .then_with(|| b.cpu_pct.partial_cmp(&a.cpu_pct).unwrap_or(Ordering::Equal)),
-        ProcessSort::Etime => b
-            .elapsed_secs
-            .cmp(&a.elapsed_secs)
-            .then_with(|| b.cpu_pct.partial_cmp(&a.cpu_pct).unwrap_or(Ordering::Equal))
-            .then_with(|| a.pid.cmp(&b.pid)),
-    });
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(3)])
+        .split(inner);
+    f.render_widget(Paragraph::new(Line::from(tags)), rows[0]);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(rows[1]);
 
-    let high_cpu = running.iter().filter(|p| p.cpu_pct >= 25.0).count();
-    let zombies = running.iter().filter(|p| p.state.starts_with('Z')).count();
-    let running_total = running.len();
-    running.truncate(app.process_limit);
-    let running_shown = running.len();
+    draw_ridgeline(f, histograms, selected, range, columns[0]);
+    draw_histogram_detail(f, histograms, selected, range, columns[1]);
+}
 
-    lines.push(Line::from(Span::styled(
-        format!(
-            "live processes: {} running | {} high-cpu | {} zombie",
-            running_total, high_cpu, zombies
-        ),
-        Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
-    )));
-    lines.push(Line::from(Span::styled(
+/// One row per step, oldest at the top, each a block-character profile of
+/// that step's distribution over the tag's whole value range.
+fn draw_ridgeline(
+    f: &mut Frame,
+    histograms: &[Histogram],
+    selected: usize,
+    (lo, hi): (f64, f64),
+    area: Rect,
+) {
+    const STEP_W: usize = 9;
+    let ridge_w = (area.width as usize).saturating_sub(STEP_W + 2).max(1);
+    let (lo_label, hi_label) = (format::value(lo), format::value(hi));
+    let mut lines = vec![Line::from(Span::styled(
         format!(
-            "sorted by {} | showing {}",
-            app.process_sort.label(),
-            running_shown
+            "{:>STEP_W$}  {lo_label}{}{hi_label}",
+            "step",
+            " ".repeat(ridge_w.saturating_sub(lo_label.len() + hi_label.len()))
         ),
-        Style::default().fg(BORDER),
-    )));
-
-    if running.is_empty() {
-        lines.push(Line::from(Span::styled(
-            "  (no running processes captured yet)",
-            Style::default().fg(TEXT_DIM),
-        )));
-    } else {
-        for p in &running {
-            let style = state_style(&p.state);
-            let row = truncate_text(
-                &format!(
-                    "[{}] {} | up {} | cpu {:>5.1}% | mem {:>4.1}% | ppid {}",
-                    p.pid,
-                    state_label(&p.state),
-                    p.elapsed,
-                    p.cpu_pct,
-                    p.mem_pct,
-                    p.ppid
-                ),
-                line_width,
-            );
-            lines.push(Line::from(Span::styled(row, style)));
-            let cmd = truncate_text(&format!("  {}", p.command), line_width);
-            lines.push(Line::from(Span::styled(
-                cmd,
-                Style::default().fg(TEXT_LIGHT),
-            )));
-        }
+        Style::default().fg(TEXT_LIGHT).add_modifier(Modifier::BOLD),
+    ))];
+
+    let visible = (area.height as usize).saturating_sub(1).max(1);
+    let skip = (selected + 1).saturating_sub(visible);
+    for (idx, histogram) in histograms.iter().enumerate().skip(skip).take(visible) {
+        let ridge = ridge_text(&histogram.rebin((lo, hi), ridge_w));
+        let (marker, style) = if idx == selected {
+            (
+                "▶",
+                Style::default()
+                    .fg(GREEN)
+                    .bg(LOG_SELECTION_BG)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            (" ", Style::default().fg(ACCENT_DISTRIBUTIONS))
+        };
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{:>STEP_W$}{marker} ", histogram.step),
+                Style::default().fg(TEXT_DIM),
+            ),
+            Span::styled(ridge, style),
+        ]));
     }
+    f.render_widget(Paragraph::new(lines), area);
+}
 
-    lines.push(Line::from(""));
-    lines.push(Line::from(Span::styled(
-        format!("recently exited ({})", app.exited_processes.len()),
-        Style::default()
-            .fg(LOG_IMPORTANT)
-            .add_modifier(Modifier::BOLD),
-    )));
-    lines.push(Line::from(Span::styled(
-        "newest first",
-        Style::default().fg(BORDER),
-    )));
+/// The selected step's distribution as horizontal bars, with its count,
+/// mean and range, and where the step sits among the logged ones.
+fn draw_histogram_detail(
+    f: &mut Frame,
+    histograms: &[Histogram],
+    selected: usize,
+    range: (f64, f64),
+    area: Rect,
+) {
+    let histogram = &histograms[selected];
+    let block = Block::default()
+        .borders(Borders::LEFT)
+        .border_style(Style::default().fg(BORDER))
+        .title(Span::styled(
+            format!(" step {} ", histogram.step),
+            Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+        ));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
 
-    if app.exited_processes.is_empty() {
-        lines.push(Line::from(Span::styled(
-            "  (no exited processes observed yet)",
-            Style::default().fg(TEXT_DIM),
-        )));
-    } else {
-        let now = unix_now_secs();
-        for p in &app.exited_processes {
-            let ago = format_ago(now.saturating_sub(p.exited_at_unix));
-            let row = truncate_text(
-                &format!(
-                    "[{}] exited {} | was {} | up {} | cpu {:>5.1}% | mem {:>4.1}%",
-                    p.snapshot.pid,
-                    ago,
-                    state_label(&p.snapshot.state),
-                    p.snapshot.elapsed,
-                    p.snapshot.cpu_pct,
-                    p.snapshot.mem_pct
-                ),
-                line_width,
-            );
-            lines.push(Line::from(Span::styled(row, Style::default().fg(TEXT_DIM))));
-            let cmd = truncate_text(&format!("  {}", p.snapshot.command), line_width);
-            lines.push(Line::from(Span::styled(
-                cmd,
-                Style::default().fg(TEXT_LIGHT),
-            )));
-        }
+    let (min, max) = histogram.range().unwrap_or(range);
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!(
+                "n {}  mean {}",
+                format::value(histogram.count()),
+                histogram.mean().map(format::value).unwrap_or_default()
+            ),
+            Style::default().fg(TEXT_LIGHT),
+        )),
+        Line::from(Span::styled(
+            format!("min {}  max {}", format::value(min), format::value(max)),
+            Style::default().fg(TEXT_LIGHT),
+        )),
+    ];
+
+    const EDGE_W: usize = 9;
+    let bins = (inner.height as usize)
+        .saturating_sub(4)
+        .clamp(1, MAX_DETAIL_BINS);
+    let counts = histogram.rebin((min, max.max(min + f64::EPSILON)), bins);
+    let peak = counts.iter().copied().fold(0.0, f64::max);
+    let bar_w = (inner.width as usize).saturating_sub(EDGE_W + 10).max(1);
+    let bin_w = (max - min) / bins as f64;
+    for (bin, count) in counts.iter().enumerate() {
+        let filled = if peak > 0.0 {
+            ((count / peak) * bar_w as f64).round() as usize
+        } else {
+            0
+        };
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{:>EDGE_W$} ", format::value(min + bin as f64 * bin_w)),
+                Style::default().fg(TEXT_DIM),
+            ),
+            Span::styled(
+                "█".repeat(filled),
+                Style::default().fg(ACCENT_DISTRIBUTIONS),
+            ),
+            Span::styled(
+                format!(" {}", format::value(*count)),
+                Style::default().fg(TEXT_DIM),
+            ),
+        ]));
     }
 
-    app.set_processes_viewport_rows(inner.height as usize);
-    app.set_processes_total_rows(lines.len());
+    let slider_w = (inner.width as usize).saturating_sub(12).max(2);
+    let at = if histograms.len() > 1 {
+        selected * (slider_w - 1) / (histograms.len() - 1)
+    } else {
+        slider_w - 1
+    };
+    lines.push(Line::from(vec![
+        Span::styled("━".repeat(at), Style::default().fg(GREEN)),
+        Span::styled("●", Style::default().fg(GREEN).add_modifier(Modifier::BOLD)),
+        Span::styled("━".repeat(slider_w - 1 - at), Style::default().fg(TEXT_DIM)),
+        Span::styled(
+            format!(" {}/{}", selected + 1, histograms.len()),
+            Style::default().fg(TEXT_DIM),
+        ),
+    ]));
+    f.render_widget(Paragraph::new(lines), inner);
+}
 
-    let paragraph = Paragraph::new(lines)
-        .scroll((app.processes_scroll, 0))
-        .wrap(Wrap { trim: false });
-    f.render_widget(paragraph, inner);
+/// One block character per bin, scaled to the row's fullest bin; empty bins
+/// stay blank so the outline of each step's distribution shows.
+fn ridge_text(counts: &[f64]) -> String {
+    let peak = counts.iter().copied().fold(0.0, f64::max);
+    counts
+        .iter()
+        .map(|count| {
+            if *count <= 0.0 || peak <= 0.0 {
+                return ' ';
+            }
+            let level = ((count / peak) * (SPARK_LEVELS.len() - 1) as f64).round() as usize;
+            SPARK_LEVELS[level.min(SPARK_LEVELS.len() - 1)]
+        })
+        .collect()
 }
 
-// ── Chat Tab ────────────────────────────────────────────────────────────
+fn run_chart_color(app: &App) -> Color {
+    app.run_color
+        .map(|(r, g, b)| Color::Rgb(r, g, b))
+        .unwrap_or(CHART_RAW)
+}
 
-const CHAT_USER: Color = Color::Rgb(52, 211, 153); // emerald for user
-const CHAT_AGENT: Color = Color::Rgb(96, 165, 250); // blue for agent
-const CHAT_SYSTEM: Color = Color::Rgb(107, 114, 128); // dim gray for system
-// reserved: const CHAT_INPUT_BG: Color = Color::Rgb(30, 35, 44);
+/// One seed group charted as dim braille shading with its mean drawn over it.
+struct BandLayer {
+    label: String,
+    color: Color,
+    fill: Vec<(f64, f64)>,
+    band: Band,
+}
 
-fn compact_brand_line(word: &str, palette: &[Color]) -> Line<'static> {
-    let mut spans = Vec::new();
-    for (idx, ch) in word.chars().enumerate() {
-        let mut label = ch.to_string();
-        if idx + 1 != word.chars().count() {
-            label.push(' ');
-        }
-        spans.push(Span::styled(
-            label,
-            Style::default()
-                .fg(palette[idx % palette.len()])
-                .add_modifier(Modifier::BOLD),
-        ));
-    }
-    Line::from(spans)
+/// Bands for the seed groups that logged `tag`, sampled across `x` at the
+/// braille resolution of `area` and shaded finely enough to look solid on a
+/// `y` axis. Values go through `plot_y` like the series they replace. Empty
+/// unless a multi-run project is loaded.
+fn seed_band_layers(
+    app: &App,
+    tag: &str,
+    x: (f64, f64),
+    (y_lo, y_hi): (f64, f64),
+    area: Rect,
+    plot_y: impl Fn(f64) -> f64,
+) -> Vec<BandLayer> {
+    let groups = app.seed_bands(tag);
+    let samples = usize::from(area.width) * 2;
+    let shade_step = (y_hi - y_lo) / (f64::from(area.height.max(1)) * 4.0);
+    groups
+        .iter()
+        .filter_map(|group| {
+            let band = seed_band::band(&group.scalars[tag], app.band_kind, x, samples)?
+                .map_values(&plot_y);
+            let color = if groups.len() == 1 {
+                run_chart_color(app)
+            } else {
+                let (r, g, b) = crate::run_color::for_run(&group.label, None);
+                Color::Rgb(r, g, b)
+            };
+            Some(BandLayer {
+                label: format!("{} ({} runs)", group.label, group.runs),
+                color,
+                fill: band.fill(shade_step),
+                band,
+            })
+        })
+        .collect()
 }
 
-fn logo_row_color(row: u16, height: u16) -> Color {
-    if height <= 1 {
-        return LOGO_GREEN_MID;
+/// Shading then mean line for each layer; means are named in the legend when
+/// there is more than one group.
+fn band_datasets(layers: &[BandLayer]) -> Vec<Dataset<'_>> {
+    let mut datasets = Vec::new();
+    for layer in layers {
+        datasets.push(
+            Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Scatter)
+                .style(Style::default().fg(layer.color).add_modifier(Modifier::DIM))
+                .data(&layer.fill),
+        );
     }
-    let position = row as f32 / (height.saturating_sub(1)) as f32;
-    if position <= 0.5 {
-        let local = position / 0.5;
-        Color::Rgb(
-            (134.0 + (46.0 - 134.0) * local).round() as u8,
-            (239.0 + (204.0 - 239.0) * local).round() as u8,
-            (172.0 + (113.0 - 172.0) * local).round() as u8,
-        )
-    } else {
-        let local = (position - 0.5) / 0.5;
-        Color::Rgb(
-            (46.0 + (12.0 - 46.0) * local).round() as u8,
-            (204.0 + (104.0 - 204.0) * local).round() as u8,
-            (113.0 + (58.0 - 113.0) * local).round() as u8,
-        )
+    for layer in layers {
+        let mean = Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(
+                Style::default()
+                    .fg(layer.color)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .data(&layer.band.mean);
+        datasets.push(if layers.len() > 1 {
+            mean.name(layer.label.clone())
+        } else {
+            mean
+        });
     }
+    datasets
 }
 
-fn logo_lines() -> Vec<Line<'static>> {
-    OPENGRAPHS_LOGO_ROWS
+// ── Dashboard Tab ───────────────────────────────────────────────────────────
+
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Render the last `width` values as a block-character sparkline.
+fn sparkline_text(values: &[f64], width: usize) -> String {
+    let finite: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    let tail = &finite[finite.len().saturating_sub(width)..];
+    let (lo, hi) = tail
         .iter()
-        .enumerate()
-        .map(|(idx, row)| {
-            Line::from(Span::styled(
-                row.trim_end().to_string(),
-                Style::default()
-                    .fg(logo_row_color(idx as u16, OPENGRAPHS_LOGO_ROWS.len() as u16))
-                    .add_modifier(Modifier::BOLD),
-            ))
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(*v), hi.max(*v))
+        });
+    let span = hi - lo;
+    tail.iter()
+        .map(|v| {
+            let level = if span > 0.0 {
+                (((v - lo) / span) * (SPARK_LEVELS.len() - 1) as f64).round() as usize
+            } else {
+                SPARK_LEVELS.len() / 2
+            };
+            SPARK_LEVELS[level.min(SPARK_LEVELS.len() - 1)]
         })
         .collect()
 }
 
-fn ogd_status_line(app: &App) -> Line<'static> {
-    let mut spans = vec![
-        Span::styled(" ogd sock ", Style::default().fg(TEXT_LIGHT)),
-        Span::styled(
-            "●",
-            Style::default()
-                .fg(if app.daemon_connected {
-                    GREEN
-                } else {
-                    Color::Yellow
-                })
-                .add_modifier(Modifier::BOLD),
-        ),
-    ];
-
-    if app.agent_thinking {
-        spans.push(Span::raw("  "));
-        spans.push(Span::styled("thinking...", Style::default().fg(TEXT_DIM)));
+fn run_status_style(status: &str) -> Style {
+    match status {
+        "running" => Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+        "inactive" | "finished" => Style::default().fg(TEXT_DIM),
+        "crashed" | "killed" => Style::default().fg(LOG_ERROR),
+        _ => Style::default().fg(LOG_IMPORTANT),
     }
+}
 
-    if app.pending_refactor.is_some() {
-        spans.push(Span::raw("  "));
-        spans.push(Span::styled(
-            "pending refactor",
-            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+fn draw_dashboard_tab(f: &mut Frame, app: &mut App, area: Rect) {
+    let mut title = vec![Span::styled(
+        format!(" runs in {} ", app.dashboard_root.display()),
+        Style::default().fg(BORDER),
+    )];
+    if let Some(free) = app.runs_free_bytes {
+        let style = if free < app.min_free_bytes {
+            Style::default()
+                .fg(LOG_IMPORTANT)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(TEXT_DIM)
+        };
+        title.push(Span::styled(
+            format!("· {} free ", format::bytes(free)),
+            style,
         ));
     }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(pane_border(app, Pane::Main))
+        .title(Line::from(title))
+        .title_bottom(opengraphs_inline_brand());
+    let inner = block.inner(area);
+    f.render_widget(block, area);
 
-    Line::from(spans)
-}
-
-fn draw_chat_brand(f: &mut Frame, area: Rect) {
-    if area.width < 12 || area.height < 3 {
+    if inner.width < 20 || inner.height < 2 {
         return;
     }
 
-    let cwd = std::env::current_dir()
-        .map(|path| path.display().to_string())
-        .unwrap_or_else(|_| ".".to_string());
-    let cwd = truncate_text(&cwd, area.width.saturating_sub(2) as usize);
-    let version = format!("(v{})", env!("CARGO_PKG_VERSION"));
-    let logo_width = OPENGRAPHS_LOGO_ROWS
-        .iter()
-        .map(|row| row.trim_end().chars().count())
-        .max()
-        .unwrap_or(0) as u16;
-    let logo_height = OPENGRAPHS_LOGO_ROWS.len() as u16;
+    const RUN_W: usize = 28;
+    const STATUS_W: usize = 9;
+    const STEP_W: usize = 9;
+    const RATE_W: usize = 10;
+    const SIZE_W: usize = 10;
+    const SPEND_W: usize = 9;
+    const METRIC_W: usize = 18;
+    const VALUE_W: usize = 10;
+    let fixed = RUN_W + STATUS_W + STEP_W + RATE_W + SIZE_W + SPEND_W + METRIC_W + VALUE_W + 8;
+    let spark_w = (inner.width as usize).saturating_sub(fixed).max(8);
+
+    let header = format!(
+        "{:<RUN_W$} {:<STATUS_W$} {:>STEP_W$} {:>RATE_W$} {:>SIZE_W$} {:>SPEND_W$} {:<METRIC_W$} {:>VALUE_W$} trend",
+        "run", "status", "step", "steps/s", "size", "spend", "metric", "last"
+    );
+    let mut lines = vec![Line::from(Span::styled(
+        header,
+        Style::default().fg(TEXT_LIGHT).add_modifier(Modifier::BOLD),
+    ))];
 
-    if area.width < logo_width || area.height < logo_height + 2 {
-        let compact = Paragraph::new(vec![
-            compact_brand_line(
-                "opengraphs",
-                &[
-                    LOGO_GREEN_TOP,
-                    LOGO_GREEN_MID,
-                    LOGO_GREEN_BOTTOM,
-                    LOGO_GREEN_BOTTOM,
-                    LOGO_GREEN_MID,
-                    LOGO_GREEN_TOP,
-                ],
+    if app.dashboard_rows.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "no runs with event files found",
+            Style::default().fg(TEXT_DIM),
+        )));
+    }
+
+    let visible = (inner.height as usize).saturating_sub(1);
+    for row in app
+        .dashboard_rows
+        .iter()
+        .skip(app.dashboard_scroll)
+        .take(visible)
+    {
+        let rate = row
+            .steps_per_sec
+            .map(|r| format!("{:.2}", r))
+            .unwrap_or_else(|| "-".to_string());
+        let metric = row
+            .metric
+            .as_deref()
+            .map(|tag| app.metric_display_name(tag))
+            .unwrap_or("-");
+        let last = row
+            .recent
+            .last()
+            .map(|v| format::value(*v))
+            .unwrap_or_else(|| "-".to_string());
+        let size = app.run_sizes.get(&row.run).copied();
+        let size_style = if size.is_some_and(|bytes| bytes > app.run_size_warn_bytes) {
+            Style::default()
+                .fg(LOG_IMPORTANT)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(TEXT_LIGHT)
+        };
+        let size = size.map(format::bytes).unwrap_or_else(|| "-".to_string());
+        let spend_style = if row.spend.is_some_and(|spend| spend.over_budget) {
+            Style::default()
+                .fg(LOG_IMPORTANT)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(TEXT_LIGHT)
+        };
+        let spend = row
+            .spend
+            .map(|spend| format!("${:.2}", spend.dollars))
+            .unwrap_or_else(|| "-".to_string());
+        let (r, g, b) = row.color;
+        let run_style = Style::default().fg(Color::Rgb(r, g, b));
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{:<RUN_W$} ", truncate_text(&row.run, RUN_W)),
+                run_style,
             ),
-            Line::from(Span::styled(version.clone(), Style::default().fg(TEXT_LIGHT))),
-            Line::from(Span::styled(cwd, Style::default().fg(TEXT_DIM))),
-        ])
-        .alignment(Alignment::Left);
-        f.render_widget(compact, area);
-        return;
+            Span::styled(
+                format!("{:<STATUS_W$} ", row.status),
+                run_status_style(&row.status),
+            ),
+            Span::styled(
+                format!("{:>STEP_W$} {:>RATE_W$} ", row.step, rate),
+                Style::default().fg(TEXT_LIGHT),
+            ),
+            Span::styled(format!("{:>SIZE_W$} ", size), size_style),
+            Span::styled(format!("{:>SPEND_W$} ", spend), spend_style),
+            Span::styled(
+                format!("{:<METRIC_W$} ", truncate_text(metric, METRIC_W)),
+                Style::default().fg(TEXT_DIM),
+            ),
+            Span::styled(
+                format!("{:>VALUE_W$} ", last),
+                Style::default().fg(TEXT_LIGHT),
+            ),
+            Span::styled(sparkline_text(&row.recent, spark_w), run_style),
+        ]));
     }
 
-    let logo_area = Rect::new(area.x, area.y, area.width, logo_height);
-    let meta_area = Rect::new(
-        area.x,
-        area.y + logo_area.height,
-        area.width,
-        area.height.saturating_sub(logo_area.height),
-    );
+    f.render_widget(Paragraph::new(lines), inner);
+}
 
-    let logo = Paragraph::new(logo_lines()).alignment(Alignment::Left);
-    f.render_widget(logo, logo_area);
+// ── Processes Tab ───────────────────────────────────────────────────────────
 
-    let meta = Paragraph::new(vec![
-        Line::from(Span::styled(version, Style::default().fg(TEXT_LIGHT))),
-        Line::from(Span::styled(cwd, Style::default().fg(TEXT_DIM))),
-    ])
-    .alignment(Alignment::Left);
-    f.render_widget(meta, meta_area);
+fn state_style(state: &str) -> Style {
+    let first = state.chars().next().unwrap_or('S');
+    match first {
+        'R' => Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+        'D' | 'Z' => Style::default().fg(LOG_ERROR).add_modifier(Modifier::BOLD),
+        'T' => Style::default().fg(LOG_IMPORTANT),
+        _ => Style::default().fg(TEXT_DIM),
+    }
 }
 
-fn draw_chat_tab(f: &mut Frame, app: &mut App, area: Rect) {
-    // Layout: banner (0-2) | messages (fill) | refactor prompt (0 or 3) | input (3) | status (1)
-    let has_banner = app.auto_mode;
-    let has_refactor = app.pending_refactor.is_some();
-    let banner_h = if has_banner { 1 } else { 0 };
-    let refactor_h: u16 = if has_refactor { 3 } else { 0 };
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(banner_h),
-            Constraint::Min(5),
-            Constraint::Length(refactor_h),
-            Constraint::Length(3),
-            Constraint::Length(1),
-        ])
-        .split(area);
+fn state_label(state: &str) -> &'static str {
+    match state.chars().next().unwrap_or('S') {
+        'R' => "running",
+        'S' => "sleeping",
+        'D' => "io-wait",
+        'T' => "stopped",
+        'Z' => "zombie",
+        'I' => "idle",
+        _ => "other",
+    }
+}
 
-    // Auto-mode banner
-    if app.auto_mode {
-        let banner = Paragraph::new(Line::from(Span::styled(
-            " ⚡ Auto mode: Agent will apply fixes automatically ",
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        )))
-        .alignment(Alignment::Center);
-        f.render_widget(banner, chunks[0]);
+/// `cpu ▁▂█  mem ▁▁▂  gpu ▂▂▂ 2.0 GiB` under a process row, each sparkline
+/// rising from zero so a sustained plateau reads differently from a spike.
+/// None when `width` leaves no room for them.
+fn process_sparklines(
+    history: &ProcessHistory,
+    gpu_mem_mb: Option<f64>,
+    width: usize,
+) -> Option<Line<'static>> {
+    let gpu = !history.gpu_mem_mb.is_empty();
+    let series: Vec<(&str, &VecDeque<f64>, f64)> = [
+        Some(("cpu", &history.cpu_pct, 100.0)),
+        Some(("mem", &history.mem_pct, 0.0)),
+        gpu.then_some(("gpu", &history.gpu_mem_mb, 0.0)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let gpu_label = gpu_mem_mb
+        .map(|mb| format!(" {}", format::bytes((mb * 1024.0 * 1024.0) as u64)))
+        .unwrap_or_default();
+    let labels = series.len() * 6 + gpu_label.len();
+    let cells = (width.saturating_sub(labels) / series.len()).min(PROCESS_HISTORY_LEN);
+    if cells < 4 {
+        return None;
+    }
+    let mut spans = Vec::new();
+    for (label, values, floor) in series {
+        let values: Vec<f64> = values.iter().copied().collect();
+        spans.push(Span::styled(
+            format!("  {label} "),
+            Style::default().fg(TEXT_DIM),
+        ));
+        spans.push(Span::styled(
+            sparkline_from_zero(&values, cells, floor),
+            Style::default().fg(CHART_RAW),
+        ));
     }
+    spans.push(Span::styled(gpu_label, Style::default().fg(TEXT_DIM)));
+    Some(Line::from(spans))
+}
 
-    // Messages area
-    draw_chat_messages(f, app, chunks[1]);
+/// Like `sparkline_text`, but scaled from zero to the larger of `floor` and
+/// the highest value, so flat series keep their level.
+fn sparkline_from_zero(values: &[f64], width: usize, floor: f64) -> String {
+    let tail = &values[values.len().saturating_sub(width)..];
+    let hi = tail
+        .iter()
+        .copied()
+        .filter(|v| v.is_finite())
+        .fold(floor, f64::max);
+    tail.iter()
+        .map(|v| {
+            let share = if hi > 0.0 && v.is_finite() {
+                (v / hi).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            SPARK_LEVELS[(share * (SPARK_LEVELS.len() - 1) as f64).round() as usize]
+        })
+        .collect()
+}
 
-    // Pending refactor approve/reject prompt
-    if has_refactor {
-        let refactor_block = Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(BORDER))
-            .title(Span::styled(
-                " pending refactor ",
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            ));
-        let prompt = Paragraph::new(Line::from(vec![
-            Span::styled(" Press ", Style::default().fg(TEXT_LIGHT)),
-            Span::styled("y", Style::default().fg(GREEN).add_modifier(Modifier::BOLD)),
-            Span::styled(" to apply │ ", Style::default().fg(TEXT_LIGHT)),
-            Span::styled(
-                "n",
-                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(" to reject", Style::default().fg(TEXT_LIGHT)),
-        ]))
-        .block(refactor_block);
-        f.render_widget(prompt, chunks[2]);
+fn truncate_text(input: &str, max_len: usize) -> String {
+    if max_len == 0 {
+        return String::new();
+    }
+    let count = input.chars().count();
+    if count <= max_len {
+        return input.to_string();
+    }
+    if max_len <= 3 {
+        return ".".repeat(max_len);
+    }
+    let keep = max_len - 3;
+    let mut out = String::new();
+    for ch in input.chars().take(keep) {
+        out.push(ch);
     }
+    out.push_str("...");
+    out
+}
 
-    // Input area
-    draw_chat_input(f, app, chunks[3]);
+fn unix_now_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
+}
 
-    // Status bar
-    let status = Paragraph::new(ogd_status_line(app));
-    f.render_widget(status, chunks[4]);
+fn unix_now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
 }
 
-fn draw_chat_messages(f: &mut Frame, app: &mut App, area: Rect) {
+/// Ranks listed in the distributed health panel before it stops growing.
+const HEALTH_PANEL_MAX_RANKS: usize = 8;
+/// Most recent NCCL / torch.distributed issues shown under the rank rows.
+const HEALTH_PANEL_ISSUES: usize = 3;
+
+fn rank_health_style(status: RankStatus) -> Style {
+    match status {
+        RankStatus::Alive => Style::default().fg(GREEN),
+        RankStatus::Stalled => Style::default()
+            .fg(LOG_IMPORTANT)
+            .add_modifier(Modifier::BOLD),
+        RankStatus::TimedOut => Style::default().fg(LOG_ERROR).add_modifier(Modifier::BOLD),
+    }
+}
+
+fn draw_dist_health_panel(f: &mut Frame, app: &App, area: Rect) {
     let block = Block::default()
         .borders(Borders::ALL)
         .border_style(Style::default().fg(BORDER))
         .title(Span::styled(
-            " agent chat ",
-            Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+            " distributed health ",
+            Style::default().fg(BORDER),
         ));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let line_width = inner.width.saturating_sub(1) as usize;
 
-    if app.chat_messages.is_empty() && !app.daemon_connected {
-        let inner = block.inner(area);
-        f.render_widget(block, area);
-
-        let help_lines = vec![
-            Line::from(""),
-            Line::from(Span::styled(
-                "Agent chat is not connected.",
-                Style::default().fg(TEXT_DIM),
-            )),
-            Line::from(""),
-            Line::from(Span::styled(
-                "Start with built-in daemon:",
-                Style::default().fg(TEXT_DIM),
-            )),
-            Line::from(Span::styled(
-                "  ogtui --path runs/ --training-file train.py",
-                Style::default().fg(TEXT_LIGHT),
-            )),
-            Line::from(""),
-            Line::from(Span::styled(
-                "Or start daemon separately:",
-                Style::default().fg(TEXT_DIM),
-            )),
-            Line::from(Span::styled(
-                "  python3 -m og_agent_chat.server --training-file train.py",
-                Style::default().fg(TEXT_LIGHT),
-            )),
-        ];
-
-        if inner.height < 11 {
-            let p = Paragraph::new(help_lines)
-                .alignment(Alignment::Center)
-                .wrap(Wrap { trim: false });
-            f.render_widget(p, inner);
-            return;
-        }
+    let mut lines = Vec::new();
+    if !app.rank_health.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!(
+                "{:<6} {:<10} {:>10} {:>12} {:>7}",
+                "rank", "status", "last step", "behind", "issues"
+            ),
+            Style::default().fg(TEXT_DIM),
+        )));
+    }
+    for health in app.rank_health.iter().take(HEALTH_PANEL_MAX_RANKS) {
+        let row = format!(
+            "{:<6} {:<10} {:>10} {:>12} {:>7}",
+            health.rank,
+            health.status.label(),
+            health.last_step,
+            format!("{:.0}s", health.behind_secs),
+            health.issues
+        );
+        lines.push(Line::from(Span::styled(
+            truncate_text(&row, line_width),
+            rank_health_style(health.status),
+        )));
+    }
+    let skip = app.dist_issues.len().saturating_sub(HEALTH_PANEL_ISSUES);
+    for issue in &app.dist_issues[skip..] {
+        let style = match issue.kind {
+            IssueKind::Warning => Style::default().fg(LOG_IMPORTANT),
+            IssueKind::Error | IssueKind::Timeout => Style::default().fg(LOG_ERROR),
+        };
+        let rank = issue
+            .rank
+            .map(|rank| format!("rank {}", rank))
+            .unwrap_or_else(|| "rank ?".to_string());
+        lines.push(Line::from(Span::styled(
+            truncate_text(
+                &format!("[{}] {}: {}", issue.kind.label(), rank, issue.message),
+                line_width,
+            ),
+            style,
+        )));
+    }
+    f.render_widget(Paragraph::new(lines), inner);
+}
 
+fn draw_processes_tab(f: &mut Frame, app: &mut App, area: Rect) {
+    let area = if app.rank_health.is_empty() && app.dist_issues.is_empty() {
+        area
+    } else {
+        let header = usize::from(!app.rank_health.is_empty());
+        let rows = header
+            + app.rank_health.len().min(HEALTH_PANEL_MAX_RANKS)
+            + app.dist_issues.len().min(HEALTH_PANEL_ISSUES);
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Min(1), Constraint::Length(8)])
-            .split(inner);
-        let p = Paragraph::new(help_lines)
-            .alignment(Alignment::Center)
-            .wrap(Wrap { trim: false });
-        f.render_widget(p, chunks[0]);
-        draw_chat_brand(f, chunks[1]);
-        return;
-    }
+            .constraints([Constraint::Length(rows as u16 + 2), Constraint::Min(5)])
+            .split(area);
+        draw_dist_health_panel(f, app, chunks[0]);
+        chunks[1]
+    };
 
-    if app.chat_messages.is_empty() {
-        let inner = block.inner(area);
-        f.render_widget(block, area);
-        let msg = Paragraph::new(Line::from(Span::styled(
-            "No messages yet. Type a message and press Enter.",
-            Style::default().fg(TEXT_DIM),
-        )))
-        .alignment(Alignment::Center);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(pane_border(app, Pane::Main))
+        .title(Span::styled(" processes ", Style::default().fg(BORDER)))
+        .title_bottom(opengraphs_inline_brand());
 
-        if inner.height < 9 {
-            f.render_widget(msg, inner);
-            return;
-        }
+    let inner = block.inner(area);
+    f.render_widget(block, area);
 
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Min(1), Constraint::Length(8)])
-            .split(inner);
-        f.render_widget(msg, chunks[0]);
-        draw_chat_brand(f, chunks[1]);
+    if inner.width < 10 || inner.height < 3 {
         return;
     }
 
-    let inner = block.inner(area);
-    f.render_widget(block, area);
-
     let mut lines: Vec<Line> = Vec::new();
-    for msg in app.chat_messages.iter() {
-        let (prefix, color) = match msg.sender.as_str() {
-            "user" => ("You", CHAT_USER),
-            "agent" => ("Agent", CHAT_AGENT),
-            "system" => ("System", CHAT_SYSTEM),
-            other => (other, TEXT_DIM),
-        };
+    let line_width = inner.width.saturating_sub(2) as usize;
 
-        lines.push(Line::from(vec![Span::styled(
-            format!("[{}] ", prefix),
-            Style::default().fg(color).add_modifier(Modifier::BOLD),
-        )]));
+    let mut running: Vec<ProcessSnapshot> = app.shown_processes().into_iter().cloned().collect();
+    running.sort_by(|a, b| match app.process_sort {
+        ProcessSort::Cpu => b
+            .cpu_pct
+            .partial_cmp(&a.cpu_pct)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| b.mem_pct.partial_cmp(&a.mem_pct).unwrap_or(Ordering::Equal))
+            .then_with(|| a.pid.cmp(&b.pid)),
+        ProcessSort::Mem => b
+            .mem_pct
+            .partial_cmp(&a.mem_pct)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| b.cpu_pct.partial_cmp(&a.cpu_pct).unwrap_or(Ordering::Equal))
+            .then_with(|| a.pid.cmp(&b.pid)),
+        ProcessSort::Pid => a
+            .pid
+            .cmp(&b.pid)
+            .then_with(|| b.cpu_pct.partial_cmp(&a.cpu_pct).unwrap_or(Ordering::Equal)),
+        ProcessSort::Etime => b
+            .elapsed_secs
+            .cmp(&a.elapsed_secs)
+            .then_with(|| b.cpu_pct.partial_cmp(&a.cpu_pct).unwrap_or(Ordering::Equal))
+            .then_with(|| a.pid.cmp(&b.pid)),
+    });
+
+    let high_cpu = running.iter().filter(|p| p.cpu_pct >= 25.0).count();
+    let zombies = running.iter().filter(|p| p.state.starts_with('Z')).count();
+    let running_total = running.len();
+    running.truncate(app.process_limit);
+    let running_shown = running.len();
 
-        for l in msg.content.lines() {
-            let style = if l.starts_with('+') && !l.starts_with("+++") {
-                Style::default().fg(GREEN)
-            } else if l.starts_with('-') && !l.starts_with("---") {
-                Style::default().fg(Color::Red)
-            } else if l.starts_with("@@") {
-                Style::default().fg(Color::Cyan)
-            } else if l.starts_with("---") || l.starts_with("+++") {
-                Style::default().fg(TEXT_DIM).add_modifier(Modifier::BOLD)
+    lines.push(Line::from(Span::styled(
+        format!(
+            "live processes: {} running | {} high-cpu | {} zombie",
+            running_total, high_cpu, zombies
+        ),
+        Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+    )));
+    lines.push(Line::from(Span::styled(
+        format!(
+            "sorted by {} | showing {} | {} (f)",
+            app.process_sort.label(),
+            running_shown,
+            if app.process_filter {
+                "training tree + gpu"
             } else {
-                Style::default().fg(TEXT_LIGHT)
-            };
-            lines.push(Line::from(Span::styled(format!("  {}", l), style)));
-        }
-        lines.push(Line::from(""));
+                "all processes"
+            }
+        ),
+        Style::default().fg(BORDER),
+    )));
+    if let Some(limits) = app.container_limits {
+        lines.push(Line::from(Span::styled(
+            format!(
+                "container limit: {} | cpu % of quota, mem % of limit",
+                limits.describe()
+            ),
+            Style::default().fg(BORDER),
+        )));
     }
 
-    let viewport_rows = inner.height as usize;
-    let max_scroll = lines.len().saturating_sub(viewport_rows) as u16;
-    if app.chat_follow_tail {
-        app.chat_scroll = max_scroll;
-    } else if app.chat_scroll > max_scroll {
-        app.chat_scroll = max_scroll;
+    if running.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  (no running processes captured yet)",
+            Style::default().fg(TEXT_DIM),
+        )));
+    } else {
+        for p in &running {
+            let style = state_style(&p.state);
+            let row = truncate_text(
+                &format!(
+                    "[{}] {} | up {} | cpu {:>5.1}% | mem {:>4.1}% | ppid {}",
+                    p.pid,
+                    state_label(&p.state),
+                    p.elapsed,
+                    p.cpu_pct,
+                    p.mem_pct,
+                    p.ppid
+                ),
+                line_width,
+            );
+            lines.push(Line::from(Span::styled(row, style)));
+            if let Some(history) = app.process_history.get(&p.pid) {
+                lines.extend(process_sparklines(history, p.gpu_mem_mb, line_width));
+            }
+            let cmd = truncate_text(&format!("  {}", p.command), line_width);
+            lines.push(Line::from(Span::styled(
+                cmd,
+                Style::default().fg(TEXT_LIGHT),
+            )));
+        }
     }
-    if app.chat_scroll >= max_scroll {
-        app.chat_follow_tail = true;
+
+    let exited = app.shown_exited_processes();
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        format!("recently exited ({})", exited.len()),
+        Style::default()
+            .fg(LOG_IMPORTANT)
+            .add_modifier(Modifier::BOLD),
+    )));
+    lines.push(Line::from(Span::styled(
+        "newest first",
+        Style::default().fg(BORDER),
+    )));
+
+    if exited.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  (no exited processes observed yet)",
+            Style::default().fg(TEXT_DIM),
+        )));
+    } else {
+        let now = unix_now_secs();
+        for p in &exited {
+            let ago = format::ago(now.saturating_sub(p.exited_at_unix));
+            let row = truncate_text(
+                &format!(
+                    "[{}] exited {} | was {} | up {} | cpu {:>5.1}% | mem {:>4.1}%",
+                    p.snapshot.pid,
+                    ago,
+                    state_label(&p.snapshot.state),
+                    p.snapshot.elapsed,
+                    p.snapshot.cpu_pct,
+                    p.snapshot.mem_pct
+                ),
+                line_width,
+            );
+            lines.push(Line::from(Span::styled(row, Style::default().fg(TEXT_DIM))));
+            let cmd = truncate_text(&format!("  {}", p.snapshot.command), line_width);
+            lines.push(Line::from(Span::styled(
+                cmd,
+                Style::default().fg(TEXT_LIGHT),
+            )));
+        }
     }
 
+    app.set_processes_viewport_rows(inner.height as usize);
+    app.set_processes_total_rows(lines.len());
+
     let paragraph = Paragraph::new(lines)
-        .scroll((app.chat_scroll, 0))
+        .scroll((app.processes_scroll, 0))
         .wrap(Wrap { trim: false });
     f.render_widget(paragraph, inner);
 }
 
-fn draw_chat_input(f: &mut Frame, app: &App, area: Rect) {
-    let border_color = BORDER;
-    let title_color = if app.chat_input_focused { GREEN } else { BORDER };
-    let title = if app.chat_input_focused {
-        " type message or !og command (Enter=send, Esc=unfocus) "
-    } else {
-        " (i) insert "
-    };
+// ── Chat Tab ────────────────────────────────────────────────────────────
 
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .border_style(Style::default().fg(border_color))
-        .title(Span::styled(title, Style::default().fg(title_color)))
-        .title_bottom(opengraphs_inline_brand());
+const CHAT_USER: Color = Color::Rgb(52, 211, 153); // emerald for user
+const CHAT_AGENT: Color = Color::Rgb(96, 165, 250); // blue for agent
+const CHAT_SYSTEM: Color = Color::Rgb(107, 114, 128); // dim gray for system
+const CHAT_CODE_BG: Color = Color::Rgb(30, 36, 46); // shading behind code
+const CHAT_INLINE_CODE: Color = Color::Rgb(251, 191, 36); // amber for `code`
+// reserved: const CHAT_INPUT_BG: Color = Color::Rgb(30, 35, 44);
 
-    let display_text = if app.chat_input.is_empty() && !app.chat_input_focused {
-        " Type message or !og run demo_train.py ...".to_string()
-    } else if app.chat_input_focused {
-        format!("{}█", app.chat_input)
-    } else {
-        app.chat_input.clone()
-    };
+fn compact_brand_line(word: &str, palette: &[Color]) -> Line<'static> {
+    let mut spans = Vec::new();
+    for (idx, ch) in word.chars().enumerate() {
+        let mut label = ch.to_string();
+        if idx + 1 != word.chars().count() {
+            label.push(' ');
+        }
+        spans.push(Span::styled(
+            label,
+            Style::default()
+                .fg(palette[idx % palette.len()])
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    Line::from(spans)
+}
 
-    let style = if app.chat_input.is_empty() && !app.chat_input_focused {
-        Style::default().fg(TEXT_DIM).add_modifier(Modifier::DIM)
+fn logo_row_color(row: u16, height: u16) -> Color {
+    if height <= 1 {
+        return LOGO_GREEN_MID;
+    }
+    let position = row as f32 / (height.saturating_sub(1)) as f32;
+    if position <= 0.5 {
+        let local = position / 0.5;
+        Color::Rgb(
+            (134.0 + (46.0 - 134.0) * local).round() as u8,
+            (239.0 + (204.0 - 239.0) * local).round() as u8,
+            (172.0 + (113.0 - 172.0) * local).round() as u8,
+        )
     } else {
-        Style::default().fg(TEXT_LIGHT)
-    };
-
-    let input = Paragraph::new(Line::from(Span::styled(display_text, style)))
-        .block(block)
-        .wrap(Wrap { trim: false });
-    f.render_widget(input, area);
+        let local = (position - 0.5) / 0.5;
+        Color::Rgb(
+            (46.0 + (12.0 - 46.0) * local).round() as u8,
+            (204.0 + (104.0 - 204.0) * local).round() as u8,
+            (113.0 + (58.0 - 113.0) * local).round() as u8,
+        )
+    }
 }
 
-// ── Help Modal ──────────────────────────────────────────────────────────────
-
-fn draw_help_modal(f: &mut Frame, area: Rect) {
-    let w = (area.width * 60 / 100).min(60);
-    let h = (area.height * 60 / 100).min(22);
-    let x = (area.width.saturating_sub(w)) / 2;
-    let y = (area.height.saturating_sub(h)) / 2;
-    let modal_area = Rect::new(x, y, w, h);
-
-    f.render_widget(Clear, modal_area);
-
-    let shortcuts = vec![
-        ("Tab / Shift+Tab", "Cycle tabs"),
-        ("q", "Quit"),
-        ("?", "Toggle this help"),
-        ("F6", "Toggle copy mode (highlight/copy text with mouse)"),
-        ("Esc", "Close help / exit detail"),
-        ("j / ↓", "Scroll logs/procs/chat down"),
-        ("k / ↑", "Scroll logs/procs/chat up"),
-        ("l / →", "Next metric"),
-        ("h / ←", "Previous metric"),
-        ("Enter / Click", "Enlarge metric"),
-        ("i", "Focus chat input"),
-        ("Enter (chat)", "Send message"),
-        ("!og ...", "Run CLI commands in chat"),
-        ("Esc (chat)", "Unfocus chat input"),
-        ("y (chat)", "Apply pending refactor"),
-        ("n (chat)", "Reject pending refactor"),
-    ];
-
-    let lines: Vec<Line> = shortcuts
+fn logo_lines() -> Vec<Line<'static>> {
+    OPENGRAPHS_LOGO_ROWS
         .iter()
-        .map(|(key, desc)| {
-            Line::from(vec![
-                Span::styled(
-                    format!("{:<20}", key),
-                    Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
-                ),
-                Span::styled(*desc, Style::default().fg(TEXT_LIGHT)),
-            ])
+        .enumerate()
+        .map(|(idx, row)| {
+            Line::from(Span::styled(
+                row.trim_end().to_string(),
+                Style::default()
+                    .fg(logo_row_color(
+                        idx as u16,
+                        OPENGRAPHS_LOGO_ROWS.len() as u16,
+                    ))
+                    .add_modifier(Modifier::BOLD),
+            ))
         })
-        .collect();
-
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .border_style(Style::default().fg(BORDER))
-        .title(Span::styled(
-            " shortcuts ",
-            Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
-        ))
-        .style(Style::default().bg(BG_DARK));
-
-    let paragraph = Paragraph::new(lines)
-        .block(block)
-        .wrap(Wrap { trim: false });
-    f.render_widget(paragraph, modal_area);
+        .collect()
 }
 
-// ── Footer ──────────────────────────────────────────────────────────────────
+fn ogd_status_line(app: &App) -> Line<'static> {
+    let mut spans = vec![
+        Span::styled(" ogd sock ", Style::default().fg(TEXT_LIGHT)),
+        Span::styled(
+            "●",
+            Style::default()
+                .fg(if app.daemon_connected {
+                    GREEN
+                } else {
+                    Color::Yellow
+                })
+                .add_modifier(Modifier::BOLD),
+        ),
+    ];
 
-fn draw_footer(f: &mut Frame, _app: &App, area: Rect) {
-    if _app.copy_mode {
-        let banner = Line::from(vec![
-            Span::styled(
-                "COPY MODE",
-                Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(
-                " │ Drag mouse to highlight/copy text │ F6 resume interactive mode",
-                Style::default().fg(TEXT_LIGHT),
-            ),
-        ]);
-        let footer = Paragraph::new(banner).alignment(Alignment::Left);
-        f.render_widget(footer, area);
-        return;
+    if app.agent_thinking {
+        spans.push(Span::raw("  "));
+        let label = if app.chat_stream.is_empty() {
+            "thinking..."
+        } else {
+            "typing..."
+        };
+        spans.push(Span::styled(label, Style::default().fg(TEXT_DIM)));
     }
 
-    let hints = if _app.active_tab == Tab::Chat {
-        Line::from(vec![
-            Span::styled("Tab", Style::default().fg(GREEN)),
-            Span::styled(" switch │ ", Style::default().fg(BORDER)),
-            Span::styled("j/k", Style::default().fg(GREEN)),
-            Span::styled(" scroll │ ", Style::default().fg(BORDER)),
-            Span::styled("F6", Style::default().fg(GREEN)),
-            Span::styled(" copy │ ", Style::default().fg(BORDER)),
-            Span::styled("?", Style::default().fg(GREEN)),
-            Span::styled(" help │ ", Style::default().fg(BORDER)),
-            Span::styled("q", Style::default().fg(GREEN)),
-            Span::styled(" quit", Style::default().fg(BORDER)),
-        ])
-    } else if _app.active_tab == Tab::Graphs {
-        Line::from(vec![
-            Span::styled("Tab", Style::default().fg(GREEN)),
-            Span::styled(" switch │ ", Style::default().fg(BORDER)),
-            Span::styled("?", Style::default().fg(GREEN)),
-            Span::styled(" help │ ", Style::default().fg(BORDER)),
-            Span::styled("q", Style::default().fg(GREEN)),
-            Span::styled(" quit │ ", Style::default().fg(BORDER)),
-            Span::styled("h/l", Style::default().fg(GREEN)),
-            Span::styled(" metrics │ ", Style::default().fg(BORDER)),
-            Span::styled("j/k", Style::default().fg(GREEN)),
-            Span::styled(" scroll │ ", Style::default().fg(BORDER)),
-            Span::styled("F6", Style::default().fg(GREEN)),
-            Span::styled(" copy", Style::default().fg(BORDER)),
-        ])
-    } else {
-        Line::from(vec![
-            Span::styled("Tab", Style::default().fg(GREEN)),
-            Span::styled(" switch │ ", Style::default().fg(BORDER)),
-            Span::styled("?", Style::default().fg(GREEN)),
-            Span::styled(" help │ ", Style::default().fg(BORDER)),
-            Span::styled("q", Style::default().fg(GREEN)),
-            Span::styled(" quit │ ", Style::default().fg(BORDER)),
-            Span::styled("j/k", Style::default().fg(GREEN)),
-            Span::styled(" scroll │ ", Style::default().fg(BORDER)),
-            Span::styled("F6", Style::default().fg(GREEN)),
-            Span::styled(" copy", Style::default().fg(BORDER)),
-        ])
-    };
+    if app.pending_refactor.is_some() {
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(
+            "pending refactor",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
 
-    let footer = Paragraph::new(hints).alignment(Alignment::Right);
-    f.render_widget(footer, area);
+    Line::from(spans)
 }
 
-// ── Helpers ─────────────────────────────────────────────────────────────────
-
-fn format_value(v: f64) -> String {
-    if v.abs() < 0.001 && v != 0.0 {
-        format!("{:.2e}", v)
-    } else if v.abs() >= 10000.0 {
-        format!("{:.2e}", v)
-    } else {
-        format!("{:.4}", v)
+fn draw_chat_brand(f: &mut Frame, area: Rect) {
+    if area.width < 12 || area.height < 3 {
+        return;
     }
-}
 
-// ── Focused Metric Detail View ──────────────────────────────────────────────
+    let cwd = std::env::current_dir()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|_| ".".to_string());
+    let cwd = truncate_text(&cwd, area.width.saturating_sub(2) as usize);
+    let version = format!("(v{})", env!("CARGO_PKG_VERSION"));
+    let logo_width = OPENGRAPHS_LOGO_ROWS
+        .iter()
+        .map(|row| row.trim_end().chars().count())
+        .max()
+        .unwrap_or(0) as u16;
+    let logo_height = OPENGRAPHS_LOGO_ROWS.len() as u16;
 
-fn draw_focused_metric(f: &mut Frame, app: &App, metric_idx: usize, area: Rect) {
-    let tag = match app.tags.get(metric_idx) {
-        Some(t) => t.as_str(),
-        None => {
-            let p = Paragraph::new("Invalid metric index").style(Style::default().fg(TEXT_DIM));
-            f.render_widget(p, area);
-            return;
-        }
-    };
-    let display_tag = app.metric_display_name(tag);
+    if area.width < logo_width || area.height < logo_height + 2 {
+        let compact = Paragraph::new(vec![
+            compact_brand_line(
+                "opengraphs",
+                &[
+                    LOGO_GREEN_TOP,
+                    LOGO_GREEN_MID,
+                    LOGO_GREEN_BOTTOM,
+                    LOGO_GREEN_BOTTOM,
+                    LOGO_GREEN_MID,
+                    LOGO_GREEN_TOP,
+                ],
+            ),
+            Line::from(Span::styled(
+                version.clone(),
+                Style::default().fg(TEXT_LIGHT),
+            )),
+            Line::from(Span::styled(cwd, Style::default().fg(TEXT_DIM))),
+        ])
+        .alignment(Alignment::Left);
+        f.render_widget(compact, area);
+        return;
+    }
 
-    let data = match app.scalars.get(tag) {
-        Some(d) if !d.is_empty() => d,
-        _ => {
-            let block = Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(BORDER))
-                .title(Span::styled(
-                    format!(" {} ", display_tag),
-                    Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
-                ));
-            let p = Paragraph::new("No data")
-                .style(Style::default().fg(TEXT_DIM))
-                .block(block);
-            f.render_widget(p, area);
-            return;
-        }
-    };
+    let logo_area = Rect::new(area.x, area.y, area.width, logo_height);
+    let meta_area = Rect::new(
+        area.x,
+        area.y + logo_area.height,
+        area.width,
+        area.height.saturating_sub(logo_area.height),
+    );
 
-    // Compute statistics
-    let x_min = data.first().unwrap().0;
-    let x_max = data.last().unwrap().0.max(x_min + 1.0);
-    let y_min = data.iter().map(|d| d.1).fold(f64::INFINITY, f64::min);
-    let y_max = data.iter().map(|d| d.1).fold(f64::NEG_INFINITY, f64::max);
-    let y_margin = (y_max - y_min).abs() * 0.05;
-    let y_lo = y_min - y_margin;
-    let y_hi = if (y_max - y_min).abs() < 1e-12 {
-        y_max + 1.0
-    } else {
-        y_max + y_margin
-    };
-    let latest = data.last().unwrap().1;
-    let count = data.len();
+    let logo = Paragraph::new(logo_lines()).alignment(Alignment::Left);
+    f.render_widget(logo, logo_area);
 
-    // Stats line
-    let stats_text = format!(
-        "latest: {}  │  min: {}  │  max: {}  │  points: {}  │  steps: {:.0}–{:.0}",
-        format_value(latest),
-        format_value(y_min),
-        format_value(y_max),
-        count,
-        x_min,
-        x_max,
-    );
+    let meta = Paragraph::new(vec![
+        Line::from(Span::styled(version, Style::default().fg(TEXT_LIGHT))),
+        Line::from(Span::styled(cwd, Style::default().fg(TEXT_DIM))),
+    ])
+    .alignment(Alignment::Left);
+    f.render_widget(meta, meta_area);
+}
 
-    // Layout: chart body | stats line (1)
+fn draw_chat_tab(f: &mut Frame, app: &mut App, area: Rect) {
+    // Layout: banner (0-2) | messages (fill) | refactor prompt (0 or 3) | input (3) | status (1)
+    let has_banner = app.auto_mode;
+    let has_refactor = app.pending_refactor.is_some();
+    let banner_h = if has_banner { 1 } else { 0 };
+    let refactor_h: u16 = if has_refactor { 3 } else { 0 };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(5), Constraint::Length(1)])
+        .constraints([
+            Constraint::Length(banner_h),
+            Constraint::Min(5),
+            Constraint::Length(refactor_h),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
         .split(area);
 
-    // X-axis labels
-    let x_labels = vec![
-        Span::styled(format!("{:.0}", x_min), Style::default().fg(TEXT_DIM)),
-        Span::styled(
-            format!("{:.0}", (x_min + x_max) / 2.0),
-            Style::default().fg(TEXT_DIM),
-        ),
-        Span::styled(format!("{:.0}", x_max), Style::default().fg(TEXT_DIM)),
-    ];
+    // Auto-mode banner
+    if app.auto_mode {
+        let banner = Paragraph::new(Line::from(Span::styled(
+            " ⚡ Auto mode: Agent will apply fixes automatically ",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )))
+        .alignment(Alignment::Center);
+        f.render_widget(banner, chunks[0]);
+    }
+
+    // Messages area
+    draw_chat_messages(f, app, chunks[1]);
+
+    // Pending refactor approve/reject prompt
+    if has_refactor {
+        let refactor_block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(BORDER))
+            .title(Span::styled(
+                " pending refactor ",
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        let prompt = Paragraph::new(Line::from(vec![
+            Span::styled(" Press ", Style::default().fg(TEXT_LIGHT)),
+            Span::styled("y", Style::default().fg(GREEN).add_modifier(Modifier::BOLD)),
+            Span::styled(" to apply │ ", Style::default().fg(TEXT_LIGHT)),
+            Span::styled(
+                "n",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(" to reject", Style::default().fg(TEXT_LIGHT)),
+        ]))
+        .block(refactor_block);
+        f.render_widget(prompt, chunks[2]);
+    }
+
+    // Input area
+    draw_chat_input(f, app, chunks[3]);
+
+    // Status bar
+    let status = Paragraph::new(ogd_status_line(app));
+    f.render_widget(status, chunks[4]);
+}
+
+fn draw_chat_messages(f: &mut Frame, app: &mut App, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(pane_border(app, Pane::Main))
+        .title(Span::styled(
+            " agent chat ",
+            Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+        ));
+
+    if app.chat_messages.is_empty() && !app.daemon_connected {
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let help_lines = vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                "Agent chat is not connected.",
+                Style::default().fg(TEXT_DIM),
+            )),
+            Line::from(""),
+            Line::from(Span::styled(
+                "Start with built-in daemon:",
+                Style::default().fg(TEXT_DIM),
+            )),
+            Line::from(Span::styled(
+                "  ogtui --path runs/ --training-file train.py",
+                Style::default().fg(TEXT_LIGHT),
+            )),
+            Line::from(""),
+            Line::from(Span::styled(
+                "Or start daemon separately:",
+                Style::default().fg(TEXT_DIM),
+            )),
+            Line::from(Span::styled(
+                "  python3 -m og_agent_chat.server --training-file train.py",
+                Style::default().fg(TEXT_LIGHT),
+            )),
+        ];
+
+        if inner.height < 11 {
+            let p = Paragraph::new(help_lines)
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: false });
+            f.render_widget(p, inner);
+            return;
+        }
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(8)])
+            .split(inner);
+        let p = Paragraph::new(help_lines)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: false });
+        f.render_widget(p, chunks[0]);
+        draw_chat_brand(f, chunks[1]);
+        return;
+    }
+
+    if app.chat_messages.is_empty() && !app.agent_thinking {
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        let msg = Paragraph::new(Line::from(Span::styled(
+            "No messages yet. Type a message and press Enter.",
+            Style::default().fg(TEXT_DIM),
+        )))
+        .alignment(Alignment::Center);
+
+        if inner.height < 9 {
+            f.render_widget(msg, inner);
+            return;
+        }
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(8)])
+            .split(inner);
+        f.render_widget(msg, chunks[0]);
+        draw_chat_brand(f, chunks[1]);
+        return;
+    }
+
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let mut lines: Vec<Line> = Vec::new();
+    if app.chat_hidden > 0 {
+        let suffix = if app.chat_hidden == 1 { "" } else { "s" };
+        lines.push(Line::from(Span::styled(
+            format!("… {} earlier message{suffix} not shown", app.chat_hidden),
+            Style::default().fg(TEXT_DIM),
+        )));
+        lines.push(Line::from(""));
+    }
+    for msg in app.chat_messages.iter() {
+        let (prefix, color) = match msg.sender.as_str() {
+            "user" => ("You", CHAT_USER),
+            "agent" => ("Agent", CHAT_AGENT),
+            "system" => ("System", CHAT_SYSTEM),
+            other => (other, TEXT_DIM),
+        };
+
+        lines.push(Line::from(vec![Span::styled(
+            format!("[{}] ", prefix),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        )]));
+
+        if msg.sender == "agent" {
+            lines.extend(markdown_lines(&msg.content));
+        } else {
+            lines.extend(chat_content_lines(&msg.content));
+        }
+        lines.push(Line::from(""));
+    }
+
+    // Reply still streaming in: show what has arrived plus a typing cursor.
+    if app.agent_thinking && app.pending_refactor.is_none() {
+        lines.push(Line::from(vec![Span::styled(
+            "[Agent] ",
+            Style::default().fg(CHAT_AGENT).add_modifier(Modifier::BOLD),
+        )]));
+        lines.extend(markdown_lines(&app.chat_stream));
+        let dots = ".".repeat((unix_now_millis() / 400 % 3 + 1) as usize);
+        lines.push(Line::from(Span::styled(
+            format!("  ▍{dots}"),
+            Style::default().fg(TEXT_DIM),
+        )));
+    }
+
+    // Scroll limits count rows after wrapping, so long replies still land
+    // with their last line at the bottom of the pane.
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false });
+    let max_scroll = paragraph
+        .line_count(inner.width)
+        .saturating_sub(inner.height as usize)
+        .min(u16::MAX as usize) as u16;
+    app.set_chat_max_scroll(max_scroll);
+    f.render_widget(paragraph.scroll((app.chat_scroll, 0)), inner);
+}
+
+fn diff_line_style(l: &str) -> Style {
+    if l.starts_with('+') && !l.starts_with("+++") {
+        Style::default().fg(GREEN)
+    } else if l.starts_with('-') && !l.starts_with("---") {
+        Style::default().fg(Color::Red)
+    } else if l.starts_with("@@") {
+        Style::default().fg(Color::Cyan)
+    } else if l.starts_with("---") || l.starts_with("+++") {
+        Style::default().fg(TEXT_DIM).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(TEXT_LIGHT)
+    }
+}
+
+fn chat_content_lines(content: &str) -> Vec<Line<'static>> {
+    content
+        .lines()
+        .map(|l| Line::from(Span::styled(format!("  {}", l), diff_line_style(l))))
+        .collect()
+}
+
+/// Whether a Markdown emphasis marker at `idx` can open (`opening`) or close
+/// a span. `_` must sit on a word boundary so `train_loss` stays literal, and
+/// `*` must hug the text so `a * b` does too.
+fn emphasis_flank(chars: &[char], idx: usize, width: usize, opening: bool) -> bool {
+    let before = idx.checked_sub(1).map(|i| chars[i]);
+    let after = chars.get(idx + width).copied();
+    let (inner, outer) = if opening {
+        (after, before)
+    } else {
+        (before, after)
+    };
+    inner.is_some_and(|c| !c.is_whitespace() && c != chars[idx])
+        && (chars[idx] != '_' || !outer.is_some_and(|c| c.is_alphanumeric() || c == '_'))
+}
+
+/// Inline Markdown: `code`, **bold**, *italic* / _italic_. Markers without a
+/// matching close are kept as text.
+fn inline_markdown_spans(text: &str, base: Style) -> Vec<Span<'static>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut spans = Vec::new();
+    let mut buf = String::new();
+    let (mut bold, mut italic) = (false, false);
+    let style = |bold: bool, italic: bool| {
+        let mut style = base;
+        if bold {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        if italic {
+            style = style.add_modifier(Modifier::ITALIC);
+        }
+        style
+    };
+    let closes_later = |from: usize, marker: &[char]| {
+        (from..chars.len()).any(|j| {
+            chars[j..].starts_with(marker) && emphasis_flank(&chars, j, marker.len(), false)
+        })
+    };
+    let mut idx = 0;
+    while idx < chars.len() {
+        let ch = chars[idx];
+        if ch == '`'
+            && let Some(len) = chars[idx + 1..].iter().position(|c| *c == '`')
+        {
+            if !buf.is_empty() {
+                spans.push(Span::styled(std::mem::take(&mut buf), style(bold, italic)));
+            }
+            let code: String = chars[idx + 1..idx + 1 + len].iter().collect();
+            spans.push(Span::styled(
+                code,
+                Style::default().fg(CHAT_INLINE_CODE).bg(CHAT_CODE_BG),
+            ));
+            idx += len + 2;
+            continue;
+        }
+        let bold_marker = ch == '*' && chars.get(idx + 1) == Some(&'*');
+        let width = if bold_marker { 2 } else { 1 };
+        let active = if bold_marker { bold } else { italic };
+        let toggles = (ch == '*' || ch == '_')
+            && if active {
+                emphasis_flank(&chars, idx, width, false)
+            } else {
+                emphasis_flank(&chars, idx, width, true)
+                    && closes_later(idx + width + 1, &chars[idx..idx + width])
+            };
+        if toggles {
+            if !buf.is_empty() {
+                spans.push(Span::styled(std::mem::take(&mut buf), style(bold, italic)));
+            }
+            if bold_marker {
+                bold = !bold;
+            } else {
+                italic = !italic;
+            }
+            idx += width;
+            continue;
+        }
+        buf.push(ch);
+        idx += 1;
+    }
+    if !buf.is_empty() {
+        spans.push(Span::styled(buf, style(bold, italic)));
+    }
+    spans
+}
+
+/// Render an agent message as basic Markdown: headings, bullet and numbered
+/// lists, fenced code blocks on a shaded background, and inline emphasis.
+/// A bare unified diff (the agent's refactor proposals) keeps diff colors.
+fn markdown_lines(content: &str) -> Vec<Line<'static>> {
+    let base = Style::default().fg(TEXT_LIGHT);
+    let raw: Vec<&str> = content.lines().collect();
+    let mut lines = Vec::new();
+    let mut fence: Option<String> = None;
+    let mut in_diff = false;
+    for (idx, l) in raw.iter().enumerate() {
+        let trimmed = l.trim_start();
+        let indent = " ".repeat(l.len() - trimmed.len());
+        if let Some(lang) = trimmed.strip_prefix("```") {
+            fence = match fence {
+                Some(_) => None,
+                None => Some(lang.trim().to_ascii_lowercase()),
+            };
+            continue;
+        }
+        if let Some(lang) = &fence {
+            let style = if lang == "diff" || lang == "patch" {
+                diff_line_style(l)
+            } else {
+                base
+            };
+            lines.push(Line::from(Span::styled(
+                format!("  {l}"),
+                style.bg(CHAT_CODE_BG),
+            )));
+            continue;
+        }
+        in_diff = in_diff
+            || l.starts_with("diff --git ")
+            || (l.starts_with("--- ") && raw.get(idx + 1).is_some_and(|n| n.starts_with("+++ ")));
+        if in_diff {
+            lines.push(Line::from(Span::styled(
+                format!("  {l}"),
+                diff_line_style(l),
+            )));
+            continue;
+        }
+
+        let hashes = trimmed.chars().take_while(|c| *c == '#').count();
+        if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+            let color = if hashes <= 2 { CHAT_AGENT } else { TEXT_LIGHT };
+            let style = Style::default().fg(color).add_modifier(Modifier::BOLD);
+            let mut spans = vec![Span::raw("  ")];
+            spans.extend(inline_markdown_spans(trimmed[hashes..].trim(), style));
+            lines.push(Line::from(spans));
+            continue;
+        }
+        if matches!(trimmed.trim_end(), "---" | "***" | "___") {
+            lines.push(Line::from(Span::styled(
+                format!("  {}", "─".repeat(24)),
+                Style::default().fg(TEXT_DIM),
+            )));
+            continue;
+        }
+        let bullet = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|marker| trimmed.strip_prefix(marker));
+        let numbered = trimmed.split_once(". ").filter(|(n, _)| {
+            !n.is_empty() && n.len() <= 3 && n.chars().all(|c| c.is_ascii_digit())
+        });
+        let mut spans = vec![Span::raw(format!("  {indent}"))];
+        let text = if let Some(item) = bullet {
+            spans.push(Span::styled("• ", Style::default().fg(CHAT_AGENT)));
+            item
+        } else if let Some((number, item)) = numbered {
+            spans.push(Span::styled(
+                format!("{number}. "),
+                Style::default().fg(CHAT_AGENT),
+            ));
+            item
+        } else {
+            trimmed
+        };
+        spans.extend(inline_markdown_spans(text, base));
+        lines.push(Line::from(spans));
+    }
+    lines
+}
+
+fn draw_chat_input(f: &mut Frame, app: &App, area: Rect) {
+    let title_color = if app.chat_input_focused {
+        GREEN
+    } else {
+        BORDER
+    };
+    let title = if app.chat_input_focused {
+        " type message or !og command (Enter=send, Esc=unfocus) "
+    } else {
+        " (i) insert "
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(pane_border(app, Pane::Input))
+        .title(Span::styled(title, Style::default().fg(title_color)))
+        .title_bottom(opengraphs_inline_brand());
+
+    let display_text = if app.chat_input.is_empty() && !app.chat_input_focused {
+        " Type message or !og run demo_train.py ...".to_string()
+    } else if app.chat_input_focused {
+        format!("{}█", app.chat_input)
+    } else {
+        app.chat_input.clone()
+    };
+
+    let style = if app.chat_input.is_empty() && !app.chat_input_focused {
+        Style::default().fg(TEXT_DIM).add_modifier(Modifier::DIM)
+    } else {
+        Style::default().fg(TEXT_LIGHT)
+    };
+
+    let input = Paragraph::new(Line::from(Span::styled(display_text, style)))
+        .block(block)
+        .wrap(Wrap { trim: false });
+    f.render_widget(input, area);
+}
+
+// ── Help Modal ──────────────────────────────────────────────────────────────
+
+fn draw_help_modal(f: &mut Frame, area: Rect) {
+    let shortcuts = vec![
+        ("Tab / Shift+Tab", "Next / previous pane, then tab"),
+        ("q", "Quit"),
+        ("?", "Toggle this help"),
+        ("F6", "Toggle copy mode (highlight/copy text with mouse)"),
+        ("Esc", "Close help / exit detail"),
+        ("j / ↓", "Scroll logs/events/procs/chat down"),
+        ("k / ↑", "Scroll logs/events/procs/chat up"),
+        ("l / →", "Next metric"),
+        ("h / ←", "Previous metric"),
+        ("Enter / Click", "Enlarge metric"),
+        ("[ / ] (metric)", "Step through checkpoint / eval markers"),
+        (
+            "o + - < > (:diff)",
+            "Layout, zoom and pan when comparing runs",
+        ),
+        ("i", "Focus chat input"),
+        ("Enter (chat)", "Send message"),
+        ("!og ...", "Run CLI commands in chat"),
+        (
+            ":",
+            "Command line (:tab, :focus, :filter, :export csv, :diff, :q, og commands)",
+        ),
+        ("Esc (chat)", "Unfocus chat input"),
+        ("y (chat)", "Apply pending refactor"),
+        ("n (chat)", "Reject pending refactor"),
+        ("N", "Add a note to the run journal"),
+        ("r", "Cycle rank view (distributed runs)"),
+        ("g", "Cycle graph presets"),
+        ("K", "Browse checkpoints / restart from one"),
+        ("L", "Learning-rate schedule and warnings"),
+        ("T", "Table summaries (confusion matrices)"),
+        ("P", "Switch project"),
+        ("b (multi-run project)", "Seed bands: min–max / p25–p75"),
+        ("c / C (graphs)", "Copy metric stats / series tail as CSV"),
+        ("v (logs)", "Select log lines (j/k extend, y yank)"),
+        ("y (logs)", "Copy selected or visible log lines"),
+        ("f (logs)", "Filter by level: all, warn+, error"),
+        (
+            "f (processes)",
+            "Training tree and GPU users / all processes",
+        ),
+        ("t (events)", "Filter events by type"),
+        ("h / l (dists)", "Previous / next histogram tag"),
+        ("j / k (dists)", "Move the step slider"),
+    ];
+
+    let w = (area.width * 60 / 100).min(60);
+    let h = (area.height * 80 / 100).min(shortcuts.len() as u16 + 2);
+    let x = (area.width.saturating_sub(w)) / 2;
+    let y = (area.height.saturating_sub(h)) / 2;
+    let modal_area = Rect::new(x, y, w, h);
+
+    f.render_widget(Clear, modal_area);
+
+    let lines: Vec<Line> = shortcuts
+        .iter()
+        .map(|(key, desc)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:<20}", key),
+                    Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(*desc, Style::default().fg(TEXT_LIGHT)),
+            ])
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(BORDER))
+        .title(Span::styled(
+            " shortcuts ",
+            Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+        ))
+        .style(Style::default().bg(BG_DARK));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, modal_area);
+}
+
+// ── Note Input ──────────────────────────────────────────────────────────────
+
+fn draw_note_input(f: &mut Frame, input: &str, area: Rect) {
+    let w = (area.width * 70 / 100).clamp(20, 80).min(area.width);
+    let h: u16 = 3;
+    let x = (area.width.saturating_sub(w)) / 2;
+    let y = area.height.saturating_sub(h + 2);
+    let modal_area = Rect::new(x, y, w, h.min(area.height));
+
+    f.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(GREEN))
+        .title(Span::styled(
+            " run note (Enter=save, Esc=cancel) ",
+            Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+        ))
+        .style(Style::default().bg(BG_DARK));
+
+    let input = Paragraph::new(Line::from(Span::styled(
+        format!("{}█", input),
+        Style::default().fg(TEXT_LIGHT),
+    )))
+    .block(block);
+    f.render_widget(input, modal_area);
+}
+
+// ── Checkpoints Panel ───────────────────────────────────────────────────────
+
+fn draw_checkpoints_panel(f: &mut Frame, app: &App, selected: usize, area: Rect) {
+    let w = (area.width * 80 / 100).clamp(40, 110).min(area.width);
+    let h = (area.height * 70 / 100)
+        .min(app.checkpoints.len() as u16 + 4)
+        .max(5);
+    let x = (area.width.saturating_sub(w)) / 2;
+    let y = (area.height.saturating_sub(h)) / 2;
+    let modal_area = Rect::new(x, y, w, h);
+
+    f.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(GREEN))
+        .title(Span::styled(
+            " checkpoints (Enter=restart from, Esc=close) ",
+            Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+        ))
+        .style(Style::default().bg(BG_DARK));
+    let inner = block.inner(modal_area);
+    f.render_widget(block, modal_area);
+
+    if app.checkpoints.is_empty() {
+        let empty = Paragraph::new(vec![
+            Line::from(Span::styled(
+                "No checkpoints recorded for this run",
+                Style::default().fg(TEXT_DIM),
+            )),
+            Line::from(Span::styled(
+                "record them with og_agent_chat.checkpoints.record_checkpoint",
+                Style::default().fg(TEXT_DIM),
+            )),
+        ]);
+        f.render_widget(empty, inner);
+        return;
+    }
+
+    const STEP_W: usize = 9;
+    const SIZE_W: usize = 10;
+    const AGE_W: usize = 8;
+    let metrics_w = (inner.width as usize)
+        .saturating_sub(STEP_W + SIZE_W + AGE_W + 3)
+        .max(10);
+    let mut lines = vec![Line::from(Span::styled(
+        format!(
+            "{:>STEP_W$} {:<metrics_w$} {:>SIZE_W$} {:>AGE_W$}",
+            "step", "metrics at save", "size", "saved"
+        ),
+        Style::default().fg(TEXT_LIGHT).add_modifier(Modifier::BOLD),
+    ))];
+
+    let visible = (inner.height as usize).saturating_sub(1).max(1);
+    let skip = (selected + 1).saturating_sub(visible);
+    let now = unix_now_secs();
+    for (idx, checkpoint) in app.checkpoints.iter().enumerate().skip(skip).take(visible) {
+        let metrics = checkpoint
+            .metrics
+            .iter()
+            .map(|(name, value)| {
+                format!(
+                    "{}={}",
+                    app.metric_display_name(name),
+                    format::value(*value)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let size = checkpoint
+            .size_bytes
+            .map(format::bytes)
+            .unwrap_or_else(|| "missing".to_string());
+        let age = checkpoint
+            .wall_time
+            .map(|t| format::ago(now.saturating_sub(t as u64)))
+            .unwrap_or_else(|| "-".to_string());
+        let mut style = if checkpoint.size_bytes.is_some() {
+            Style::default().fg(TEXT_LIGHT)
+        } else {
+            Style::default().fg(TEXT_DIM)
+        };
+        if idx == selected {
+            style = style.bg(LOG_SELECTION_BG).add_modifier(Modifier::BOLD);
+        }
+        lines.push(Line::from(Span::styled(
+            format!(
+                "{:>STEP_W$} {:<metrics_w$} {:>SIZE_W$} {:>AGE_W$}",
+                checkpoint.step,
+                truncate_text(&metrics, metrics_w),
+                size,
+                age
+            ),
+            style,
+        )));
+    }
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+// ── Projects Panel ──────────────────────────────────────────────────────────
+
+fn draw_projects_panel(f: &mut Frame, app: &App, selected: usize, area: Rect) {
+    let w = (area.width * 70 / 100).clamp(40, 100).min(area.width);
+    let h = (area.height * 70 / 100)
+        .min(app.projects.len() as u16 + 4)
+        .max(5);
+    let x = (area.width.saturating_sub(w)) / 2;
+    let y = (area.height.saturating_sub(h)) / 2;
+    let modal_area = Rect::new(x, y, w, h);
+
+    f.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(GREEN))
+        .title(Span::styled(
+            " projects (Enter=switch, Esc=close) ",
+            Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+        ))
+        .style(Style::default().bg(BG_DARK));
+    let inner = block.inner(modal_area);
+    f.render_widget(block, modal_area);
+
+    if app.projects.is_empty() {
+        let empty = Paragraph::new(Line::from(Span::styled(
+            "No projects next to this one",
+            Style::default().fg(TEXT_DIM),
+        )));
+        f.render_widget(empty, inner);
+        return;
+    }
+
+    const NAME_W: usize = 24;
+    const RUNS_W: usize = 6;
+    let path_w = (inner.width as usize)
+        .saturating_sub(NAME_W + RUNS_W + 2)
+        .max(10);
+    let mut lines = vec![Line::from(Span::styled(
+        format!(
+            "{:<NAME_W$} {:>RUNS_W$} {:<path_w$}",
+            "name", "runs", "path"
+        ),
+        Style::default().fg(TEXT_LIGHT).add_modifier(Modifier::BOLD),
+    ))];
+
+    let visible = (inner.height as usize).saturating_sub(1).max(1);
+    let skip = (selected + 1).saturating_sub(visible);
+    for (idx, project) in app.projects.iter().enumerate().skip(skip).take(visible) {
+        let current = Path::new(&project.path) == app.dashboard_root;
+        let mut style = if current {
+            Style::default().fg(GREEN)
+        } else {
+            Style::default().fg(TEXT_LIGHT)
+        };
+        if idx == selected {
+            style = style.bg(LOG_SELECTION_BG).add_modifier(Modifier::BOLD);
+        }
+        lines.push(Line::from(Span::styled(
+            format!(
+                "{:<NAME_W$} {:>RUNS_W$} {:<path_w$}",
+                truncate_text(&project.name, NAME_W),
+                project.run_count,
+                truncate_text(&project.path, path_w)
+            ),
+            style,
+        )));
+    }
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+// ── Learning-rate panel ─────────────────────────────────────────────────────
+
+/// Cell background between `BG_DARK` (lowest) and `GREEN` (highest).
+fn heat_color(share: f64) -> Color {
+    let lerp = |from: u8, to: u8| (from as f64 + (to as f64 - from as f64) * share).round() as u8;
+    match (BG_DARK, GREEN) {
+        (Color::Rgb(r0, g0, b0), Color::Rgb(r1, g1, b1)) => {
+            Color::Rgb(lerp(r0, r1), lerp(g0, g1), lerp(b0, b1))
+        }
+        _ => GREEN,
+    }
+}
+
+fn draw_table_panel(f: &mut Frame, app: &App, selected: usize, area: Rect) {
+    let w = (area.width * 80 / 100).clamp(40, 110).min(area.width);
+    let h = (area.height * 70 / 100).max(8).min(area.height);
+    let x = (area.width.saturating_sub(w)) / 2;
+    let y = (area.height.saturating_sub(h)) / 2;
+    let modal_area = Rect::new(x, y, w, h);
+
+    f.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(GREEN))
+        .title(Span::styled(
+            " tables (h/l=switch, Esc=close) ",
+            Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+        ))
+        .style(Style::default().bg(BG_DARK));
+    let inner = block.inner(modal_area);
+    f.render_widget(block, modal_area);
+
+    let Some(table) = app.tables.get(selected) else {
+        let empty = Paragraph::new(vec![
+            Line::from(Span::styled(
+                "No table summaries logged yet",
+                Style::default().fg(TEXT_DIM),
+            )),
+            Line::from(Span::styled(
+                "log a 2-D tensor, or an event with a rows matrix",
+                Style::default().fg(TEXT_DIM),
+            )),
+        ]);
+        f.render_widget(empty, inner);
+        return;
+    };
+
+    const CELL_W: usize = 8;
+    let (lo, hi) = table.range().unwrap_or((0.0, 0.0));
+    let label_w = table
+        .row_labels
+        .iter()
+        .map(|label| label.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(16);
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(
+                format!("{} ", table.name),
+                Style::default().fg(TEXT_LIGHT).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(
+                    "@ step {}  │  {}/{}  │  min {}  max {}",
+                    table.step,
+                    selected + 1,
+                    app.tables.len(),
+                    table::format_cell(lo),
+                    table::format_cell(hi)
+                ),
+                Style::default().fg(TEXT_DIM),
+            ),
+        ]),
+        Line::default(),
+    ];
+    let mut header = vec![Span::raw(" ".repeat(label_w + 1))];
+    for label in &table.col_labels {
+        header.push(Span::styled(
+            format!("{:>CELL_W$}", truncate_text(label, CELL_W - 1)),
+            Style::default().fg(TEXT_LIGHT).add_modifier(Modifier::BOLD),
+        ));
+    }
+    lines.push(Line::from(header));
+    for (label, row) in table.row_labels.iter().zip(&table.rows) {
+        let mut spans = vec![Span::styled(
+            format!("{:<label_w$} ", truncate_text(label, label_w)),
+            Style::default().fg(TEXT_LIGHT).add_modifier(Modifier::BOLD),
+        )];
+        for value in row {
+            let share = if hi > lo && value.is_finite() {
+                (value - lo) / (hi - lo)
+            } else {
+                0.0
+            };
+            let fg = if share > 0.6 { BG_DARK } else { TEXT_LIGHT };
+            spans.push(Span::styled(
+                format!(
+                    "{:>CELL_W$}",
+                    truncate_text(&table::format_cell(*value), CELL_W - 1)
+                ),
+                Style::default().fg(fg).bg(heat_color(share)),
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+fn draw_lr_panel(f: &mut Frame, app: &App, area: Rect) {
+    let w = (area.width * 80 / 100).clamp(40, 110).min(area.width);
+    let h = (area.height * 70 / 100).max(8).min(area.height);
+    let x = (area.width.saturating_sub(w)) / 2;
+    let y = (area.height.saturating_sub(h)) / 2;
+    let modal_area = Rect::new(x, y, w, h);
+
+    f.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(GREEN))
+        .title(Span::styled(
+            " learning rate (Esc=close) ",
+            Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+        ))
+        .style(Style::default().bg(BG_DARK));
+    let inner = block.inner(modal_area);
+    f.render_widget(block, modal_area);
+
+    let Some(report) = app.lr_report() else {
+        let empty = Paragraph::new(vec![
+            Line::from(Span::styled(
+                "No learning-rate metric logged yet",
+                Style::default().fg(TEXT_DIM),
+            )),
+            Line::from(Span::styled(
+                "log it under a tag named lr or learning_rate (e.g. train/lr)",
+                Style::default().fg(TEXT_DIM),
+            )),
+        ]);
+        f.render_widget(empty, inner);
+        return;
+    };
+
+    let mut lines = vec![Line::from(vec![
+        Span::styled(
+            format!("{} ", app.metric_display_name(&report.tag)),
+            Style::default().fg(TEXT_LIGHT).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(report.shape.label(), Style::default().fg(GREEN)),
+        Span::styled(
+            format!(
+                "  │  {}  │  peak {} at step {:.0}  │  now {}",
+                match report.warmup_end {
+                    Some(step) => format!("warmup until step {step:.0}"),
+                    None => "no warmup".to_string(),
+                },
+                format::value(report.peak),
+                report.peak_step,
+                format::value(report.last)
+            ),
+            Style::default().fg(TEXT_DIM),
+        ),
+    ])];
+    if report.warnings.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "no schedule problems found",
+            Style::default().fg(TEXT_DIM),
+        )));
+    }
+    for warning in &report.warnings {
+        lines.push(Line::from(vec![
+            Span::styled("⚠ ", Style::default().fg(MARKER_LR)),
+            Span::styled(warning.message.clone(), Style::default().fg(TEXT_LIGHT)),
+        ]));
+    }
+    let lr_changes: Vec<&Annotation> = app
+        .annotations
+        .iter()
+        .filter(|a| a.kind == AnnotationKind::LrChange)
+        .collect();
+    for annotation in &lr_changes {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("⚑ {} ", annotation.step),
+                Style::default().fg(LOG_IMPORTANT),
+            ),
+            Span::styled(annotation.message.clone(), Style::default().fg(TEXT_LIGHT)),
+        ]));
+    }
+
+    let text_h = (lines.len() as u16).min(inner.height.saturating_sub(4));
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(4), Constraint::Length(text_h)])
+        .split(inner);
+
+    let series: Vec<(f64, f64)> = app.scalars[&report.tag]
+        .iter()
+        .copied()
+        .filter(|(_, v)| v.is_finite())
+        .collect();
+    let x_min = series.first().map(|(s, _)| *s).unwrap_or(0.0);
+    let x_max = series
+        .last()
+        .map(|(s, _)| *s)
+        .unwrap_or(1.0)
+        .max(x_min + 1.0);
+    let (lo, hi) = series
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), (_, v)| {
+            (lo.min(*v), hi.max(*v))
+        });
+    let pad = ((hi - lo) * 0.05).max(hi.abs() * 0.05).max(1e-12);
+    let (y_lo, y_hi) = (lo - pad, hi + pad);
+
+    // Warmup end and LR changes as vertical lines, warnings as dots on top.
+    let mut verticals: Vec<(Color, [(f64, f64); 2])> = lr_changes
+        .iter()
+        .map(|a| {
+            (
+                LOG_IMPORTANT,
+                [(a.step as f64, y_lo), (a.step as f64, y_hi)],
+            )
+        })
+        .collect();
+    if let Some(step) = report.warmup_end {
+        verticals.push((TEXT_DIM, [(step, y_lo), (step, y_hi)]));
+    }
+    let warning_points: Vec<(f64, f64)> = report
+        .warnings
+        .iter()
+        .filter_map(|w| w.step)
+        .map(|step| (step, y_hi))
+        .collect();
+
+    let mut datasets = vec![
+        Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(run_chart_color(app)))
+            .data(&series),
+    ];
+    for (color, line) in &verticals {
+        datasets.push(
+            Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(*color))
+                .data(line),
+        );
+    }
+    if !warning_points.is_empty() {
+        datasets.push(
+            Dataset::default()
+                .marker(symbols::Marker::Dot)
+                .graph_type(GraphType::Scatter)
+                .style(Style::default().fg(MARKER_LR))
+                .data(&warning_points),
+        );
+    }
+    let chart = Chart::new(datasets)
+        .x_axis(
+            Axis::default()
+                .bounds([x_min, x_max])
+                .labels(vec![
+                    Span::styled(format!("{x_min:.0}"), Style::default().fg(TEXT_DIM)),
+                    Span::styled(format!("{x_max:.0}"), Style::default().fg(TEXT_DIM)),
+                ])
+                .style(Style::default().fg(BORDER)),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([y_lo, y_hi])
+                .labels(vec![
+                    Span::styled(format::value(lo), Style::default().fg(TEXT_DIM)),
+                    Span::styled(format::value(hi), Style::default().fg(TEXT_DIM)),
+                ])
+                .style(Style::default().fg(BORDER)),
+        );
+    f.render_widget(chart, chunks[0]);
+    f.render_widget(Paragraph::new(lines), chunks[1]);
+}
+
+// ── Toasts ──────────────────────────────────────────────────────────────────
+
+/// Newest toast first, stacked down the right edge below the header.
+fn draw_toasts(f: &mut Frame, toasts: &[Toast], area: Rect) {
+    let mut y = area.y + 3;
+    for toast in toasts.iter().rev() {
+        let (title, color) = match toast.level {
+            ToastLevel::Info => (" info ", CHAT_AGENT),
+            ToastLevel::Success => (" done ", GREEN),
+            ToastLevel::Alert => (" alert ", LOG_IMPORTANT),
+            ToastLevel::Thermal => (" thermal ", LOG_ERROR),
+        };
+        let w = (toast.message.chars().count() as u16 + 4)
+            .clamp(20, 50)
+            .min(area.width);
+        let h: u16 = 3;
+        if y + h > area.y + area.height {
+            break;
+        }
+        let rect = Rect::new(area.x + area.width - w, y, w, h);
+        f.render_widget(Clear, rect);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(color))
+            .title(Span::styled(
+                title,
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ))
+            .style(Style::default().bg(BG_DARK));
+        let text = truncate_text(&toast.message, w.saturating_sub(2) as usize);
+        f.render_widget(
+            Paragraph::new(Span::styled(text, Style::default().fg(TEXT_LIGHT))).block(block),
+            rect,
+        );
+        y += h;
+    }
+}
+
+// ── Footer ──────────────────────────────────────────────────────────────────
+
+fn draw_footer(f: &mut Frame, _app: &App, area: Rect) {
+    if _app.copy_mode {
+        let banner = Line::from(vec![
+            Span::styled(
+                "COPY MODE",
+                Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                " │ Drag mouse to highlight/copy text │ F6 resume interactive mode",
+                Style::default().fg(TEXT_LIGHT),
+            ),
+        ]);
+        let footer = Paragraph::new(banner).alignment(Alignment::Left);
+        f.render_widget(footer, area);
+        return;
+    }
+
+    if _app.quit_pending {
+        let prompt = Line::from(vec![
+            Span::styled(
+                "QUIT?",
+                Style::default()
+                    .fg(LOG_IMPORTANT)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                " Training stops with the TUI (start with --detach to keep it running) │ ",
+                Style::default().fg(TEXT_LIGHT),
+            ),
+            Span::styled("q/y", Style::default().fg(GREEN)),
+            Span::styled(" quit │ ", Style::default().fg(BORDER)),
+            Span::styled("any key", Style::default().fg(GREEN)),
+            Span::styled(" stay", Style::default().fg(BORDER)),
+        ]);
+        f.render_widget(Paragraph::new(prompt), area);
+        return;
+    }
+
+    if let Some(input) = _app.command_input.as_deref() {
+        let prompt = Line::from(vec![
+            Span::styled(":", Style::default().fg(GREEN).add_modifier(Modifier::BOLD)),
+            Span::styled(format!("{input}█"), Style::default().fg(TEXT_LIGHT)),
+        ]);
+        f.render_widget(Paragraph::new(prompt), area);
+        return;
+    }
+
+    let hints = if _app.active_tab == Tab::Logs && _app.log_selection.is_some() {
+        Line::from(vec![
+            Span::styled(
+                "VISUAL",
+                Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(" │ ", Style::default().fg(BORDER)),
+            Span::styled("j/k", Style::default().fg(GREEN)),
+            Span::styled(" extend │ ", Style::default().fg(BORDER)),
+            Span::styled("y", Style::default().fg(GREEN)),
+            Span::styled(" yank │ ", Style::default().fg(BORDER)),
+            Span::styled("Esc", Style::default().fg(GREEN)),
+            Span::styled(" cancel", Style::default().fg(BORDER)),
+        ])
+    } else if _app.active_tab == Tab::Chat {
+        Line::from(vec![
+            Span::styled("Tab", Style::default().fg(GREEN)),
+            Span::styled(" switch │ ", Style::default().fg(BORDER)),
+            Span::styled("j/k", Style::default().fg(GREEN)),
+            Span::styled(" scroll │ ", Style::default().fg(BORDER)),
+            Span::styled("F6", Style::default().fg(GREEN)),
+            Span::styled(" copy │ ", Style::default().fg(BORDER)),
+            Span::styled("?", Style::default().fg(GREEN)),
+            Span::styled(" help │ ", Style::default().fg(BORDER)),
+            Span::styled("q", Style::default().fg(GREEN)),
+            Span::styled(" quit", Style::default().fg(BORDER)),
+        ])
+    } else if _app.active_tab == Tab::Events {
+        Line::from(vec![
+            Span::styled("Tab", Style::default().fg(GREEN)),
+            Span::styled(" switch │ ", Style::default().fg(BORDER)),
+            Span::styled("t", Style::default().fg(GREEN)),
+            Span::styled(" filter type │ ", Style::default().fg(BORDER)),
+            Span::styled("j/k", Style::default().fg(GREEN)),
+            Span::styled(" scroll │ ", Style::default().fg(BORDER)),
+            Span::styled("?", Style::default().fg(GREEN)),
+            Span::styled(" help │ ", Style::default().fg(BORDER)),
+            Span::styled("q", Style::default().fg(GREEN)),
+            Span::styled(" quit", Style::default().fg(BORDER)),
+        ])
+    } else if _app.active_tab == Tab::Distributions {
+        Line::from(vec![
+            Span::styled("Tab", Style::default().fg(GREEN)),
+            Span::styled(" switch │ ", Style::default().fg(BORDER)),
+            Span::styled("h/l", Style::default().fg(GREEN)),
+            Span::styled(" tag │ ", Style::default().fg(BORDER)),
+            Span::styled("j/k", Style::default().fg(GREEN)),
+            Span::styled(" step │ ", Style::default().fg(BORDER)),
+            Span::styled("?", Style::default().fg(GREEN)),
+            Span::styled(" help │ ", Style::default().fg(BORDER)),
+            Span::styled("q", Style::default().fg(GREEN)),
+            Span::styled(" quit", Style::default().fg(BORDER)),
+        ])
+    } else if _app.active_tab == Tab::Graphs {
+        Line::from(vec![
+            Span::styled("Tab", Style::default().fg(GREEN)),
+            Span::styled(" switch │ ", Style::default().fg(BORDER)),
+            Span::styled("?", Style::default().fg(GREEN)),
+            Span::styled(" help │ ", Style::default().fg(BORDER)),
+            Span::styled("q", Style::default().fg(GREEN)),
+            Span::styled(" quit │ ", Style::default().fg(BORDER)),
+            Span::styled("h/l", Style::default().fg(GREEN)),
+            Span::styled(" metrics │ ", Style::default().fg(BORDER)),
+            Span::styled("j/k", Style::default().fg(GREEN)),
+            Span::styled(" scroll │ ", Style::default().fg(BORDER)),
+            Span::styled("F6", Style::default().fg(GREEN)),
+            Span::styled(" copy", Style::default().fg(BORDER)),
+        ])
+    } else {
+        Line::from(vec![
+            Span::styled("Tab", Style::default().fg(GREEN)),
+            Span::styled(" switch │ ", Style::default().fg(BORDER)),
+            Span::styled("?", Style::default().fg(GREEN)),
+            Span::styled(" help │ ", Style::default().fg(BORDER)),
+            Span::styled("q", Style::default().fg(GREEN)),
+            Span::styled(" quit │ ", Style::default().fg(BORDER)),
+            Span::styled("j/k", Style::default().fg(GREEN)),
+            Span::styled(" scroll │ ", Style::default().fg(BORDER)),
+            Span::styled("F6", Style::default().fg(GREEN)),
+            Span::styled(" copy", Style::default().fg(BORDER)),
+        ])
+    };
+
+    let footer = Paragraph::new(hints).alignment(Alignment::Right);
+    f.render_widget(footer, area);
+}
+
+// ── Helpers ─────────────────────────────────────────────────────────────────
+
+// ── Focused Metric Detail View ──────────────────────────────────────────────
+
+fn draw_focused_metric(f: &mut Frame, app: &App, metric_idx: usize, area: Rect) {
+    let tag = match app.tags.get(metric_idx) {
+        Some(t) => t.as_str(),
+        None => {
+            let p = Paragraph::new("Invalid metric index").style(Style::default().fg(TEXT_DIM));
+            f.render_widget(p, area);
+            return;
+        }
+    };
+    let display_tag = app.metric_display_name(tag);
+
+    let data = match app.scalars.get(tag) {
+        Some(d) if !d.is_empty() => d,
+        _ => {
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(BORDER))
+                .title(Span::styled(
+                    format!(" {} ", display_tag),
+                    Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+                ));
+            let p = Paragraph::new("No data")
+                .style(Style::default().fg(TEXT_DIM))
+                .block(block);
+            f.render_widget(p, area);
+            return;
+        }
+    };
+
+    // Compute statistics
+    let x_min = data.first().unwrap().0;
+    let x_max = data.last().unwrap().0.max(x_min + 1.0);
+    let y_min = data.iter().map(|d| d.1).fold(f64::INFINITY, f64::min);
+    let y_max = data.iter().map(|d| d.1).fold(f64::NEG_INFINITY, f64::max);
+    let latest = data.last().unwrap().1;
+    let count = data.len();
+
+    // A log-scale metric is charted as log10 of its values, as long as they
+    // are all positive; the axis labels undo it.
+    let log_scale = app.metric_scale(tag) == Scale::Log && data.iter().all(|(_, v)| *v > 0.0);
+    let plot_y = |v: f64| if log_scale { v.log10() } else { v };
+    let log_data: Vec<(f64, f64)>;
+    let plotted: &[(f64, f64)] = if log_scale {
+        log_data = data.iter().map(|(x, y)| (*x, y.log10())).collect();
+        &log_data
+    } else {
+        data
+    };
+    let (plot_min, plot_max) = (plot_y(y_min), plot_y(y_max));
+    let y_margin = (plot_max - plot_min).abs() * 0.05;
+    let y_lo = plot_min - y_margin;
+    let y_hi = if (plot_max - plot_min).abs() < 1e-12 {
+        plot_max + 1.0
+    } else {
+        plot_max + y_margin
+    };
+
+    // Stats line
+    let mut stats_text = format!(
+        "latest: {}  │  min: {}  │  max: {}  │  points: {}  │  steps: {:.0}–{:.0}",
+        format::value(latest),
+        format::value(y_min),
+        format::value(y_max),
+        count,
+        x_min,
+        x_max,
+    );
+    if let Some(scale) = app.x_epochs() {
+        stats_text.push_str(&format!(
+            "  │  epochs: {}–{}",
+            format::epoch(scale.epoch_at(x_min)),
+            format::epoch(scale.epoch_at(x_max))
+        ));
+    }
+    if let Some((arrow, step, best)) = best_value(data, app.higher_is_better(tag)) {
+        stats_text.push_str(&format!(
+            "  │  {arrow} best: {} @ {step:.0}",
+            format::value(best)
+        ));
+    }
+    if let Some(found) = app.anomalies.get(tag).filter(|found| !found.is_empty()) {
+        let suffix = if found.len() == 1 { "y" } else { "ies" };
+        stats_text.push_str(&format!("  │  {} anomal{suffix}", found.len()));
+    }
+
+    // A leak-like memory trend is extended to where it runs out.
+    let projection = oom_projection(app, tag);
+    let (x_max, y_hi) = match &projection {
+        Some(forecast) => {
+            stats_text.push_str(&format!(
+                "  │  out of memory ~step {:.0}",
+                forecast.exhausted_at
+            ));
+            (
+                x_max.max(forecast.exhausted_at),
+                y_hi.max(plot_y(forecast.total)),
+            )
+        }
+        None => (x_max, y_hi),
+    };
+    let projection_points = projection.as_ref().map(|forecast| {
+        let mut points = projection_line(forecast);
+        for point in &mut points {
+            point.1 = plot_y(point.1);
+        }
+        points
+    });
+
+    // Annotations inside the visible step range become vertical markers.
+    let visible_annotations: Vec<&Annotation> = app
+        .annotations
+        .iter()
+        .filter(|a| (a.step as f64) >= x_min && (a.step as f64) <= x_max)
+        .collect();
+    let marker_points: Vec<[(f64, f64); 2]> = visible_annotations
+        .iter()
+        .map(|a| [(a.step as f64, y_lo), (a.step as f64, y_hi)])
+        .collect();
+    let annotation_h: u16 = if visible_annotations.is_empty() { 0 } else { 1 };
+
+    // Checkpoint saves and evals sit as dots along the top edge; the one picked
+    // with `[` / `]` also gets a vertical line and a detail row.
+    let markers = app.chart_markers();
+    let chart_marker_points = chart_marker_positions(&markers, tag, x_min, x_max, y_hi);
+    let selected_marker = app.selected_marker.and_then(|idx| markers.get(idx));
+    let selected_marker_line: Option<[(f64, f64); 2]> = selected_marker
+        .filter(|m| (m.step as f64) >= x_min && (m.step as f64) <= x_max)
+        .map(|m| [(m.step as f64, y_lo), (m.step as f64, y_hi)]);
+    let marker_h: u16 = if selected_marker.is_some() { 1 } else { 0 };
+
+    // Wall-clock gaps (evals, crashes, preemptions) shade the steps they sit between.
+    let visible_gaps: Vec<&Gap> = app
+        .wall_gaps
+        .iter()
+        .filter(|gap| {
+            let (lo, hi) = gap.step_span();
+            hi >= x_min && lo <= x_max
+        })
+        .collect();
+    let gap_h: u16 = if visible_gaps.is_empty() { 0 } else { 1 };
+
+    // Layout: chart body | annotation labels (0 or 1) | selected marker (0 or 1) | gaps (0 or 1) | stats line (1)
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(5),
+            Constraint::Length(annotation_h),
+            Constraint::Length(marker_h),
+            Constraint::Length(gap_h),
+            Constraint::Length(1),
+        ])
+        .split(area);
+
+    // X-axis labels; on the epoch axis the chart keeps its steps (markers,
+    // gaps and annotations sit at steps) and the labels name their epochs.
+    let x_epochs = app.x_epochs();
+    let x_label = |x: f64| {
+        let text = match x_epochs {
+            Some(scale) => format::epoch(scale.epoch_at(x)),
+            None => format!("{x:.0}"),
+        };
+        Span::styled(text, Style::default().fg(TEXT_DIM))
+    };
+    let x_labels = vec![
+        x_label(x_min),
+        x_label((x_min + x_max) / 2.0),
+        x_label(x_max),
+    ];
+    let x_title = if x_epochs.is_some() { "epoch" } else { "step" };
+
+    // Y-axis labels
+    let y_label = |y: f64| {
+        Span::styled(
+            format::value(if log_scale { 10f64.powf(y) } else { y }),
+            Style::default().fg(TEXT_DIM),
+        )
+    };
+    let y_labels = vec![y_label(y_lo), y_label((y_lo + y_hi) / 2.0), y_label(y_hi)];
+    let y_title = match (app.metric_unit(tag), log_scale) {
+        (Some(unit), true) => format!("{unit} (log)"),
+        (Some(unit), false) => unit.to_string(),
+        (None, true) => "value (log)".to_string(),
+        (None, false) => "value".to_string(),
+    };
+
+    let dataset = Dataset::default()
+        .name(display_tag)
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(run_chart_color(app)));
+
+    let dataset = dataset.data(plotted);
+
+    // A multi-run project is charted as one band per seed group.
+    let bands = seed_band_layers(app, tag, (x_min, x_max), (y_lo, y_hi), chunks[0], plot_y);
+    let mut datasets = if bands.is_empty() {
+        vec![dataset]
+    } else {
+        band_datasets(&bands)
+    };
+    if !bands.is_empty() {
+        let runs: usize = app.seed_bands(tag).iter().map(|group| group.runs).sum();
+        stats_text.push_str(&format!(
+            "  │  {runs} runs · {} band",
+            app.band_kind.label()
+        ));
+    }
+    for points in &marker_points {
+        datasets.push(
+            Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(LOG_IMPORTANT))
+                .data(points),
+        );
+    }
+    datasets.extend(marker_datasets(&chart_marker_points));
+    let anomalies = anomaly_positions(app, tag, x_min, x_max, y_hi, plot_y);
+    datasets.extend(anomaly_dataset(&anomalies));
+    datasets.extend(projection_points.as_ref().map(projection_dataset));
+    if let (Some(marker), Some(line)) = (selected_marker, selected_marker_line.as_ref()) {
+        datasets.push(
+            Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(marker_color(marker.kind)))
+                .data(line),
+        );
+    }
+    let close_hint = match (markers.is_empty(), app.epoch_scale.is_some()) {
+        (true, false) => " Esc to close ",
+        (true, true) => " e epochs · Esc to close ",
+        (false, false) => " [ ] markers · Esc to close ",
+        (false, true) => " [ ] markers · e epochs · Esc to close ",
+    };
+
+    let chart = Chart::new(datasets)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(BORDER))
+                .title(Span::styled(
+                    format!(" {} ", display_tag),
+                    Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+                ))
+                .title_bottom(
+                    Line::from(Span::styled(close_hint, Style::default().fg(TEXT_DIM)))
+                        .alignment(Alignment::Right),
+                ),
+        )
+        .x_axis(
+            Axis::default()
+                .title(Span::styled(x_title, Style::default().fg(TEXT_DIM)))
+                .bounds([x_min, x_max])
+                .labels(x_labels)
+                .style(Style::default().fg(BORDER)),
+        )
+        .y_axis(
+            Axis::default()
+                .title(Span::styled(y_title, Style::default().fg(TEXT_DIM)))
+                .bounds([y_lo, y_hi])
+                .labels(y_labels)
+                .style(Style::default().fg(BORDER)),
+        );
+
+    f.render_widget(chart, chunks[0]);
+    shade_gaps(f.buffer_mut(), chunks[0], &visible_gaps, x_min, x_max);
+
+    if !visible_annotations.is_empty() {
+        let mut spans = vec![Span::styled("⚑ ", Style::default().fg(LOG_IMPORTANT))];
+        for (i, annotation) in visible_annotations.iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::styled("  │  ", Style::default().fg(TEXT_DIM)));
+            }
+            spans.push(Span::styled(
+                format!("{} ", annotation.step),
+                Style::default()
+                    .fg(LOG_IMPORTANT)
+                    .add_modifier(Modifier::BOLD),
+            ));
+            spans.push(Span::styled(
+                format!("{}: {}", annotation.kind.label(), annotation.message),
+                Style::default().fg(TEXT_LIGHT),
+            ));
+        }
+        let labels = Paragraph::new(Line::from(spans)).alignment(Alignment::Center);
+        f.render_widget(labels, chunks[1]);
+    }
+
+    if let Some(marker) = selected_marker {
+        let color = marker_color(marker.kind);
+        let label = match (&marker.kind, &marker.id) {
+            (MarkerKind::Checkpoint, Some(id)) => format!("checkpoint {id}"),
+            (MarkerKind::Checkpoint, None) => "checkpoint".to_string(),
+            (MarkerKind::Eval, _) => "eval".to_string(),
+            (MarkerKind::Lr, Some(warning)) => format!("lr: {warning}"),
+            (MarkerKind::Lr, None) => "lr".to_string(),
+        };
+        let mut spans = vec![
+            Span::styled("◆ ", Style::default().fg(color)),
+            Span::styled(
+                format!("step {} ", marker.step),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(label, Style::default().fg(TEXT_LIGHT)),
+        ];
+        for (tag, value) in &marker.values {
+            spans.push(Span::styled("  │  ", Style::default().fg(TEXT_DIM)));
+            spans.push(Span::styled(
+                format!("{} {}", app.metric_display_name(tag), format::value(*value)),
+                Style::default().fg(TEXT_LIGHT),
+            ));
+        }
+        if selected_marker_line.is_none() {
+            spans.push(Span::styled(
+                "  (outside chart)",
+                Style::default().fg(TEXT_DIM),
+            ));
+        }
+        let detail = Paragraph::new(Line::from(spans)).alignment(Alignment::Center);
+        f.render_widget(detail, chunks[2]);
+    }
+
+    if !visible_gaps.is_empty() {
+        let summary =
+            Paragraph::new(gap_summary(&visible_gaps, app.wall_secs)).alignment(Alignment::Center);
+        f.render_widget(summary, chunks[3]);
+    }
+
+    // Stats bar
+    let stats = Paragraph::new(Line::from(Span::styled(
+        stats_text,
+        Style::default().fg(CHART_SMOOTH),
+    )))
+    .alignment(Alignment::Center);
+    f.render_widget(stats, chunks[4]);
+}
+
+// ── Run comparison ──────────────────────────────────────────────────────────
+
+/// The focused metric of the loaded run and of `diff`'s run, over one step
+/// window and one value axis, with their config differences on the right.
+fn draw_run_diff(f: &mut Frame, app: &App, diff: &RunDiff, metric_idx: usize, area: Rect) {
+    let Some(tag) = app.tags.get(metric_idx) else {
+        return;
+    };
+    let display_tag = app.metric_display_name(tag);
+    let (loaded, other, by_epoch) = app.run_diff_series(diff, tag);
+    let (loaded, other) = (&loaded[..], &other[..]);
+    let loaded_name = run_meta::run_dir_for(&app.events_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "loaded".to_string());
+    let loaded_color = run_chart_color(app);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(75), Constraint::Percentage(25)])
+        .split(area);
+
+    let Some(full) = run_diff::span(loaded, other) else {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(BORDER))
+            .title(Span::styled(
+                format!(" {display_tag} "),
+                Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+            ));
+        let empty = Paragraph::new(format!("Neither run logs {display_tag}"))
+            .style(Style::default().fg(TEXT_DIM))
+            .block(block);
+        f.render_widget(empty, columns[0]);
+        draw_run_diff_panel(f, diff, (&loaded_name, None), None, columns[1]);
+        return;
+    };
+    let window = diff.visible(full);
+    let loaded_shown = run_diff::clip(loaded, window);
+    let other_shown = run_diff::clip(other, window);
+    let values = value_bounds(&[loaded_shown, other_shown]);
+    let hint = " o layout · + - zoom · < > pan · 0 all steps · Esc close ";
+
+    match diff.layout {
+        run_diff::Layout::SideBySide => {
+            let halves = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(columns[0]);
+            let left = Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(loaded_color))
+                .data(loaded_shown);
+            let right = Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(CHART_SMOOTH))
+                .data(other_shown);
+            let left_title = format!(" {display_tag} · {loaded_name} ");
+            let right_title = format!(" {display_tag} · {} ", diff.name);
+            f.render_widget(
+                comparison_chart(left_title, vec![left], (by_epoch, window), values, ""),
+                halves[0],
+            );
+            f.render_widget(
+                comparison_chart(right_title, vec![right], (by_epoch, window), values, hint),
+                halves[1],
+            );
+        }
+        run_diff::Layout::Overlay => {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+                .split(columns[0]);
+            let datasets = vec![
+                Dataset::default()
+                    .name(loaded_name.clone())
+                    .marker(symbols::Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::default().fg(loaded_color))
+                    .data(loaded_shown),
+                Dataset::default()
+                    .name(diff.name.clone())
+                    .marker(symbols::Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::default().fg(CHART_SMOOTH))
+                    .data(other_shown),
+            ];
+            f.render_widget(
+                comparison_chart(
+                    format!(" {display_tag} "),
+                    datasets,
+                    (by_epoch, window),
+                    values,
+                    "",
+                ),
+                rows[0],
+            );
+
+            let delta = run_diff::delta(loaded, other);
+            let delta_shown = run_diff::clip(&delta, window);
+            let zero = [(window.0, 0.0), (window.1, 0.0)];
+            let delta_values = value_bounds(&[delta_shown, &zero]);
+            let datasets = vec![
+                Dataset::default()
+                    .marker(symbols::Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::default().fg(TEXT_DIM))
+                    .data(&zero),
+                Dataset::default()
+                    .marker(symbols::Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::default().fg(LOG_IMPORTANT))
+                    .data(delta_shown),
+            ];
+            let title = format!(" {} − {loaded_name} ", diff.name);
+            f.render_widget(
+                comparison_chart(title, datasets, (by_epoch, window), delta_values, hint),
+                rows[1],
+            );
+        }
+    }
+
+    let latest = |series: &[(f64, f64)]| series.last().map(|(_, value)| *value);
+    draw_run_diff_panel(
+        f,
+        diff,
+        (&loaded_name, latest(loaded_shown)),
+        latest(other_shown),
+        columns[1],
+    );
+}
+
+/// Value axis fitting every point of `series`, with a 5% margin.
+fn value_bounds(series: &[&[(f64, f64)]]) -> (f64, f64) {
+    let (lo, hi) = series
+        .iter()
+        .flat_map(|points| points.iter().map(|(_, value)| *value))
+        .filter(|value| value.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), value| {
+            (lo.min(value), hi.max(value))
+        });
+    if lo > hi {
+        return (0.0, 1.0);
+    }
+    if (hi - lo).abs() < 1e-12 {
+        return (lo - 1.0, hi + 1.0);
+    }
+    let margin = (hi - lo) * 0.05;
+    (lo - margin, hi + margin)
+}
+
+/// A chart of the comparison view, its x axis in steps or in epochs.
+fn comparison_chart<'a>(
+    title: String,
+    datasets: Vec<Dataset<'a>>,
+    (by_epoch, (x_lo, x_hi)): (bool, (f64, f64)),
+    (y_lo, y_hi): (f64, f64),
+    hint: &'a str,
+) -> Chart<'a> {
+    let x_labels = [x_lo, (x_lo + x_hi) / 2.0, x_hi].map(|x| {
+        let text = if by_epoch {
+            format::epoch(x)
+        } else {
+            format!("{x:.0}")
+        };
+        Span::styled(text, Style::default().fg(TEXT_DIM))
+    });
+    let x_title = if by_epoch { "epoch" } else { "step" };
+    let y_labels = [y_lo, (y_lo + y_hi) / 2.0, y_hi]
+        .map(|value| Span::styled(format::value(value), Style::default().fg(TEXT_DIM)));
+    Chart::new(datasets)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(BORDER))
+                .title(Span::styled(
+                    title,
+                    Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+                ))
+                .title_bottom(
+                    Line::from(Span::styled(hint, Style::default().fg(TEXT_DIM)))
+                        .alignment(Alignment::Right),
+                ),
+        )
+        .x_axis(
+            Axis::default()
+                .title(Span::styled(x_title, Style::default().fg(TEXT_DIM)))
+                .bounds([x_lo, x_hi])
+                .labels(x_labels.to_vec())
+                .style(Style::default().fg(BORDER)),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([y_lo, y_hi])
+                .labels(y_labels.to_vec())
+                .style(Style::default().fg(BORDER)),
+        )
+}
+
+/// Last value shown for each run and the `og diff` lines between them.
+fn draw_run_diff_panel(
+    f: &mut Frame,
+    diff: &RunDiff,
+    (loaded_name, loaded_last): (&str, Option<f64>),
+    other_last: Option<f64>,
+    area: Rect,
+) {
+    let shown = |value: Option<f64>| value.map(format::value).unwrap_or_else(|| "-".into());
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(format!("{loaded_name}: "), Style::default().fg(TEXT_DIM)),
+            Span::styled(shown(loaded_last), Style::default().fg(TEXT_LIGHT)),
+        ]),
+        Line::from(vec![
+            Span::styled(format!("{}: ", diff.name), Style::default().fg(TEXT_DIM)),
+            Span::styled(shown(other_last), Style::default().fg(CHART_SMOOTH)),
+        ]),
+    ];
+    if let (Some(loaded), Some(other)) = (loaded_last, other_last) {
+        lines.push(Line::from(vec![
+            Span::styled("difference: ", Style::default().fg(TEXT_DIM)),
+            Span::styled(
+                format::value(other - loaded),
+                Style::default().fg(LOG_IMPORTANT),
+            ),
+        ]));
+    }
+    lines.push(Line::from(""));
+    if diff.differences.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "same config and git state",
+            Style::default().fg(TEXT_DIM),
+        )));
+    }
+    for line in &diff.differences {
+        lines.push(Line::from(Span::styled(
+            line.clone(),
+            diff_line_style(line),
+        )));
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(BORDER))
+        .title(Span::styled(
+            " config diff ",
+            Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
+        ));
+    f.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(block),
+        area,
+    );
+}
+
+/// `▒ 2 wall-clock gaps · 1h 14m paused (52% of 2h 22m) · longest 1h 2m after step 80, resumed at 60`.
+fn gap_summary(gaps: &[&Gap], wall_secs: Option<f64>) -> Line<'static> {
+    let paused: f64 = gaps.iter().map(|gap| gap.secs).sum();
+    let mut text = format!(
+        "{} wall-clock gap{} · {} paused",
+        gaps.len(),
+        if gaps.len() == 1 { "" } else { "s" },
+        format::duration(paused)
+    );
+    if let Some(wall) = wall_secs.filter(|wall| *wall > 0.0) {
+        text.push_str(&format!(
+            " ({:.0}% of {})",
+            (paused / wall * 100.0).min(100.0),
+            format::duration(wall)
+        ));
+    }
+    if let Some(longest) = gaps.iter().max_by(|a, b| a.secs.total_cmp(&b.secs)) {
+        text.push_str(&format!(
+            " · longest {} after step {}",
+            format::duration(longest.secs),
+            longest.from_step
+        ));
+        if longest.resumed() {
+            text.push_str(&format!(", resumed at {}", longest.to_step));
+        }
+    }
+    Line::from(vec![
+        Span::styled("▒ ", Style::default().fg(GAP_SHADE_FG)),
+        Span::styled(text, Style::default().fg(TEXT_LIGHT)),
+    ])
+}
+
+/// Shade the plot columns of a rendered chart that fall inside a gap. The
+/// plot starts right of the axes' `└` corner, which is found in the buffer
+/// so the shading lines up with however wide the y labels came out.
+fn shade_gaps(buf: &mut Buffer, area: Rect, gaps: &[&Gap], x_min: f64, x_max: f64) {
+    if gaps.is_empty() || area.width < 3 || area.height < 3 {
+        return;
+    }
+    let inner_right = area.right() - 2;
+    let inner_bottom = area.bottom() - 2;
+    // Bottom-up: the legend box has a `└` of its own near the top.
+    let corner = (area.y + 1..=inner_bottom).rev().find_map(|y| {
+        (area.x + 1..=inner_right)
+            .find(|&x| buf[(x, y)].symbol() == symbols::line::BOTTOM_LEFT)
+            .map(|x| (x, y))
+    });
+    let Some((origin_x, axis_y)) = corner else {
+        return;
+    };
+    let left = origin_x + 1;
+    if left > inner_right || x_max <= x_min {
+        return;
+    }
+    let width = f64::from(inner_right - left);
+    let column = |step: f64| {
+        let t = ((step - x_min) / (x_max - x_min)).clamp(0.0, 1.0);
+        left + (t * width).round() as u16
+    };
+    for gap in gaps {
+        let (lo, hi) = gap.step_span();
+        for x in column(lo)..=column(hi) {
+            for y in area.y + 1..axis_y {
+                buf[(x, y)].set_bg(GAP_SHADE);
+            }
+        }
+    }
+}
+
+fn marker_color(kind: MarkerKind) -> Color {
+    match kind {
+        MarkerKind::Checkpoint => MARKER_CHECKPOINT,
+        MarkerKind::Eval => MARKER_EVAL,
+        MarkerKind::Lr => MARKER_LR,
+    }
+}
+
+/// Top-edge positions of the markers inside the chart's step range; LR
+/// warnings only appear on loss charts.
+fn chart_marker_positions(
+    markers: &[ChartMarker],
+    tag: &str,
+    x_min: f64,
+    x_max: f64,
+    y_top: f64,
+) -> Vec<(MarkerKind, Vec<(f64, f64)>)> {
+    [MarkerKind::Checkpoint, MarkerKind::Eval, MarkerKind::Lr]
+        .into_iter()
+        .filter(|kind| *kind != MarkerKind::Lr || is_loss_tag(tag))
+        .map(|kind| {
+            let points = markers
+                .iter()
+                .filter(|m| m.kind == kind)
+                .map(|m| m.step as f64)
+                .filter(|step| *step >= x_min && *step <= x_max)
+                .map(|step| (step, y_top))
+                .collect();
+            (kind, points)
+        })
+        .collect()
+}
+
+fn marker_datasets(points: &[(MarkerKind, Vec<(f64, f64)>)]) -> Vec<Dataset<'_>> {
+    points
+        .iter()
+        .filter(|(_, points)| !points.is_empty())
+        .map(|(kind, points)| {
+            Dataset::default()
+                .marker(symbols::Marker::Dot)
+                .graph_type(GraphType::Scatter)
+                .style(Style::default().fg(marker_color(*kind)))
+                .data(points)
+        })
+        .collect()
+}
+
+/// Anomalous points of `tag` inside the chart's step range; non-finite ones
+/// sit on the top edge.
+fn anomaly_positions(
+    app: &App,
+    tag: &str,
+    x_min: f64,
+    x_max: f64,
+    y_top: f64,
+    plot_y: impl Fn(f64) -> f64,
+) -> Vec<(f64, f64)> {
+    app.anomalies
+        .get(tag)
+        .into_iter()
+        .flatten()
+        .filter(|a| a.step >= x_min && a.step <= x_max)
+        .map(|a| {
+            let y = plot_y(a.value);
+            (a.step, if y.is_finite() { y } else { y_top })
+        })
+        .collect()
+}
+
+/// Direction arrow, step and value of a series' best point, when the
+/// metric's direction is known.
+fn best_value(data: &[(f64, f64)], higher_is_better: Option<bool>) -> Option<(char, f64, f64)> {
+    let higher = higher_is_better?;
+    let (step, best) =
+        data.iter()
+            .filter(|(_, v)| v.is_finite())
+            .copied()
+            .reduce(|best, point| {
+                let better = if higher {
+                    point.1 > best.1
+                } else {
+                    point.1 < best.1
+                };
+                if better { point } else { best }
+            })?;
+    Some((if higher { '▲' } else { '▼' }, step, best))
+}
+
+fn anomaly_dataset(points: &[(f64, f64)]) -> Option<Dataset<'_>> {
+    (!points.is_empty()).then(|| {
+        Dataset::default()
+            .marker(symbols::Marker::Dot)
+            .graph_type(GraphType::Scatter)
+            .style(Style::default().fg(LOG_ERROR))
+            .data(points)
+    })
+}
+
+fn oom_projection(app: &App, tag: &str) -> Option<Forecast> {
+    app.oom_forecasts()
+        .into_iter()
+        .find(|forecast| forecast.tag == tag)
+}
+
+/// From the latest point to the projected exhaustion point.
+fn projection_line(forecast: &Forecast) -> [(f64, f64); 2] {
+    [
+        (forecast.step, forecast.used),
+        (forecast.exhausted_at, forecast.total),
+    ]
+}
+
+fn projection_dataset(points: &[(f64, f64); 2]) -> Dataset<'_> {
+    Dataset::default()
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(LOG_ERROR))
+        .data(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    use ratatui::{Terminal, backend::TestBackend, buffer::Buffer};
+
+    use crate::app::{App, Tab, ToastLevel};
+    use crate::checkpoints::Checkpoint;
+    use crate::dashboard::DashboardRow;
+    use crate::epoch::EpochScale;
+    use crate::log_entry::LogSource;
+    use crate::metrics_jsonl::StructuredEvent;
+    use crate::run_meta::{AnnotationKind, MetricAttrs};
+    use crate::table::Table;
+
+    fn empty_app() -> App {
+        App::new(
+            BTreeMap::new(),
+            BTreeMap::new(),
+            Vec::new(),
+            PathBuf::from("runs/demo"),
+            0,
+            0,
+        )
+    }
+
+    fn app_with_metric() -> App {
+        let mut scalars = BTreeMap::new();
+        scalars.insert(
+            "train/loss".to_string(),
+            vec![(1.0, 2.0), (2.0, 1.25), (3.0, 0.5)],
+        );
+
+        let mut labels = BTreeMap::new();
+        labels.insert("train/loss".to_string(), "Loss".to_string());
+
+        App::new(
+            scalars,
+            labels,
+            Vec::new(),
+            PathBuf::from("runs/demo"),
+            42,
+            3,
+        )
+    }
+
+    fn render_screen(app: &mut App, width: u16, height: u16) -> (String, LayoutRegions) {
+        let backend = TestBackend::new(width, height);
+        let mut terminal = Terminal::new(backend).expect("test backend should initialize");
+        let mut regions = LayoutRegions::default();
+
+        terminal
+            .draw(|f| {
+                regions = draw(f, app);
+            })
+            .expect("UI should render into the test backend");
+
+        (buffer_to_string(terminal.backend().buffer()), regions)
+    }
+
+    fn buffer_to_string(buffer: &Buffer) -> String {
+        let mut lines = Vec::with_capacity(buffer.area.height as usize);
+        for y in 0..buffer.area.height {
+            let mut line = String::new();
+            for x in 0..buffer.area.width {
+                line.push_str(buffer[(x, y)].symbol());
+            }
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+
+    fn assert_screen_contains(screen: &str, needle: &str) {
+        assert!(
+            screen.contains(needle),
+            "expected screen to contain {needle:?}\n\nscreen:\n{screen}"
+        );
+    }
+
+    #[test]
+    fn too_small_terminals_get_a_placeholder_instead_of_the_layout() {
+        let mut app = app_with_metric();
+        app.active_tab = Tab::Graphs;
+
+        let (screen, regions) = render_screen(&mut app, 50, 12);
+
+        assert_screen_contains(&screen, "terminal too small");
+        assert_screen_contains(&screen, "50x12");
+        assert_screen_contains(&screen, &format!("need {MIN_WIDTH}x{MIN_HEIGHT}"));
+        assert!(regions.tab_rects.is_empty());
+        assert!(!screen.contains("metrics (1)"));
+    }
+
+    #[test]
+    fn shrinking_the_terminal_keeps_the_selected_metric_on_screen() {
+        let scalars: BTreeMap<String, Vec<(f64, f64)>> = (0..16)
+            .map(|idx| (format!("m{idx:02}"), vec![(1.0, 1.0), (2.0, 2.0)]))
+            .collect();
+        let mut app = App::new(
+            scalars,
+            BTreeMap::new(),
+            Vec::new(),
+            PathBuf::from("runs/demo"),
+            32,
+            2,
+        );
+        app.active_tab = Tab::Graphs;
+        render_screen(&mut app, 120, 80);
+        assert_eq!(app.metrics_scroll, 0);
+        app.selected_metric = 15;
+
+        let (screen, _) = render_screen(&mut app, 120, 30);
+
+        assert_eq!(app.metrics_visible_rows, 2);
+        assert_eq!(app.metrics_scroll, 2);
+        assert_screen_contains(&screen, "m15");
+    }
+
+    #[test]
+    fn state_label_maps_known_process_states() {
+        assert_eq!(state_label("R"), "running");
+        assert_eq!(state_label("S"), "sleeping");
+        assert_eq!(state_label("D"), "io-wait");
+        assert_eq!(state_label("T"), "stopped");
+        assert_eq!(state_label("Z"), "zombie");
+        assert_eq!(state_label("I"), "idle");
+        assert_eq!(state_label("?"), "other");
+        assert_eq!(state_label(""), "sleeping");
+    }
+
+    #[test]
+    fn truncate_text_handles_zero_short_and_exact_widths() {
+        assert_eq!(truncate_text("abcdef", 0), "");
+        assert_eq!(truncate_text("abcdef", 2), "..");
+        assert_eq!(truncate_text("abcdef", 3), "...");
+        assert_eq!(truncate_text("abcdef", 4), "a...");
+        assert_eq!(truncate_text("abcd", 4), "abcd");
+    }
+
+    #[test]
+    fn style_for_log_entry_follows_the_level() {
+        let style =
+            |level, source, message| style_for_log_entry(&LogEntry::new(level, source, message));
+        assert_eq!(
+            style(LogLevel::Error, LogSource::Og, "boom"),
+            Style::default().fg(LOG_ERROR).add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(
+            style(LogLevel::Success, LogSource::Og, "fixed"),
+            Style::default().fg(GREEN).add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(
+            style(LogLevel::Important, LogSource::Daemon, "heads up"),
+            Style::default()
+                .fg(LOG_IMPORTANT)
+                .add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(
+            style(LogLevel::Info, LogSource::Og, "note"),
+            Style::default().fg(LOG_INFO_ASH)
+        );
+        assert_eq!(
+            style(LogLevel::Info, LogSource::Events, "-- separator"),
+            Style::default().fg(BORDER)
+        );
+        assert_eq!(
+            style(LogLevel::Info, LogSource::Events, "step 1 │ loss │ 0.5"),
+            Style::default().fg(TEXT_LIGHT)
+        );
+    }
+
+    #[test]
+    fn draw_shows_disconnected_chat_guidance_and_status() {
+        let mut app = empty_app();
+
+        let (screen, regions) = render_screen(&mut app, 100, 30);
+
+        assert_eq!(regions.tab_rects.len(), Tab::ALL.len());
+        assert_screen_contains(&screen, "Agent chat is not connected.");
+        assert_screen_contains(&screen, "Start with built-in daemon:");
+        assert_screen_contains(&screen, "ogtui --path runs/ --training-file train.py");
+        assert_screen_contains(&screen, "ogd sock");
+        assert_screen_contains(&screen, "(i) insert");
+        assert_screen_contains(&screen, "opengraphs");
+        assert_screen_contains(&screen, "██████╗  ██████╗  ███████╗");
+        assert_screen_contains(&screen, &format!("(v{})", env!("CARGO_PKG_VERSION")));
+        assert_screen_contains(
+            &screen,
+            &std::env::current_dir()
+                .expect("cwd should resolve")
+                .display()
+                .to_string(),
+        );
+    }
+
+    fn span_texts(spans: &[Span]) -> Vec<(String, bool, bool)> {
+        spans
+            .iter()
+            .map(|span| {
+                (
+                    span.content.to_string(),
+                    span.style.add_modifier.contains(Modifier::BOLD),
+                    span.style.add_modifier.contains(Modifier::ITALIC),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn inline_markdown_handles_emphasis_code_and_identifiers() {
+        let base = Style::default();
+        assert_eq!(
+            span_texts(&inline_markdown_spans("**lr** is *too* high", base)),
+            vec![
+                ("lr".to_string(), true, false),
+                (" is ".to_string(), false, false),
+                ("too".to_string(), false, true),
+                (" high".to_string(), false, false),
+            ]
+        );
+        let spans = inline_markdown_spans("set `lr=3e-4` now", base);
+        assert_eq!(spans[1].content, "lr=3e-4");
+        assert_eq!(spans[1].style.bg, Some(CHAT_CODE_BG));
+        for literal in ["train_loss and val_loss", "2 * 3 * 4", "__init__", "**open"] {
+            assert_eq!(
+                span_texts(&inline_markdown_spans(literal, base)),
+                vec![(literal.to_string(), false, false)],
+                "{literal}"
+            );
+        }
+    }
+
+    #[test]
+    fn markdown_lines_render_blocks_and_keep_bare_diffs_colored() {
+        let text = |line: &Line| {
+            line.spans
+                .iter()
+                .map(|s| s.content.as_ref())
+                .collect::<String>()
+        };
+        let lines = markdown_lines(
+            "## Diagnosis\n- loss *spikes*\n2. lower lr\n```python\nlr = 3e-4\n```\nProposed:\n--- a/t.py\n+++ b/t.py\n-lr = 1\n+lr = 2",
+        );
+        let rendered: Vec<String> = lines.iter().map(text).collect();
+        assert_eq!(
+            rendered,
+            vec![
+                "  Diagnosis",
+                "  • loss spikes",
+                "  2. lower lr",
+                "  lr = 3e-4",
+                "  Proposed:",
+                "  --- a/t.py",
+                "  +++ b/t.py",
+                "  -lr = 1",
+                "  +lr = 2",
+            ]
+        );
+        assert!(
+            lines[0].spans[1]
+                .style
+                .add_modifier
+                .contains(Modifier::BOLD)
+        );
+        assert_eq!(lines[3].spans[0].style.bg, Some(CHAT_CODE_BG));
+        assert_eq!(lines[8].spans[0].style.fg, Some(GREEN));
+    }
+
+    #[test]
+    fn draw_chat_renders_streamed_reply_with_typing_cursor() {
+        let mut app = empty_app();
+        app.daemon_connected = true;
+        app.agent_thinking = true;
+        app.chat_stream = "Loss spiked after\nstep 1200".to_string();
+
+        let (screen, _) = render_screen(&mut app, 100, 30);
+
+        assert_screen_contains(&screen, "[Agent]");
+        assert_screen_contains(&screen, "Loss spiked after");
+        assert_screen_contains(&screen, "step 1200");
+        assert_screen_contains(&screen, "▍");
+        assert_screen_contains(&screen, "typing...");
+    }
+
+    #[test]
+    fn wrapped_chat_and_log_lines_still_follow_the_tail() {
+        let mut app = empty_app();
+        app.daemon_connected = true;
+        let long = "the gradient norm grew steadily ".repeat(12);
+        let messages = (0..6)
+            .map(|idx| crate::socket_client::ChatMessage {
+                sender: if idx % 2 == 0 { "user" } else { "agent" }.to_string(),
+                content: format!("{long}message {idx}"),
+                timestamp: idx as f64,
+            })
+            .collect();
+        app.update_chat_messages(messages);
+
+        let (screen, _) = render_screen(&mut app, 100, 30);
+        assert_screen_contains(&screen, "message 5");
+        assert!(app.chat_follow_tail);
+        let bottom = app.chat_scroll;
+        app.scroll_chat_down();
+        assert_eq!(app.chat_scroll, bottom, "cannot scroll past the last row");
+        app.scroll_chat_up();
+        assert!(!app.chat_follow_tail);
+
+        app.active_tab = Tab::Logs;
+        app.log_lines = (0..40)
+            .map(|idx| {
+                LogEntry::new(
+                    LogLevel::Info,
+                    LogSource::Og,
+                    format!("{}line {idx}", "x".repeat(150)),
+                )
+            })
+            .collect();
+        let (screen, _) = render_screen(&mut app, 100, 30);
+        assert_screen_contains(&screen, "line 39");
+        assert!(app.logs_rendered_rows > app.log_lines.len());
+    }
+
+    #[test]
+    fn log_selection_shows_in_title_and_footer() {
+        let mut app = empty_app();
+        app.active_tab = Tab::Logs;
+        app.log_lines = (0..5)
+            .map(|idx| LogEntry::new(LogLevel::Info, LogSource::Og, format!("line {idx}")))
+            .collect();
+        render_screen(&mut app, 100, 30);
+        app.start_log_selection();
+        app.move_log_cursor(-1);
+
+        let (screen, _) = render_screen(&mut app, 100, 30);
+        assert_screen_contains(&screen, "logs · VISUAL 2 lines");
+        assert_screen_contains(&screen, "j/k extend │ y yank │ Esc cancel");
+    }
+
+    #[test]
+    fn checkpoints_panel_lists_step_metrics_and_size() {
+        let mut app = empty_app();
+        let checkpoint = |step: i64, size_bytes: Option<u64>| crate::checkpoints::Checkpoint {
+            step,
+            path: PathBuf::from(format!("ckpt/step_{step}.pt")),
+            wall_time: None,
+            metrics: BTreeMap::from([("val/loss".to_string(), 1.25)]),
+            size_bytes,
+        };
+        app.open_checkpoints(vec![checkpoint(500, None), checkpoint(1000, Some(3 << 20))]);
+        assert_eq!(app.selected_checkpoint().map(|c| c.step), Some(1000));
+
+        let (screen, _) = render_screen(&mut app, 100, 30);
+        assert_screen_contains(&screen, "checkpoints (Enter=restart from, Esc=close)");
+        assert_screen_contains(&screen, "val/loss=1.2500");
+        assert_screen_contains(&screen, "3.0 MiB");
+        assert_screen_contains(&screen, "missing");
+
+        app.move_checkpoint_selection(-5);
+        assert_eq!(app.selected_checkpoint().map(|c| c.step), Some(500));
+    }
+
+    #[test]
+    fn draw_stacks_toasts_newest_first_on_any_tab() {
+        let mut app = app_with_metric();
+        app.active_tab = Tab::Graphs;
+        app.toast(ToastLevel::Success, "training finished");
+        app.toast(ToastLevel::Alert, "train/loss: loss spiked");
+
+        let (screen, _) = render_screen(&mut app, 100, 30);
+        let rows: Vec<&str> = screen.lines().collect();
+        let row_of = |needle: &str| rows.iter().position(|row| row.contains(needle));
+        let alert_row = row_of("train/loss: loss spiked").expect("alert toast");
+        let done_row = row_of("training finished").expect("done toast");
+        assert!(alert_row < done_row);
+        assert!(rows[alert_row].trim_end().ends_with('│'));
+        assert_screen_contains(&screen, " alert ");
+    }
+
+    #[test]
+    fn draw_overlays_help_modal_shortcuts() {
+        let mut app = empty_app();
+        app.show_help = true;
+
+        let (screen, _) = render_screen(&mut app, 100, 30);
+
+        assert_screen_contains(&screen, "shortcuts");
+        assert_screen_contains(&screen, "Toggle this help");
+        assert_screen_contains(&screen, "Apply pending refactor");
+        assert_screen_contains(&screen, "Focus chat input");
+    }
+
+    #[test]
+    fn draw_graphs_tab_renders_header_stats_and_metric_output() {
+        let mut app = app_with_metric();
+        app.active_tab = Tab::Graphs;
+
+        let (screen, regions) = render_screen(&mut app, 120, 30);
+
+        assert_eq!(regions.metric_card_rects.len(), 1);
+        assert_screen_contains(&screen, "1 tags");
+        assert_screen_contains(&screen, "42 events");
+        assert_screen_contains(&screen, "step 3/3");
+        assert_screen_contains(&screen, "metrics (1)");
+        assert_screen_contains(&screen, "Loss");
+        assert_screen_contains(&screen, "0.5000");
+        assert_screen_contains(&screen, "path:   runs/demo");
+    }
+
+    #[test]
+    fn seed_groups_chart_as_labelled_bands_around_their_means() {
+        let mut app = app_with_metric();
+        app.active_tab = Tab::Graphs;
+        app.focused_metric = Some(0);
+        let group = |label: &str, offset: f64| crate::seed_band::SeedGroup {
+            label: label.to_string(),
+            runs: 2,
+            scalars: BTreeMap::from([(
+                "train/loss".to_string(),
+                vec![
+                    vec![(1.0, 2.0 + offset), (3.0, 0.5 + offset)],
+                    vec![(1.0, 1.5 + offset), (3.0, 0.25 + offset)],
+                ],
+            )]),
+        };
+        app.seed_groups = vec![group("lr=0.1", 0.0), group("lr=0.01", 0.5)];
+
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "4 runs · p25–p75 band");
+        assert_screen_contains(&screen, "lr=0.1 (2 runs)");
+        assert_screen_contains(&screen, "lr=0.01 (2 runs)");
+
+        app.band_kind = app.band_kind.toggle();
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "4 runs · min–max band");
+    }
+
+    #[test]
+    fn draw_focused_metric_shades_wall_clock_gaps() {
+        let mut app = app_with_metric();
+        app.active_tab = Tab::Graphs;
+        app.focused_metric = Some(0);
+        app.wall_secs = Some(4.0 * 3600.0);
+        app.wall_gaps = vec![crate::timeline::Gap {
+            from_step: 2,
+            to_step: 3,
+            secs: 3600.0,
+        }];
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).expect("terminal");
+        terminal.draw(|f| drop(draw(f, &mut app))).expect("draw");
+        let buffer = terminal.backend().buffer();
+        let screen = buffer_to_string(buffer);
+        assert_screen_contains(
+            &screen,
+            "1 wall-clock gap · 1h paused (25% of 4h) · longest 1h after step 2",
+        );
+        // Steps 2–3 are the right half of the plot; the left half stays clear.
+        let row = 10;
+        let shaded: Vec<u16> = (0..120)
+            .filter(|&x| buffer[(x, row)].bg == GAP_SHADE)
+            .collect();
+        assert!(!shaded.is_empty());
+        assert!(
+            shaded[0] > 50 && *shaded.last().unwrap() >= 110,
+            "{shaded:?}"
+        );
+    }
+
+    #[test]
+    fn draw_focused_metric_renders_detail_header_and_stats() {
+        let mut app = app_with_metric();
+        app.active_tab = Tab::Graphs;
+        app.focused_metric = Some(0);
+
+        let (screen, _) = render_screen(&mut app, 120, 30);
+
+        assert_screen_contains(&screen, "Loss");
+        assert_screen_contains(&screen, "Esc to close");
+        assert_screen_contains(&screen, "latest: 0.5000");
+        assert_screen_contains(&screen, "min: 0.5000");
+        assert_screen_contains(&screen, "max: 2.0000");
+        assert_screen_contains(&screen, "points: 3");
+        assert_screen_contains(&screen, "steps: 1–3");
+    }
+
+    #[test]
+    fn draw_focused_metric_uses_recorded_unit_direction_and_scale() {
+        let mut app = app_with_metric();
+        app.active_tab = Tab::Graphs;
+        app.metric_attrs.insert(
+            "train/loss".to_string(),
+            MetricAttrs {
+                unit: Some("nats".to_string()),
+                higher_is_better: Some(false),
+                scale: Some(Scale::Log),
+                display_name: None,
+                bounds: None,
+                card: None,
+            },
+        );
+
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "0.5000 nats");
+
+        app.focused_metric = Some(0);
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "nats (log)");
+        assert_screen_contains(&screen, "▼ best: 0.5000 @ 3");
+        assert_screen_contains(&screen, "max: 2.0000");
+    }
+
+    #[test]
+    fn bounded_metric_cards_draw_a_gauge_instead_of_a_chart() {
+        let mut app = app_with_metric();
+        app.scalars.insert(
+            "val/acc".to_string(),
+            vec![(1.0, 0.25), (2.0, 0.5), (3.0, 0.75)],
+        );
+        app.tags.push("val/acc".to_string());
+        app.active_tab = Tab::Graphs;
+
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "75%");
+        assert_screen_contains(&screen, "0.0000–1.0000");
+        assert_screen_contains(&screen, "▁▅█");
+        assert!(!screen.contains("0.0000–2.0000"), "the loss stays a chart");
+
+        assert_eq!(
+            gauge_footer((0.0, 1.0), "0.6000", 20),
+            ("0.0000–1.0000".to_string(), 1)
+        );
+        assert_eq!(
+            gauge_footer((0.0, 100.0), "62.5000 %", 16),
+            ("0–100".to_string(), 2)
+        );
+        assert_eq!(gauge_footer((0.0, 1.0), "0.6000", 9), (String::new(), 3));
+    }
+
+    #[test]
+    fn draw_replaces_the_footer_with_the_command_line() {
+        let mut app = app_with_metric();
+        app.command_input = Some("focus val/lo".to_string());
+
+        let (screen, _) = render_screen(&mut app, 100, 30);
+
+        let last = screen.lines().last().expect("footer row");
+        assert!(last.starts_with(":focus val/lo█"), "{last}");
+    }
+
+    #[test]
+    fn quitting_while_owning_training_asks_in_the_footer() {
+        let mut app = app_with_metric();
+        app.owns_training = true;
 
-    // Y-axis labels
-    let y_labels = vec![
-        Span::styled(format_value(y_lo), Style::default().fg(TEXT_DIM)),
-        Span::styled(
-            format_value((y_lo + y_hi) / 2.0),
-            Style::default().fg(TEXT_DIM),
-        ),
-        Span::styled(format_value(y_hi), Style::default().fg(TEXT_DIM)),
-    ];
+        app.request_quit();
+        assert!(app.quit_pending && !app.should_quit);
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        let last = screen.lines().last().expect("footer row");
+        assert!(last.starts_with("QUIT? Training stops"), "{last}");
 
-    let dataset = Dataset::default()
-        .name(display_tag)
-        .marker(symbols::Marker::Braille)
-        .graph_type(GraphType::Line)
-        .style(Style::default().fg(CHART_RAW));
+        app.quit_pending = false;
+        app.owns_training = false;
+        app.request_quit();
+        assert!(app.should_quit);
+    }
 
-    let dataset = dataset.data(data);
+    #[test]
+    fn focused_pane_border_takes_the_tab_accent() {
+        let mut app = app_with_metric();
+        app.active_tab = Tab::Graphs;
+        let corner_colors = |app: &mut App| {
+            let mut terminal = Terminal::new(TestBackend::new(100, 30)).expect("terminal");
+            terminal.draw(|f| drop(draw(f, app))).expect("draw");
+            let buffer = terminal.backend().buffer();
+            // Top-left corners of the metrics grid and of the stats column.
+            (buffer[(0, 3)].fg, buffer[(74, 3)].fg)
+        };
+        assert_eq!(corner_colors(&mut app), (ACCENT_GRAPHS, BORDER));
 
-    let chart = Chart::new(vec![dataset])
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(BORDER))
-                .title(Span::styled(
-                    format!(" {} ", display_tag),
-                    Style::default().fg(GREEN).add_modifier(Modifier::BOLD),
-                ))
-                .title_bottom(
-                    Line::from(Span::styled(
-                        " Esc to close ",
-                        Style::default().fg(TEXT_DIM),
-                    ))
-                    .alignment(Alignment::Right),
-                ),
-        )
-        .x_axis(
-            Axis::default()
-                .title(Span::styled("step", Style::default().fg(TEXT_DIM)))
-                .bounds([x_min, x_max])
-                .labels(x_labels)
-                .style(Style::default().fg(BORDER)),
-        )
-        .y_axis(
-            Axis::default()
-                .title(Span::styled("value", Style::default().fg(TEXT_DIM)))
-                .bounds([y_lo, y_hi])
-                .labels(y_labels)
-                .style(Style::default().fg(BORDER)),
-        );
+        app.focus_next_pane();
+        assert_eq!(corner_colors(&mut app), (BORDER, ACCENT_GRAPHS));
+    }
 
-    f.render_widget(chart, chunks[0]);
+    #[test]
+    fn draw_shows_note_input_box() {
+        let mut app = app_with_metric();
+        app.note_input = Some("tried lr=3e-4".to_string());
 
-    // Stats bar
-    let stats = Paragraph::new(Line::from(Span::styled(
-        stats_text,
-        Style::default().fg(CHART_SMOOTH),
-    )))
-    .alignment(Alignment::Center);
-    f.render_widget(stats, chunks[1]);
-}
+        let (screen, _) = render_screen(&mut app, 100, 30);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::collections::BTreeMap;
-    use std::path::PathBuf;
+        assert_screen_contains(&screen, "run note");
+        assert_screen_contains(&screen, "tried lr=3e-4");
+    }
 
-    use ratatui::{Terminal, backend::TestBackend, buffer::Buffer};
+    #[test]
+    fn sparkline_text_scales_to_range() {
+        assert_eq!(sparkline_text(&[0.0, 0.5, 1.0], 8), "▁▅█");
+        assert_eq!(sparkline_text(&[1.0, 2.0, 3.0, 4.0], 2), "▁█");
+        assert_eq!(sparkline_text(&[2.0, 2.0], 8), "▅▅");
+        assert_eq!(sparkline_text(&[], 8), "");
+    }
 
-    use crate::app::{App, Tab};
+    #[test]
+    fn draw_events_lists_structured_events_under_the_type_filter() {
+        let mut app = empty_app();
+        app.active_tab = Tab::Events;
+        let (screen, _) = render_screen(&mut app, 120, 20);
+        assert_screen_contains(&screen, "no structured events logged");
+
+        let event = |step: i64, kind: &str, payload: serde_json::Value| StructuredEvent {
+            step,
+            wall_time: 0.0,
+            kind: kind.to_string(),
+            payload: payload.as_object().cloned().unwrap_or_default(),
+        };
+        app.set_structured_events(vec![
+            event(100, "eval_result", serde_json::json!({"accuracy": 0.41})),
+            event(
+                200,
+                "generation_sample",
+                serde_json::json!({"prompt": "2+2?", "output": "4"}),
+            ),
+        ]);
+        let (screen, _) = render_screen(&mut app, 120, 20);
+        assert_screen_contains(&screen, "all 2 · eval_result 1 · generation_sample 1");
+        assert_screen_contains(&screen, "{\"accuracy\":0.41}");
+        assert_screen_contains(&screen, "{\"output\":\"4\",\"prompt\":\"2+2?\"}");
+
+        app.cycle_events_filter();
+        let (screen, _) = render_screen(&mut app, 120, 20);
+        assert_screen_contains(&screen, "{\"accuracy\":0.41}");
+        assert!(!screen.contains("2+2?"));
+    }
 
-    fn empty_app() -> App {
-        App::new(
-            BTreeMap::new(),
-            BTreeMap::new(),
-            Vec::new(),
-            PathBuf::from("runs/demo"),
-            0,
-            0,
-        )
+    #[test]
+    fn draw_table_panel_shows_a_labelled_heatmap() {
+        let mut app = empty_app();
+        app.open_tables(Vec::new());
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "No table summaries logged yet");
+
+        app.open_tables(vec![Table {
+            name: "val/confusion".to_string(),
+            step: 500,
+            row_labels: vec!["cat".to_string(), "dog".to_string()],
+            col_labels: vec!["cat".to_string(), "dog".to_string()],
+            rows: vec![vec![50.0, 2.0], vec![3.0, 45.0]],
+        }]);
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(
+            &screen,
+            "val/confusion @ step 500  │  1/1  │  min 2  max 50",
+        );
+        assert_screen_contains(&screen, "cat       50       2");
+        assert_screen_contains(&screen, "dog        3      45");
+        assert_eq!(heat_color(1.0), GREEN);
+        assert_eq!(heat_color(0.0), BG_DARK);
     }
 
-    fn app_with_metric() -> App {
+    #[test]
+    fn draw_run_diff_shows_both_runs_and_their_config_differences() {
+        let mut app = app_with_metric();
         let mut scalars = BTreeMap::new();
         scalars.insert(
             "train/loss".to_string(),
-            vec![(1.0, 2.0), (2.0, 1.25), (3.0, 0.5)],
+            vec![(1.0, 2.5), (2.0, 1.5), (3.0, 1.0)],
         );
-
-        let mut labels = BTreeMap::new();
-        labels.insert("train/loss".to_string(), "Loss".to_string());
-
-        App::new(
+        app.run_diff = Some(RunDiff {
+            path: PathBuf::from("runs/baseline"),
+            name: "baseline".to_string(),
             scalars,
-            labels,
-            Vec::new(),
-            PathBuf::from("runs/demo"),
-            42,
-            3,
-        )
+            epoch_scale: None,
+            differences: vec!["~ lr: 0.001 -> 0.0003".to_string()],
+            layout: run_diff::Layout::SideBySide,
+            window: None,
+        });
+        app.focus_metric(0);
+
+        let (screen, _) = render_screen(&mut app, 140, 30);
+        assert_screen_contains(&screen, "Loss · demo");
+        assert_screen_contains(&screen, "Loss · baseline");
+        assert_screen_contains(&screen, "~ lr: 0.001 -> 0.0003");
+        assert_screen_contains(&screen, "difference: 0.5");
+
+        app.run_diff_key('o');
+        let (screen, _) = render_screen(&mut app, 140, 30);
+        assert_screen_contains(&screen, "baseline − demo");
     }
 
-    fn render_screen(app: &mut App, width: u16, height: u16) -> (String, LayoutRegions) {
-        let backend = TestBackend::new(width, height);
-        let mut terminal = Terminal::new(backend).expect("test backend should initialize");
-        let mut regions = LayoutRegions::default();
-
-        terminal
-            .draw(|f| {
-                regions = draw(f, app);
-            })
-            .expect("UI should render into the test backend");
-
-        (buffer_to_string(terminal.backend().buffer()), regions)
+    #[test]
+    fn epoch_axis_relabels_the_focused_chart_and_aligns_the_comparison() {
+        let mut app = app_with_metric();
+        app.focus_metric(0);
+        app.toggle_epoch_axis();
+        assert!(!app.epoch_axis, "no scale, no epoch axis");
+        assert!(app.chat_status.starts_with("No epoch axis"));
+
+        app.epoch_scale = Some(EpochScale::StepsPerEpoch(2.0));
+        app.toggle_epoch_axis();
+        let (screen, _) = render_screen(&mut app, 140, 30);
+        assert_screen_contains(&screen, "epochs: 0.5–1.5");
+        assert_screen_contains(&screen, "e epochs");
+
+        // Twice the batch size: one step of the baseline covers two of demo's.
+        let scalars = BTreeMap::from([(
+            "train/loss".to_string(),
+            vec![(1.0, 2.5), (2.0, 1.5), (3.0, 1.0)],
+        )]);
+        app.run_diff = Some(RunDiff {
+            path: PathBuf::from("runs/baseline"),
+            name: "baseline".to_string(),
+            scalars,
+            epoch_scale: Some(EpochScale::StepsPerEpoch(1.0)),
+            differences: Vec::new(),
+            layout: run_diff::Layout::Overlay,
+            window: None,
+        });
+        let diff = app.run_diff.as_ref().expect("diff");
+        let (loaded, other, by_epoch) = app.run_diff_series(diff, "train/loss");
+        assert!(by_epoch);
+        assert_eq!(loaded[..], [(0.5, 2.0), (1.0, 1.25), (1.5, 0.5)]);
+        assert_eq!(other[0], (1.0, 2.5));
+        let (screen, _) = render_screen(&mut app, 140, 30);
+        assert_screen_contains(&screen, "epoch");
+        assert_screen_contains(&screen, "1.75");
     }
 
-    fn buffer_to_string(buffer: &Buffer) -> String {
-        let mut lines = Vec::with_capacity(buffer.area.height as usize);
-        for y in 0..buffer.area.height {
-            let mut line = String::new();
-            for x in 0..buffer.area.width {
-                line.push_str(buffer[(x, y)].symbol());
-            }
-            lines.push(line);
+    #[test]
+    fn sys_strip_stays_under_the_header_on_every_tab() {
+        let mut app = app_with_metric();
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert!(!screen.contains("step/s"), "no strip without readings");
+
+        app.sys_strip = crate::sys_strip::SysStrip {
+            gpu_util: Some(50.0),
+            gpu_mem: Some(95.0),
+            cpu: Some(30.0),
+            ram: None,
+            steps_per_sec: Some(3.5),
+        };
+        for tab in [Tab::Graphs, Tab::Logs, Tab::Chat] {
+            app.active_tab = tab;
+            let (screen, _) = render_screen(&mut app, 120, 30);
+            let strip = screen.lines().nth(3).expect("strip row");
+            assert!(strip.contains("GPU ████····  50%"), "{strip}");
+            assert!(strip.contains("VRAM ███████▋  95%"), "{strip}");
+            assert!(strip.contains("CPU ██▍·····  30%"), "{strip}");
+            assert!(strip.contains("RAM --"), "{strip}");
+            assert!(strip.contains("3.5000 step/s"), "{strip}");
         }
-        lines.join("\n")
+        assert_eq!(gauge_bar(0.0, 4), "····");
+        assert_eq!(gauge_bar(100.0, 4), "████");
     }
 
-    fn assert_screen_contains(screen: &str, needle: &str) {
-        assert!(
-            screen.contains(needle),
-            "expected screen to contain {needle:?}\n\nscreen:\n{screen}"
+    #[test]
+    fn draw_distributions_tab_shows_a_ridgeline_and_the_picked_step() {
+        use crate::histogram::{Bucket, Histogram};
+        let mut app = empty_app();
+        app.active_tab = Tab::Distributions;
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "no histograms logged");
+
+        app.histogram_error = Some("truncated record at byte 96".to_string());
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "histograms could not be read");
+        assert_screen_contains(&screen, "truncated record at byte 96");
+
+        let histogram = |step: i64, center: f64| Histogram {
+            step,
+            buckets: vec![Bucket {
+                left: center - 0.5,
+                right: center + 0.5,
+                count: 10.0,
+            }],
+        };
+        let mut histograms = BTreeMap::new();
+        histograms.insert(
+            "weights/w".to_string(),
+            vec![histogram(10, 0.0), histogram(20, 1.0)],
         );
+        app.set_histograms(histograms);
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "weights/w");
+        assert_screen_contains(&screen, "step 20");
+        assert_screen_contains(&screen, "2/2");
+
+        app.move_histogram_step(-1);
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "step 10");
+        assert_screen_contains(&screen, "1/2");
+        assert_eq!(ridge_text(&[0.0, 1.0, 4.0]), " ▃█");
     }
 
     #[test]
-    fn state_label_maps_known_process_states() {
-        assert_eq!(state_label("R"), "running");
-        assert_eq!(state_label("S"), "sleeping");
-        assert_eq!(state_label("D"), "io-wait");
-        assert_eq!(state_label("T"), "stopped");
-        assert_eq!(state_label("Z"), "zombie");
-        assert_eq!(state_label("I"), "idle");
-        assert_eq!(state_label("?"), "other");
-        assert_eq!(state_label(""), "sleeping");
+    fn draw_dashboard_lists_runs_with_step_and_rate() {
+        let mut app = empty_app();
+        app.active_tab = Tab::Dashboard;
+        app.dashboard_rows = vec![DashboardRow {
+            run: "2024-06-01_frosty-otter-17".to_string(),
+            metric: Some("train/loss".to_string()),
+            recent: vec![2.0, 1.5, 1.0],
+            step: 1200,
+            steps_per_sec: Some(3.5),
+            status: "running".to_string(),
+            color: (100, 149, 237),
+            spend: None,
+        }];
+
+        let (screen, _) = render_screen(&mut app, 140, 20);
+
+        assert_screen_contains(&screen, "dashboard");
+        assert_screen_contains(&screen, "2024-06-01_frosty-otter-17");
+        assert_screen_contains(&screen, "running");
+        assert_screen_contains(&screen, "1200");
+        assert_screen_contains(&screen, "3.50");
+        assert_screen_contains(&screen, "train/loss");
+        assert!(!screen.contains("free"));
+
+        app.run_sizes
+            .insert("2024-06-01_frosty-otter-17".to_string(), 3 << 30);
+        app.runs_free_bytes = Some(1 << 30);
+        let (screen, _) = render_screen(&mut app, 140, 20);
+        assert_screen_contains(&screen, "3.0 GiB");
+        assert_screen_contains(&screen, "1.0 GiB free");
+
+        app.dashboard_rows[0].spend = crate::cost::estimate(
+            7200.0,
+            4,
+            crate::cost::Pricing {
+                gpu_hour_price: Some(2.5),
+                budget: None,
+            },
+        );
+        let (screen, _) = render_screen(&mut app, 160, 20);
+        assert_screen_contains(&screen, "$20.00");
     }
 
     #[test]
-    fn truncate_text_handles_zero_short_and_exact_widths() {
-        assert_eq!(truncate_text("abcdef", 0), "");
-        assert_eq!(truncate_text("abcdef", 2), "..");
-        assert_eq!(truncate_text("abcdef", 3), "...");
-        assert_eq!(truncate_text("abcdef", 4), "a...");
-        assert_eq!(truncate_text("abcd", 4), "abcd");
+    fn draw_processes_tab_shows_distributed_health_panel() {
+        let mut app = app_with_metric();
+        app.active_tab = Tab::Processes;
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert!(!screen.contains("distributed health"));
+
+        let mut progress = BTreeMap::new();
+        for (rank, step, wall) in [(0, 900, 1_000.0), (1, 400, 700.0)] {
+            progress.insert(
+                rank,
+                crate::tfevents::RankProgress {
+                    last_step: step,
+                    last_wall_time: wall,
+                },
+            );
+        }
+        app.set_rank_progress(progress);
+        app.append_live_log(
+            LogLevel::Warn,
+            "node1:42:43 [1] NCCL WARN Rank 1 socket peer closed",
+        );
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "distributed health");
+        assert_screen_contains(&screen, "stalled");
+        assert_screen_contains(&screen, "300s");
+        assert_screen_contains(&screen, "[warn] rank 1");
     }
 
     #[test]
-    fn format_ago_uses_seconds_minutes_hours_and_days() {
-        assert_eq!(format_ago(59), "59s ago");
-        assert_eq!(format_ago(60), "1m ago");
-        assert_eq!(format_ago(3_599), "59m ago");
-        assert_eq!(format_ago(3_600), "1h ago");
-        assert_eq!(format_ago(86_399), "23h ago");
-        assert_eq!(format_ago(86_400), "1d ago");
+    fn draw_processes_tab_shows_sparklines_of_recent_samples() {
+        let mut app = app_with_metric();
+        app.active_tab = Tab::Processes;
+        let process = |cpu_pct: f32, gpu_mem_mb: Option<f64>| crate::app::ProcessSnapshot {
+            pid: 4242,
+            ppid: 1,
+            state: "R".to_string(),
+            elapsed: "01:00".to_string(),
+            elapsed_secs: 60,
+            cpu_pct,
+            mem_pct: 5.0,
+            command: "python train.py".to_string(),
+            gpu_mem_mb,
+        };
+        for (cpu, gpu) in [(0.0, None), (100.0, Some(1024.0)), (50.0, Some(2048.0))] {
+            app.update_processes(vec![process(cpu, gpu)], 1_700_000_000);
+        }
+
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "cpu ▁█▅  mem ███  gpu ▅█ 2.0 GiB");
+        assert_eq!(sparkline_from_zero(&[0.0, 0.0], 4, 0.0), "▁▁");
+        assert!(!screen.contains("container limit"));
+
+        app.container_limits = Some(crate::cgroup::Limits {
+            cpus: Some(2.0),
+            memory_bytes: Some(4 << 30),
+        });
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "container limit: 2 cpus, 4.0 GiB");
     }
 
     #[test]
-    fn format_value_switches_between_fixed_and_scientific_notation() {
-        assert_eq!(format_value(12.34567), "12.3457");
-        assert_eq!(format_value(0.0), "0.0000");
-        assert_eq!(format_value(0.0005), "5.00e-4");
-        assert_eq!(format_value(12_345.0), "1.23e4");
+    fn draw_header_shows_selected_rank() {
+        let mut app = app_with_metric();
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert!(!screen.contains("ranks"));
+
+        app.ranks = vec![0, 1, 2, 3];
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "all 4 ranks");
+
+        app.selected_rank = Some(2);
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "rank 2/4");
     }
 
     #[test]
-    fn style_for_log_line_classifies_common_prefixes() {
-        assert_eq!(
-            style_for_log_line("[error] boom"),
-            Style::default().fg(LOG_ERROR).add_modifier(Modifier::BOLD)
-        );
-        assert_eq!(
-            style_for_log_line("[sucess] fixed"),
-            Style::default().fg(GREEN).add_modifier(Modifier::BOLD)
-        );
-        assert_eq!(
-            style_for_log_line("[IMPORTANT] heads up"),
-            Style::default()
-                .fg(LOG_IMPORTANT)
-                .add_modifier(Modifier::BOLD)
-        );
-        assert_eq!(
-            style_for_log_line("[info] note"),
-            Style::default().fg(LOG_INFO_ASH)
-        );
-        assert_eq!(
-            style_for_log_line("-- separator"),
-            Style::default().fg(BORDER)
-        );
-        assert_eq!(
-            style_for_log_line("plain log line"),
-            Style::default().fg(TEXT_LIGHT)
-        );
+    fn draw_header_summarizes_gradient_norm_health() {
+        let mut app = app_with_metric();
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert!(!screen.contains("grad"));
+
+        app.scalars
+            .insert("train/grad_norm".to_string(), vec![(0.0, 1.5), (1.0, 2.0)]);
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "grad ok 2.0");
+
+        app.scalars
+            .get_mut("train/grad_norm")
+            .expect("grad norm")
+            .push((2.0, 5e3));
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "grad exploding");
     }
 
     #[test]
-    fn draw_shows_disconnected_chat_guidance_and_status() {
-        let mut app = empty_app();
-
-        let (screen, regions) = render_screen(&mut app, 100, 30);
+    fn draw_header_flags_dirty_tree_runs() {
+        let mut app = app_with_metric();
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert!(!screen.contains("dirty tree"));
 
-        assert_eq!(regions.tab_rects.len(), Tab::ALL.len());
-        assert_screen_contains(&screen, "Agent chat is not connected.");
-        assert_screen_contains(&screen, "Start with built-in daemon:");
-        assert_screen_contains(&screen, "ogtui --path runs/ --training-file train.py");
-        assert_screen_contains(&screen, "ogd sock");
-        assert_screen_contains(&screen, "(i) insert");
-        assert_screen_contains(&screen, "opengraphs");
-        assert_screen_contains(&screen, "██████╗  ██████╗  ███████╗");
-        assert_screen_contains(&screen, &format!("(v{})", env!("CARGO_PKG_VERSION")));
-        assert_screen_contains(
-            &screen,
-            &std::env::current_dir()
-                .expect("cwd should resolve")
-                .display()
-                .to_string(),
-        );
+        app.dirty_tree = true;
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "dirty tree");
     }
 
     #[test]
-    fn draw_overlays_help_modal_shortcuts() {
+    fn draw_focused_metric_projects_a_growing_memory_metric_to_oom() {
         let mut app = empty_app();
-        app.show_help = true;
+        app.active_tab = Tab::Graphs;
+        app.scalars.insert(
+            "sys/gpu0/mem_used_mb".to_string(),
+            (0..10)
+                .map(|i| (i as f64 * 100.0, 1000.0 + i as f64 * 500.0))
+                .collect(),
+        );
+        app.scalars
+            .insert("sys/gpu0/mem_total_mb".to_string(), vec![(0.0, 10_000.0)]);
+        app.tags = app.scalars.keys().cloned().collect();
+        // Tags sort `mem_total_mb` before `mem_used_mb`.
+        app.focused_metric = Some(1);
 
-        let (screen, _) = render_screen(&mut app, 100, 30);
+        let (screen, _) = render_screen(&mut app, 140, 30);
 
-        assert_screen_contains(&screen, "shortcuts");
-        assert_screen_contains(&screen, "Toggle this help");
-        assert_screen_contains(&screen, "Apply pending refactor");
-        assert_screen_contains(&screen, "Focus chat input");
+        assert_screen_contains(&screen, "out of memory ~step 1800");
     }
 
     #[test]
-    fn draw_graphs_tab_renders_header_stats_and_metric_output() {
+    fn draw_focused_metric_lists_annotations_in_range() {
         let mut app = app_with_metric();
         app.active_tab = Tab::Graphs;
+        app.focused_metric = Some(0);
+        app.annotations = vec![
+            Annotation {
+                step: 2,
+                kind: AnnotationKind::Restart,
+                message: "resumed".to_string(),
+                created_unix: 0,
+            },
+            Annotation {
+                step: 99,
+                kind: AnnotationKind::Note,
+                message: "out of range".to_string(),
+                created_unix: 0,
+            },
+        ];
 
-        let (screen, regions) = render_screen(&mut app, 120, 30);
+        let (screen, _) = render_screen(&mut app, 120, 30);
 
-        assert_eq!(regions.metric_card_rects.len(), 1);
-        assert_screen_contains(&screen, "1 tags");
-        assert_screen_contains(&screen, "42 events");
-        assert_screen_contains(&screen, "step 3/3");
-        assert_screen_contains(&screen, "metrics (1)");
-        assert_screen_contains(&screen, "Loss");
-        assert_screen_contains(&screen, "0.5000");
-        assert_screen_contains(&screen, "path:   runs/demo");
+        assert_screen_contains(&screen, "restart: resumed");
+        assert!(!screen.contains("out of range"));
     }
 
     #[test]
-    fn draw_focused_metric_renders_detail_header_and_stats() {
+    fn draw_focused_metric_details_the_selected_checkpoint_marker() {
         let mut app = app_with_metric();
         app.active_tab = Tab::Graphs;
         app.focused_metric = Some(0);
+        app.checkpoints = vec![Checkpoint {
+            step: 2,
+            path: PathBuf::from("/ckpt/step_2.pt"),
+            wall_time: None,
+            metrics: BTreeMap::from([("train/loss".to_string(), 1.25)]),
+            size_bytes: None,
+        }];
 
         let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "[ ] markers");
+        assert!(!screen.contains("step_2.pt"));
 
-        assert_screen_contains(&screen, "Loss");
-        assert_screen_contains(&screen, "Esc to close");
-        assert_screen_contains(&screen, "latest: 0.5000");
-        assert_screen_contains(&screen, "min: 0.5000");
-        assert_screen_contains(&screen, "max: 2.0000");
-        assert_screen_contains(&screen, "points: 3");
-        assert_screen_contains(&screen, "steps: 1–3");
+        app.move_marker_selection(1);
+        let (screen, _) = render_screen(&mut app, 120, 30);
+        assert_screen_contains(&screen, "step 2 checkpoint step_2.pt");
+        assert_screen_contains(&screen, "Loss 1.2500");
     }
 }
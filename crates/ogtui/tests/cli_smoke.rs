@@ -103,9 +103,20 @@ fn assert_failure(output: &Output) -> String {
     stderr
 }
 
+/// Subcommand columns are padded to the longest name, so compare whitespace-insensitively.
+fn assert_help_lists_command(help: &str, name: &str, about: &str) {
+    let listed = help.lines().any(|line| {
+        let trimmed = line.trim_start();
+        trimmed
+            .strip_prefix(name)
+            .is_some_and(|rest| rest.starts_with(' ') && rest.trim_start() == about)
+    });
+    assert!(listed, "expected `{name}  {about}` in help:\n{help}");
+}
+
 fn masked_crc32c(data: &[u8]) -> u32 {
     let crc = crc32c::crc32c(data);
-    ((crc >> 15) | (crc << 17)).wrapping_add(0xa282_ead8)
+    crc.rotate_right(15).wrapping_add(0xa282_ead8)
 }
 
 fn write_record(mut file: &File, data: &[u8]) {
@@ -161,8 +172,8 @@ fn top_level_help_mentions_graph_alias_and_subcommands() {
     let stdout = assert_success(&ogtui(["--help"]));
     assert!(stdout.contains("OpenGraphs CLI + TUI"));
     assert!(stdout.contains("Usage: ogtui [OPTIONS] [COMMAND]"));
-    assert!(stdout.contains("list     List entities"));
-    assert!(stdout.contains("search   Search entities"));
+    assert_help_lists_command(&stdout, "list", "List entities");
+    assert_help_lists_command(&stdout, "search", "Search entities");
     assert!(stdout.contains("aliases: --graphs"));
 }
 
@@ -173,6 +184,12 @@ fn run_help_mentions_graph_alias_and_runtime_values() {
     assert!(stdout.contains("--graph <GRAPH>"));
     assert!(stdout.contains("aliases: --graphs"));
     assert!(stdout.contains("[possible values: local, modal]"));
+    assert!(stdout.contains("--debug-log <FILE>"));
+    assert!(stdout.contains("--max-fps <MAX_FPS>"));
+    assert!(stdout.contains("--no-idle-backoff"));
+
+    let stderr = assert_failure(&ogtui(["--max-fps", "0", "list", "projects"]));
+    assert!(stderr.contains("0 is not in 1..=240"));
 }
 
 #[test]
@@ -182,6 +199,42 @@ fn graphs_alias_is_accepted_before_graph_validation_runs() {
     assert!(!stderr.contains("unexpected argument '--graphs'"));
 }
 
+#[test]
+fn graph_presets_resolve_from_the_config_file() {
+    let temp = TestDir::new();
+    let config = temp.path().join("config.toml");
+    fs::write(&config, "preset.losses = [\"train/loss\", \"val/loss\"]\n").expect("write config");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ogtui"))
+        .args(["run", "demo.py", "--graph", "preset:sys"])
+        .env("OG_CONFIG", &config)
+        .output()
+        .expect("run ogtui");
+    let stderr = assert_failure(&output);
+    assert!(stderr.contains("graph preset 'sys' not found (known: losses)"));
+}
+
+#[test]
+fn graph_strict_rejects_selectors_that_match_nothing() {
+    let temp = TestDir::new();
+    let run_dir = sample_run(temp.path());
+    let run = run_dir.to_str().expect("run path should be utf8");
+
+    let stderr = assert_failure(&ogtui([
+        "--path",
+        run,
+        "--graph",
+        r#"{"metrics":["loss","lsos"]}"#,
+        "--graph-strict",
+    ]));
+    assert!(
+        stderr.contains("--graph-strict: 0 metrics matched 'lsos'; did you mean 'train/loss'?")
+    );
+
+    let stderr = assert_failure(&ogtui(["--path", run, "--graph", r#"{"metric":"loss"}"#]));
+    assert!(stderr.contains("unknown --graph key 'metric'"));
+}
+
 #[test]
 fn list_projects_text_reports_run_counts() {
     let temp = TestDir::new();
@@ -223,6 +276,217 @@ fn list_runs_json_reports_real_run_summary() {
     assert_eq!(runs[0]["max_step"].as_i64(), Some(2));
 }
 
+#[test]
+fn list_runs_filters_by_recorded_slurm_job() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    let run = sample_run(temp.path());
+    create_run(temp.path(), "alpha", "local-run", &[(1, "train/loss", 1.0)]);
+    fs::write(
+        run.join("og_meta.json"),
+        r#"{"slurm":{"job_id":"4242_3","node_list":"gpu[01-04]","partition":"a100"}}"#,
+    )
+    .expect("write og_meta.json");
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "list",
+        "runs",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--slurm-job",
+        "4242",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse list runs json");
+    assert_eq!(payload["count"].as_u64(), Some(1));
+    assert_eq!(payload["runs"][0]["id"].as_str(), Some("demo-run"));
+
+    let stdout = assert_success(&ogtui([
+        "get",
+        "run",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "demo-run",
+    ]));
+    assert!(
+        stdout.contains("slurm: job 4242_3 on gpu[01-04] (a100)"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn gc_spares_unreadable_runs_and_checkpoints_outside_the_run() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    let old = create_run(temp.path(), "alpha", "old", &[(1, "train/loss", 1.0)]);
+    let broken = create_run(temp.path(), "alpha", "broken", &[(1, "train/loss", 1.0)]);
+    let month_ago = SystemTime::now() - std::time::Duration::from_secs(30 * 86_400);
+    for run in [&old, &broken] {
+        File::options()
+            .write(true)
+            .open(run.join("events.out.tfevents.test"))
+            .expect("open events")
+            .set_modified(month_ago)
+            .expect("age events");
+    }
+    // Half-written metadata must not read as "untagged".
+    fs::write(broken.join("og_meta.json"), r#"{"tags": ["kee"#).expect("write meta");
+    let elsewhere = temp.path().join("shared/ckpt.pt");
+    fs::create_dir_all(elsewhere.parent().expect("parent")).expect("shared dir");
+    fs::write(&elsewhere, [0u8; 16]).expect("write checkpoint");
+    fs::write(
+        old.join("checkpoints.jsonl"),
+        format!("{}\n", serde_json::json!({"step": 1, "path": elsewhere})),
+    )
+    .expect("write registry");
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "gc",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--older-than",
+        "7",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse gc json");
+    let action = |run: &str| {
+        payload["runs"]
+            .as_array()
+            .expect("runs")
+            .iter()
+            .find(|entry| entry["run"] == run)
+            .map(|entry| entry["action"].as_str().expect("action").to_string())
+    };
+    assert_eq!(action("broken").as_deref(), Some("skip"));
+    assert_eq!(action("old").as_deref(), Some("delete"));
+    assert!(broken.exists());
+    assert!(!old.exists());
+    assert!(
+        elsewhere.exists(),
+        "outside the run dir without --external-checkpoints"
+    );
+}
+
+#[test]
+fn k8s_events_needs_a_recorded_or_named_pod() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    sample_run(temp.path());
+
+    let stderr = assert_failure(&ogtui([
+        "k8s",
+        "events",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "demo-run",
+    ]));
+    assert!(stderr.contains("name one with --pod"), "{stderr}");
+}
+
+#[test]
+fn tail_json_reports_structured_entries_and_filters_by_level() {
+    let temp = TestDir::new();
+    let log = temp.path().join("train.log");
+    fs::write(
+        &log,
+        "[info] warmup\n[sucess] step 3 completed\nTraceback (most recent call last):\n[warn] slow io\n",
+    )
+    .expect("write log");
+    let log = log.to_str().expect("temp path should be utf8");
+
+    let stdout = assert_success(&ogtui(["--json", "tail", log]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse tail json");
+    assert_eq!(payload["kind"].as_str(), Some("text"));
+    let entries = payload["entries"].as_array().expect("entries array");
+    assert_eq!(entries.len(), 4);
+    assert_eq!(entries[1]["level"].as_str(), Some("success"));
+    assert_eq!(entries[1]["source"].as_str(), Some("file"));
+    assert_eq!(entries[1]["message"].as_str(), Some("step 3 completed"));
+    assert_eq!(
+        payload["lines"][1].as_str(),
+        Some("[sucess] step 3 completed")
+    );
+
+    let stdout = assert_success(&ogtui(["--json", "tail", log, "--level", "warn"]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse tail json");
+    let levels: Vec<&str> = payload["entries"]
+        .as_array()
+        .expect("entries array")
+        .iter()
+        .filter_map(|entry| entry["level"].as_str())
+        .collect();
+    assert_eq!(levels, ["error", "warn"]);
+}
+
+#[test]
+fn parallel_scans_match_serial_results() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    for idx in 0..12 {
+        create_run(
+            temp.path(),
+            "alpha",
+            &format!("run-{idx:02}"),
+            &[
+                (idx, "train/loss", 1.0),
+                (idx, &format!("eval/acc_{idx:02}"), 0.5),
+            ],
+        );
+    }
+
+    let list = |jobs: &str| -> Value {
+        serde_json::from_str(&assert_success(&ogtui([
+            "--json",
+            "list",
+            "runs",
+            "--path",
+            root,
+            "--project",
+            "alpha",
+            "--jobs",
+            jobs,
+        ])))
+        .expect("parse list runs json")
+    };
+    let serial = list("1");
+    assert_eq!(serial["count"].as_u64(), Some(12));
+    assert_eq!(serial["runs"], list("4")["runs"]);
+
+    let search = assert_success(&ogtui([
+        "--json",
+        "search",
+        "metrics",
+        "--query",
+        "eval/",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--jobs",
+        "3",
+    ]));
+    let payload: Value = serde_json::from_str(&search).expect("parse search json");
+    let metrics: Vec<&str> = payload["matches"]
+        .as_array()
+        .expect("matches array")
+        .iter()
+        .filter_map(|m| m["metric"].as_str())
+        .collect();
+    assert_eq!(metrics.len(), 12);
+    assert_eq!(metrics[0], "eval/acc_00");
+    assert_eq!(metrics[11], "eval/acc_11");
+}
+
 #[test]
 fn list_metrics_and_system_metrics_filter_tags() {
     let temp = TestDir::new();
@@ -294,3 +558,1679 @@ fn get_run_json_reports_latest_metric_values() {
         Some(91.0)
     );
 }
+
+#[test]
+fn get_run_merges_torchrun_ranks_and_selects_one() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    let run_dir = temp.path().join("alpha").join("ddp-run");
+    for (rank, loss, mem) in [(0, 0.5, 10.0), (1, 0.9, 30.0)] {
+        let rank_dir = run_dir.join(format!("rank{rank}"));
+        fs::create_dir_all(&rank_dir).expect("create rank directory");
+        write_tfevents_file(
+            &rank_dir.join("events.out.tfevents.test"),
+            &[(1, "train/loss", loss), (1, "sys/gpu_mem", mem)],
+        );
+    }
+
+    let get_run = |extra: &[&str]| -> Value {
+        let mut args = vec![
+            "--json",
+            "get",
+            "run",
+            "--path",
+            root,
+            "--project",
+            "alpha",
+            "--run",
+            "ddp-run",
+        ];
+        args.extend_from_slice(extra);
+        serde_json::from_str(&assert_success(&ogtui(args))).expect("parse get run json")
+    };
+
+    let merged = get_run(&[]);
+    assert_eq!(merged["ranks"], serde_json::json!([0, 1]));
+    let latest = &merged["latest_metrics"];
+    assert_eq!(latest["train/loss"]["value"].as_f64(), Some(0.5));
+    assert_eq!(
+        latest["sys/gpu_mem (rank mean)"]["value"].as_f64(),
+        Some(20.0)
+    );
+    assert_eq!(
+        latest["sys/gpu_mem (rank max)"]["value"].as_f64(),
+        Some(30.0)
+    );
+
+    let rank_one = get_run(&["--rank", "1"]);
+    assert_eq!(
+        rank_one["latest_metrics"]["train/loss"]["value"].as_f64(),
+        Some(0.8999999761581421)
+    );
+    assert_eq!(
+        rank_one["latest_metrics"]["sys/gpu_mem"]["value"].as_f64(),
+        Some(30.0)
+    );
+
+    let stderr = assert_failure(&ogtui([
+        "get",
+        "run",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "ddp-run",
+        "--rank",
+        "7",
+    ]));
+    assert!(stderr.contains("has no rank 7"));
+}
+
+#[test]
+fn failures_exit_with_documented_codes_and_hints() {
+    let temp = TestDir::new();
+    sample_run(temp.path());
+    let root = temp.path().to_str().expect("utf8 path");
+
+    let output = ogtui([
+        "stats",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "demo-rnu",
+        "--metric",
+        "loss",
+    ]);
+    let stderr = assert_failure(&output);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr.contains("run 'demo-rnu' not found"), "{stderr}");
+    assert!(
+        stderr.contains("hint: did you mean run 'demo-run'?"),
+        "{stderr}"
+    );
+
+    let output = ogtui([
+        "--json",
+        "get",
+        "metric",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "demo-run",
+        "--metric",
+        "train/lsos",
+    ]);
+    assert_failure(&output);
+    assert_eq!(output.status.code(), Some(2));
+    let error: Value = serde_json::from_slice(&output.stdout).expect("json error");
+    assert_eq!(error["kind"], "metric_not_found");
+    assert_eq!(error["exit_code"], 2);
+    assert_eq!(error["hint"], "did you mean metric 'train/loss'?");
+
+    let output = ogtui(["--path", root, "--graph", "{not json"]);
+    assert!(assert_failure(&output).contains("parsing --graph JSON"));
+    assert_eq!(output.status.code(), Some(3));
+
+    let output = ogtui(["list", "runs", "--no-such-flag"]);
+    assert_failure(&output);
+    assert_eq!(output.status.code(), Some(64));
+
+    // Other failures keep exit code 1.
+    let output = ogtui(["list", "readers", "--file", root]);
+    assert_failure(&output);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn porcelain_flattens_the_json_and_quiet_prints_nothing() {
+    let temp = TestDir::new();
+    sample_run(temp.path());
+    let root = temp.path().to_str().expect("utf8 path");
+    let args = |mode: &'static str| {
+        ogtui([
+            mode,
+            "get",
+            "metric",
+            "--path",
+            root,
+            "--project",
+            "alpha",
+            "--run",
+            "demo-run",
+            "--metric",
+            "train/loss",
+        ])
+    };
+
+    let stdout = assert_success(&args("--porcelain"));
+    assert!(stdout.contains("count\t2\n"), "{stdout}");
+    assert!(stdout.contains("points.1.step\t2.0\n"), "{stdout}");
+    assert!(stdout.contains("points.1.value\t0.75\n"), "{stdout}");
+    assert!(stdout.lines().all(|line| line.split('\t').count() == 2));
+
+    let quiet = args("--quiet");
+    assert!(quiet.status.success());
+    assert!(quiet.stdout.is_empty() && quiet.stderr.is_empty());
+    let quiet = ogtui([
+        "-q", "get", "metric", "--path", root, "--run", "missing", "--metric", "x",
+    ]);
+    assert_eq!(quiet.status.code(), Some(2));
+    assert!(quiet.stdout.is_empty() && quiet.stderr.is_empty());
+
+    let porcelain = ogtui([
+        "--porcelain",
+        "get",
+        "metric",
+        "--path",
+        root,
+        "--run",
+        "missing",
+        "--metric",
+        "x",
+    ]);
+    assert_eq!(porcelain.status.code(), Some(2));
+    let stdout = String::from_utf8(porcelain.stdout).expect("utf8");
+    assert!(stdout.contains("kind\trun_not_found\n"), "{stdout}");
+}
+
+#[test]
+fn latest_and_aliases_name_runs_for_every_command() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("utf8 path");
+    let old = sample_run(temp.path());
+    create_run(
+        temp.path(),
+        "alpha",
+        "run_2024_06_02",
+        &[(1, "train/loss", 2.0)],
+    );
+    let events = old.join("events.out.tfevents.test");
+    File::options()
+        .write(true)
+        .open(&events)
+        .expect("open events")
+        .set_modified(SystemTime::now() - std::time::Duration::from_secs(3600))
+        .expect("age demo-run");
+
+    let get_run = |run: &str| -> Value {
+        let stdout = assert_success(&ogtui([
+            "--json",
+            "get",
+            "run",
+            "--path",
+            root,
+            "--project",
+            "alpha",
+            "--run",
+            run,
+        ]));
+        serde_json::from_str(&stdout).expect("get run json")
+    };
+    assert_eq!(get_run("latest")["run"]["id"], "run_2024_06_02");
+
+    assert_success(&ogtui([
+        "alias",
+        "set",
+        "best",
+        "demo-run",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+    ]));
+    assert_eq!(get_run("best")["run"]["id"], "demo-run");
+    let stdout = assert_success(&ogtui([
+        "stats",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "best",
+        "--metric",
+        "train/loss",
+    ]));
+    assert!(stdout.contains("train/loss"), "{stdout}");
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "alias",
+        "list",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+    ]));
+    let listed: Value = serde_json::from_str(&stdout).expect("alias list json");
+    assert_eq!(listed["latest"], "run_2024_06_02");
+    assert_eq!(listed["aliases"]["best"], "demo-run");
+
+    let stderr = assert_failure(&ogtui([
+        "alias",
+        "set",
+        "latest",
+        "demo-run",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+    ]));
+    assert!(stderr.contains("most recently updated run"), "{stderr}");
+    assert_success(&ogtui([
+        "alias",
+        "rm",
+        "best",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+    ]));
+    let gone = ogtui([
+        "get",
+        "run",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "best",
+    ]);
+    assert_eq!(gone.status.code(), Some(2));
+}
+
+#[test]
+fn metrics_jsonl_runs_are_listed_and_loaded() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    let run_dir = temp.path().join("alpha").join("jsonl-run");
+    fs::create_dir_all(&run_dir).expect("create run directory");
+    fs::write(
+        run_dir.join("metrics.jsonl"),
+        concat!(
+            "{\"step\": 1, \"wall_time\": 100.0, \"metrics\": {\"train/loss\": 1.5}}\n",
+            "{\"step\": 2, \"wall_time\": 101.0, \"metrics\": {\"train/loss\": 0.5, \"sys/gpu_util\": 80}}\n",
+        ),
+    )
+    .expect("write metrics.jsonl");
+
+    let runs = assert_success(&ogtui([
+        "--json",
+        "list",
+        "runs",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+    ]));
+    assert!(runs.contains("jsonl-run"));
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "get",
+        "run",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "jsonl-run",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse get run json");
+    assert_eq!(
+        payload["latest_metrics"]["train/loss"]["value"].as_f64(),
+        Some(0.5)
+    );
+    assert_eq!(
+        payload["latest_metrics"]["sys/gpu_util"]["value"].as_f64(),
+        Some(80.0)
+    );
+}
+
+#[test]
+fn import_csv_creates_a_loadable_run() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    let csv = temp.path().join("metrics.csv");
+    fs::write(
+        &csv,
+        "epoch,step,train_loss,val_loss\n0,49,0.9,\n0,99,0.7,\n0,99,,0.8\n",
+    )
+    .expect("write csv");
+    let csv = csv.to_str().expect("csv path should be utf8");
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "import",
+        "csv",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--file",
+        csv,
+        "--run",
+        "lightning",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse import json");
+    assert_eq!(payload["rows"].as_u64(), Some(3));
+    assert_eq!(payload["points"].as_u64(), Some(6));
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "get",
+        "metric",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "lightning",
+        "--metric",
+        "train_loss",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse get metric json");
+    assert_eq!(payload["count"].as_u64(), Some(2));
+    assert_eq!(payload["last"].as_f64(), Some(0.7));
+
+    let meta = fs::read_to_string(temp.path().join("alpha/lightning/og_meta.json"))
+        .expect("read og_meta.json");
+    assert!(meta.contains("\"kind\": \"csv\""));
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "list",
+        "runs",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse list runs json");
+    assert_eq!(payload["runs"][0]["status"].as_str(), Some("finished"));
+
+    let again = ogtui([
+        "import",
+        "csv",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--file",
+        csv,
+        "--run",
+        "lightning",
+    ]);
+    assert!(!again.status.success());
+    assert!(String::from_utf8_lossy(&again.stderr).contains("already has metric data"));
+}
+
+#[test]
+fn import_tensorboard_copies_each_event_dir_as_a_run() {
+    let temp = TestDir::new();
+    let root = temp.path().join("runs");
+    let root = root.to_str().expect("runs path should be utf8");
+    let logdir = temp.path().join("tb-logs");
+    for (dir, value) in [("train", 0.5), ("validation", 0.75)] {
+        fs::create_dir_all(logdir.join(dir)).expect("create tb run dir");
+        write_tfevents_file(
+            &logdir
+                .join(dir)
+                .join("events.out.tfevents.1700000000.host.1.v2"),
+            &[(1, "epoch_loss", 1.0), (2, "epoch_loss", value)],
+        );
+    }
+    let logdir = logdir.to_str().expect("logdir should be utf8");
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "import",
+        "tensorboard",
+        "--path",
+        root,
+        "--project",
+        "keras",
+        "--logdir",
+        logdir,
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse import json");
+    let names: Vec<&str> = payload["runs"]
+        .as_array()
+        .expect("runs")
+        .iter()
+        .map(|run| run["run"].as_str().expect("run name"))
+        .collect();
+    assert_eq!(names, vec!["train", "validation"]);
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "get",
+        "metric",
+        "--path",
+        root,
+        "--project",
+        "keras",
+        "--run",
+        "validation",
+        "--metric",
+        "epoch_loss",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse get metric json");
+    assert_eq!(payload["last"].as_f64(), Some(0.75));
+    let meta = fs::read_to_string(temp.path().join("runs/keras/train/og_meta.json"))
+        .expect("read og_meta.json");
+    assert!(meta.contains("\"kind\": \"tensorboard\""));
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "import",
+        "tensorboard",
+        "--path",
+        root,
+        "--project",
+        "keras",
+        "--logdir",
+        logdir,
+        "--link",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse re-import json");
+    assert_eq!(payload["runs"].as_array().map(Vec::len), Some(0));
+    assert_eq!(payload["skipped"].as_array().map(Vec::len), Some(2));
+
+    assert_success(&ogtui([
+        "import",
+        "tensorboard",
+        "--path",
+        root,
+        "--project",
+        "linked",
+        "--logdir",
+        logdir,
+        "--link",
+    ]));
+    let linked = temp
+        .path()
+        .join("runs/linked/train/events.out.tfevents.1700000000.host.1.v2");
+    assert!(linked.is_symlink());
+}
+
+#[test]
+fn reader_plugins_from_env_load_custom_formats() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    let run_dir = temp.path().join("alpha").join("custom");
+    fs::create_dir_all(&run_dir).expect("create run directory");
+    let log = run_dir.join("train.mylog");
+    fs::write(&log, "{\"step\": 3, \"metrics\": {\"train/loss\": 0.25}}\n")
+        .expect("write custom log");
+
+    let with_plugin = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_ogtui"))
+            .args(args)
+            .env("OG_READERS", "*.mylog=cat")
+            .output()
+            .expect("run ogtui")
+    };
+    let stdout = assert_success(&with_plugin(&[
+        "--json",
+        "get",
+        "run",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "custom",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse get run json");
+    assert_eq!(
+        payload["latest_metrics"]["train/loss"]["value"].as_f64(),
+        Some(0.25)
+    );
+
+    let log = log.to_str().expect("log path should be utf8");
+    let stdout = assert_success(&with_plugin(&["--json", "list", "readers", "--file", log]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse list readers json");
+    assert_eq!(payload["readers"][0].as_str(), Some("*.mylog=cat"));
+    assert_eq!(payload["file"]["summary"]["points"].as_u64(), Some(1));
+
+    let stderr = assert_failure(&ogtui(["list", "readers", "--file", log]));
+    assert!(stderr.contains("no reader recognizes"));
+}
+
+#[test]
+fn serve_queries_answers_agent_queries_over_a_socket() {
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::net::UnixStream;
+
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    sample_run(temp.path());
+    let socket = temp.path().join("query.sock");
+    let mut server = Command::new(env!("CARGO_BIN_EXE_ogtui"))
+        .args(["serve-queries", "--socket"])
+        .arg(&socket)
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .expect("start query server");
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    let stream = loop {
+        if let Ok(stream) = UnixStream::connect(&socket) {
+            break stream;
+        }
+        assert!(
+            std::time::Instant::now() < deadline,
+            "query socket never came up"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    };
+
+    let mut writer = stream.try_clone().expect("clone stream");
+    let mut reader = BufReader::new(stream);
+    let mut ask = |argv: Value| {
+        let request = serde_json::json!({"type": "query", "argv": argv});
+        writeln!(writer, "{request}").expect("send query");
+        let mut line = String::new();
+        reader.read_line(&mut line).expect("read response");
+        serde_json::from_str::<Value>(&line).expect("json response")
+    };
+    let run = ask(serde_json::json!([
+        "get",
+        "metric",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "demo-run",
+        "--metric",
+        "train/loss"
+    ]));
+    let denied = ask(serde_json::json!([
+        "note", "add", "--run", "demo-run", "hi"
+    ]));
+    server.kill().ok();
+    server.wait().ok();
+
+    assert_eq!(run["ok"], true);
+    assert_eq!(run["command"], "get.metric");
+    assert_eq!(run["data"]["last"].as_f64(), Some(0.75));
+    assert_eq!(denied["ok"], false);
+    assert_eq!(denied["error"], "command_not_allowed: note");
+}
+
+#[test]
+fn get_run_recovers_past_corrupt_records() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    let run_dir = create_run(temp.path(), "alpha", "crashed", &[(1, "train/loss", 1.0)]);
+    let later = temp.path().join("later.tfevents");
+    write_tfevents_file(&later, &[(2, "train/loss", 0.5)]);
+    let later_bytes = fs::read(&later).expect("read later record");
+
+    let mut file = fs::OpenOptions::new()
+        .append(true)
+        .open(run_dir.join("events.out.tfevents.test"))
+        .expect("open event file");
+    file.write_all(&[0xab; 7]).expect("write garbage");
+    file.write_all(&later_bytes).expect("write later record");
+    file.write_all(&later_bytes[..3])
+        .expect("write truncated tail");
+    drop(file);
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "get",
+        "run",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "crashed",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse get run json");
+    assert_eq!(
+        payload["latest_metrics"]["train/loss"]["step"].as_f64(),
+        Some(2.0)
+    );
+    let corruption = &payload["corruption"];
+    assert_eq!(corruption["damaged_files"].as_u64(), Some(1));
+    assert_eq!(corruption["resyncs"].as_u64(), Some(1));
+    assert_eq!(corruption["skipped_bytes"].as_u64(), Some(7));
+    assert_eq!(corruption["truncated_bytes"].as_u64(), Some(3));
+
+    let text = assert_success(&ogtui([
+        "get",
+        "run",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "crashed",
+    ]));
+    assert!(text.contains("corruption: recovered 1 damaged event file: 1 resync, 7 bytes skipped"));
+}
+
+#[test]
+fn compact_gzips_finished_runs_and_reads_them_back() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    let finished = create_run(
+        temp.path(),
+        "alpha",
+        "finished",
+        &[(1, "train/loss", 1.0), (2, "train/loss", 0.5)],
+    );
+    create_run(temp.path(), "alpha", "live", &[(1, "train/loss", 1.0)]);
+    let event_file = finished.join("events.out.tfevents.test");
+    let day_ago = SystemTime::now() - std::time::Duration::from_secs(86_400);
+    File::options()
+        .write(true)
+        .open(&event_file)
+        .expect("open event file")
+        .set_modified(day_ago)
+        .expect("age event file");
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "compact",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse compact json");
+    assert_eq!(payload["runs"][0]["run"].as_str(), Some("finished"));
+    assert_eq!(payload["runs"][0]["files"].as_u64(), Some(1));
+    assert_eq!(payload["skipped"][0]["run"].as_str(), Some("live"));
+    assert_eq!(payload["skipped"][0]["reason"].as_str(), Some("running"));
+    assert!(!event_file.exists());
+    assert!(finished.join("events.out.tfevents.test.gz").exists());
+
+    let run = assert_success(&ogtui([
+        "--json",
+        "get",
+        "run",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "finished",
+    ]));
+    let payload: Value = serde_json::from_str(&run).expect("parse get run json");
+    assert_eq!(
+        payload["latest_metrics"]["train/loss"]["value"].as_f64(),
+        Some(0.5)
+    );
+}
+
+#[test]
+fn compact_rollup_thins_high_frequency_tags_but_keeps_whitelist() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    let mut samples: Vec<(i64, &str, f32)> = (1..=40)
+        .map(|step| (step, "train/loss", 1.0 / step as f32))
+        .collect();
+    samples.extend((1..=4).map(|n| (n * 10, "eval/acc", n as f32 / 4.0)));
+    let run_dir = create_run(temp.path(), "alpha", "long", &samples);
+    let day_ago = SystemTime::now() - std::time::Duration::from_secs(86_400);
+    File::options()
+        .write(true)
+        .open(run_dir.join("events.out.tfevents.test"))
+        .expect("open event file")
+        .set_modified(day_ago)
+        .expect("age event file");
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "compact",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--rollup",
+        "10",
+        "--keep",
+        "eval/*",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse compact json");
+    assert_eq!(
+        payload["runs"][0]["rollup"]["points_before"].as_u64(),
+        Some(44)
+    );
+    assert_eq!(
+        payload["runs"][0]["rollup"]["points_after"].as_u64(),
+        Some(8)
+    );
+
+    let metric = |name: &str| {
+        let stdout = assert_success(&ogtui([
+            "--json",
+            "get",
+            "metric",
+            "--path",
+            root,
+            "--project",
+            "alpha",
+            "--run",
+            "long",
+            "--metric",
+            name,
+        ]));
+        let payload: Value = serde_json::from_str(&stdout).expect("parse get metric json");
+        payload["points"]
+            .as_array()
+            .expect("points")
+            .iter()
+            .map(|point| point["step"].as_f64().expect("step"))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(metric("train/loss"), vec![10.0, 20.0, 30.0, 40.0]);
+    assert_eq!(metric("eval/acc"), vec![10.0, 20.0, 30.0, 40.0]);
+}
+
+#[test]
+fn apply_patches_the_codebase_and_rollback_restores_it() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    let original = "lr = 1e-3\nepochs = 10\n";
+    fs::write(temp.path().join("train.py"), original).expect("write train.py");
+    let diff = temp.path().join("fix.diff");
+    fs::write(
+        &diff,
+        "The loss diverges; lower the learning rate.\n```diff\n--- a/train.py\n+++ b/train.py\n@@ -1,2 +1,2 @@\n-lr = 1e-3\n+lr = 3e-4\n epochs = 10\n```\n",
+    )
+    .expect("write diff");
+    let diff = diff.to_str().expect("diff path should be utf8");
+
+    let dry: Value = serde_json::from_str(&assert_success(&ogtui([
+        "--json",
+        "apply",
+        diff,
+        "--codebase-root",
+        root,
+        "--dry-run",
+    ])))
+    .expect("dry run json");
+    assert_eq!(dry["checkpoint"], Value::Null);
+    assert_eq!(
+        fs::read_to_string(temp.path().join("train.py")).expect("read"),
+        original
+    );
+
+    let applied: Value = serde_json::from_str(&assert_success(&ogtui([
+        "--json",
+        "apply",
+        diff,
+        "--codebase-root",
+        root,
+    ])))
+    .expect("apply json");
+    assert_eq!(applied["changes"][0]["path"], "train.py");
+    assert_eq!(applied["changes"][0]["kind"], "modified");
+    let checkpoint = applied["checkpoint"].as_str().expect("checkpoint");
+    assert!(Path::new(checkpoint).join("refactor.json").exists());
+    assert_eq!(
+        fs::read_to_string(temp.path().join("train.py")).expect("read"),
+        "lr = 3e-4\nepochs = 10\n"
+    );
+
+    // Applying again fails on the first hunk and leaves the file alone.
+    let stderr = assert_failure(&ogtui(["apply", diff, "--codebase-root", root]));
+    assert!(stderr.contains("does not match"), "{stderr}");
+
+    let stdout = assert_success(&ogtui(["rollback", "--codebase-root", root]));
+    assert!(stdout.contains("restored: train.py"), "{stdout}");
+    assert_eq!(
+        fs::read_to_string(temp.path().join("train.py")).expect("read"),
+        original
+    );
+    let stderr = assert_failure(&ogtui(["rollback", "--codebase-root", root]));
+    assert!(stderr.contains("no local refactor snapshots"), "{stderr}");
+}
+
+#[test]
+fn annotate_is_listed_by_get_run() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    sample_run(temp.path());
+
+    let annotate_stdout = assert_success(&ogtui([
+        "annotate",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "demo-run",
+        "--step",
+        "2",
+        "--kind",
+        "lr-change",
+        "lr 3e-4 -> 1e-4",
+    ]));
+    assert!(annotate_stdout.contains("at step 2 (lr change)"));
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "get",
+        "run",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "demo-run",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse get run json");
+    let annotations = payload["annotations"]
+        .as_array()
+        .expect("annotations array");
+
+    assert_eq!(annotations.len(), 1);
+    assert_eq!(annotations[0]["step"].as_i64(), Some(2));
+    assert_eq!(annotations[0]["kind"].as_str(), Some("lr_change"));
+    assert_eq!(annotations[0]["message"].as_str(), Some("lr 3e-4 -> 1e-4"));
+}
+
+#[test]
+fn set_updates_control_file_and_annotates_the_change() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    let run_dir = sample_run(temp.path());
+    fs::write(
+        run_dir.join("control.json"),
+        r#"{"version": 0, "tunable": ["lr"], "params": {"lr": 0.001}}"#,
+    )
+    .expect("declare live params");
+
+    let set = [
+        "set",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "demo-run",
+    ];
+    let denied = assert_failure(&ogtui(set.iter().copied().chain(["batch=64"])));
+    assert!(denied.contains("not live-tunable: batch"));
+
+    let stdout = assert_success(&ogtui(["--json"].into_iter().chain(set).chain(["lr=1e-4"])));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse set json");
+    assert_eq!(payload["version"].as_u64(), Some(1));
+    assert_eq!(payload["changes"][0]["new"].as_f64(), Some(1e-4));
+
+    let control: Value = serde_json::from_str(
+        &fs::read_to_string(run_dir.join("control.json")).expect("read control"),
+    )
+    .expect("parse control");
+    assert_eq!(control["params"]["lr"].as_f64(), Some(1e-4));
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "get",
+        "run",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "demo-run",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse get run json");
+    let annotations = payload["annotations"].as_array().expect("annotations");
+    assert_eq!(annotations.len(), 1);
+    assert_eq!(annotations[0]["kind"].as_str(), Some("lr_change"));
+    assert_eq!(
+        annotations[0]["message"].as_str(),
+        Some("lr 0.001 -> 0.0001")
+    );
+}
+
+#[test]
+fn note_add_then_list_returns_journal_entries() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    sample_run(temp.path());
+
+    let add_stdout = assert_success(&ogtui([
+        "note",
+        "add",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "demo-run",
+        "tried lr=3e-4, diverged",
+    ]));
+    assert!(add_stdout.contains("tried lr=3e-4, diverged"));
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "note",
+        "list",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "demo-run",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse note list json");
+    let notes = payload["notes"].as_array().expect("notes array");
+
+    assert_eq!(payload["count"].as_u64(), Some(1));
+    assert_eq!(notes[0]["text"].as_str(), Some("tried lr=3e-4, diverged"));
+    assert_eq!(notes[0]["step"].as_i64(), Some(2));
+}
+
+#[test]
+fn fork_copies_config_with_overrides_into_new_run() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    let run_dir = sample_run(temp.path());
+    fs::write(
+        run_dir.join("og_meta.json"),
+        r#"{
+  "launch": {"training_file": "/tmp/train.py", "codebase_root": "/tmp"},
+  "config": {"lr": "3e-4", "batch_size": "32"},
+  "notes": [{"created_unix": 1, "text": "baseline"}]
+}"#,
+    )
+    .expect("write source metadata");
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "fork",
+        "demo-run",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--name",
+        "demo-run-lr",
+        "--set",
+        "lr=1e-4",
+        "--set",
+        "warmup=100",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse fork json");
+    assert_eq!(payload["run"].as_str(), Some("demo-run-lr"));
+    assert_eq!(payload["config"]["lr"].as_str(), Some("1e-4"));
+    assert_eq!(payload["config"]["batch_size"].as_str(), Some("32"));
+    assert_eq!(payload["config"]["warmup"].as_str(), Some("100"));
+
+    let forked_meta = fs::read_to_string(temp.path().join("alpha/demo-run-lr/og_meta.json"))
+        .expect("read forked metadata");
+    let forked: Value = serde_json::from_str(&forked_meta).expect("parse forked metadata");
+    assert_eq!(forked["forked_from"].as_str(), Some("demo-run"));
+    assert_eq!(
+        forked["launch"]["training_file"].as_str(),
+        Some("/tmp/train.py")
+    );
+    assert!(forked.get("notes").is_none());
+
+    let stderr = assert_failure(&ogtui([
+        "fork",
+        "demo-run",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--name",
+        "demo-run-lr",
+    ]));
+    assert!(stderr.contains("already exists"));
+}
+
+#[test]
+fn diff_reports_config_and_environment_changes() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    let base = sample_run(temp.path());
+    let other = create_run(
+        temp.path(),
+        "alpha",
+        "demo-run-2",
+        &[(1, "train/loss", 1.0)],
+    );
+    fs::write(base.join("og_meta.json"), r#"{"config": {"lr": "3e-4"}}"#).expect("write meta");
+    fs::write(other.join("og_meta.json"), r#"{"config": {"lr": "1e-4"}}"#).expect("write meta");
+    fs::write(
+        base.join("og_env.json"),
+        r#"{"packages": {"torch": "2.2.0"}, "env_vars": {"NCCL_DEBUG": "INFO"}}"#,
+    )
+    .expect("write env");
+    fs::write(
+        other.join("og_env.json"),
+        r#"{"packages": {"torch": "2.3.0"}, "env_vars": {}}"#,
+    )
+    .expect("write env");
+
+    let stdout = assert_success(&ogtui([
+        "diff",
+        "demo-run",
+        "demo-run-2",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+    ]));
+    assert!(stdout.contains("~ lr: 3e-4 -> 1e-4"));
+    assert!(stdout.contains("~ torch: 2.2.0 -> 2.3.0"));
+    assert!(stdout.contains("- NCCL_DEBUG=INFO"));
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "get",
+        "run",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "demo-run",
+        "--env",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse get run json");
+    assert_eq!(
+        payload["environment"]["packages"]["torch"].as_str(),
+        Some("2.2.0")
+    );
+}
+
+#[test]
+fn leaderboard_ranks_runs_by_best_metric_with_hparams() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    let low = create_run(
+        temp.path(),
+        "alpha",
+        "run-low",
+        &[(1, "val/acc", 0.50), (2, "val/acc", 0.61)],
+    );
+    let high = create_run(
+        temp.path(),
+        "alpha",
+        "run-high",
+        &[(1, "val/acc", 0.70), (2, "val/acc", 0.65)],
+    );
+    create_run(temp.path(), "alpha", "run-other", &[(1, "train/loss", 1.0)]);
+    fs::write(low.join("og_meta.json"), r#"{"config": {"lr": "1e-3"}}"#).expect("write meta");
+    fs::write(high.join("og_meta.json"), r#"{"config": {"lr": "3e-4"}}"#).expect("write meta");
+
+    let stdout = assert_success(&ogtui([
+        "leaderboard",
+        "--metric",
+        "val/acc",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+    ]));
+    let high_pos = stdout.find("run-high").expect("run-high listed");
+    let low_pos = stdout.find("run-low").expect("run-low listed");
+    assert!(high_pos < low_pos);
+    assert!(stdout.contains("lr"));
+    assert!(!stdout.contains("run-other"));
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "leaderboard",
+        "--metric",
+        "val/acc",
+        "--mode",
+        "min",
+        "--top",
+        "1",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse leaderboard json");
+    let entries = payload["entries"].as_array().expect("entries array");
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["run"].as_str(), Some("run-low"));
+    assert_eq!(entries[0]["best_step"].as_i64(), Some(1));
+    assert_eq!(entries[0]["hparams"]["lr"].as_str(), Some("1e-3"));
+
+    // An unreadable run is listed as skipped instead of failing the board.
+    let broken = temp.path().join("alpha/run-broken");
+    fs::create_dir_all(&broken).expect("create broken run");
+    fs::write(broken.join("events.out.tfevents.1.gz"), b"not gzip").expect("write events");
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "leaderboard",
+        "--metric",
+        "val/acc",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse leaderboard json");
+    assert_eq!(payload["entries"].as_array().map(Vec::len), Some(2));
+    let skipped = payload["skipped"].as_array().expect("skipped array");
+    assert_eq!(skipped.len(), 1);
+    assert!(
+        skipped[0]["path"]
+            .as_str()
+            .is_some_and(|path| path.ends_with("run-broken"))
+    );
+    fs::remove_dir_all(&broken).expect("remove broken run");
+
+    // No recorded direction: a loss is ranked lowest first.
+    create_run(temp.path(), "alpha", "run-worse", &[(1, "train/loss", 2.0)]);
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "leaderboard",
+        "--metric",
+        "train/loss",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse leaderboard json");
+    assert_eq!(payload["mode"].as_str(), Some("min"));
+    assert_eq!(payload["entries"][0]["run"].as_str(), Some("run-other"));
+}
+
+#[test]
+fn search_runs_filters_on_config_predicates() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    let small = create_run(
+        temp.path(),
+        "alpha",
+        "run-small-lr",
+        &[(1, "train/loss", 1.0)],
+    );
+    let large = create_run(
+        temp.path(),
+        "alpha",
+        "run-large-lr",
+        &[(1, "train/loss", 1.0)],
+    );
+    fs::write(
+        small.join("og_meta.json"),
+        r#"{"config": {"lr": "3e-4", "optimizer": "adamw"}}"#,
+    )
+    .expect("write meta");
+    fs::write(
+        large.join("og_meta.json"),
+        r#"{"config": {"lr": "1e-2", "optimizer": "adamw"}}"#,
+    )
+    .expect("write meta");
+
+    let stdout = assert_success(&ogtui([
+        "search",
+        "runs",
+        "--where",
+        "lr<1e-3 and optimizer=adamw",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+    ]));
+    assert!(stdout.contains("run-small-lr"));
+    assert!(!stdout.contains("run-large-lr"));
+    assert!(stdout.contains("3e-4"));
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "list",
+        "runs",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--config",
+        "lr>=1e-3",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse list runs json");
+    let runs = payload["runs"].as_array().expect("runs array");
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0]["id"].as_str(), Some("run-large-lr"));
+}
+
+#[test]
+fn run_colors_are_stable_and_overridable() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    let run_dir = sample_run(temp.path());
+    let compare_color = || {
+        let stdout = assert_success(&ogtui([
+            "--json",
+            "compare",
+            "--runs",
+            "demo-run",
+            "--metric",
+            "train/loss",
+            "--path",
+            root,
+            "--project",
+            "alpha",
+        ]));
+        let payload: Value = serde_json::from_str(&stdout).expect("parse compare json");
+        payload["comparisons"][0]["color"]
+            .as_str()
+            .expect("color")
+            .to_string()
+    };
+
+    let hashed = compare_color();
+    assert!(hashed.starts_with('#') && hashed.len() == 7);
+    assert_eq!(compare_color(), hashed);
+
+    fs::write(run_dir.join("og_meta.json"), r##"{"color": "#e45756"}"##).expect("write meta");
+    assert_eq!(compare_color(), "#e45756");
+}
+
+#[test]
+fn compare_target_ranks_runs_by_time_to_reach_it() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    // Event wall times equal their steps.
+    create_run(
+        temp.path(),
+        "alpha",
+        "slow",
+        &[
+            (1, "val/loss", 4.0),
+            (5, "val/loss", 3.0),
+            (9, "val/loss", 2.4),
+        ],
+    );
+    create_run(
+        temp.path(),
+        "alpha",
+        "fast",
+        &[
+            (1, "val/loss", 4.0),
+            (3, "val/loss", 2.5),
+            (5, "val/loss", 2.0),
+        ],
+    );
+    create_run(
+        temp.path(),
+        "alpha",
+        "stuck",
+        &[(1, "val/loss", 4.0), (9, "val/loss", 3.5)],
+    );
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "compare",
+        "--runs",
+        "stuck,slow,fast",
+        "--metric",
+        "val/loss",
+        "--target",
+        "2.5",
+        "--x",
+        "wall_time",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse compare json");
+    assert_eq!(payload["x"].as_str(), Some("wall_time"));
+    let comparisons = payload["comparisons"].as_array().expect("comparisons");
+    let order: Vec<&str> = comparisons
+        .iter()
+        .map(|c| c["run"].as_str().expect("run").rsplit('/').next().unwrap())
+        .collect();
+    assert_eq!(order, ["fast", "slow", "stuck"]);
+    assert_eq!(comparisons[0]["target"]["step"].as_i64(), Some(3));
+    assert_eq!(comparisons[0]["target"]["wall_secs"].as_f64(), Some(2.0));
+    assert_eq!(comparisons[1]["target"]["step"].as_i64(), Some(9));
+    assert_eq!(comparisons[2]["target"]["reached"].as_bool(), Some(false));
+
+    let stdout = assert_success(&ogtui([
+        "compare",
+        "--runs",
+        "slow",
+        "--metric",
+        "val/loss",
+        "--target",
+        "2.5",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+    ]));
+    assert!(
+        stdout.contains("reached 2.5000 after 8s at step 9"),
+        "{stdout}"
+    );
+
+    let by_epoch = |runs: &str| {
+        ogtui([
+            "compare",
+            "--runs",
+            runs,
+            "--metric",
+            "val/loss",
+            "--target",
+            "2.5",
+            "--x",
+            "epoch",
+            "--path",
+            root,
+            "--project",
+            "alpha",
+        ])
+    };
+    let stderr = assert_failure(&by_epoch("fast,slow"));
+    assert!(stderr.contains("--x epoch needs epoch data"), "{stderr}");
+    fs::write(
+        temp.path().join("alpha/fast/og_meta.json"),
+        r#"{"config": {"steps_per_epoch": "2"}}"#,
+    )
+    .expect("write meta");
+    let stdout = assert_success(&by_epoch("fast,slow"));
+    assert!(stdout.contains("at step 3, epoch 1.5"), "{stdout}");
+    assert!(
+        stdout.contains("warning: ") && stdout.contains("slow has no epoch counter"),
+        "{stdout}"
+    );
+
+    let stderr = assert_failure(&ogtui([
+        "compare",
+        "--runs",
+        "slow",
+        "--metric",
+        "val/loss",
+        "--x",
+        "step",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+    ]));
+    assert!(stderr.contains("--target"), "{stderr}");
+}
+
+#[test]
+fn regress_fails_only_when_the_candidate_is_worse_at_matched_steps() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    let curve = |offset: f32| -> Vec<(i64, &'static str, f32)> {
+        (1..=10)
+            .map(|step| (step * 10, "val/loss", 5.0 / step as f32 + offset))
+            .collect()
+    };
+    create_run(temp.path(), "alpha", "baseline", &curve(0.0));
+    create_run(temp.path(), "alpha", "same", &curve(0.001));
+    create_run(temp.path(), "alpha", "worse", &curve(0.2));
+    let regress = |candidate: &str| {
+        ogtui([
+            "regress",
+            "--baseline",
+            "baseline",
+            "--candidate",
+            candidate,
+            "--metric",
+            "val/loss",
+            "--tolerance",
+            "1%",
+            "--path",
+            root,
+            "--project",
+            "alpha",
+        ])
+    };
+
+    let stdout = assert_success(&regress("same"));
+    assert!(stdout.contains("lower is better"), "{stdout}");
+    assert!(stdout.contains("result: ok"), "{stdout}");
+
+    let output = regress("worse");
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = assert_failure(&output);
+    assert!(stdout.contains("result: REGRESSED"), "{stdout}");
+    assert!(stderr.contains("regressed on val/loss"), "{stderr}");
+}
+
+#[test]
+fn digest_reports_recent_runs_best_values_and_alerts() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("clock")
+        .as_secs_f64();
+    let run_dir = temp.path().join("alpha").join("fresh");
+    fs::create_dir_all(&run_dir).expect("create run directory");
+    fs::write(
+        run_dir.join("metrics.jsonl"),
+        format!(
+            "{{\"step\": 1, \"wall_time\": {}, \"metrics\": {{\"val/loss\": 2.0}}}}\n\
+             {{\"step\": 2, \"wall_time\": {}, \"metrics\": {{\"val/loss\": 1.5}}}}\n",
+            now - 600.0,
+            now - 300.0
+        ),
+    )
+    .expect("write metrics.jsonl");
+    fs::write(
+        run_dir.join("og_alerts.jsonl"),
+        format!(
+            "{{\"metric\": \"val/loss\", \"message\": \"val/loss stalled\", \"timestamp\": {}}}\n",
+            now - 120.0
+        ),
+    )
+    .expect("write alerts");
+    fs::write(
+        temp.path().join("alpha").join("project.toml"),
+        "[objective]\nmetric = \"val/loss\"\nmode = \"min\"\n",
+    )
+    .expect("write project.toml");
+
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "digest",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse digest json");
+    let digest = &payload["digest"];
+    assert_eq!(digest["project"].as_str(), Some("alpha"));
+    assert_eq!(digest["started"][0].as_str(), Some("fresh"));
+    assert_eq!(digest["best"][0]["value"].as_f64(), Some(1.5));
+    assert_eq!(
+        digest["alerts"][0]["message"].as_str(),
+        Some("val/loss stalled")
+    );
+
+    let stderr = assert_failure(&ogtui([
+        "digest",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--send",
+    ]));
+    assert!(stderr.contains("no [digest] table"), "{stderr}");
+}
+
+#[test]
+fn snapshot_renders_the_graphs_view_as_text() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    sample_run(temp.path());
+    let snapshot = |extra: &[&str]| {
+        let mut args = vec![
+            "snapshot",
+            "--path",
+            root,
+            "--project",
+            "alpha",
+            "--run",
+            "demo-run",
+            "--width",
+            "80",
+            "--height",
+            "24",
+        ];
+        args.extend_from_slice(extra);
+        assert_success(&ogtui(args))
+    };
+
+    let grid = snapshot(&[]);
+    assert_eq!(grid.lines().count(), 24);
+    assert!(grid.contains("train/loss") && grid.contains("gpu_util"));
+    assert!(!grid.contains('\u{1b}'));
+    assert_eq!(snapshot(&[]), grid, "snapshots should be reproducible");
+
+    let focused = snapshot(&["--metric", "sys/gpu_util"]);
+    assert!(focused.contains("sys/gpu_util") && !focused.contains("train/loss"));
+
+    let out = temp.path().join("grid.ansi");
+    let out_str = out.to_str().expect("output path should be utf8");
+    snapshot(&["--ansi", "-o", out_str]);
+    let ansi = fs::read_to_string(&out).expect("read snapshot file");
+    assert!(ansi.contains("\u{1b}[0;") && ansi.contains("train/loss"));
+
+    let stderr = assert_failure(&ogtui([
+        "snapshot",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "demo-run",
+        "--metric",
+        "trian/loss",
+    ]));
+    assert!(stderr.contains("0 metrics matched 'trian/loss'; did you mean 'train/loss'?"));
+}
+
+#[test]
+fn replay_checks_the_session_file_before_opening_the_tui() {
+    let temp = TestDir::new();
+    let help = assert_success(&ogtui(["--help"]));
+    assert_help_lists_command(
+        &help,
+        "replay",
+        "Play a session recorded with --record back into the TUI",
+    );
+    assert!(assert_success(&ogtui(["run", "--help"])).contains("--record <FILE>"));
+
+    let not_session = temp.path().join("notes.txt");
+    fs::write(&not_session, "hello\n").expect("write file");
+    let not_session = not_session.to_str().expect("path should be utf8");
+    let stderr = assert_failure(&ogtui(["replay", not_session]));
+    assert!(stderr.contains("is not an og session recording"));
+
+    let stderr = assert_failure(&ogtui(["replay", not_session, "--speed", "0"]));
+    assert!(stderr.contains("--speed must be a positive number"));
+}
+
+#[test]
+fn bundle_create_and_import_move_a_run_between_projects() {
+    let temp = TestDir::new();
+    let root = temp.path().to_str().expect("temp path should be utf8");
+    create_run(
+        temp.path(),
+        "alpha",
+        "r1",
+        &[(1, "loss", 1.0), (2, "loss", 0.5)],
+    );
+    let run_dir = temp.path().join("alpha").join("r1");
+    fs::write(run_dir.join("train.log"), "step 2 loss 0.5\n").expect("write log");
+    assert_success(&ogtui([
+        "note",
+        "add",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "--run",
+        "r1",
+        "lr too high",
+    ]));
+
+    let archive = temp.path().join("r1.tar");
+    let archive = archive.to_str().expect("archive path should be utf8");
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "bundle",
+        "create",
+        "r1",
+        "--path",
+        root,
+        "--project",
+        "alpha",
+        "-o",
+        archive,
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse bundle json");
+    assert_eq!(payload["manifest"]["run"].as_str(), Some("r1"));
+
+    assert_success(&ogtui([
+        "bundle",
+        "import",
+        archive,
+        "--path",
+        root,
+        "--project",
+        "beta",
+    ]));
+    let imported = temp.path().join("beta").join("r1");
+    assert_eq!(
+        fs::read_to_string(imported.join("train.log")).expect("read log"),
+        "step 2 loss 0.5\n"
+    );
+    let meta = fs::read_to_string(imported.join("og_meta.json")).expect("read meta");
+    assert!(meta.contains("lr too high"), "{meta}");
+    assert!(meta.contains("\"bundle\""), "{meta}");
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "list",
+        "runs",
+        "--path",
+        root,
+        "--project",
+        "beta",
+    ]));
+    let payload: Value = serde_json::from_str(&stdout).expect("parse list runs json");
+    assert_eq!(payload["runs"][0]["status"].as_str(), Some("finished"));
+    let stdout = assert_success(&ogtui([
+        "--json",
+        "get",
+        "metric",
+        "--path",
+        root,
+        "--project",
+        "beta",
+        "--run",
+        "r1",
+        "--metric",
+        "loss",
+    ]));
+    assert!(stdout.contains("0.5"), "{stdout}");
+
+    let stderr = assert_failure(&ogtui([
+        "bundle",
+        "import",
+        archive,
+        "--path",
+        root,
+        "--project",
+        "beta",
+    ]));
+    assert!(stderr.contains("already exists"), "{stderr}");
+}
@@ -1,5 +1,6 @@
 """Demo training script for OpenGraphs."""
 
+import json
 import math
 import os
 import random
@@ -52,6 +53,38 @@ def report(metric, value, step):
         pass
 
 
+def live_params(**defaults):
+    try:
+        from og_agent_chat.control import LiveParams
+        return LiveParams(**defaults)
+    except Exception:
+        return None
+
+
+def save_checkpoint(step, state, metrics):
+    # Stand-in for torch.save; og lists these and can restart from one.
+    run_dir = os.getenv("TB_LOG_DIR")
+    if run_dir is None:
+        return
+    path = os.path.join(run_dir, "checkpoints", f"step_{step}.json")
+    os.makedirs(os.path.dirname(path), exist_ok=True)
+    with open(path, "w") as f:
+        json.dump(state, f)
+    try:
+        from og_agent_chat.checkpoints import record_checkpoint
+        record_checkpoint(path, step=step, metrics=metrics)
+    except Exception:
+        pass
+
+
+def resumed_state():
+    path = os.getenv("OG_RESUME_CHECKPOINT")
+    if not path:
+        return None
+    with open(path) as f:
+        return json.load(f)
+
+
 def log(writer, tag, value, step):
     if writer is not None:
         writer.add_scalar(tag, value, step)
@@ -109,6 +142,8 @@ def scheduled_lr(step, base_lr):
 def train():
     lr = LEARNING_RATE
     writer = get_tb_writer()
+    # `og set --run <run> lr=...` changes the base LR while training runs.
+    params = live_params(lr=lr)
     small_batch = BATCH_SIZE < 16
     noise_std = 0.05 if small_batch else 0.02
 
@@ -117,10 +152,19 @@ def train():
 
     loss = 2.5
     acc = 0.10
+    first_step = 1
+    state = resumed_state()
+    if state is not None:
+        loss, acc, lr = state["loss"], state["acc"], state["lr"]
+        first_step = state["step"] + 1
+        print(f"[info] resumed from checkpoint at step {state['step']}")
     if psutil is not None:
         psutil.cpu_percent(interval=None)
 
-    for step in range(1, TOTAL_STEPS + 1):
+    for step in range(first_step, TOTAL_STEPS + 1):
+        if params is not None and "lr" in params.poll():
+            lr = float(params["lr"])
+            print(f"[control] base_lr -> {lr}")
         noise = random.gauss(0, noise_std)
         eff_lr = scheduled_lr(step, lr)
 
@@ -173,6 +217,12 @@ def train():
             f"base_lr={lr:.4f} | eff_lr={eff_lr:.4f}"
         )
         sys.stdout.flush()
+        if step % 25 == 0:
+            save_checkpoint(
+                step,
+                {"step": step, "loss": loss, "acc": acc, "lr": lr},
+                {"train/loss": loss, "train/accuracy": acc},
+            )
         time.sleep(SLEEP_PER_STEP)
 
     print("[info] training complete")
@@ -4,17 +4,26 @@ import asyncio
 import difflib
 import json
 import re
+import shlex
 import time
 from dataclasses import dataclass
 from pathlib import Path
-from typing import Callable, Iterable
+from typing import Awaitable, Callable, Iterable
 
 import unidiff
 
+from . import client
+from .client import OGDClientError
 from .codebase import CodebaseIndex
 from .config import ensure_dspy_configured, get_dspy
 from .models import ActionPlan, Alert, ChatMessage, ExecutionResult, RunState
 
+# Keep query results small enough to fit in the agent's context alongside logs.
+QUERY_RESULT_MAX_CHARS = 4000
+
+# Receives ("chunk", answer text) and ("status", tool progress) while a reply streams.
+StreamCallback = Callable[[str, str], Awaitable[None]]
+
 SYSTEM_PROMPT = """
 You are an ML training assistant for OpenGraphs.
 Role: Diagnose issues and suggest safe code fixes when metrics plateau/degrade.
@@ -220,6 +229,19 @@ class ToolCaller:
             tail = self.run_state.metric_tail(metric, n=n)
             return f"{metric}: {tail}"
 
+        def query_runs(command: str) -> str:
+            """Run a read-only og query, e.g. "get metric --run r1 --metric loss",
+            "compare --runs r1,r2 --metric loss", "stats --run r1 --metric loss" or
+            "search metrics --query loss"."""
+            try:
+                response = client.query(shlex.split(command))
+            except (OGDClientError, OSError, ValueError) as exc:
+                return f"Query failed: {exc}"
+            text = json.dumps(response.get("data", {}), default=str)
+            if len(text) > QUERY_RESULT_MAX_CHARS:
+                text = text[:QUERY_RESULT_MAX_CHARS] + "... (truncated)"
+            return text
+
         def log_tail(n: int = 50) -> str:
             """Return the last n log lines."""
             return self.run_state.log_tail(n)
@@ -248,6 +270,7 @@ class ToolCaller:
             alert_summary,
             list_metrics,
             metric_tail,
+            query_runs,
             log_tail,
             read_training_file,
             list_codebase_files,
@@ -259,6 +282,31 @@ class ToolCaller:
         prediction = self.react(context=context, question=question)
         return prediction.answer
 
+    async def run_stream(
+        self,
+        context: str,
+        question: str,
+        on_event: StreamCallback,
+    ) -> str:
+        """Like run(), but forward answer tokens and tool-call status as they arrive."""
+        dspy = get_dspy()
+        streaming = getattr(dspy, "streaming", None)
+        if streaming is None or not hasattr(dspy, "streamify"):
+            return self.run(context=context, question=question)
+        program = dspy.streamify(
+            self.react,
+            stream_listeners=[streaming.StreamListener(signature_field_name="answer")],
+        )
+        answer = ""
+        async for value in program(context=context, question=question):
+            if isinstance(value, streaming.StreamResponse):
+                await on_event("chunk", value.chunk)
+            elif isinstance(value, streaming.StatusMessage):
+                await on_event("status", value.message)
+            elif isinstance(value, dspy.Prediction):
+                answer = value.answer
+        return answer
+
 
 class CodeEditor:
     def __init__(
@@ -518,9 +566,13 @@ class AgentEngine:
     def get_chat_history(self) -> list[ChatMessage]:
         return list(self.chat_messages)
 
-    async def handle_chat_message(self, user_message: str) -> AgentResponse:
+    async def handle_chat_message(
+        self,
+        user_message: str,
+        on_event: StreamCallback | None = None,
+    ) -> AgentResponse:
         self.add_chat_message("user", user_message)
-        response = await self._respond(question=user_message)
+        response = await self._respond(question=user_message, on_event=on_event)
         return response
 
     async def handle_alert(self, alert: Alert | None = None) -> AgentResponse | None:
@@ -546,6 +598,7 @@ class AgentEngine:
         self,
         question: str,
         alert: Alert | None = None,
+        on_event: StreamCallback | None = None,
     ) -> AgentResponse:
         raw = ""
         plan: ActionPlan
@@ -564,7 +617,14 @@ class AgentEngine:
                     self.codebase_index,
                     alert=alert,
                 )
-                raw = self.tool_caller.run(context=context, question=question)
+                if on_event is None:
+                    raw = self.tool_caller.run(context=context, question=question)
+                else:
+                    raw = await self.tool_caller.run_stream(
+                        context=context,
+                        question=question,
+                        on_event=on_event,
+                    )
                 plan = self.action_planner.parse_response(raw)
                 if plan.action == "refactor" and not plan.code_changes:
                     diff = self.editor.propose_diff(
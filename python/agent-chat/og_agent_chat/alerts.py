@@ -1,6 +1,7 @@
 from __future__ import annotations
 
 import json
+import math
 import os
 import time
 from dataclasses import dataclass
@@ -74,19 +75,112 @@ class StallRule:
         )
 
 
-AlertRule = ThresholdRule | StallRule
+@dataclass
+class GradNormRule:
+    """Built-in watchdog for a gradient-norm metric: alerts when the norm
+    explodes past `max_norm` (or goes NaN/inf) or stays below `min_norm`
+    for `vanish_points` logged values."""
+
+    metric: str
+    max_norm: float = 100.0
+    min_norm: float = 1e-7
+    vanish_points: int = 50
+    cooldown_secs: float = 60.0
+    message: str | None = None
+
+    def exploding(self, current: float) -> bool:
+        return not math.isfinite(current) or current > self.max_norm
+
+    def evaluate(self, values: Sequence[float]) -> bool:
+        if not values:
+            return False
+        if self.exploding(values[-1]):
+            return True
+        tail = values[-self.vanish_points :]
+        return len(tail) >= self.vanish_points and all(abs(v) < self.min_norm for v in tail)
+
+    def to_alert(self, current: float) -> Alert:
+        if self.exploding(current):
+            threshold = self.max_norm
+            default = f"{self.metric} exploding ({current} > {self.max_norm})"
+        else:
+            threshold = self.min_norm
+            default = (
+                f"{self.metric} vanishing (below {self.min_norm} "
+                f"for {self.vanish_points} points)"
+            )
+        return Alert(
+            metric=self.metric,
+            threshold=threshold,
+            current=current,
+            message=self.message or default,
+            timestamp=time.time(),
+        )
+
+
+AlertRule = ThresholdRule | StallRule | GradNormRule
+
+
+@dataclass
+class GradNormLimits:
+    max_norm: float = 100.0
+    min_norm: float = 1e-7
+    vanish_points: int = 50
+
+
+def is_grad_norm_metric(metric: str) -> bool:
+    name = metric.lower()
+    return any(key in name for key in ("grad_norm", "gradnorm", "grad-norm", "grad/norm"))
+
+
+def grad_norm_limits_from_env() -> GradNormLimits | None:
+    """Limits for the built-in gradient-norm rules; OG_GRAD_WATCH=0 turns them off."""
+    if os.getenv("OG_GRAD_WATCH", "1").strip().lower() in {"0", "off", "false", "no"}:
+        return None
+    defaults = GradNormLimits()
+    try:
+        return GradNormLimits(
+            max_norm=float(os.getenv("OG_GRAD_EXPLODE", defaults.max_norm)),
+            min_norm=float(os.getenv("OG_GRAD_VANISH", defaults.min_norm)),
+            vanish_points=max(int(os.getenv("OG_GRAD_VANISH_POINTS", defaults.vanish_points)), 1),
+        )
+    except ValueError:
+        return defaults
 
 
 class AlertDetector:
-    def __init__(self, rules: Sequence[AlertRule] | None = None) -> None:
+    def __init__(
+        self,
+        rules: Sequence[AlertRule] | None = None,
+        grad_norm: GradNormLimits | None = None,
+    ) -> None:
         self.rules = list(rules or [])
+        self.grad_norm = grad_norm
         self.last_triggered: dict[str, float] = {}
 
     def set_rules(self, rules: Sequence[AlertRule]) -> None:
         self.rules = list(rules)
 
+    def _watch_grad_norms(self, run_state: RunState) -> None:
+        """Add a built-in rule for each gradient-norm metric no rule covers yet."""
+        if self.grad_norm is None:
+            return
+        covered = {rule.metric for rule in self.rules}
+        for name in run_state.metrics:
+            if name in covered or not is_grad_norm_metric(name):
+                continue
+            self.rules.append(
+                GradNormRule(
+                    metric=name,
+                    max_norm=self.grad_norm.max_norm,
+                    min_norm=self.grad_norm.min_norm,
+                    vanish_points=self.grad_norm.vanish_points,
+                )
+            )
+
     def check(self, run_state: RunState, metric: str | None = None) -> Alert | None:
         now = time.time()
+        self._watch_grad_norms(run_state)
         for rule in self.rules:
             if metric and rule.metric != metric:
                 continue
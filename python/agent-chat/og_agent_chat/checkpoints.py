@@ -0,0 +1,61 @@
+"""Checkpoint registry for training scripts.
+
+Each saved checkpoint is appended to the run's `checkpoints.jsonl`, which the
+TUI lists in its checkpoints panel. Restarting from one there re-launches
+training with `OG_RESUME_CHECKPOINT` pointing at the chosen path::
+
+    start = resume_checkpoint()
+    if start is not None:
+        load(model, start)
+    ...
+    torch.save(model.state_dict(), path)
+    record_checkpoint(path, step=step, metrics={"val/loss": val_loss})
+"""
+
+from __future__ import annotations
+
+import json
+import os
+import time
+from pathlib import Path
+from typing import Any
+
+# Set by the daemon when training is restarted from a checkpoint.
+RESUME_ENV = "OG_RESUME_CHECKPOINT"
+REGISTRY_FILE_NAME = "checkpoints.jsonl"
+
+
+def default_registry_path() -> Path | None:
+    log_dir = os.getenv("TB_LOG_DIR")
+    if log_dir:
+        return Path(log_dir) / REGISTRY_FILE_NAME
+    return None
+
+
+def record_checkpoint(
+    path: str | Path,
+    *,
+    step: int,
+    metrics: dict[str, float] | None = None,
+    registry: str | Path | None = None,
+) -> None:
+    """Append a saved checkpoint to the registry. A no-op outside og."""
+    target = Path(registry) if registry is not None else default_registry_path()
+    if target is None:
+        return
+    entry: dict[str, Any] = {
+        "step": int(step),
+        "path": str(Path(path).resolve()),
+        "wall_time": time.time(),
+    }
+    if metrics:
+        entry["metrics"] = {name: float(value) for name, value in metrics.items()}
+    target.parent.mkdir(parents=True, exist_ok=True)
+    with target.open("a", encoding="utf-8") as handle:
+        handle.write(json.dumps(entry) + "\n")
+
+
+def resume_checkpoint() -> Path | None:
+    """Checkpoint chosen in the TUI for this launch, if any."""
+    raw = os.getenv(RESUME_ENV)
+    return Path(raw) if raw else None
@@ -13,6 +13,9 @@ def _default_socket_path() -> str:
 
 DEFAULT_SOCKET = os.getenv("OGD_SOCKET", _default_socket_path())
 
+# Set by ogtui when it serves read-only `og` queries (list/get/compare/search/...).
+QUERY_SOCKET_ENV = "OG_QUERY_SOCKET"
+
 
 class OGDClientError(RuntimeError):
     pass
@@ -82,3 +85,17 @@ def append_log(line: str, socket_path: str | Path = DEFAULT_SOCKET) -> dict[str,
 
 def set_training_file(path: str | Path, socket_path: str | Path = DEFAULT_SOCKET) -> dict[str, Any]:
     return send_request({"type": "set_training_file", "path": str(path)}, socket_path)
+
+
+def query(argv: list[str], socket_path: str | Path | None = None) -> dict[str, Any]:
+    """Run a read-only `og` command through the Rust query socket.
+
+    Returns the same payload `og --json <argv>` prints.
+    """
+    path = socket_path or os.getenv(QUERY_SOCKET_ENV)
+    if not path:
+        raise OGDClientError(f"{QUERY_SOCKET_ENV} is not set")
+    response = send_request({"type": "query", "argv": list(argv)}, path)
+    if not response.get("ok"):
+        raise OGDClientError(str(response.get("error", "query failed")))
+    return response
@@ -0,0 +1,105 @@
+"""Live-tunable parameters for training scripts.
+
+The training script declares which values may change mid-run; `og set --run X
+lr=1e-4` rewrites the run's `control.json` and the next `poll()` picks it up::
+
+    params = LiveParams(lr=3e-4, weight_decay=0.01)
+    for step in range(steps):
+        for name, value in params.poll().items():
+            print(f"[control] {name} -> {value}")
+        optimizer.param_groups[0]["lr"] = params["lr"]
+"""
+
+from __future__ import annotations
+
+import json
+import os
+import time
+from pathlib import Path
+from typing import Any
+
+# Set by the runtime to `<run dir>/control.json`.
+CONTROL_FILE_ENV = "OG_CONTROL_FILE"
+CONTROL_FILE_NAME = "control.json"
+
+
+def default_control_path() -> Path | None:
+    explicit = os.getenv(CONTROL_FILE_ENV)
+    if explicit:
+        return Path(explicit)
+    log_dir = os.getenv("TB_LOG_DIR")
+    if log_dir:
+        return Path(log_dir) / CONTROL_FILE_NAME
+    return None
+
+
+def _read(path: Path) -> dict[str, Any] | None:
+    try:
+        data = json.loads(path.read_text(encoding="utf-8"))
+    except (OSError, ValueError):
+        return None
+    return data if isinstance(data, dict) else None
+
+
+def _write(path: Path, data: dict[str, Any]) -> None:
+    path.parent.mkdir(parents=True, exist_ok=True)
+    tmp = path.with_suffix(".json.tmp")
+    tmp.write_text(json.dumps(data, indent=2), encoding="utf-8")
+    os.replace(tmp, path)
+
+
+class LiveParams:
+    """Declared defaults, overridden by whatever `og set` wrote since."""
+
+    def __init__(
+        self,
+        path: str | Path | None = None,
+        *,
+        poll_interval: float = 1.0,
+        **defaults: Any,
+    ) -> None:
+        self.path = Path(path) if path is not None else default_control_path()
+        self.poll_interval = poll_interval
+        self.values: dict[str, Any] = dict(defaults)
+        self.version = 0
+        self._next_poll = 0.0
+        self._declare()
+
+    def _declare(self) -> None:
+        if self.path is None:
+            return
+        existing = _read(self.path) or {}
+        params = dict(existing.get("params") or {})
+        # A restarted run keeps values set while it was running.
+        for name, value in self.values.items():
+            params.setdefault(name, value)
+        tunable = sorted(set(existing.get("tunable") or []) | set(self.values))
+        self.version = int(existing.get("version") or 0)
+        self.values.update({name: params[name] for name in self.values})
+        _write(
+            self.path,
+            {**existing, "version": self.version, "tunable": tunable, "params": params},
+        )
+
+    def __getitem__(self, name: str) -> Any:
+        return self.values[name]
+
+    def poll(self, *, force: bool = False) -> dict[str, Any]:
+        """Re-read the control file (at most every `poll_interval` seconds)
+        and return the declared params whose value changed."""
+        if self.path is None:
+            return {}
+        now = time.monotonic()
+        if not force and now < self._next_poll:
+            return {}
+        self._next_poll = now + self.poll_interval
+        data = _read(self.path)
+        if data is None or int(data.get("version") or 0) == self.version:
+            return {}
+        self.version = int(data.get("version") or 0)
+        changed: dict[str, Any] = {}
+        for name, value in (data.get("params") or {}).items():
+            if name in self.values and self.values[name] != value:
+                self.values[name] = value
+                changed[name] = value
+        return changed
@@ -0,0 +1,86 @@
+"""Run liveness recorded in the run's `og_meta.json`.
+
+The daemon supervising training writes a heartbeat while the job runs and
+its final state when it ends. The TUI trusts a fresh heartbeat over file
+modification times, so a run that logs a step every ten minutes still shows
+as running::
+
+    "status": {"state": "running", "heartbeat_unix": 1712000000, "pid": 4242}
+
+Alerts raised for the run are appended to `og_alerts.jsonl` beside it, one
+JSON object per line, for `og digest` to report.
+"""
+
+from __future__ import annotations
+
+import fcntl
+import itertools
+import json
+import os
+import time
+from pathlib import Path
+
+META_FILE_NAME = "og_meta.json"
+# Advisory lock every writer of og_meta.json holds around its
+# read-modify-write, the same file og and ogd lock.
+LOCK_FILE_NAME = "og_meta.json.lock"
+
+# Numbers this process's writes so their temp files never collide.
+_WRITES = itertools.count()
+ALERTS_FILE_NAME = "og_alerts.jsonl"
+
+# Runtime statuses of the supervisor mapped to run states. Statuses missing
+# here (idle, recovering after a failure, ...) leave the recorded state alone.
+RUNTIME_TO_RUN_STATE = {
+    "starting": "running",
+    "running": "running",
+    "completed": "finished",
+    "failed": "crashed",
+    "timeout": "crashed",
+    "stopped": "killed",
+}
+
+
+def write_run_status(
+    run_dir: Path,
+    state: str,
+    *,
+    pid: int | None = None,
+    exit_code: int | None = None,
+    reason: str | None = None,
+    now: float | None = None,
+) -> None:
+    """Set the `status` field of `<run_dir>/og_meta.json`, keeping the rest."""
+    path = run_dir / META_FILE_NAME
+    status: dict[str, object] = {
+        "state": state,
+        "heartbeat_unix": int(time.time() if now is None else now),
+        "pid": os.getpid() if pid is None else pid,
+    }
+    if exit_code is not None:
+        status["exit_code"] = exit_code
+    if reason:
+        status["reason"] = reason
+    run_dir.mkdir(parents=True, exist_ok=True)
+    with (run_dir / LOCK_FILE_NAME).open("a") as lock:
+        fcntl.flock(lock, fcntl.LOCK_EX)
+        try:
+            meta = json.loads(path.read_text(encoding="utf-8"))
+        except FileNotFoundError:
+            meta = {}
+        if not isinstance(meta, dict):
+            raise ValueError(f"{path} is not a JSON object")
+        meta["status"] = status
+        tmp = path.with_name(f"{META_FILE_NAME}.{os.getpid()}.{next(_WRITES)}.tmp")
+        try:
+            tmp.write_text(json.dumps(meta, indent=2), encoding="utf-8")
+            os.replace(tmp, path)
+        finally:
+            tmp.unlink(missing_ok=True)
+
+
+def append_alert(run_dir: Path, alert: dict[str, object]) -> None:
+    """Append one alert to `<run_dir>/og_alerts.jsonl`."""
+    run_dir.mkdir(parents=True, exist_ok=True)
+    with (run_dir / ALERTS_FILE_NAME).open("a", encoding="utf-8") as handle:
+        handle.write(json.dumps(alert) + "\n")
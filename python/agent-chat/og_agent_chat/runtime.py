@@ -4,13 +4,29 @@ import asyncio
 import contextlib
 import os
 import shlex
+import signal
 import sys
 from dataclasses import dataclass, field
 from pathlib import Path
 from typing import Awaitable, Callable, Literal, Protocol
 
+from .control import CONTROL_FILE_ENV, CONTROL_FILE_NAME
+
 RuntimeType = Literal["local", "modal"]
 
+# Seconds training gets to exit after SIGINT (e.g. to write a checkpoint)
+# before its process group is killed.
+STOP_TIMEOUT_ENV = "OG_STOP_TIMEOUT_SECS"
+DEFAULT_STOP_TIMEOUT_SECS = 30.0
+
+
+def stop_timeout_from_env() -> float:
+    raw = os.getenv(STOP_TIMEOUT_ENV, "").strip()
+    try:
+        return max(float(raw), 0.0) if raw else DEFAULT_STOP_TIMEOUT_SECS
+    except ValueError:
+        return DEFAULT_STOP_TIMEOUT_SECS
+
 
 @dataclass
 class RuntimeFailure:
@@ -31,9 +47,9 @@ class RuntimeAdapter(Protocol):
 
     async def start(self) -> RuntimeStartResult: ...
 
-    async def stop(self) -> None: ...
+    async def stop(self, reason: str | None = None) -> None: ...
 
-    async def close(self) -> None: ...
+    async def close(self, reason: str | None = None) -> None: ...
 
 
 def _looks_like_oom_text(text: str) -> bool:
@@ -67,6 +83,7 @@ class LocalRuntimeAdapter:
         on_failure: Callable[[RuntimeFailure], Awaitable[None]],
         on_complete: Callable[[str], Awaitable[None]],
         on_heartbeat: Callable[[], None],
+        stop_timeout_secs: float = DEFAULT_STOP_TIMEOUT_SECS,
     ) -> None:
         self.training_file = training_file
         self.codebase_root = codebase_root
@@ -78,6 +95,7 @@ class LocalRuntimeAdapter:
         self.on_failure = on_failure
         self.on_complete = on_complete
         self.on_heartbeat = on_heartbeat
+        self.stop_timeout_secs = stop_timeout_secs
         self._process: asyncio.subprocess.Process | None = None
         self._log_task: asyncio.Task[None] | None = None
         self._stop_requested = False
@@ -89,6 +107,7 @@ class LocalRuntimeAdapter:
         if self.run_dir is not None:
             self.run_dir.mkdir(parents=True, exist_ok=True)
             env.setdefault("TB_LOG_DIR", str(self.run_dir))
+            env.setdefault(CONTROL_FILE_ENV, str(self.run_dir / CONTROL_FILE_NAME))
         env.setdefault("ENABLE_TB", "1")
         env.update(self.runtime_env_overrides)
         return env
@@ -148,6 +167,9 @@ class LocalRuntimeAdapter:
             stdout=asyncio.subprocess.PIPE,
             stderr=asyncio.subprocess.STDOUT,
             env=self._build_env(),
+            # A process group of its own, so stop() reaches every worker
+            # (torchrun, dataloaders) and the terminal's Ctrl-C does not.
+            start_new_session=True,
         )
         self._process = process
         self.on_log(f"[system] training restarted (pid={process.pid})")
@@ -160,17 +182,27 @@ class LocalRuntimeAdapter:
         self._log_task = asyncio.create_task(self._stream_logs(process))
         return RuntimeStartResult(runtime_id=str(process.pid))
 
-    async def stop(self) -> None:
+    async def stop(self, reason: str | None = None) -> None:
         self._stop_requested = True
 
         process = self._process
         self._process = None
         if process is not None and process.returncode is None:
-            process.terminate()
+            why = f" ({reason})" if reason else ""
+            timeout = self.stop_timeout_secs
+            self.on_log(
+                f"[system] stopping training{why}: SIGINT to process group {process.pid}, "
+                f"SIGKILL after {timeout:g}s"
+            )
+            _signal_group(process, signal.SIGINT)
             try:
-                await asyncio.wait_for(process.wait(), timeout=5)
+                await asyncio.wait_for(process.wait(), timeout=timeout)
+                self.on_log(
+                    f"[system] training exited with code {process.returncode} after SIGINT"
+                )
             except TimeoutError:
-                process.kill()
+                self.on_log(f"[system] training still running {timeout:g}s after SIGINT; killing")
+                _signal_group(process, signal.SIGKILL)
                 await process.wait()
 
         current = asyncio.current_task()
@@ -183,8 +215,17 @@ class LocalRuntimeAdapter:
                     await self._log_task
         self._log_task = None
 
-    async def close(self) -> None:
-        await self.stop()
+    async def close(self, reason: str | None = None) -> None:
+        await self.stop(reason)
+
+
+def _signal_group(process: asyncio.subprocess.Process, sig: signal.Signals) -> None:
+    try:
+        os.killpg(process.pid, sig)
+    except ProcessLookupError:
+        pass
+    except PermissionError:
+        process.send_signal(sig)
 
 
 class ModalRuntimeScaffoldAdapter(LocalRuntimeAdapter):
@@ -213,6 +254,7 @@ def build_runtime_adapter(
     on_failure: Callable[[RuntimeFailure], Awaitable[None]],
     on_complete: Callable[[str], Awaitable[None]],
     on_heartbeat: Callable[[], None],
+    stop_timeout_secs: float = DEFAULT_STOP_TIMEOUT_SECS,
 ) -> RuntimeAdapter:
     if runtime == "modal":
         return ModalRuntimeScaffoldAdapter(
@@ -226,6 +268,7 @@ def build_runtime_adapter(
             on_failure=on_failure,
             on_complete=on_complete,
             on_heartbeat=on_heartbeat,
+            stop_timeout_secs=stop_timeout_secs,
         )
     return LocalRuntimeAdapter(
         training_file=training_file,
@@ -238,4 +281,5 @@ def build_runtime_adapter(
         on_failure=on_failure,
         on_complete=on_complete,
         on_heartbeat=on_heartbeat,
+        stop_timeout_secs=stop_timeout_secs,
     )
@@ -6,16 +6,33 @@ import contextlib
 import json
 import logging
 import os
+import signal
 import stat
+import threading
 import time
 from dataclasses import dataclass, field
 from pathlib import Path
 from typing import Any, Awaitable, Callable
 
 from .agent import AgentEngine
-from .alerts import AlertDetector, AlertRule, default_alert_rules, load_alert_rules_from_env
+from .alerts import (
+    AlertDetector,
+    AlertRule,
+    GradNormLimits,
+    default_alert_rules,
+    grad_norm_limits_from_env,
+    load_alert_rules_from_env,
+)
+from .checkpoints import RESUME_ENV
 from .models import ActionPlan, Alert, ChatMessage, RunState
-from .runtime import RuntimeFailure, RuntimeType, build_runtime_adapter
+from .run_status import RUNTIME_TO_RUN_STATE, append_alert, write_run_status
+from .runtime import (
+    DEFAULT_STOP_TIMEOUT_SECS,
+    RuntimeFailure,
+    RuntimeType,
+    build_runtime_adapter,
+    stop_timeout_from_env,
+)
 
 
 LOGGER = logging.getLogger(__name__)
@@ -37,6 +54,8 @@ class DaemonConfig:
     runtime_retry_backoff_max_secs: float = 20.0
     runtime_heartbeat_timeout_secs: float = 30.0
     runtime_heartbeat_check_secs: float = 2.0
+    run_status_interval_secs: float = 30.0
+    stop_timeout_secs: float = DEFAULT_STOP_TIMEOUT_SECS
     oom_policy_enabled: bool = True
     oom_min_batch_size: int = 1
     oom_default_batch_size: int = 32
@@ -65,6 +84,7 @@ class DaemonConfig:
         ]
     )
     alert_rules: list[AlertRule] = field(default_factory=list)
+    grad_norm: GradNormLimits | None = None
 
 
 def _serialize_message(message: ChatMessage) -> dict[str, Any]:
@@ -225,6 +245,28 @@ async def serve(config: DaemonConfig) -> None:
         run_state.append_log(line)
         _mark_runtime_heartbeat()
 
+    status_run_dir = _resolve_run_dir(config.run_dir)
+
+    def _record_run_status(runtime_status: str, reason: str | None = None) -> None:
+        # Heartbeat / final state in og_meta.json, read by the TUI's run list.
+        state = RUNTIME_TO_RUN_STATE.get(runtime_status)
+        if status_run_dir is None or state is None:
+            return
+        exit_code = None if state == "running" else run_state.runtime_last_exit_code
+        try:
+            write_run_status(status_run_dir, state, exit_code=exit_code, reason=reason)
+        except (OSError, ValueError) as exc:
+            LOGGER.warning("Failed to record run status: %s", exc)
+
+    def _record_alert(alert: Alert) -> None:
+        run_state.add_alert(alert)
+        if status_run_dir is None:
+            return
+        try:
+            append_alert(status_run_dir, _serialize_alert(alert))
+        except OSError as exc:
+            LOGGER.warning("Failed to record alert: %s", exc)
+
     def _set_runtime_state(
         *,
         status: str,
@@ -248,6 +290,7 @@ async def serve(config: DaemonConfig) -> None:
             run_state.runtime_last_exit_code = exit_code
         _mark_runtime_heartbeat()
         _record_runtime_metrics()
+        _record_run_status(status, reason)
         _set_rollout_state(
             observed=status,
             desired=run_state.rollout_desired_state,
@@ -424,16 +467,21 @@ async def serve(config: DaemonConfig) -> None:
             )
         return changes
 
-    async def _stop_training_process() -> None:
+    async def _stop_training_process(reason: str | None = None) -> None:
         nonlocal runtime_adapter
         if runtime_adapter is None:
             return
         try:
-            await runtime_adapter.close()
+            await runtime_adapter.close(reason)
         finally:
             runtime_adapter = None
             if run_state.runtime_status not in {"failed", "error"}:
-                _set_runtime_state(status="stopped")
+                _set_runtime_state(status="stopped", reason=reason)
+
+    async def _request_training_stop() -> None:
+        # Keep the supervisor from treating the exit as a failure to recover from.
+        run_state.rollout_desired_state = "stopped"
+        await _stop_training_process("stop requested")
 
     async def _handle_runtime_completion(status: str) -> None:
         run_state.rollout_desired_state = status
@@ -471,6 +519,7 @@ async def serve(config: DaemonConfig) -> None:
             on_failure=_handle_runtime_failure,
             on_complete=_handle_runtime_completion,
             on_heartbeat=_mark_runtime_heartbeat,
+            stop_timeout_secs=config.stop_timeout_secs,
         )
 
         run_state.runtime_id = None
@@ -544,7 +593,7 @@ async def serve(config: DaemonConfig) -> None:
                 message=message,
                 timestamp=time.time(),
             )
-            run_state.add_alert(alert)
+            _record_alert(alert)
             run_state.add_metric(
                 "runtime/failures",
                 float(run_state.runtime_restarts + 1),
@@ -572,7 +621,7 @@ async def serve(config: DaemonConfig) -> None:
         auto_mode=config.auto_mode,
         restart_callback=_restart_training_process,
     )
-    alert_detector = AlertDetector(config.alert_rules)
+    alert_detector = AlertDetector(config.alert_rules, grad_norm=config.grad_norm)
 
     async def handle_client(
         reader: asyncio.StreamReader,
@@ -593,15 +642,24 @@ async def serve(config: DaemonConfig) -> None:
                     continue
 
                 try:
-                    response = await _handle_payload(
-                        payload,
-                        run_state,
-                        agent,
-                        alert_detector,
-                        _restart_training_process,
-                        runtime_ref,
-                        runtime_env_overrides,
-                    )
+                    if payload.get("type") == "chat_message" and payload.get("stream"):
+                        response = await _handle_chat_stream(
+                            payload,
+                            agent,
+                            lambda event: _write_response(writer, event),
+                        )
+                    else:
+                        response = await _handle_payload(
+                            payload,
+                            run_state,
+                            agent,
+                            alert_detector,
+                            _restart_training_process,
+                            runtime_ref,
+                            runtime_env_overrides,
+                            _request_training_stop,
+                            _record_alert,
+                        )
                 except asyncio.CancelledError:
                     raise
                 except Exception:
@@ -667,7 +725,33 @@ async def serve(config: DaemonConfig) -> None:
                 )
             )
 
+    async def _run_status_loop() -> None:
+        interval = max(config.run_status_interval_secs, 1.0)
+        while True:
+            await asyncio.sleep(interval)
+            if runtime_adapter is not None and run_state.runtime_status == "running":
+                _record_run_status("running")
+
     runtime_watchdog_task = asyncio.create_task(_runtime_watchdog_loop())
+    run_status_task = asyncio.create_task(_run_status_loop())
+
+    # SIGTERM/SIGINT/SIGHUP (the TUI quitting, `og daemon stop`, a closed
+    # terminal) shut down through the finally below, which gives training its
+    # SIGINT and stop timeout instead of leaving it orphaned.
+    shutdown = asyncio.Event()
+    shutdown_reason: str | None = None
+
+    def _on_shutdown_signal(sig: signal.Signals) -> None:
+        nonlocal shutdown_reason
+        shutdown_reason = f"daemon received {sig.name}"
+        shutdown.set()
+
+    loop = asyncio.get_running_loop()
+    # Signal handlers can only be installed from the main thread (tests run
+    # the daemon on another one).
+    if threading.current_thread() is threading.main_thread():
+        for sig in (signal.SIGTERM, signal.SIGINT, signal.SIGHUP):
+            loop.add_signal_handler(sig, _on_shutdown_signal, sig)
     async with server:
         try:
             if config.start_training:
@@ -681,13 +765,16 @@ async def serve(config: DaemonConfig) -> None:
                     await _restart_training_process(run_state)
                 except Exception as exc:
                     run_state.append_log(f"[error] failed to start training: {exc}")
-            await server.serve_forever()
+            await shutdown.wait()
         finally:
             runtime_watchdog_task.cancel()
+            run_status_task.cancel()
             with contextlib.suppress(asyncio.CancelledError):
                 await runtime_watchdog_task
+            with contextlib.suppress(asyncio.CancelledError):
+                await run_status_task
             run_state.rollout_desired_state = "stopped"
-            await _stop_training_process()
+            await _stop_training_process(shutdown_reason)
 
 
 async def _write_response(writer: asyncio.StreamWriter, response: dict[str, Any]) -> None:
@@ -698,6 +785,33 @@ async def _write_response(writer: asyncio.StreamWriter, response: dict[str, Any]
         return
 
 
+async def _handle_chat_stream(
+    payload: dict[str, Any],
+    agent: AgentEngine,
+    emit: Callable[[dict[str, Any]], Awaitable[None]],
+) -> dict[str, Any]:
+    """Answer a chat_message sent with "stream": true.
+
+    Partial output goes out through `emit` as {"ok": true, "type": "chunk" |
+    "status", "text": ...} lines; the returned reply is the final line and
+    carries the same fields as the non-streaming response.
+    """
+    content = payload.get("content", "")
+    if not content:
+        return {"ok": False, "error": "missing_content"}
+
+    async def on_event(kind: str, text: str) -> None:
+        await emit({"ok": True, "type": kind, "text": text})
+
+    response = await agent.handle_chat_message(content, on_event=on_event)
+    return {
+        "ok": True,
+        "type": "done",
+        "response": _serialize_plan(response.plan),
+        "chat_history": [_serialize_message(msg) for msg in agent.get_chat_history()],
+    }
+
+
 async def _handle_payload(
     payload: dict[str, Any],
     run_state: RunState,
@@ -706,6 +820,8 @@ async def _handle_payload(
     restart_training_callback: Callable[[RunState], Awaitable[None]] | None = None,
     runtime_ref: dict[str, RuntimeType] | None = None,
     runtime_env_overrides: dict[str, str] | None = None,
+    stop_training_callback: Callable[[], Awaitable[None]] | None = None,
+    record_alert: Callable[[Alert], None] | None = None,
 ) -> dict[str, Any]:
     msg_type = payload.get("type")
 
@@ -780,7 +896,7 @@ async def _handle_payload(
         alert = alert_detector.check(run_state, metric=metric)
         response: dict[str, Any] = {"ok": True}
         if alert:
-            run_state.add_alert(alert)
+            (record_alert or run_state.add_alert)(alert)
             agent_response = await agent.handle_alert(alert)
             response["alert"] = _serialize_alert(alert)
             if agent_response:
@@ -822,12 +938,33 @@ async def _handle_payload(
     if msg_type == "start_training":
         if restart_training_callback is None:
             return {"ok": False, "error": "training_control_unavailable"}
+        resume_from = payload.get("resume_from")
+        if runtime_env_overrides is not None:
+            if resume_from:
+                runtime_env_overrides[RESUME_ENV] = str(resume_from)
+            else:
+                runtime_env_overrides.pop(RESUME_ENV, None)
+        if resume_from:
+            run_state.append_log(f"[system] resuming training from {resume_from}")
         try:
             await restart_training_callback(run_state)
         except Exception as exc:
             return {"ok": False, "error": f"failed_to_start_training: {exc}"}
         return {"ok": True}
 
+    if msg_type == "stop_training":
+        if stop_training_callback is None:
+            return {"ok": False, "error": "training_control_unavailable"}
+        reason = payload.get("reason")
+        run_state.append_log(
+            f"[system] stopping training: {reason}" if reason else "[system] stopping training"
+        )
+        try:
+            await stop_training_callback()
+        except Exception as exc:
+            return {"ok": False, "error": f"failed_to_stop_training: {exc}"}
+        return {"ok": True}
+
     if msg_type == "apply_refactor":
         diagnosis = payload.get("diagnosis", "")
         action = payload.get("action", "refactor")
@@ -927,6 +1064,12 @@ def main() -> None:
         default=float(os.getenv("OG_RUNTIME_HEARTBEAT_TIMEOUT_SECS", "30")),
         help="Runtime heartbeat stale timeout (seconds) before fail-fast recovery",
     )
+    parser.add_argument(
+        "--stop-timeout-secs",
+        type=float,
+        default=stop_timeout_from_env(),
+        help="Seconds training gets to exit after SIGINT before its process group is killed",
+    )
     parser.add_argument(
         "--runtime-heartbeat-check-secs",
         type=float,
@@ -1026,6 +1169,7 @@ def main() -> None:
         runtime_retry_backoff_max_secs=retry_backoff_max,
         runtime_heartbeat_timeout_secs=heartbeat_timeout,
         runtime_heartbeat_check_secs=heartbeat_check,
+        stop_timeout_secs=max(float(args.stop_timeout_secs), 0.0),
         oom_policy_enabled=oom_policy_enabled,
         oom_min_batch_size=max(int(args.oom_min_batch_size), 1),
         oom_default_batch_size=max(int(args.oom_default_batch_size), 1),
@@ -1035,6 +1179,7 @@ def main() -> None:
         oom_accum_env_keys=oom_accum_keys or ["GRAD_ACCUM_STEPS"],
         oom_seq_env_keys=oom_seq_keys,
         alert_rules=alert_rules,
+        grad_norm=grad_norm_limits_from_env(),
     )
     asyncio.run(serve(config))
 
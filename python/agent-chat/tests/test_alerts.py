@@ -5,9 +5,11 @@ import json
 import og_agent_chat.alerts as alerts_module
 from og_agent_chat.alerts import (
     AlertDetector,
+    GradNormLimits,
     StallRule,
     ThresholdRule,
     default_alert_rules,
+    grad_norm_limits_from_env,
     load_alert_rules_from_env,
 )
 from og_agent_chat.models import RunState
@@ -110,3 +112,43 @@ def test_default_alert_rules_only_apply_to_demo_training_file(
     assert rules[0].min_delta == 0.125
     assert rules[0].cooldown_secs == 9.0
     assert default_alert_rules("other_train.py") == []
+
+
+def test_grad_norm_watchdog_flags_explosion_and_vanishing(tmp_path) -> None:
+    state = RunState(training_file=tmp_path / "train.py", codebase_root=tmp_path)
+    state.add_metric("train/loss", 0.9, step=1)
+    state.add_metric("optim/grad_norm", 1.5, step=1)
+    detector = AlertDetector(
+        [], grad_norm=GradNormLimits(max_norm=10.0, min_norm=1e-6, vanish_points=3)
+    )
+
+    assert detector.check(state) is None
+    assert [rule.metric for rule in detector.rules] == ["optim/grad_norm"]
+
+    state.add_metric("optim/grad_norm", float("inf"), step=2)
+    alert = detector.check(state)
+    assert alert is not None
+    assert alert.metric == "optim/grad_norm"
+    assert "exploding" in alert.message
+
+    rule = detector.rules[0]
+    assert rule.evaluate([1.0, 1e-8, 1e-8]) is False
+    assert rule.evaluate([1e-8, 1e-8, 1e-8]) is True
+    assert "vanishing" in rule.to_alert(1e-8).message
+
+    # A user rule on the metric replaces the built-in one.
+    user_rule = ThresholdRule(metric="optim/grad_norm", threshold=5.0)
+    covered = AlertDetector([user_rule], grad_norm=GradNormLimits())
+    covered.check(state)
+    assert covered.rules == [user_rule]
+    assert AlertDetector([]).check(state) is None
+
+
+def test_grad_norm_limits_from_env(monkeypatch) -> None:
+    monkeypatch.setenv("OG_GRAD_EXPLODE", "50")
+    monkeypatch.setenv("OG_GRAD_VANISH_POINTS", "5")
+    limits = grad_norm_limits_from_env()
+    assert limits == GradNormLimits(max_norm=50.0, min_norm=1e-7, vanish_points=5)
+
+    monkeypatch.setenv("OG_GRAD_WATCH", "0")
+    assert grad_norm_limits_from_env() is None
@@ -0,0 +1,31 @@
+from __future__ import annotations
+
+import json
+
+from og_agent_chat.checkpoints import record_checkpoint, resume_checkpoint
+
+
+def test_record_checkpoint_appends_registry_lines(tmp_path, monkeypatch) -> None:
+    monkeypatch.setenv("TB_LOG_DIR", str(tmp_path))
+    record_checkpoint(tmp_path / "step_10.pt", step=10, metrics={"val/loss": 1.5})
+    record_checkpoint(tmp_path / "step_20.pt", step=20)
+
+    lines = (tmp_path / "checkpoints.jsonl").read_text(encoding="utf-8").splitlines()
+    entries = [json.loads(line) for line in lines]
+    assert [entry["step"] for entry in entries] == [10, 20]
+    assert entries[0]["path"] == str((tmp_path / "step_10.pt").resolve())
+    assert entries[0]["metrics"] == {"val/loss": 1.5}
+    assert "metrics" not in entries[1]
+
+
+def test_record_checkpoint_outside_og_is_a_no_op(tmp_path, monkeypatch) -> None:
+    monkeypatch.delenv("TB_LOG_DIR", raising=False)
+    record_checkpoint(tmp_path / "step_10.pt", step=10)
+    assert list(tmp_path.iterdir()) == []
+
+
+def test_resume_checkpoint_reads_the_env(monkeypatch) -> None:
+    monkeypatch.delenv("OG_RESUME_CHECKPOINT", raising=False)
+    assert resume_checkpoint() is None
+    monkeypatch.setenv("OG_RESUME_CHECKPOINT", "/ckpt/step_50.pt")
+    assert str(resume_checkpoint()) == "/ckpt/step_50.pt"
@@ -5,7 +5,7 @@ import socket
 import pytest
 
 import og_agent_chat.client as client_module
-from og_agent_chat.client import OGDClientError, _recv_line, send_metric, send_request
+from og_agent_chat.client import OGDClientError, _recv_line, query, send_metric, send_request
 from og_agent_chat.config import (
     _normalize_inference_provider,
     _resolve_provider_api_base,
@@ -76,3 +76,30 @@ def test_send_metric_builds_expected_payload(monkeypatch) -> None:
         },
         "socket_path": "/tmp/demo.sock",
     }
+
+
+def test_query_sends_argv_to_query_socket_from_env(monkeypatch) -> None:
+    captured: dict[str, object] = {}
+
+    def fake_send_request(payload: dict[str, object], socket_path: str) -> dict[str, object]:
+        captured["payload"] = payload
+        captured["socket_path"] = socket_path
+        if payload["argv"][0] == "fork":
+            return {"ok": False, "error": "command_not_allowed: fork"}
+        return {"ok": True, "command": "list.runs", "data": {"runs": []}}
+
+    monkeypatch.setattr(client_module, "send_request", fake_send_request)
+    monkeypatch.setenv("OG_QUERY_SOCKET", "/tmp/ogd.sock.query")
+
+    response = query(["list", "runs", "--project", "alpha"])
+
+    assert response["data"] == {"runs": []}
+    assert captured == {
+        "payload": {"type": "query", "argv": ["list", "runs", "--project", "alpha"]},
+        "socket_path": "/tmp/ogd.sock.query",
+    }
+    with pytest.raises(OGDClientError, match="command_not_allowed"):
+        query(["fork", "r1"])
+    monkeypatch.delenv("OG_QUERY_SOCKET")
+    with pytest.raises(OGDClientError, match="OG_QUERY_SOCKET is not set"):
+        query(["list", "runs"])
@@ -0,0 +1,28 @@
+from __future__ import annotations
+
+import json
+
+from og_agent_chat.control import LiveParams
+
+
+def test_live_params_declare_and_pick_up_og_set_changes(tmp_path) -> None:
+    path = tmp_path / "control.json"
+    params = LiveParams(path, lr=0.001, wd=0.0)
+
+    declared = json.loads(path.read_text(encoding="utf-8"))
+    assert declared["tunable"] == ["lr", "wd"]
+    assert declared["params"] == {"lr": 0.001, "wd": 0.0}
+    assert params.poll(force=True) == {}
+
+    # What `og set --run X lr=1e-4` writes.
+    declared["version"] = 1
+    declared["params"]["lr"] = 1e-4
+    declared["params"]["unknown"] = 5
+    path.write_text(json.dumps(declared), encoding="utf-8")
+
+    assert params.poll(force=True) == {"lr": 1e-4}
+    assert params["lr"] == 1e-4
+    assert params.poll(force=True) == {}
+
+    restarted = LiveParams(path, lr=0.001, wd=0.0)
+    assert restarted["lr"] == 1e-4
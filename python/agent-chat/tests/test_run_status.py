@@ -0,0 +1,55 @@
+from __future__ import annotations
+
+import json
+import threading
+
+from og_agent_chat.run_status import (
+    ALERTS_FILE_NAME,
+    RUNTIME_TO_RUN_STATE,
+    append_alert,
+    write_run_status,
+)
+
+
+def test_write_run_status_keeps_other_meta_fields(tmp_path) -> None:
+    (tmp_path / "og_meta.json").write_text(json.dumps({"tags": ["baseline"]}), encoding="utf-8")
+
+    write_run_status(tmp_path, "running", pid=42, now=1000.7)
+    meta = json.loads((tmp_path / "og_meta.json").read_text(encoding="utf-8"))
+    assert meta["tags"] == ["baseline"]
+    assert meta["status"] == {"state": "running", "heartbeat_unix": 1000, "pid": 42}
+
+    write_run_status(tmp_path, RUNTIME_TO_RUN_STATE["failed"], pid=42, exit_code=137, now=1060)
+    meta = json.loads((tmp_path / "og_meta.json").read_text(encoding="utf-8"))
+    assert meta["status"]["state"] == "crashed"
+    assert meta["status"]["exit_code"] == 137
+    assert not list(tmp_path.glob("*.tmp"))
+
+
+def test_write_run_status_keeps_concurrent_writers_fields(tmp_path) -> None:
+    writers = [
+        threading.Thread(target=write_run_status, args=(tmp_path, "running"), kwargs={"pid": pid})
+        for pid in range(8)
+    ]
+    for writer in writers:
+        writer.start()
+    for writer in writers:
+        writer.join()
+    meta = json.loads((tmp_path / "og_meta.json").read_text(encoding="utf-8"))
+    assert meta["status"]["pid"] in range(8)
+    assert not list(tmp_path.glob("*.tmp"))
+
+
+def test_write_run_status_creates_the_sidecar(tmp_path) -> None:
+    run_dir = tmp_path / "run"
+    write_run_status(run_dir, "finished", exit_code=0)
+    meta = json.loads((run_dir / "og_meta.json").read_text(encoding="utf-8"))
+    assert meta["status"]["state"] == "finished"
+    assert meta["status"]["exit_code"] == 0
+
+
+def test_append_alert_adds_one_line_per_alert(tmp_path) -> None:
+    append_alert(tmp_path, {"metric": "train/loss", "message": "spiked", "timestamp": 1.5})
+    append_alert(tmp_path, {"metric": "val/loss", "message": "stalled", "timestamp": 2.5})
+    lines = (tmp_path / ALERTS_FILE_NAME).read_text(encoding="utf-8").splitlines()
+    assert [json.loads(line)["message"] for line in lines] == ["spiked", "stalled"]
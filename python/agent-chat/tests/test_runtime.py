@@ -1,6 +1,7 @@
 from __future__ import annotations
 
 import asyncio
+import time
 from pathlib import Path
 
 from og_agent_chat.runtime import LocalRuntimeAdapter, RuntimeFailure, _looks_like_oom_text
@@ -18,6 +19,7 @@ def _build_adapter(
     on_failure=None,
     on_complete=None,
     on_heartbeat=None,
+    stop_timeout_secs: float = 30.0,
 ) -> LocalRuntimeAdapter:
     async def noop_failure(_: RuntimeFailure) -> None:
         return None
@@ -36,6 +38,7 @@ def _build_adapter(
         on_failure=on_failure or noop_failure,
         on_complete=on_complete or noop_complete,
         on_heartbeat=on_heartbeat or (lambda: None),
+        stop_timeout_secs=stop_timeout_secs,
     )
 
 
@@ -63,6 +66,7 @@ def test_local_runtime_adapter_builds_env_and_command(tmp_path) -> None:
 
     assert env["OGD_SOCKET"] == str(socket_path)
     assert env["TB_LOG_DIR"] == str(run_dir)
+    assert env["OG_CONTROL_FILE"] == str(run_dir / "control.json")
     assert env["ENABLE_TB"] == "1"
     assert env["BATCH_SIZE"] == "8"
     assert run_dir.is_dir()
@@ -146,3 +150,70 @@ def test_local_runtime_adapter_reports_oom_failures(tmp_path) -> None:
     assert len(failures) == 1
     assert failures[0].error_type == "LOCAL_OOM"
     assert failures[0].exit_code == 1
+
+
+def test_local_runtime_adapter_stop_sends_sigint_then_kills(tmp_path) -> None:
+    # Checkpoints on SIGINT and exits.
+    graceful = tmp_path / "train_graceful.py"
+    graceful.write_text(
+        "\n".join(
+            [
+                "import pathlib, signal, sys, time",
+                "def on_int(*_):",
+                "    pathlib.Path('checkpoint.pt').write_text('saved')",
+                "    sys.exit(0)",
+                "signal.signal(signal.SIGINT, on_int)",
+                "print('ready', flush=True)",
+                "time.sleep(60)",
+            ]
+        )
+        + "\n",
+        encoding="utf-8",
+    )
+    # Ignores SIGINT.
+    stubborn = tmp_path / "train_stubborn.py"
+    stubborn.write_text(
+        "\n".join(
+            [
+                "import signal, time",
+                "signal.signal(signal.SIGINT, signal.SIG_IGN)",
+                "print('ready', flush=True)",
+                "time.sleep(60)",
+            ]
+        )
+        + "\n",
+        encoding="utf-8",
+    )
+
+    async def run_and_stop(training_file: Path, logs: list[str]) -> float:
+        ready = asyncio.Event()
+
+        def on_log(line: str) -> None:
+            logs.append(line)
+            if line == "ready":
+                ready.set()
+
+        adapter = _build_adapter(
+            training_file=training_file,
+            codebase_root=tmp_path,
+            socket_path=tmp_path / "ogd.sock",
+            on_log=on_log,
+            stop_timeout_secs=1.0,
+        )
+        await adapter.start()
+        await asyncio.wait_for(ready.wait(), timeout=10)
+        started = time.monotonic()
+        await adapter.stop("test quit")
+        return time.monotonic() - started
+
+    graceful_logs: list[str] = []
+    elapsed = asyncio.run(run_and_stop(graceful, graceful_logs))
+    assert (tmp_path / "checkpoint.pt").read_text() == "saved"
+    assert elapsed < 1.0
+    assert any("stopping training (test quit)" in line for line in graceful_logs)
+    assert any("exited with code 0 after SIGINT" in line for line in graceful_logs)
+
+    stubborn_logs: list[str] = []
+    elapsed = asyncio.run(run_and_stop(stubborn, stubborn_logs))
+    assert elapsed >= 1.0
+    assert any("still running 1s after SIGINT; killing" in line for line in stubborn_logs)
@@ -9,6 +9,7 @@ from og_agent_chat.alerts import AlertDetector, ThresholdRule
 from og_agent_chat.models import ActionPlan, Alert, ChatMessage, ExecutionResult, RunState
 from og_agent_chat.server import (
     _clear_tfevents_files,
+    _handle_chat_stream,
     _handle_payload,
     _normalize_runtime,
     _prepare_socket_path,
@@ -46,6 +47,20 @@ class StubAgent:
         self.executed_plans.append(plan)
         return ExecutionResult(success=True, checkpoint_id="ckpt-1")
 
+    async def handle_chat_message(self, content: str, on_event=None) -> StubAgentResponse:
+        if on_event is not None:
+            await on_event("status", "Calling metric_tail")
+            await on_event("chunk", "Loss ")
+            await on_event("chunk", "diverged.")
+        return StubAgentResponse(
+            ActionPlan(
+                diagnosis=f"Answered: {content}",
+                action="explain",
+                code_changes="",
+                raw_output="Loss diverged.",
+            )
+        )
+
 
 def test_server_filesystem_helpers_clean_up_expected_paths(tmp_path) -> None:
     socket_path = tmp_path / "sockdir" / "ogd.sock"
@@ -77,6 +92,29 @@ def test_prepare_socket_path_rejects_existing_directories(tmp_path) -> None:
         _prepare_socket_path(socket_path)
 
 
+def test_handle_chat_stream_emits_chunks_then_final_reply() -> None:
+    agent = StubAgent()
+    emitted: list[dict] = []
+
+    async def emit(event: dict) -> None:
+        emitted.append(event)
+
+    final = asyncio.run(_handle_chat_stream({"content": "why?"}, agent, emit))
+
+    assert emitted == [
+        {"ok": True, "type": "status", "text": "Calling metric_tail"},
+        {"ok": True, "type": "chunk", "text": "Loss "},
+        {"ok": True, "type": "chunk", "text": "diverged."},
+    ]
+    assert final["type"] == "done"
+    assert final["response"]["diagnosis"] == "Answered: why?"
+    assert final["chat_history"][0]["content"] == "hello"
+    assert asyncio.run(_handle_chat_stream({}, agent, emit)) == {
+        "ok": False,
+        "error": "missing_content",
+    }
+
+
 def test_handle_payload_metrics_update_can_trigger_alert_callbacks(tmp_path) -> None:
     run_state = RunState(training_file=tmp_path / "train.py", codebase_root=tmp_path)
     agent = StubAgent()
@@ -193,6 +231,54 @@ def test_handle_payload_supports_runtime_controls_and_state_queries(tmp_path) ->
     assert start_response == {"ok": True}
     assert restarts == ["called"]
 
+    overrides: dict[str, str] = {}
+    resume_response = asyncio.run(
+        _handle_payload(
+            {"type": "start_training", "resume_from": "/ckpt/step_50.pt"},
+            run_state,
+            agent,
+            detector,
+            restart_training_callback=restart_training,
+            runtime_env_overrides=overrides,
+        )
+    )
+    assert resume_response == {"ok": True}
+    assert overrides == {"OG_RESUME_CHECKPOINT": "/ckpt/step_50.pt"}
+    assert run_state.logs[-1] == "[system] resuming training from /ckpt/step_50.pt"
+    asyncio.run(
+        _handle_payload(
+            {"type": "start_training"},
+            run_state,
+            agent,
+            detector,
+            restart_training_callback=restart_training,
+            runtime_env_overrides=overrides,
+        )
+    )
+    assert overrides == {}
+
+    stops: list[str] = []
+
+    async def stop_training() -> None:
+        stops.append("called")
+
+    stop_response = asyncio.run(
+        _handle_payload(
+            {"type": "stop_training", "reason": "val/loss plateaued"},
+            run_state,
+            agent,
+            detector,
+            stop_training_callback=stop_training,
+        )
+    )
+    assert stop_response == {"ok": True}
+    assert stops == ["called"]
+    assert run_state.logs[-1] == "[system] stopping training: val/loss plateaued"
+    unavailable = asyncio.run(
+        _handle_payload({"type": "stop_training"}, run_state, agent, detector)
+    )
+    assert unavailable == {"ok": False, "error": "training_control_unavailable"}
+
     apply_response = asyncio.run(
         _handle_payload(
             {